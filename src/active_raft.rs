@@ -6,10 +6,70 @@ use crate::raft::{
 use std::collections::{HashMap, VecDeque};
 use std::future::Future;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// Shareable view of the raft membership and leadership of a node.
+#[derive(Debug, Clone)]
+pub struct RaftInfo {
+    /// false if RAFT is bypassed.
+    use_raft: bool,
+    /// The raft peer id.
+    peer_id: u64,
+    /// Number of peers in the raft, including this one.
+    peers_len: usize,
+    /// Last leader id seen by the running RaftNode.
+    leader_id: Arc<AtomicU64>,
+}
+
+impl Default for RaftInfo {
+    /// Single peer bypassing RAFT
+    fn default() -> Self {
+        Self {
+            use_raft: false,
+            peer_id: 1,
+            peers_len: 1,
+            leader_id: Arc::new(AtomicU64::new(raft::INVALID_ID)),
+        }
+    }
+}
+
+impl RaftInfo {
+    /// Returns the number of peers in the raft, including this one
+    pub fn peers_len(&self) -> usize {
+        self.peers_len
+    }
+
+    /// Returns whether this node is the first peer of the raft.
+    /// Without raft, this peer is the only one.
+    pub fn first_raft_peer(&self) -> bool {
+        self.peer_id == 1 || !self.use_raft
+    }
+
+    /// Returns the peer ID of the last known leader, if any.
+    /// Without raft, this peer is its own leader.
+    pub fn leader_id(&self) -> Option<u64> {
+        self.raft_info().leader_id()
+    }
+
+    /// Returns a shareable view of the raft membership and leadership
+    pub fn raft_info(&self) -> RaftInfo {
+        RaftInfo {
+            use_raft: self.use_raft,
+            peer_id: self.peer_id,
+            peers_len: self.peers_len(),
+            leader_id: self.leader_id.clone(),
+        }
+    }
+
+    /// Returns whether this node is the last known leader
+    pub fn is_leader(&self) -> bool {
+        self.leader_id() == Some(self.peer_id)
+    }
+}
+
 /// Provide RAFT loop and in/out channels to interact with it.
 ///
 pub struct ActiveRaft {
@@ -32,6 +92,12 @@ pub struct ActiveRaft {
     raft_peers_to_connect: Vec<SocketAddr>,
     /// Collection of the peer expected to be connected.
     raft_peer_addrs: Vec<SocketAddr>,
+    /// Last leader id seen by the running RaftNode.
+    leader_id: Arc<AtomicU64>,
+    /// Index of the last commit when RAFT is bypassed.
+    bypass_commit_idx: u64,
+    /// Snapshot loaded on start up when RAFT is bypassed, not applied yet.
+    bypass_snapshot: Option<RaftCommit>,
 }
 
 impl ActiveRaft {
@@ -81,16 +147,23 @@ impl ActiveRaft {
             .map(|(_, addr)| *addr)
             .collect();
 
+        let raft_node = RaftNode::new(raft_config);
+        let bypass_snapshot = (!use_raft).then(|| raft_node.loaded_snapshot()).flatten();
+        let bypass_commit_idx = bypass_snapshot.as_ref().map_or(0, |s| s.index);
+
         Self {
             use_raft,
             peer_id,
-            raft_node: Arc::new(Mutex::new(RaftNode::new(raft_config))),
+            raft_node: Arc::new(Mutex::new(raft_node)),
             cmd_tx: raft_channels.cmd_tx,
             msg_out_rx: Arc::new(Mutex::new(raft_channels.msg_out_rx)),
             committed_rx: Arc::new(Mutex::new((raft_channels.committed_rx, VecDeque::new()))),
             peer_addr,
             raft_peers_to_connect,
             raft_peer_addrs,
+            leader_id: raft_channels.leader_id,
+            bypass_commit_idx,
+            bypass_snapshot,
         }
     }
 
@@ -104,6 +177,18 @@ impl ActiveRaft {
         self.peer_id
     }
 
+    /// Returns the peer ID of the last known leader, if any.
+    /// Without raft, this peer is its own leader.
+    pub fn leader_id(&self) -> Option<u64> {
+        if !self.use_raft {
+            return Some(self.peer_id);
+        }
+        match self.leader_id.load(Ordering::Relaxed) {
+            raft::INVALID_ID => None,
+            leader_id => Some(leader_id),
+        }
+    }
+
     /// Returns a map to the addresses of this raft's peers
     pub fn peers_len(&self) -> usize {
         self.peer_addr.len()
@@ -151,6 +236,27 @@ impl ActiveRaft {
         self.raft_node.lock().await.backup_persistent_store()
     }
 
+    /// Take the snapshot loaded on start up when RAFT is bypassed.
+    /// With RAFT, the loaded snapshot is committed by the running loop instead.
+    pub fn take_bypass_snapshot(&mut self) -> Option<RaftCommit> {
+        self.bypass_snapshot.take()
+    }
+
+    /// Persist a snapshot when RAFT is bypassed, so it can be loaded on restart.
+    ///
+    /// ## Arguments
+    /// * `idx` - The index of the last commit applied to the data
+    /// * `data` - The data to snapshot
+    /// * `backup` - Whether or not to backup the DB
+    pub async fn persist_bypass_snapshot(&mut self, idx: u64, data: RaftData, backup: bool) {
+        if !self.use_raft {
+            self.raft_node
+                .lock()
+                .await
+                .persist_snapshot(idx, data, backup);
+        }
+    }
+
     /// Blocks & waits for a next commit from a peer.
     pub async fn next_commit(&self) -> Option<RaftCommit> {
         let mut committed_rx = self.committed_rx.lock().await;
@@ -184,7 +290,9 @@ impl ActiveRaft {
                 .send(RaftCmd::Propose { data, context })
                 .unwrap();
         } else {
+            self.bypass_commit_idx += 1;
             self.committed_rx.lock().await.1.push_back(RaftCommit {
+                index: self.bypass_commit_idx,
                 data: RaftCommitData::Proposed(data, context),
                 ..RaftCommit::default()
             });
@@ -12,6 +12,7 @@ pub enum ApiErrorType {
     Generic(String),
     InvalidPassphrase,
     BlankPassphrase,
+    WalletLocked,
     InvalidRequestBody,
     CannotParseAddress,
     CannotAccessWallet,
@@ -21,12 +22,18 @@ pub enum ApiErrorType {
     CannotAccessPeerUserNode,
     CannotSaveAddressesToWallet,
     CannotFetchBalance,
+    InsufficientFunds,
     NoDataFoundForKey,
     InternalError,
     Unauthorized,
     MethodNotFound,
     MethodNotAllowed,
     BadRequest,
+    InvalidPublicKey,
+    DatabaseUnavailable,
+    Overloaded,
+    RateLimited,
+    TooManyItems(usize),
 }
 
 impl ApiError {
@@ -48,12 +55,85 @@ impl std::fmt::Display for ApiError {
 
 impl warp::reject::Reject for ApiError {}
 
+impl ApiErrorType {
+    /// Status code replied with the error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiErrorType::InvalidPassphrase
+            | ApiErrorType::BlankPassphrase
+            | ApiErrorType::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiErrorType::WalletLocked => StatusCode::FORBIDDEN,
+            ApiErrorType::InvalidRequestBody
+            | ApiErrorType::CannotParseAddress
+            | ApiErrorType::InsufficientFunds
+            | ApiErrorType::BadRequest
+            | ApiErrorType::InvalidPublicKey
+            | ApiErrorType::TooManyItems(_) => StatusCode::BAD_REQUEST,
+            ApiErrorType::NoDataFoundForKey | ApiErrorType::MethodNotFound => StatusCode::NOT_FOUND,
+            ApiErrorType::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ApiErrorType::Overloaded => StatusCode::SERVICE_UNAVAILABLE,
+            ApiErrorType::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ApiErrorType::Generic(_)
+            | ApiErrorType::CannotAccessWallet
+            | ApiErrorType::CannotAccessUserNode
+            | ApiErrorType::CannotAccessMinerNode
+            | ApiErrorType::CannotAccessMempoolNode
+            | ApiErrorType::CannotAccessPeerUserNode
+            | ApiErrorType::CannotSaveAddressesToWallet
+            | ApiErrorType::CannotFetchBalance
+            | ApiErrorType::InternalError
+            | ApiErrorType::DatabaseUnavailable => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine readable code of the error, generic errors taking it from their status
+    ///
+    /// ### Arguments
+    ///
+    /// * `status` - Status code replied with the error
+    pub fn code(&self, status: StatusCode) -> &'static str {
+        match self {
+            ApiErrorType::Generic(_) | ApiErrorType::BadRequest => match status {
+                StatusCode::BAD_REQUEST => "bad_request",
+                StatusCode::UNAUTHORIZED => "unauthorized",
+                StatusCode::FORBIDDEN => "forbidden",
+                StatusCode::NOT_FOUND => "not_found",
+                _ => "internal",
+            },
+            ApiErrorType::InvalidPassphrase => "invalid_passphrase",
+            ApiErrorType::BlankPassphrase => "blank_passphrase",
+            ApiErrorType::WalletLocked => "wallet_locked",
+            ApiErrorType::InvalidRequestBody => "invalid_request_body",
+            ApiErrorType::CannotParseAddress => "invalid_address",
+            ApiErrorType::CannotAccessWallet
+            | ApiErrorType::CannotAccessUserNode
+            | ApiErrorType::CannotAccessMinerNode
+            | ApiErrorType::CannotAccessMempoolNode
+            | ApiErrorType::CannotAccessPeerUserNode
+            | ApiErrorType::CannotSaveAddressesToWallet
+            | ApiErrorType::CannotFetchBalance
+            | ApiErrorType::InternalError => "internal",
+            ApiErrorType::InsufficientFunds => "insufficient_funds",
+            ApiErrorType::NoDataFoundForKey => "not_found",
+            ApiErrorType::Unauthorized => "unauthorized",
+            ApiErrorType::MethodNotFound => "method_not_found",
+            ApiErrorType::MethodNotAllowed => "method_not_allowed",
+            ApiErrorType::InvalidPublicKey => "invalid_public_key",
+            ApiErrorType::DatabaseUnavailable => "db_unavailable",
+            ApiErrorType::Overloaded => "overloaded",
+            ApiErrorType::RateLimited => "rate_limited",
+            ApiErrorType::TooManyItems(_) => "too_many_items",
+        }
+    }
+}
+
 impl std::fmt::Display for ApiErrorType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match &self {
             ApiErrorType::Generic(message) => write!(f, "Generic error: {message}"),
             ApiErrorType::InvalidPassphrase => write!(f, "Invalid passphrase"),
             ApiErrorType::BlankPassphrase => write!(f, "New passphrase cannot be blank"),
+            ApiErrorType::WalletLocked => write!(f, "Wallet is locked"),
             ApiErrorType::InvalidRequestBody => write!(f, "Invalid request body"),
             ApiErrorType::CannotParseAddress => write!(f, "Cannot parse address"),
             ApiErrorType::CannotAccessWallet => write!(f, "Cannot access wallet"),
@@ -65,12 +145,20 @@ impl std::fmt::Display for ApiErrorType {
                 write!(f, "Cannot save address to wallet")
             }
             ApiErrorType::CannotFetchBalance => write!(f, "Cannot fetch balance"),
+            ApiErrorType::InsufficientFunds => write!(f, "Insufficient funds for payment"),
             ApiErrorType::NoDataFoundForKey => write!(f, "No data found for key"),
             ApiErrorType::InternalError => write!(f, "Internal Error"),
             ApiErrorType::Unauthorized => write!(f, "Unauthorized"),
             ApiErrorType::MethodNotFound => write!(f, "Method not found"),
             ApiErrorType::MethodNotAllowed => write!(f, "Method not allowed"),
             ApiErrorType::BadRequest => write!(f, "Bad request"),
+            ApiErrorType::InvalidPublicKey => write!(f, "Invalid public key"),
+            ApiErrorType::DatabaseUnavailable => write!(f, "Database unavailable"),
+            ApiErrorType::Overloaded => write!(f, "Too many requests in progress"),
+            ApiErrorType::RateLimited => write!(f, "Too many requests, try again later"),
+            ApiErrorType::TooManyItems(limit) => {
+                write!(f, "Too many items in the request, at most {limit} allowed")
+            }
         }
     }
 }
@@ -79,3 +167,11 @@ impl std::fmt::Display for ApiErrorType {
 pub struct OverloadedError;
 
 impl warp::reject::Reject for OverloadedError {}
+
+/// Rejection of a rate limited request, with the seconds until the client can retry
+#[derive(Debug)]
+pub struct RateLimitedError {
+    pub retry_after_secs: u64,
+}
+
+impl warp::reject::Reject for RateLimitedError {}
@@ -1,41 +1,67 @@
+use crate::active_raft::RaftInfo;
 use crate::api::errors::ApiErrorType;
+use crate::api::metrics::{MetricsSource, PrometheusMetrics, PROMETHEUS_CONTENT_TYPE};
 use crate::api::responses::{
     json_embed, json_embed_block, json_embed_transaction, json_serialize_embed, APIAsset,
     APICreateResponseContent, CallResponse, JsonReply,
 };
 use crate::api::utils::{map_string_err, map_to_string_err};
+use crate::chain_quality::ChainQualityHandle;
 use crate::comms_handler::Node;
 use crate::configurations::MempoolNodeSharedConfig;
-use crate::constants::LAST_BLOCK_HASH_KEY;
+use crate::constants::{
+    ADDRESS_HISTORY_PAGE_LIMIT, API_BATCH_ITEMS_LIMIT, BLOCK_HEADER_RANGE_LIMIT,
+    BLOCK_RANGE_SPAN_LIMIT, D_DISPLAY_PLACES_U64, LAST_BLOCK_HASH_KEY, TOKEN_DISPLAY_DECIMALS,
+    UTXO_INDEX_PAGE_LIMIT, WALLET_UNLOCK_MAX_TIMEOUT_SECS, WALLET_UNLOCK_TIMEOUT_SECS,
+};
 use crate::db_utils::SimpleDb;
 use crate::interfaces::{
     node_type_as_str, AddressesWithOutPoints, BlockchainItem, BlockchainItemMeta,
-    BlockchainItemType, DebugData, DruidPool, MempoolApi, MineApiRequest, MineRequest, NodeType,
-    OutPointData, StoredSerializingBlock, UserApi, UserApiRequest, UserRequest, UtxoFetchType,
+    BlockchainItemType, DebugData, DruidPool, MempoolApi, MineApiRequest, MineRequest, NodeInfo,
+    NodeRaftInfo, NodeType, OutPointData, StoredSerializingBlock, UserApi, UserApiRequest,
+    UserRequest, UtxoFetchType,
 };
 use crate::mempool::MempoolError;
 use crate::miner::{BlockPoWReceived, CurrentBlockWithMutex};
-use crate::storage::{get_stored_value_from_db, indexed_block_hash_key};
+use crate::miner_stats::MinerStatsHandle;
+use crate::pool_mining::{PoolMiningError, PoolMiningHandle, PoolMiningMessage};
+use crate::storage::{
+    get_address_history_page, get_block_header_summaries_from_db, get_druid_tx_hashes_from_db,
+    get_stored_supply_from_db, get_stored_value_from_db, get_utxo_index_page,
+    indexed_block_hash_key, BlockStreamSender, StoredSupply, StoredSupplyHandle,
+};
 use crate::threaded_call::{self, ThreadedCallSender};
-use crate::utils::{decode_pub_key, decode_signature, StringError};
+use crate::user::PaymentEventSender;
+use crate::utils::{decode_pub_key, decode_signature, is_valid_payment_address, StringError};
+use crate::utxo_subscription::{
+    UtxoSubscriptionError, UtxoSubscriptionMessage, UtxoSubscriptionsHandle,
+};
+use crate::wallet::activity::{ActivityRange, ExportFormat};
 use crate::wallet::{AddressStore, AddressStoreHex, WalletDb, WalletDbError};
 use crate::Response;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
 use serde::de::{Error, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{fmt, str};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error};
 use tw_chain::constants::{D_DISPLAY_PLACES, TOTAL_TOKENS};
 use tw_chain::crypto::sign_ed25519::{PublicKey, Signature};
-use tw_chain::primitives::asset::{Asset, ItemAsset, TokenAmount};
+use tw_chain::primitives::asset::{Asset, AssetValues, ItemAsset, TokenAmount};
 use tw_chain::primitives::druid::DdeValues;
 use tw_chain::primitives::transaction::{GenesisTxHashSpec, OutPoint, Transaction, TxIn, TxOut};
 use tw_chain::script::lang::Script;
 use tw_chain::script::{OpCodes, StackEntry};
 use tw_chain::utils::transaction_utils::{construct_address_for, construct_tx_hash};
 use warp::hyper::StatusCode;
+use warp::ws::{Message, WebSocket};
 
 pub type DbgPaths = Vec<&'static str>;
 
@@ -64,6 +90,38 @@ struct WalletInfo {
     available_total_tokens: u64,
     item_total: BTreeMap<String, u64>, /* DRS tx hash - amount */
     addresses: AddressesWithOutPoints,
+    labels: BTreeMap<String, String>, /* address - label */
+}
+
+/// Balance of a set of wallet addresses to be returned to requester
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WalletBalance {
+    total: AssetValues,
+    address_balances: BTreeMap<String, AssetValues>,
+}
+
+/// Token supply in raw fractionated units and as a human-readable decimal string
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenSupply {
+    pub tokens: u64,
+    pub display: String,
+}
+
+impl TokenSupply {
+    pub fn new(tokens: u64) -> Self {
+        let scale = 10u128.pow(TOKEN_DISPLAY_DECIMALS);
+        let whole = tokens / D_DISPLAY_PLACES_U64;
+        let fraction =
+            (tokens % D_DISPLAY_PLACES_U64) as u128 * scale / D_DISPLAY_PLACES_U64 as u128;
+        let display = format!(
+            "{}.{:0width$}",
+            whole,
+            fraction,
+            width = TOKEN_DISPLAY_DECIMALS as usize
+        );
+
+        Self { tokens, display }
+    }
 }
 
 /// Encapsulated payment received from client
@@ -75,6 +133,16 @@ pub struct EncapsulatedPayment {
     pub locktime: Option<u64>,
 }
 
+/// Encapsulated item asset payment received from client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncapsulatedItemPayment {
+    pub address: String,
+    pub item_amount: u64,
+    pub genesis_hash: String,
+    pub passphrase: String,
+    pub locktime: Option<u64>,
+}
+
 /// Item asset creation structure received from client
 ///
 /// This structure is used to create a item asset on EITHER
@@ -191,7 +259,190 @@ pub struct ChangePassphraseData {
     pub new_passphrase: String,
 }
 
+/// Struct received from client to unlock the wallet
+///
+/// Entries will be encrypted with TLS
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UnlockWalletData {
+    pub passphrase: String,
+    /// Seconds the wallet stays unlocked for (WALLET_UNLOCK_TIMEOUT_SECS if unset)
+    pub timeout_secs: Option<u64>,
+}
+
+impl fmt::Debug for UnlockWalletData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnlockWalletData")
+            .field("passphrase", &"<redacted>")
+            .field("timeout_secs", &self.timeout_secs)
+            .finish()
+    }
+}
+
+/// Struct received from client to export the full wallet
+///
+/// Entries will be encrypted with TLS
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportWalletData {
+    /// Current wallet passphrase
+    pub passphrase: String,
+    /// Passphrase the export is encrypted with
+    pub export_passphrase: String,
+}
+
+impl fmt::Debug for ExportWalletData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportWalletData")
+            .field("passphrase", &"<redacted>")
+            .field("export_passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Struct received from client to import a full wallet export
+///
+/// Entries will be encrypted with TLS
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportWalletData {
+    /// Current wallet passphrase
+    pub passphrase: String,
+    /// Passphrase the export was encrypted with
+    pub export_passphrase: String,
+    /// Hex encoded export returned by `export_wallet`
+    pub export: String,
+}
+
+impl fmt::Debug for ImportWalletData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportWalletData")
+            .field("passphrase", &"<redacted>")
+            .field("export_passphrase", &"<redacted>")
+            .field("export", &self.export)
+            .finish()
+    }
+}
+
+/// Struct received from client to export the wallet key-pairs
+///
+/// Entries will be encrypted with TLS
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportKeypairsData {
+    pub passphrase: String,
+}
+
+impl fmt::Debug for ExportKeypairsData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportKeypairsData")
+            .field("passphrase", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Struct received from client to import key-pairs into the wallet
+///
+/// Entries will be encrypted with TLS
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImportKeypairsData {
+    pub passphrase: String,
+    pub addresses: BTreeMap<String, AddressStoreHex>,
+}
+
+impl fmt::Debug for ImportKeypairsData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportKeypairsData")
+            .field("passphrase", &"<redacted>")
+            .field("addresses", &self.addresses.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Struct received from client to delete a key-pair from the wallet
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DeleteKeypairData {
+    pub passphrase: String,
+    pub address: String,
+}
+
+impl fmt::Debug for DeleteKeypairData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeleteKeypairData")
+            .field("passphrase", &"<redacted>")
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+/// Struct received from client to cancel a pending payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelPendingPaymentData {
+    pub tx_hash: String,
+}
+
+/// Struct received from client to export wallet activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportActivityData {
+    pub range: ActivityRange,
+    pub format: ExportFormat,
+}
+
 /// Struct received from client to construct address
+/// Page request for the stored UTXO set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoIndexPageData {
+    pub start: Option<String>,
+    pub limit: usize,
+}
+
+/// Page request for the history of the transactions touching an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressHistoryPageData {
+    pub address: String,
+    pub page: u64,
+    pub per_page: usize,
+}
+
+/// Miner API keys and addresses to add to or remove from the whitelist
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MinerWhitelistData {
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+    #[serde(default)]
+    pub addresses: Vec<SocketAddr>,
+}
+
+/// Wallet addresses to fetch the balance of, either as a list or as an object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FetchBalanceData {
+    Addresses(Vec<String>),
+    Object { addresses: Vec<String> },
+}
+
+/// Keys to retrieve from the blockchain db, either all at once or a page of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlockchainEntryKeys {
+    Keys(Vec<String>),
+    Page {
+        keys: Vec<String>,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    },
+}
+
+/// Inclusive range of block numbers to retrieve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockRangeData {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Page of the stored UTXO set, with the cursor of the next page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoIndexPage {
+    pub out_points: Vec<OutPoint>,
+    pub next: Option<String>,
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct AddressConstructData {
     pub pub_key: Option<Vec<u8>>,
@@ -267,6 +518,7 @@ pub async fn get_wallet_info(
         available_total_tokens: available.tokens.0,
         item_total: total.items,
         addresses,
+        labels: wallet_db.get_labels(),
     };
 
     r.into_ok(
@@ -276,12 +528,20 @@ pub async fn get_wallet_info(
 }
 
 /// Gets all present keys and sends them out for export
-pub async fn get_export_keypairs(
+pub async fn post_export_keypairs(
     wallet_db: WalletDb,
+    keypairs_data: ExportKeypairsData,
     route: &'static str,
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    if let Err(e) = wallet_db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = wallet_db.test_passphrase(keypairs_data.passphrase).await {
+        return wallet_db_error(e, r);
+    }
+
     let known_addr = wallet_db.get_known_addresses();
     let mut addresses = BTreeMap::new();
 
@@ -297,6 +557,34 @@ pub async fn get_export_keypairs(
     )
 }
 
+/// Post to delete a key-pair and its tracked transactions from the wallet
+pub async fn post_delete_keypair(
+    wallet_db: WalletDb,
+    keypair_data: DeleteKeypairData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    if let Err(e) = wallet_db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = wallet_db.test_passphrase(keypair_data.passphrase).await {
+        return wallet_db_error(e, r);
+    }
+
+    match wallet_db
+        .delete_address_from_wallet(keypair_data.address)
+        .await
+    {
+        Ok(0) => r.into_ok("Key-pair not found", json_serialize_embed(0)),
+        Ok(removed) => r.into_ok(
+            "Key-pair successfully deleted",
+            json_serialize_embed(removed),
+        ),
+        Err(e) => wallet_db_error(e, r),
+    }
+}
+
 /// Gets a newly generated payment address
 pub async fn get_payment_address(
     mut wallet_db: WalletDb,
@@ -320,6 +608,74 @@ pub async fn get_latest_block(
     get_json_reply_stored_value_from_db(db, LAST_BLOCK_HASH_KEY, false, call_id, route)
 }
 
+/// Gets the storage node's view of the chain quality
+pub async fn get_chain_quality(
+    chain_quality: ChainQualityHandle,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let report = chain_quality
+        .lock()
+        .unwrap()
+        .report(std::time::Instant::now());
+
+    r.into_ok(
+        "Chain quality successfully retrieved",
+        json_serialize_embed(report),
+    )
+}
+
+/// Gets the tokens created by the stored blockchain, computing them if not cached
+///
+/// ### Arguments
+///
+/// * `db`            - Database of the storage node
+/// * `stored_supply` - Supply cache cleared by the storage node on each stored block
+fn get_cached_stored_supply(
+    db: Arc<Mutex<SimpleDb>>,
+    stored_supply: StoredSupplyHandle,
+) -> StoredSupply {
+    // Lock the database first, as the storage node does when storing a block
+    let db = db.lock().unwrap();
+    let mut stored_supply = stored_supply.lock().unwrap();
+    *stored_supply.get_or_insert_with(|| get_stored_supply_from_db(&db))
+}
+
+/// GET total supply created by the stored blockchain
+pub async fn get_stored_total_supply(
+    db: Arc<Mutex<SimpleDb>>,
+    stored_supply: StoredSupplyHandle,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    let supply = get_cached_stored_supply(db, stored_supply);
+
+    r.into_ok(
+        "Successfully fetched total supply",
+        json_serialize_embed(TokenSupply::new(supply.total().0)),
+    )
+}
+
+/// GET supply issued by mining up to the latest stored block
+pub async fn get_stored_issued_supply(
+    db: Arc<Mutex<SimpleDb>>,
+    stored_supply: StoredSupplyHandle,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    let supply = get_cached_stored_supply(db, stored_supply);
+
+    r.into_ok(
+        "Successfully fetched issued supply",
+        json_serialize_embed(TokenSupply::new(supply.issued.0)),
+    )
+}
+
 /// Gets the debug info for a specified node type
 ///
 /// Contains an optional field for an auxiliary `Node`,
@@ -362,6 +718,32 @@ pub async fn get_debug_data(
     )
 }
 
+/// Gets the version, type and raft role of this node
+pub async fn get_node_info(
+    node: Node,
+    raft_info: Option<RaftInfo>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+
+    let raft = raft_info.map(|raft_info| NodeRaftInfo {
+        peers_len: raft_info.peers_len(),
+        first_raft_peer: raft_info.first_raft_peer(),
+        leader_id: raft_info.leader_id(),
+        is_leader: raft_info.is_leader(),
+    });
+    let data = NodeInfo {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        node_type: node_type_as_str(node.get_node_type()).to_owned(),
+        raft,
+    };
+    r.into_ok(
+        "Node info successfully retrieved",
+        json_serialize_embed(data),
+    )
+}
+
 /// Get to fetch information about the current mining block
 pub async fn get_current_mining_block(
     current_block: CurrentBlockWithMutex,
@@ -376,6 +758,180 @@ pub async fn get_current_mining_block(
     )
 }
 
+/// Gets the mining statistics of a miner node
+pub async fn get_miner_stats(
+    miner_stats: MinerStatsHandle,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let report = miner_stats
+        .lock()
+        .unwrap()
+        .report(std::time::Instant::now());
+
+    r.into_ok(
+        "Miner statistics successfully retrieved",
+        json_serialize_embed(report),
+    )
+}
+
+/// Gets the node metrics in the Prometheus text exposition format
+pub async fn get_metrics(
+    node: Node,
+    source: MetricsSource,
+    route: &'static str,
+    call_id: String,
+) -> Result<warp::reply::Response, JsonReply> {
+    use warp::http::header::{HeaderValue, CONTENT_TYPE};
+    let r = CallResponse::new(route, &call_id);
+
+    let mut metrics = PrometheusMetrics::default();
+    metrics.gauge(
+        "connected_peers",
+        "Number of peers connected to the node",
+        node.get_peers().await.len(),
+    );
+
+    match source {
+        MetricsSource::Mempool {
+            mut threaded_calls,
+            raft_info,
+        } => {
+            let mempool = make_api_threaded_call(
+                &mut threaded_calls,
+                move |c| c.get_mempool_metrics(),
+                "Cannot access Mempool Node",
+            )
+            .await
+            .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+            metrics
+                .counter(
+                    "mined_blocks",
+                    "Blocks mined since the node started",
+                    mempool.mined_block_count,
+                )
+                .counter(
+                    "mined_transactions",
+                    "Transactions included in the blocks mined since the node started",
+                    mempool.mined_tx_count,
+                )
+                .counter(
+                    "double_spend_transactions",
+                    "Submitted transactions rejected as double spends",
+                    mempool.double_spend_count,
+                )
+                .gauge(
+                    "local_tx_pool_size",
+                    "Transactions waiting to be proposed to the raft",
+                    mempool.status.local_tx_pool_len,
+                )
+                .gauge(
+                    "proposed_tx_pool_size",
+                    "Transactions proposed to the raft and not yet committed",
+                    mempool.status.proposed_tx_pool_len,
+                )
+                .gauge(
+                    "consensused_tx_pool_size",
+                    "Transactions committed by the raft waiting for a block",
+                    mempool.status.consensused_tx_pool_len,
+                )
+                .gauge(
+                    "druid_pool_size",
+                    "DRUID transaction groups pending",
+                    mempool.status.druid_pool_len,
+                )
+                .gauge(
+                    "raft_peers",
+                    "Number of peers in the raft, including this one",
+                    raft_info.peers_len(),
+                );
+            if let Some(b_num) = mempool.current_block_num {
+                metrics.gauge("current_block_num", "Number of the current block", b_num);
+            }
+        }
+        MetricsSource::Storage {
+            db,
+            chain_quality,
+            stored_supply,
+            raft_info,
+        } => {
+            let quality = chain_quality
+                .lock()
+                .unwrap()
+                .report(std::time::Instant::now());
+            let supply = get_cached_stored_supply(db, stored_supply);
+
+            metrics
+                .counter(
+                    "orphan_blocks",
+                    "Stored blocks superseded by a different block",
+                    quality.orphan_count,
+                )
+                .gauge(
+                    "missing_blocks",
+                    "Blocks missing below the highest stored block",
+                    quality.missing_block_count,
+                )
+                .gauge(
+                    "raft_peers",
+                    "Number of peers in the raft, including this one",
+                    raft_info.peers_len(),
+                )
+                .gauge(
+                    "stored_total_supply",
+                    "Tokens created by the stored blockchain",
+                    supply.total().0,
+                );
+            if let Some(b_num) = quality.highest_stored_block {
+                metrics.gauge("highest_stored_block", "Highest block number stored", b_num);
+            }
+        }
+        MetricsSource::Miner { miner_stats } => {
+            let report = miner_stats
+                .lock()
+                .unwrap()
+                .report(std::time::Instant::now());
+
+            metrics
+                .counter(
+                    "hashes",
+                    "Hashes computed by the miner",
+                    report.counters.total_hashes,
+                )
+                .counter(
+                    "blocks_won",
+                    "Blocks won by the miner",
+                    report.counters.blocks_won,
+                )
+                .counter(
+                    "rejected_submissions",
+                    "PoW submissions rejected as no longer current",
+                    report.counters.rejected_submissions,
+                )
+                .gauge(
+                    "hash_rate",
+                    "Hashes per second over the instantaneous window",
+                    report.hash_rate,
+                )
+                .gauge(
+                    "average_hash_rate",
+                    "Hashes per second over the average window",
+                    report.average_hash_rate,
+                );
+        }
+        MetricsSource::User => (),
+    }
+
+    let mut res = warp::reply::Response::new(metrics.into_text().into());
+    res.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(PROMETHEUS_CONTENT_TYPE),
+    );
+    Ok(res)
+}
+
 /// Get all addresses for unspent tokens on the UTXO set
 pub async fn get_utxo_addresses(
     mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
@@ -438,7 +994,109 @@ pub async fn get_issued_supply(
 
     r.into_ok(
         "Successfully fetched issued supply",
-        json_serialize_embed(res),
+        json_serialize_embed(TokenSupply::new(res.0)),
+    )
+}
+
+/// GET the number of transactions pending consensus on the mempool node
+pub async fn get_mempool_status(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let status = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.get_mempool_status(),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok(
+        "Successfully fetched mempool status",
+        json_serialize_embed(status),
+    )
+}
+
+/// GET the fee suggested by the mempool node for a transaction to be included in the next blocks
+pub async fn get_estimate_fee(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let estimate = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.estimate_fee(),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok("Successfully estimated fee", json_serialize_embed(estimate))
+}
+
+/// GET the difficulty of the block offered to miners by the mempool node
+pub async fn get_current_difficulty(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let difficulty = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.get_current_difficulty(),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok(
+        "Successfully fetched current difficulty",
+        json_serialize_embed(difficulty),
+    )
+}
+
+/// GET a snapshot of the raft consensus state of the mempool node
+pub async fn get_debug_raft_status(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let status = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.get_raft_status(),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok(
+        "Successfully fetched raft status",
+        json_serialize_embed(status),
+    )
+}
+
+/// GET the miner whitelist of the mempool node
+pub async fn get_miner_whitelist(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let whitelist = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.get_miner_whitelist(),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok(
+        "Successfully fetched miner whitelist",
+        json_serialize_embed(whitelist),
     )
 }
 
@@ -451,7 +1109,7 @@ pub async fn get_total_supply(
 
     r.into_ok(
         "Successfully fetched total supply",
-        json_serialize_embed(TOTAL_TOKENS),
+        json_serialize_embed(TokenSupply::new(TOTAL_TOKENS)),
     )
 }
 
@@ -472,16 +1130,211 @@ pub async fn get_outgoing_txs(
     }
 }
 
+/// GET payments sent to mempool and their confirmation state
+pub async fn get_pending_payments(
+    db: WalletDb,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    r.into_ok(
+        "Pending payments successfully retrieved",
+        json_serialize_embed(db.get_pending_payments()),
+    )
+}
+
 //======= POST HANDLERS =======//
 
+/// Post to cancel a payment not confirmed within the safety horizon
+pub async fn post_cancel_pending_payment(
+    db: WalletDb,
+    info: CancelPendingPaymentData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    match db.cancel_pending_payment(&info.tx_hash) {
+        Ok(payment) => r.into_ok(
+            "Pending payment successfully cancelled",
+            json_serialize_embed(payment),
+        ),
+        Err(e) => wallet_db_error(e, r),
+    }
+}
+
+/// Gets the payments sent and received by the wallet, oldest first,
+/// keeping only the most recent ones when a limit is given
+pub async fn get_transaction_history(
+    db: WalletDb,
+    limit: Option<usize>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let history = db.get_activity_log().transaction_history(limit);
+    r.into_ok(
+        "Transaction history successfully retrieved",
+        json_serialize_embed(history),
+    )
+}
+
+/// Post to export the wallet activity over a range, streamed as CSV or JSON
+pub async fn post_export_activity(
+    db: WalletDb,
+    info: ExportActivityData,
+    route: &'static str,
+    call_id: String,
+) -> Result<warp::reply::Response, JsonReply> {
+    use warp::http::header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_TYPE};
+    let r = CallResponse::new(route, &call_id);
+
+    let (ActivityRange::BlockNum { start, end } | ActivityRange::Timestamp { start, end }) =
+        info.range;
+    if start > end {
+        let e = "Range start is after range end";
+        return Err(map_string_err(r, e, StatusCode::BAD_REQUEST));
+    }
+
+    let report = db.get_activity_log().report(info.range);
+    let chunks = report
+        .into_chunks(info.format)
+        .map(Ok::<_, std::convert::Infallible>);
+    let body = warp::hyper::Body::wrap_stream(futures::stream::iter(chunks));
+
+    let file_name = match info.format {
+        ExportFormat::Csv => "attachment; filename=\"wallet_activity.csv\"",
+        ExportFormat::Json => "attachment; filename=\"wallet_activity.json\"",
+    };
+    let mut res = warp::reply::Response::new(body);
+    res.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(info.format.content_type()),
+    );
+    res.headers_mut()
+        .insert(CONTENT_DISPOSITION, HeaderValue::from_static(file_name));
+    Ok(res)
+}
+
 /// Post to retrieve items from the blockchain db by hash key
+///
+/// When a `limit` is given, only that page of the keys is retrieved and the
+/// items are returned alongside the `total` number of keys requested.
 pub async fn post_blockchain_entry_by_key(
     db: Arc<Mutex<SimpleDb>>,
-    keys: Vec<String>,
+    info: BlockchainEntryKeys,
     route: &'static str,
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
-    get_json_reply_items_from_db(db, keys, route, call_id)
+    let (keys, offset, limit) = match info {
+        BlockchainEntryKeys::Keys(keys)
+        | BlockchainEntryKeys::Page {
+            keys, limit: None, ..
+        } => return get_json_reply_items_from_db(db, keys, route, call_id),
+        BlockchainEntryKeys::Page {
+            keys,
+            offset,
+            limit: Some(limit),
+        } => (keys, offset.unwrap_or_default(), limit),
+    };
+
+    let r = CallResponse::new(route, &call_id);
+    check_batch_len(keys.len(), &r)?;
+    check_db_available(&db, &r)?;
+    let total = keys.len().to_string();
+    let page = keys.into_iter().skip(offset).take(limit).collect();
+    let items = get_json_items_from_db(db, page);
+
+    r.into_ok(
+        "Database item(s) successfully retrieved",
+        json_embed(&[
+            &b"{\"total\":"[..],
+            total.as_bytes(),
+            b",\"items\":",
+            &items,
+            b"}",
+        ]),
+    )
+}
+
+/// Post to retrieve the stored transactions of each DRUID, grouped by DRUID
+pub async fn post_blockchain_entry_by_druid(
+    db: Arc<Mutex<SimpleDb>>,
+    druids: Vec<String>,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_batch_len(druids.len(), &r)?;
+    check_db_available(&db, &r)?;
+    let groups: Vec<Vec<u8>> = druids
+        .into_iter()
+        .map(|druid| {
+            let tx_hashes = get_druid_tx_hashes_from_db(db.clone(), &druid);
+            let items = get_json_items_from_db(db.clone(), tx_hashes);
+            let druid = serde_json::to_vec(&druid).unwrap_or_default();
+            [druid, b":".to_vec(), items].concat()
+        })
+        .collect();
+
+    r.into_ok(
+        "Database item(s) successfully retrieved",
+        json_embed(&[&b"{"[..], &groups.join(&b","[..]), b"}"]),
+    )
+}
+
+/// Post to retrieve all the stored transactions sharing a DRUID,
+/// an unknown DRUID retrieving no transaction
+pub async fn post_transactions_by_druid(
+    db: Arc<Mutex<SimpleDb>>,
+    druid: String,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    let tx_hashes = get_druid_tx_hashes_from_db(db.clone(), &druid);
+
+    r.into_ok(
+        "Database item(s) successfully retrieved",
+        json_embed(&[&get_json_items_from_db(db, tx_hashes)]),
+    )
+}
+
+/// Post to retrieve a page of the stored UTXO set out points
+pub async fn post_utxo_index_page(
+    db: Arc<Mutex<SimpleDb>>,
+    info: UtxoIndexPageData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    let start = info.start.unwrap_or_default();
+    let limit = info.limit.min(UTXO_INDEX_PAGE_LIMIT);
+    let (out_points, next) = get_utxo_index_page(&db.lock().unwrap(), &start, limit);
+
+    r.into_ok(
+        "UTXO set page successfully retrieved",
+        json_serialize_embed(UtxoIndexPage { out_points, next }),
+    )
+}
+
+/// Post to retrieve a page of the stored transactions touching an address, newest first
+pub async fn post_transactions_by_address(
+    db: Arc<Mutex<SimpleDb>>,
+    info: AddressHistoryPageData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    let per_page = info.per_page.min(ADDRESS_HISTORY_PAGE_LIMIT);
+    let history = get_address_history_page(&db.lock().unwrap(), &info.address, info.page, per_page);
+
+    r.into_ok(
+        "Address history successfully retrieved",
+        json_serialize_embed(history),
+    )
 }
 
 /// Post to batch retrieve multiple transactions from the blockchain db by hash keys
@@ -494,6 +1347,84 @@ pub async fn post_transactions_by_key(
     get_json_reply_items_from_db(db, keys, route, call_id)
 }
 
+/// Post to retrieve the stored blocks in a range of numbers, listing the missing ones
+pub async fn post_blocks_by_num_range(
+    db: Arc<Mutex<SimpleDb>>,
+    info: BlockRangeData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    if info.start > info.end {
+        let e = "Range start is after range end";
+        return Err(map_string_err(r, e, StatusCode::BAD_REQUEST));
+    }
+
+    let end = info
+        .end
+        .min(info.start.saturating_add(BLOCK_RANGE_SPAN_LIMIT - 1));
+    let mut blocks = Vec::new();
+    let mut missing = Vec::new();
+    for b_num in info.start..=end {
+        match get_stored_value_from_db(db.clone(), indexed_block_hash_key(b_num)) {
+            Some(item) => blocks.push(
+                [
+                    &b"[\""[..],
+                    &item.key,
+                    &b"\","[..],
+                    &item.data_json,
+                    &b","[..],
+                    &construct_json_meta(item.item_meta),
+                    &b"]"[..],
+                ]
+                .concat(),
+            ),
+            None => missing.push(b_num),
+        }
+    }
+
+    // Make JSON object with the ordered blocks and missing numbers
+    let blocks = blocks.join(&b","[..]);
+    let missing = serde_json::to_vec(&missing).unwrap();
+    r.into_ok(
+        "Database item(s) successfully retrieved",
+        json_embed(&[
+            &b"{\"blocks\":["[..],
+            &blocks,
+            &b"],\"missing\":"[..],
+            &missing,
+            &b"}"[..],
+        ]),
+    )
+}
+
+/// Post to retrieve the header summaries of the blocks in a range of numbers,
+/// null for the numbers without stored block
+pub async fn post_block_range(
+    db: Arc<Mutex<SimpleDb>>,
+    info: BlockRangeData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
+    if info.start > info.end {
+        let e = "Range start is after range end";
+        return Err(map_string_err(r, e, StatusCode::BAD_REQUEST));
+    }
+
+    let end = info
+        .end
+        .min(info.start.saturating_add(BLOCK_HEADER_RANGE_LIMIT - 1));
+    let headers = get_block_header_summaries_from_db(&db.lock().unwrap(), info.start, end);
+
+    r.into_ok(
+        "Block headers successfully retrieved",
+        json_serialize_embed(headers),
+    )
+}
+
 /// Post to retrieve block information by number
 pub async fn post_block_by_num(
     db: Arc<Mutex<SimpleDb>>,
@@ -512,13 +1443,19 @@ pub async fn post_block_by_num(
 pub async fn post_import_keypairs(
     peer: Node,
     db: WalletDb,
-    keypairs: Addresses,
+    keypairs: ImportKeypairsData,
     route: &'static str,
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let response_keys: Vec<String> = keypairs.addresses.keys().cloned().collect();
     let response_data = json_serialize_embed(response_keys);
     let r = CallResponse::new(route, &call_id);
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = db.test_passphrase(keypairs.passphrase).await {
+        return wallet_db_error(e, r);
+    }
     let addresses: Vec<String> = keypairs.addresses.keys().cloned().collect();
 
     let mut key_pairs_converted = BTreeMap::new();
@@ -578,42 +1515,104 @@ pub async fn post_import_keypairs(
         _ => return r.into_err_internal(ApiErrorType::InternalError),
     }
 
-    r.into_ok("Key-pairs successfully imported", response_data)
+    r.into_ok("Key-pairs successfully imported", response_data)
+}
+
+///Post make a new payment from the connected wallet
+pub async fn post_make_payment(
+    db: WalletDb,
+    peer: Node,
+    mut threaded_calls: ThreadedCallSender<dyn UserApi>,
+    encapsulated_data: EncapsulatedPayment,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let EncapsulatedPayment {
+        address,
+        amount,
+        passphrase,
+        locktime,
+    } = encapsulated_data;
+
+    let r = CallResponse::new(route, &call_id);
+
+    if !is_valid_payment_address(&address) {
+        return r.into_err_bad_req(ApiErrorType::CannotParseAddress);
+    }
+
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = db.test_passphrase(passphrase).await {
+        return wallet_db_error(e, r);
+    };
+
+    let response = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.make_payment(address, amount, locktime),
+        "Cannot fetch UTXO balance",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    if !response.success {
+        return r.into_err_bad_req(ApiErrorType::InsufficientFunds);
+    }
+
+    let request = UserRequest::UserApi(UserApiRequest::SendNextPayment);
+    if let Err(e) = peer.inject_next_event(peer.local_address(), request) {
+        error!("route:make_payment error: {:?}", e);
+        return r.into_err_internal(ApiErrorType::CannotAccessUserNode);
+    }
+
+    r.into_progress("Payment processing", json_serialize_embed(response))
 }
 
-///Post make a new payment from the connected wallet
-pub async fn post_make_payment(
+/// Post make a new item asset payment from the connected wallet
+pub async fn post_make_item_payment(
     db: WalletDb,
     peer: Node,
     mut threaded_calls: ThreadedCallSender<dyn UserApi>,
-    encapsulated_data: EncapsulatedPayment,
+    encapsulated_data: EncapsulatedItemPayment,
     route: &'static str,
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
-    let EncapsulatedPayment {
+    let EncapsulatedItemPayment {
         address,
-        amount,
+        item_amount,
+        genesis_hash,
         passphrase,
         locktime,
     } = encapsulated_data;
 
     let r = CallResponse::new(route, &call_id);
 
+    if !is_valid_payment_address(&address) {
+        return r.into_err_bad_req(ApiErrorType::CannotParseAddress);
+    }
+
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
     if let Err(e) = db.test_passphrase(passphrase).await {
         return wallet_db_error(e, r);
     };
 
     let response = make_api_threaded_call(
         &mut threaded_calls,
-        move |c| c.make_payment(address, amount, locktime),
+        move |c| c.make_item_payment(address, item_amount, genesis_hash, locktime),
         "Cannot fetch UTXO balance",
     )
     .await
     .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
 
+    if !response.success {
+        return r.into_err_bad_req(ApiErrorType::InsufficientFunds);
+    }
+
     let request = UserRequest::UserApi(UserApiRequest::SendNextPayment);
     if let Err(e) = peer.inject_next_event(peer.local_address(), request) {
-        error!("route:make_payment error: {:?}", e);
+        error!("route:make_item_payment error: {:?}", e);
         return r.into_err_internal(ApiErrorType::CannotAccessUserNode);
     }
 
@@ -637,6 +1636,10 @@ pub async fn post_make_ip_payment(
 
     let r = CallResponse::new(route, &call_id);
 
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+
     let payment_peer: SocketAddr = match address.parse::<SocketAddr>() {
         Ok(addr) => addr,
         Err(_) => {
@@ -702,7 +1705,7 @@ pub async fn post_update_running_total(
     let r = CallResponse::new(route, &call_id);
 
     if addresses.is_empty() {
-        return r.into_err_internal(ApiErrorType::Generic("No addresses provided".to_owned()));
+        return r.into_err_bad_req(ApiErrorType::Generic("No addresses provided".to_owned()));
     }
 
     let known_addresses = wallet_db.get_known_addresses();
@@ -732,6 +1735,7 @@ pub async fn post_fetch_utxo_balance(
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_batch_len(addresses.len(), &r)?;
 
     let balances = make_api_threaded_call(
         &mut threaded_calls,
@@ -750,6 +1754,27 @@ pub async fn post_fetch_utxo_balance(
     )
 }
 
+/// Post to fetch the balance of addresses tracked by the wallet
+pub async fn post_fetch_wallet_balance(
+    db: WalletDb,
+    addresses: FetchBalanceData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let (FetchBalanceData::Addresses(addresses) | FetchBalanceData::Object { addresses }) =
+        addresses;
+
+    let (total, address_balances) = db.get_balance_for_addresses(&addresses);
+    r.into_ok(
+        "Balance successfully fetched",
+        json_serialize_embed(WalletBalance {
+            total,
+            address_balances,
+        }),
+    )
+}
+
 //POST fetch pending transaction from a mempool node
 pub async fn post_fetch_druid_pending(
     mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
@@ -758,6 +1783,7 @@ pub async fn post_fetch_druid_pending(
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_batch_len(fetch_input.druid_list.len(), &r)?;
 
     let pending_transactions = make_api_threaded_call(
         &mut threaded_calls,
@@ -782,6 +1808,7 @@ pub async fn post_fetch_druid_pending(
 /// Post to create a item asset transaction on User node
 pub async fn post_create_item_asset_user(
     peer: Node,
+    db: WalletDb,
     item_data: CreateItemAssetDataUser,
     route: &'static str,
     call_id: String,
@@ -799,6 +1826,10 @@ pub async fn post_create_item_asset_user(
     });
     let r = CallResponse::new(route, &call_id);
 
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+
     if let Err(e) = peer.inject_next_event(peer.local_address(), request) {
         error!("route:create_item_asset error: {:?}", e);
         return r.into_err_internal(ApiErrorType::CannotAccessUserNode);
@@ -904,7 +1935,39 @@ pub async fn post_create_transactions(
     r.into_ok("Transaction(s) processing", json_serialize_embed(ctx_map))
 }
 
-/// Get the status of a transaction on the mempool node
+/// Submit a hex or base64 encoded serialized transaction to mempool node
+pub async fn post_submit_transaction(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    data: String,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+
+    let transaction = from_encoded_transaction(&data)
+        .map_err(|e| map_string_err(r.clone(), e, StatusCode::BAD_REQUEST))?;
+    let tx_hash = construct_tx_hash(&transaction);
+
+    let mempool_resp = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| c.submit_transaction(transaction),
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    if !mempool_resp.success {
+        debug!(
+            "route:post_submit_transaction error: {:?}",
+            mempool_resp.reason
+        );
+        return r.into_err_internal(ApiErrorType::Generic(mempool_resp.reason.to_owned()));
+    }
+
+    r.into_ok("Transaction submitted", json_serialize_embed(tx_hash))
+}
+
+/// Get the stage of transactions between the mempool and storage, with their mempool status
 pub async fn post_transaction_status(
     mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     data: Vec<String>,
@@ -912,10 +1975,11 @@ pub async fn post_transaction_status(
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_batch_len(data.len(), &r)?;
 
     let status = make_api_threaded_call(
         &mut threaded_calls,
-        move |c| c.get_transaction_status(data),
+        move |c| c.get_transaction_lifecycle(data),
         "Cannot access Mempool Node",
     )
     .await
@@ -1004,6 +2068,105 @@ pub async fn post_change_wallet_passphrase(
     }
 }
 
+// POST to unlock the wallet for key-revealing and payment-creating calls
+pub async fn post_unlock_wallet(
+    db: WalletDb,
+    info: UnlockWalletData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let UnlockWalletData {
+        passphrase,
+        timeout_secs,
+    } = info;
+
+    let r = CallResponse::new(route, &call_id);
+
+    if passphrase.is_empty() {
+        return r.into_err(StatusCode::UNAUTHORIZED, ApiErrorType::BlankPassphrase);
+    }
+    let timeout_secs = timeout_secs.unwrap_or(WALLET_UNLOCK_TIMEOUT_SECS);
+    if timeout_secs > WALLET_UNLOCK_MAX_TIMEOUT_SECS {
+        return r.into_err(
+            StatusCode::BAD_REQUEST,
+            ApiErrorType::Generic(format!(
+                "Unlock timeout exceeds {WALLET_UNLOCK_MAX_TIMEOUT_SECS} seconds"
+            )),
+        );
+    }
+    match db
+        .unlock(passphrase, Duration::from_secs(timeout_secs))
+        .await
+    {
+        Ok(_) => r.into_ok("Wallet unlocked", json_serialize_embed(timeout_secs)),
+        Err(e) => wallet_db_error(e, r),
+    }
+}
+
+// POST to lock the wallet
+pub async fn post_lock_wallet(
+    db: WalletDb,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    db.lock();
+    r.into_ok("Wallet locked", json_serialize_embed("null"))
+}
+
+// POST to export the full wallet encrypted with a passphrase
+pub async fn post_export_wallet(
+    db: WalletDb,
+    info: ExportWalletData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = db.test_passphrase(info.passphrase).await {
+        return wallet_db_error(e, r);
+    }
+    if info.export_passphrase.is_empty() {
+        return r.into_err(StatusCode::UNAUTHORIZED, ApiErrorType::BlankPassphrase);
+    }
+    match db.export_wallet(info.export_passphrase).await {
+        Ok(export) => r.into_ok(
+            "Wallet exported successfully",
+            json_serialize_embed(hex::encode(export)),
+        ),
+        Err(e) => wallet_db_error(e, r),
+    }
+}
+
+// POST to import a full wallet export
+pub async fn post_import_wallet(
+    db: WalletDb,
+    info: ImportWalletData,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+
+    if let Err(e) = db.check_unlocked() {
+        return wallet_db_error(e, r);
+    }
+    if let Err(e) = db.test_passphrase(info.passphrase).await {
+        return wallet_db_error(e, r);
+    }
+    let export = hex::decode(info.export)
+        .map_err(|e| map_string_err(r.clone(), e, StatusCode::BAD_REQUEST))?;
+    match db.import_wallet(export, info.export_passphrase).await {
+        Ok(summary) => r.into_ok(
+            "Wallet imported successfully",
+            json_serialize_embed(summary),
+        ),
+        Err(e) => wallet_db_error(e, r),
+    }
+}
+
 // POST to check for transaction presence
 pub async fn post_blocks_by_tx_hashes(
     db: Arc<Mutex<SimpleDb>>,
@@ -1012,6 +2175,8 @@ pub async fn post_blocks_by_tx_hashes(
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_batch_len(tx_hashes.len(), &r)?;
+    check_db_available(&db, &r)?;
     let block_nums: Vec<u64> = tx_hashes
         .into_iter()
         .filter_map(
@@ -1041,37 +2206,43 @@ pub async fn post_payment_address_construction(
     let version = data.version;
     let r = CallResponse::new(route, &call_id);
 
-    let pub_key = pub_key.or_else(|| pub_key_hex.and_then(|k| hex::decode(k).ok()));
-    let pub_key = pub_key.filter(|k| !k.is_empty());
-    let pub_key = pub_key.and_then(|k| PublicKey::from_slice(&k));
+    let pub_key = match pub_key_hex.filter(|_| pub_key.is_none()).map(hex::decode) {
+        Some(Ok(pub_key)) => Some(pub_key),
+        Some(Err(_)) => return r.into_err_type(ApiErrorType::InvalidPublicKey),
+        None => pub_key,
+    };
 
-    if let Some(pub_key) = pub_key {
-        let data: String = construct_address_for(&pub_key, version);
-        return r.into_ok(
+    match pub_key
+        .filter(|k| !k.is_empty())
+        .map(|k| PublicKey::from_slice(&k))
+    {
+        Some(Some(pub_key)) => {
+            let data: String = construct_address_for(&pub_key, version);
+            r.into_ok(
+                "Address successfully constructed",
+                json_serialize_embed(data),
+            )
+        }
+        Some(None) => r.into_err_type(ApiErrorType::InvalidPublicKey),
+        None => r.into_ok(
             "Address successfully constructed",
-            json_serialize_embed(data),
-        );
+            json_serialize_embed("null"),
+        ),
     }
-
-    r.into_ok(
-        "Address successfully constructed",
-        json_serialize_embed("null"),
-    )
 }
 
-//POST pause nodes in a coordinated manner
+//POST pause block generation on all mempool nodes
 pub async fn pause_nodes(
     mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     route: &'static str,
     call_id: String,
-    b_num: Option<u64>, // NOTE: Nodes will pause at b_num + b_num
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
     // Send request to mempool node
     let res = make_api_threaded_call(
         &mut threaded_calls,
-        // NOTE: Nodes will pause at current_block + b_num; default is 1 block from current block
-        move |c| c.pause_nodes(b_num.unwrap_or(1)),
+        // NOTE: The block being mined completes, no further block is generated until resumed
+        |c| c.pause_network(),
         "Cannot access Mempool Node",
     )
     .await
@@ -1085,7 +2256,7 @@ pub async fn pause_nodes(
     r.into_ok(&res.reason, json_serialize_embed("null"))
 }
 
-//POST resume nodes in a coordinated manner
+//POST resume block generation on all mempool nodes
 pub async fn resume_nodes(
     mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     route: &'static str,
@@ -1095,7 +2266,7 @@ pub async fn resume_nodes(
     // Send request to mempool node
     let res = make_api_threaded_call(
         &mut threaded_calls,
-        |c| c.resume_nodes(),
+        |c| c.resume_network(),
         "Cannot access Mempool Node",
     )
     .await
@@ -1134,8 +2305,238 @@ pub async fn update_shared_config(
     r.into_ok(&res.reason, json_serialize_embed("null"))
 }
 
+//POST add or remove miner whitelist entries on a mempool node
+pub async fn post_update_miner_whitelist(
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    info: MinerWhitelistData,
+    add: bool,
+    route: &'static str,
+    call_id: String,
+) -> Result<JsonReply, JsonReply> {
+    let r = CallResponse::new(route, &call_id);
+    let MinerWhitelistData {
+        api_keys,
+        addresses,
+    } = info;
+
+    let res = make_api_threaded_call(
+        &mut threaded_calls,
+        move |c| match add {
+            true => c.add_miner_whitelist_entries(api_keys, addresses),
+            false => c.remove_miner_whitelist_entries(api_keys, addresses),
+        },
+        "Cannot access Mempool Node",
+    )
+    .await
+    .map_err(|e| map_string_err(r.clone(), e, StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    r.into_ok(&res.reason, json_serialize_embed("null"))
+}
+
+//======= WEBSOCKET HANDLERS =======//
+
+// WS stream UTXO events for the subscribed addresses of the connection
+pub async fn utxo_subscription_socket(socket: WebSocket, subscriptions: UtxoSubscriptionsHandle) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let (id, mut events_rx) = subscriptions.lock().unwrap().subscribe();
+
+    loop {
+        let replies = tokio::select! {
+            msg = ws_rx.next() => match msg {
+                Some(Ok(msg)) if msg.is_close() => break,
+                Some(Ok(msg)) => match msg.to_str().map(serde_json::from_str) {
+                    Ok(Ok(request)) => subscriptions.lock().unwrap().handle_request(id, request),
+                    Ok(Err(e)) => vec![invalid_utxo_subscription_request(e.to_string())],
+                    Err(()) => {
+                        let message = "Expected text message".to_owned();
+                        vec![invalid_utxo_subscription_request(message)]
+                    }
+                },
+                Some(Err(e)) => {
+                    debug!("route:utxo_subscription error: {:?}", e);
+                    break;
+                }
+                None => break,
+            },
+            event = events_rx.recv() => match event {
+                Some(event) => vec![event],
+                None => break,
+            },
+        };
+
+        if send_utxo_subscription_messages(&mut ws_tx, replies)
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    subscriptions.lock().unwrap().unsubscribe(id);
+}
+
+/// Stream each newly stored block to a WS connection until it closes.
+/// Consumers falling too far behind are disconnected.
+pub async fn block_stream_socket(socket: WebSocket, block_stream: BlockStreamSender) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let mut blocks_rx = block_stream.subscribe();
+
+    loop {
+        let block = tokio::select! {
+            msg = ws_rx.next() => match msg {
+                Some(Ok(msg)) if msg.is_close() => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!("route:block_stream error: {:?}", e);
+                    break;
+                }
+                None => break,
+            },
+            block = blocks_rx.recv() => match block {
+                Ok(block) => block,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("route:block_stream dropping slow consumer: {} skipped", skipped);
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        if ws_tx.send(Message::text(block)).await.is_err() {
+            break;
+        }
+    }
+
+    let _ = ws_tx.close().await;
+}
+
+/// Stream each payment received by the wallet to a WS connection until it closes.
+/// Consumers falling too far behind are disconnected.
+pub async fn payment_events_socket(socket: WebSocket, payment_events: PaymentEventSender) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let mut events_rx = payment_events.subscribe();
+
+    loop {
+        let event = tokio::select! {
+            msg = ws_rx.next() => match msg {
+                Some(Ok(msg)) if msg.is_close() => break,
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!("route:payment_events error: {:?}", e);
+                    break;
+                }
+                None => break,
+            },
+            event = events_rx.recv() => match event {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("route:payment_events dropping slow consumer: {} skipped", skipped);
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+
+        let event = serde_json::to_string(&event).unwrap();
+        if ws_tx.send(Message::text(event)).await.is_err() {
+            break;
+        }
+    }
+
+    let _ = ws_tx.close().await;
+}
+
+/// Serve pool mining jobs to a WS connection and forward the solutions it finds
+/// to the mempool node. The partition of a closed connection goes to the other miners.
+pub async fn pool_mining_socket(
+    socket: WebSocket,
+    pool_mining: PoolMiningHandle,
+    mut threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+) {
+    let (mut ws_tx, mut ws_rx) = socket.split();
+    let (id, mut jobs_rx) = pool_mining.lock().unwrap().connect();
+
+    loop {
+        let replies = tokio::select! {
+            msg = ws_rx.next() => match msg {
+                Some(Ok(msg)) if msg.is_close() => break,
+                Some(Ok(msg)) => match msg.to_str().map(serde_json::from_str) {
+                    Ok(Ok(request)) => {
+                        let (replies, solution) =
+                            pool_mining.lock().unwrap().handle_request(id, request);
+                        if let Some(solution) = solution {
+                            let res = make_api_threaded_call(
+                                &mut threaded_calls,
+                                move |c| c.submit_pool_pow(solution),
+                                "Cannot access Mempool Node",
+                            )
+                            .await;
+                            debug!("route:pool_mining submitted: {:?}", res);
+                        }
+                        replies
+                    }
+                    Ok(Err(e)) => vec![invalid_pool_mining_request(e.to_string())],
+                    Err(()) => {
+                        let message = "Expected text message".to_owned();
+                        vec![invalid_pool_mining_request(message)]
+                    }
+                },
+                Some(Err(e)) => {
+                    debug!("route:pool_mining error: {:?}", e);
+                    break;
+                }
+                None => break,
+            },
+            Some(job) = jobs_rx.recv() => vec![job],
+        };
+
+        if send_pool_mining_messages(&mut ws_tx, replies)
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+
+    pool_mining.lock().unwrap().disconnect(id);
+}
+
 //======= Helpers =======//
 
+/// Send the given messages to a pool miner connection
+async fn send_pool_mining_messages(
+    ws_tx: &mut SplitSink<WebSocket, Message>,
+    messages: Vec<PoolMiningMessage>,
+) -> Result<(), warp::Error> {
+    for msg in messages {
+        let msg = serde_json::to_string(&msg).unwrap();
+        ws_tx.send(Message::text(msg)).await?;
+    }
+    Ok(())
+}
+
+/// Error message for a pool mining request that could not be parsed
+fn invalid_pool_mining_request(message: String) -> PoolMiningMessage {
+    PoolMiningMessage::Error(PoolMiningError::InvalidRequest { message })
+}
+
+/// Send the given messages to a UTXO subscription connection
+async fn send_utxo_subscription_messages(
+    ws_tx: &mut SplitSink<WebSocket, Message>,
+    messages: Vec<UtxoSubscriptionMessage>,
+) -> Result<(), warp::Error> {
+    for msg in messages {
+        let msg = serde_json::to_string(&msg).unwrap();
+        ws_tx.send(Message::text(msg)).await?;
+    }
+    Ok(())
+}
+
+/// Error message for a request that could not be parsed
+fn invalid_utxo_subscription_request(message: String) -> UtxoSubscriptionMessage {
+    UtxoSubscriptionMessage::Error(UtxoSubscriptionError::InvalidRequest { message })
+}
+
 /// Filters through wallet errors which are internal vs errors caused by user input
 pub fn wallet_db_error(
     err: WalletDbError,
@@ -1145,6 +2546,18 @@ pub fn wallet_db_error(
         WalletDbError::PassphraseError => {
             call_response.into_err(StatusCode::UNAUTHORIZED, ApiErrorType::InvalidPassphrase)
         }
+        WalletDbError::WalletLockedError => {
+            call_response.into_err(StatusCode::FORBIDDEN, ApiErrorType::WalletLocked)
+        }
+        WalletDbError::ExportFormatError
+        | WalletDbError::ImportKeyConflictError(_)
+        | WalletDbError::HdSeedConflictError
+        | WalletDbError::UnknownAddressError(_)
+        | WalletDbError::UnlockTimeoutError
+        | WalletDbError::CancelPendingPayment(_) => call_response.into_err(
+            StatusCode::BAD_REQUEST,
+            ApiErrorType::Generic(err.to_string()),
+        ),
         _ => call_response.into_err_internal(ApiErrorType::InternalError),
     }
 }
@@ -1262,6 +2675,19 @@ pub fn to_transaction(data: CreateTransaction) -> Result<Transaction, StringErro
     })
 }
 
+/// Decode a hex or base64 string representing a serialized `Transaction`.
+/// Hex is tried first as its alphabet is a subset of base64's.
+fn from_encoded_transaction(data: &str) -> Result<Transaction, StringError> {
+    let bytes = match hex::decode(data) {
+        Ok(bytes) => bytes,
+        Err(_) => STANDARD
+            .decode(data)
+            .map_err(|e| StringError(format!("Transaction is not hex or base64: {e}")))?,
+    };
+    bincode::deserialize::<Transaction>(bytes.as_slice())
+        .map_err(|e| StringError(format!("Transaction could not be decoded: {e}")))
+}
+
 /// Create a `CreateTransaction` from a hex string representing a serialized `Transaction`
 fn from_hex_transaction(data: String) -> Result<CreateTransaction, StringError> {
     let bytes = hex::decode(data).map_err(map_to_string_err)?;
@@ -1315,9 +2741,10 @@ fn get_json_reply_stored_value_from_db(
     route: &'static str,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_db_available(&db, &r)?;
     let item = get_stored_value_from_db(db, key.as_bytes()).ok_or_else(|| {
         r.clone()
-            .into_err(StatusCode::NO_CONTENT, ApiErrorType::NoDataFoundForKey)
+            .into_err_type(ApiErrorType::NoDataFoundForKey)
             .unwrap_err()
     })?;
 
@@ -1339,6 +2766,49 @@ pub fn get_json_reply_items_from_db(
     call_id: String,
 ) -> Result<JsonReply, JsonReply> {
     let r = CallResponse::new(route, &call_id);
+    check_batch_len(keys.len(), &r)?;
+    check_db_available(&db, &r)?;
+    r.into_ok(
+        "Database item(s) successfully retrieved",
+        JsonReply::new(get_json_items_from_db(db, keys)),
+    )
+}
+
+/// Error reply when a panic while holding the database lock left the database unusable
+///
+/// ### Arguments
+///
+/// * `db` - Database the route reads from
+/// * `r`  - Response of the call
+fn check_db_available(db: &Mutex<SimpleDb>, r: &CallResponse) -> Result<(), JsonReply> {
+    if db.is_poisoned() {
+        error!("route:{} database lock poisoned", r.route);
+        return Err(r
+            .clone()
+            .into_err_type(ApiErrorType::DatabaseUnavailable)
+            .unwrap_err());
+    }
+    Ok(())
+}
+
+/// Error reply when a batch request has more items than allowed
+///
+/// ### Arguments
+///
+/// * `len` - Number of items in the request
+/// * `r`   - Response of the call
+fn check_batch_len(len: usize, r: &CallResponse) -> Result<(), JsonReply> {
+    if len > API_BATCH_ITEMS_LIMIT {
+        return Err(r
+            .clone()
+            .into_err_type(ApiErrorType::TooManyItems(API_BATCH_ITEMS_LIMIT))
+            .unwrap_err());
+    }
+    Ok(())
+}
+
+/// Get the JSON array of key, item and meta tuples from the database
+fn get_json_items_from_db(db: Arc<Mutex<SimpleDb>>, keys: Vec<String>) -> Vec<u8> {
     let key_values: Vec<_> = keys
         .into_iter()
         .map(|key| {
@@ -1364,11 +2834,7 @@ pub fn get_json_reply_items_from_db(
     let mut key_values: Vec<_> = key_values.join(&&b","[..]);
     key_values.insert(0, &b"["[..]);
     key_values.push(&b"]"[..]);
-
-    r.into_ok(
-        "Database item(s) successfully retrieved",
-        json_embed(&key_values),
-    )
+    key_values.concat()
 }
 
 /// Threaded call for API
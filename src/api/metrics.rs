@@ -0,0 +1,81 @@
+//! Node metrics formatted in the Prometheus text exposition format
+
+use crate::active_raft::RaftInfo;
+use crate::chain_quality::ChainQualityHandle;
+use crate::db_utils::SimpleDb;
+use crate::interfaces::MempoolApi;
+use crate::miner_stats::MinerStatsHandle;
+use crate::storage::StoredSupplyHandle;
+use crate::threaded_call::ThreadedCallSender;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Prefix of the name of all exposed metrics
+const METRIC_NAME_PREFIX: &str = "aiblock_";
+
+/// Node components the metrics of each node type are populated from
+#[derive(Clone)]
+pub enum MetricsSource {
+    Mempool {
+        threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+        raft_info: RaftInfo,
+    },
+    Storage {
+        db: Arc<Mutex<SimpleDb>>,
+        chain_quality: ChainQualityHandle,
+        stored_supply: StoredSupplyHandle,
+        raft_info: RaftInfo,
+    },
+    Miner {
+        miner_stats: MinerStatsHandle,
+    },
+    User,
+}
+
+/// Metrics rendered in the Prometheus text exposition format
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    text: String,
+}
+
+impl PrometheusMetrics {
+    /// Add a counter, its name suffixed with `_total`
+    ///
+    /// ### Arguments
+    ///
+    /// * `name`  - Name of the counter without prefix or suffix
+    /// * `help`  - Description of the counter
+    /// * `value` - Current value of the counter
+    pub fn counter(&mut self, name: &str, help: &str, value: impl fmt::Display) -> &mut Self {
+        self.push(&format!("{name}_total"), "counter", help, value)
+    }
+
+    /// Add a gauge
+    ///
+    /// ### Arguments
+    ///
+    /// * `name`  - Name of the gauge without prefix
+    /// * `help`  - Description of the gauge
+    /// * `value` - Current value of the gauge
+    pub fn gauge(&mut self, name: &str, help: &str, value: impl fmt::Display) -> &mut Self {
+        self.push(name, "gauge", help, value)
+    }
+
+    /// Get the rendered metrics
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Render a metric with its HELP and TYPE lines
+    fn push(&mut self, name: &str, kind: &str, help: &str, value: impl fmt::Display) -> &mut Self {
+        let name = format!("{METRIC_NAME_PREFIX}{name}");
+        // Writing to a String cannot fail
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+        let _ = writeln!(self.text, "{name} {value}");
+        self
+    }
+}
@@ -2,6 +2,7 @@
 
 pub mod errors;
 pub mod handlers;
+pub mod metrics;
 pub mod responses;
 pub mod routes;
 pub mod utils;
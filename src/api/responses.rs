@@ -10,6 +10,7 @@ use warp::hyper::StatusCode;
 pub struct JsonReply {
     data: Vec<u8>,
     status_code: StatusCode,
+    retry_after: Option<u64>,
 }
 
 impl JsonReply {
@@ -17,6 +18,7 @@ impl JsonReply {
         JsonReply {
             data,
             status_code: StatusCode::OK,
+            retry_after: None,
         }
     }
 
@@ -24,16 +26,26 @@ impl JsonReply {
         self.status_code = status_code;
         self
     }
+
+    /// Reply with a `Retry-After` header, in seconds
+    pub fn with_retry_after(mut self, retry_after_secs: u64) -> Self {
+        self.retry_after = Some(retry_after_secs);
+        self
+    }
 }
 
 impl warp::reply::Reply for JsonReply {
     #[inline]
     fn into_response(self) -> warp::reply::Response {
-        use warp::http::header::{HeaderValue, CONTENT_TYPE};
+        use warp::http::header::{HeaderValue, CONTENT_TYPE, RETRY_AFTER};
         let res = warp::reply::Response::new(self.data.into());
         let mut res = warp::reply::with_status(res, self.status_code).into_response();
         res.headers_mut()
             .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(retry_after) = self.retry_after {
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+        }
         res
     }
 }
@@ -100,7 +112,12 @@ impl<'a> CallResponse<'a> {
         status: StatusCode,
         api_error_type: ApiErrorType,
     ) -> Result<JsonReply, JsonReply> {
-        self.into_err_with_data(status, api_error_type, json_serialize_embed("null"))
+        let data = json_error_content(status, &api_error_type);
+        self.into_err_with_data(status, api_error_type, data)
+    }
+
+    pub fn into_err_type(self, api_error_type: ApiErrorType) -> Result<JsonReply, JsonReply> {
+        self.into_err(api_error_type.status(), api_error_type)
     }
 
     pub fn into_ok(self, reason: &str, data: JsonReply) -> Result<JsonReply, JsonReply> {
@@ -138,6 +155,22 @@ impl std::fmt::Display for APIResponseStatus {
     }
 }
 
+/// Content of error replies, with a machine readable code for the error
+#[derive(Debug, Clone, Serialize)]
+pub struct APIErrorContent {
+    error: &'static str,
+    message: String,
+}
+
+impl APIErrorContent {
+    pub fn new(status: StatusCode, error_type: &ApiErrorType) -> Self {
+        APIErrorContent {
+            error: error_type.code(status),
+            message: error_type.to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct APIAsset {
     asset: Asset,
@@ -241,6 +274,16 @@ pub fn common_error_reply(
     .with_code(status)
 }
 
+/// Embed the content of an error reply
+///
+/// ### Arguments
+///
+/// * `status` - Status code replied with the error
+/// * `error_type` - The reason for the API call's failure
+pub fn json_error_content(status: StatusCode, error_type: &ApiErrorType) -> JsonReply {
+    json_serialize_embed(APIErrorContent::new(status, error_type))
+}
+
 /// Handles optional response content. Defaults to null if None provided
 fn optional_content_default(content: Option<JsonReply>) -> JsonReply {
     match content {
@@ -1,43 +1,64 @@
+use crate::active_raft::RaftInfo;
 use crate::api::handlers::{self, DbgPaths};
+use crate::api::metrics::MetricsSource;
 use crate::api::utils::{
-    auth_request, create_new_cache, handle_rejection, map_api_res_and_cache, warp_path,
-    with_node_component, with_semaphore, ReplyCache, CACHE_LIVE_TIME,
+    auth_request, create_new_cache, handle_rejection, map_api_res, map_api_res_and_cache,
+    warp_path, with_node_component, with_rate_limit, with_semaphore, RateLimiter, ReplyCache,
+    CACHE_LIVE_TIME,
 };
+use crate::chain_quality::ChainQualityHandle;
 use crate::comms_handler::Node;
+use crate::configurations::ApiRateLimits;
 use crate::constants::API_CONCURRENCY_LIMIT;
 use crate::db_utils::SimpleDb;
 use crate::interfaces::{MempoolApi, UserApi};
 use crate::miner::CurrentBlockWithMutex;
+use crate::miner_stats::MinerStatsHandle;
+use crate::pool_mining::PoolMiningHandle;
+use crate::storage::{BlockStreamSender, StoredSupplyHandle};
 use crate::threaded_call::ThreadedCallSender;
+use crate::user::PaymentEventSender;
 use crate::utils::{ApiKeys, RoutesPoWInfo};
+use crate::utxo_subscription::UtxoSubscriptionsHandle;
 use crate::wallet::WalletDb;
 use std::sync::{Arc, Mutex};
 use tokio::sync::Semaphore;
 
 use warp::{Filter, Rejection, Reply};
 
+/// Headers allowed in the requests to the routes
+const CORS_ALLOWED_HEADERS: [&str; 14] = [
+    "Accept",
+    "User-Agent",
+    "Sec-Fetch-Mode",
+    "Referer",
+    "Origin",
+    "Access-Control-Request-Method",
+    "Access-Control-Request-Headers",
+    "Access-Control-Allow-Origin",
+    "Access-Control-Allow-Headers",
+    "Content-Type",
+    "x-cache-id",
+    "x-request-id",
+    "x-nonce",
+    "x-api-key",
+];
+
+// Node routes CORS, applied once rejections are recovered so error replies carry it too
+pub fn node_routes_cors() -> warp::cors::Builder {
+    warp::cors()
+        .allow_any_origin()
+        .allow_headers(CORS_ALLOWED_HEADERS)
+        .allow_methods(vec!["GET", "POST", "OPTIONS"])
+}
+
 //======= GET ROUTES =======//
 
 // GET CORS
 pub fn get_cors() -> warp::cors::Builder {
     warp::cors()
         .allow_any_origin()
-        .allow_headers(vec![
-            "Accept",
-            "User-Agent",
-            "Sec-Fetch-Mode",
-            "Referer",
-            "Origin",
-            "Access-Control-Request-Method",
-            "Access-Control-Request-Headers",
-            "Access-Control-Allow-Origin",
-            "Access-Control-Allow-Headers",
-            "Content-Type",
-            "x-cache-id",
-            "x-request-id",
-            "x-nonce",
-            "x-api-key",
-        ])
+        .allow_headers(CORS_ALLOWED_HEADERS)
         .allow_methods(vec!["GET", "OPTIONS"])
 }
 
@@ -70,31 +91,54 @@ pub fn wallet_info(
         .with(get_cors())
 }
 
-// GET all keypairs
-// TODO: Requires password (will move to POST)
-pub fn export_keypairs(
+// GET the payments sent and received by the wallet, optionally only the most recent ones
+pub fn transaction_history(
     dp: &mut DbgPaths,
     db: WalletDb,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    let route = "export_keypairs";
+    let route = "transaction_history";
     warp_path(dp, route)
         .and(warp::get())
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
+        .and(
+            warp::path::param::<usize>()
+                .map(Some)
+                .or_else(|_| async { Ok::<(Option<usize>,), std::convert::Infallible>((None,)) }),
+        )
         .and(with_node_component(cache))
-        .and_then(move |call_id: String, db, cache| {
+        .and_then(move |call_id: String, db, limit, cache| {
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::get_export_keypairs(db, route, call_id),
+                handlers::get_transaction_history(db, limit, route, call_id),
             )
         })
         .with(get_cors())
 }
 
+// POST all keypairs, given the wallet passphrase
+pub fn export_keypairs(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "export_keypairs";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_export_keypairs(db, info, route, call_id))
+        })
+        .with(post_cors())
+}
+
 // GET new payment address
 pub fn payment_address(
     dp: &mut DbgPaths,
@@ -125,11 +169,13 @@ pub fn latest_block(
     db: Arc<Mutex<SimpleDb>>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "latest_block";
     warp_path(dp, route)
         .and(warp::get())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(with_node_component(cache))
@@ -143,6 +189,156 @@ pub fn latest_block(
         .with(get_cors())
 }
 
+// GET chain quality as seen by storage
+pub fn chain_quality(
+    dp: &mut DbgPaths,
+    chain_quality: ChainQualityHandle,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "chain_quality";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(chain_quality))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, chain_quality, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_chain_quality(chain_quality, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET total supply created by the stored blockchain
+pub fn stored_total_supply(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    stored_supply: StoredSupplyHandle,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "total_supply";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(with_node_component(stored_supply))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, stored_supply, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_stored_total_supply(db, stored_supply, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET supply issued by mining up to the latest stored block
+pub fn stored_issued_supply(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    stored_supply: StoredSupplyHandle,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "issued_supply";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(with_node_component(stored_supply))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, stored_supply, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_stored_issued_supply(db, stored_supply, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// WS subscription to UTXO events for a set of addresses
+pub fn utxo_subscription(
+    dp: &mut DbgPaths,
+    subscriptions: UtxoSubscriptionsHandle,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "utxo_subscription";
+    warp_path(dp, route)
+        .and(warp::ws())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(subscriptions))
+        .map(|ws: warp::ws::Ws, _call_id: String, subscriptions| {
+            ws.on_upgrade(move |socket| handlers::utxo_subscription_socket(socket, subscriptions))
+        })
+}
+
+// WS stream of newly stored blocks
+pub fn block_stream(
+    dp: &mut DbgPaths,
+    block_stream: BlockStreamSender,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "block_stream";
+    warp_path(dp, route)
+        .and(warp::ws())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(block_stream))
+        .map(|ws: warp::ws::Ws, _call_id: String, block_stream| {
+            ws.on_upgrade(move |socket| handlers::block_stream_socket(socket, block_stream))
+        })
+}
+
+// WS stream of the payments received by the wallet
+pub fn payment_events(
+    dp: &mut DbgPaths,
+    payment_events: PaymentEventSender,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "payment_events";
+    warp_path(dp, route)
+        .and(warp::ws())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(payment_events))
+        .map(|ws: warp::ws::Ws, _call_id: String, payment_events| {
+            ws.on_upgrade(move |socket| handlers::payment_events_socket(socket, payment_events))
+        })
+}
+
+// WS pool mining jobs and solutions
+pub fn pool_mining(
+    dp: &mut DbgPaths,
+    pool_mining: PoolMiningHandle,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "pool_mining";
+    warp_path(dp, route)
+        .and(warp::ws())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(pool_mining))
+        .and(with_node_component(threaded_calls))
+        .map(
+            |ws: warp::ws::Ws, _call_id: String, pool_mining, threaded_calls| {
+                ws.on_upgrade(move |socket| {
+                    handlers::pool_mining_socket(socket, pool_mining, threaded_calls)
+                })
+            },
+        )
+}
+
 // GET debug data
 pub fn debug_data(
     mut dp: DbgPaths,
@@ -176,6 +372,32 @@ pub fn debug_data(
         .with(get_cors())
 }
 
+// GET node version, type and raft role
+pub fn node_info(
+    dp: &mut DbgPaths,
+    node: Node,
+    raft_info: Option<RaftInfo>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "node_info";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(node))
+        .and(with_node_component(raft_info))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, node, raft_info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_node_info(node, raft_info, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
 // GET current block being mined
 pub fn current_mining_block(
     dp: &mut DbgPaths,
@@ -200,6 +422,52 @@ pub fn current_mining_block(
         .with(get_cors())
 }
 
+// GET mining statistics of the miner node
+pub fn miner_stats(
+    dp: &mut DbgPaths,
+    miner_stats: MinerStatsHandle,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "miner_stats";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(miner_stats))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, miner_stats, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_miner_stats(miner_stats, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET node metrics in the Prometheus text exposition format
+pub fn metrics(
+    dp: &mut DbgPaths,
+    node: Node,
+    source: MetricsSource,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "metrics";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(node))
+        .and(with_node_component(source))
+        .and_then(move |call_id: String, node, source| async move {
+            handlers::get_metrics(node, source, route, call_id)
+                .await
+                .or_else(|e| Ok::<_, Rejection>(e.into_response()))
+        })
+        .with(get_cors())
+}
+
 // GET total supply in the system. Can be pulled directly from the blockchain
 pub fn total_supply(
     dp: &mut DbgPaths,
@@ -250,6 +518,136 @@ pub fn issued_supply(
         .with(get_cors())
 }
 
+// GET number of transactions pending consensus
+pub fn mempool_status(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "mempool_status";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_mempool_status(tc, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET fee suggested for a transaction to be included in the next blocks
+pub fn estimate_fee(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "estimate_fee";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_estimate_fee(tc, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET difficulty of the block offered to miners
+pub fn current_difficulty(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "current_difficulty";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_current_difficulty(tc, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET raft consensus status for debugging
+pub fn debug_raft_status(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "debug_raft_status";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_debug_raft_status(tc, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
+// GET miner whitelist
+pub fn miner_whitelist(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "miner_whitelist";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_miner_whitelist(tc, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
 // GET UTXO set addresses
 pub fn utxo_addresses(
     dp: &mut DbgPaths,
@@ -322,28 +720,62 @@ pub fn get_outgoing_txs(
         .with(get_cors())
 }
 
+// GET payments sent to mempool and their confirmation state
+pub fn pending_payments(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "pending_payments";
+    warp_path(dp, route)
+        .and(warp::get())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::get_pending_payments(db, route, call_id),
+            )
+        })
+        .with(get_cors())
+}
+
 //======= POST ROUTES =======//
 
+// POST cancel a payment not confirmed within the safety horizon
+pub fn cancel_pending_payment(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "cancel_pending_payment";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_cancel_pending_payment(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
 // POST CORS
 pub fn post_cors() -> warp::cors::Builder {
     warp::cors()
         .allow_any_origin()
-        .allow_headers(vec![
-            "Accept",
-            "User-Agent",
-            "Sec-Fetch-Mode",
-            "Referer",
-            "Origin",
-            "Access-Control-Request-Method",
-            "Access-Control-Request-Headers",
-            "Access-Control-Allow-Origin",
-            "Access-Control-Allow-Headers",
-            "Content-Type",
-            "x-cache-id",
-            "x-request-id",
-            "x-nonce",
-            "x-api-key",
-        ])
+        .allow_headers(CORS_ALLOWED_HEADERS)
         .allow_methods(vec!["POST", "OPTIONS"])
 }
 
@@ -353,11 +785,13 @@ pub fn blockchain_entry_by_key(
     db: Arc<Mutex<SimpleDb>>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "blockchain_entry";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(warp::body::json())
@@ -372,17 +806,181 @@ pub fn blockchain_entry_by_key(
         .with(post_cors())
 }
 
+// POST get the stored transactions of DRUIDs
+pub fn blockchain_entry_by_druid(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "blockchain_entry_by_druid";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_blockchain_entry_by_druid(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST get the stored transactions sharing a DRUID
+pub fn transactions_by_druid(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "transactions_by_druid";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_transactions_by_druid(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST get a page of the stored UTXO set
+pub fn storage_utxo_addresses(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "utxo_addresses";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_utxo_index_page(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST get a page of the stored transactions touching an address
+pub fn transactions_by_address(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "transactions_by_address";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_transactions_by_address(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST get the stored blocks in a range of numbers
+pub fn blocks_by_num_range(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "blocks_by_num_range";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_blocks_by_num_range(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST get the header summaries of the blocks in a range of numbers
+pub fn block_range(
+    dp: &mut DbgPaths,
+    db: Arc<Mutex<SimpleDb>>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "block_range";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_block_range(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
 // POST get block information by number
 pub fn block_by_num(
     dp: &mut DbgPaths,
     db: Arc<Mutex<SimpleDb>>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "block_by_num";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(warp::body::json())
@@ -403,11 +1001,13 @@ pub fn transactions_by_key(
     db: Arc<Mutex<SimpleDb>>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "transactions_by_key";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(warp::body::json())
@@ -422,47 +1022,92 @@ pub fn transactions_by_key(
         .with(post_cors())
 }
 
-// POST save keypair
-// TODO: Requires password
+// POST save keypair, given the wallet passphrase
 pub fn import_keypairs(
     dp: &mut DbgPaths,
     db: WalletDb,
     node: Node,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "import_keypairs";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(with_node_component(node))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, node, kp| {
+            map_api_res(handlers::post_import_keypairs(node, db, kp, route, call_id))
+        })
+        .with(post_cors())
+}
+
+// POST delete keypair, given the wallet passphrase
+pub fn delete_keypair(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "delete_keypair";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_delete_keypair(db, info, route, call_id))
+        })
+        .with(post_cors())
+}
+
+// POST make payment
+pub fn make_payment(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    node: Node,
+    threaded_calls: ThreadedCallSender<dyn UserApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    let route = "import_keypairs";
+    let route = "make_payment";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(with_node_component(node))
+        .and(with_node_component(threaded_calls))
         .and(warp::body::json())
         .and(with_node_component(cache))
-        .and_then(move |call_id: String, db, node, kp, cache| {
+        .and_then(move |call_id: String, db, node, tc, pi, cache| {
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::post_import_keypairs(node, db, kp, route, call_id),
+                handlers::post_make_payment(db, node, tc, pi, route, call_id),
             )
         })
         .with(post_cors())
 }
 
-// POST make payment
-pub fn make_payment(
+// POST make item asset payment
+pub fn make_item_payment(
     dp: &mut DbgPaths,
     db: WalletDb,
     node: Node,
     threaded_calls: ThreadedCallSender<dyn UserApi>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    let route = "make_payment";
+    let route = "make_item_payment";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(with_node_component(node))
@@ -473,7 +1118,7 @@ pub fn make_payment(
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::post_make_payment(db, node, tc, pi, route, call_id),
+                handlers::post_make_item_payment(db, node, tc, pi, route, call_id),
             )
         })
         .with(post_cors())
@@ -486,11 +1131,13 @@ pub fn make_ip_payment(
     node: Node,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "make_ip_payment";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(with_node_component(node))
@@ -537,12 +1184,14 @@ pub fn transaction_status(
     threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     semaphore: Arc<Semaphore>,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "transaction_status";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(with_semaphore(semaphore))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(threaded_calls))
@@ -591,12 +1240,14 @@ pub fn fetch_balance(
     threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     semaphore: Arc<Semaphore>,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "fetch_balance";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(with_semaphore(semaphore))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(threaded_calls))
@@ -612,6 +1263,31 @@ pub fn fetch_balance(
         .with(post_cors())
 }
 
+// POST fetch balance for wallet addresses
+pub fn fetch_wallet_balance(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "fetch_balance";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |call_id: String, db, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_fetch_wallet_balance(db, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
 // POST fetch balance for addresses
 pub fn fetch_pending(
     dp: &mut DbgPaths,
@@ -668,6 +1344,7 @@ pub fn create_item_asset(
 pub fn create_item_asset_user(
     dp: &mut DbgPaths,
     node: Node,
+    db: WalletDb,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
     cache: ReplyCache,
@@ -677,13 +1354,14 @@ pub fn create_item_asset_user(
         .and(warp::post())
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(node))
+        .and(with_node_component(db))
         .and(warp::body::json())
         .and(with_node_component(cache))
-        .and_then(move |call_id: String, node, info, cache| {
+        .and_then(move |call_id: String, node, db: WalletDb, info, cache| {
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::post_create_item_asset_user(node, info, route, call_id),
+                handlers::post_create_item_asset_user(node, db, info, route, call_id),
             )
         })
         .with(post_cors())
@@ -695,7 +1373,6 @@ pub fn change_passphrase(
     db: WalletDb,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
-    cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let route = "change_passphrase";
     warp_path(dp, route)
@@ -703,29 +1380,130 @@ pub fn change_passphrase(
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_change_wallet_passphrase(
+                db, info, route, call_id,
+            ))
+        })
+        .with(post_cors())
+}
+
+// POST unlock wallet for key-revealing and payment-creating routes
+pub fn unlock_wallet(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "unlock_wallet";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_unlock_wallet(db, info, route, call_id))
+        })
+        .with(post_cors())
+}
+
+// POST lock wallet
+pub fn lock_wallet(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "lock_wallet";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
         .and(with_node_component(cache))
-        .and_then(move |call_id: String, db, info, cache| {
+        .and_then(move |call_id: String, db, cache| {
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::post_change_wallet_passphrase(db, info, route, call_id),
+                handlers::post_lock_wallet(db, route, call_id),
             )
         })
         .with(post_cors())
 }
 
+// POST export full wallet encrypted with a passphrase
+pub fn export_wallet(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "export_wallet";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_export_wallet(db, info, route, call_id))
+        })
+        .with(post_cors())
+}
+
+// POST import a full wallet export
+pub fn import_wallet(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "import_wallet";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| {
+            map_api_res(handlers::post_import_wallet(db, info, route, call_id))
+        })
+        .with(post_cors())
+}
+
+// POST export wallet activity report
+pub fn export_activity(
+    dp: &mut DbgPaths,
+    db: WalletDb,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "export_activity";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(db))
+        .and(warp::body::json())
+        .and_then(move |call_id: String, db, info| async move {
+            handlers::post_export_activity(db, info, route, call_id)
+                .await
+                .or_else(|e| Ok::<_, Rejection>(e.into_response()))
+        })
+        .with(post_cors())
+}
+
 // POST create transactions
 pub fn create_transactions(
     dp: &mut DbgPaths,
     threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     semaphore: Arc<Semaphore>,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
     let route = "create_transactions";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(with_semaphore(semaphore))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(threaded_calls))
@@ -741,6 +1519,35 @@ pub fn create_transactions(
         .with(post_cors())
 }
 
+// POST submit a serialized transaction
+pub fn submit_transaction(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
+    semaphore: Arc<Semaphore>,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
+    let route = "submit_transaction";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
+        .and(with_semaphore(semaphore))
+        .and(auth_request(routes_pow, api_keys))
+        .and(with_node_component(threaded_calls))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and_then(move |_, call_id: String, tc, info, cache| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_submit_transaction(tc, info, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
 // POST serialize transactions
 pub fn serialize_transactions(
     dp: &mut DbgPaths,
@@ -793,11 +1600,13 @@ pub fn blocks_by_tx_hashes(
     db: Arc<Mutex<SimpleDb>>,
     routes_pow: RoutesPoWInfo,
     api_keys: ApiKeys,
+    rate_limiter: RateLimiter,
     cache: ReplyCache,
 ) -> impl Filter<Extract = (impl Reply,), Error = warp::Rejection> + Clone {
     let route = "check_transaction_presence";
     warp_path(dp, route)
         .and(warp::post())
+        .and(with_rate_limit(rate_limiter))
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(db))
         .and(warp::body::json())
@@ -849,12 +1658,11 @@ pub fn pause_nodes(
         .and(auth_request(routes_pow, api_keys))
         .and(with_node_component(cache))
         .and(with_node_component(threaded_calls))
-        .and(warp::body::json())
-        .and_then(move |call_id: String, cache, tc, b_num| {
+        .and_then(move |call_id: String, cache, tc| {
             map_api_res_and_cache(
                 call_id.clone(),
                 cache,
-                handlers::pause_nodes(tc, route, call_id, b_num),
+                handlers::pause_nodes(tc, route, call_id),
             )
         })
         .with(post_cors())
@@ -909,6 +1717,56 @@ pub fn update_shared_config(
         .with(post_cors())
 }
 
+// POST add miner whitelist entries
+pub fn add_miner_whitelist(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "add_miner_whitelist";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and(with_node_component(threaded_calls))
+        .and_then(move |call_id: String, info, cache, tc| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_update_miner_whitelist(tc, info, true, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
+// POST remove miner whitelist entries
+pub fn remove_miner_whitelist(
+    dp: &mut DbgPaths,
+    threaded_calls: ThreadedCallSender<dyn MempoolApi>,
+    routes_pow: RoutesPoWInfo,
+    api_keys: ApiKeys,
+    cache: ReplyCache,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let route = "remove_miner_whitelist";
+    warp_path(dp, route)
+        .and(warp::post())
+        .and(auth_request(routes_pow, api_keys))
+        .and(warp::body::json())
+        .and(with_node_component(cache))
+        .and(with_node_component(threaded_calls))
+        .and_then(move |call_id: String, info, cache, tc| {
+            map_api_res_and_cache(
+                call_id.clone(),
+                cache,
+                handlers::post_update_miner_whitelist(tc, info, false, route, call_id),
+            )
+        })
+        .with(post_cors())
+}
+
 //======= NODE ROUTES =======//
 //TODO: Nodes share similar routes; We need to find a way to reduce ambiguity
 
@@ -916,9 +1774,11 @@ pub fn update_shared_config(
 pub fn user_node_routes(
     api_keys: ApiKeys,
     routes_pow_info: RoutesPoWInfo,
+    rate_limits: ApiRateLimits,
     db: WalletDb,
     node: Node,
     threaded_calls: ThreadedCallSender<dyn UserApi>,
+    payment_events_sender: PaymentEventSender,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let mut dp_vec = DbgPaths::new();
     let dp = &mut dp_vec;
@@ -939,6 +1799,34 @@ pub fn user_node_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(pending_payments(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(transaction_history(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(cancel_pending_payment(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(fetch_wallet_balance(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     .or(make_payment(
         dp,
         db.clone(),
@@ -946,6 +1834,17 @@ pub fn user_node_routes(
         threaded_calls.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
+        cache.clone(),
+    ))
+    .or(make_item_payment(
+        dp,
+        db.clone(),
+        node.clone(),
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
         cache.clone(),
     ))
     .or(make_ip_payment(
@@ -954,6 +1853,7 @@ pub fn user_node_routes(
         node.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
         cache.clone(),
     ))
     .or(request_donation(
@@ -968,7 +1868,6 @@ pub fn user_node_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
     ))
     .or(import_keypairs(
         dp,
@@ -976,7 +1875,12 @@ pub fn user_node_routes(
         node.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
+    ))
+    .or(delete_keypair(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
     ))
     .or(update_running_total(
         dp,
@@ -989,6 +1893,7 @@ pub fn user_node_routes(
     .or(create_item_asset_user(
         dp,
         node.clone(),
+        db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
         cache.clone(),
@@ -1000,13 +1905,43 @@ pub fn user_node_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(export_activity(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
     .or(change_passphrase(
         dp,
-        db,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(unlock_wallet(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(lock_wallet(
+        dp,
+        db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(export_wallet(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(import_wallet(
+        dp,
+        db,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
     // .or(address_construction(
     //     dp,
     //     routes_pow_info.clone(),
@@ -1025,6 +1960,27 @@ pub fn user_node_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(payment_events(
+        dp,
+        payment_events_sender,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(metrics(
+        dp,
+        node.clone(),
+        MetricsSource::User,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(node_info(
+        dp,
+        node.clone(),
+        None,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     .or(debug_data(
         dp_vec,
         node,
@@ -1035,15 +1991,21 @@ pub fn user_node_routes(
         cache,
     ));
 
-    routes.recover(handle_rejection)
+    routes.recover(handle_rejection).with(node_routes_cors())
 }
 
 // API routes for Storage nodes
 pub fn storage_node_routes(
     api_keys: ApiKeys,
     routes_pow_info: RoutesPoWInfo,
+    rate_limits: ApiRateLimits,
     db: Arc<Mutex<SimpleDb>>,
+    utxo_subscriptions: UtxoSubscriptionsHandle,
+    block_stream_sender: BlockStreamSender,
+    chain_quality_handle: ChainQualityHandle,
+    stored_supply: StoredSupplyHandle,
     node: Node,
+    raft_info: RaftInfo,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let mut dp_vec = DbgPaths::new();
     let dp = &mut dp_vec;
@@ -1055,13 +2017,31 @@ pub fn storage_node_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
         cache.clone(),
     )
+    .or(blocks_by_num_range(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
+    .or(block_range(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
     // .or(transactions_by_key(
     //     dp,
     //     db.clone(),
     //     routes_pow_info.clone(),
     //     api_keys.clone(),
+    //     RateLimiter::new(rate_limits.reads),
     //     cache.clone(),
     // ))
     .or(latest_block(
@@ -1069,6 +2049,7 @@ pub fn storage_node_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
         cache.clone(),
     ))
     .or(blockchain_entry_by_key(
@@ -1076,6 +2057,39 @@ pub fn storage_node_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
+    .or(blockchain_entry_by_druid(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
+    .or(transactions_by_druid(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
+    .or(storage_utxo_addresses(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        cache.clone(),
+    ))
+    .or(transactions_by_address(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
         cache.clone(),
     ))
     // .or(blocks_by_tx_hashes(
@@ -1083,6 +2097,7 @@ pub fn storage_node_routes(
     //     db,
     //     routes_pow_info.clone(),
     //     api_keys.clone(),
+    //     RateLimiter::new(rate_limits.reads),
     //     cache.clone(),
     // ))
     // .or(address_construction(
@@ -1091,6 +2106,61 @@ pub fn storage_node_routes(
     //     api_keys.clone(),
     //     cache.clone(),
     // ))
+    .or(utxo_subscription(
+        dp,
+        utxo_subscriptions,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(block_stream(
+        dp,
+        block_stream_sender,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(chain_quality(
+        dp,
+        chain_quality_handle.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(stored_total_supply(
+        dp,
+        db.clone(),
+        stored_supply.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(stored_issued_supply(
+        dp,
+        db.clone(),
+        stored_supply.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(metrics(
+        dp,
+        node.clone(),
+        MetricsSource::Storage {
+            db,
+            chain_quality: chain_quality_handle,
+            stored_supply,
+            raft_info: raft_info.clone(),
+        },
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(node_info(
+        dp,
+        node.clone(),
+        Some(raft_info),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     .or(debug_data(
         dp_vec,
         node,
@@ -1101,7 +2171,7 @@ pub fn storage_node_routes(
         cache,
     ));
 
-    routes.recover(handle_rejection)
+    routes.recover(handle_rejection).with(node_routes_cors())
 }
 
 // API routes for Mempool nodes
@@ -1110,8 +2180,11 @@ pub fn storage_node_routes(
 pub fn mempool_node_routes(
     api_keys: ApiKeys,
     routes_pow_info: RoutesPoWInfo,
+    rate_limits: ApiRateLimits,
     threaded_calls: ThreadedCallSender<dyn MempoolApi>,
     node: Node,
+    raft_info: RaftInfo,
+    pool_mining_handle: PoolMiningHandle,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let mut dp_vec = DbgPaths::new();
     let dp = &mut dp_vec;
@@ -1123,6 +2196,7 @@ pub fn mempool_node_routes(
         threaded_calls.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
         semaphore.clone(),
         cache.clone(),
     )
@@ -1139,6 +2213,16 @@ pub fn mempool_node_routes(
         threaded_calls.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(submit_transaction(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
         semaphore.clone(),
         cache.clone(),
     ))
@@ -1158,6 +2242,47 @@ pub fn mempool_node_routes(
         cache.clone(),
     ))
     .or(transaction_status(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.reads),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(mempool_status(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(current_difficulty(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(estimate_fee(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(debug_raft_status(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        semaphore.clone(),
+        cache.clone(),
+    ))
+    .or(miner_whitelist(
         dp,
         threaded_calls.clone(),
         routes_pow_info.clone(),
@@ -1165,6 +2290,34 @@ pub fn mempool_node_routes(
         semaphore.clone(),
         cache.clone(),
     ))
+    .or(add_miner_whitelist(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(remove_miner_whitelist(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(pause_nodes(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(resume_nodes(
+        dp,
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     // .or(utxo_addresses(
     //     dp,
     //     threaded_calls.clone(),
@@ -1178,20 +2331,6 @@ pub fn mempool_node_routes(
     //     api_keys.clone(),
     //     cache.clone(),
     // ))
-    // .or(pause_nodes(
-    //     dp,
-    //     threaded_calls.clone(),
-    //     routes_pow_info.clone(),
-    //     api_keys.clone(),
-    //     cache.clone(),
-    // ))
-    // .or(resume_nodes(
-    //     dp,
-    //     threaded_calls.clone(),
-    //     routes_pow_info.clone(),
-    //     api_keys.clone(),
-    //     cache.clone(),
-    // ))
     // .or(update_shared_config(
     //     dp,
     //     threaded_calls.clone(),
@@ -1206,6 +2345,31 @@ pub fn mempool_node_routes(
     //     api_keys.clone(),
     //     cache.clone(),
     // ))
+    .or(metrics(
+        dp,
+        node.clone(),
+        MetricsSource::Mempool {
+            threaded_calls: threaded_calls.clone(),
+            raft_info: raft_info.clone(),
+        },
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(node_info(
+        dp,
+        node.clone(),
+        Some(raft_info),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(pool_mining(
+        dp,
+        pool_mining_handle,
+        threaded_calls,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
     .or(debug_data(
         dp_vec,
         node,
@@ -1216,7 +2380,7 @@ pub fn mempool_node_routes(
         cache,
     ));
 
-    routes.recover(handle_rejection)
+    routes.recover(handle_rejection).with(node_routes_cors())
 }
 
 // API routes for Miner nodes
@@ -1224,6 +2388,7 @@ pub fn miner_node_routes(
     api_keys: ApiKeys,
     routes_pow_info: RoutesPoWInfo,
     current_block: CurrentBlockWithMutex,
+    miner_stats_handle: MinerStatsHandle,
     db: WalletDb,
     node: Node,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
@@ -1244,7 +2409,6 @@ pub fn miner_node_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
     ))
     .or(import_keypairs(
         dp,
@@ -1252,7 +2416,12 @@ pub fn miner_node_routes(
         node.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
+    ))
+    .or(delete_keypair(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
     ))
     .or(payment_address(
         dp,
@@ -1262,6 +2431,18 @@ pub fn miner_node_routes(
         cache.clone(),
     ))
     .or(change_passphrase(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(unlock_wallet(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(lock_wallet(
         dp,
         db,
         routes_pow_info.clone(),
@@ -1275,12 +2456,36 @@ pub fn miner_node_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(miner_stats(
+        dp,
+        miner_stats_handle.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     // .or(address_construction(
     //     dp,
     //     routes_pow_info.clone(),
     //     api_keys.clone(),
     //     cache.clone(),
     // ))
+    .or(metrics(
+        dp,
+        node.clone(),
+        MetricsSource::Miner {
+            miner_stats: miner_stats_handle,
+        },
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(node_info(
+        dp,
+        node.clone(),
+        None,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     .or(debug_data(
         dp_vec,
         node,
@@ -1291,17 +2496,20 @@ pub fn miner_node_routes(
         cache,
     ));
 
-    routes.recover(handle_rejection)
+    routes.recover(handle_rejection).with(node_routes_cors())
 }
 
 // API routes for Miner nodes with User node capabilities
 pub fn miner_node_with_user_routes(
     api_keys: ApiKeys,
     routes_pow_info: RoutesPoWInfo,
+    rate_limits: ApiRateLimits,
     current_block: CurrentBlockWithMutex,
+    miner_stats_handle: MinerStatsHandle,
     db: WalletDb, /* Shared WalletDb */
     miner_node: Node,
     threaded_calls: ThreadedCallSender<dyn UserApi>,
+    payment_events_sender: PaymentEventSender,
     user_node: Node, /* Additional User `Node` */
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     let mut dp_vec = DbgPaths::new();
@@ -1323,6 +2531,17 @@ pub fn miner_node_with_user_routes(
         threaded_calls.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
+        cache.clone(),
+    ))
+    .or(make_item_payment(
+        dp,
+        db.clone(),
+        user_node.clone(),
+        threaded_calls.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        RateLimiter::new(rate_limits.writes),
         cache.clone(),
     ))
     .or(get_outgoing_txs(
@@ -1332,6 +2551,20 @@ pub fn miner_node_with_user_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(pending_payments(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
+    .or(cancel_pending_payment(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     // .or(make_ip_payment(
     //     dp,
     //     db.clone(),
@@ -1352,7 +2585,6 @@ pub fn miner_node_with_user_routes(
         db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
     ))
     .or(import_keypairs(
         dp,
@@ -1360,7 +2592,12 @@ pub fn miner_node_with_user_routes(
         user_node.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
-        cache.clone(),
+    ))
+    .or(delete_keypair(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
     ))
     .or(update_running_total(
         dp,
@@ -1373,6 +2610,7 @@ pub fn miner_node_with_user_routes(
     .or(create_item_asset_user(
         dp,
         user_node.clone(),
+        db.clone(),
         routes_pow_info.clone(),
         api_keys.clone(),
         cache.clone(),
@@ -1385,6 +2623,18 @@ pub fn miner_node_with_user_routes(
         cache.clone(),
     ))
     .or(change_passphrase(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(unlock_wallet(
+        dp,
+        db.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(lock_wallet(
         dp,
         db,
         routes_pow_info.clone(),
@@ -1398,12 +2648,42 @@ pub fn miner_node_with_user_routes(
         api_keys.clone(),
         cache.clone(),
     ))
+    .or(miner_stats(
+        dp,
+        miner_stats_handle.clone(),
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     // .or(address_construction(
     //     dp,
     //     routes_pow_info.clone(),
     //     api_keys.clone(),
     //     cache.clone(),
     // ))
+    .or(payment_events(
+        dp,
+        payment_events_sender,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(metrics(
+        dp,
+        miner_node.clone(),
+        MetricsSource::Miner {
+            miner_stats: miner_stats_handle,
+        },
+        routes_pow_info.clone(),
+        api_keys.clone(),
+    ))
+    .or(node_info(
+        dp,
+        miner_node.clone(),
+        None,
+        routes_pow_info.clone(),
+        api_keys.clone(),
+        cache.clone(),
+    ))
     .or(debug_data(
         dp_vec,
         miner_node,
@@ -1414,5 +2694,5 @@ pub fn miner_node_with_user_routes(
         cache,
     ));
 
-    routes.recover(handle_rejection)
+    routes.recover(handle_rejection).with(node_routes_cors())
 }
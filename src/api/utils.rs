@@ -1,14 +1,17 @@
 use super::{
-    errors::{ApiError, ApiErrorType, OverloadedError},
+    errors::{ApiError, ApiErrorType, OverloadedError, RateLimitedError},
     handlers::DbgPaths,
-    responses::{common_error_reply, json_serialize_embed, CallResponse, JsonReply},
+    responses::{common_error_reply, json_error_content, CallResponse, JsonReply},
 };
+use crate::configurations::{ApiRateLimitSpec, TlsPrivateInfo};
 use crate::utils::{ApiKeys, RoutesPoWInfo, StringError};
 use futures::Future;
 use moka::future::{Cache, CacheBuilder};
+use std::collections::HashMap;
 use std::convert::Infallible;
-use std::sync::Arc;
-use std::time::Duration;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 use tracing::{log::error, warn};
 use warp::{
@@ -17,6 +20,36 @@ use warp::{
     Filter, Rejection, Reply,
 };
 
+// Load the PEM certificate and private key from files to serve the API routes over TLS
+pub fn load_api_tls_files(cert_path: &str, key_path: &str) -> std::io::Result<TlsPrivateInfo> {
+    Ok(TlsPrivateInfo {
+        pem_certs: std::fs::read_to_string(cert_path)?,
+        pem_pkcs8_private_keys: std::fs::read_to_string(key_path)?,
+    })
+}
+
+// Serve the API routes over TLS when a certificate is given, falling back to plain HTTP
+pub async fn serve_api_routes<F>(
+    routes: F,
+    bind_address: SocketAddr,
+    api_tls: Option<TlsPrivateInfo>,
+) where
+    F: Filter<Error = Rejection> + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    let serve = warp::serve(routes);
+    if let Some(api_tls) = api_tls {
+        serve
+            .tls()
+            .key(&api_tls.pem_pkcs8_private_keys)
+            .cert(&api_tls.pem_certs)
+            .run(bind_address)
+            .await;
+    } else {
+        serve.run(bind_address).await;
+    }
+}
+
 // Clone component/struct to use in route
 pub fn with_node_component<T: Clone + Send>(
     comp: T,
@@ -44,6 +77,15 @@ pub fn map_to_string_err<T: ToString>(e: T) -> StringError {
     StringError(e.to_string())
 }
 
+// Map API response from Result<JsonReply, JsonReply> to Result<warp::Reply, warp::Rejection>
+// Responses are not cached, for routes whose reply depends on secrets in the request body
+pub fn map_api_res(
+    r: impl Future<Output = Result<JsonReply, JsonReply>>,
+) -> impl Future<Output = Result<impl warp::Reply, warp::Rejection>> {
+    use futures::future::TryFutureExt;
+    r.map_ok_or_else(Ok, Ok)
+}
+
 // Map API response from Result<JsonReply, JsonReply> to Result<warp::Reply, warp::Rejection>
 //Adds responses to a cache
 pub fn map_api_res_and_cache(
@@ -134,37 +176,42 @@ pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
 
     if err.is_not_found() {
         // Method not found
-        error.code = StatusCode::NOT_FOUND;
         error.message = ApiErrorType::MethodNotFound;
     } else if err
         .find::<warp::filters::body::BodyDeserializeError>()
         .is_some()
     {
         // Failure to deserialize request body
-        error.code = StatusCode::BAD_REQUEST;
         error.message = ApiErrorType::BadRequest;
     } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
         // Method not allowed
-        error.code = StatusCode::METHOD_NOT_ALLOWED;
         error.message = ApiErrorType::MethodNotAllowed;
+    } else if err.find::<OverloadedError>().is_some() {
+        // Too many requests in progress
+        error.message = ApiErrorType::Overloaded;
+    } else if let Some(err) = err.find::<RateLimitedError>() {
+        // Too many requests from the client
+        error.message = ApiErrorType::RateLimited;
+        error.code = error.message.status();
+        return Ok(api_error_reply(error).with_retry_after(err.retry_after_secs));
     } else if let Some(err) = err.find::<ApiError>().cloned() {
         // Custom errors
-        error = err;
+        return Ok(api_error_reply(err));
     } else {
         // This should not happen! All errors should be handled
         error!("Unhandled API rejection: {:?}", err);
-        error.code = StatusCode::INTERNAL_SERVER_ERROR;
         error.message =
             ApiErrorType::Generic(format!("Unhandled rejection. Internal Server Error"));
     }
+    error.code = error.message.status();
+
+    Ok(api_error_reply(error))
+}
 
-    Ok(common_error_reply(
-        error.code,
-        error.message,
-        &error.id,
-        &error.route,
-        json_serialize_embed("null"),
-    ))
+/// Reply for an error rejecting a request
+fn api_error_reply(error: ApiError) -> JsonReply {
+    let data = json_error_content(error.code, &error.message);
+    common_error_reply(error.code, error.message, &error.id, &error.route, data)
 }
 
 //Cache data type, live time and maximum size
@@ -232,3 +279,104 @@ pub fn with_semaphore(
         }
     })
 }
+
+/// Tokens left to a client of a rate limited route
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Buckets of the clients of rate limited routes
+#[derive(Debug)]
+struct RateLimiterState {
+    buckets: HashMap<Option<IpAddr>, TokenBucket>,
+    evicted_at: Instant,
+}
+
+/// Token bucket rate limiter keyed by client IP, owned by a single route
+/// so exhausting one route does not throttle the others.
+/// Limits nothing if created without spec.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    spec: Option<ApiRateLimitSpec>,
+    state: Arc<Mutex<RateLimiterState>>,
+}
+
+impl RateLimiter {
+    pub fn new(spec: Option<ApiRateLimitSpec>) -> Self {
+        Self {
+            spec,
+            state: Arc::new(Mutex::new(RateLimiterState {
+                buckets: Default::default(),
+                evicted_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Take a token from the client bucket, holding up to `burst` tokens and
+    /// refilled with `max_requests` tokens per window.
+    /// Returns the time until a token is available if the bucket is empty.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ip` - IP of the client, if known
+    pub fn try_acquire(&self, ip: Option<IpAddr>) -> Result<(), Duration> {
+        let spec = match &self.spec {
+            Some(spec) => spec,
+            None => return Ok(()),
+        };
+        let capacity = spec.burst.max(1) as f64;
+        let window = Duration::from_millis(spec.window_ms).as_secs_f64();
+        let rate = (spec.max_requests as f64 / window.max(f64::EPSILON)).max(f64::EPSILON);
+        let refill_time = Duration::from_secs_f64((capacity / rate).min(u32::MAX as f64));
+        let now = Instant::now();
+
+        let mut state = self.state.lock().unwrap();
+        // Periodically forget the clients whose bucket is full again
+        if now.duration_since(state.evicted_at) >= refill_time {
+            state
+                .buckets
+                .retain(|_, bucket| now.duration_since(bucket.refilled_at) < refill_time);
+            state.evicted_at = now;
+        }
+
+        let bucket = state.buckets.entry(ip).or_insert(TokenBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            let wait = (1.0 - bucket.tokens) / rate;
+            return Err(Duration::from_secs_f64(wait.min(u32::MAX as f64)));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Filter to limit the rate of requests from each client IP.
+///
+/// # Arguments
+///
+/// * `rate_limiter` - Rate limiter of the route it protects.
+pub fn with_rate_limit(
+    rate_limiter: RateLimiter,
+) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    warp::addr::remote()
+        .and_then(move |addr: Option<SocketAddr>| {
+            let rate_limiter = rate_limiter.clone();
+            async move {
+                rate_limiter
+                    .try_acquire(addr.map(|addr| addr.ip()))
+                    .map_err(|wait| {
+                        let retry_after_secs = wait.as_secs_f64().ceil().max(1.0) as u64;
+                        warp::reject::custom(RateLimitedError { retry_after_secs })
+                    })
+            }
+        })
+        .untuple_one()
+}
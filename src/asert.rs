@@ -1,6 +1,7 @@
 use {
     crate::constants::{ASERT_HALF_LIFE, ASERT_TARGET_HASHES_PER_BLOCK},
     rug::{integer::ParseIntegerError, Integer},
+    serde::{Deserialize, Serialize},
     std::{
         cmp::Ordering,
         fmt,
@@ -286,7 +287,7 @@ impl Sub for Timestamp {
 /// Bitcoin calls this representation `nBits` in block headers.
 ///
 /// This encoding originates from OpenSSL.
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct CompactTarget(u32);
 
 impl CompactTarget {
@@ -442,6 +443,17 @@ impl Target {
         CompactTarget::MAX.expand()
     }
 
+    /// Returns the target met by hashes starting with `leading_zeroes` zero bytes.
+    pub fn from_leading_zeroes(leading_zeroes: usize) -> Self {
+        let bits = 256u32.saturating_sub(8 * leading_zeroes as u32);
+        Self((Integer::from(1) << bits) - 1u32)
+    }
+
+    /// Returns `self` multiplied by `numerator / denominator`.
+    pub fn scaled(&self, numerator: u64, denominator: u64) -> Self {
+        Self(self.0.clone() * numerator / denominator)
+    }
+
     /// Returns the compacted form of `self`.
     ///
     /// Note that this is usually lossy as `Target` represents
@@ -0,0 +1,220 @@
+//! App to drive a reproducible transaction load against a running network.
+
+use aiblock_network::configurations::LoadGenConfig;
+use aiblock_network::load_gen::{LoadGenerator, LoadStage, LoadTarget, StageObservation};
+use async_trait::async_trait;
+use clap::{App, Arg};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tracing::info;
+use tw_chain::primitives::transaction::Transaction;
+use tw_chain::script::StackEntry;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let matches = clap_app().get_matches();
+    let config = load_settings(&matches);
+    let mut target = HttpLoadTarget {
+        client: reqwest::Client::new(),
+        mempool_api: matches.value_of("mempool_api").unwrap().to_owned(),
+        storage_api: matches.value_of("storage_api").unwrap().to_owned(),
+        api_key: matches.value_of("api_key").map(|k| k.to_owned()),
+    };
+
+    info!("Load generation plan seed: {}", config.seed);
+    let mut generator = LoadGenerator::new(config);
+    let report = generator.run(&mut target).await;
+    let report = serde_json::to_string_pretty(&report).unwrap();
+
+    match matches.value_of("output") {
+        Some(path) => std::fs::write(path, report).unwrap(),
+        None => println!("{report}"),
+    }
+}
+
+/// Drives a network through the public mempool and storage APIs.
+///
+/// Only acceptance and storage are observable this way: intermediate stages
+/// are reported as inferred.
+struct HttpLoadTarget {
+    client: reqwest::Client,
+    mempool_api: String,
+    storage_api: String,
+    api_key: Option<String>,
+}
+
+impl HttpLoadTarget {
+    async fn post(&self, base: &str, route: &str, body: Value) -> Result<Value, String> {
+        let mut request = self
+            .client
+            .post(format!("{}/{}", base.trim_end_matches('/'), route))
+            .json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+
+        let response: Value = request
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response["status"] == "Success" {
+            Ok(response["content"].clone())
+        } else {
+            Err(response["reason"].as_str().unwrap_or_default().to_owned())
+        }
+    }
+}
+
+#[async_trait]
+impl LoadTarget for HttpLoadTarget {
+    async fn submit(&mut self, transactions: Vec<Transaction>) -> Result<(), String> {
+        let body = Value::Array(transactions.iter().map(to_create_transaction).collect());
+        self.post(&self.mempool_api, "create_transactions", body)
+            .await
+            .map(|_| ())
+    }
+
+    async fn observe(
+        &mut self,
+        tx_hashes: Vec<String>,
+    ) -> Result<BTreeMap<String, StageObservation>, String> {
+        let status = self
+            .post(&self.mempool_api, "transaction_status", json!(tx_hashes))
+            .await?;
+
+        let mut observations = BTreeMap::new();
+        for tx_hash in tx_hashes {
+            let stored = self
+                .post(
+                    &self.storage_api,
+                    "check_transaction_presence",
+                    json!([tx_hash]),
+                )
+                .await?;
+
+            let observation = match (&status[&tx_hash]["status"], stored.as_array()) {
+                (_, Some(blocks)) if !blocks.is_empty() => {
+                    StageObservation::Reached(LoadStage::Stored)
+                }
+                (s, _) if *s == "Rejected" => StageObservation::Rejected(
+                    status[&tx_hash]["additional_info"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_owned(),
+                ),
+                (s, _) if *s == "Confirmed" => StageObservation::Reached(LoadStage::Accepted),
+                _ => StageObservation::Unknown,
+            };
+            observations.insert(tx_hash, observation);
+        }
+
+        Ok(observations)
+    }
+}
+
+/// JSON body accepted by the `create_transactions` route
+fn to_create_transaction(tx: &Transaction) -> Value {
+    let inputs: Vec<Value> = tx
+        .inputs
+        .iter()
+        .map(|tx_in| {
+            let stack = &tx_in.script_signature.stack;
+            let signable_data = stack.iter().find_map(|e| match e {
+                StackEntry::Bytes(v) => Some(v.clone()),
+                _ => None,
+            });
+            let signature = stack.iter().find_map(|e| match e {
+                StackEntry::Signature(v) => Some(hex::encode(v.as_ref())),
+                _ => None,
+            });
+            let public_key = stack.iter().find_map(|e| match e {
+                StackEntry::PubKey(v) => Some(hex::encode(v.as_ref())),
+                _ => None,
+            });
+
+            json!({
+                "previous_out": tx_in.previous_out,
+                "script_signature": {
+                    "Pay2PkH": {
+                        "signable_data": signable_data,
+                        "signature": signature,
+                        "public_key": public_key,
+                        "address_version": null,
+                    }
+                }
+            })
+        })
+        .collect();
+
+    json!({
+        "inputs": inputs,
+        "outputs": tx.outputs,
+        "version": tx.version,
+        "fees": tx.fees,
+        "druid_info": tx.druid_info,
+    })
+}
+
+fn clap_app<'a, 'b>() -> App<'a, 'b> {
+    App::new("load_gen")
+        .about("Drives a reproducible transaction load against a running network.")
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .short("c")
+                .env("CONFIG")
+                .help("Use a different load generation config file.")
+                .value_name("FILE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("mempool_api")
+                .long("mempool_api")
+                .env("MEMPOOL_API")
+                .help("Base URL of the mempool node API.")
+                .default_value("http://127.0.0.1:3003")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("storage_api")
+                .long("storage_api")
+                .env("STORAGE_API")
+                .help("Base URL of the storage node API.")
+                .default_value("http://127.0.0.1:3001")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("api_key")
+                .long("api_key")
+                .env("API_KEY")
+                .help("API key sent with each request.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .short("o")
+                .help("Write the JSON report to this file instead of stdout.")
+                .value_name("FILE")
+                .takes_value(true),
+        )
+}
+
+fn load_settings(matches: &clap::ArgMatches) -> LoadGenConfig {
+    let mut settings = config::Config::default();
+    let setting_file = matches
+        .value_of("config")
+        .unwrap_or("src/bin/load_gen_settings.toml");
+
+    settings
+        .merge(config::File::with_name(setting_file))
+        .unwrap();
+
+    settings.try_into().unwrap()
+}
@@ -3,8 +3,8 @@
 use aiblock_network::configurations::MempoolNodeConfig;
 use aiblock_network::MempoolNode;
 use aiblock_network::{
-    get_sanction_addresses, loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, routes,
-    shutdown_connections, ResponseResult, SANC_LIST_PROD,
+    exit_on_shutdown_signal, get_sanction_addresses, loop_wait_connnect_to_peers_async,
+    loops_re_connect_disconnect, routes, serve_api_routes, ResponseResult, SANC_LIST_PROD,
 };
 use clap::{App, Arg, ArgMatches};
 use config::ConfigError;
@@ -26,6 +26,10 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     let (node_conn, addrs_to_connect, expected_connected_addrs) = node.connect_info_peers();
     let local_event_tx = node.local_event_tx().clone();
     let threaded_calls_tx = node.threaded_call_tx().clone();
+    let raft_info = node.raft_info();
+    let pool_mining = node.pool_mining();
+
+    tokio::spawn(exit_on_shutdown_signal(local_event_tx.clone()));
 
     // PERMANENT CONNEXION/DISCONNECTION HANDLING
     let ((conn_loop_handle, stop_re_connect_tx), (disconn_loop_handle, stop_disconnect_tx)) = {
@@ -54,7 +58,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
 
     // Warp API
     let warp_handle = tokio::spawn({
-        let (api_addr, api_tls, api_keys, routes_pow, peer) = api_inputs;
+        let (api_addr, api_tls, api_keys, routes_pow, rate_limit, peer) = api_inputs;
         let threaded_calls_tx = threaded_calls_tx;
 
         info!("Warp API started on port {:?}", api_addr.port());
@@ -64,29 +68,26 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
         bind_address.set_port(api_addr.port());
 
         async move {
-            let serve = warp::serve(routes::mempool_node_routes(
-                api_keys,
-                routes_pow,
-                threaded_calls_tx,
-                peer,
-            ));
-            if let Some(api_tls) = api_tls {
-                serve
-                    .tls()
-                    .key(&api_tls.pem_pkcs8_private_keys)
-                    .cert(&api_tls.pem_certs)
-                    .run(bind_address)
-                    .await;
-            } else {
-                serve.run(bind_address).await;
-            }
+            serve_api_routes(
+                routes::mempool_node_routes(
+                    api_keys,
+                    routes_pow,
+                    rate_limit,
+                    threaded_calls_tx,
+                    peer,
+                    raft_info,
+                    pool_mining,
+                ),
+                bind_address,
+                api_tls,
+            )
+            .await;
         }
     });
 
     // REQUEST HANDLING
     let main_loop_handle = tokio::spawn({
         let mut node = node;
-        let mut node_conn = node_conn;
 
         async move {
             node.send_startup_requests().await.unwrap();
@@ -100,8 +101,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             stop_re_connect_tx.send(()).unwrap();
             stop_disconnect_tx.send(()).unwrap();
 
-            node.close_raft_loop().await;
-            shutdown_connections(&mut node_conn).await;
+            node.shutdown().await;
         }
     });
 
@@ -252,6 +252,22 @@ fn load_settings(matches: &clap::ArgMatches) -> config::Config {
     settings
         .set_default("mempool_transaction_timeout", 100)
         .unwrap();
+    settings
+        .set_default("mempool_proposal_timeout", 5000)
+        .unwrap();
+    settings
+        .set_default("mempool_local_tx_max_age", 600000)
+        .unwrap();
+    settings
+        .set_default("mempool_block_stored_info_timeout", 30000)
+        .unwrap();
+    settings.set_default("mempool_pow_batch_window", 5).unwrap();
+    settings
+        .set_default("mempool_storage_resend_interval", 2000)
+        .unwrap();
+    settings
+        .set_default("mempool_druid_pool_expiry_blocks", 10)
+        .unwrap();
     settings
         .set_default("mempool_mining_event_timeout", 500)
         .unwrap();
@@ -2,8 +2,8 @@
 
 use aiblock_network::configurations::{ExtraNodeParams, MinerNodeConfig, UserNodeConfig};
 use aiblock_network::{
-    loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, routes, shutdown_connections,
-    ResponseResult,
+    exit_on_shutdown_signal, loop_wait_connnect_to_peers_async, loops_re_connect_disconnect,
+    routes, serve_api_routes, ResponseResult,
 };
 use aiblock_network::{MinerNode, UserNode};
 use clap::{App, Arg, ArgMatches};
@@ -19,10 +19,13 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     info!("Started node at {}", node.local_address());
 
     let miner_api_inputs = node.api_inputs();
+    let miner_stats = node.miner_stats();
     let shared_wallet_db = Some(node.get_wallet_db().clone());
     let (node_conn, addrs_to_connect, expected_connected_addrs) = node.connect_info_peers();
     let local_event_tx = node.local_event_tx().clone();
 
+    tokio::spawn(exit_on_shutdown_signal(local_event_tx.clone()));
+
     // PERMANENT CONNEXION/DISCONNECTION HANDLING
     let ((conn_loop_handle, stop_re_connect_tx), (disconn_loop_handle, stop_disconnect_tx)) = {
         let (re_connect, disconnect_test) =
@@ -40,7 +43,6 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     // Miner main loop
     let main_loop_handle = tokio::spawn({
         let mut node = node;
-        let mut node_conn = node_conn;
 
         async move {
             node.send_startup_requests().await.unwrap();
@@ -54,7 +56,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             stop_re_connect_tx.send(()).unwrap();
             stop_disconnect_tx.send(()).unwrap();
 
-            shutdown_connections(&mut node_conn).await;
+            node.shutdown().await;
         }
     });
 
@@ -68,6 +70,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             info!("Start user node with config {config:?}");
             let user_node = UserNode::new(config, shared_members).await.unwrap();
             let api_inputs = (user_node.api_inputs(), miner_api_inputs);
+            let payment_events = user_node.payment_events();
             info!("Started user node at {}", user_node.local_address());
 
             let (user_node_conn, user_addrs_to_connect, user_expected_connected_addrs) =
@@ -75,6 +78,8 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             let user_local_event_tx = user_node.local_event_tx().clone();
             let threaded_calls_tx = user_node.threaded_call_tx().clone();
 
+            tokio::spawn(exit_on_shutdown_signal(user_local_event_tx.clone()));
+
             // PERMANENT CONNEXION/DISCONNECTION HANDLING
             let (
                 (user_conn_loop_handle, user_stop_re_connect_tx),
@@ -102,7 +107,6 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             // User main loop
             let user_main_loop_handle = tokio::spawn({
                 let mut node = user_node;
-                let mut node_conn = user_node_conn;
 
                 async move {
                     node.send_startup_requests().await.unwrap();
@@ -116,7 +120,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
                     user_stop_re_connect_tx.send(()).unwrap();
                     user_stop_disconnect_tx.send(()).unwrap();
 
-                    shutdown_connections(&mut node_conn).await;
+                    node.shutdown().await;
                 }
             });
 
@@ -124,7 +128,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             let warp_handle = tokio::spawn({
                 let threaded_calls_tx = threaded_calls_tx;
                 let (
-                    (db, user_node, api_addr, api_tls, api_keys, api_pow_info),
+                    (db, user_node, api_addr, api_tls, api_keys, api_pow_info, rate_limit),
                     (_, miner_node, _, _, _, current_block, _),
                 ) = api_inputs;
 
@@ -135,25 +139,23 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
                 bind_address.set_port(api_addr.port());
 
                 async move {
-                    let serve = warp::serve(routes::miner_node_with_user_routes(
-                        api_keys,
-                        api_pow_info,
-                        current_block,
-                        db,
-                        miner_node,
-                        threaded_calls_tx,
-                        user_node,
-                    ));
-                    if let Some(api_tls) = api_tls {
-                        serve
-                            .tls()
-                            .key(&api_tls.pem_pkcs8_private_keys)
-                            .cert(&api_tls.pem_certs)
-                            .run(bind_address)
-                            .await;
-                    } else {
-                        serve.run(bind_address).await;
-                    }
+                    serve_api_routes(
+                        routes::miner_node_with_user_routes(
+                            api_keys,
+                            api_pow_info,
+                            rate_limit,
+                            current_block,
+                            miner_stats,
+                            db,
+                            miner_node,
+                            threaded_calls_tx,
+                            payment_events,
+                            user_node,
+                        ),
+                        bind_address,
+                        api_tls,
+                    )
+                    .await;
                 }
             });
 
@@ -188,23 +190,19 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
                 bind_address.set_port(api_addr.port());
 
                 async move {
-                    let serve = warp::serve(routes::miner_node_routes(
-                        api_keys,
-                        api_pow_info,
-                        current_block,
-                        db,
-                        miner_node,
-                    ));
-                    if let Some(api_tls) = api_tls {
-                        serve
-                            .tls()
-                            .key(&api_tls.pem_pkcs8_private_keys)
-                            .cert(&api_tls.pem_certs)
-                            .run(bind_address)
-                            .await;
-                    } else {
-                        serve.run(bind_address).await;
-                    }
+                    serve_api_routes(
+                        routes::miner_node_routes(
+                            api_keys,
+                            api_pow_info,
+                            current_block,
+                            miner_stats,
+                            db,
+                            miner_node,
+                        ),
+                        bind_address,
+                        api_tls,
+                    )
+                    .await;
                 }
             });
 
@@ -283,6 +281,34 @@ pub fn clap_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Limit the amount of addresses that can be kept before aggregation is triggered")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("address_aggregation_max_inputs")
+                .long("address_aggregation_max_inputs")
+                .env("ADDRESS_AGGREGATION_MAX_INPUTS")
+                .help("Maximum number of outputs merged by one aggregation transaction")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("enable_address_aggregation")
+                .long("enable_address_aggregation")
+                .env("ENABLE_ADDRESS_AGGREGATION")
+                .help("Whether to aggregate the spendable outputs: 0 to disable")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("outage_mining_secs")
+                .long("outage_mining_secs")
+                .env("OUTAGE_MINING_SECS")
+                .help("Seconds to keep mining the current block while the mempool node is unreachable")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("pow_threads")
+                .long("pow_threads")
+                .env("POW_THREADS")
+                .help("Number of threads searching block PoW nonces")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("index")
                 .short("i")
@@ -511,6 +537,26 @@ fn load_settings(matches: &clap::ArgMatches) -> (config::Config, Option<config::
             .unwrap();
     }
 
+    if let Some(max_inputs) = matches.value_of("address_aggregation_max_inputs") {
+        settings
+            .set("address_aggregation_max_inputs", max_inputs)
+            .unwrap();
+    }
+
+    if let Some(enable) = matches.value_of("enable_address_aggregation") {
+        settings.set("enable_address_aggregation", enable).unwrap();
+    }
+
+    if let Some(outage_mining_secs) = matches.value_of("outage_mining_secs") {
+        settings
+            .set("outage_mining_secs", outage_mining_secs)
+            .unwrap();
+    }
+
+    if let Some(pow_threads) = matches.value_of("pow_threads") {
+        settings.set("pow_threads", pow_threads).unwrap();
+    }
+
     if let Some(certificate) = matches.value_of("tls_certificate_override") {
         let mut tls_config = settings.get_table("tls_config").unwrap();
         tls_config.insert(
@@ -3,8 +3,8 @@
 use aiblock_network::configurations::PreLaunchNodeConfig;
 use aiblock_network::PreLaunchNode;
 use aiblock_network::{
-    loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, shutdown_connections,
-    ResponseResult,
+    exit_on_shutdown_signal, loop_wait_connnect_to_peers_async, loops_re_connect_disconnect,
+    shutdown_connections, ResponseResult,
 };
 use clap::{App, Arg, ArgMatches};
 use config::ConfigError;
@@ -23,6 +23,8 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     let (node_conn, addrs_to_connect, expected_connected_addrs) = node.connect_info_peers();
     let local_event_tx = node.local_event_tx().clone();
 
+    tokio::spawn(exit_on_shutdown_signal(local_event_tx.clone()));
+
     // PERMANENT CONNEXION/DISCONNECTION HANDLING
     let ((conn_loop_handle, stop_re_connect_tx), (disconn_loop_handle, stop_disconnect_tx)) = {
         let (re_connect, disconnect_test) =
@@ -3,8 +3,8 @@
 use aiblock_network::configurations::StorageNodeConfig;
 use aiblock_network::StorageNode;
 use aiblock_network::{
-    loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, routes, shutdown_connections,
-    ResponseResult,
+    exit_on_shutdown_signal, loop_wait_connnect_to_peers_async, loops_re_connect_disconnect,
+    routes, serve_api_routes, ResponseResult,
 };
 use clap::{App, Arg, ArgMatches};
 use config::ConfigError;
@@ -21,9 +21,16 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
 
     let (node_conn, addrs_to_connect, expected_connected_addrs) = node.connect_info_peers();
     let api_inputs = node.api_inputs();
+    let utxo_subscriptions = node.utxo_subscriptions();
+    let block_stream = node.block_stream();
+    let chain_quality = node.chain_quality();
+    let stored_supply = node.stored_supply();
+    let raft_info = node.raft_info();
 
     let local_event_tx = node.local_event_tx().clone();
 
+    tokio::spawn(exit_on_shutdown_signal(local_event_tx.clone()));
+
     // PERMANENT CONNEXION/DISCONNECTION HANDLING
     let ((conn_loop_handle, stop_re_connect_tx), (disconn_loop_handle, stop_disconnect_tx)) = {
         let (re_connect, disconnect_test) =
@@ -50,7 +57,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
 
     // Warp API
     let warp_handle = tokio::spawn({
-        let (db, api_addr, api_tls, api_keys, api_pow_info) = api_inputs;
+        let (db, api_addr, api_tls, api_keys, api_pow_info, rate_limit) = api_inputs;
 
         info!("Warp API started on port {:?}", api_addr.port());
         info!("");
@@ -60,29 +67,29 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
         let node_conn_debug = node_conn.clone();
 
         async move {
-            let serve = warp::serve(routes::storage_node_routes(
-                api_keys,
-                api_pow_info,
-                db,
-                node_conn_debug,
-            ));
-            if let Some(api_tls) = api_tls {
-                serve
-                    .tls()
-                    .key(&api_tls.pem_pkcs8_private_keys)
-                    .cert(&api_tls.pem_certs)
-                    .run(bind_address)
-                    .await;
-            } else {
-                serve.run(bind_address).await;
-            }
+            serve_api_routes(
+                routes::storage_node_routes(
+                    api_keys,
+                    api_pow_info,
+                    rate_limit,
+                    db,
+                    utxo_subscriptions,
+                    block_stream,
+                    chain_quality,
+                    stored_supply,
+                    node_conn_debug,
+                    raft_info,
+                ),
+                bind_address,
+                api_tls,
+            )
+            .await;
         }
     });
 
     // REQUEST HANDLING
     let main_loop_handle = tokio::spawn({
         let mut node = node;
-        let mut node_conn = node_conn.clone();
 
         async move {
             node.send_startup_requests().await.unwrap();
@@ -96,8 +103,7 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             stop_re_connect_tx.send(()).unwrap();
             stop_disconnect_tx.send(()).unwrap();
 
-            node.close_raft_loop().await;
-            shutdown_connections(&mut node_conn).await;
+            node.shutdown().await;
         }
     });
 
@@ -3,8 +3,8 @@
 use aiblock_network::configurations::UserNodeConfig;
 use aiblock_network::interfaces::{UserApiRequest, UserRequest, UtxoFetchType};
 use aiblock_network::{
-    loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, routes, shutdown_connections,
-    ResponseResult, UserNode,
+    exit_on_shutdown_signal, loop_wait_connnect_to_peers_async, loops_re_connect_disconnect,
+    routes, serve_api_routes, ResponseResult, UserNode,
 };
 use clap::{App, Arg, ArgMatches};
 use config::{ConfigError, Value};
@@ -40,9 +40,12 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     let local_event_tx = node.local_event_tx().clone();
     let threaded_calls_tx = node.threaded_call_tx().clone();
     let api_inputs = node.api_inputs();
+    let payment_events = node.payment_events();
     let peer_node = node.get_node().clone();
     let wallet_db = node.get_wallet_db().clone();
 
+    tokio::spawn(exit_on_shutdown_signal(local_event_tx.clone()));
+
     // PERMANENT CONNEXION/DISCONNECTION HANDLING
     let ((conn_loop_handle, stop_re_connect_tx), (disconn_loop_handle, stop_disconnect_tx)) = {
         let (re_connect, disconnect_test) =
@@ -60,7 +63,6 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
     // REQUEST HANDLING
     let main_loop_handle = tokio::spawn({
         let mut node = node;
-        let mut node_conn = node_conn;
 
         async move {
             node.send_startup_requests().await.unwrap();
@@ -73,13 +75,13 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
             }
             stop_re_connect_tx.send(()).unwrap();
             stop_disconnect_tx.send(()).unwrap();
-            shutdown_connections(&mut node_conn).await;
+            node.shutdown().await;
         }
     });
 
     // Warp API
     let warp_handle = tokio::spawn({
-        let (db, node, api_addr, api_tls, api_keys, api_pow_info) = api_inputs;
+        let (db, node, api_addr, api_tls, api_keys, api_pow_info, rate_limit) = api_inputs;
         let threaded_calls_tx = threaded_calls_tx.clone();
 
         info!("Warp API started on port {:?}", api_addr.port());
@@ -89,23 +91,20 @@ pub async fn run_node(matches: &ArgMatches<'_>) {
         bind_address.set_port(api_addr.port());
 
         async move {
-            let serve = warp::serve(routes::user_node_routes(
-                api_keys,
-                api_pow_info,
-                db,
-                node,
-                threaded_calls_tx,
-            ));
-            if let Some(api_tls) = api_tls {
-                serve
-                    .tls()
-                    .key(&api_tls.pem_pkcs8_private_keys)
-                    .cert(&api_tls.pem_certs)
-                    .run(bind_address)
-                    .await;
-            } else {
-                serve.run(bind_address).await;
-            }
+            serve_api_routes(
+                routes::user_node_routes(
+                    api_keys,
+                    api_pow_info,
+                    rate_limit,
+                    db,
+                    node,
+                    threaded_calls_tx,
+                    payment_events,
+                ),
+                bind_address,
+                api_tls,
+            )
+            .await;
         }
     });
 
@@ -39,6 +39,9 @@ pub enum MiningPipelineItem {
     WinningPoW(SocketAddr, Box<WinningPoWInfo>),
     CompleteMining,
     ResetPipeline,
+    /// PoW found by a pool miner of the proposing mempool node, given with its address.
+    /// Kept last so persisted RAFT log entries keep their variant tags.
+    PoolWinningPoW(SocketAddr, Box<WinningPoWInfo>),
 }
 
 /// Participants collection (unsorted: given order, and lookup collection)
@@ -370,6 +373,9 @@ impl MiningPipelineInfo {
             (WinningPoW(addr, info), AllItemsIntake) => {
                 self.add_to_winning_pow(extra.proposer_id, (addr, *info));
             }
+            (PoolWinningPoW(addr, info), AllItemsIntake) => {
+                self.add_to_pool_winning_pow(extra.proposer_id, (addr, *info));
+            }
             (CompleteMining, AllItemsIntake) => {
                 self.append_current_phase_timeout(extra.proposer_id);
             }
@@ -539,6 +545,20 @@ impl MiningPipelineInfo {
         self.all_winning_pow.push(winning_pow);
     }
 
+    /// Add a PoW found by a pool miner to the winning PoW list.
+    /// Pool miners are not participants: the proposing mempool node vouches for them.
+    pub fn add_to_pool_winning_pow(
+        &mut self,
+        proposer_id: u64,
+        winning_pow: (SocketAddr, WinningPoWInfo),
+    ) {
+        debug!(
+            "Adding PoW entry from pool: {}-{:?}",
+            proposer_id, winning_pow.0
+        );
+        self.all_winning_pow.push(winning_pow);
+    }
+
     /// Selects a winning miner from the list via UNICORN
     pub fn has_ready_select_participating_miners(&mut self, sufficient_majority: usize) -> bool {
         self.current_phase_timeout_peer_ids.len() >= sufficient_majority
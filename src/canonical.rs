@@ -0,0 +1,1349 @@
+//! Canonical encoding of values whose hash peers have to agree on.
+//!
+//! Bincode output follows the Rust definition of a type, so reordering fields or
+//! variants silently changes every hash computed over it. The encoders here write
+//! each field explicitly, in a fixed order, behind a format version byte:
+//!
+//! - integers are fixed width big endian, `usize` is written as `u64`
+//!   and signed integers as their two's complement
+//! - byte strings and strings are prefixed with their `u64` length
+//! - sequences and maps are prefixed with their `u64` item count
+//! - options are written as a `bool` followed by the value if present
+//! - enum variants are written as a `u8` tag followed by their fields
+//! - script op codes are written as their serde JSON text, socket addresses as text
+//!
+//! Values owned by the chain library (`Block`, `Transaction`) are written the same
+//! way: their encoders destructure every field, so a chain upgrade adding one fails
+//! to build rather than leaving it out of the hash.
+//! Changing anything in this file changes consensus hashes: bump
+//! `CANONICAL_FORMAT_VERSION` and update the golden vectors in the tests.
+
+use crate::block_pipeline::{MiningPipelineItem, MiningPipelineStatus};
+use crate::difficulty_adjustment::DifficultyAdjustmentConfig;
+use crate::interfaces::{
+    BlockStoredInfo, CommonBlockInfo, InitialIssuance, MinedBlockExtraInfo, WinningPoWInfo,
+};
+use crate::mempool_raft::{
+    AccumulatingBlockStoredInfo, AssemblyRuleset, CoordinatedCommand, MempoolRaftItem,
+    MempoolRuntimeItem,
+};
+use crate::storage_raft::{ReceivedBlock, StorageRaftItem};
+use crate::unicorn::Unicorn;
+use rug::integer::Order;
+use rug::Integer;
+use std::collections::BTreeMap;
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+use std::net::SocketAddr;
+use tw_chain::crypto::sha3_256;
+use tw_chain::crypto::sign_ed25519::{PublicKey, Signature};
+use tw_chain::primitives::asset::{Asset, ItemAsset, TokenAmount};
+use tw_chain::primitives::block::{Block, BlockHeader};
+use tw_chain::primitives::druid::{DdeValues, DruidExpectation};
+use tw_chain::primitives::transaction::{OutPoint, Transaction, TxIn, TxOut};
+use tw_chain::script::{lang::Script, OpCodes, StackEntry};
+
+/// Version byte leading every canonical encoding
+pub const CANONICAL_FORMAT_VERSION: u8 = 1;
+
+/// Result wrapper for canonical decoding errors
+pub type Result<T> = std::result::Result<T, CanonicalError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanonicalError {
+    UnexpectedEnd,
+    TrailingBytes(usize),
+    UnknownVersion(u8),
+    UnknownVariant(u8),
+    InvalidUtf8,
+    LengthOverflow(u64),
+    ChainValue(String),
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "Unexpected end of canonical bytes"),
+            Self::TrailingBytes(len) => write!(f, "{len} trailing bytes after canonical value"),
+            Self::UnknownVersion(v) => write!(f, "Unknown canonical format version: {v}"),
+            Self::UnknownVariant(tag) => write!(f, "Unknown canonical variant tag: {tag}"),
+            Self::InvalidUtf8 => write!(f, "Invalid UTF-8 in canonical string"),
+            Self::LengthOverflow(len) => write!(f, "Canonical length too large: {len}"),
+            Self::ChainValue(err) => write!(f, "Invalid chain value: {err}"),
+        }
+    }
+}
+
+impl Error for CanonicalError {}
+
+/// Value with a canonical encoding
+pub trait CanonicalEncode {
+    /// Write the fields of this value in canonical order
+    fn encode(&self, writer: &mut CanonicalWriter);
+}
+
+/// Value that can be read back from its canonical encoding
+pub trait CanonicalDecode: Sized {
+    /// Read the fields of this value in canonical order
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self>;
+}
+
+/// Canonical bytes of the given value, prefixed with the format version
+///
+/// ### Arguments
+///
+/// * `value` - Value to encode
+pub fn to_canonical_bytes<T: CanonicalEncode + ?Sized>(value: &T) -> Vec<u8> {
+    let mut writer = CanonicalWriter::default();
+    writer.write_u8(CANONICAL_FORMAT_VERSION);
+    value.encode(&mut writer);
+    writer.bytes
+}
+
+/// Decode a value from bytes produced by `to_canonical_bytes`
+///
+/// ### Arguments
+///
+/// * `bytes` - Canonical bytes including the format version
+pub fn from_canonical_bytes<T: CanonicalDecode>(bytes: &[u8]) -> Result<T> {
+    let mut reader = CanonicalReader { bytes };
+    match reader.read_u8()? {
+        CANONICAL_FORMAT_VERSION => {}
+        version => return Err(CanonicalError::UnknownVersion(version)),
+    }
+
+    let value = T::decode(&mut reader)?;
+    match reader.bytes.len() {
+        0 => Ok(value),
+        len => Err(CanonicalError::TrailingBytes(len)),
+    }
+}
+
+/// SHA3-256 hash of the canonical bytes of the given value
+///
+/// ### Arguments
+///
+/// * `value` - Value to hash
+pub fn canonical_hash<T: CanonicalEncode + ?Sized>(value: &T) -> Vec<u8> {
+    sha3_256::digest(&to_canonical_bytes(value)).to_vec()
+}
+
+/// Accumulates canonical bytes
+#[derive(Default, Debug)]
+pub struct CanonicalWriter {
+    bytes: Vec<u8>,
+}
+
+impl CanonicalWriter {
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    pub fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.write_usize(value.len());
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    /// Write an integer as a sign byte followed by its big endian magnitude
+    pub fn write_integer(&mut self, value: &Integer) {
+        self.write_bool(*value < 0);
+        self.write_bytes(&value.to_digits::<u8>(Order::MsfBe));
+    }
+
+    pub fn write_option<T: CanonicalEncode>(&mut self, value: &Option<T>) {
+        self.write_bool(value.is_some());
+        if let Some(value) = value {
+            value.encode(self);
+        }
+    }
+
+    pub fn write_seq<T: CanonicalEncode>(&mut self, values: &[T]) {
+        self.write_usize(values.len());
+        for value in values {
+            value.encode(self);
+        }
+    }
+
+    pub fn write_tx_map(&mut self, txs: &BTreeMap<String, Transaction>) {
+        self.write_usize(txs.len());
+        for (hash, tx) in txs {
+            self.write_str(hash);
+            tx.encode(self);
+        }
+    }
+}
+
+/// Reads canonical bytes in the order they were written
+#[derive(Debug)]
+pub struct CanonicalReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> CanonicalReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(CanonicalError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(CanonicalError::UnknownVariant(tag)),
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_usize(&mut self) -> Result<usize> {
+        let value = self.read_u64()?;
+        usize::try_from(value).map_err(|_| CanonicalError::LengthOverflow(value))
+    }
+
+    /// Read a length prefix that cannot exceed the remaining bytes
+    fn read_len(&mut self) -> Result<usize> {
+        let len = self.read_usize()?;
+        if len > self.bytes.len() {
+            return Err(CanonicalError::LengthOverflow(len as u64));
+        }
+        Ok(len)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.read_len()?;
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub fn read_string(&mut self) -> Result<String> {
+        String::from_utf8(self.read_bytes()?).map_err(|_| CanonicalError::InvalidUtf8)
+    }
+
+    pub fn read_integer(&mut self) -> Result<Integer> {
+        let negative = self.read_bool()?;
+        let magnitude = Integer::from_digits(&self.read_bytes()?, Order::MsfBe);
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    pub fn read_option<T: CanonicalDecode>(&mut self) -> Result<Option<T>> {
+        match self.read_bool()? {
+            true => Ok(Some(T::decode(self)?)),
+            false => Ok(None),
+        }
+    }
+
+    pub fn read_seq<T: CanonicalDecode>(&mut self) -> Result<Vec<T>> {
+        let len = self.read_len()?;
+        (0..len).map(|_| T::decode(self)).collect()
+    }
+
+    pub fn read_tx_map(&mut self) -> Result<BTreeMap<String, Transaction>> {
+        let len = self.read_len()?;
+        (0..len)
+            .map(|_| Ok((self.read_string()?, Transaction::decode(self)?)))
+            .collect()
+    }
+}
+
+impl<T: CanonicalEncode + ?Sized> CanonicalEncode for &T {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        (**self).encode(writer)
+    }
+}
+
+impl CanonicalEncode for str {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_str(self);
+    }
+}
+
+impl CanonicalEncode for String {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_str(self);
+    }
+}
+
+impl CanonicalDecode for String {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        reader.read_string()
+    }
+}
+
+impl<A: CanonicalEncode, B: CanonicalEncode> CanonicalEncode for (A, B) {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        self.0.encode(writer);
+        self.1.encode(writer);
+    }
+}
+
+impl<A: CanonicalDecode, B: CanonicalDecode> CanonicalDecode for (A, B) {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok((A::decode(reader)?, B::decode(reader)?))
+    }
+}
+
+impl CanonicalEncode for BlockStoredInfo {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_str(&self.block_hash);
+        writer.write_u64(self.block_num);
+        writer.write_bytes(&self.nonce);
+        writer.write_tx_map(&self.mining_transactions);
+        writer.write_bool(self.shutdown);
+    }
+}
+
+impl CanonicalDecode for BlockStoredInfo {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            block_hash: reader.read_string()?,
+            block_num: reader.read_u64()?,
+            nonce: reader.read_bytes()?,
+            mining_transactions: reader.read_tx_map()?,
+            shutdown: reader.read_bool()?,
+        })
+    }
+}
+
+impl CanonicalEncode for AccumulatingBlockStoredInfo {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::FirstBlock(utxo_set) => {
+                writer.write_u8(0);
+                writer.write_tx_map(utxo_set);
+            }
+            Self::Block(info) => {
+                writer.write_u8(1);
+                info.encode(writer);
+            }
+        }
+    }
+}
+
+impl CanonicalDecode for AccumulatingBlockStoredInfo {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        match reader.read_u8()? {
+            0 => Ok(Self::FirstBlock(reader.read_tx_map()?)),
+            1 => Ok(Self::Block(BlockStoredInfo::decode(reader)?)),
+            tag => Err(CanonicalError::UnknownVariant(tag)),
+        }
+    }
+}
+
+impl CanonicalEncode for Unicorn {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_u64(self.iterations);
+        writer.write_u32(self.security_level);
+        writer.write_integer(&self.seed);
+        writer.write_integer(&self.modulus);
+    }
+}
+
+impl CanonicalDecode for Unicorn {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            iterations: reader.read_u64()?,
+            security_level: reader.read_u32()?,
+            seed: reader.read_integer()?,
+            modulus: reader.read_integer()?,
+        })
+    }
+}
+
+impl CanonicalEncode for CommonBlockInfo {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        self.block.encode(writer);
+        writer.write_tx_map(&self.block_txs);
+        writer.write_u8(self.pow_p_value);
+        writer.write_u8(self.pow_d_value);
+        self.unicorn.encode(writer);
+        writer.write_integer(&self.unicorn_witness);
+    }
+}
+
+impl CanonicalDecode for CommonBlockInfo {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            block: Block::decode(reader)?,
+            block_txs: reader.read_tx_map()?,
+            pow_p_value: reader.read_u8()?,
+            pow_d_value: reader.read_u8()?,
+            unicorn: Unicorn::decode(reader)?,
+            unicorn_witness: reader.read_integer()?,
+        })
+    }
+}
+
+impl CanonicalEncode for InitialIssuance {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_u64(self.amount.0);
+        writer.write_str(&self.address);
+        writer.write_u64(self.block_height);
+    }
+}
+
+impl CanonicalDecode for InitialIssuance {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            amount: TokenAmount(reader.read_u64()?),
+            address: reader.read_string()?,
+            block_height: reader.read_u64()?,
+        })
+    }
+}
+
+impl CanonicalEncode for AssemblyRuleset {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_u32(self.version);
+        writer.write_usize(self.block_size_in_tx);
+        writer.write_usize(self.partition_full_size);
+        writer.write_u64(self.coinbase_maturity);
+        writer.write_u64(self.activation_height_asert);
+        writer.write_seq(&self.initial_issuances);
+        writer.write_option(&self.difficulty_adjustment);
+    }
+}
+
+impl CanonicalDecode for AssemblyRuleset {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            version: reader.read_u32()?,
+            block_size_in_tx: reader.read_usize()?,
+            partition_full_size: reader.read_usize()?,
+            coinbase_maturity: reader.read_u64()?,
+            activation_height_asert: reader.read_u64()?,
+            initial_issuances: reader.read_seq()?,
+            difficulty_adjustment: reader.read_option()?,
+        })
+    }
+}
+
+impl CanonicalEncode for DifficultyAdjustmentConfig {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_u64(self.target_block_interval_secs);
+        writer.write_u64(self.period_blocks);
+    }
+}
+
+impl CanonicalDecode for DifficultyAdjustmentConfig {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            target_block_interval_secs: reader.read_u64()?,
+            period_blocks: reader.read_u64()?,
+        })
+    }
+}
+
+impl CanonicalEncode for Block {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let Block {
+            header,
+            transactions,
+        } = self;
+        header.encode(writer);
+        writer.write_seq(transactions);
+    }
+}
+
+impl CanonicalDecode for Block {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            header: BlockHeader::decode(reader)?,
+            transactions: reader.read_seq()?,
+        })
+    }
+}
+
+impl CanonicalEncode for BlockHeader {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let BlockHeader {
+            version,
+            bits,
+            nonce_and_mining_tx_hash,
+            b_num,
+            timestamp,
+            difficulty,
+            seed_value,
+            previous_hash,
+            txs_merkle_root_and_hash,
+        } = self;
+        writer.write_u32(*version);
+        writer.write_usize(*bits);
+        writer.write_bytes(&nonce_and_mining_tx_hash.0);
+        writer.write_str(&nonce_and_mining_tx_hash.1);
+        writer.write_u64(*b_num);
+        writer.write_u64(*timestamp as u64);
+        writer.write_bytes(difficulty);
+        writer.write_bytes(seed_value);
+        writer.write_option(previous_hash);
+        writer.write_str(&txs_merkle_root_and_hash.0);
+        writer.write_str(&txs_merkle_root_and_hash.1);
+    }
+}
+
+impl CanonicalDecode for BlockHeader {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            version: reader.read_u32()?,
+            bits: reader.read_usize()?,
+            nonce_and_mining_tx_hash: (reader.read_bytes()?, reader.read_string()?),
+            b_num: reader.read_u64()?,
+            timestamp: reader.read_u64()? as i64,
+            difficulty: reader.read_bytes()?,
+            seed_value: reader.read_bytes()?,
+            previous_hash: reader.read_option()?,
+            txs_merkle_root_and_hash: (reader.read_string()?, reader.read_string()?),
+        })
+    }
+}
+
+impl CanonicalEncode for Transaction {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let Transaction {
+            inputs,
+            outputs,
+            version,
+            fees,
+            druid_info,
+        } = self;
+        writer.write_seq(inputs);
+        writer.write_seq(outputs);
+        writer.write_usize(*version);
+        writer.write_seq(fees);
+        writer.write_option(druid_info);
+    }
+}
+
+impl CanonicalDecode for Transaction {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            inputs: reader.read_seq()?,
+            outputs: reader.read_seq()?,
+            version: reader.read_usize()?,
+            fees: reader.read_seq()?,
+            druid_info: reader.read_option()?,
+        })
+    }
+}
+
+impl CanonicalEncode for TxIn {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let TxIn {
+            previous_out,
+            script_signature: Script { stack },
+        } = self;
+        writer.write_option(previous_out);
+        writer.write_seq(stack);
+    }
+}
+
+impl CanonicalDecode for TxIn {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            previous_out: reader.read_option()?,
+            script_signature: Script {
+                stack: reader.read_seq()?,
+            },
+        })
+    }
+}
+
+impl CanonicalEncode for OutPoint {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let OutPoint { t_hash, n } = self;
+        writer.write_str(t_hash);
+        writer.write_u32(*n as u32);
+    }
+}
+
+impl CanonicalDecode for OutPoint {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            t_hash: reader.read_string()?,
+            n: reader.read_u32()? as i32,
+        })
+    }
+}
+
+impl CanonicalEncode for StackEntry {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::Op(op) => {
+                writer.write_u8(0);
+                writer.write_str(&op_code_name(op));
+            }
+            Self::Signature(signature) => {
+                writer.write_u8(1);
+                writer.write_bytes(signature.as_ref());
+            }
+            Self::PubKey(public_key) => {
+                writer.write_u8(2);
+                writer.write_bytes(public_key.as_ref());
+            }
+            Self::Num(num) => {
+                writer.write_u8(3);
+                writer.write_usize(*num);
+            }
+            Self::Bytes(bytes) => {
+                writer.write_u8(4);
+                writer.write_str(bytes);
+            }
+        }
+    }
+}
+
+impl CanonicalDecode for StackEntry {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        let invalid = |what: &str| CanonicalError::ChainValue(format!("Invalid {what}"));
+        match reader.read_u8()? {
+            0 => Ok(Self::Op(op_code_from_name(reader.read_string()?)?)),
+            1 => Signature::from_slice(&reader.read_bytes()?)
+                .map(Self::Signature)
+                .ok_or_else(|| invalid("signature")),
+            2 => PublicKey::from_slice(&reader.read_bytes()?)
+                .map(Self::PubKey)
+                .ok_or_else(|| invalid("public key")),
+            3 => Ok(Self::Num(reader.read_usize()?)),
+            4 => Ok(Self::Bytes(reader.read_string()?)),
+            tag => Err(CanonicalError::UnknownVariant(tag)),
+        }
+    }
+}
+
+/// Op code as the JSON text of its serde serialization
+fn op_code_name(op: &OpCodes) -> String {
+    serde_json::to_string(op).expect("op codes serialize to JSON")
+}
+
+/// Op code from the JSON text of its serde serialization
+fn op_code_from_name(name: String) -> Result<OpCodes> {
+    serde_json::from_str(&name).map_err(|e| CanonicalError::ChainValue(e.to_string()))
+}
+
+impl CanonicalEncode for TxOut {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let TxOut {
+            value,
+            locktime,
+            script_public_key,
+        } = self;
+        value.encode(writer);
+        writer.write_u64(*locktime);
+        writer.write_option(script_public_key);
+    }
+}
+
+impl CanonicalDecode for TxOut {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            value: Asset::decode(reader)?,
+            locktime: reader.read_u64()?,
+            script_public_key: reader.read_option()?,
+        })
+    }
+}
+
+impl CanonicalEncode for Asset {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::Token(amount) => {
+                writer.write_u8(0);
+                writer.write_u64(amount.0);
+            }
+            Self::Item(ItemAsset {
+                amount,
+                genesis_hash,
+                metadata,
+            }) => {
+                writer.write_u8(1);
+                writer.write_u64(*amount);
+                writer.write_option(genesis_hash);
+                writer.write_option(metadata);
+            }
+        }
+    }
+}
+
+impl CanonicalDecode for Asset {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        match reader.read_u8()? {
+            0 => Ok(Self::Token(TokenAmount(reader.read_u64()?))),
+            1 => Ok(Self::Item(ItemAsset {
+                amount: reader.read_u64()?,
+                genesis_hash: reader.read_option()?,
+                metadata: reader.read_option()?,
+            })),
+            tag => Err(CanonicalError::UnknownVariant(tag)),
+        }
+    }
+}
+
+impl CanonicalEncode for DdeValues {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let DdeValues {
+            druid,
+            genesis_hash,
+            participants,
+            expectations,
+        } = self;
+        writer.write_str(druid);
+        writer.write_option(genesis_hash);
+        writer.write_usize(*participants);
+        writer.write_seq(expectations);
+    }
+}
+
+impl CanonicalDecode for DdeValues {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            druid: reader.read_string()?,
+            genesis_hash: reader.read_option()?,
+            participants: reader.read_usize()?,
+            expectations: reader.read_seq()?,
+        })
+    }
+}
+
+impl CanonicalEncode for DruidExpectation {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        let DruidExpectation { from, to, asset } = self;
+        writer.write_str(from);
+        writer.write_str(to);
+        asset.encode(writer);
+    }
+}
+
+impl CanonicalDecode for DruidExpectation {
+    fn decode(reader: &mut CanonicalReader<'_>) -> Result<Self> {
+        Ok(Self {
+            from: reader.read_string()?,
+            to: reader.read_string()?,
+            asset: Asset::decode(reader)?,
+        })
+    }
+}
+
+impl CanonicalEncode for BTreeMap<String, Transaction> {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_tx_map(self);
+    }
+}
+
+impl CanonicalEncode for SocketAddr {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_str(&self.to_string());
+    }
+}
+
+impl CanonicalEncode for MempoolRaftItem {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::FirstBlock(utxo_set) => {
+                writer.write_u8(0);
+                writer.write_tx_map(utxo_set);
+            }
+            Self::Block(info) => {
+                writer.write_u8(1);
+                info.encode(writer);
+            }
+            Self::Transactions(txs) => {
+                writer.write_u8(2);
+                writer.write_tx_map(txs);
+            }
+            Self::DruidTransactions(droplets) => {
+                writer.write_u8(3);
+                writer.write_seq(droplets);
+            }
+            Self::PipelineItem(item, b_num) => {
+                writer.write_u8(4);
+                item.encode(writer);
+                writer.write_u64(*b_num);
+            }
+            Self::CoordinatedCmd(cmd) => {
+                writer.write_u8(5);
+                cmd.encode(writer);
+            }
+            Self::Timestamp(timestamp) => {
+                writer.write_u8(6);
+                writer.write_u64(*timestamp as u64);
+            }
+            Self::RuntimeData(item) => {
+                writer.write_u8(7);
+                item.encode(writer);
+            }
+            Self::Pause => writer.write_u8(8),
+            Self::Resume => writer.write_u8(9),
+            Self::FirstBlockSized(utxo_set, block_size_in_tx) => {
+                writer.write_u8(10);
+                writer.write_tx_map(utxo_set);
+                writer.write_usize(*block_size_in_tx);
+            }
+            Self::BlockWithRuleset(info, ruleset_hash) => {
+                writer.write_u8(11);
+                info.encode(writer);
+                writer.write_str(ruleset_hash);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for MiningPipelineItem {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::MiningParticipant(addr, status) => {
+                writer.write_u8(0);
+                addr.encode(writer);
+                status.encode(writer);
+            }
+            Self::CompleteParticipant => writer.write_u8(1),
+            Self::WinningPoW(addr, info) => {
+                writer.write_u8(2);
+                addr.encode(writer);
+                info.encode(writer);
+            }
+            Self::CompleteMining => writer.write_u8(3),
+            Self::ResetPipeline => writer.write_u8(4),
+            Self::PoolWinningPoW(addr, info) => {
+                writer.write_u8(5);
+                addr.encode(writer);
+                info.encode(writer);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for MiningPipelineStatus {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_u8(match self {
+            Self::Halted => 0,
+            Self::ParticipantOnlyIntake => 1,
+            Self::AllItemsIntake => 2,
+        });
+    }
+}
+
+impl CanonicalEncode for WinningPoWInfo {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_bytes(&self.nonce);
+        writer.write_str(&self.mining_tx.0);
+        self.mining_tx.1.encode(writer);
+        writer.write_u8(self.p_value);
+        writer.write_u8(self.d_value);
+    }
+}
+
+impl CanonicalEncode for CoordinatedCommand {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::PauseNodes { b_num } => {
+                writer.write_u8(0);
+                writer.write_u64(*b_num);
+            }
+            Self::ResumeNodes => writer.write_u8(1),
+            Self::ApplySharedConfig => writer.write_u8(2),
+        }
+    }
+}
+
+impl CanonicalEncode for MempoolRuntimeItem {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::AddMiningApiKeys(keys) => {
+                writer.write_u8(0);
+                writer.write_seq(keys);
+            }
+            Self::RemoveMiningApiKeys(addrs) => {
+                writer.write_u8(1);
+                writer.write_seq(addrs);
+            }
+            Self::AssemblyRuleset(ruleset) => {
+                writer.write_u8(2);
+                ruleset.encode(writer);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for StorageRaftItem {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        match self {
+            Self::PartBlock(block) => {
+                writer.write_u8(0);
+                block.encode(writer);
+            }
+        }
+    }
+}
+
+impl CanonicalEncode for ReceivedBlock {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        self.peer.encode(writer);
+        self.common.encode(writer);
+        self.per_node.encode(writer);
+    }
+}
+
+impl CanonicalEncode for MinedBlockExtraInfo {
+    fn encode(&self, writer: &mut CanonicalWriter) {
+        writer.write_bool(self.shutdown);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bincode::serialize;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use serde::Serialize;
+    use std::collections::HashMap;
+    use tw_chain::crypto::sign_ed25519 as sign;
+
+    const PROPERTY_TEST_CASES: usize = 2000;
+
+    fn golden_block_stored_info() -> BlockStoredInfo {
+        BlockStoredInfo {
+            block_hash: "b01".to_owned(),
+            block_num: 2,
+            nonce: vec![3, 4],
+            mining_transactions: BTreeMap::new(),
+            shutdown: true,
+        }
+    }
+
+    fn golden_assembly_ruleset() -> AssemblyRuleset {
+        AssemblyRuleset {
+            version: 1,
+            block_size_in_tx: 2,
+            partition_full_size: 3,
+            coinbase_maturity: 4,
+            activation_height_asert: 5,
+            initial_issuances: vec![InitialIssuance::new(TokenAmount(6), "a7".to_owned(), 8)],
+            difficulty_adjustment: Some(DifficultyAdjustmentConfig {
+                target_block_interval_secs: 9,
+                period_blocks: 10,
+            }),
+        }
+    }
+
+    fn assert_golden<T: CanonicalEncode>(value: &T, expected_bytes: &str, expected_hash: &str) {
+        assert_eq!(hex::encode(to_canonical_bytes(value)), expected_bytes);
+        assert_eq!(hex::encode(canonical_hash(value)), expected_hash);
+    }
+
+    #[test]
+    fn golden_vectors_block_stored_info() {
+        assert_golden(
+            &golden_block_stored_info(),
+            "010000000000000003623031000000000000000200000000000000020304000000000000000001",
+            "8119a9fd3af3cdde28632d2d88b6f4ff1c8418f8b00f1e716481e21caff91c2b",
+        );
+    }
+
+    #[test]
+    fn golden_vectors_accumulating_block_stored_info() {
+        assert_golden(
+            &AccumulatingBlockStoredInfo::Block(golden_block_stored_info()),
+            "01010000000000000003623031000000000000000200000000000000020304000000000000000001",
+            "5c666fc539d1da68d77ccd7f60976d39bcd023ff92e6974b12f631f8f09843d2",
+        );
+        assert_golden(
+            &AccumulatingBlockStoredInfo::FirstBlock(BTreeMap::new()),
+            "01000000000000000000",
+            "97d65126a708ca6175920164039b0dcbb476bfb851c2d277ae2b713289e9f468",
+        );
+        assert_golden(
+            &(
+                "ab12",
+                AccumulatingBlockStoredInfo::FirstBlock(BTreeMap::new()),
+            ),
+            "01000000000000000461623132000000000000000000",
+            "6a0812a87b814510bcf205d4d9088e56973a8c78715b47951dbfb8085772ae15",
+        );
+    }
+
+    #[test]
+    fn golden_vectors_assembly_ruleset() {
+        let ruleset = golden_assembly_ruleset();
+        assert_golden(
+            &ruleset,
+            "0100000001000000000000000200000000000000030000000000000004000000000000000500000000000000010000000000000006000000000000000261370000000000000008010000000000000009000000000000000a",
+            "9d99a05606ba60ba1a212b8dd3f657a80b3736d220faf3603509fd70fa535ce4",
+        );
+        assert_eq!(ruleset.short_hash(), "9d99a05606ba60ba");
+    }
+
+    fn golden_common_block_info() -> CommonBlockInfo {
+        let tx = Transaction {
+            inputs: vec![TxIn {
+                previous_out: Some(OutPoint::new("a".to_owned(), -1)),
+                script_signature: Script {
+                    stack: vec![StackEntry::Num(1), StackEntry::Bytes("ab".to_owned())],
+                },
+            }],
+            outputs: vec![TxOut {
+                value: Asset::Token(TokenAmount(5)),
+                locktime: 0,
+                script_public_key: Some("c".to_owned()),
+            }],
+            version: 2,
+            fees: Vec::new(),
+            druid_info: None,
+        };
+        let header = BlockHeader {
+            version: 2,
+            bits: 3,
+            nonce_and_mining_tx_hash: (vec![1], "m".to_owned()),
+            b_num: 4,
+            timestamp: -5,
+            difficulty: Vec::new(),
+            seed_value: vec![6],
+            previous_hash: Some("p".to_owned()),
+            txs_merkle_root_and_hash: ("r".to_owned(), "h".to_owned()),
+        };
+
+        CommonBlockInfo {
+            block: Block {
+                header,
+                transactions: vec!["t1".to_owned()],
+            },
+            block_txs: std::iter::once(("t1".to_owned(), tx)).collect(),
+            pow_p_value: 7,
+            pow_d_value: 8,
+            unicorn: Unicorn {
+                iterations: 9,
+                security_level: 10,
+                seed: Integer::from(11),
+                modulus: Integer::from(13),
+            },
+            unicorn_witness: Integer::from(-12),
+        }
+    }
+
+    #[test]
+    fn golden_vectors_common_block_info() {
+        assert_golden(
+            &golden_common_block_info(),
+            "0100000002000000000000000300000000000000010100000000000000016d0000000000000004fffffffffffffffb000000000000000000000000000000010601000000000000000170000000000000000172000000000000000168000000000000000100000000000000027431000000000000000100000000000000027431000000000000000101000000000000000161ffffffff0000000000000002030000000000000001040000000000000002616200000000000000010000000000000000050000000000000000010000000000000001630000000000000002000000000000000000070800000000000000090000000a0000000000000000010b0000000000000000010d0100000000000000010c",
+            "4ae9e8e3756a89e275dc4e590e964296212d1b77252f817febab34c4518494ce",
+        );
+    }
+
+    /// Copy of `BlockStoredInfo` with `nonce` moved before `block_num`
+    #[derive(Serialize)]
+    struct ReorderedBlockStoredInfo {
+        block_hash: String,
+        nonce: Vec<u8>,
+        block_num: u64,
+        mining_transactions: BTreeMap<String, Transaction>,
+        shutdown: bool,
+    }
+
+    impl CanonicalEncode for ReorderedBlockStoredInfo {
+        fn encode(&self, writer: &mut CanonicalWriter) {
+            writer.write_str(&self.block_hash);
+            writer.write_bytes(&self.nonce);
+            writer.write_u64(self.block_num);
+            writer.write_tx_map(&self.mining_transactions);
+            writer.write_bool(self.shutdown);
+        }
+    }
+
+    #[test]
+    fn golden_vectors_catch_reordered_field() {
+        //
+        // Arrange
+        //
+        let info = golden_block_stored_info();
+        let reordered = ReorderedBlockStoredInfo {
+            block_hash: info.block_hash.clone(),
+            nonce: info.nonce.clone(),
+            block_num: info.block_num,
+            mining_transactions: info.mining_transactions.clone(),
+            shutdown: info.shutdown,
+        };
+
+        //
+        // Act
+        //
+        let bincode_changed = serialize(&reordered).unwrap() != serialize(&info).unwrap();
+        let reordered_hash = hex::encode(canonical_hash(&reordered));
+
+        //
+        // Assert
+        //
+        assert!(bincode_changed);
+        assert_ne!(
+            reordered_hash,
+            "8119a9fd3af3cdde28632d2d88b6f4ff1c8418f8b00f1e716481e21caff91c2b"
+        );
+    }
+
+    #[test]
+    fn decode_rejects_malformed_bytes() {
+        let bytes = to_canonical_bytes(&golden_block_stored_info());
+        let decode = |bytes: &[u8]| from_canonical_bytes::<BlockStoredInfo>(bytes);
+
+        let mut unknown_version = bytes.clone();
+        unknown_version[0] = 2;
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+
+        assert_eq!(
+            decode(&unknown_version),
+            Err(CanonicalError::UnknownVersion(2))
+        );
+        assert_eq!(decode(&trailing), Err(CanonicalError::TrailingBytes(1)));
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]),
+            Err(CanonicalError::UnexpectedEnd)
+        );
+    }
+
+    fn gen_string(rng: &mut StdRng) -> String {
+        let len = rng.gen_range(0, 4);
+        (0..len).map(|_| ['a', 'b'][rng.gen_range(0, 2)]).collect()
+    }
+
+    fn gen_bytes(rng: &mut StdRng) -> Vec<u8> {
+        let len = rng.gen_range(0, 4);
+        (0..len).map(|_| rng.gen_range(0, 3)).collect()
+    }
+
+    fn gen_option<T>(rng: &mut StdRng, generate: impl Fn(&mut StdRng) -> T) -> Option<T> {
+        rng.gen::<bool>().then(|| generate(rng))
+    }
+
+    fn gen_asset(rng: &mut StdRng) -> Asset {
+        if rng.gen() {
+            Asset::Token(TokenAmount(rng.gen_range(0, 3)))
+        } else {
+            Asset::Item(ItemAsset {
+                amount: rng.gen_range(0, 3),
+                genesis_hash: gen_option(rng, gen_string),
+                metadata: gen_option(rng, gen_string),
+            })
+        }
+    }
+
+    fn gen_stack_entry(rng: &mut StdRng) -> StackEntry {
+        match rng.gen_range(0, 5) {
+            0 => StackEntry::Op(OpCodes::OP_DUP),
+            1 => {
+                let (_, sk) = sign::gen_keypair();
+                StackEntry::Signature(sign::sign_detached(&gen_bytes(rng), &sk))
+            }
+            2 => StackEntry::PubKey(sign::gen_keypair().0),
+            3 => StackEntry::Num(rng.gen_range(0, 3)),
+            _ => StackEntry::Bytes(gen_string(rng)),
+        }
+    }
+
+    fn gen_tx_out(rng: &mut StdRng) -> TxOut {
+        TxOut {
+            value: gen_asset(rng),
+            locktime: rng.gen_range(0, 3),
+            script_public_key: gen_option(rng, gen_string),
+        }
+    }
+
+    fn gen_transaction(rng: &mut StdRng) -> Transaction {
+        let inputs_len = rng.gen_range(0, 3);
+        let outputs_len = rng.gen_range(0, 3);
+        Transaction {
+            inputs: (0..inputs_len)
+                .map(|_| TxIn {
+                    previous_out: gen_option(rng, |rng| {
+                        OutPoint::new(gen_string(rng), rng.gen_range(-1, 2))
+                    }),
+                    script_signature: Script {
+                        stack: (0..rng.gen_range(0, 3))
+                            .map(|_| gen_stack_entry(rng))
+                            .collect(),
+                    },
+                })
+                .collect(),
+            outputs: (0..outputs_len).map(|_| gen_tx_out(rng)).collect(),
+            version: rng.gen_range(0, 3),
+            fees: gen_option(rng, gen_tx_out).into_iter().collect(),
+            druid_info: gen_option(rng, |rng| DdeValues {
+                druid: gen_string(rng),
+                genesis_hash: gen_option(rng, gen_string),
+                participants: rng.gen_range(0, 3),
+                expectations: gen_option(rng, |rng| DruidExpectation {
+                    from: gen_string(rng),
+                    to: gen_string(rng),
+                    asset: gen_asset(rng),
+                })
+                .into_iter()
+                .collect(),
+            }),
+        }
+    }
+
+    fn gen_tx_map(rng: &mut StdRng) -> BTreeMap<String, Transaction> {
+        let len = rng.gen_range(0, 3);
+        (0..len)
+            .map(|_| (gen_string(rng), gen_transaction(rng)))
+            .collect()
+    }
+
+    fn gen_block_stored_info(rng: &mut StdRng) -> BlockStoredInfo {
+        BlockStoredInfo {
+            block_hash: gen_string(rng),
+            block_num: rng.gen_range(0, 3),
+            nonce: gen_bytes(rng),
+            mining_transactions: gen_tx_map(rng),
+            shutdown: rng.gen(),
+        }
+    }
+
+    fn gen_accumulating_info(rng: &mut StdRng) -> AccumulatingBlockStoredInfo {
+        if rng.gen() {
+            AccumulatingBlockStoredInfo::FirstBlock(gen_tx_map(rng))
+        } else {
+            AccumulatingBlockStoredInfo::Block(gen_block_stored_info(rng))
+        }
+    }
+
+    fn gen_assembly_ruleset(rng: &mut StdRng) -> AssemblyRuleset {
+        let issuances_len = rng.gen_range(0, 3);
+        AssemblyRuleset {
+            version: rng.gen_range(0, 3),
+            block_size_in_tx: rng.gen_range(0, 3),
+            partition_full_size: rng.gen_range(0, 3),
+            coinbase_maturity: rng.gen_range(0, 3),
+            activation_height_asert: rng.gen(),
+            initial_issuances: (0..issuances_len)
+                .map(|_| {
+                    let amount = TokenAmount(rng.gen_range(0, 3));
+                    InitialIssuance::new(amount, gen_string(rng), rng.gen_range(0, 3))
+                })
+                .collect(),
+            difficulty_adjustment: rng.gen::<bool>().then(|| DifficultyAdjustmentConfig {
+                target_block_interval_secs: rng.gen_range(0, 3),
+                period_blocks: rng.gen_range(0, 3),
+            }),
+        }
+    }
+
+    fn gen_integer(rng: &mut StdRng) -> Integer {
+        let value = Integer::from(rng.gen::<u64>()) << rng.gen_range(0u32, 80);
+        if rng.gen() {
+            -value
+        } else {
+            value
+        }
+    }
+
+    fn gen_common_block_info(rng: &mut StdRng) -> CommonBlockInfo {
+        let mut block = Block::new();
+        block.header.b_num = rng.gen_range(0, 3);
+        block.header.timestamp = rng.gen_range(-1, 2);
+        block.header.previous_hash = gen_option(rng, gen_string);
+        block.transactions = (0..rng.gen_range(0, 3)).map(|_| gen_string(rng)).collect();
+        CommonBlockInfo {
+            block,
+            block_txs: gen_tx_map(rng),
+            pow_p_value: rng.gen_range(0, 3),
+            pow_d_value: rng.gen_range(0, 3),
+            unicorn: Unicorn {
+                iterations: rng.gen_range(0, 3),
+                security_level: rng.gen_range(0, 3),
+                seed: gen_integer(rng),
+                modulus: gen_integer(rng),
+            },
+            unicorn_witness: gen_integer(rng),
+        }
+    }
+
+    /// Check encoding round-trips and that distinct values never share bytes.
+    /// Values are drawn from small domains so distinct values often differ in one field.
+    fn check_round_trip_and_injective<T, K>(
+        seed: u64,
+        generate: impl Fn(&mut StdRng) -> T,
+        key: impl Fn(&T) -> K,
+    ) where
+        T: CanonicalEncode + CanonicalDecode,
+        K: Eq + fmt::Debug,
+    {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut encoded: HashMap<Vec<u8>, K> = HashMap::new();
+
+        for _ in 0..PROPERTY_TEST_CASES {
+            let value = generate(&mut rng);
+            let bytes = to_canonical_bytes(&value);
+
+            let decoded: T = from_canonical_bytes(&bytes).unwrap();
+            assert_eq!(key(&decoded), key(&value));
+
+            let value_key = key(&value);
+            if let Some(existing) = encoded.get(&bytes) {
+                assert_eq!(existing, &value_key);
+            }
+            encoded.insert(bytes, value_key);
+        }
+    }
+
+    #[test]
+    fn round_trip_and_injective_block_stored_info() {
+        check_round_trip_and_injective(1, gen_accumulating_info, |v| v.clone());
+        check_round_trip_and_injective(2, gen_block_stored_info, |v| v.clone());
+    }
+
+    #[test]
+    fn round_trip_and_injective_assembly_ruleset() {
+        check_round_trip_and_injective(3, gen_assembly_ruleset, |v| v.clone());
+    }
+
+    #[test]
+    fn round_trip_and_injective_transaction() {
+        check_round_trip_and_injective(5, gen_transaction, |v| v.clone());
+    }
+
+    #[test]
+    fn round_trip_and_injective_common_block_info() {
+        check_round_trip_and_injective(4, gen_common_block_info, |v| {
+            (
+                serialize(&v.block).unwrap(),
+                v.block_txs.clone(),
+                (v.pow_p_value, v.pow_d_value),
+                v.unicorn.clone(),
+                v.unicorn_witness.clone(),
+            )
+        });
+    }
+}
@@ -0,0 +1,243 @@
+use crate::constants::{
+    CHAIN_QUALITY_LATENCY_SAMPLES, CHAIN_QUALITY_MAX_REPORTED_GAPS,
+    CHAIN_QUALITY_REJECTION_WINDOW_SECS,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared chain quality tracking between the storage node and its API
+pub type ChainQualityHandle = Arc<Mutex<ChainQuality>>;
+
+/// Summary of the chain quality as seen by a storage node
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainQualityReport {
+    pub highest_stored_block: Option<u64>,
+    /// Inclusive ranges of block numbers missing below the highest stored block
+    pub missing_block_ranges: Vec<(u64, u64)>,
+    pub missing_block_count: u64,
+    pub orphan_count: u64,
+    /// Blocks rejected by validation within the rolling window, per reason
+    pub rejected_blocks: BTreeMap<String, usize>,
+    pub rejection_window_secs: u64,
+    pub store_latency_avg_micros: Option<u64>,
+    pub store_latency_p95_micros: Option<u64>,
+    pub time_since_last_block_millis: Option<u64>,
+}
+
+/// Incrementally maintained counters and indices describing the stored chain
+#[derive(Debug, Default)]
+pub struct ChainQuality {
+    /// Contiguous stored block ranges: first block -> last block (inclusive)
+    stored_ranges: BTreeMap<u64, u64>,
+    orphan_count: u64,
+    rejections: VecDeque<(Instant, String)>,
+    store_latencies: VecDeque<Duration>,
+    last_stored_at: Option<Instant>,
+}
+
+impl ChainQuality {
+    /// Create a new empty chain quality tracker shared with the API
+    pub fn new_handle() -> ChainQualityHandle {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Record the block numbers already present in the database on startup
+    ///
+    /// ### Arguments
+    ///
+    /// * `block_nums` - Block numbers found in the block number index
+    pub fn load_stored_blocks(&mut self, block_nums: impl Iterator<Item = u64>) {
+        for b_num in block_nums {
+            self.insert_stored_block_num(b_num);
+        }
+    }
+
+    /// Record a block being stored
+    ///
+    /// ### Arguments
+    ///
+    /// * `b_num`      - Block number stored
+    /// * `superseded` - Whether a different block was previously stored with that number
+    /// * `latency`    - Time taken to store the block
+    /// * `now`        - Time the block was stored
+    pub fn block_stored(&mut self, b_num: u64, superseded: bool, latency: Duration, now: Instant) {
+        self.insert_stored_block_num(b_num);
+        if superseded {
+            self.orphan_count += 1;
+        }
+
+        self.store_latencies.push_back(latency);
+        while self.store_latencies.len() > CHAIN_QUALITY_LATENCY_SAMPLES {
+            self.store_latencies.pop_front();
+        }
+        self.last_stored_at = Some(now);
+    }
+
+    /// Record a block rejected by validation
+    ///
+    /// ### Arguments
+    ///
+    /// * `reason` - Reason the block was rejected
+    /// * `now`    - Time the block was rejected
+    pub fn block_rejected(&mut self, reason: &str, now: Instant) {
+        self.rejections.push_back((now, reason.to_owned()));
+        self.expire_rejections(now);
+    }
+
+    /// Summarize the current chain quality
+    ///
+    /// ### Arguments
+    ///
+    /// * `now` - Time the report is generated
+    pub fn report(&mut self, now: Instant) -> ChainQualityReport {
+        self.expire_rejections(now);
+
+        let mut missing_block_ranges = Vec::new();
+        let mut missing_block_count = 0;
+        let mut next_expected = 0;
+        for (first, last) in &self.stored_ranges {
+            if *first > next_expected {
+                missing_block_count += first - next_expected;
+                if missing_block_ranges.len() < CHAIN_QUALITY_MAX_REPORTED_GAPS {
+                    missing_block_ranges.push((next_expected, first - 1));
+                }
+            }
+            next_expected = last + 1;
+        }
+
+        let mut rejected_blocks = BTreeMap::new();
+        for (_, reason) in &self.rejections {
+            *rejected_blocks.entry(reason.clone()).or_default() += 1;
+        }
+
+        let (store_latency_avg_micros, store_latency_p95_micros) = {
+            let mut latencies: Vec<_> = self.store_latencies.iter().copied().collect();
+            latencies.sort_unstable();
+            if latencies.is_empty() {
+                (None, None)
+            } else {
+                let total: Duration = latencies.iter().sum();
+                let avg = total / latencies.len() as u32;
+                let p95 = latencies[(latencies.len() * 95).div_ceil(100) - 1];
+                (Some(avg.as_micros() as u64), Some(p95.as_micros() as u64))
+            }
+        };
+
+        ChainQualityReport {
+            highest_stored_block: self.stored_ranges.values().next_back().copied(),
+            missing_block_ranges,
+            missing_block_count,
+            orphan_count: self.orphan_count,
+            rejected_blocks,
+            rejection_window_secs: CHAIN_QUALITY_REJECTION_WINDOW_SECS,
+            store_latency_avg_micros,
+            store_latency_p95_micros,
+            time_since_last_block_millis: self
+                .last_stored_at
+                .map(|at| now.saturating_duration_since(at).as_millis() as u64),
+        }
+    }
+
+    /// Add a block number to the stored ranges, merging with adjacent ranges
+    fn insert_stored_block_num(&mut self, b_num: u64) {
+        let before = self.stored_ranges.range(..=b_num).next_back();
+        let (first, mut last) = match before {
+            Some((_, last)) if *last >= b_num => return,
+            Some((first, last)) if *last + 1 == b_num => (*first, b_num),
+            _ => (b_num, b_num),
+        };
+
+        if let Some(next_first) = b_num.checked_add(1) {
+            if let Some(next_last) = self.stored_ranges.remove(&next_first) {
+                last = next_last;
+            }
+        }
+        self.stored_ranges.insert(first, last);
+    }
+
+    /// Drop rejections older than the rolling window
+    fn expire_rejections(&mut self, now: Instant) {
+        let window = Duration::from_secs(CHAIN_QUALITY_REJECTION_WINDOW_SECS);
+        while let Some((at, _)) = self.rejections.front() {
+            if now.saturating_duration_since(*at) <= window {
+                break;
+            }
+            self.rejections.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chain_quality_reports_gaps_and_orphans() {
+        //
+        // Arrange
+        //
+        let start = Instant::now();
+        let latency = Duration::from_millis(2);
+        let mut quality = ChainQuality::default();
+
+        //
+        // Act
+        //
+        for b_num in [0, 1, 2, 4, 5, 8] {
+            quality.block_stored(b_num, false, latency, start);
+        }
+        quality.block_stored(4, true, latency, start);
+        quality.block_rejected("PoW invalid", start);
+        let report = quality.report(start + Duration::from_secs(3));
+
+        //
+        // Assert
+        //
+        assert_eq!(report.highest_stored_block, Some(8));
+        assert_eq!(report.missing_block_ranges, vec![(3, 3), (6, 7)]);
+        assert_eq!(report.missing_block_count, 3);
+        assert_eq!(report.orphan_count, 1);
+        assert_eq!(
+            report.rejected_blocks,
+            std::iter::once(("PoW invalid".to_owned(), 1)).collect()
+        );
+        assert_eq!(report.store_latency_avg_micros, Some(2_000));
+        assert_eq!(report.store_latency_p95_micros, Some(2_000));
+        assert_eq!(report.time_since_last_block_millis, Some(3_000));
+    }
+
+    #[test]
+    fn chain_quality_fills_gaps_and_expires_rejections() {
+        //
+        // Arrange
+        //
+        let start = Instant::now();
+        let window = Duration::from_secs(CHAIN_QUALITY_REJECTION_WINDOW_SECS);
+        let mut quality = ChainQuality::default();
+        quality.load_stored_blocks(vec![0, 2, 4].into_iter());
+        quality.block_rejected("PoW invalid", start);
+
+        //
+        // Act
+        //
+        let report_gaps = quality.report(start);
+        quality.block_stored(3, false, Duration::from_millis(1), start);
+        quality.block_stored(1, false, Duration::from_millis(9), start);
+        let report_filled = quality.report(start + window + Duration::from_secs(1));
+
+        //
+        // Assert
+        //
+        assert_eq!(report_gaps.missing_block_ranges, vec![(1, 1), (3, 3)]);
+        assert_eq!(report_gaps.rejected_blocks.len(), 1);
+        assert_eq!(report_gaps.time_since_last_block_millis, None);
+        assert!(report_filled.missing_block_ranges.is_empty());
+        assert_eq!(report_filled.missing_block_count, 0);
+        assert_eq!(report_filled.highest_stored_block, Some(4));
+        assert!(report_filled.rejected_blocks.is_empty());
+        assert_eq!(report_filled.store_latency_avg_micros, Some(5_000));
+        assert_eq!(report_filled.store_latency_p95_micros, Some(9_000));
+    }
+}
@@ -28,6 +28,16 @@ pub enum CommsError {
     PeerDuplicate(PeerInfo),
     /// This peer is not compatible.
     PeerIncompatible(PeerInfo),
+    /// The peer list served by this peer failed verification.
+    PeerListInvalid(PeerInfo),
+    /// The frame is larger than the peer accepts.
+    FrameSizeLimitExceeded(PeerInfo),
+    /// The tracked peer is reconnecting and its queue of pending messages is full.
+    PeerDisconnected(PeerInfo),
+    /// The peer certificate is not the one pinned for it.
+    PeerCertificateMismatch(PeerInfo),
+    /// Only one side of the connection uses TLS.
+    TlsModeMismatch(PeerInfo),
     /// Serialization-related error.
     Serialization(bincode::Error),
     /// MPSC channel error.
@@ -55,6 +65,15 @@ impl fmt::Display for CommsError {
             Self::PeerDuplicate(info) => write!(f, "Duplicate peer: {info:?}"),
             Self::PeerInvalidState(info) => write!(f, "Peer has invalid state: {info:?}"),
             Self::PeerIncompatible(info) => write!(f, "Peer incompatible: {info:?}"),
+            Self::PeerListInvalid(info) => write!(f, "Peer list invalid: {info:?}"),
+            Self::FrameSizeLimitExceeded(info) => write!(f, "Frame size limit exceeded: {info:?}"),
+            Self::PeerDisconnected(info) => write!(f, "Peer disconnected: {info:?}"),
+            Self::PeerCertificateMismatch(info) => {
+                write!(f, "Peer certificate does not match pinned one: {info:?}")
+            }
+            Self::TlsModeMismatch(info) => {
+                write!(f, "TLS used by only one side of the connection: {info:?}")
+            }
             Self::Serialization(err) => write!(f, "Serialization error: {err}"),
             Self::ChannelSendError(err) => write!(f, "MPSC channel send error: {err}"),
             Self::WebpkiError(err) => write!(f, "Webpki error: {err}"),
@@ -75,6 +94,11 @@ impl Error for CommsError {
             Self::PeerInvalidState(_) => None,
             Self::PeerDuplicate(_) => None,
             Self::PeerIncompatible(_) => None,
+            Self::PeerListInvalid(_) => None,
+            Self::FrameSizeLimitExceeded(_) => None,
+            Self::PeerDisconnected(_) => None,
+            Self::PeerCertificateMismatch(_) => None,
+            Self::TlsModeMismatch(_) => None,
             Self::Serialization(err) => Some(err),
             Self::ChannelSendError(err) => Some(err),
             Self::WebpkiError(err) => Some(err),
@@ -2,6 +2,8 @@
 
 mod error;
 pub mod node;
+pub mod peer_discovery;
+mod reconnect;
 mod stream_cancel;
 pub mod tcp_tls;
 #[cfg(test)]
@@ -10,7 +12,8 @@ pub mod test_tls_certificates;
 mod tests;
 
 pub use error::CommsError;
-pub use node::Node;
+pub use node::{default_max_frame_size, split_into_frame_batches, Node};
+pub use reconnect::{ConnectRetryPolicy, ReconnectBackoff};
 pub use tcp_tls::{TcpTlsConfig, TcpTlsConnector, TcpTlsListner};
 
 use bytes::Bytes;
@@ -21,5 +24,20 @@ pub type Result<T> = std::result::Result<T, CommsError>;
 /// Events from peer.
 #[derive(Debug)]
 pub enum Event {
-    NewFrame { peer: SocketAddr, frame: Bytes },
+    NewFrame {
+        peer: SocketAddr,
+        frame: Bytes,
+    },
+    /// The peer sent a frame over our size limit and was disconnected.
+    FrameSizeLimitExceeded {
+        peer: SocketAddr,
+    },
+    /// A tracked peer was connected again after its connection was lost.
+    PeerReconnected {
+        peer: SocketAddr,
+    },
+    /// The first attempt to reconnect to a tracked peer failed; attempts continue with a backoff.
+    PeerUnreachable {
+        peer: SocketAddr,
+    },
 }
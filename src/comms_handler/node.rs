@@ -80,12 +80,22 @@
 //! [serde]: https://serde.rs
 //! [netbuffersize]: https://stackoverflow.com/a/7865130/168853
 
+use super::peer_discovery::{
+    announce_signable, unix_now_secs, NodeIdentity, PeerDiscovery, PeerRegistryEntry,
+    SignedPeerList,
+};
+use super::reconnect::{ConnectRetryPolicy, ReconnectBackoff, TrackedPeer};
 use super::tcp_tls::{
     verify_is_valid_for_dns_names, TcpTlsConnector, TcpTlsListner, TcpTlsStream, TlsCertificate,
 };
 use super::{CommsError, Event, Result, TcpTlsConfig};
 use crate::comms_handler::error::PeerInfo;
-use crate::constants::NETWORK_VERSION;
+use crate::constants::{
+    MEMPOOL_MAX_FRAME_SIZE, MINER_MAX_FRAME_SIZE, NETWORK_VERSION, PEER_CONNECT_MAX_ATTEMPTS,
+    PEER_CONNECT_MAX_DURATION_MS, PEER_RECONNECT_BASE_DELAY_MS, PEER_RECONNECT_MAX_DELAY_MS,
+    PEER_RECONNECT_QUEUE_DEPTH, PRE_LAUNCH_MAX_FRAME_SIZE, STORAGE_MAX_FRAME_SIZE,
+    USER_MAX_FRAME_SIZE,
+};
 use crate::interfaces::{node_type_as_str, CommMessage, NodeType, Token};
 use crate::utils::MpscTracingSender;
 use bincode::{deserialize, serialize};
@@ -96,18 +106,22 @@ use rand::prelude::*;
 use serde::Serialize;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::{fmt, io};
-use tokio::time::{interval, timeout, Duration};
+use tokio::time::{interval, timeout, Duration, Instant};
 use tokio::{
     self, spawn,
     sync::{mpsc, oneshot, Mutex, RwLock},
     task::JoinHandle,
 };
 use tokio_stream::{Stream, StreamExt};
-use tokio_util::codec::{length_delimited, FramedRead, FramedWrite, LengthDelimitedCodec};
+use tokio_util::codec::{
+    length_delimited, FramedRead, FramedWrite, LengthDelimitedCodec, LengthDelimitedCodecError,
+};
 use tracing::{debug, error, info, info_span, trace, warn, Span};
 use tracing_futures::Instrument;
+use tw_chain::crypto::sign_ed25519::{self as sign, PublicKey, Signature};
 
 extern crate serde_json;
 
@@ -124,6 +138,9 @@ const HEART_BEAT_INTERVAL: u64 = 3;
 
 const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5); // 5 seconds is just a wild guess. Tweak if necessary.
 
+/// Bytes reserved in a frame batch for the message envelope around the batched items.
+const FRAME_BATCH_OVERHEAD: usize = 1_024;
+
 /// Contains a shared list of connected peers.
 type PeerList = HashMap<SocketAddr, Peer>;
 
@@ -169,6 +186,18 @@ pub struct Node {
     connect_to_handshake_contacts: bool,
     /// Threadhandle for a HeartBeat Prober
     heartbeat_handle: Option<Arc<JoinHandle<()>>>,
+    /// Identity and registry of announced peers for peer discovery.
+    peer_discovery: Arc<RwLock<PeerDiscovery>>,
+    /// Peer list requests waiting for a response.
+    peer_list_requests: Arc<Mutex<HashMap<SocketAddr, oneshot::Sender<SignedPeerList>>>>,
+    /// Announce challenge requests waiting for the listener address and nonce to sign.
+    announce_challenges: Arc<Mutex<HashMap<SocketAddr, oneshot::Sender<(SocketAddr, u64)>>>>,
+    /// The max size of a frame accepted from peers, applied to new connections.
+    max_frame_size: Arc<AtomicUsize>,
+    /// Long-lived peers reconnected to when their connection is lost.
+    tracked_peers: Arc<Mutex<HashMap<SocketAddr, TrackedPeer>>>,
+    /// The max delay in milliseconds between reconnection attempts to tracked peers.
+    reconnect_max_delay_ms: Arc<AtomicU64>,
 }
 
 pub(crate) struct Peer {
@@ -188,6 +217,8 @@ pub(crate) struct Peer {
     /// Notification to trigger a task waiting for a handshake response.
     // TODO: move it to a separate state enum, manage state transitions in a better way
     notify_handshake_response: (Option<oneshot::Sender<()>>, Option<oneshot::Receiver<()>>),
+    /// Nonce issued to the peer for signing its identity announcement, used once.
+    announce_nonce: Option<u64>,
     /// Stop receiving when message sent or Sender dropped.
     close_receiver_tx: oneshot::Sender<()>,
     /// Joining handles for this connection tasks.
@@ -281,6 +312,12 @@ impl Node {
             seen_gossip_messages: Arc::new(RwLock::new(HashSet::new())),
             connect_to_handshake_contacts: false,
             heartbeat_handle: None,
+            peer_discovery: Default::default(),
+            peer_list_requests: Default::default(),
+            announce_challenges: Default::default(),
+            max_frame_size: Arc::new(AtomicUsize::new(default_max_frame_size(node_type))),
+            tracked_peers: Default::default(),
+            reconnect_max_delay_ms: Arc::new(AtomicU64::new(PEER_RECONNECT_MAX_DELAY_MS)),
         };
 
         if !disable_listening {
@@ -336,6 +373,93 @@ impl Node {
         self.connect_to_handshake_contacts = value;
     }
 
+    /// Sets the max size of a frame accepted from peers connecting from now on.
+    ///
+    /// ### Arguments
+    /// * `max_frame_size` - max frame size in bytes.
+    pub fn set_max_frame_size(&self, max_frame_size: usize) {
+        self.max_frame_size.store(max_frame_size, Ordering::SeqCst);
+    }
+
+    /// Returns the max size of a frame accepted from peers.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size.load(Ordering::SeqCst)
+    }
+
+    /// Sets the max delay between reconnection attempts to tracked peers.
+    ///
+    /// ### Arguments
+    /// * `max_delay` - max delay between two attempts.
+    pub fn set_reconnect_max_delay(&self, max_delay: Duration) {
+        let max_delay_ms = max_delay.as_millis().min(u64::MAX as u128) as u64;
+        self.reconnect_max_delay_ms
+            .store(max_delay_ms, Ordering::SeqCst);
+    }
+
+    /// Returns the delays between reconnection attempts to tracked peers.
+    pub fn reconnect_backoff(&self) -> ReconnectBackoff {
+        let max_delay_ms = self.reconnect_max_delay_ms.load(Ordering::SeqCst);
+        ReconnectBackoff {
+            base_delay: Duration::from_millis(PEER_RECONNECT_BASE_DELAY_MS.min(max_delay_ms)),
+            max_delay: Duration::from_millis(max_delay_ms),
+        }
+    }
+
+    /// Returns the bounds on the attempts to connect to a peer, spaced by the reconnection backoff.
+    pub fn connect_retry_policy(&self) -> ConnectRetryPolicy {
+        ConnectRetryPolicy {
+            backoff: self.reconnect_backoff(),
+            max_attempts: PEER_CONNECT_MAX_ATTEMPTS,
+            max_duration: Duration::from_millis(PEER_CONNECT_MAX_DURATION_MS),
+        }
+    }
+
+    /// Tracks long-lived peers: once their connection is lost, they are reconnected to
+    /// with a backoff, and messages sent to them meanwhile are queued.
+    ///
+    /// ### Arguments
+    /// * `peers` - Endpoint addresses of the peers.
+    pub async fn track_peers(&self, peers: &[SocketAddr]) {
+        let mut tracked_peers = self.tracked_peers.lock().await;
+        for peer in peers {
+            tracked_peers.entry(*peer).or_default();
+        }
+    }
+
+    /// Stops tracking peers, dropping their queued messages.
+    /// If provided, only stop tracking subset of peers.
+    pub async fn untrack_peers(&self, subset: Option<&[SocketAddr]>) {
+        let mut tracked_peers = self.tracked_peers.lock().await;
+        if let Some(subset) = subset {
+            tracked_peers.retain(|addr, _| !subset.contains(addr));
+        } else {
+            tracked_peers.clear();
+        }
+    }
+
+    /// Returns the tracked peers currently reconnecting.
+    pub async fn reconnecting_peers(&self) -> Vec<SocketAddr> {
+        let tracked_peers = self.tracked_peers.lock().await;
+        let reconnecting = tracked_peers.iter().filter(|(_, p)| p.reconnecting);
+        reconnecting.map(|(addr, _)| *addr).collect()
+    }
+
+    /// Enables peer discovery: announce this node to the peers it connects to,
+    /// and serve signed lists of the peers that announced themselves.
+    ///
+    /// ### Arguments
+    /// * `identity` - key pair used to sign announcements and served peer lists.
+    /// * `listed`   - whether this node should appear in the peer lists served by others.
+    pub async fn set_peer_discovery(&mut self, identity: NodeIdentity, listed: bool) {
+        info!(
+            "Peer discovery identity key: {}",
+            hex::encode(identity.public_key.as_ref())
+        );
+        let mut peer_discovery = self.peer_discovery.write().await;
+        peer_discovery.identity = Some(identity);
+        peer_discovery.listed = listed;
+    }
+
     /// Handles the listener.
     async fn listen(self, listener: TcpTlsListner) -> Result<Self> {
         let node = self.clone();
@@ -472,9 +596,202 @@ impl Node {
         self.connect_to_peer(peer).await?;
         self.send_handshake(peer).await?;
         self.wait_handshake_response(peer).await?;
+        self.send_peer_announce(peer).await?;
+        self.send_queued_messages(peer).await
+    }
+
+    /// Connects to a remote peer, retrying failed attempts with a backoff.
+    ///
+    /// ### Arguments
+    /// * `peer`   - Endpoint address of a remote peer.
+    /// * `policy` - Delays between the attempts and bounds on their number and duration.
+    ///
+    /// ### Returns
+    /// * `Ok(())` if this node has successfully connected to a peer.
+    /// * The error of the last attempt if none succeeded.
+    pub async fn connect_to_with_retry(
+        &mut self,
+        peer: SocketAddr,
+        policy: ConnectRetryPolicy,
+    ) -> Result<()> {
+        let start = Instant::now();
+        let mut attempt = 0;
+        loop {
+            let error = match self.connect_to(peer).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+
+            // Drop any half-established connection so the next attempt starts afresh
+            let _ = self.disconnect_all(Some(&[peer])).await;
+            let delay = policy
+                .backoff
+                .delay_with_jitter(attempt, &mut rand::thread_rng());
+            attempt = attempt.saturating_add(1);
+            if attempt >= policy.max_attempts || start.elapsed() + delay > policy.max_duration {
+                debug!(?peer, ?error, attempt, "Connection attempts exhausted");
+                return Err(error);
+            }
+
+            trace!(?peer, ?error, attempt, ?delay, "Connection attempt failed");
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Queues a message for a tracked peer that is not connected.
+    ///
+    /// ### Errors
+    /// - `CommsError::PeerNotFound` - if the peer is not tracked.
+    /// - `CommsError::PeerDisconnected` - if the queue of the peer is full.
+    async fn queue_message(&self, peer_addr: SocketAddr, data: Bytes) -> Result<()> {
+        let peer_info = || PeerInfo {
+            node_type: None,
+            address: Some(peer_addr),
+        };
+
+        let mut tracked_peers = self.tracked_peers.lock().await;
+        let tracked = tracked_peers
+            .get_mut(&peer_addr)
+            .ok_or_else(|| CommsError::PeerNotFound(peer_info()))?;
+        if tracked.queue.len() >= PEER_RECONNECT_QUEUE_DEPTH {
+            return Err(CommsError::PeerDisconnected(peer_info()));
+        }
+
+        trace!(?peer_addr, "queue_message");
+        tracked.queue.push_back(data);
         Ok(())
     }
 
+    /// Sends the messages queued while a tracked peer was not connected.
+    async fn send_queued_messages(&self, peer_addr: SocketAddr) -> Result<()> {
+        let queue = match self.tracked_peers.lock().await.get_mut(&peer_addr) {
+            Some(tracked) => std::mem::take(&mut tracked.queue),
+            None => return Ok(()),
+        };
+        if queue.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = {
+            let peers = self.peers.read().await;
+            let peer = peers
+                .get(&peer_addr)
+                .ok_or(CommsError::PeerNotFound(PeerInfo {
+                    node_type: None,
+                    address: Some(peer_addr),
+                }))?;
+            peer.send_tx.clone()
+        };
+
+        debug!("Sending {} queued messages to {peer_addr:?}", queue.len());
+        for data in queue {
+            self.send_bytes(peer_addr, &mut tx, data).await?;
+        }
+        Ok(())
+    }
+
+    /// Starts reconnecting to a peer whose connection was lost, if it is tracked.
+    async fn reconnect_if_tracked(&self, peer: SocketAddr) {
+        match self.tracked_peers.lock().await.get_mut(&peer) {
+            Some(tracked) if !tracked.reconnecting => tracked.reconnecting = true,
+            _ => return,
+        }
+
+        let node = self.clone();
+        spawn(
+            node.reconnect_to(peer)
+                .instrument(info_span!(parent: &self.span, "reconnect_to", ?peer)),
+        );
+    }
+
+    /// Attempts to reconnect to a tracked peer until it succeeds or the peer is untracked.
+    /// Attempts are spaced by the reconnection backoff.
+    async fn reconnect_to(mut self, peer: SocketAddr) {
+        let mut attempt = 0;
+        loop {
+            let delay = self
+                .reconnect_backoff()
+                .delay_with_jitter(attempt, &mut rand::thread_rng());
+            tokio::time::sleep(delay).await;
+
+            if !self.tracked_peers.lock().await.contains_key(&peer) {
+                trace!(?peer, "Peer no longer tracked");
+                return;
+            }
+
+            let result = if self.unconnected_peers(&[peer]).await.is_empty() {
+                // Connected again from elsewhere
+                self.send_queued_messages(peer).await
+            } else {
+                self.connect_to(peer).await
+            };
+
+            match result {
+                Ok(()) => break,
+                Err(error) => {
+                    debug!(?peer, ?error, attempt, "Reconnection attempt failed");
+                    // Drop any half-established connection so the next attempt starts afresh
+                    let _ = self.disconnect_all(Some(&[peer])).await;
+                    if attempt == 0 {
+                        let event = Event::PeerUnreachable { peer };
+                        if let Err(error) = self.event_tx.send(event) {
+                            warn!(?error, ?peer, "event_tx.send");
+                        }
+                    }
+                    attempt = attempt.saturating_add(1);
+                }
+            }
+        }
+
+        if let Some(tracked) = self.tracked_peers.lock().await.get_mut(&peer) {
+            tracked.reconnecting = false;
+        }
+        info!(?peer, "Reconnected to peer");
+        if let Err(error) = self.event_tx.send(Event::PeerReconnected { peer }) {
+            warn!(?error, ?peer, "event_tx.send");
+        }
+    }
+
+    /// Requests the signed peer list of a bootstrap node and verifies it.
+    ///
+    /// ### Arguments
+    /// * `bootstrap`       - Endpoint address of the bootstrap node.
+    /// * `expected_signer` - Identity key the bootstrap node is known to use, if any.
+    pub async fn discover_peers(
+        &mut self,
+        bootstrap: SocketAddr,
+        expected_signer: Option<PublicKey>,
+    ) -> Result<Vec<PeerRegistryEntry>> {
+        if !self.unconnected_peers(&[bootstrap]).await.is_empty() {
+            self.connect_to(bootstrap).await?;
+        }
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.peer_list_requests
+            .lock()
+            .await
+            .insert(bootstrap, response_tx);
+        self.send_message(bootstrap, CommMessage::PeerListRequest)
+            .await?;
+
+        let peer_info = || PeerInfo {
+            node_type: None,
+            address: Some(bootstrap),
+        };
+        let peer_list = match timeout(RESPONSE_TIMEOUT, response_rx).await {
+            Ok(Ok(peer_list)) => peer_list,
+            _ => {
+                self.peer_list_requests.lock().await.remove(&bootstrap);
+                return Err(CommsError::PeerNotFound(peer_info()));
+            }
+        };
+
+        if !peer_list.verify(expected_signer.as_ref()) || peer_list.is_stale(unix_now_secs()) {
+            return Err(CommsError::PeerListInvalid(peer_info()));
+        }
+        Ok(peer_list.entries)
+    }
+
     /// Wait for the handshake response to be received
     async fn wait_handshake_response(&mut self, peer: SocketAddr) -> Result<()> {
         // Wait for a handshake response
@@ -553,6 +870,14 @@ impl Node {
         take_join_handles(all_peers.iter_mut().map(|(_, p)| p))
     }
 
+    /// Notify all remote peers that this node is shutting down.
+    /// Returns a list of the peers to which the notice could not be sent.
+    pub async fn notify_disconnect_all(&mut self) -> Vec<SocketAddr> {
+        let peers: Vec<_> = self.peers.read().await.keys().copied().collect();
+        self.send_multicast(peers.into_iter(), CommMessage::Disconnect)
+            .await
+    }
+
     /// Take the specified join handle to wait on externally.
     pub async fn take_join_handle(&mut self, addr: SocketAddr) -> Vec<JoinHandle<()>> {
         let mut all_peers = self.peers.write().await;
@@ -681,16 +1006,27 @@ impl Node {
     }
 
     /// Sends data to a peer.
+    /// Frames over the peer limit are not sent, as the peer would drop the connection.
+    /// The limit is not exchanged, so the default for the peer node type is used.
     async fn send_message(&mut self, peer_addr: SocketAddr, message: CommMessage) -> Result<()> {
         let data = Bytes::from(serialize(&message)?);
 
         let peers = self.peers.read().await;
-        let peer = peers
-            .get(&peer_addr)
-            .ok_or(CommsError::PeerNotFound(PeerInfo {
-                node_type: None,
-                address: Some(peer_addr),
-            }))?;
+        let peer = match peers.get(&peer_addr) {
+            Some(peer) => peer,
+            None => {
+                drop(peers);
+                return self.queue_message(peer_addr, data).await;
+            }
+        };
+        if let Some(peer_type) = peer.peer_type {
+            if data.len() > default_max_frame_size(peer_type) {
+                return Err(CommsError::FrameSizeLimitExceeded(PeerInfo {
+                    node_type: Some(peer_type),
+                    address: Some(peer_addr),
+                }));
+            }
+        }
         let mut tx = peer.send_tx.clone();
         self.send_bytes(peer_addr, &mut tx, data).await
     }
@@ -758,6 +1094,137 @@ impl Node {
         .await
     }
 
+    /// Announces this node identity to a peer if peer discovery is enabled,
+    /// signing the listener address and nonce the peer issued for this connection.
+    async fn send_peer_announce(&mut self, peer: SocketAddr) -> Result<()> {
+        if self.peer_discovery.read().await.identity.is_none() {
+            return Ok(());
+        }
+
+        let (challenge_tx, challenge_rx) = oneshot::channel();
+        self.announce_challenges
+            .lock()
+            .await
+            .insert(peer, challenge_tx);
+        self.send_message(peer, CommMessage::PeerAnnounceChallengeRequest)
+            .await?;
+
+        let (address, nonce) = match timeout(RESPONSE_TIMEOUT, challenge_rx).await {
+            Ok(Ok(challenge)) => challenge,
+            _ => {
+                self.announce_challenges.lock().await.remove(&peer);
+                return Err(CommsError::PeerNotFound(PeerInfo {
+                    node_type: None,
+                    address: Some(peer),
+                }));
+            }
+        };
+        if address.port() != self.local_listener_address.port() {
+            return Err(CommsError::PeerInvalidState(PeerInfo {
+                node_type: None,
+                address: Some(peer),
+            }));
+        }
+
+        let announce = {
+            let peer_discovery = self.peer_discovery.read().await;
+            let identity = peer_discovery.identity.as_ref();
+            identity.map(|identity| CommMessage::PeerAnnounce {
+                identity_key: identity.public_key,
+                signature: identity.sign_announce(self.node_type, address, nonce),
+                listed: peer_discovery.listed,
+            })
+        };
+
+        match announce {
+            Some(announce) => self.send_message(peer, announce).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Issues a nonce for the peer to sign its identity announcement with.
+    async fn send_peer_announce_challenge(&mut self, peer: SocketAddr) -> Result<()> {
+        let nonce = thread_rng().gen();
+        match self.peers.write().await.get_mut(&peer) {
+            Some(peer) => peer.announce_nonce = Some(nonce),
+            None => {
+                return Err(CommsError::PeerNotFound(PeerInfo {
+                    node_type: None,
+                    address: Some(peer),
+                }))
+            }
+        }
+
+        let challenge = CommMessage::PeerAnnounceChallenge {
+            address: peer,
+            nonce,
+        };
+        self.send_message(peer, challenge).await
+    }
+
+    /// Sends the signed list of announced peers, if peer discovery is enabled.
+    async fn send_peer_list(&mut self, peer: SocketAddr) -> Result<()> {
+        let now = unix_now_secs();
+        let connected: Vec<_> = self.peers.read().await.keys().copied().collect();
+        let own_address = self
+            .public_address()
+            .await
+            .unwrap_or(self.local_listener_address);
+
+        let peer_list = {
+            let mut peer_discovery = self.peer_discovery.write().await;
+            peer_discovery.refresh(connected.into_iter(), now);
+            let entries = peer_discovery.entries(self.node_type, own_address, now);
+            match &peer_discovery.identity {
+                Some(identity) => SignedPeerList::new(entries, now, identity),
+                None => return Err(CommsError::ConfigError("Peer discovery not enabled")),
+            }
+        };
+
+        self.send_message(peer, CommMessage::PeerListResponse(peer_list))
+            .await
+    }
+
+    /// Handles an identity announcement, recording the peer in the discovery registry.
+    ///
+    /// ### Arguments
+    /// * `peer_addr`    - address of the announcing peer.
+    /// * `identity_key` - identity key of the announcing peer.
+    /// * `signature`    - signature of the peer address and nonce issued to it, proving
+    ///                    ownership of the identity key.
+    /// * `listed`       - whether the peer wants to appear in served peer lists.
+    async fn handle_peer_announce(
+        &self,
+        peer_addr: SocketAddr,
+        identity_key: PublicKey,
+        signature: Signature,
+        listed: bool,
+    ) -> Result<()> {
+        let node_type = self.get_peer_node_type(peer_addr).await?;
+        let nonce = (self.peers.write().await)
+            .get_mut(&peer_addr)
+            .and_then(|peer| peer.announce_nonce.take());
+        let signable =
+            nonce.map(|nonce| announce_signable(node_type, &identity_key, peer_addr, nonce));
+        if !signable.map_or(false, |signable| {
+            sign::verify_detached(&signature, &signable, &identity_key)
+        }) {
+            return Err(CommsError::PeerInvalidState(PeerInfo {
+                node_type: Some(node_type),
+                address: Some(peer_addr),
+            }));
+        }
+
+        let entry = PeerRegistryEntry {
+            node_type,
+            address: peer_addr,
+            identity_key,
+            last_seen: unix_now_secs(),
+        };
+        self.peer_discovery.write().await.record(entry, listed);
+        Ok(())
+    }
+
     /// Blocks & waits for a next event from a peer.
     pub async fn next_event(&mut self) -> Option<Event> {
         self.event_rx.lock().await.recv().await
@@ -891,6 +1358,51 @@ impl Node {
                 CommMessage::HeartBeatProbe(id) => {
                     debug!("HeartBeat message from {peer_addr:?} with ID: {id:?}");
                 }
+                CommMessage::Disconnect => {
+                    debug!("Disconnect notice from {peer_addr:?}");
+                    self.flush_stale_peers(vec![peer_addr]).await;
+                    break;
+                }
+                CommMessage::PeerAnnounce {
+                    identity_key,
+                    signature,
+                    listed,
+                } => {
+                    if let Err(error) = self
+                        .handle_peer_announce(peer_addr, identity_key, signature, listed)
+                        .await
+                    {
+                        warn!(?error, ?peer_addr, "handle_peer_announce");
+                    }
+                }
+                CommMessage::PeerAnnounceChallengeRequest => {
+                    if let Err(error) = self.clone().send_peer_announce_challenge(peer_addr).await {
+                        warn!(?error, ?peer_addr, "send_peer_announce_challenge");
+                    }
+                }
+                CommMessage::PeerAnnounceChallenge { address, nonce } => {
+                    let challenge_tx = self.announce_challenges.lock().await.remove(&peer_addr);
+                    match challenge_tx {
+                        Some(challenge_tx) => {
+                            let _ = challenge_tx.send((address, nonce));
+                        }
+                        None => warn!(?peer_addr, "Ignoring unrequested announce challenge"),
+                    }
+                }
+                CommMessage::PeerListRequest => {
+                    if let Err(error) = self.clone().send_peer_list(peer_addr).await {
+                        warn!(?error, ?peer_addr, "send_peer_list");
+                    }
+                }
+                CommMessage::PeerListResponse(peer_list) => {
+                    let response_tx = self.peer_list_requests.lock().await.remove(&peer_addr);
+                    match response_tx {
+                        Some(response_tx) => {
+                            let _ = response_tx.send(peer_list);
+                        }
+                        None => warn!(?peer_addr, "Ignoring unrequested peer list"),
+                    }
+                }
                 other => {
                     warn!(?other, "Received unexpected message; ignoring");
                 }
@@ -992,6 +1504,12 @@ impl Node {
             }));
         }
 
+        // Pinned peers must present their expected certificate
+        self.tcp_tls_connector
+            .read()
+            .await
+            .verify_pinned_certificate(peer_in_addr, peer_cert.as_ref())?;
+
         // Check for duplicate peers
         let mut all_peers = self.peers.write().await;
         if all_peers.contains_key(&peer_in_addr) {
@@ -1151,13 +1669,17 @@ impl Node {
         let (send_tx, mut send_rx) = mpsc::channel(128);
 
         // Wrap the peer socket into the tokio codec which handles length-delimited frames.
+        // Incoming frames over our limit are rejected from their length prefix, before buffering.
+        // Outgoing frames are checked against the peer limit in `send_message`.
         let (sock_in, sock_out) = tokio::io::split(socket);
-        let codec_builder = *length_delimited::Builder::new().max_frame_length(
-            // max frame length of 100MB
-            100 * 1_024 * 1_024,
-        );
-        let sock_in = FramedRead::new(sock_in, codec_builder.new_codec());
-        let mut sock_out = FramedWrite::new(sock_out, codec_builder.new_codec());
+        let in_codec = length_delimited::Builder::new()
+            .max_frame_length(self.max_frame_size())
+            .new_codec();
+        let out_codec = length_delimited::Builder::new()
+            .max_frame_length(u32::MAX as usize)
+            .new_codec();
+        let sock_in = FramedRead::new(sock_in, in_codec);
+        let mut sock_out = FramedWrite::new(sock_out, out_codec);
 
         // Spawn the sender task.
         // Redirect messages from the mpsc channel into the TCP socket
@@ -1180,7 +1702,8 @@ impl Node {
 
         // Spawn the receiver task which will redirect the incoming messages into the MPSC channel
         // and manage the peer state transitions.
-        let (messages, close_receiver_tx) = get_messages_stream(sock_in);
+        let (messages, close_receiver_tx) =
+            get_messages_stream(sock_in, peer_addr, self.event_tx.clone());
         let sock_in_h = spawn({
             let mut node = self.clone();
             let send_tx = send_tx.clone().into();
@@ -1225,9 +1748,9 @@ impl Node {
                 node.handle_peer_recv(public_address, messages).await;
                 // Since we don't wait for any messages from this peer, we can drop the connection.
                 warn!("Remove peer: {}", public_address);
-                let mut peers_list = peers.write().await;
-                let _ = peers_list.remove(&public_address);
+                let _ = peers.write().await.remove(&public_address);
                 trace!("sock_in dropped for {:?}", peer_addr);
+                node.reconnect_if_tracked(public_address).await;
             }
             .instrument(span)
         });
@@ -1244,6 +1767,7 @@ impl Node {
                 let (tx, rx) = oneshot::channel::<()>();
                 (Some(tx), Some(rx))
             },
+            announce_nonce: None,
             close_receiver_tx,
             sock_in_out_join_handles: (Some(sock_in_h), Some(sock_out_h)),
         }
@@ -1294,16 +1818,74 @@ impl Node {
     }
 }
 
+/// Default max size of a frame accepted by a node type.
+///
+/// ### Arguments
+/// * `node_type` - the node type receiving the frames.
+pub fn default_max_frame_size(node_type: NodeType) -> usize {
+    match node_type {
+        NodeType::Miner => MINER_MAX_FRAME_SIZE,
+        NodeType::Storage => STORAGE_MAX_FRAME_SIZE,
+        NodeType::Mempool => MEMPOOL_MAX_FRAME_SIZE,
+        NodeType::User => USER_MAX_FRAME_SIZE,
+        NodeType::PreLaunch => PRE_LAUNCH_MAX_FRAME_SIZE,
+    }
+}
+
+/// Splits items into batches each small enough to be sent in a single frame.
+/// An item too large on its own is still given its own batch.
+///
+/// ### Arguments
+/// * `items`          - items to send.
+/// * `max_frame_size` - max frame size accepted by the receiving peer.
+pub fn split_into_frame_batches<T: Serialize>(items: Vec<T>, max_frame_size: usize) -> Vec<Vec<T>> {
+    let max_batch_size = max_frame_size.saturating_sub(FRAME_BATCH_OVERHEAD);
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    let mut batch_size = 0;
+
+    for item in items {
+        let item_size = bincode::serialized_size(&item).map_or(usize::MAX, |s| s as usize);
+        if !batch.is_empty() && batch_size.saturating_add(item_size) > max_batch_size {
+            batches.push(std::mem::take(&mut batch));
+            batch_size = 0;
+        }
+        batch_size = batch_size.saturating_add(item_size);
+        batch.push(item);
+    }
+
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
 /// Transforms a stream of incoming TCP frames into a stream of deserialized messages.
+/// A frame over the size limit ends the stream and is reported as an event.
+///
+/// ### Arguments
+/// * `sock_in`   - incoming TCP frames.
+/// * `peer_addr` - address of the remote peer.
+/// * `event_tx`  - a channel for user-level events.
 fn get_messages_stream(
     sock_in: FramedRead<tokio::io::ReadHalf<TcpTlsStream>, LengthDelimitedCodec>,
+    peer_addr: SocketAddr,
+    event_tx: mpsc::UnboundedSender<Event>,
 ) -> (impl Stream<Item = CommMessage>, oneshot::Sender<()>) {
     let messages = sock_in
-        .map(|frame| {
+        .map(move |frame| {
             trace!(?frame, "recv_frame");
 
             let frame = match frame {
                 Ok(inner) => inner,
+                Err(error) if is_frame_size_error(&error) => {
+                    warn!(?error, ?peer_addr, "Frame size limit exceeded");
+                    let event = Event::FrameSizeLimitExceeded { peer: peer_addr };
+                    if let Err(error) = event_tx.send(event) {
+                        warn!(?error, ?peer_addr, "event_tx.send");
+                    }
+                    return None;
+                }
                 Err(error) => {
                     warn!(?error, "Could not decode frame");
                     return None;
@@ -1329,6 +1911,13 @@ fn get_messages_stream(
     (cancellable_messages, close_tx)
 }
 
+/// Whether a frame decoding error is caused by a frame over the size limit.
+fn is_frame_size_error(error: &io::Error) -> bool {
+    error
+        .get_ref()
+        .map_or(false, |e| e.is::<LengthDelimitedCodecError>())
+}
+
 fn take_join_handles<'a>(peers: impl Iterator<Item = &'a mut Peer>) -> Vec<JoinHandle<()>> {
     peers
         .map(|p| &mut p.sock_in_out_join_handles)
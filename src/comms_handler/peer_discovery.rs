@@ -0,0 +1,335 @@
+//! Registry of peers served by bootstrap nodes to newly connecting nodes.
+//!
+//! Nodes with an identity key announce themselves to the nodes they connect to,
+//! signing the listener address they are known under and a nonce issued for the connection.
+//! Any node with an identity key keeps a registry of the announced peers that passed
+//! the handshake, and serves it as a peer list signed with its identity key.
+//! Raft membership is not affected: it stays explicitly configured.
+
+use super::{CommsError, Node};
+use crate::configurations::{BootstrapNodeSpec, PeerDiscoveryConfig};
+use crate::constants::PEER_REGISTRY_STALE_SECS;
+use crate::interfaces::NodeType;
+use crate::utils::create_socket_addr;
+use bincode::serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use tw_chain::crypto::sign_ed25519::{self as sign, PublicKey, SecretKey, Signature};
+
+/// A peer known to a bootstrap node
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerRegistryEntry {
+    pub node_type: NodeType,
+    pub address: SocketAddr,
+    pub identity_key: PublicKey,
+    /// Unix time in seconds when the peer was last seen connected
+    pub last_seen: u64,
+}
+
+/// Peer list signed by the identity key of the node serving it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPeerList {
+    pub entries: Vec<PeerRegistryEntry>,
+    pub issued_at: u64,
+    pub signer: PublicKey,
+    pub signature: Signature,
+}
+
+impl SignedPeerList {
+    /// Sign the given entries with the node identity
+    ///
+    /// ### Arguments
+    ///
+    /// * `entries`   - Peers to list
+    /// * `issued_at` - Unix time in seconds the list is issued
+    /// * `identity`  - Identity of the serving node
+    pub fn new(entries: Vec<PeerRegistryEntry>, issued_at: u64, identity: &NodeIdentity) -> Self {
+        let signable = peer_list_signable(&entries, issued_at);
+        Self {
+            entries,
+            issued_at,
+            signer: identity.public_key,
+            signature: sign::sign_detached(&signable, &identity.secret_key),
+        }
+    }
+
+    /// Check the list was signed by its signer, and that the signer is the expected one
+    ///
+    /// ### Arguments
+    ///
+    /// * `expected_signer` - Identity key the bootstrap node is known to use, if any
+    pub fn verify(&self, expected_signer: Option<&PublicKey>) -> bool {
+        let signable = peer_list_signable(&self.entries, self.issued_at);
+        expected_signer.map_or(true, |key| key == &self.signer)
+            && sign::verify_detached(&self.signature, &signable, &self.signer)
+    }
+
+    /// Whether the list was issued too long ago to be trusted
+    ///
+    /// ### Arguments
+    ///
+    /// * `now` - Unix time in seconds
+    pub fn is_stale(&self, now: u64) -> bool {
+        now.saturating_sub(self.issued_at) > PEER_REGISTRY_STALE_SECS
+    }
+}
+
+/// Identity key pair of a node, used to sign announcements and served peer lists
+#[derive(Clone)]
+pub struct NodeIdentity {
+    pub public_key: PublicKey,
+    pub secret_key: SecretKey,
+}
+
+impl fmt::Debug for NodeIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeIdentity {{ public_key: {:?} }}", self.public_key)
+    }
+}
+
+impl NodeIdentity {
+    /// Create the identity from the configured keys, or a new random identity
+    ///
+    /// ### Arguments
+    ///
+    /// * `config` - Peer discovery configuration
+    pub fn from_config(config: &PeerDiscoveryConfig) -> Result<Self, &'static str> {
+        match (&config.identity_public_key, &config.identity_secret_key) {
+            (Some(public_key), Some(secret_key)) => {
+                let public_key = hex::decode(public_key)
+                    .ok()
+                    .and_then(|k| PublicKey::from_slice(&k))
+                    .ok_or("Invalid identity public key")?;
+                let secret_key = hex::decode(secret_key)
+                    .ok()
+                    .and_then(|k| SecretKey::from_slice(&k))
+                    .ok_or("Invalid identity secret key")?;
+                Ok(Self {
+                    public_key,
+                    secret_key,
+                })
+            }
+            (None, None) => {
+                let (public_key, secret_key) = sign::gen_keypair();
+                Ok(Self {
+                    public_key,
+                    secret_key,
+                })
+            }
+            _ => Err("Identity public and secret keys must be provided together"),
+        }
+    }
+
+    /// Sign an announcement of this node
+    ///
+    /// ### Arguments
+    ///
+    /// * `node_type` - Type of this node
+    /// * `address`   - Listener address the peer knows this node under
+    /// * `nonce`     - Nonce issued by the peer for the connection
+    pub fn sign_announce(&self, node_type: NodeType, address: SocketAddr, nonce: u64) -> Signature {
+        sign::sign_detached(
+            &announce_signable(node_type, &self.public_key, address, nonce),
+            &self.secret_key,
+        )
+    }
+}
+
+/// Identity and registry used for peer discovery, shared by all connections of a node
+#[derive(Debug, Default)]
+pub struct PeerDiscovery {
+    pub identity: Option<NodeIdentity>,
+    pub listed: bool,
+    registry: BTreeMap<SocketAddr, PeerRegistryEntry>,
+}
+
+impl PeerDiscovery {
+    /// Record an announced peer, or forget it if it opted out of being listed
+    ///
+    /// ### Arguments
+    ///
+    /// * `entry`  - Announced peer
+    /// * `listed` - Whether the peer wants to be listed
+    pub fn record(&mut self, entry: PeerRegistryEntry, listed: bool) {
+        if listed {
+            self.registry.insert(entry.address, entry);
+        } else {
+            self.registry.remove(&entry.address);
+        }
+    }
+
+    /// Refresh connected peers and age out the stale ones
+    ///
+    /// ### Arguments
+    ///
+    /// * `connected` - Addresses of the currently connected peers
+    /// * `now`       - Unix time in seconds
+    pub fn refresh(&mut self, connected: impl Iterator<Item = SocketAddr>, now: u64) {
+        for address in connected {
+            if let Some(entry) = self.registry.get_mut(&address) {
+                entry.last_seen = now;
+            }
+        }
+        self.registry
+            .retain(|_, e| now.saturating_sub(e.last_seen) <= PEER_REGISTRY_STALE_SECS);
+    }
+
+    /// Registered peers, including this node itself if listed
+    ///
+    /// ### Arguments
+    ///
+    /// * `node_type` - Type of this node
+    /// * `address`   - Public address of this node
+    /// * `now`       - Unix time in seconds
+    pub fn entries(
+        &self,
+        node_type: NodeType,
+        address: SocketAddr,
+        now: u64,
+    ) -> Vec<PeerRegistryEntry> {
+        let own_entry = self
+            .identity
+            .as_ref()
+            .filter(|_| self.listed)
+            .map(|identity| PeerRegistryEntry {
+                node_type,
+                address,
+                identity_key: identity.public_key,
+                last_seen: now,
+            });
+
+        own_entry
+            .into_iter()
+            .chain(
+                self.registry
+                    .values()
+                    .filter(|e| e.address != address)
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+/// Data signed by a node announcing itself
+///
+/// ### Arguments
+///
+/// * `node_type`    - Type of the announcing node
+/// * `identity_key` - Identity key of the announcing node
+/// * `address`      - Listener address of the announcing node
+/// * `nonce`        - Nonce issued for the connection the announcement is sent on
+pub fn announce_signable(
+    node_type: NodeType,
+    identity_key: &PublicKey,
+    address: SocketAddr,
+    nonce: u64,
+) -> Vec<u8> {
+    serialize(&(node_type, identity_key, address, nonce)).unwrap()
+}
+
+/// Data signed by a node serving a peer list
+fn peer_list_signable(entries: &[PeerRegistryEntry], issued_at: u64) -> Vec<u8> {
+    serialize(&(entries, issued_at)).unwrap()
+}
+
+/// Addresses of the listed peers of the given type, in order of preference:
+/// the bootstrap node itself first, then the most recently seen.
+///
+/// ### Arguments
+///
+/// * `entries`   - Peers from a verified peer list
+/// * `node_type` - Type of the peers to select
+/// * `bootstrap` - Address of the bootstrap node that served the list
+pub fn select_peers(
+    entries: &[PeerRegistryEntry],
+    node_type: NodeType,
+    bootstrap: SocketAddr,
+) -> Vec<SocketAddr> {
+    let mut selected: Vec<_> = entries
+        .iter()
+        .filter(|e| e.node_type == node_type)
+        .collect();
+    selected.sort_by_key(|e| (e.address != bootstrap, std::cmp::Reverse(e.last_seen)));
+    selected.into_iter().map(|e| e.address).collect()
+}
+
+/// Enable peer discovery on the node if configured
+///
+/// ### Arguments
+///
+/// * `node`   - Node to announce and serve peer lists
+/// * `config` - Peer discovery configuration
+pub async fn configure_peer_discovery(
+    node: &mut Node,
+    config: Option<&PeerDiscoveryConfig>,
+) -> Result<(), CommsError> {
+    if let Some(config) = config {
+        let identity = NodeIdentity::from_config(config).map_err(CommsError::ConfigError)?;
+        node.set_peer_discovery(identity, !config.unlisted).await;
+    }
+    Ok(())
+}
+
+/// Select a peer of the given type from the peer lists served by the bootstrap nodes,
+/// trying the bootstrap nodes in their configured order.
+///
+/// ### Arguments
+///
+/// * `node`            - Node requesting the peer lists
+/// * `bootstrap_nodes` - Bootstrap nodes in order of priority
+/// * `node_type`       - Type of the peer to select
+pub async fn discover_peer_addr(
+    node: &mut Node,
+    bootstrap_nodes: &[BootstrapNodeSpec],
+    node_type: NodeType,
+) -> Option<SocketAddr> {
+    for spec in bootstrap_nodes {
+        let bootstrap = match create_socket_addr(&spec.address).await {
+            Ok(bootstrap) => bootstrap,
+            Err(e) => {
+                warn!("Invalid bootstrap node address {}: {}", spec.address, e);
+                continue;
+            }
+        };
+        let expected_signer = match &spec.identity_public_key {
+            Some(key) => match hex::decode(key)
+                .ok()
+                .and_then(|k| PublicKey::from_slice(&k))
+            {
+                Some(key) => Some(key),
+                None => {
+                    warn!("Invalid bootstrap node identity key for {}", bootstrap);
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        match node.discover_peers(bootstrap, expected_signer).await {
+            Ok(entries) => match select_peers(&entries, node_type, bootstrap).first() {
+                Some(addr) => {
+                    info!(
+                        "Discovered {:?} node {} from {}",
+                        node_type, addr, bootstrap
+                    );
+                    return Some(*addr);
+                }
+                None => warn!("No {:?} node listed by {}", node_type, bootstrap),
+            },
+            Err(e) => warn!("Peer discovery from {} failed: {}", bootstrap, e),
+        }
+    }
+    None
+}
+
+/// Current unix time in seconds
+pub fn unix_now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
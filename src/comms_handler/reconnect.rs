@@ -0,0 +1,64 @@
+//! Reconnection to the long-lived peers of a node once their connection is lost.
+//!
+//! Attempts are spaced by an exponential backoff capped to a maximum delay,
+//! with a random jitter so that peers losing the same node do not retry in lockstep.
+
+use bytes::Bytes;
+use rand::Rng;
+use std::collections::VecDeque;
+use tokio::time::Duration;
+
+/// Delays between the reconnection attempts to a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectBackoff {
+    /// Delay before the first attempt
+    pub base_delay: Duration,
+    /// Maximum delay between two attempts
+    pub max_delay: Duration,
+}
+
+impl ReconnectBackoff {
+    /// Delay before an attempt, doubling from the base delay up to the maximum delay
+    ///
+    /// ### Arguments
+    ///
+    /// * `attempt` - Number of failed attempts so far
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Delay before an attempt, with a random jitter of up to half of it taken off
+    ///
+    /// ### Arguments
+    ///
+    /// * `attempt` - Number of failed attempts so far
+    /// * `rng`     - Source of the jitter
+    pub fn delay_with_jitter(&self, attempt: u32, rng: &mut impl Rng) -> Duration {
+        let delay = self.delay(attempt);
+        delay - (delay / 2).mul_f64(rng.gen::<f64>())
+    }
+}
+
+/// Bounds on the attempts of a connection retried with a backoff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectRetryPolicy {
+    /// Delays between the attempts
+    pub backoff: ReconnectBackoff,
+    /// Maximum number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Maximum time spent attempting, from the first attempt
+    pub max_duration: Duration,
+}
+
+/// Reconnection state of a tracked peer
+#[derive(Debug, Default)]
+pub(crate) struct TrackedPeer {
+    /// Frames sent while disconnected, delivered once connected again
+    pub queue: VecDeque<Bytes>,
+    /// Whether a reconnection task is running for the peer
+    pub reconnecting: bool,
+}
@@ -1,12 +1,13 @@
 //! Utilities necessary to establish a TCP connection with TLS overay stream.
 
 use super::{CommsError, Result};
+use crate::comms_handler::error::PeerInfo;
 use crate::configurations::{TlsPrivateInfo, TlsSpec};
 use rustls_pemfile::{certs, pkcs8_private_keys};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt;
-use std::io::Cursor;
+use std::io::{self, Cursor};
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -15,6 +16,7 @@ use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::Mutex;
 use tokio_rustls::rustls::client::ServerName;
+use tokio_rustls::rustls::server::AllowAnyAnonymousOrAuthenticatedClient;
 use tokio_rustls::rustls::{
     Certificate, ClientConfig, CommonState, PrivateKey, RootCertStore, ServerConfig,
 };
@@ -26,6 +28,9 @@ pub type TlsStreamClient = tokio_rustls::client::TlsStream<TcpStream>;
 pub type TlsStreamServer = tokio_rustls::server::TlsStream<TcpStream>;
 pub type TlsCertificate = Certificate;
 
+/// Record type of the first bytes sent by a TLS client
+const TLS_HANDSHAKE_RECORD_TYPE: u8 = 0x16;
+
 #[derive(Debug)]
 pub struct TcpTlsConfig {
     address: SocketAddr,
@@ -33,6 +38,7 @@ pub struct TcpTlsConfig {
     pem_certs: String,
     pem_pkcs8_private_keys: String,
     trusted_pem_certs: Vec<String>,
+    pinned_certs: BTreeMap<SocketAddr, TlsCertificate>,
     use_tls: bool,
     listener: Arc<Mutex<Option<TcpListener>>>,
 }
@@ -45,17 +51,18 @@ impl TcpTlsConfig {
             pem_certs: Default::default(),
             pem_pkcs8_private_keys: Default::default(),
             trusted_pem_certs: Default::default(),
+            pinned_certs: Default::default(),
             use_tls: false,
             listener: Default::default(),
         }
     }
 
     pub fn from_tls_spec(address: SocketAddr, config: &TlsSpec) -> Result<Self> {
-        if config.pem_certificates.is_empty() {
+        if config.pem_certificates.is_empty() && config.pem_certificate_path.is_none() {
             Ok(Self::new_no_tls(address))
         } else {
             let name = socket_name_mapping_or_default(&config.socket_name_mapping, address);
-            let (socket_name_mapping, trusted_pem_certs) =
+            let (socket_name_mapping, mut trusted_pem_certs): (_, Vec<_>) =
                 if let Some(untrusted_names) = &config.untrusted_names {
                     (
                         config
@@ -78,22 +85,53 @@ impl TcpTlsConfig {
                     )
                 };
 
-            Ok(Self {
-                address,
-                socket_name_mapping,
-                pem_certs: config
-                    .pem_certificate_override
-                    .as_ref()
-                    .or_else(|| config.pem_certificates.get(&name))
+            if let Some(path) = &config.pem_ca_bundle_path {
+                trusted_pem_certs.push(std::fs::read_to_string(path)?);
+            }
+
+            let pem_certs = match (
+                &config.pem_certificate_override,
+                &config.pem_certificate_path,
+            ) {
+                (Some(pem), _) => pem.clone(),
+                (None, Some(path)) => std::fs::read_to_string(path)?,
+                (None, None) => config
+                    .pem_certificates
+                    .get(&name)
                     .ok_or(CommsError::ConfigError("Missing TLS node certificate"))?
                     .clone(),
-                pem_pkcs8_private_keys: config
-                    .pem_pkcs8_private_key_override
-                    .as_ref()
-                    .or_else(|| config.pem_pkcs8_private_keys.get(&name))
+            };
+            let pem_pkcs8_private_keys = match (
+                &config.pem_pkcs8_private_key_override,
+                &config.pem_pkcs8_private_key_path,
+            ) {
+                (Some(pem), _) => pem.clone(),
+                (None, Some(path)) => std::fs::read_to_string(path)?,
+                (None, None) => config
+                    .pem_pkcs8_private_keys
+                    .get(&name)
                     .ok_or(CommsError::ConfigError("Missing TLS node keys"))?
                     .clone(),
+            };
+
+            let mut pinned_certs = BTreeMap::new();
+            for (addr, name) in &config.socket_name_mapping {
+                if let Some(pem) = config.pinned_pem_certificates.get(name) {
+                    let cert = load_certs(pem)
+                        .into_iter()
+                        .next()
+                        .ok_or(CommsError::ConfigError("invalid pinned cert"))?;
+                    pinned_certs.insert(*addr, cert);
+                }
+            }
+
+            Ok(Self {
+                address,
+                socket_name_mapping,
+                pem_certs,
+                pem_pkcs8_private_keys,
                 trusted_pem_certs,
+                pinned_certs,
                 use_tls: true,
                 listener: Default::default(),
             })
@@ -174,8 +212,19 @@ impl TcpTlsListner {
     }
 
     async fn next_tcp_tls_stream(&mut self) -> Result<TcpTlsStream> {
-        let (stream, _addr) = self.tcp_listener.accept().await?;
+        let (stream, addr) = self.tcp_listener.accept().await?;
         if let Some(tls_acceptor) = &mut self.tls_acceptor {
+            // Reject plain TCP peers rather than failing on a garbled handshake
+            let mut first_byte = [0u8; 1];
+            if stream.peek(&mut first_byte).await? == 0
+                || first_byte[0] != TLS_HANDSHAKE_RECORD_TYPE
+            {
+                return Err(CommsError::TlsModeMismatch(PeerInfo {
+                    node_type: None,
+                    address: Some(addr),
+                }));
+            }
+
             let stream = tls_acceptor.accept(stream).await?;
             let peer_addr = stream.get_ref().0.peer_addr()?;
             Ok(TcpTlsStream::Server(stream, peer_addr))
@@ -189,6 +238,7 @@ impl TcpTlsListner {
 #[derive(Clone)]
 pub struct TcpTlsConnector {
     socket_name_mapping: BTreeMap<SocketAddr, String>,
+    pinned_certs: BTreeMap<SocketAddr, TlsCertificate>,
     tls_connector: Option<TlsConnector>,
 }
 
@@ -209,9 +259,11 @@ impl TcpTlsConnector {
             None
         };
         let socket_name_mapping = config.socket_name_mapping.clone();
+        let pinned_certs = config.pinned_certs.clone();
 
         Ok(Self {
             socket_name_mapping,
+            pinned_certs,
             tls_connector,
         })
     }
@@ -224,9 +276,24 @@ impl TcpTlsConnector {
             let domain = ServerName::try_from(tls_name.as_str())
                 .map_err(|_| CommsError::ConfigError("invalid dnsname"))?;
 
-            let stream = tls_connector.connect(domain, stream).await?;
+            let stream =
+                tls_connector
+                    .connect(domain, stream)
+                    .await
+                    .map_err(|e| match e.kind() {
+                        // Plain TCP peers drop the connection on our handshake
+                        io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset => {
+                            CommsError::TlsModeMismatch(PeerInfo {
+                                node_type: None,
+                                address: Some(addr),
+                            })
+                        }
+                        _ => CommsError::Io(e),
+                    })?;
             let peer_addr = stream.get_ref().0.peer_addr()?;
-            Ok(TcpTlsStream::Client(stream, peer_addr))
+            let stream = TcpTlsStream::Client(stream, peer_addr);
+            self.verify_pinned_certificate(addr, stream.peer_tls_certificate().as_ref())?;
+            Ok(stream)
         } else {
             let peer_addr = stream.peer_addr()?;
             Ok(TcpTlsStream::RawTcp(stream, peer_addr))
@@ -236,6 +303,29 @@ impl TcpTlsConnector {
     pub fn socket_name_mapping(&self, addr: SocketAddr) -> String {
         socket_name_mapping_or_default(&self.socket_name_mapping, addr)
     }
+
+    /// Check the peer presented the certificate pinned for its address, if any
+    pub fn verify_pinned_certificate(
+        &self,
+        addr: SocketAddr,
+        cert: Option<&TlsCertificate>,
+    ) -> Result<()> {
+        let pinned = self.pinned_certs.get(&addr).or_else(|| {
+            let mut addr = addr;
+            addr.set_port(0);
+            self.pinned_certs.get(&addr)
+        });
+
+        match pinned {
+            Some(pinned) if Some(pinned) != cert => {
+                Err(CommsError::PeerCertificateMismatch(PeerInfo {
+                    node_type: None,
+                    address: Some(addr),
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 fn load_certs(pem: &str) -> Vec<TlsCertificate> {
@@ -284,18 +374,23 @@ fn new_root_certs(trusted_pem_certs: &[String]) -> Result<RootCertStore> {
 }
 
 fn new_server_config(config: &TcpTlsConfig) -> Result<ServerConfig> {
-    // TODO: Handle root_store
-    // let root_store = new_root_certs(&config.trusted_pem_certs)?;
     let certs = load_certs(&config.pem_certs);
     let mut keys = load_keys(&config.pem_pkcs8_private_keys);
 
-    let server_config = ServerConfig::builder()
+    let builder = ServerConfig::builder()
         .with_safe_default_cipher_suites()
         .with_safe_default_kx_groups()
         .with_safe_default_protocol_versions()
-        .unwrap()
-        .with_no_client_auth()
-        .with_single_cert(certs, keys.remove(0))?;
+        .unwrap();
+
+    // Client certificates are only needed to check pinned peers
+    let builder = if config.pinned_certs.is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let root_store = new_root_certs(&config.trusted_pem_certs)?;
+        builder.with_client_cert_verifier(AllowAnyAnonymousOrAuthenticatedClient::new(root_store))
+    };
+    let server_config = builder.with_single_cert(certs, keys.remove(0))?;
 
     Ok(server_config)
 }
@@ -1,17 +1,26 @@
 //! Tests for peer-to-peer communication.
 
-use super::{CommsError, Event, Node, TcpTlsConfig};
-use crate::constants::NETWORK_VERSION;
-use crate::interfaces::NodeType;
+use super::peer_discovery::{announce_signable, unix_now_secs, NodeIdentity, SignedPeerList};
+use super::{
+    split_into_frame_batches, CommsError, ConnectRetryPolicy, Event, Node, ReconnectBackoff,
+    TcpTlsConfig,
+};
+use crate::constants::{NETWORK_VERSION, PEER_RECONNECT_QUEUE_DEPTH};
+use crate::interfaces::{MempoolRequest, NodeType};
+use crate::raft::RaftMessageWrapper;
 use crate::test_utils::{get_bound_common_tls_configs, get_common_tls_config, get_test_tls_spec};
 use crate::utils::tracing_log_try_init;
 use bincode::deserialize;
 use futures::future::join_all;
+use raft::prelude::Message as RaftMessage;
 use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::time::Duration;
+use tokio::net::TcpListener;
 use tokio::time;
 use tracing::debug;
+use tw_chain::crypto::sign_ed25519 as sign;
+use tw_chain::primitives::transaction::Transaction;
 
 const TIMEOUT_TEST_WAIT_DURATION: Duration = Duration::from_millis(5000);
 
@@ -575,6 +584,507 @@ async fn nodes_tls_ca_unmapped_mismatch() {
     complete_mempool_nodes(nodes).await;
 }
 
+/// Check nodes fail fast rather than hang when only one side uses TLS.
+#[tokio::test(flavor = "current_thread")]
+async fn nodes_tls_mode_mismatch() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let raw_address = "127.0.0.1:0".parse().unwrap();
+    let configs = vec![
+        get_common_tls_config(),
+        TcpTlsConfig::new_no_tls(raw_address),
+    ];
+    let mut nodes = create_config_mempool_nodes(configs, 4).await;
+    let (n1, tail) = nodes.split_first_mut().unwrap();
+    let (n2, _) = tail.split_first_mut().unwrap();
+    let fail_fast_duration = Duration::from_secs(2);
+
+    //
+    // Act
+    //
+    let actual_c1_2 = time::timeout(fail_fast_duration, n1.connect_to(n2.local_address())).await;
+    let actual_c2_1 = time::timeout(fail_fast_duration, n2.connect_to(n1.local_address())).await;
+
+    //
+    // Assert
+    //
+    let actual = (actual_c1_2, actual_c2_1);
+    assert!(
+        matches!(
+            actual,
+            (
+                Ok(Err(CommsError::TlsModeMismatch(_))),
+                Ok(Err(CommsError::PeerNotFound(_)))
+            )
+        ),
+        "{:?}",
+        "{actual:?}"
+    );
+
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check nodes only connect to pinned peers presenting their pinned certificate.
+#[tokio::test(flavor = "current_thread")]
+async fn nodes_tls_pinned_certificates() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let configs =
+        get_bound_common_tls_configs(&["mempool1", "mempool2", "mempool3"], |name, mut s| {
+            if name == "mempool1.aiblock.ch" {
+                let mempool2_cert = s.pem_certificates["mempool2.aiblock.ch"].clone();
+                let pinned = &mut s.pinned_pem_certificates;
+                pinned.insert("mempool2.aiblock.ch".to_owned(), mempool2_cert.clone());
+                pinned.insert("mempool3.aiblock.ch".to_owned(), mempool2_cert);
+            }
+            s
+        })
+        .await;
+    let mut nodes = create_config_mempool_nodes(configs, 4).await;
+    let (n1, tail) = nodes.split_first_mut().unwrap();
+    let (n2, tail) = tail.split_first_mut().unwrap();
+    let (n3, _) = tail.split_first_mut().unwrap();
+
+    //
+    // Act
+    //
+    let actual_c1_2 = n1.connect_to(n2.local_address()).await;
+    let actual_c1_3 = n1.connect_to(n3.local_address()).await;
+    let actual_c3_1 = n3.connect_to(n1.local_address()).await;
+    let actual_s1_2 = n1.send(n2.local_address(), "Hello2").await;
+    let actual_s2_1 = n2.send(n1.local_address(), "Hello1").await;
+
+    //
+    // Assert
+    //
+    let actual = (
+        (actual_c1_2, actual_s1_2, actual_s2_1),
+        (actual_c1_3, actual_c3_1),
+    );
+    assert!(
+        matches!(
+            actual,
+            (
+                (Ok(_), Ok(_), Ok(_)),
+                (
+                    Err(CommsError::PeerCertificateMismatch(_)),
+                    Err(CommsError::PeerNotFound(_))
+                )
+            )
+        ),
+        "{:?}",
+        "{actual:?}"
+    );
+
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check node certificate, key and CA bundle can be loaded from files.
+#[test]
+fn tls_config_from_paths() {
+    //
+    // Arrange
+    //
+    let test_tls_spec = get_test_tls_spec();
+    let address = "127.0.0.1:0".parse().unwrap();
+    let mapping = std::iter::once((address, "node.aiblock.ch".to_owned())).collect();
+    let cert = test_tls_spec.pem_certificates["mempool1.aiblock.ch"].clone();
+    let key = test_tls_spec.pem_pkcs8_private_keys["mempool1.aiblock.ch"].clone();
+    let ca_bundle = test_tls_spec.pem_certificates["ca_root.aiblock.ch"].clone();
+
+    let dir = std::env::temp_dir().join(format!("tls_config_from_paths_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str, content: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        Some(path.to_string_lossy().into_owned())
+    };
+
+    let mut tls_spec = test_tls_spec.make_tls_spec(&mapping);
+    tls_spec.pem_certificates.remove("node.aiblock.ch");
+    tls_spec.pem_certificate_path = path("cert.pem", &cert);
+    tls_spec.pem_pkcs8_private_key_path = path("key.pem", &key);
+    tls_spec.pem_ca_bundle_path = path("ca_bundle.pem", &ca_bundle);
+
+    //
+    // Act
+    //
+    let mut config = TcpTlsConfig::from_tls_spec(address, &tls_spec).unwrap();
+    let missing_path = {
+        let mut tls_spec = tls_spec.clone();
+        tls_spec.pem_certificate_path = Some(dir.join("missing.pem").to_string_lossy().into());
+        TcpTlsConfig::from_tls_spec(address, &tls_spec)
+    };
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    //
+    // Assert
+    //
+    let private_info = config.clone_private_info();
+    assert_eq!(private_info.pem_certs, cert);
+    assert_eq!(private_info.pem_pkcs8_private_keys, key);
+    assert_eq!(config.mut_trusted_pem_certs().last(), Some(&ca_bundle));
+    assert!(matches!(missing_path, Err(CommsError::Io(_))));
+}
+
+/// Check that a bootstrap node serves a signed list of the peers that announced
+/// themselves, omitting the peers that opted out of being listed.
+#[tokio::test(flavor = "current_thread")]
+async fn peer_discovery_signed_peer_list() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let mut nodes = create_mempool_nodes(4, 4).await;
+    let identities: Vec<_> = (0..3)
+        .map(|_| NodeIdentity::from_config(&Default::default()).unwrap())
+        .collect();
+    let bootstrap_key = identities[0].public_key;
+    for ((node, identity), listed) in nodes.iter_mut().zip(identities).zip([true, true, false]) {
+        node.set_peer_discovery(identity, listed).await;
+    }
+
+    let (bootstrap, tail) = nodes.split_first_mut().unwrap();
+    let (listed, tail) = tail.split_first_mut().unwrap();
+    let (unlisted, tail) = tail.split_first_mut().unwrap();
+    let (discoverer, _) = tail.split_first_mut().unwrap();
+    let bootstrap_addr = bootstrap.local_address();
+
+    for node in [listed, unlisted] {
+        node.connect_to(bootstrap_addr).await.unwrap();
+        node.send(bootstrap_addr, "Announced").await.unwrap();
+        // Announcement is handled before the following frame is delivered
+        bootstrap.next_event().await.unwrap();
+    }
+
+    //
+    // Act
+    //
+    let entries = discoverer
+        .discover_peers(bootstrap_addr, Some(bootstrap_key))
+        .await
+        .unwrap();
+    let wrong_signer = NodeIdentity::from_config(&Default::default())
+        .unwrap()
+        .public_key;
+    let invalid = discoverer
+        .discover_peers(bootstrap_addr, Some(wrong_signer))
+        .await;
+
+    //
+    // Assert
+    //
+    let actual: Vec<_> = entries.iter().map(|e| e.address).collect();
+    let expected = vec![bootstrap_addr, nodes[1].local_address()];
+    assert_eq!(actual, expected);
+    assert!(matches!(invalid, Err(CommsError::PeerListInvalid(_))));
+
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check that an announce signature is only valid for the listener address and
+/// nonce it was issued for, and that old peer lists are rejected as stale.
+#[test]
+fn peer_discovery_replay_rejected() {
+    //
+    // Arrange
+    //
+    let identity = NodeIdentity::from_config(&Default::default()).unwrap();
+    let address: SocketAddr = "127.0.0.1:12300".parse().unwrap();
+    let other_address: SocketAddr = "127.0.0.1:12301".parse().unwrap();
+    let now = unix_now_secs();
+
+    //
+    // Act
+    //
+    let signature = identity.sign_announce(NodeType::Mempool, address, 1);
+    let verify = |address, nonce| {
+        let signable = announce_signable(NodeType::Mempool, &identity.public_key, address, nonce);
+        sign::verify_detached(&signature, &signable, &identity.public_key)
+    };
+    let fresh_list = SignedPeerList::new(Vec::new(), now, &identity);
+    let old_list = SignedPeerList::new(Vec::new(), 0, &identity);
+
+    //
+    // Assert
+    //
+    assert!(verify(address, 1));
+    assert!(!verify(address, 2));
+    assert!(!verify(other_address, 1));
+    assert!(!fresh_list.is_stale(now));
+    assert!(old_list.verify(None));
+    assert!(old_list.is_stale(now));
+}
+
+/// Check that a frame over the receiver size limit is rejected and reported,
+/// while the same transactions split into frame batches are all received.
+#[tokio::test(flavor = "current_thread")]
+async fn frame_size_limit_exceeded() {
+    let _ = tracing_log_try_init();
+
+    let mut nodes = create_mempool_nodes(2, 2).await;
+    let (n1, tail) = nodes.split_first_mut().unwrap();
+    let (n2, _) = tail.split_first_mut().unwrap();
+    n1.set_max_frame_size(64 * 1_024);
+
+    let transactions: BTreeMap<String, Transaction> = (0..20_000)
+        .map(|i| (format!("{i:064}"), Transaction::new()))
+        .collect();
+
+    // Oversized frame
+    n2.connect_to(n1.local_address()).await.unwrap();
+    n2.send(n1.local_address(), &transactions).await.unwrap();
+    let oversized_event = time::timeout(TIMEOUT_TEST_WAIT_DURATION, n1.next_event()).await;
+
+    // Batched frames, once the rejecting connection is closed
+    let closed = time::timeout(TIMEOUT_TEST_WAIT_DURATION, async {
+        while !n2.get_peers().await.is_empty() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+    closed.await.unwrap();
+    n2.connect_to(n1.local_address()).await.unwrap();
+    let batches = split_into_frame_batches(transactions.into_iter().collect(), n1.max_frame_size());
+    let batches_len = batches.len();
+    for batch in batches {
+        n2.send(n1.local_address(), batch).await.unwrap();
+    }
+    let mut received_len = 0;
+    for _ in 0..batches_len {
+        match time::timeout(TIMEOUT_TEST_WAIT_DURATION, n1.next_event()).await {
+            Ok(Some(Event::NewFrame { peer: _, frame })) => {
+                let batch: Vec<(String, Transaction)> = deserialize(&frame).unwrap();
+                received_len += batch.len();
+            }
+            other => panic!("Unexpected event {other:?}"),
+        }
+    }
+
+    assert!(matches!(
+        oversized_event,
+        Ok(Some(Event::FrameSizeLimitExceeded { peer: _ }))
+    ));
+    assert!(batches_len > 1);
+    assert_eq!(received_len, 20_000);
+
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check that reconnection delays double from the base delay up to the cap,
+/// the jitter taking at most half of the delay off.
+#[test]
+fn reconnect_backoff_delays() {
+    //
+    // Arrange
+    //
+    let backoff = ReconnectBackoff {
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(1_000),
+    };
+    let mut rng = rand::thread_rng();
+
+    //
+    // Act
+    //
+    let delays: Vec<_> = (0..6).map(|a| backoff.delay(a).as_millis()).collect();
+    let jittered: Vec<_> = (0..60)
+        .map(|a| {
+            (
+                backoff.delay(a % 6),
+                backoff.delay_with_jitter(a % 6, &mut rng),
+            )
+        })
+        .collect();
+
+    //
+    // Assert
+    //
+    assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+    assert_eq!(backoff.delay(u32::MAX), backoff.max_delay);
+    assert!(
+        jittered.iter().all(|(d, j)| *j <= *d && *j >= *d / 2),
+        "{jittered:?}"
+    );
+}
+
+/// Check that a raft message sent to a tracked peer while it restarts is queued,
+/// and delivered once the peer is reconnected to.
+#[tokio::test(flavor = "current_thread")]
+async fn reconnect_tracked_peer_delivers_queued_message() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let mut nodes = create_mempool_nodes(2, 2).await;
+    let (n1, tail) = nodes.split_first_mut().unwrap();
+    let (n2, _) = tail.split_first_mut().unwrap();
+    let n1_addr = n1.local_address();
+    n2.set_reconnect_max_delay(Duration::from_millis(200));
+    n2.track_peers(&[n1_addr]).await;
+    n2.connect_to(n1_addr).await.unwrap();
+
+    let mut raft_msg = RaftMessage::new();
+    raft_msg.set_to(1);
+    raft_msg.set_from(2);
+    raft_msg.set_term(7);
+    let request = MempoolRequest::SendRaftCmd(RaftMessageWrapper(raft_msg.clone()));
+
+    //
+    // Act
+    //
+    join_all(n1.stop_listening().await).await;
+    n1.notify_disconnect_all().await;
+    join_all(n1.disconnect_all(None).await).await;
+
+    let unreachable_event = time::timeout(TIMEOUT_TEST_WAIT_DURATION, n2.next_event()).await;
+    let queued = n2.send(n1_addr, &request).await;
+
+    let tls_spec = get_test_tls_spec().make_tls_spec(
+        &vec![(n1_addr, "node.aiblock.ch".to_owned())]
+            .into_iter()
+            .collect(),
+    );
+    let tcp_tls_config = TcpTlsConfig::from_tls_spec(n1_addr, &tls_spec).unwrap();
+    let mut restarted = create_config_mempool_nodes(vec![tcp_tls_config], 2).await;
+
+    let reconnected_event = time::timeout(TIMEOUT_TEST_WAIT_DURATION, n2.next_event()).await;
+    let received = time::timeout(TIMEOUT_TEST_WAIT_DURATION, restarted[0].next_event()).await;
+    let reconnecting = n2.reconnecting_peers().await;
+    n2.untrack_peers(None).await;
+
+    //
+    // Assert
+    //
+    assert!(
+        matches!(unreachable_event, Ok(Some(Event::PeerUnreachable { peer })) if peer == n1_addr),
+        "{unreachable_event:?}"
+    );
+    assert!(queued.is_ok(), "{queued:?}");
+    assert!(
+        matches!(reconnected_event, Ok(Some(Event::PeerReconnected { peer })) if peer == n1_addr),
+        "{reconnected_event:?}"
+    );
+    match received {
+        Ok(Some(Event::NewFrame { peer: _, frame })) => {
+            match deserialize::<MempoolRequest>(&frame).unwrap() {
+                MempoolRequest::SendRaftCmd(RaftMessageWrapper(actual)) => {
+                    assert_eq!(actual, raft_msg)
+                }
+                other => panic!("Unexpected request {other:?}"),
+            }
+        }
+        other => panic!("Unexpected event {other:?}"),
+    }
+    assert!(reconnecting.is_empty(), "{reconnecting:?}");
+
+    nodes.append(&mut restarted);
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check that sending to a tracked peer that is not connected fails once its queue is full.
+#[tokio::test(flavor = "current_thread")]
+async fn reconnect_tracked_peer_queue_full() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let mut nodes = create_mempool_nodes(1, 2).await;
+    let n1 = &mut nodes[0];
+    let unknown_addr = "127.0.0.1:1".parse().unwrap();
+    n1.track_peers(&[unknown_addr]).await;
+
+    //
+    // Act
+    //
+    let mut results = Vec::new();
+    for i in 0..=PEER_RECONNECT_QUEUE_DEPTH {
+        results.push(n1.send(unknown_addr, i).await);
+    }
+    n1.untrack_peers(None).await;
+    let untracked = n1.send(unknown_addr, "Hello").await;
+
+    //
+    // Assert
+    //
+    let (last, queued) = results.split_last().unwrap();
+    assert!(queued.iter().all(|r| r.is_ok()));
+    assert!(
+        matches!(last, Err(CommsError::PeerDisconnected(_))),
+        "{last:?}"
+    );
+    assert!(
+        matches!(untracked, Err(CommsError::PeerNotFound(_))),
+        "{untracked:?}"
+    );
+
+    complete_mempool_nodes(nodes).await;
+}
+
+/// Check that connecting with retries keeps attempting a peer dropping the first
+/// two connections, and succeeds once the peer accepts.
+#[tokio::test(flavor = "current_thread")]
+async fn connect_to_with_retry_after_refused_attempts() {
+    let _ = tracing_log_try_init();
+
+    //
+    // Arrange
+    //
+    let mut nodes = create_mempool_nodes(1, 2).await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let peer_addr = listener.local_addr().unwrap();
+    let refusing = tokio::spawn(async move {
+        for _ in 0..2 {
+            let (socket, _) = listener.accept().await.unwrap();
+            drop(socket);
+        }
+    });
+    let policy = ConnectRetryPolicy {
+        backoff: ReconnectBackoff {
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(200),
+        },
+        max_attempts: 50,
+        max_duration: Duration::from_secs(20),
+    };
+
+    //
+    // Act
+    //
+    let mut node = nodes[0].clone();
+    let connecting =
+        tokio::spawn(async move { node.connect_to_with_retry(peer_addr, policy).await });
+    refusing.await.unwrap();
+
+    let tls_spec = get_test_tls_spec().make_tls_spec(
+        &vec![(peer_addr, "node.aiblock.ch".to_owned())]
+            .into_iter()
+            .collect(),
+    );
+    let tcp_tls_config = TcpTlsConfig::from_tls_spec(peer_addr, &tls_spec).unwrap();
+    let mut accepting = create_config_mempool_nodes(vec![tcp_tls_config], 2).await;
+
+    let result = connecting.await.unwrap();
+    let unconnected = nodes[0].unconnected_peers(&[peer_addr]).await;
+
+    //
+    // Assert
+    //
+    assert!(result.is_ok(), "{result:?}");
+    assert!(unconnected.is_empty(), "{unconnected:?}");
+
+    nodes.append(&mut accepting);
+    complete_mempool_nodes(nodes).await;
+}
+
 async fn create_mempool_nodes(num_nodes: usize, peer_limit: usize) -> Vec<Node> {
     let configs = std::iter::repeat_with(get_common_tls_config)
         .take(num_nodes)
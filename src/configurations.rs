@@ -1,4 +1,8 @@
 // use crate::comms_handler::Node;
+use crate::constants::{
+    API_RATE_LIMIT_BURST, API_RATE_LIMIT_MAX_REQUESTS, API_RATE_LIMIT_WINDOW_MS,
+    API_READ_RATE_LIMIT_BURST, API_READ_RATE_LIMIT_MAX_REQUESTS,
+};
 use crate::db_utils::{CustomDbSpec, SimpleDb};
 use crate::interfaces::InitialIssuance;
 use crate::mempool_raft::MinerWhitelist;
@@ -26,6 +30,15 @@ pub struct TlsSpec {
     pub pem_certificate_override: Option<String>,
     /// Private key override to use for this node ignoring pem_pkcs8_private_keys
     pub pem_pkcs8_private_key_override: Option<String>,
+    /// PEM file with the node certificate, used if no certificate override is given
+    pub pem_certificate_path: Option<String>,
+    /// PEM file with the node private key, used if no private key override is given
+    pub pem_pkcs8_private_key_path: Option<String>,
+    /// PEM file with additional CA certificates to add to root store
+    pub pem_ca_bundle_path: Option<String>,
+    /// Certificates peers must present, by name of the peer
+    #[serde(default)]
+    pub pinned_pem_certificates: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,14 +51,56 @@ impl fmt::Debug for TlsSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "TlsSpec({:?}, key({:?}), cert({:?}))",
+            "TlsSpec({:?}, key({:?}), cert({:?}), paths({:?}, {:?}, {:?}), pinned({:?}))",
             &self.socket_name_mapping,
             self.pem_pkcs8_private_key_override,
-            self.pem_certificate_override
+            self.pem_certificate_override,
+            self.pem_pkcs8_private_key_path,
+            self.pem_certificate_path,
+            self.pem_ca_bundle_path,
+            self.pinned_pem_certificates.keys().collect::<Vec<_>>()
         )
     }
 }
 
+/// Configuration info for rate limiting each route of a group of API routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ApiRateLimitSpec {
+    /// Requests a client IP can sustain per window
+    pub max_requests: u32,
+    /// Window in milliseconds over which the requests are counted
+    pub window_ms: u64,
+    /// Requests a client IP can make at once before being limited to the sustained rate
+    pub burst: u32,
+}
+
+/// Configuration info for rate limiting the API routes, per group of routes
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ApiRateLimits {
+    /// Limit of the routes reading stored data, unlimited if unset
+    pub reads: Option<ApiRateLimitSpec>,
+    /// Limit of the routes submitting payments or transactions, unlimited if unset
+    pub writes: Option<ApiRateLimitSpec>,
+}
+
+impl ApiRateLimits {
+    /// Limits used by a node without rate limit configuration
+    pub fn node_default() -> Self {
+        Self {
+            reads: Some(ApiRateLimitSpec {
+                max_requests: API_READ_RATE_LIMIT_MAX_REQUESTS,
+                window_ms: API_RATE_LIMIT_WINDOW_MS,
+                burst: API_READ_RATE_LIMIT_BURST,
+            }),
+            writes: Some(ApiRateLimitSpec {
+                max_requests: API_RATE_LIMIT_MAX_REQUESTS,
+                window_ms: API_RATE_LIMIT_WINDOW_MS,
+                burst: API_RATE_LIMIT_BURST,
+            }),
+        }
+    }
+}
+
 /// Configuration info for unicorn
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UnicornFixedInfo {
@@ -60,12 +115,17 @@ pub struct UnicornFixedInfo {
 /// Configuration info for a TxOut
 #[derive(Debug, Clone, Deserialize)]
 pub struct TxOutSpec {
-    /// Hex encoded public key to seed script_public_key
+    /// Hex encoded public key to seed script_public_key, unused if an address is given
+    #[serde(default)]
     pub public_key: String,
+    /// Address to seed script_public_key
+    #[serde(default)]
+    pub address: Option<String>,
     /// Amount that this TxOut can spend
     #[serde(deserialize_with = "deserialize_token_amount")]
     pub amount: TokenAmount,
-    /// Locktime for the TxOut
+    /// Locktime for the TxOut, spendable from the first block if unset
+    #[serde(default)]
     pub locktime: u64,
 }
 
@@ -99,6 +159,28 @@ pub struct NodeSpec {
     pub address: String,
 }
 
+/// Configuration info for a bootstrap node serving peer lists
+#[derive(Debug, Clone, Deserialize)]
+pub struct BootstrapNodeSpec {
+    pub address: String,
+    /// Hex encoded identity key expected to sign the served peer lists
+    pub identity_public_key: Option<String>,
+}
+
+/// Configuration option for discovering peers through bootstrap nodes
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PeerDiscoveryConfig {
+    /// Hex encoded identity key pair, generated on startup if not provided
+    pub identity_public_key: Option<String>,
+    pub identity_secret_key: Option<String>,
+    /// Do not appear in the peer lists served by other nodes
+    #[serde(default)]
+    pub unlisted: bool,
+    /// Bootstrap nodes to request peer lists from, in order of priority
+    #[serde(default)]
+    pub bootstrap_nodes: Vec<BootstrapNodeSpec>,
+}
+
 /// Configuration option for a mempool node
 #[derive(Debug, Clone, Deserialize)]
 pub struct MempoolNodeConfig {
@@ -124,12 +206,26 @@ pub struct MempoolNodeConfig {
     pub mempool_api_port: u16,
     /// API use TLS
     pub mempool_api_use_tls: bool,
+    /// Rate limits of the API routes (default limits if unset)
+    pub mempool_api_rate_limit: Option<ApiRateLimits>,
     /// Timeout for ticking raft
     pub mempool_raft_tick_timeout: usize,
     /// Timeout duration between mining event pipelines
     pub mempool_mining_event_timeout: usize,
     /// Timeout duration between committing transactions
     pub mempool_transaction_timeout: usize,
+    /// Timeout duration before re-proposing uncommitted transactions
+    pub mempool_proposal_timeout: usize,
+    /// Maximum age of a transaction waiting in the local pool before it is evicted
+    pub mempool_local_tx_max_age: usize,
+    /// Timeout after which the block stored info with most votes is applied below majority
+    pub mempool_block_stored_info_timeout: usize,
+    /// Time to collect block PoWs from miners before verifying them together
+    pub mempool_pow_batch_window: usize,
+    /// Interval between re-sending a mined block to storage until it is acknowledged
+    pub mempool_storage_resend_interval: usize,
+    /// Number of blocks a partial DRUID droplet waits for its counterparts before expiring
+    pub mempool_druid_pool_expiry_blocks: u64,
     /// Transaction hash and TxOut info to use to seed utxo
     pub mempool_seed_utxo: UtxoSetSpec,
     /// String to use for genesis block TxIn
@@ -148,6 +244,8 @@ pub struct MempoolNodeConfig {
     pub backup_block_modulo: Option<u64>,
     /// Check UTXO set block modulo
     pub utxo_re_align_block_modulo: Option<u64>,
+    /// Persist snapshot without RAFT for block that given modulo result in 0 (every block if unset)
+    pub mempool_snapshot_block_modulo: Option<u64>,
     /// Restore backup if true
     pub backup_restore: Option<bool>,
     /// Enable trigger messages to reset the pipeline when it gets stuck
@@ -156,6 +254,10 @@ pub struct MempoolNodeConfig {
     pub mempool_miner_whitelist: MinerWhitelist,
     /// Limit for the number of peers this node can have
     pub peer_limit: usize,
+    /// Maximum size in bytes of a frame accepted from peers (node type default if unset)
+    pub max_frame_size: Option<usize>,
+    /// Maximum delay in milliseconds between reconnection attempts to lost peers (PEER_RECONNECT_MAX_DELAY_MS if unset)
+    pub peer_reconnect_max_delay_ms: Option<u64>,
     /// Limit for the number of sub-peers (miners) this node can have
     pub sub_peer_limit: usize,
     /// Initial issuances
@@ -164,6 +266,18 @@ pub struct MempoolNodeConfig {
     pub tx_status_lifetime: i64,
     /// Activation height for ASERT DAA
     pub activation_height_asert: Option<u64>,
+    /// Maximum number of transactions in a block (BLOCK_SIZE_IN_TX if unset)
+    pub block_size_in_tx: Option<usize>,
+    /// Maximum number of transactions waiting in the pools (TX_POOL_LIMIT if unset)
+    pub tx_pool_limit: Option<usize>,
+    /// Maximum number of proposals in flight before transaction proposals pause (MAX_IN_FLIGHT_PROPOSALS if unset)
+    pub max_in_flight: Option<usize>,
+    /// Block interval in seconds the difficulty is adjusted towards (adjustment disabled if unset)
+    pub target_block_interval_secs: Option<u64>,
+    /// Number of blocks between difficulty adjustments (DIFFICULTY_ADJUSTMENT_PERIOD_BLOCKS if unset)
+    pub difficulty_adjustment_period: Option<u64>,
+    /// Peer discovery identity and listing
+    pub peer_discovery: Option<PeerDiscoveryConfig>,
 }
 
 /// Configuration option for a mempool node that can be shared across peers
@@ -198,6 +312,12 @@ pub struct StorageNodeConfig {
     pub storage_api_port: u16,
     /// API use TLS
     pub storage_api_use_tls: bool,
+    /// PEM file with the API certificate, used instead of the node certificate if set with its key
+    pub storage_api_tls_cert_path: Option<String>,
+    /// PEM file with the API private key, used instead of the node key if set with its certificate
+    pub storage_api_tls_key_path: Option<String>,
+    /// Rate limits of the API routes (default limits if unset)
+    pub storage_api_rate_limit: Option<ApiRateLimits>,
     /// Timeout for ticking raft
     pub storage_raft_tick_timeout: usize,
     /// Timeout for fetch catchup
@@ -210,8 +330,16 @@ pub struct StorageNodeConfig {
     pub backup_restore: Option<bool>,
     /// Limit for the number of peers this node can have
     pub peer_limit: usize,
+    /// Maximum size in bytes of a frame accepted from peers (node type default if unset)
+    pub max_frame_size: Option<usize>,
+    /// Maximum delay in milliseconds between reconnection attempts to lost peers (PEER_RECONNECT_MAX_DELAY_MS if unset)
+    pub peer_reconnect_max_delay_ms: Option<u64>,
     /// Activation height for ASERT DAA
     pub activation_height_asert: Option<u64>,
+    /// Peer discovery identity and listing
+    pub peer_discovery: Option<PeerDiscoveryConfig>,
+    /// Number of most recent blocks kept whole, older block bodies being pruned (archival if unset)
+    pub prune_keep_last_blocks: Option<u64>,
 }
 
 /// Configuration option for a storage node
@@ -247,10 +375,24 @@ pub struct MinerNodeConfig {
     pub mining_api_key: Option<String>,
     /// Limit for the number of peers this node can have
     pub peer_limit: usize,
+    /// Maximum size in bytes of a frame accepted from peers (node type default if unset)
+    pub max_frame_size: Option<usize>,
+    /// Maximum delay in milliseconds between reconnection attempts to lost peers (PEER_RECONNECT_MAX_DELAY_MS if unset)
+    pub peer_reconnect_max_delay_ms: Option<u64>,
     /// Aggregation limit
     pub address_aggregation_limit: Option<usize>,
+    /// Maximum number of outputs merged by one aggregation transaction
+    pub address_aggregation_max_inputs: Option<usize>,
+    /// Aggregate the spendable outputs once above the aggregation limit (true if unset)
+    pub enable_address_aggregation: Option<bool>,
     /// Activation height for ASERT DAA
     pub activation_height_asert: Option<u64>,
+    /// Peer discovery identity and bootstrap nodes, used when no mempool node is configured
+    pub peer_discovery: Option<PeerDiscoveryConfig>,
+    /// Seconds to keep mining the cached block template while the mempool node is unreachable
+    pub outage_mining_secs: Option<u64>,
+    /// Number of threads searching block PoW nonces (available parallelism if unset)
+    pub pow_threads: Option<usize>,
 }
 
 /// Configuration option for a user node
@@ -272,6 +414,12 @@ pub struct UserNodeConfig {
     pub user_api_port: u16,
     /// API use TLS
     pub user_api_use_tls: bool,
+    /// PEM file with the API certificate, used instead of the node certificate if set with its key
+    pub user_api_tls_cert_path: Option<String>,
+    /// PEM file with the API private key, used instead of the node key if set with its certificate
+    pub user_api_tls_key_path: Option<String>,
+    /// Rate limits of the API routes (default limits if unset)
+    pub user_api_rate_limit: Option<ApiRateLimits>,
     /// Wallet seeds
     pub user_wallet_seeds: Vec<WalletTxSpec>,
     /// Option of the passphrase used for encryption
@@ -287,6 +435,10 @@ pub struct UserNodeConfig {
     pub backup_block_modulo: Option<u64>,
     /// Limit for the number of peers this node can have
     pub peer_limit: usize,
+    /// Maximum size in bytes of a frame accepted from peers (node type default if unset)
+    pub max_frame_size: Option<usize>,
+    /// Peer discovery identity and bootstrap nodes, used when no mempool node is configured
+    pub peer_discovery: Option<PeerDiscoveryConfig>,
 }
 
 /// Configuration option for a pre-launch node
@@ -310,6 +462,8 @@ pub struct PreLaunchNodeConfig {
     pub storage_nodes: Vec<String>,
     /// Limit for the number of peers this node can have
     pub peer_limit: usize,
+    /// Maximum size in bytes of a frame accepted from peers (node type default if unset)
+    pub max_frame_size: Option<usize>,
 }
 
 /// Type of node in pre-launch mode
@@ -332,6 +486,48 @@ pub struct UserAutoGenTxSetup {
     pub user_setup_tx_max_count: usize,
 }
 
+/// Relative weights of the operations issued by the load generator
+#[derive(Default, Debug, Copy, Clone, Deserialize)]
+pub struct LoadGenMix {
+    /// Single recipient payments, recipients are Zipf distributed
+    pub payment: u32,
+    /// Payments to several recipients in one transaction
+    pub batch: u32,
+    /// Token swaps between two funding wallets through a DRUID trade
+    pub druid_pair: u32,
+    /// Merge of several outputs of a funding wallet into one
+    pub aggregation: u32,
+}
+
+/// Configuration option for a load generation run
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoadGenConfig {
+    /// Seed for the deterministic operation plan
+    pub seed: u64,
+    /// Number of operations to issue
+    pub operation_count: usize,
+    /// Open-loop arrival rate in operations per second
+    pub rate_per_sec: f64,
+    /// Relative weights of each operation kind
+    pub mix: LoadGenMix,
+    /// Number of distinct payment recipients
+    pub recipient_count: usize,
+    /// Zipf exponent used to pick payment recipients
+    pub zipf_exponent: f64,
+    /// Number of outputs of a batch payment
+    pub batch_outputs: usize,
+    /// Maximum number of outputs merged by an aggregation
+    pub aggregation_inputs: usize,
+    /// Interval between lifecycle observations in milliseconds
+    pub poll_interval_ms: u64,
+    /// Time after which a call to the network is abandoned in milliseconds
+    pub call_timeout_ms: u64,
+    /// Time to wait for outstanding operations once all are issued in milliseconds
+    pub settle_timeout_ms: u64,
+    /// Funding outputs the generator may spend
+    pub wallet_seeds: Vec<WalletTxSpec>,
+}
+
 /// Extra params for Node construction
 #[derive(Default)]
 pub struct ExtraNodeParams {
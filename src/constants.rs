@@ -6,6 +6,9 @@ pub use tw_chain::constants::*;
 pub const REWARD_ISSUANCE_VAL: u8 = 27;
 pub const REWARD_SMOOTHING_VAL: u8 = 8;
 
+/// Version of the block assembly rules, to bump whenever assembly logic changes
+pub const ASSEMBLY_RULESET_VERSION: u32 = 3;
+
 /*------- ISSUANCE CONSTANTS --------*/
 
 pub const ISSUANCE_INTERVALS: usize = 48;
@@ -54,6 +57,12 @@ pub const DB_PATH_LIVE: &str = "live";
 /// Path to wallet DB
 pub const WALLET_PATH: &str = "src/wallet/wallet";
 
+/// Default time in seconds a wallet stays unlocked for
+pub const WALLET_UNLOCK_TIMEOUT_SECS: u64 = 300;
+
+/// Maximum time in seconds a wallet can be unlocked for
+pub const WALLET_UNLOCK_MAX_TIMEOUT_SECS: u64 = 86_400;
+
 /// Key for local addresses in wallet
 pub const KNOWN_ADDRESS_KEY: &str = "a";
 
@@ -77,18 +86,60 @@ pub const INTERNAL_TX_LIMIT: usize = 999;
 /// Default limit on the number of concurrent API connections per node
 pub const API_CONCURRENCY_LIMIT: usize = 100;
 
+/// Default number of requests a client IP can sustain per window on the API routes submitting payments or transactions
+pub const API_RATE_LIMIT_MAX_REQUESTS: u32 = 10;
+
+/// Default number of requests a client IP can make at once on the API routes submitting payments or transactions
+pub const API_RATE_LIMIT_BURST: u32 = 20;
+
+/// Default number of requests a client IP can sustain per window on the API routes reading stored data
+pub const API_READ_RATE_LIMIT_MAX_REQUESTS: u32 = 50;
+
+/// Default number of requests a client IP can make at once on the API routes reading stored data
+pub const API_READ_RATE_LIMIT_BURST: u32 = 100;
+
+/// Default window in milliseconds over which rate limited API requests are counted
+pub const API_RATE_LIMIT_WINDOW_MS: u64 = 1_000;
+
+/// Maximum number of items (hashes, keys, block numbers, addresses) in a single batch API request
+pub const API_BATCH_ITEMS_LIMIT: usize = 1_000;
+
 /// Maximum number of attempts to resend trigger messages before proposing to reset the mining pipeline
 pub const RESEND_TRIGGER_MESSAGES_COMPUTE_LIMIT: usize = 5;
 
+/// Maximum time in milliseconds a shutting down mempool node waits for its proposals in flight to commit
+pub const MEMPOOL_SHUTDOWN_DRAIN_TIMEOUT_MS: u64 = 2_000;
+
 /// Limit for the transaction pool per mempool node
 pub const TX_POOL_LIMIT: usize = 10_000_000;
 
+/// Number of transactions received at once above which they are verified on a blocking worker
+pub const TX_VERIFICATION_BLOCKING_THRESHOLD: usize = 100;
+
+/// Limit for the number of proposals a mempool node may have in flight before it stops proposing transactions
+pub const MAX_IN_FLIGHT_PROPOSALS: usize = 1_000;
+
+/// Number of recent blocks whose fill rate the fee estimate is derived from
+pub const FEE_ESTIMATE_BLOCK_WINDOW: usize = 10;
+
+/// Fee suggested when no pooled transaction is available to estimate it from
+pub const DEFAULT_FEE_ESTIMATE: u64 = 100;
+
+/// Limit for the number of DRUID droplets per mempool node
+pub const DRUID_POOL_LIMIT: usize = 100_000;
+
 /// Limit for the number of PoWs a mempool node may have for UnicornShard creation
 pub const UNICORN_LIMIT: usize = 5;
 
 /// Set the mining difficulty by number of required zeroes
 pub const MINING_DIFFICULTY: usize = 1;
 
+/// Default number of blocks between two difficulty retargets
+pub const DIFFICULTY_ADJUSTMENT_PERIOD_BLOCKS: u64 = 10;
+
+/// Maximum factor the difficulty target may change by at each retarget
+pub const DIFFICULTY_ADJUSTMENT_MAX_FACTOR: u64 = 4;
+
 /// The size of a block in bytes
 pub const BLOCK_SIZE: usize = 1_000_000;
 
@@ -101,6 +152,111 @@ pub const MR_PRIME_ITERS: u32 = 15;
 /// Number of old backups to keep before purging
 pub const OLD_BACKUP_COUNT: usize = 5;
 
+/// Number of entries per chunk of a streamed wallet activity export
+pub const ACTIVITY_EXPORT_CHUNK_ROWS: usize = 500;
+
+/// Time in seconds after which a disconnected peer is removed from the discovery registry
+pub const PEER_REGISTRY_STALE_SECS: u64 = 300;
+
+/// Maximum number of addresses a single UTXO subscription connection may track
+pub const UTXO_SUBSCRIPTION_ADDRESS_CAP: usize = 10_000;
+
+/// Number of most recent blocks kept in memory for UTXO subscription gap replay
+pub const UTXO_SUBSCRIPTION_HISTORY_LEN: usize = 1_000;
+
+/// Maximum number of blocks a single UTXO subscription gap replay may cover
+pub const UTXO_SUBSCRIPTION_MAX_REPLAY_RANGE: u64 = 100;
+
+/// Interval in blocks between UTXO subscription checkpoint messages
+pub const UTXO_SUBSCRIPTION_CHECKPOINT_INTERVAL: u64 = 10;
+
+/// Number of messages a UTXO subscription connection may fall behind before being dropped
+pub const UTXO_SUBSCRIPTION_CAPACITY: usize = 256;
+
+/// Number of most recent block store latencies kept for chain quality statistics
+pub const CHAIN_QUALITY_LATENCY_SAMPLES: usize = 1_000;
+
+/// Rolling window in seconds over which rejected blocks are counted
+pub const CHAIN_QUALITY_REJECTION_WINDOW_SECS: u64 = 3_600;
+
+/// Maximum number of missing block ranges listed in a chain quality report
+pub const CHAIN_QUALITY_MAX_REPORTED_GAPS: usize = 100;
+
+/// Maximum number of out points returned in a single UTXO index page
+pub const UTXO_INDEX_PAGE_LIMIT: usize = 1_000;
+
+/// Maximum number of transactions returned in a single address history page
+pub const ADDRESS_HISTORY_PAGE_LIMIT: usize = 1_000;
+
+/// Maximum number of blocks returned for a single block number range
+pub const BLOCK_RANGE_SPAN_LIMIT: u64 = 1_000;
+
+/// Maximum number of block header summaries returned for a single block number range
+pub const BLOCK_HEADER_RANGE_LIMIT: u64 = 500;
+
+/// Maximum number of blocks sent to a catching up storage peer in a single range
+pub const CATCHUP_BLOCK_RANGE_LIMIT: u64 = 16;
+
+/// Number of stored blocks a block stream subscriber may fall behind before being dropped
+pub const BLOCK_STREAM_CAPACITY: usize = 16;
+
+/// Number of payment events a payment events subscriber may fall behind before being dropped
+pub const PAYMENT_EVENTS_CAPACITY: usize = 64;
+
+/// Number of blocks after sending a payment before it can be cancelled if still unconfirmed
+pub const PENDING_PAYMENT_CANCEL_HORIZON: u64 = 10;
+
+/// Number of decimal places shown in human-readable token amounts
+pub const TOKEN_DISPLAY_DECIMALS: u32 = 8;
+
+/// Time in seconds a miner keeps mining its cached block template while its mempool node is unreachable
+pub const MINER_OUTAGE_MINING_SECS: u64 = 30;
+
+/// Interval in milliseconds at which a miner checks its mempool connection while mining
+pub const MINER_OUTAGE_CHECK_INTERVAL_MS: u64 = 1_000;
+
+/// Interval in milliseconds at which a miner folds its computed hashes into its mining statistics
+pub const MINER_STATS_UPDATE_INTERVAL_MS: u64 = 1_000;
+
+/// Window in seconds over which a miner's instantaneous hash rate is computed
+pub const MINER_STATS_INSTANT_WINDOW_SECS: u64 = 10;
+
+/// Window in seconds over which a miner's average hash rate is computed
+pub const MINER_STATS_AVERAGE_WINDOW_SECS: u64 = 600;
+
+/// Maximum number of most recent block win timestamps kept in mining statistics
+pub const MINER_STATS_MAX_WIN_TIMESTAMPS: usize = 100;
+
+/// Default maximum size of a comms frame received by a storage node, which syncs block ranges
+pub const STORAGE_MAX_FRAME_SIZE: usize = 100 * 1_024 * 1_024;
+
+/// Default maximum size of a comms frame received by a mempool node
+pub const MEMPOOL_MAX_FRAME_SIZE: usize = 64 * 1_024 * 1_024;
+
+/// Default maximum size of a comms frame received by a miner node
+pub const MINER_MAX_FRAME_SIZE: usize = 16 * 1_024 * 1_024;
+
+/// Default maximum size of a comms frame received by a user node
+pub const USER_MAX_FRAME_SIZE: usize = 64 * 1_024 * 1_024;
+
+/// Default maximum size of a comms frame received by a pre-launch node, which imports full databases
+pub const PRE_LAUNCH_MAX_FRAME_SIZE: usize = 100 * 1_024 * 1_024;
+
+/// Delay in milliseconds before the first reconnection attempt to a lost peer, doubled on each failure
+pub const PEER_RECONNECT_BASE_DELAY_MS: u64 = 100;
+
+/// Default maximum delay in milliseconds between reconnection attempts to a lost peer
+pub const PEER_RECONNECT_MAX_DELAY_MS: u64 = 30_000;
+
+/// Maximum number of attempts to connect to a peer before giving up for the round
+pub const PEER_CONNECT_MAX_ATTEMPTS: u32 = 3;
+
+/// Maximum time in milliseconds spent attempting to connect to a peer before giving up for the round
+pub const PEER_CONNECT_MAX_DURATION_MS: u64 = 2_000;
+
+/// Maximum number of messages queued for a peer while it is reconnecting
+pub const PEER_RECONNECT_QUEUE_DEPTH: usize = 1_024;
+
 /// Coinbase locktime constant
 /// TODO: Update to 5 once locktime tests are introduced
 pub const COINBASE_MATURITY: u64 = if cfg!(test) { 0 } else { 100 };
@@ -4,7 +4,7 @@ use crate::constants::{
 };
 use rocksdb::backup::{BackupEngine, BackupEngineOptions};
 use rocksdb::Env;
-use rocksdb::{DBCompressionType, IteratorMode, Options, WriteBatch, DB};
+use rocksdb::{DBCompressionType, Direction, IteratorMode, Options, WriteBatch, DB};
 pub use rocksdb::{Error as DBError, DEFAULT_COLUMN_FAMILY_NAME as DB_COL_DEFAULT};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::{error::Error, fmt};
@@ -225,6 +225,14 @@ impl SimpleDb {
         Ok(())
     }
 
+    /// Flush pending writes of file db to disk
+    pub fn flush(&self) -> Result<()> {
+        if let Self::File { db, .. } = &self {
+            db.flush()?;
+        }
+        Ok(())
+    }
+
     /// Create a column as part of an upgrade if not already open
     pub fn upgrade_create_missing_cf(&mut self, name: &'static str) -> Result<()> {
         match self {
@@ -405,6 +413,35 @@ impl SimpleDb {
         self.iter_cf_clone_pvt(cf)
     }
 
+    /// Get entries from database as iterable db items, starting at the given key
+    pub fn iter_cf_clone_from(
+        &self,
+        cf: &'static str,
+        start: &[u8],
+    ) -> Box<dyn Iterator<Item = DbIteratorItem> + '_> {
+        match self {
+            Self::File { db, .. } => {
+                let cf = db.cf_handle(cf).unwrap();
+                let mode = IteratorMode::From(start, Direction::Forward);
+                let iter = db.iterator_cf(cf, mode).map(|iter_result| {
+                    let (k, v) = iter_result.unwrap();
+                    (k.to_vec(), v.to_vec())
+                });
+                Box::new(iter)
+            }
+            Self::InMemory {
+                key_values,
+                columns,
+            } => {
+                let cf = columns.get(cf).unwrap();
+                let iter = key_values[*cf]
+                    .range(start.to_vec()..)
+                    .map(|(k, v)| (k.clone(), v.clone()));
+                Box::new(iter)
+            }
+        }
+    }
+
     /// Get entries from database as iterable db items for all opened columns
     pub fn iter_all_cf_clone(
         &self,
@@ -0,0 +1,261 @@
+//! Periodic retargeting of the PoW difficulty from consensused block timestamps
+
+use crate::asert::{CompactTarget, Target};
+use crate::constants::{
+    DIFFICULTY_ADJUSTMENT_MAX_FACTOR, DIFFICULTY_ADJUSTMENT_PERIOD_BLOCKS, MINING_DIFFICULTY,
+};
+use serde::{Deserialize, Serialize};
+
+/// Settings of the difficulty adjustment, identical on all raft peers.
+/// Part of the assembly ruleset, so a peer configured differently fails its block votes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultyAdjustmentConfig {
+    /// Block interval in seconds the target aims at
+    pub target_block_interval_secs: u64,
+    /// Number of block intervals between two retargets
+    pub period_blocks: u64,
+}
+
+impl DifficultyAdjustmentConfig {
+    /// Resolve the settings from the node configuration: none if adjustment is disabled
+    ///
+    /// ### Arguments
+    ///
+    /// * `target_block_interval_secs` - Configured block interval, disabling adjustment if unset
+    /// * `period_blocks`              - Configured retarget period, the default if unset
+    pub fn from_config(
+        target_block_interval_secs: Option<u64>,
+        period_blocks: Option<u64>,
+    ) -> Result<Option<Self>, &'static str> {
+        let target_block_interval_secs = match target_block_interval_secs {
+            Some(0) => return Err("Target block interval must be at least one second"),
+            Some(secs) => secs,
+            None => return Ok(None),
+        };
+        let period_blocks = match period_blocks.unwrap_or(DIFFICULTY_ADJUSTMENT_PERIOD_BLOCKS) {
+            0 => return Err("Difficulty adjustment period must be at least one block"),
+            period_blocks => period_blocks,
+        };
+
+        Ok(Some(Self {
+            target_block_interval_secs,
+            period_blocks,
+        }))
+    }
+}
+
+/// Difficulty target retargeted every period of blocks to aim at a block interval.
+/// Only block timestamps agreed through raft are recorded so all peers compute the same target.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultyAdjustment {
+    /// Timestamp of the block starting the current period
+    period_start_timestamp: Option<i64>,
+    /// Number of block intervals recorded in the current period
+    period_intervals: u64,
+    /// Target after the last retarget: leading zeroes difficulty until the first one
+    target: Option<CompactTarget>,
+}
+
+impl DifficultyAdjustment {
+    /// Record the timestamp of a new block, retargeting at the end of each period
+    ///
+    /// ### Arguments
+    ///
+    /// * `timestamp` - Consensused timestamp of the block in seconds
+    /// * `config`    - Settings of the difficulty adjustment
+    pub fn record_block(&mut self, timestamp: i64, config: &DifficultyAdjustmentConfig) {
+        let period_start = match self.period_start_timestamp {
+            Some(period_start) => period_start,
+            None => {
+                self.period_start_timestamp = Some(timestamp);
+                return;
+            }
+        };
+
+        self.period_intervals += 1;
+        if self.period_intervals < config.period_blocks {
+            return;
+        }
+
+        let expected_secs = self.period_intervals * config.target_block_interval_secs;
+        let actual_secs = timestamp.saturating_sub(period_start).max(0) as u64;
+        self.target = Some(retarget(&self.current_target(), actual_secs, expected_secs));
+        self.period_start_timestamp = Some(timestamp);
+        self.period_intervals = 0;
+    }
+
+    /// Target the blocks must currently meet
+    pub fn current_target(&self) -> Target {
+        match self.target {
+            Some(target) => target.expand(),
+            None => Target::from_leading_zeroes(MINING_DIFFICULTY),
+        }
+    }
+
+    /// Difficulty to set in the header of a new block: empty for leading zeroes
+    pub fn header_difficulty(&self) -> Vec<u8> {
+        self.target
+            .map(|target| target.into_array().to_vec())
+            .unwrap_or_default()
+    }
+}
+
+/// Scale the target by the ratio of actual to expected period duration,
+/// by at most DIFFICULTY_ADJUSTMENT_MAX_FACTOR either way.
+/// Slower blocks give a higher (easier) target, faster blocks a lower (harder) one.
+///
+/// ### Arguments
+///
+/// * `target`        - Target during the period
+/// * `actual_secs`   - Time the blocks of the period took
+/// * `expected_secs` - Time the blocks of the period should have taken
+pub fn retarget(target: &Target, actual_secs: u64, expected_secs: u64) -> CompactTarget {
+    if expected_secs == 0 {
+        return target.compact();
+    }
+
+    let lowest = target.scaled(1, DIFFICULTY_ADJUSTMENT_MAX_FACTOR);
+    let highest = target.scaled(DIFFICULTY_ADJUSTMENT_MAX_FACTOR, 1);
+    let scaled = target.scaled(actual_secs, expected_secs);
+    let scaled = scaled.max(lowest).min(highest);
+
+    // Stay within what a hash can meet, and never reach a zero target
+    let scaled = scaled
+        .min(Target::from_leading_zeroes(0))
+        .max(Target::from_leading_zeroes(31));
+    scaled.compact()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: DifficultyAdjustmentConfig = DifficultyAdjustmentConfig {
+        target_block_interval_secs: 10,
+        period_blocks: 5,
+    };
+
+    /// Record blocks `interval_secs` apart, returning the timestamp of the last one
+    fn record_blocks(
+        adjustment: &mut DifficultyAdjustment,
+        start: i64,
+        count: u64,
+        interval_secs: i64,
+    ) -> i64 {
+        let mut timestamp = start;
+        for _ in 0..count {
+            adjustment.record_block(timestamp, &CONFIG);
+            timestamp += interval_secs;
+        }
+        timestamp - interval_secs
+    }
+
+    #[test]
+    fn difficulty_adjustment_retargets_each_period() {
+        //
+        // Arrange
+        //
+        let mut adjustment = DifficultyAdjustment::default();
+        let initial = adjustment.current_target();
+
+        //
+        // Act
+        //
+        let end = record_blocks(&mut adjustment, 1_000, CONFIG.period_blocks, 10);
+        let before_period_end = adjustment.current_target();
+        let before_period_end_header = adjustment.header_difficulty();
+        let end = end + 10;
+        adjustment.record_block(end, &CONFIG);
+        let on_time = adjustment.current_target();
+
+        let end = record_blocks(&mut adjustment, end + 30, CONFIG.period_blocks, 30);
+        let slow = adjustment.current_target();
+
+        record_blocks(&mut adjustment, end + 5, CONFIG.period_blocks, 5);
+        let fast = adjustment.current_target();
+
+        //
+        // Assert
+        //
+        assert_eq!(before_period_end, initial);
+        assert!(before_period_end_header.is_empty());
+        assert_eq!(adjustment.header_difficulty().len(), 4);
+        assert_eq!(on_time, initial.compact().expand());
+        assert!(slow > on_time);
+        assert_eq!(slow, on_time.scaled(3, 1).compact().expand());
+        assert!(fast < slow);
+        assert_eq!(fast, slow.scaled(1, 2).compact().expand());
+    }
+
+    #[test]
+    fn difficulty_adjustment_clamped_per_period() {
+        //
+        // Arrange
+        //
+        let mut adjustment = DifficultyAdjustment::default();
+        let end = record_blocks(&mut adjustment, 0, CONFIG.period_blocks + 1, 10);
+        let initial = adjustment.current_target();
+
+        //
+        // Act
+        //
+        // Blocks 100 times slower than expected
+        let end = record_blocks(&mut adjustment, end + 1_000, CONFIG.period_blocks, 1_000);
+        let slow = adjustment.current_target();
+
+        // All blocks with the same timestamp, then going back in time
+        let end = record_blocks(&mut adjustment, end, CONFIG.period_blocks, 0);
+        let fast = adjustment.current_target();
+        record_blocks(&mut adjustment, end - 1, CONFIG.period_blocks, -1);
+        let backwards = adjustment.current_target();
+
+        //
+        // Assert
+        //
+        assert_eq!(slow, initial.scaled(4, 1).compact().expand());
+        assert_eq!(fast, slow.scaled(1, 4).compact().expand());
+        assert_eq!(backwards, fast.scaled(1, 4).compact().expand());
+    }
+
+    #[test]
+    fn difficulty_adjustment_config_validated() {
+        //
+        // Act
+        //
+        let disabled = DifficultyAdjustmentConfig::from_config(None, Some(0));
+        let default_period = DifficultyAdjustmentConfig::from_config(Some(10), None);
+        let zero_interval = DifficultyAdjustmentConfig::from_config(Some(0), Some(5));
+        let zero_period = DifficultyAdjustmentConfig::from_config(Some(10), Some(0));
+
+        //
+        // Assert
+        //
+        let expected_default_period = DifficultyAdjustmentConfig {
+            target_block_interval_secs: 10,
+            period_blocks: DIFFICULTY_ADJUSTMENT_PERIOD_BLOCKS,
+        };
+        assert_eq!(disabled, Ok(None));
+        assert_eq!(default_period, Ok(Some(expected_default_period)));
+        assert!(zero_interval.is_err());
+        assert!(zero_period.is_err());
+    }
+
+    #[test]
+    fn difficulty_adjustment_no_easier_than_any_hash() {
+        //
+        // Arrange
+        //
+        let easiest = Target::from_leading_zeroes(0);
+
+        //
+        // Act
+        //
+        let target = retarget(&easiest.scaled(1, 2), 1_000, 10);
+
+        //
+        // Assert
+        //
+        assert!(target.expand() <= easiest);
+        assert!(target.expand() > easiest.scaled(1, 4));
+    }
+}
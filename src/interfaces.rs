@@ -1,6 +1,8 @@
+use crate::comms_handler::peer_discovery::SignedPeerList;
 use crate::configurations::MempoolNodeSharedConfig;
 use crate::mempool::MempoolError;
-use crate::mempool_raft::MempoolConsensusedRuntimeData;
+use crate::mempool_raft::{MempoolConsensusedRuntimeData, MinerWhitelist};
+use crate::pool_mining::PoolSolution;
 use crate::raft::{CommittedIndex, RaftMessageWrapper};
 use crate::tracked_utxo::TrackedUtxoSet;
 use crate::unicorn::Unicorn;
@@ -11,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::net::SocketAddr;
+use tw_chain::crypto::sign_ed25519::{PublicKey, Signature};
 use tw_chain::primitives::asset::Asset;
 use tw_chain::primitives::asset::TokenAmount;
 use tw_chain::primitives::block::{Block, BlockHeader};
@@ -93,6 +96,90 @@ pub struct TxStatus {
     pub additional_info: String,
 }
 
+/// Where a transaction is between its submission to the mempool and its storage
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStage {
+    Unknown,
+    Pending,
+    InCurrentBlock,
+    Stored { block_num: u64, block_hash: String },
+}
+
+/// The stage of a transaction, with its status as per the mempool if any
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TxLifecycleStatus {
+    pub stage: TxStage,
+    pub status: Option<TxStatus>,
+}
+
+/// Number of transactions a mempool node holds at each stage before being mined
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolStatus {
+    pub local_tx_pool_len: usize,
+    pub proposed_tx_pool_len: usize,
+    pub consensused_tx_pool_len: usize,
+    pub druid_pool_len: usize,
+}
+
+/// Counters and gauges of a mempool node exposed as metrics
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolMetrics {
+    pub status: MempoolStatus,
+    pub current_block_num: Option<u64>,
+    /// Blocks mined since the node started
+    pub mined_block_count: u64,
+    /// Transactions included in the blocks mined since the node started
+    pub mined_tx_count: u64,
+    pub double_spend_count: u64,
+}
+
+/// Fee suggested for a transaction to be included in the next blocks
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Suggested fee in tokens
+    pub fee: TokenAmount,
+    /// Number of pooled transactions the fee is estimated from
+    pub pooled_txs: usize,
+}
+
+/// Proof of work difficulty a block must meet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoWDifficulty {
+    /// Number of zero bytes the PoW hash must start with
+    LeadingZeroes(usize),
+    /// Hex encoded compact target the PoW hash must be below
+    CompactTarget(String),
+}
+
+/// Difficulty of the block offered to miners, or of the next one if none is being mined
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurrentDifficulty {
+    pub block_num: Option<u64>,
+    pub difficulty: PoWDifficulty,
+}
+
+/// Snapshot of a mempool node's raft state for diagnosing stalled consensus
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolRaftStatus {
+    pub peer_id: u64,
+    pub first_raft_peer: bool,
+    pub leader_id: Option<u64>,
+    pub is_leader: bool,
+    pub local_tx_pool_len: usize,
+    pub proposed_in_flight_len: usize,
+    pub consensused_tx_pool_len: usize,
+    pub tx_druid_pool_len: usize,
+    pub tx_current_block_num: Option<u64>,
+    /// Number of distinct block stored info proposals for the current block
+    pub block_stored_info_proposals: usize,
+    /// Number of peers agreeing on each of these proposals
+    pub block_stored_info_votes: Vec<usize>,
+    /// Attempts to send the mined block to storage not yet acknowledged
+    pub block_to_storage_attempts: usize,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TxStatusType {
     Pending,
@@ -156,6 +243,10 @@ pub struct PaymentResponse {
     pub reason: String,
     pub tx_hash: String,
     pub tx: Option<Transaction>,
+    /// Address the excess of the selected inputs is returned to, if any
+    pub change_address: Option<String>,
+    /// Amount returned to `change_address`
+    pub change_amount: Option<TokenAmount>,
 }
 
 /// Mined block as stored in DB.
@@ -165,6 +256,17 @@ pub struct StoredSerializingBlock {
     pub block: Block,
 }
 
+/// Header level summary of a stored block, stored alongside it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeaderSummary {
+    pub b_num: u64,
+    pub hash: String,
+    pub previous_hash: Option<String>,
+    pub timestamp: i64,
+    pub tx_count: usize,
+    pub mining_tx_count: usize,
+}
+
 /// Common info in all mined block that form a complete block.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CommonBlockInfo {
@@ -207,12 +309,26 @@ pub struct PowInfo {
     pub b_num: u64,
 }
 
+/// Share of the block nonce space a miner searches in a PoW round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoncePartition {
+    pub index: u64,
+    pub count: u64,
+}
+
 /// Transaction hashes that have been mined with DRUID info
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct DruidTxInfo {
     pub tx_hashes: Vec<String>,
 }
 
+/// Transaction touching an address, as listed in the address history
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressTxEntry {
+    pub tx_hash: String,
+    pub block_num: u64,
+}
+
 /// PoW structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProofOfWork {
@@ -406,6 +522,30 @@ pub enum CommMessage {
         id: Token,
     },
     HeartBeatProbe(Token),
+    /// Announce this node identity to a peer, asking to be listed or not.
+    PeerAnnounce {
+        /// Identity key of the announcing node.
+        identity_key: PublicKey,
+        /// Signature of the node type, identity key, listener address and announce nonce.
+        signature: Signature,
+        /// Whether the node wants to appear in served peer lists.
+        listed: bool,
+    },
+    /// Request the list of peers known to a bootstrap node.
+    PeerListRequest,
+    /// Signed list of peers known to a bootstrap node.
+    PeerListResponse(SignedPeerList),
+    /// Notice that the sending node is shutting down and is closing the connection.
+    Disconnect,
+    /// Request the nonce to sign an identity announcement on this connection.
+    PeerAnnounceChallengeRequest,
+    /// Nonce to sign an identity announcement with, valid once on this connection.
+    PeerAnnounceChallenge {
+        /// Listener address the announcing node is known under.
+        address: SocketAddr,
+        /// Random nonce issued for the connection.
+        nonce: u64,
+    },
 }
 
 ///============ STORAGE NODE ============///
@@ -423,6 +563,8 @@ pub enum StorageRequest {
     Store { incoming_contract: Contract },
     Closing,
     SendRaftCmd(RaftMessageWrapper),
+    GetBlockRange { start: u64, end: u64 },
+    SendBlockRange { items: Vec<BlockchainItem> },
 }
 
 impl fmt::Debug for StorageRequest {
@@ -439,6 +581,8 @@ impl fmt::Debug for StorageRequest {
             Store { .. } => write!(f, "Store"),
             Closing => write!(f, "Closing"),
             SendRaftCmd(_) => write!(f, "SendRaftCmd"),
+            GetBlockRange { .. } => write!(f, "GetBlockRange"),
+            SendBlockRange { .. } => write!(f, "SendBlockRange"),
         }
     }
 }
@@ -466,6 +610,23 @@ pub trait StorageInterface {
         item: BlockchainItem,
     ) -> Response;
 
+    /// Get the complete blocks of a range of stored history.
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`  - The requestor address.
+    /// * `start` - The first block number of the range.
+    /// * `end`   - The last block number of the range.
+    fn get_block_range(&mut self, peer: SocketAddr, start: u64, end: u64) -> Response;
+
+    /// Receive the complete blocks of a range from storage node.
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`  - The requestor address.
+    /// * `items` - The blockchain items of the blocks in order.
+    fn receive_block_range(&mut self, peer: SocketAddr, items: Vec<BlockchainItem>) -> Response;
+
     /// Returns a read only section of a stored history.
     /// Time slices are considered to be block IDs (u64).
     fn get_history(&self, start_time: &u64, end_time: &u64) -> Response;
@@ -537,6 +698,8 @@ pub enum MineRequest {
         reward: TokenAmount,
         block: Option<BlockHeader>,
         b_num: u64,
+        /// Nonce partition to mine `block` in, if partitioned
+        partition: Option<NoncePartition>,
     },
     SendBlockchainItem {
         key: String,
@@ -553,6 +716,16 @@ pub enum MineRequest {
     MinerNotAuthorized,
     MinerApi(MineApiRequest),
     Closing,
+    /// Whether the block template a miner holds queued PoW for is still mined
+    SendMiningTemplateStatus {
+        b_num: u64,
+        template_id: String,
+        current: bool,
+    },
+    /// No new block follows block `b_num` until the network is resumed
+    NetworkPaused {
+        b_num: u64,
+    },
 }
 
 impl fmt::Debug for MineRequest {
@@ -578,6 +751,8 @@ impl fmt::Debug for MineRequest {
                 write!(f, "SetStaticMinerAddress")
             }
             MinerApi(MineApiRequest::GetStaticMinerAddress) => write!(f, "GetStaticMinerAddress"),
+            SendMiningTemplateStatus { .. } => write!(f, "SendMiningTemplateStatus"),
+            NetworkPaused { .. } => write!(f, "NetworkPaused"),
         }
     }
 }
@@ -641,6 +816,11 @@ pub enum MempoolApiRequest {
     SendSharedConfig {
         shared_config: MempoolNodeSharedConfig,
     },
+    PauseNetwork,
+    ResumeNetwork,
+    SendPoolPoW {
+        solution: PoolSolution,
+    },
 }
 
 /// Encapsulates mempool requests & responses.
@@ -685,6 +865,15 @@ pub enum MempoolRequest {
         runtime_data: MempoolConsensusedRuntimeData,
     },
     SendRaftCmd(RaftMessageWrapper),
+    /// Acknowledges the receipt of a mined block by storage
+    SendBlockAck {
+        b_num: u64,
+        block_hash: String,
+    },
+    CheckMiningTemplate {
+        b_num: u64,
+        template_id: String,
+    },
 }
 
 impl fmt::Debug for MempoolRequest {
@@ -703,6 +892,11 @@ impl fmt::Debug for MempoolRequest {
             MempoolApi(MempoolApiRequest::SendSharedConfig { .. }) => {
                 write!(f, "Api::SendSharedConfig")
             }
+            MempoolApi(MempoolApiRequest::PauseNetwork) => write!(f, "Api::PauseNetwork"),
+            MempoolApi(MempoolApiRequest::ResumeNetwork) => write!(f, "Api::ResumeNetwork"),
+            MempoolApi(MempoolApiRequest::SendPoolPoW { ref solution }) => {
+                write!(f, "Api::SendPoolPoW({})", solution.b_num)
+            }
             SendUtxoRequest { .. } => write!(f, "SendUtxoRequest"),
             SendBlockStored(_) => write!(f, "SendBlockStored"),
             SendPoW { ref block_num, .. } => write!(f, "SendPoW({block_num})"),
@@ -718,6 +912,8 @@ impl fmt::Debug for MempoolRequest {
             RequestRuntimeData => write!(f, "RequestRuntimeData"),
             SendRuntimeData { .. } => write!(f, "SendRuntimeData"),
             SendRaftCmd(_) => write!(f, "SendRaftCmd"),
+            SendBlockAck { ref b_num, .. } => write!(f, "SendBlockAck({b_num})"),
+            CheckMiningTemplate { ref b_num, .. } => write!(f, "CheckMiningTemplate({b_num})"),
         }
     }
 }
@@ -760,6 +956,19 @@ pub trait MempoolApi {
     /// Resume all mempool nodes
     fn resume_nodes(&mut self) -> Response;
 
+    /// Pause block generation on all mempool nodes at the same point in the RAFT log
+    fn pause_network(&mut self) -> Response;
+
+    /// Resume block generation on all mempool nodes at the same point in the RAFT log
+    fn resume_network(&mut self) -> Response;
+
+    /// Submit a block PoW found by a pool miner for verification and proposal
+    ///
+    /// ### Arguments
+    ///
+    /// * `solution` - Nonce and coinbase found by the pool miner
+    fn submit_pool_pow(&mut self, solution: PoolSolution) -> Response;
+
     /// Share mempool node config with other mempool nodes
     fn send_shared_config(&mut self, shared_config: MempoolNodeSharedConfig) -> Response;
 
@@ -772,8 +981,53 @@ pub trait MempoolApi {
     /// Get pending DRUID pool
     fn get_pending_druid_pool(&self) -> &DruidPool;
 
-    /// Get the status of transaction/s
-    fn get_transaction_status(&self, tx_hashes: Vec<String>) -> BTreeMap<String, TxStatus>;
+    /// Get the stage and status of transaction/s, once for each distinct hash
+    fn get_transaction_lifecycle(
+        &self,
+        tx_hashes: Vec<String>,
+    ) -> BTreeMap<String, TxLifecycleStatus>;
+
+    /// Get the number of transactions pending consensus
+    fn get_mempool_status(&self) -> MempoolStatus;
+
+    /// Get a snapshot of the raft consensus state
+    fn get_raft_status(&self) -> MempoolRaftStatus;
+
+    /// Get the counters and gauges exposed as metrics
+    fn get_mempool_metrics(&self) -> MempoolMetrics;
+
+    /// Get the difficulty of the block offered to miners
+    fn get_current_difficulty(&self) -> CurrentDifficulty;
+
+    /// Get the fee suggested for a transaction to be included in the next blocks
+    fn estimate_fee(&self) -> FeeEstimate;
+
+    /// Get the miner whitelist
+    fn get_miner_whitelist(&self) -> MinerWhitelist;
+
+    /// Allow miners by API key or address
+    ///
+    /// ### Arguments
+    ///
+    /// * `api_keys`  - Miner API keys to allow
+    /// * `addresses` - Miner addresses to allow
+    fn add_miner_whitelist_entries(
+        &mut self,
+        api_keys: Vec<String>,
+        addresses: Vec<SocketAddr>,
+    ) -> Response;
+
+    /// Deny miners previously allowed by API key or address
+    ///
+    /// ### Arguments
+    ///
+    /// * `api_keys`  - Miner API keys to deny
+    /// * `addresses` - Miner addresses to deny
+    fn remove_miner_whitelist_entries(
+        &mut self,
+        api_keys: Vec<String>,
+        addresses: Vec<SocketAddr>,
+    ) -> Response;
 
     /// Receives transactions to be bundled into blocks
     ///
@@ -782,6 +1036,14 @@ pub trait MempoolApi {
     /// * `transactions` - Transactions to be added into blocks.
     fn receive_transactions(&mut self, transactions: Vec<Transaction>) -> Response;
 
+    /// Receives a single signed transaction after checking its inputs
+    /// against the committed UTXO set
+    ///
+    /// ### Arguments
+    ///
+    /// * `transaction` - Transaction to be added into blocks.
+    fn submit_transaction(&mut self, transaction: Transaction) -> Response;
+
     /// Creates a new set of item assets
     fn create_item_asset_tx(
         &mut self,
@@ -803,6 +1065,14 @@ pub trait UserApi {
         amount: TokenAmount,
         locktime: Option<u64>,
     ) -> PaymentResponse;
+
+    fn make_item_payment(
+        &mut self,
+        address: String,
+        item_amount: u64,
+        genesis_hash: String,
+        locktime: Option<u64>,
+    ) -> PaymentResponse;
 }
 
 /// Encapsulates user requests injected by API
@@ -955,6 +1225,23 @@ pub struct DebugData {
     pub routes_pow: BTreeMap<String, usize>,
 }
 
+/// API Node Info Struct
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NodeInfo {
+    pub version: String,
+    pub node_type: String,
+    pub raft: Option<NodeRaftInfo>,
+}
+
+/// Raft role of a node, for nodes running a raft
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NodeRaftInfo {
+    pub peers_len: usize,
+    pub first_raft_peer: bool,
+    pub leader_id: Option<u64>,
+    pub is_leader: bool,
+}
+
 /// Encapsulates storage requests
 #[derive(Deserialize, Serialize, Clone)]
 pub enum PreLaunchRequest {
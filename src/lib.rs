@@ -7,15 +7,21 @@ mod active_raft;
 mod api;
 mod asert;
 mod block_pipeline;
+mod canonical;
+mod chain_quality;
 pub mod comms_handler;
 pub mod configurations;
 mod constants;
 pub mod db_utils;
+mod difficulty_adjustment;
 pub mod interfaces;
 pub mod key_creation;
+pub mod load_gen;
 mod mempool;
 mod mempool_raft;
 mod miner;
+mod miner_stats;
+mod pool_mining;
 mod pre_launch;
 mod raft;
 mod raft_store;
@@ -35,9 +41,11 @@ mod unicorn;
 pub mod upgrade;
 mod user;
 pub mod utils;
+mod utxo_subscription;
 pub mod wallet;
 
 pub use api::routes;
+pub use api::utils::serve_api_routes;
 pub use constants::SANC_LIST_PROD;
 pub use interfaces::Rs2JsMsg;
 pub use interfaces::{MempoolRequest, MinerInterface, Response, StorageInterface};
@@ -49,9 +57,10 @@ pub use transaction_gen::TransactionGen;
 pub use user::UserNode;
 pub use utils::LocalEvent;
 pub use utils::{
-    create_and_save_fake_to_wallet, create_valid_transaction, get_sanction_addresses,
-    get_test_common_unicorn, loop_connnect_to_peers_async, loop_wait_connnect_to_peers_async,
-    loops_re_connect_disconnect, shutdown_connections, ResponseResult,
+    create_and_save_fake_to_wallet, create_valid_transaction, exit_on_shutdown_signal,
+    get_sanction_addresses, get_test_common_unicorn, loop_connnect_to_peers_async,
+    loop_wait_connnect_to_peers_async, loops_re_connect_disconnect, shutdown_connections,
+    ResponseResult,
 };
 pub use wallet::WalletDb;
 
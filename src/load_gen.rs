@@ -0,0 +1,848 @@
+//! Reproducible load generation against a running network.
+//!
+//! A `LoadGenerator` plans a deterministic stream of operations from a seed
+//! (Zipf distributed payments, multi-output batches, DRUID trade pairs and wallet
+//! aggregations), issues them at an open-loop arrival rate against a `LoadTarget`
+//! and follows each operation through its lifecycle stages until it is stored.
+//! Latencies are measured from the time an operation was scheduled, not from the
+//! time it was actually sent, so a slow target cannot hide its own backlog.
+
+use crate::configurations::{LoadGenConfig, LoadGenMix, WalletTxSpec};
+use crate::utils::make_wallet_tx_info;
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+use tracing::{debug, warn};
+use tw_chain::crypto::sha3_256;
+use tw_chain::crypto::sign_ed25519::{self as sign, PublicKey, SecretKey};
+use tw_chain::primitives::asset::{Asset, TokenAmount};
+use tw_chain::primitives::druid::{DdeValues, DruidExpectation};
+use tw_chain::primitives::transaction::{OutPoint, Transaction, TxConstructor, TxIn, TxOut};
+use tw_chain::utils::transaction_utils::{
+    construct_address_for, construct_payment_tx_ins, construct_tx_core, construct_tx_hash,
+    construct_tx_in_signable_hash, construct_tx_ins_address,
+};
+
+/// Lifecycle stages an operation goes through, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LoadStage {
+    Accepted,
+    Consensused,
+    InBlock,
+    Stored,
+}
+
+impl LoadStage {
+    /// All stages in lifecycle order
+    pub const ALL: [LoadStage; 4] = [
+        LoadStage::Accepted,
+        LoadStage::Consensused,
+        LoadStage::InBlock,
+        LoadStage::Stored,
+    ];
+}
+
+/// The kind of operation issued by the generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LoadOpKind {
+    Payment,
+    Batch,
+    DruidPair,
+    Aggregation,
+}
+
+/// What a target knows about a single submitted transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageObservation {
+    /// The furthest lifecycle stage the transaction is known to have reached
+    Reached(LoadStage),
+    /// The transaction was rejected with the given reason
+    Rejected(String),
+    /// Nothing is known about the transaction yet
+    Unknown,
+}
+
+/// A network the generator can drive
+#[async_trait]
+pub trait LoadTarget {
+    /// Submit transactions to be bundled into blocks.
+    /// An error rejects every transaction submitted with the returned reason.
+    async fn submit(&mut self, transactions: Vec<Transaction>) -> Result<(), String>;
+
+    /// Report what is known about each of the given transactions
+    async fn observe(
+        &mut self,
+        tx_hashes: Vec<String>,
+    ) -> Result<BTreeMap<String, StageObservation>, String>;
+}
+
+/// A single operation of the deterministic plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedOp {
+    pub kind: LoadOpKind,
+    /// Index of the funding wallet, and of the counterparty for DRUID pairs
+    pub wallets: (usize, usize),
+    /// Indexes of the recipients paid by this operation
+    pub recipients: Vec<usize>,
+    /// Amount paid to each recipient
+    pub amount: u64,
+}
+
+/// Final outcome of an operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoadOpOutcome {
+    /// Still making its way through the lifecycle
+    InFlight,
+    /// Reached the final lifecycle stage
+    Stored,
+    /// Rejected by the target with the given reason
+    Rejected(String),
+    /// Not submitted because its wallets had no spendable outputs
+    Starved,
+}
+
+/// Latency summary for a single lifecycle stage, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageLatency {
+    /// Number of operations that reached the stage
+    pub reached: usize,
+    /// Number of those for which the stage was inferred from a later stage
+    pub inferred: usize,
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+}
+
+/// Machine-readable summary of a load generation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadReport {
+    /// Seed the plan was generated from
+    pub seed: u64,
+    /// Hex encoded hash of the plan: equal for runs with the same configuration
+    pub plan_digest: String,
+    pub operations: usize,
+    pub operations_by_kind: BTreeMap<LoadOpKind, usize>,
+    pub submitted: usize,
+    pub stored: usize,
+    pub rejected: usize,
+    pub starved: usize,
+    /// Operations neither stored nor rejected when the run finished
+    pub unresolved: usize,
+    /// Observations reporting an earlier stage than previously seen
+    pub stage_regressions: usize,
+    pub elapsed_ms: u64,
+    /// Stored operations per second over the whole run
+    pub throughput_ops_per_sec: f64,
+    pub stages: BTreeMap<LoadStage, StageLatency>,
+    pub rejections: BTreeMap<String, usize>,
+}
+
+/// Key material and spendable outputs of a funding wallet
+#[derive(Debug, Clone)]
+struct LoadWallet {
+    pk: PublicKey,
+    sk: SecretKey,
+    address: String,
+    version: Option<u64>,
+    ready: Vec<(OutPoint, TokenAmount)>,
+}
+
+/// Transaction issued for an operation
+#[derive(Debug, Clone)]
+struct LoadTx {
+    hash: String,
+    stage: Option<LoadStage>,
+    /// Outputs returned to the wallet if the transaction is rejected
+    inputs: Vec<(usize, OutPoint, TokenAmount)>,
+    /// Outputs spendable by the wallet once the transaction is in a block
+    change: Vec<(usize, OutPoint, TokenAmount)>,
+}
+
+/// Progress of a single operation
+#[derive(Debug, Clone)]
+struct OpRecord {
+    scheduled: Instant,
+    txs: Vec<LoadTx>,
+    stage: Option<LoadStage>,
+    stages: BTreeMap<LoadStage, (Duration, bool)>,
+    outcome: LoadOpOutcome,
+}
+
+/// Samples ranks `0..n` following a Zipf distribution
+#[derive(Debug, Clone)]
+pub struct ZipfSampler {
+    cdf: Vec<f64>,
+}
+
+impl ZipfSampler {
+    /// Create a sampler over `n` ranks with the given exponent
+    ///
+    /// ### Arguments
+    ///
+    /// * `n`        - Number of ranks
+    /// * `exponent` - Zipf exponent, 0 is uniform
+    pub fn new(n: usize, exponent: f64) -> Self {
+        let weights: Vec<f64> = (1..=n.max(1))
+            .map(|k| 1.0 / (k as f64).powf(exponent))
+            .collect();
+        let total: f64 = weights.iter().sum();
+
+        let mut acc = 0.0;
+        let cdf = weights
+            .into_iter()
+            .map(|w| {
+                acc += w / total;
+                acc
+            })
+            .collect();
+
+        Self { cdf }
+    }
+
+    /// Sample a rank, 0 being the most likely
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let u: f64 = rng.gen();
+        let rank = self.cdf.partition_point(|c| *c < u);
+        rank.min(self.cdf.len() - 1)
+    }
+}
+
+/// Drives a deterministic operation mix against a `LoadTarget`
+#[derive(Debug)]
+pub struct LoadGenerator {
+    config: LoadGenConfig,
+    plan: Vec<PlannedOp>,
+    recipients: Vec<String>,
+    wallets: Vec<LoadWallet>,
+    ops: Vec<OpRecord>,
+    tx_to_op: BTreeMap<String, usize>,
+    stage_regressions: usize,
+}
+
+impl LoadGenerator {
+    /// Create a generator and its operation plan from configuration
+    ///
+    /// ### Arguments
+    ///
+    /// * `config` - Load generation configuration
+    pub fn new(config: LoadGenConfig) -> Self {
+        let wallets = make_wallets(&config.wallet_seeds);
+        let recipients = (0..config.recipient_count.max(1))
+            .map(|i| make_recipient_address(config.seed, i))
+            .collect();
+        let plan = make_plan(&config, wallets.len());
+
+        Self {
+            config,
+            plan,
+            recipients,
+            wallets,
+            ops: Vec::new(),
+            tx_to_op: BTreeMap::new(),
+            stage_regressions: 0,
+        }
+    }
+
+    /// The deterministic operation plan
+    pub fn plan(&self) -> &[PlannedOp] {
+        &self.plan
+    }
+
+    /// Run the whole plan against the target and summarize the results
+    ///
+    /// ### Arguments
+    ///
+    /// * `target` - Network to drive
+    pub async fn run<T: LoadTarget + Send>(&mut self, target: &mut T) -> LoadReport {
+        let interval = Duration::from_secs_f64(1.0 / self.config.rate_per_sec.max(f64::EPSILON));
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms.max(1));
+        let start = Instant::now();
+        let mut next_poll = start + poll_interval;
+
+        for index in 0..self.plan.len() {
+            let due = start + interval * index as u32;
+            while Instant::now() < due {
+                time::sleep_until(due.min(next_poll)).await;
+                if Instant::now() >= next_poll {
+                    self.poll(target).await;
+                    next_poll = Instant::now() + poll_interval;
+                }
+            }
+            self.dispatch(index, due, target).await;
+        }
+
+        let settle_deadline = Instant::now() + Duration::from_millis(self.config.settle_timeout_ms);
+        while self.in_flight_count() > 0 && Instant::now() < settle_deadline {
+            time::sleep_until(next_poll.min(settle_deadline)).await;
+            self.poll(target).await;
+            next_poll = Instant::now() + poll_interval;
+        }
+
+        self.report(start.elapsed())
+    }
+
+    /// Build, submit and track the planned operation at `index`
+    async fn dispatch<T: LoadTarget + Send>(
+        &mut self,
+        index: usize,
+        scheduled: Instant,
+        target: &mut T,
+    ) {
+        let planned = self.plan[index].clone();
+        let built = match planned.kind {
+            LoadOpKind::Payment | LoadOpKind::Batch => self.build_payment(&planned),
+            LoadOpKind::DruidPair => self.build_druid_pair(&planned, index),
+            LoadOpKind::Aggregation => self.build_aggregation(&planned),
+        };
+
+        let (txs, load_txs) = match built {
+            Some(built) => built.into_iter().unzip::<_, _, Vec<_>, Vec<_>>(),
+            None => {
+                debug!("Load operation {} starved of funds", index);
+                self.push_op(scheduled, Vec::new(), LoadOpOutcome::Starved);
+                return;
+            }
+        };
+
+        let op_index = self.push_op(scheduled, load_txs, LoadOpOutcome::InFlight);
+        let result = self.with_timeout(target.submit(txs)).await;
+        if let Err(reason) = result {
+            self.reject_op(op_index, reason);
+        }
+    }
+
+    /// Observe all in-flight transactions and advance their operations
+    async fn poll<T: LoadTarget + Send>(&mut self, target: &mut T) {
+        let tx_hashes: Vec<String> = self
+            .ops
+            .iter()
+            .filter(|op| op.outcome == LoadOpOutcome::InFlight)
+            .flat_map(|op| op.txs.iter())
+            .filter(|tx| tx.stage != Some(LoadStage::Stored))
+            .map(|tx| tx.hash.clone())
+            .collect();
+
+        if tx_hashes.is_empty() {
+            return;
+        }
+
+        let observations = match self.with_timeout(target.observe(tx_hashes)).await {
+            Ok(observations) => observations,
+            Err(e) => {
+                warn!("Load target observation failed: {}", e);
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        for (tx_hash, observation) in observations {
+            let op_index = match self.tx_to_op.get(&tx_hash) {
+                Some(op_index) => *op_index,
+                None => continue,
+            };
+
+            match observation {
+                StageObservation::Reached(stage) => self.advance_tx(op_index, &tx_hash, stage, now),
+                StageObservation::Rejected(reason) => self.reject_op(op_index, reason),
+                StageObservation::Unknown => (),
+            }
+        }
+    }
+
+    /// Record a transaction reaching `stage` and update its operation
+    fn advance_tx(&mut self, op_index: usize, tx_hash: &str, stage: LoadStage, now: Instant) {
+        let op = &mut self.ops[op_index];
+        if op.outcome != LoadOpOutcome::InFlight {
+            return;
+        }
+
+        let mut matured = Vec::new();
+        for tx in op.txs.iter_mut().filter(|tx| tx.hash == tx_hash) {
+            if tx.stage > Some(stage) {
+                self.stage_regressions += 1;
+                continue;
+            }
+            if tx.stage < Some(LoadStage::InBlock) && stage >= LoadStage::InBlock {
+                matured.append(&mut tx.change);
+            }
+            tx.stage = Some(stage);
+        }
+
+        let op_stage = op.txs.iter().map(|tx| tx.stage).min().flatten();
+        if op_stage > op.stage {
+            let elapsed = now.saturating_duration_since(op.scheduled);
+            for reached in LoadStage::ALL.iter().copied() {
+                if Some(reached) > op.stage && Some(reached) <= op_stage {
+                    let inferred = Some(reached) != op_stage;
+                    op.stages.insert(reached, (elapsed, inferred));
+                }
+            }
+            op.stage = op_stage;
+        }
+
+        if op.stage == Some(LoadStage::Stored) {
+            op.outcome = LoadOpOutcome::Stored;
+        }
+
+        for (wallet, out_point, amount) in matured {
+            self.wallets[wallet].ready.push((out_point, amount));
+        }
+    }
+
+    /// Reject an in-flight operation, returning its unspent inputs to the wallets
+    fn reject_op(&mut self, op_index: usize, reason: String) {
+        let op = &mut self.ops[op_index];
+        if op.outcome != LoadOpOutcome::InFlight {
+            return;
+        }
+
+        debug!("Load operation {} rejected: {}", op_index, reason);
+        op.outcome = LoadOpOutcome::Rejected(reason);
+        for tx in op.txs.iter_mut() {
+            if tx.stage < Some(LoadStage::InBlock) {
+                for (wallet, out_point, amount) in tx.inputs.drain(..) {
+                    self.wallets[wallet].ready.push((out_point, amount));
+                }
+            }
+        }
+    }
+
+    /// Await a target call, giving up after the configured timeout
+    async fn with_timeout<R>(
+        &self,
+        call: impl Future<Output = Result<R, String>>,
+    ) -> Result<R, String> {
+        let timeout = Duration::from_millis(self.config.call_timeout_ms);
+        match time::timeout(timeout, call).await {
+            Ok(result) => result,
+            Err(_) => Err("Load target call timed out".to_owned()),
+        }
+    }
+
+    fn push_op(&mut self, scheduled: Instant, txs: Vec<LoadTx>, outcome: LoadOpOutcome) -> usize {
+        let op_index = self.ops.len();
+        for tx in &txs {
+            self.tx_to_op.insert(tx.hash.clone(), op_index);
+        }
+        self.ops.push(OpRecord {
+            scheduled,
+            txs,
+            stage: None,
+            stages: BTreeMap::new(),
+            outcome,
+        });
+        op_index
+    }
+
+    fn in_flight_count(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| op.outcome == LoadOpOutcome::InFlight)
+            .count()
+    }
+
+    /// Pay `amount` to each planned recipient from a single wallet output
+    fn build_payment(&mut self, planned: &PlannedOp) -> Option<Vec<(Transaction, LoadTx)>> {
+        let wallet = planned.wallets.0;
+        let (out_point, value) = self.take_ready(wallet, 1)?.pop()?;
+
+        let mut tx_outs = Vec::new();
+        let mut remaining = value.0;
+        for recipient in &planned.recipients {
+            let amount = planned.amount.min(remaining);
+            if amount == 0 {
+                break;
+            }
+            remaining -= amount;
+            tx_outs.push(token_tx_out(&self.recipients[*recipient], amount));
+        }
+
+        let inputs = vec![(wallet, out_point, value)];
+        Some(vec![self.make_tx(inputs, tx_outs, remaining, None)])
+    }
+
+    /// Merge several outputs of a wallet into one
+    fn build_aggregation(&mut self, planned: &PlannedOp) -> Option<Vec<(Transaction, LoadTx)>> {
+        let wallet = planned.wallets.0;
+        let max_inputs = self.config.aggregation_inputs.max(2);
+        if self.wallets[wallet].ready.len() < 2 {
+            return None;
+        }
+
+        let inputs: Vec<_> = self
+            .take_ready(wallet, max_inputs)?
+            .into_iter()
+            .map(|(out_point, amount)| (wallet, out_point, amount))
+            .collect();
+        let total = inputs.iter().map(|(_, _, amount)| amount.0).sum();
+
+        Some(vec![self.make_tx(inputs, Vec::new(), total, None)])
+    }
+
+    /// Two wallets swapping `amount` tokens through a DRUID trade
+    fn build_druid_pair(
+        &mut self,
+        planned: &PlannedOp,
+        index: usize,
+    ) -> Option<Vec<(Transaction, LoadTx)>> {
+        let (first, second) = planned.wallets;
+        if first == second
+            || self.wallets[first].ready.is_empty()
+            || self.wallets[second].ready.is_empty()
+        {
+            return None;
+        }
+
+        let sides: Vec<_> = [first, second]
+            .iter()
+            .map(|wallet| {
+                let (out_point, value) = self.take_ready(*wallet, 1).unwrap().remove(0);
+                let amount = planned.amount.min(value.0);
+                let tx_ins = self.signed_tx_ins(*wallet, &[out_point.clone()]);
+                (*wallet, out_point, value, amount, tx_ins)
+            })
+            .collect();
+
+        let druid = make_druid(self.config.seed, index);
+        let mut built = Vec::new();
+        for (side, (wallet, out_point, value, amount, tx_ins)) in sides.iter().enumerate() {
+            let (other_wallet, _, _, other_amount, other_tx_ins) = &sides[1 - side];
+            let expectation = DruidExpectation {
+                from: construct_tx_ins_address(other_tx_ins),
+                to: self.wallets[*wallet].address.clone(),
+                asset: Asset::Token(TokenAmount(*other_amount)),
+            };
+            let druid_info = DdeValues {
+                druid: druid.clone(),
+                participants: 2,
+                expectations: vec![expectation],
+                genesis_hash: None,
+            };
+
+            let tx_outs = vec![token_tx_out(&self.wallets[*other_wallet].address, *amount)];
+            let inputs = vec![(*wallet, out_point.clone(), *value)];
+            built.push(self.make_tx(inputs, tx_outs, value.0 - amount, Some(druid_info)));
+        }
+
+        Some(built)
+    }
+
+    /// Construct a signed transaction returning `change` to the first input's wallet
+    fn make_tx(
+        &self,
+        inputs: Vec<(usize, OutPoint, TokenAmount)>,
+        mut tx_outs: Vec<TxOut>,
+        change: u64,
+        druid_info: Option<DdeValues>,
+    ) -> (Transaction, LoadTx) {
+        let wallet = inputs[0].0;
+        let out_points: Vec<_> = inputs.iter().map(|(_, o, _)| o.clone()).collect();
+        let tx_ins = self.signed_tx_ins(wallet, &out_points);
+
+        let change_n = tx_outs.len() as i32;
+        if change > 0 {
+            tx_outs.push(token_tx_out(&self.wallets[wallet].address, change));
+        }
+
+        let tx = construct_tx_core(tx_ins, tx_outs, druid_info);
+        let hash = construct_tx_hash(&tx);
+        let change = if change > 0 {
+            let out_point = OutPoint::new(hash.clone(), change_n);
+            vec![(wallet, out_point, TokenAmount(change))]
+        } else {
+            Vec::new()
+        };
+
+        let load_tx = LoadTx {
+            hash,
+            stage: None,
+            inputs,
+            change,
+        };
+        (tx, load_tx)
+    }
+
+    fn signed_tx_ins(&self, wallet: usize, out_points: &[OutPoint]) -> Vec<TxIn> {
+        let wallet = &self.wallets[wallet];
+        let tx_in_cons = out_points
+            .iter()
+            .map(|out_point| {
+                let signable_h = construct_tx_in_signable_hash(out_point);
+                let signature = sign::sign_detached(signable_h.as_bytes(), &wallet.sk);
+                TxConstructor {
+                    previous_out: out_point.clone(),
+                    signatures: vec![signature],
+                    pub_keys: vec![wallet.pk],
+                    address_version: wallet.version,
+                }
+            })
+            .collect();
+
+        construct_payment_tx_ins(tx_in_cons)
+    }
+
+    fn take_ready(&mut self, wallet: usize, count: usize) -> Option<Vec<(OutPoint, TokenAmount)>> {
+        let ready = &mut self.wallets.get_mut(wallet)?.ready;
+        if ready.is_empty() {
+            return None;
+        }
+        let count = count.min(ready.len());
+        Some(ready.drain(..count).collect())
+    }
+
+    /// Summarize the run
+    fn report(&self, elapsed: Duration) -> LoadReport {
+        let count =
+            |outcome: &LoadOpOutcome| self.ops.iter().filter(|op| &op.outcome == outcome).count();
+        let stored = count(&LoadOpOutcome::Stored);
+        let starved = count(&LoadOpOutcome::Starved);
+        let unresolved = count(&LoadOpOutcome::InFlight);
+
+        let mut rejections: BTreeMap<String, usize> = BTreeMap::new();
+        for op in &self.ops {
+            if let LoadOpOutcome::Rejected(reason) = &op.outcome {
+                *rejections.entry(reason.clone()).or_default() += 1;
+            }
+        }
+
+        let mut operations_by_kind: BTreeMap<LoadOpKind, usize> = BTreeMap::new();
+        for planned in &self.plan {
+            *operations_by_kind.entry(planned.kind).or_default() += 1;
+        }
+
+        let stages = LoadStage::ALL
+            .iter()
+            .map(|stage| {
+                let samples: Vec<_> = self
+                    .ops
+                    .iter()
+                    .filter_map(|op| op.stages.get(stage))
+                    .collect();
+                (*stage, stage_latency(&samples))
+            })
+            .collect();
+
+        let elapsed_secs = elapsed.as_secs_f64();
+        LoadReport {
+            seed: self.config.seed,
+            plan_digest: plan_digest(&self.plan),
+            operations: self.plan.len(),
+            operations_by_kind,
+            submitted: self.ops.len() - starved,
+            stored,
+            rejected: rejections.values().sum(),
+            starved,
+            unresolved,
+            stage_regressions: self.stage_regressions,
+            elapsed_ms: elapsed.as_millis() as u64,
+            throughput_ops_per_sec: if elapsed_secs > 0.0 {
+                stored as f64 / elapsed_secs
+            } else {
+                0.0
+            },
+            stages,
+            rejections,
+        }
+    }
+}
+
+/// Generate the deterministic operation plan for a configuration
+///
+/// ### Arguments
+///
+/// * `config`       - Load generation configuration
+/// * `wallet_count` - Number of funding wallets
+fn make_plan(config: &LoadGenConfig, wallet_count: usize) -> Vec<PlannedOp> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let zipf = ZipfSampler::new(config.recipient_count.max(1), config.zipf_exponent);
+    let wallet_count = wallet_count.max(1);
+
+    (0..config.operation_count)
+        .map(|_| {
+            let kind = pick_kind(&config.mix, &mut rng);
+            let first = rng.gen_range(0, wallet_count);
+            let second = (first + rng.gen_range(1, wallet_count.max(2))) % wallet_count;
+            let recipients = match kind {
+                LoadOpKind::Payment => vec![zipf.sample(&mut rng)],
+                LoadOpKind::Batch => (0..config.batch_outputs.max(1))
+                    .map(|_| zipf.sample(&mut rng))
+                    .collect(),
+                LoadOpKind::DruidPair | LoadOpKind::Aggregation => Vec::new(),
+            };
+            let amount = match kind {
+                LoadOpKind::Aggregation => 0,
+                _ => 1,
+            };
+
+            PlannedOp {
+                kind,
+                wallets: (first, second),
+                recipients,
+                amount,
+            }
+        })
+        .collect()
+}
+
+fn pick_kind<R: Rng>(mix: &LoadGenMix, rng: &mut R) -> LoadOpKind {
+    let weights = [
+        (LoadOpKind::Payment, mix.payment),
+        (LoadOpKind::Batch, mix.batch),
+        (LoadOpKind::DruidPair, mix.druid_pair),
+        (LoadOpKind::Aggregation, mix.aggregation),
+    ];
+    let total: u32 = weights.iter().map(|(_, w)| w).sum();
+    if total == 0 {
+        return LoadOpKind::Payment;
+    }
+
+    let mut pick = rng.gen_range(0, total);
+    for (kind, weight) in weights.iter() {
+        if pick < *weight {
+            return *kind;
+        }
+        pick -= weight;
+    }
+    LoadOpKind::Payment
+}
+
+/// Group wallet seeds by address into funding wallets
+fn make_wallets(seeds: &[WalletTxSpec]) -> Vec<LoadWallet> {
+    let mut wallets: Vec<LoadWallet> = Vec::new();
+    for seed in seeds {
+        let (out_point, pk, sk, amount, version) = make_wallet_tx_info(seed);
+        let address = construct_address_for(&pk, version);
+        match wallets.iter_mut().find(|w| w.address == address) {
+            Some(wallet) => wallet.ready.push((out_point, amount)),
+            None => wallets.push(LoadWallet {
+                pk,
+                sk,
+                address,
+                version,
+                ready: vec![(out_point, amount)],
+            }),
+        }
+    }
+    wallets
+}
+
+fn make_recipient_address(seed: u64, index: usize) -> String {
+    hex::encode(sha3_256::digest(
+        format!("load_gen_recipient_{seed}_{index}").as_bytes(),
+    ))
+}
+
+fn make_druid(seed: u64, index: usize) -> String {
+    hex::encode(sha3_256::digest(
+        format!("load_gen_druid_{seed}_{index}").as_bytes(),
+    ))
+}
+
+fn plan_digest(plan: &[PlannedOp]) -> String {
+    hex::encode(sha3_256::digest(
+        &serde_json::to_vec(plan).unwrap_or_default(),
+    ))
+}
+
+fn token_tx_out(address: &str, amount: u64) -> TxOut {
+    TxOut::new_token_amount(address.to_owned(), TokenAmount(amount), None)
+}
+
+/// Nearest-rank percentiles over the latencies of a stage
+fn stage_latency(samples: &[&(Duration, bool)]) -> StageLatency {
+    let mut millis: Vec<u64> = samples.iter().map(|(d, _)| d.as_millis() as u64).collect();
+    millis.sort_unstable();
+
+    let percentile = |p: usize| -> u64 {
+        if millis.is_empty() {
+            return 0;
+        }
+        let rank = (p * millis.len() + 99) / 100;
+        millis[rank.clamp(1, millis.len()) - 1]
+    };
+
+    StageLatency {
+        reached: samples.len(),
+        inferred: samples.iter().filter(|(_, inferred)| *inferred).count(),
+        p50_ms: percentile(50),
+        p90_ms: percentile(90),
+        p99_ms: percentile(99),
+        max_ms: millis.last().copied().unwrap_or_default(),
+    }
+}
+
+/*---- TESTS ----*/
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_config(seed: u64) -> LoadGenConfig {
+        LoadGenConfig {
+            seed,
+            operation_count: 200,
+            rate_per_sec: 100.0,
+            mix: LoadGenMix {
+                payment: 6,
+                batch: 2,
+                druid_pair: 1,
+                aggregation: 1,
+            },
+            recipient_count: 50,
+            zipf_exponent: 1.1,
+            batch_outputs: 4,
+            aggregation_inputs: 4,
+            poll_interval_ms: 50,
+            call_timeout_ms: 1000,
+            settle_timeout_ms: 1000,
+            wallet_seeds: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn same_seed_same_plan() {
+        //
+        // Act
+        //
+        let first = make_plan(&test_config(7), 3);
+        let second = make_plan(&test_config(7), 3);
+        let other = make_plan(&test_config(8), 3);
+
+        //
+        // Assert
+        //
+        assert_eq!(first, second);
+        assert_eq!(plan_digest(&first), plan_digest(&second));
+        assert_ne!(plan_digest(&first), plan_digest(&other));
+    }
+
+    #[test]
+    fn zipf_favours_low_ranks() {
+        //
+        // Arrange
+        //
+        let zipf = ZipfSampler::new(20, 1.2);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        //
+        // Act
+        //
+        let mut counts = vec![0usize; 20];
+        for _ in 0..10_000 {
+            counts[zipf.sample(&mut rng)] += 1;
+        }
+
+        //
+        // Assert
+        //
+        assert!(counts[0] > counts[1]);
+        assert!(counts[1] > counts[19]);
+        assert_eq!(counts.iter().sum::<usize>(), 10_000);
+    }
+}
@@ -1,32 +1,43 @@
+use crate::active_raft::RaftInfo;
 use crate::block_pipeline::{MiningPipelineItem, MiningPipelineStatus, Participants};
+use crate::comms_handler::peer_discovery::configure_peer_discovery;
 use crate::comms_handler::{CommsError, Event, Node, TcpTlsConfig};
 use crate::configurations::{
-    ExtraNodeParams, MempoolNodeConfig, MempoolNodeSharedConfig, TlsPrivateInfo,
+    ApiRateLimits, ExtraNodeParams, MempoolNodeConfig, MempoolNodeSharedConfig, TlsPrivateInfo,
+};
+use crate::constants::{
+    DB_PATH, MEMPOOL_SHUTDOWN_DRAIN_TIMEOUT_MS, RESEND_TRIGGER_MESSAGES_COMPUTE_LIMIT,
+    TX_VERIFICATION_BLOCKING_THRESHOLD,
 };
-use crate::constants::{DB_PATH, RESEND_TRIGGER_MESSAGES_COMPUTE_LIMIT};
 use crate::db_utils::{self, SimpleDb, SimpleDbError, SimpleDbSpec};
+use crate::difficulty_adjustment::DifficultyAdjustmentConfig;
 use crate::interfaces::{
-    BlockStoredInfo, CommonBlockInfo, Contract, DruidDroplet, DruidPool, InitialIssuance,
-    MempoolApi, MempoolApiRequest, MempoolInterface, MempoolRequest, MineRequest, MinedBlock,
-    MinedBlockExtraInfo, NodeType, PowInfo, ProofOfWork, Response, StorageRequest, TxStatus,
-    TxStatusType, UserRequest, UtxoFetchType, UtxoSet, WinningPoWInfo,
+    BlockStoredInfo, CommonBlockInfo, Contract, CurrentDifficulty, DruidDroplet, DruidPool,
+    FeeEstimate, InitialIssuance, MempoolApi, MempoolApiRequest, MempoolInterface, MempoolMetrics,
+    MempoolRaftStatus, MempoolRequest, MempoolStatus, MineRequest, MinedBlock, MinedBlockExtraInfo,
+    NodeType, NoncePartition, PowInfo, ProofOfWork, Response, StorageRequest,
+    TransactionResponseMeta, TxLifecycleStatus, TxStage, TxStatus, TxStatusType, UserRequest,
+    UtxoFetchType, UtxoSet, WinningPoWInfo,
 };
 use crate::mempool_raft::{
     CommittedItem, CoordinatedCommand, MempoolConsensusedRuntimeData, MempoolRaft,
-    MempoolRuntimeItem,
+    MempoolRuntimeItem, MinerWhitelist,
 };
+use crate::pool_mining::{PoolMining, PoolMiningHandle, PoolSolution};
 use crate::raft::RaftCommit;
 use crate::threaded_call::{ThreadedCallChannel, ThreadedCallSender};
 use crate::tracked_utxo::TrackedUtxoSet;
 use crate::utils::{
-    apply_mining_tx, check_druid_participants, create_item_asset_tx_from_sig, create_socket_addr,
-    format_parition_pow_address, generate_pow_random_num, get_timestamp_now,
-    is_timestamp_difference_greater, to_api_keys, to_route_pow_infos, validate_pow_block,
-    validate_pow_for_address, ApiKeys, LocalEvent, LocalEventChannel, LocalEventSender,
-    ResponseResult, RoutesPoWInfo, StringError,
+    apply_mining_tx, check_druid_participants, construct_block_template_id,
+    construct_valid_block_pow_hash, create_item_asset_tx_from_sig, create_socket_addr,
+    find_first_valid_pow_block, format_parition_pow_address, generate_pow_random_num,
+    get_timestamp_now, is_timestamp_difference_greater, shutdown_connections, to_api_keys,
+    to_route_pow_infos, validate_pow_block, validate_pow_for_address, ApiKeys, LocalEvent,
+    LocalEventChannel, LocalEventSender, ResponseResult, RoutesPoWInfo, StringError,
 };
 use bincode::{deserialize, serialize};
 use bytes::Bytes;
+use futures::future::join_all;
 use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
@@ -35,17 +46,19 @@ use std::{
     fmt,
     future::Future,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    time::Duration,
 };
 use tokio::sync::RwLock;
 use tokio::task;
+use tokio::time::{self, Instant};
 use tracing::{debug, error, error_span, info, trace, warn};
 use tracing_futures::Instrument;
 use tw_chain::primitives::asset::TokenAmount;
-use tw_chain::primitives::block::Block;
+use tw_chain::primitives::block::{Block, BlockHeader};
 use tw_chain::primitives::transaction::{GenesisTxHashSpec, Transaction};
 use tw_chain::utils::druid_utils::druid_expectations_are_met;
 use tw_chain::utils::script_utils::{tx_has_valid_create_script, tx_is_valid};
-use tw_chain::utils::transaction_utils::construct_tx_hash;
+use tw_chain::utils::transaction_utils::{construct_tx_hash, get_inputs_previous_out_point};
 
 /// Key for local miner list
 pub const REQUEST_LIST_KEY: &str = "RequestListKey";
@@ -53,6 +66,8 @@ pub const USER_NOTIFY_LIST_KEY: &str = "UserNotifyListKey";
 pub const POW_RANDOM_NUM_KEY: &str = "PowRandomNumKey";
 pub const POW_PREV_RANDOM_NUM_KEY: &str = "PowPreviousRandomNumKey";
 pub const RAFT_KEY_RUN: &str = "RaftKeyRun";
+pub const MINER_WHITELIST_KEY: &str = "MinerWhitelistKey";
+pub const LOCAL_POOLS_KEY: &str = "LocalPoolsKey";
 
 /// Database columns
 pub const DB_COL_INTERNAL: &str = "internal";
@@ -133,6 +148,23 @@ impl From<StringError> for MempoolError {
     }
 }
 
+/// Block PoW received from a miner, waiting to be verified with its batch
+#[derive(Debug)]
+struct PendingBlockPoW {
+    address: SocketAddr,
+    header: BlockHeader,
+    coinbase: Transaction,
+    from_pool: bool,
+}
+
+/// Mined block sent to storage, re-sent until storage acknowledges it
+#[derive(Debug)]
+struct PendingStorageBlock {
+    block_hash: String,
+    attempts: usize,
+    resend_deadline: Instant,
+}
+
 #[derive(Debug)]
 pub struct MempoolNode {
     shared_config: MempoolNodeSharedConfig,
@@ -149,6 +181,8 @@ pub struct MempoolNode {
     jurisdiction: String,
     current_mined_block: Option<MinedBlock>,
     druid_pool: DruidPool,
+    druid_pool_first_seen: BTreeMap<String, u64>,
+    druid_pool_expiry_blocks: u64,
     previous_random_num: Vec<u8>,
     current_random_num: Vec<u8>,
     current_trigger_messages_count: usize,
@@ -158,6 +192,8 @@ pub struct MempoolNode {
     request_list: BTreeSet<SocketAddr>,
     request_list_first_flood: Option<usize>,
     miner_removal_list: Arc<RwLock<BTreeSet<SocketAddr>>>,
+    nonce_partitions: BTreeMap<SocketAddr, NoncePartition>,
+    pool_mining: PoolMiningHandle,
     storage_addr: SocketAddr,
     sanction_list: Vec<String>,
     user_notification_list: BTreeSet<SocketAddr>,
@@ -165,15 +201,25 @@ pub struct MempoolNode {
     shutdown_group: BTreeSet<SocketAddr>,
     fetched_utxo_set: Option<(SocketAddr, NodeType, UtxoSet)>,
     tx_status_list: BTreeMap<String, TxStatus>,
+    stored_tx_list: BTreeMap<String, (TransactionResponseMeta, i64)>,
     tx_status_lifetime: i64,
     api_info: (
         SocketAddr,
         Option<TlsPrivateInfo>,
         ApiKeys,
         RoutesPoWInfo,
+        ApiRateLimits,
         Node,
     ),
     init_issuances: Vec<InitialIssuance>,
+    pending_block_pows: Vec<PendingBlockPoW>,
+    pow_batch_deadline: Option<Instant>,
+    pow_batch_window: Duration,
+    pending_storage_block: Option<PendingStorageBlock>,
+    storage_resend_interval: Duration,
+    double_spend_count: u64,
+    mined_block_count: u64,
+    mined_tx_count: u64,
 }
 
 impl MempoolNode {
@@ -191,9 +237,10 @@ impl MempoolNode {
         })?;
 
         let init_issuances = config.initial_issuances.clone();
+        // Mempool nodes outnumbering storage nodes share them in turn
         let raw_storage_addr = config
             .storage_nodes
-            .get(config.mempool_node_idx)
+            .get(config.mempool_node_idx % config.storage_nodes.len().max(1))
             .ok_or(MempoolError::ConfigError("Invalid storage index"))?;
         let storage_addr = create_socket_addr(&raw_storage_addr.address)
             .await
@@ -207,7 +254,7 @@ impl MempoolNode {
             .mempool_api_use_tls
             .then(|| tcp_tls_config.clone_private_info());
 
-        let node = Node::new(
+        let mut node = Node::new(
             &tcp_tls_config,
             config.peer_limit,
             config.sub_peer_limit,
@@ -216,6 +263,18 @@ impl MempoolNode {
             true,
         )
         .await?;
+        if let Some(max_frame_size) = config.max_frame_size {
+            node.set_max_frame_size(max_frame_size);
+        }
+        if let Some(max_delay_ms) = config.peer_reconnect_max_delay_ms {
+            node.set_reconnect_max_delay(Duration::from_millis(max_delay_ms));
+        }
+        configure_peer_discovery(&mut node, config.peer_discovery.as_ref()).await?;
+        DifficultyAdjustmentConfig::from_config(
+            config.target_block_interval_secs,
+            config.difficulty_adjustment_period,
+        )
+        .map_err(MempoolError::ConfigError)?;
         let node_raft = MempoolRaft::new(&config, extra.raft_db.take()).await;
 
         if config.backup_restore.unwrap_or(false) {
@@ -233,7 +292,17 @@ impl MempoolNode {
         let enable_trigger_messages_pipeline_reset = config
             .enable_trigger_messages_pipeline_reset
             .unwrap_or(false);
-        let api_info = (api_addr, api_tls_info, api_keys, api_pow_info, node.clone());
+        let api_rate_limit = config
+            .mempool_api_rate_limit
+            .unwrap_or_else(ApiRateLimits::node_default);
+        let api_info = (
+            api_addr,
+            api_tls_info,
+            api_keys,
+            api_pow_info,
+            api_rate_limit,
+            node.clone(),
+        );
 
         let shared_config = MempoolNodeSharedConfig {
             mempool_mining_event_timeout: config.mempool_mining_event_timeout,
@@ -261,11 +330,15 @@ impl MempoolNode {
             threaded_calls: Default::default(),
             current_mined_block: None,
             druid_pool: Default::default(),
+            druid_pool_first_seen: Default::default(),
+            druid_pool_expiry_blocks: config.mempool_druid_pool_expiry_blocks,
             current_trigger_messages_count: Default::default(),
             enable_trigger_messages_pipeline_reset,
             previous_random_num: Default::default(),
             current_random_num: Default::default(),
             miner_removal_list: Default::default(),
+            nonce_partitions: Default::default(),
+            pool_mining: PoolMining::new_handle(),
             miners_changed: false,
             request_list: Default::default(),
             sanction_list: config.sanction_list,
@@ -280,7 +353,18 @@ impl MempoolNode {
             fetched_utxo_set: None,
             init_issuances,
             tx_status_list: Default::default(),
+            stored_tx_list: Default::default(),
             tx_status_lifetime: config.tx_status_lifetime,
+            pending_block_pows: Default::default(),
+            pow_batch_deadline: None,
+            pow_batch_window: Duration::from_millis(config.mempool_pow_batch_window as u64),
+            pending_storage_block: None,
+            storage_resend_interval: Duration::from_millis(
+                config.mempool_storage_resend_interval as u64,
+            ),
+            double_spend_count: 0,
+            mined_block_count: 0,
+            mined_tx_count: 0,
         }
         .load_local_db()
     }
@@ -336,6 +420,11 @@ impl MempoolNode {
         self.node.public_address().await
     }
 
+    /// Get the pool mining state shared with the API
+    pub fn pool_mining(&self) -> PoolMiningHandle {
+        self.pool_mining.clone()
+    }
+
     /// Get the node's mined block if any
     pub fn get_current_mined_block(&self) -> &Option<MinedBlock> {
         &self.current_mined_block
@@ -456,11 +545,36 @@ impl MempoolNode {
         self.node_raft.raft_loop()
     }
 
-    /// Signal to the raft loop to complete
+    /// Returns the raft membership and leadership shared with the API
+    pub fn raft_info(&self) -> RaftInfo {
+        self.node_raft.raft_info()
+    }
+
+    /// Signal to the raft loop to complete, storing the local pools
+    /// so they are restored on restart
     pub async fn close_raft_loop(&mut self) {
+        if let Err(e) = self.node_raft.save_local_pools(&mut self.db) {
+            error!("Error saving local pools: {:?}", e);
+        }
         self.node_raft.close_raft_loop().await
     }
 
+    /// Stop accepting connections, drain the proposals in flight, persist the state
+    /// needed to restart in place, and notify connected peers of the disconnection
+    pub async fn shutdown(&mut self) {
+        join_all(self.node.stop_listening().await).await;
+        let timeout = Duration::from_millis(MEMPOOL_SHUTDOWN_DRAIN_TIMEOUT_MS);
+        self.node_raft.shutdown(&mut self.db, timeout).await;
+        delete_local_transactions(
+            &mut self.db,
+            &self.node_raft.take_local_tx_hash_last_commited(),
+        );
+        if let Err(e) = self.db.flush() {
+            error!("Error flushing main db: {:?}", e);
+        }
+        shutdown_connections(&mut self.node).await;
+    }
+
     /// Extract persistent dbs
     pub async fn take_closed_extra_params(&mut self) -> ExtraNodeParams {
         let raft_db = self.node_raft.take_closed_persistent_store().await;
@@ -488,6 +602,7 @@ impl MempoolNode {
         Option<TlsPrivateInfo>,
         ApiKeys,
         RoutesPoWInfo,
+        ApiRateLimits,
         Node,
     ) {
         self.api_info.clone()
@@ -503,6 +618,7 @@ impl MempoolNode {
         transactions: BTreeMap<String, Transaction>,
     ) -> Vec<(bool, BTreeMap<String, Transaction>)> {
         let mut ready_txs = Vec::new();
+        let b_num = self.get_committed_current_block_num().unwrap_or_default();
 
         for (tx_hash, tx) in transactions {
             if let Some(druid_info) = tx.druid_info.clone() {
@@ -513,6 +629,9 @@ impl MempoolNode {
                     .druid_pool
                     .entry(druid.clone())
                     .or_insert_with(|| DruidDroplet::new(participants));
+                self.druid_pool_first_seen
+                    .entry(druid.clone())
+                    .or_insert(b_num);
 
                 droplet.txs.insert(tx_hash.clone(), tx);
 
@@ -522,14 +641,43 @@ impl MempoolNode {
                         && check_druid_participants(droplet);
                     ready_txs.push((valid, droplet.txs.clone()));
 
-                    // TODO: Implement time-based removal?
                     self.druid_pool.remove(&druid);
+                    self.druid_pool_first_seen.remove(&druid);
                 }
             }
         }
         ready_txs
     }
 
+    /// Expire partial DRUID droplets whose counterparts did not arrive
+    /// within `druid_pool_expiry_blocks` of them being first seen.
+    ///
+    /// Driven by the committed block number so all peers expire at the same block.
+    fn expire_druid_droplets(&mut self) {
+        let b_num = self.get_committed_current_block_num().unwrap_or_default();
+        let expiry_blocks = self.druid_pool_expiry_blocks;
+        let expired: Vec<String> = self
+            .druid_pool_first_seen
+            .iter()
+            .filter(|(_, first_seen)| b_num >= first_seen.saturating_add(expiry_blocks))
+            .map(|(druid, _)| druid.clone())
+            .collect();
+
+        for druid in expired {
+            self.druid_pool_first_seen.remove(&druid);
+            let droplet = match self.druid_pool.remove(&druid) {
+                Some(droplet) => droplet,
+                None => continue,
+            };
+
+            debug!("Expiring DRUID droplet {} at block {}", druid, b_num);
+            for tx in droplet.txs.values() {
+                let info = "DRUID droplet expired before all participants arrived".to_owned();
+                self.update_tx_status(tx, TxStatusType::Rejected, info);
+            }
+        }
+    }
+
     /// Returns the mining block from the node_raft
     pub fn get_mining_block(&self) -> &Option<Block> {
         self.node_raft.get_mining_block()
@@ -575,38 +723,28 @@ impl MempoolNode {
     /// Return closure use to validate a transaction
     fn transactions_validator(&mut self) -> impl Fn(&Transaction) -> (bool, String) + '_ {
         let utxo_set = self.node_raft.get_committed_utxo_set();
-        let lock_expired = self
-            .node_raft
-            .get_committed_current_block_num()
-            .unwrap_or_default();
-        //let sanction_list = &self.sanction_list;
         let b_num = self
             .node_raft
             .get_committed_current_block_num()
             .unwrap_or_default();
 
-        move |tx| {
-            if tx.is_create_tx() {
-                let is_valid = tx_has_valid_create_script(
-                    &tx.inputs[0].script_signature,
-                    &tx.outputs[0].value,
-                );
-
-                return match is_valid {
-                    true => (true, "Create script is valid".to_string()),
-                    false => (false, "Create script is invalid".to_string()),
-                };
-            }
-
-            let (is_valid, validity_info) = tx_is_valid(tx, b_num, |v| {
-                utxo_set
-                    .get(v)
-                    // .filter(|_| !sanction_list.contains(&v.t_hash))
-                    .filter(|tx_out| lock_expired >= tx_out.locktime)
-            });
+        move |tx| verify_transaction(tx, b_num, utxo_set)
+    }
 
-            (!tx.is_coinbase() && is_valid, validity_info)
-        }
+    /// Copy of the committed UTXO entries spent by the transactions,
+    /// to verify them away from the node
+    ///
+    /// ### Arguments
+    ///
+    /// * `transactions` - Transactions to be verified
+    fn spent_utxo_entries(&self, transactions: &[Transaction]) -> UtxoSet {
+        let utxo_set = self.node_raft.get_committed_utxo_set();
+        get_inputs_previous_out_point(transactions.iter())
+            .filter_map(|out_point| {
+                let tx_out = utxo_set.get(out_point)?;
+                Some((out_point.clone(), tx_out.clone()))
+            })
+            .collect()
     }
 
     /// Sends the latest block to storage
@@ -621,12 +759,61 @@ impl MempoolNode {
         info!("");
 
         self.node_raft.propose_timestamp().await;
+        self.track_block_sent_to_storage();
         self.node
             .send(self.storage_addr, StorageRequest::SendBlock { mined_block })
             .await?;
         Ok(())
     }
 
+    /// Re-sends the latest block to storage as it was not acknowledged yet
+    async fn resend_block_to_storage(&mut self) -> Result<()> {
+        let mined_block = self.current_mined_block.clone();
+        self.track_block_sent_to_storage();
+        self.node
+            .send(self.storage_addr, StorageRequest::SendBlock { mined_block })
+            .await?;
+        Ok(())
+    }
+
+    /// Counts an attempt to send the latest block to storage and schedules its resend
+    fn track_block_sent_to_storage(&mut self) {
+        let block_hash = match &self.current_mined_block {
+            Some(mined) => construct_valid_block_pow_hash(&mined.common.block).ok(),
+            None => None,
+        };
+        let block_hash = match block_hash {
+            Some(block_hash) => block_hash,
+            None => {
+                self.pending_storage_block = None;
+                return;
+            }
+        };
+
+        let attempts = match &self.pending_storage_block {
+            Some(pending) if pending.block_hash == block_hash => pending.attempts + 1,
+            _ => 1,
+        };
+        if attempts > 1 {
+            debug!(
+                "Resend block {} to storage: attempt {}",
+                block_hash, attempts
+            );
+        }
+        self.pending_storage_block = Some(PendingStorageBlock {
+            block_hash,
+            attempts,
+            resend_deadline: Instant::now() + self.storage_resend_interval,
+        });
+    }
+
+    /// Number of attempts to send the latest block to storage not yet acknowledged
+    pub fn block_to_storage_attempts(&self) -> usize {
+        self.pending_storage_block
+            .as_ref()
+            .map_or(0, |pending| pending.attempts)
+    }
+
     /// Floods all peers with a PoW for UnicornShard creation
     /// TODO: Add in comms handling for sending and receiving requests
     /// ### Arguments
@@ -717,6 +904,38 @@ impl MempoolNode {
             }) if reason == "Node resumed" => {
                 warn!("NODE RESUMED");
             }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Network pause proposed" => {
+                debug!("Network pause proposed");
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Network resume proposed" => {
+                debug!("Network resume proposed");
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Network paused" => {
+                warn!("NETWORK PAUSED: no new block will be generated");
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Network resumed" => {
+                warn!("NETWORK RESUMED");
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Block generation paused" => {
+                if let Err(e) = self.flood_network_paused_to_miners().await {
+                    error!("Network paused notice not sent to miners {:?}", e);
+                }
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -762,6 +981,10 @@ impl MempoolNode {
                 success: true,
                 reason,
             }) if reason == "Received first full partition request" => {}
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Mining template status sent" => {}
             Ok(Response {
                 success: true,
                 reason,
@@ -837,6 +1060,18 @@ impl MempoolNode {
             }) if reason == "Transactions added to tx pool" => {
                 debug!("Transactions received and processed successfully");
             }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Some transactions double spend. Adding valid transactions only" => {
+                warn!("Rejected double spend transactions");
+            }
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Double spend transactions provided" => {
+                warn!("Rejected double spend transactions");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -924,6 +1159,12 @@ impl MempoolNode {
                 success: false,
                 reason,
             }) if reason == "Not block currently mined" => {}
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Frame size limit exceeded" => {
+                warn!("Disconnected peer sending frames over the size limit");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -953,6 +1194,11 @@ impl MempoolNode {
         loop {
             let ready = !self.node_raft.need_initial_state();
             let shutdown = self.node_raft.is_shutdown_commit_processed();
+            let pow_batch_deadline = self.pow_batch_deadline;
+            let storage_resend_deadline = self
+                .pending_storage_block
+                .as_ref()
+                .map(|pending| pending.resend_deadline);
 
             // State machines are not keept between iterations or calls.
             // All selection calls (between = and =>), need to be dropable
@@ -1003,6 +1249,18 @@ impl MempoolNode {
                 Some(f) = self.threaded_calls.rx.recv(), if ready => {
                     f(self);
                 }
+                _ = time::sleep_until(pow_batch_deadline.unwrap_or_else(Instant::now)), if pow_batch_deadline.is_some() => {
+                    trace!("handle_next_event verify block PoW batch");
+                    if let Some(res) = self.verify_pending_block_pows().await {
+                        return Some(Ok(res));
+                    }
+                }
+                _ = time::sleep_until(storage_resend_deadline.unwrap_or_else(Instant::now)), if storage_resend_deadline.is_some() => {
+                    trace!("handle_next_event resend block to storage");
+                    if let Err(e) = self.resend_block_to_storage().await {
+                        debug!("Resend block to storage failed {:?}", e);
+                    }
+                }
                 reason = &mut *exit => return Some(Ok(Response {
                     success: true,
                     reason: reason.to_string(),
@@ -1027,6 +1285,7 @@ impl MempoolNode {
                 }))
             }
             Some(CommittedItem::Block) => {
+                self.expire_druid_droplets();
                 self.reset_mining_block_process().await;
                 self.backup_persistent_dbs().await;
                 Some(Ok(Response {
@@ -1078,6 +1337,18 @@ impl MempoolNode {
                 }))
             }
             Some(CommittedItem::CoordinatedCmd(cmd)) => self.handle_coordinated_cmd(cmd).await,
+            Some(CommittedItem::Paused) => Some(Ok(Response {
+                success: true,
+                reason: "Network paused".to_owned(),
+            })),
+            Some(CommittedItem::Resumed) => Some(Ok(Response {
+                success: true,
+                reason: "Network resumed".to_owned(),
+            })),
+            Some(CommittedItem::BlockPaused) => Some(Ok(Response {
+                success: true,
+                reason: "Block generation paused".to_owned(),
+            })),
             None => None,
         }
     }
@@ -1130,6 +1401,23 @@ impl MempoolNode {
                     .instrument(peer_span)
                     .await
             }
+            Event::FrameSizeLimitExceeded { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Frame size limit exceeded".to_owned(),
+            })),
+            Event::PeerReconnected { peer } => {
+                if let Err(err) = self.send_startup_requests().await {
+                    error!("Failed to send startup requests on reconnect to {peer:?}: {err}");
+                }
+                Ok(Some(Response {
+                    success: true,
+                    reason: "Peer reconnected".to_owned(),
+                }))
+            }
+            Event::PeerUnreachable { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Peer unreachable".to_owned(),
+            })),
         }
     }
 
@@ -1183,13 +1471,19 @@ impl MempoolNode {
                 self.receive_partition_entry(peer, pow_info, partition_entry)
                     .await
             }
-            SendTransactions { transactions } => Some(self.receive_transactions(transactions)),
+            SendTransactions { transactions } => {
+                Some(self.receive_transactions_batch(transactions).await)
+            }
             SendUserBlockNotificationRequest => {
                 Some(self.receive_block_user_notification_request(peer))
             }
             SendPartitionRequest { mining_api_key } => {
                 Some(self.receive_partition_request(peer, mining_api_key).await)
             }
+            CheckMiningTemplate { b_num, template_id } => Some(
+                self.receive_mining_template_check(peer, b_num, template_id)
+                    .await,
+            ),
             SendSharedConfig { shared_config } => {
                 match peer != self.local_address() && !self.node_raft.get_peers().contains(&peer) {
                     true => None,
@@ -1226,6 +1520,7 @@ impl MempoolNode {
                     }
                 }
             }
+            SendBlockAck { b_num, block_hash } => self.receive_block_ack(peer, b_num, block_hash),
         }
     }
 
@@ -1369,9 +1664,26 @@ impl MempoolNode {
                     None
                 }
             },
-            SendTransactions { transactions } => Some(self.receive_transactions(transactions)),
+            SendTransactions { transactions } => {
+                Some(self.receive_transactions_batch(transactions).await)
+            }
             PauseNodes { b_num } => Some(self.pause_nodes(b_num)),
             ResumeNodes => Some(self.resume_nodes()),
+            PauseNetwork => {
+                self.node_raft.propose_pause().await;
+                Some(Response {
+                    success: true,
+                    reason: "Network pause proposed".to_owned(),
+                })
+            }
+            ResumeNetwork => {
+                self.node_raft.propose_resume().await;
+                Some(Response {
+                    success: true,
+                    reason: "Network resume proposed".to_owned(),
+                })
+            }
+            SendPoolPoW { solution } => self.receive_pool_pow(solution),
             SendSharedConfig { shared_config } => Some(self.send_shared_config(shared_config)),
         }
     }
@@ -1421,13 +1733,7 @@ impl MempoolNode {
             .update_mining_event_timeout_duration(mempool_mining_event_timeout);
         self.node_raft
             .update_partition_full_size(mempool_partition_full_size);
-        self.node_raft
-            .update_mempool_miner_whitelist_active(mempool_miner_whitelist.active);
-        self.node_raft.update_mempool_miner_whitelist_api_keys(
-            mempool_miner_whitelist.miner_api_keys.clone(),
-        );
-        self.node_raft
-            .update_mempool_miner_whitelist_addresses(mempool_miner_whitelist.miner_addresses);
+        self.set_miner_whitelist(mempool_miner_whitelist);
 
         if let Some(unauthorized) = self.flush_unauthorized_miners().await {
             self.node_raft
@@ -1531,6 +1837,47 @@ impl MempoolNode {
         }
     }
 
+    /// Apply the miner whitelist and persist it to survive restarts
+    ///
+    /// ### Arguments
+    ///
+    /// * `whitelist` - Miner whitelist to apply
+    fn set_miner_whitelist(&mut self, whitelist: MinerWhitelist) {
+        self.node_raft
+            .update_mempool_miner_whitelist_active(whitelist.active);
+        self.node_raft
+            .update_mempool_miner_whitelist_api_keys(whitelist.miner_api_keys.clone());
+        self.node_raft
+            .update_mempool_miner_whitelist_addresses(whitelist.miner_addresses.clone());
+
+        if let Err(e) = self.db.put_cf(
+            DB_COL_INTERNAL,
+            MINER_WHITELIST_KEY,
+            &serialize(&whitelist).unwrap(),
+        ) {
+            error!("Error writing miner whitelist to disk: {e:?}");
+        }
+    }
+
+    /// Rejection for a miner that is not whitelisted.
+    /// Checked before any PoW is validated.
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer` - Address of the miner
+    fn reject_non_whitelisted_miner(&self, peer: SocketAddr) -> Option<Response> {
+        let mining_api_key = self.node_raft.get_mining_api_key_entry(peer);
+        if self.check_miner_whitelisted(peer, mining_api_key) {
+            return None;
+        }
+
+        debug!("Rejecting PoW from non-whitelisted miner: {peer:?}");
+        Some(Response {
+            success: false,
+            reason: "Miner not whitelisted".to_owned(),
+        })
+    }
+
     /// Check if a miner is whitelisted
     ///
     /// ### Arguments
@@ -1641,6 +1988,10 @@ impl MempoolNode {
             return None;
         }
 
+        if let Some(rejection) = self.reject_non_whitelisted_miner(peer) {
+            return Some(rejection);
+        }
+
         let status = self.node_raft.get_mining_pipeline_status().clone();
         let random_number = match (&status, pow_info.participant_only) {
             (MiningPipelineStatus::ParticipantOnlyIntake, true) => &self.previous_random_num,
@@ -1714,7 +2065,7 @@ impl MempoolNode {
     }
 
     pub fn get_current_mining_reward(&self) -> TokenAmount {
-        *self.node_raft.get_current_reward()
+        self.node_raft.get_current_reward()
     }
 
     /// Floods the current block to participants for mining
@@ -1737,18 +2088,23 @@ impl MempoolNode {
 
         let header = block.header.clone();
         let b_num = header.b_num;
-        let reward = *self.node_raft.get_current_reward();
+        let reward = self.node_raft.get_current_reward();
         let pow_info = PowInfo {
             participant_only,
             b_num,
         };
+        if !participant_only {
+            let mut pool_mining = self.pool_mining.lock().unwrap();
+            pool_mining.set_block_template(header.clone(), reward);
+        }
 
         let miner_removal_list = self.miner_removal_list.read().await.clone();
         let all_participants = self.node_raft.get_mining_participants().clone();
-        let participants = all_participants
+        let mut participants: Vec<SocketAddr> = all_participants
             .iter()
             .filter(|participant| !miner_removal_list.contains(participant))
-            .copied();
+            .copied()
+            .collect();
         let request_list = self.request_list.clone();
         let non_participants = request_list.difference(all_participants.lookup()).copied();
         let mut unsent_miners = self.flush_unauthorized_miners().await.unwrap_or_default();
@@ -1761,22 +2117,37 @@ impl MempoolNode {
             )
             .await;
 
-        if let Ok(unsent_nodes) = self
-            .node
-            .send_to_all(
-                participants,
-                MineRequest::SendBlock {
-                    pow_info,
-                    rnum: rnum.clone(),
-                    win_coinbases: win_coinbases.clone(),
-                    block: Some(header.clone()),
-                    reward,
-                    b_num: header.b_num,
-                },
-            )
-            .await
-        {
-            unsent_miners.extend(unsent_nodes);
+        loop {
+            self.nonce_partitions = assign_nonce_partitions(&participants);
+            let mut unsent_participants = Vec::new();
+            for (peer, partition) in &self.nonce_partitions {
+                if let Ok(unsent_nodes) = self
+                    .node
+                    .send_to_all(
+                        std::iter::once(*peer),
+                        MineRequest::SendBlock {
+                            pow_info,
+                            rnum: rnum.clone(),
+                            win_coinbases: win_coinbases.clone(),
+                            block: Some(header.clone()),
+                            reward,
+                            b_num: header.b_num,
+                            partition: Some(*partition),
+                        },
+                    )
+                    .await
+                {
+                    unsent_participants.extend(unsent_nodes);
+                }
+            }
+
+            if unsent_participants.is_empty() {
+                break;
+            }
+
+            // Partitions of unreachable miners would go unsearched, so share them out again
+            participants.retain(|peer| !unsent_participants.contains(peer));
+            unsent_miners.extend(unsent_participants);
         }
 
         if let Ok(unsent_nodes) = self
@@ -1790,6 +2161,7 @@ impl MempoolNode {
                     block: None,
                     reward,
                     b_num: header.b_num,
+                    partition: None,
                 },
             )
             .await
@@ -1814,6 +2186,11 @@ impl MempoolNode {
         Ok(())
     }
 
+    /// Get the nonce partitions assigned to the participants of the current block
+    pub fn get_nonce_partitions(&self) -> &BTreeMap<SocketAddr, NoncePartition> {
+        &self.nonce_partitions
+    }
+
     /// If whitelisting is active, this function will remove all miners that are not whitelisted
     pub async fn flush_unauthorized_miners(&mut self) -> Option<Vec<SocketAddr>> {
         // Determine if whitelisting is active
@@ -1915,10 +2292,14 @@ impl MempoolNode {
 
     /// Logs the winner of the block and changes the current block to a new block to be mined
     pub fn mining_block_mined(&mut self) {
+        self.pool_mining.lock().unwrap().clear_block_template();
         let (mut block, mut block_txs) = self.node_raft.take_mining_block().unwrap();
         let (_, winning_pow) = self.node_raft.get_winning_miner().clone().unwrap();
         let unicorn = self.node_raft.get_current_unicorn().clone();
 
+        self.mined_block_count += 1;
+        self.mined_tx_count += block_txs.len() as u64;
+
         let mining_tx = winning_pow.mining_tx;
         let nonce = winning_pow.nonce;
         block.header = apply_mining_tx(block.header, nonce, mining_tx.0.clone());
@@ -1961,6 +2342,7 @@ impl MempoolNode {
             .unwrap();
 
         self.current_mined_block = None;
+        self.pending_storage_block = None;
         self.node_raft.clear_block_pipeline_proposed_keys();
         // If the node should pause, set the pause node flag to true
         if self.should_pause() {
@@ -1979,6 +2361,15 @@ impl MempoolNode {
             Ok(None) => self.request_list,
             Err(e) => panic!("Error accessing db: {:?}", e),
         };
+        match self.db.get_cf(DB_COL_INTERNAL, MINER_WHITELIST_KEY) {
+            Ok(Some(whitelist)) => {
+                let whitelist = deserialize::<MinerWhitelist>(&whitelist)?;
+                debug!("load_local_db: miner_whitelist {:?}", whitelist);
+                self.set_miner_whitelist(whitelist);
+            }
+            Ok(None) => (),
+            Err(e) => panic!("Error accessing db: {:?}", e),
+        }
         if let Some(first) = self.request_list_first_flood {
             if first <= self.request_list.len() {
                 self.request_list_first_flood = None;
@@ -2032,11 +2423,49 @@ impl MempoolNode {
 
         self.node_raft
             .append_to_tx_pool(get_local_transactions(&self.db));
+        if let Err(e) = self.node_raft.load_local_pools(&self.db) {
+            panic!("Error accessing db: {:?}", e);
+        }
 
         Ok(self)
     }
 
-    /// Recieves a ProofOfWork from miner
+    /// Tells a reconnecting miner whether the block template it holds queued PoW for
+    /// is still the block being mined, so only current work gets submitted
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`        - Address of miner
+    /// * `b_num`       - Block number of the cached template
+    /// * `template_id` - Identifier of the cached template
+    async fn receive_mining_template_check(
+        &mut self,
+        peer: SocketAddr,
+        b_num: u64,
+        template_id: String,
+    ) -> Response {
+        let current = (self.node_raft.get_mining_block().as_ref())
+            .filter(|b| b_num == b.header.b_num)
+            .filter(|_| self.node_raft.get_mining_participants().contains(&peer))
+            .is_some_and(|b| construct_block_template_id(&b.header) == template_id);
+
+        trace!(?peer, b_num, current, "Received mining template check");
+        let status = MineRequest::SendMiningTemplateStatus {
+            b_num,
+            template_id,
+            current,
+        };
+        if let Err(e) = self.node.send(peer, status).await {
+            error!("Error sending mining template status to {peer:?}: {e}");
+        }
+
+        Response {
+            success: true,
+            reason: "Mining template status sent".to_owned(),
+        }
+    }
+
+    /// Recieves a ProofOfWork from miner, queueing it for batch verification
     ///
     /// ### Arguments
     ///
@@ -2051,6 +2480,10 @@ impl MempoolNode {
         nonce: Vec<u8>,
         coinbase: Transaction,
     ) -> Option<Response> {
+        if let Some(rejection) = self.reject_non_whitelisted_miner(address) {
+            return Some(rejection);
+        }
+
         let pow_mining_block = (self.node_raft.get_mining_block().as_ref())
             .filter(|b| block_num == b.header.b_num)
             .filter(|_| self.node_raft.get_mining_participants().contains(&address));
@@ -2067,27 +2500,135 @@ impl MempoolNode {
             });
         };
 
-        // Check coinbase amount and structure
-        let coinbase_amount = self.node_raft.get_current_reward();
-        if !coinbase.is_coinbase() || coinbase.outputs[0].value.token_amount() != *coinbase_amount {
+        self.queue_block_pow(address, block_to_check, nonce, coinbase, false)
+    }
+
+    /// Receives a PoW found by one of the pool miners, queued for validation
+    /// as if submitted by this node
+    ///
+    /// ### Arguments
+    ///
+    /// * `solution` - Nonce and coinbase found by the pool miner
+    fn receive_pool_pow(&mut self, solution: PoolSolution) -> Option<Response> {
+        let PoolSolution {
+            b_num,
+            nonce,
+            coinbase,
+        } = solution;
+
+        let block_to_check = match self.node_raft.get_mining_block() {
+            Some(mining_block) if mining_block.header.b_num == b_num => mining_block.header.clone(),
+            _ => {
+                trace!(b_num, "Received outdated pool PoW");
+                return Some(Response {
+                    success: false,
+                    reason: "Not block currently mined".to_owned(),
+                });
+            }
+        };
+
+        let address = self.local_address();
+        self.queue_block_pow(address, block_to_check, nonce, coinbase, true)
+    }
+
+    /// Check the coinbase of a received PoW and queue it for validation
+    /// with the other PoWs received in the batch window
+    ///
+    /// ### Arguments
+    ///
+    /// * `address`        - Address the PoW is proposed for
+    /// * `block_to_check` - Header of the block currently mined
+    /// * `nonce`          - Nonce of the PoW
+    /// * `coinbase`       - Coinbase transaction of the PoW
+    /// * `from_pool`      - Whether found by a pool miner
+    fn queue_block_pow(
+        &mut self,
+        address: SocketAddr,
+        block_to_check: BlockHeader,
+        nonce: Vec<u8>,
+        coinbase: Transaction,
+        from_pool: bool,
+    ) -> Option<Response> {
+        // Check coinbase structure and amount, the reward being derived from the consensused issuance
+        if !coinbase.is_coinbase() {
             return Some(Response {
                 success: false,
                 reason: "Coinbase transaction invalid".to_owned(),
             });
         }
+        let coinbase_amount = self.node_raft.get_current_reward();
+        if coinbase.outputs[0].value.token_amount() != coinbase_amount {
+            return Some(Response {
+                success: false,
+                reason: "Coinbase amount does not match block reward".to_owned(),
+            });
+        }
 
-        // Perform validation
+        // Queue for validation with the other PoWs received in the batch window
         let coinbase_hash = construct_tx_hash(&coinbase);
-        let block_to_check = apply_mining_tx(block_to_check, nonce, coinbase_hash);
-        if !validate_pow_block(&block_to_check) {
+        let header = apply_mining_tx(block_to_check, nonce, coinbase_hash);
+        self.pending_block_pows.push(PendingBlockPoW {
+            address,
+            header,
+            coinbase,
+            from_pool,
+        });
+        if self.pow_batch_deadline.is_none() {
+            self.pow_batch_deadline = Some(Instant::now() + self.pow_batch_window);
+        }
+
+        None
+    }
+
+    /// Verify the PoWs received during the batch window, proposing the first
+    /// valid one in order of arrival
+    async fn verify_pending_block_pows(&mut self) -> Option<Response> {
+        self.pow_batch_deadline = None;
+        let mining_b_num = self
+            .node_raft
+            .get_mining_block()
+            .as_ref()
+            .map(|b| b.header.b_num);
+        let mut pending = std::mem::take(&mut self.pending_block_pows);
+        pending.retain(|pow| Some(pow.header.b_num) == mining_b_num);
+
+        let winner = match pending.len() {
+            0 => {
+                return Some(Response {
+                    success: false,
+                    reason: "Not block currently mined".to_owned(),
+                })
+            }
+            1 => validate_pow_block(&pending[0].header).then_some(0),
+            _ => {
+                let headers: Vec<_> = pending.iter().map(|pow| pow.header.clone()).collect();
+                task::spawn_blocking(move || find_first_valid_pow_block(&headers))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+        };
+
+        let Some(winner) = winner else {
             return Some(Response {
                 success: false,
                 reason: "Invalid PoW for block".to_owned(),
             });
-        }
+        };
+        let PendingBlockPoW {
+            address,
+            header,
+            coinbase,
+            from_pool,
+        } = pending.swap_remove(winner);
+        debug!(
+            ?address,
+            candidates = pending.len() + 1,
+            "Selected block PoW"
+        );
 
         // TODO: D and P will need to change with keccak prime intro
-        let (nonce, coinbase_hash) = block_to_check.nonce_and_mining_tx_hash;
+        let (nonce, coinbase_hash) = header.nonce_and_mining_tx_hash;
         let pow_info = WinningPoWInfo {
             nonce,
             mining_tx: (coinbase_hash, coinbase),
@@ -2096,14 +2637,12 @@ impl MempoolNode {
         };
 
         // Propose the received PoW to the block pipeline
-        if !self
-            .node_raft
-            .propose_mining_pipeline_item(MiningPipelineItem::WinningPoW(
-                address,
-                Box::new(pow_info),
-            ))
-            .await
-        {
+        let item = if from_pool {
+            MiningPipelineItem::PoolWinningPoW(address, Box::new(pow_info))
+        } else {
+            MiningPipelineItem::WinningPoW(address, Box::new(pow_info))
+        };
+        if !self.node_raft.propose_mining_pipeline_item(item).await {
             return None;
         }
 
@@ -2131,6 +2670,7 @@ impl MempoolNode {
             });
         }
 
+        self.update_stored_tx_list(&previous_block_info);
         if !self
             .node_raft
             .propose_block_with_last_info(previous_block_info)
@@ -2146,6 +2686,36 @@ impl MempoolNode {
         })
     }
 
+    /// Receives the acknowledgement of the block sent to storage, ending its resend
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`       - Sending peer's socket address
+    /// * `b_num`      - Number of the acknowledged block
+    /// * `block_hash` - Hash of the acknowledged block
+    fn receive_block_ack(
+        &mut self,
+        peer: SocketAddr,
+        b_num: u64,
+        block_hash: String,
+    ) -> Option<Response> {
+        if peer != self.storage_addr {
+            return None;
+        }
+
+        match &self.pending_storage_block {
+            Some(pending) if pending.block_hash == block_hash => {
+                debug!(
+                    "Block {} acknowledged by storage after {} attempts",
+                    b_num, pending.attempts
+                );
+                self.pending_storage_block = None;
+            }
+            _ => trace!("Acknowledgement for block {} not pending", b_num),
+        }
+        None
+    }
+
     /// Re-sends messages triggering the next step in flow
     pub async fn resend_trigger_message(&mut self) {
         match self.node_raft.get_mining_pipeline_status().clone() {
@@ -2279,19 +2849,8 @@ impl MempoolNode {
     /// * `tx` - Transaction to construct status for
     pub fn construct_tx_status(&mut self, tx: &Transaction) -> (TxStatusType, String) {
         let tx_validator = self.transactions_validator();
-        let (is_valid, mut validation_info) = tx_validator(&tx);
-        let mut status = TxStatusType::Confirmed;
-
-        if !is_valid {
-            status = TxStatusType::Rejected;
-        }
-
-        if tx.druid_info.is_some() {
-            status = TxStatusType::Pending;
-            validation_info = "DRUID transaction valid. Awaiting settlement".to_owned();
-        }
-
-        (status, validation_info)
+        let (is_valid, validation_info) = tx_validator(tx);
+        verified_tx_status(tx, is_valid, validation_info)
     }
 
     /// Flushes transaction statuses if their lifetimes have expired
@@ -2314,6 +2873,68 @@ impl MempoolNode {
         for tx_hash in stale_txs {
             self.tx_status_list.remove(&tx_hash);
         }
+
+        let now = get_timestamp_now() as u64;
+        let lifetime = self.tx_status_lifetime as u64;
+        self.stored_tx_list.retain(|_, (_, timestamp)| {
+            !is_timestamp_difference_greater(*timestamp as u64, now, lifetime)
+        });
+    }
+
+    /// Records the transactions of our mined block once storage confirms it stored it
+    ///
+    /// ### Arguments
+    ///
+    /// * `info` - Infomation about the stored block
+    fn update_stored_tx_list(&mut self, info: &BlockStoredInfo) {
+        let mined = match &self.current_mined_block {
+            Some(mined) if mined.common.block.header.b_num == info.block_num => mined,
+            _ => return,
+        };
+        match construct_valid_block_pow_hash(&mined.common.block) {
+            Ok(hash) if hash == info.block_hash => (),
+            _ => return,
+        }
+
+        let timestamp = get_timestamp_now();
+        for tx_hash in mined.common.block_txs.keys() {
+            let meta = TransactionResponseMeta::new(info.block_num, info.block_hash.clone());
+            self.stored_tx_list
+                .insert(tx_hash.clone(), (meta, timestamp));
+        }
+    }
+
+    /// Retrieves the stage and status for a list of transactions
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hashes` - The hashes of the transactions, answered once each
+    pub fn get_transaction_lifecycle(
+        &self,
+        tx_hashes: Vec<String>,
+    ) -> BTreeMap<String, TxLifecycleStatus> {
+        let mut tx_lifecycle = BTreeMap::new();
+
+        for tx_hash in tx_hashes {
+            let stage = match self.stored_tx_list.get(&tx_hash) {
+                Some((meta, _)) => TxStage::Stored {
+                    block_num: meta.block_num,
+                    block_hash: meta.block_hash.clone(),
+                },
+                None if self
+                    .druid_pool
+                    .values()
+                    .any(|d| d.txs.contains_key(&tx_hash)) =>
+                {
+                    TxStage::Pending
+                }
+                None => self.node_raft.get_tx_stage(&tx_hash),
+            };
+            let status = self.tx_status_list.get(&tx_hash).cloned();
+            tx_lifecycle.insert(tx_hash, TxLifecycleStatus { stage, status });
+        }
+
+        tx_lifecycle
     }
 
     /// Retrieves the status for a list of transactions
@@ -2339,6 +2960,63 @@ impl MempoolNode {
     ///
     /// * `transactions` - Transactions to be processed
     pub fn receive_transactions(&mut self, transactions: Vec<Transaction>) -> Response {
+        let verified = {
+            let tx_validator = self.transactions_validator();
+            transactions.iter().map(tx_validator).collect()
+        };
+        self.receive_verified_transactions(transactions, verified)
+    }
+
+    /// Receive incoming transactions from a peer, verifying large batches
+    /// on a blocking worker so the event loop stays responsive
+    ///
+    /// ### Arguments
+    ///
+    /// * `transactions` - Transactions to be processed
+    pub async fn receive_transactions_batch(&mut self, transactions: Vec<Transaction>) -> Response {
+        if transactions.len() < TX_VERIFICATION_BLOCKING_THRESHOLD {
+            return self.receive_transactions(transactions);
+        }
+
+        let utxo_set = self.spent_utxo_entries(&transactions);
+        let b_num = self
+            .node_raft
+            .get_committed_current_block_num()
+            .unwrap_or_default();
+        let verification = task::spawn_blocking(move || {
+            let verified: Vec<_> = transactions
+                .iter()
+                .map(|tx| verify_transaction(tx, b_num, &utxo_set))
+                .collect();
+            (transactions, verified)
+        });
+
+        match verification.await {
+            Ok((transactions, verified)) => {
+                self.receive_verified_transactions(transactions, verified)
+            }
+            Err(e) => {
+                error!("Transactions verification failed: {:?}", e);
+                Response {
+                    success: false,
+                    reason: "Transactions verification failed".to_owned(),
+                }
+            }
+        }
+    }
+
+    /// Process incoming transactions once their scripts and signatures are verified.
+    /// Only verified transactions can reach the pools proposed to raft.
+    ///
+    /// ### Arguments
+    ///
+    /// * `transactions` - Transactions to be processed
+    /// * `verified`     - Verification result and info of each transaction
+    fn receive_verified_transactions(
+        &mut self,
+        transactions: Vec<Transaction>,
+        verified: Vec<(bool, String)>,
+    ) -> Response {
         let transactions_len = transactions.len();
         if !self.node_raft.tx_pool_can_accept(transactions_len) {
             let reason = "Transaction pool for this mempool node is full".to_owned();
@@ -2354,29 +3032,60 @@ impl MempoolNode {
             };
         }
 
-        let (valid_dde_txs, valid_txs): (BTreeMap<_, _>, BTreeMap<_, _>) = {
-            let tx_validator = self.transactions_validator();
-            transactions
-                .clone()
-                .into_iter()
-                .filter(|tx| tx_validator(tx).0)
-                .map(|tx| (construct_tx_hash(&tx), tx))
-                .partition(|tx| tx.1.druid_info.is_some())
-        };
+        let verified: Vec<_> = transactions.into_iter().zip(verified).collect();
+        let rejected_txs: BTreeSet<String> = verified
+            .iter()
+            .filter(|(_, (is_valid, _))| !is_valid)
+            .map(|(tx, _)| construct_tx_hash(tx))
+            .collect();
+        let (valid_dde_txs, valid_txs): (BTreeMap<_, _>, BTreeMap<_, _>) = verified
+            .iter()
+            .filter(|(_, (is_valid, _))| *is_valid)
+            .map(|(tx, _)| (construct_tx_hash(tx), tx.clone()))
+            .partition(|tx| tx.1.druid_info.is_some());
+        let double_spend_txs = self.find_double_spend_txs(valid_txs.iter());
+        let valid_txs: BTreeMap<_, _> = valid_txs
+            .into_iter()
+            .filter(|(hash, _)| !double_spend_txs.contains(hash))
+            .collect();
+        self.double_spend_count += double_spend_txs.len() as u64;
 
         let total_valid_txs_len = valid_txs.len() + valid_dde_txs.len();
 
         // Update transaction status after initial validation
-        for tx in transactions {
-            let (status, validation_info) = self.construct_tx_status(&tx);
+        for (tx, (is_valid, validation_info)) in verified {
+            let (status, validation_info) = if double_spend_txs.contains(&construct_tx_hash(&tx)) {
+                let validation_info = "Input already spent by a pending transaction".to_owned();
+                (TxStatusType::Rejected, validation_info)
+            } else {
+                verified_tx_status(&tx, is_valid, validation_info)
+            };
             self.update_tx_status(&tx, status, validation_info);
         }
 
+        // Report the transactions failing verification
+        let with_rejected_txs = |reason: &str| match rejected_txs.is_empty() {
+            true => reason.to_owned(),
+            false => {
+                let rejected_txs: Vec<_> = rejected_txs.iter().map(String::as_str).collect();
+                format!(
+                    "{}. Rejected invalid transactions: {}",
+                    reason,
+                    rejected_txs.join(", ")
+                )
+            }
+        };
+
         // No valid transactions (normal or DDE) provided
         if total_valid_txs_len == 0 {
+            let reason = if double_spend_txs.is_empty() {
+                with_rejected_txs("No valid transactions provided")
+            } else {
+                "Double spend transactions provided".to_owned()
+            };
             return Response {
                 success: false,
-                reason: "No valid transactions provided".to_owned(),
+                reason,
             };
         }
 
@@ -2388,6 +3097,7 @@ impl MempoolNode {
         // TODO: Save DDE transactions to local DB storage
         let ready_dde_txs = self.validate_dde_txs(valid_dde_txs);
         let mut invalid_dde_txs_len = 0;
+        let mut rejected_full_dde_txs_len = 0;
         for (valid, ready) in ready_dde_txs {
             let mut status = TxStatusType::Confirmed;
             let mut validation_info = Default::default();
@@ -2396,6 +3106,10 @@ impl MempoolNode {
                 invalid_dde_txs_len += 1;
                 status = TxStatusType::Rejected;
                 validation_info = "DRUID trade expectations not met".to_owned();
+            } else if !self.node_raft.druid_pool_can_accept(1) {
+                rejected_full_dde_txs_len += 1;
+                status = TxStatusType::Rejected;
+                validation_info = "DRUID pool for this mempool node is full".to_owned();
             } else {
                 self.node_raft.append_to_tx_druid_pool(ready.clone());
             }
@@ -2406,12 +3120,28 @@ impl MempoolNode {
             }
         }
 
+        if rejected_full_dde_txs_len != 0 {
+            return Response {
+                success: false,
+                reason: "DRUID pool for this mempool node is full".to_owned(),
+            };
+        }
+
+        if !double_spend_txs.is_empty() {
+            return Response {
+                success: true,
+                reason: "Some transactions double spend. Adding valid transactions only".to_owned(),
+            };
+        }
+
         // Some txs are invalid or some DDE txs are ready to execute but fail to validate
         // TODO: Should provide better feedback on DDE transactions that fail
         if (total_valid_txs_len < transactions_len) || invalid_dde_txs_len != 0 {
             return Response {
                 success: true,
-                reason: "Some transactions invalid. Adding valid transactions only".to_owned(),
+                reason: with_rejected_txs(
+                    "Some transactions invalid. Adding valid transactions only",
+                ),
             };
         }
 
@@ -2421,6 +3151,78 @@ impl MempoolNode {
         }
     }
 
+    /// Receive a single transaction, rejecting it before the usual checks
+    /// if its inputs are not in the committed UTXO set
+    ///
+    /// ### Arguments
+    ///
+    /// * `transaction` - Transaction to be processed
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> Response {
+        let new_txs: BTreeMap<_, _> =
+            std::iter::once((construct_tx_hash(&transaction), transaction)).collect();
+
+        if !self.node_raft.find_invalid_new_txs(&new_txs).is_empty() {
+            let reason = "Transaction inputs not in the committed UTXO set".to_owned();
+            for tx in new_txs.values() {
+                self.update_tx_status(tx, TxStatusType::Rejected, reason.clone());
+            }
+
+            return Response {
+                success: false,
+                reason,
+            };
+        }
+
+        self.receive_transactions(new_txs.into_values().collect())
+    }
+
+    /// Find the transactions spending an input already spent by a different pending
+    /// transaction, or by an earlier transaction of the same submission
+    ///
+    /// ### Arguments
+    ///
+    /// * `txs` - Valid transactions submitted, with their hash
+    fn find_double_spend_txs<'a>(
+        &self,
+        txs: impl Iterator<Item = (&'a String, &'a Transaction)>,
+    ) -> BTreeSet<String> {
+        let mut spent_inputs = self.node_raft.get_pending_spent_inputs();
+        let mut double_spend_txs = BTreeSet::new();
+
+        for (hash, tx) in txs {
+            let inputs: Vec<_> = get_inputs_previous_out_point(Some(tx).into_iter()).collect();
+            let is_double_spend = inputs
+                .iter()
+                .any(|op| matches!(spent_inputs.get(*op), Some(spender) if spender != hash));
+
+            if is_double_spend {
+                double_spend_txs.insert(hash.clone());
+            } else {
+                spent_inputs.extend(inputs.into_iter().map(|op| (op.clone(), hash.clone())));
+            }
+        }
+
+        double_spend_txs
+    }
+
+    /// Number of submitted transactions rejected as double spends
+    pub fn double_spend_count(&self) -> u64 {
+        self.double_spend_count
+    }
+
+    /// Notify the miners that requested blocks that no new block will be
+    /// sent until the network is resumed
+    pub async fn flood_network_paused_to_miners(&mut self) -> Result<()> {
+        let b_num = self.node_raft.get_current_block_num();
+        self.node
+            .send_to_all(
+                self.request_list.iter().copied(),
+                MineRequest::NetworkPaused { b_num },
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Execute the initialization of a coordinated pause by invoking peers
     ///
     /// NOTE: Current block number has already been added to b_num from the coordinator
@@ -2527,8 +3329,74 @@ impl MempoolApi for MempoolNode {
         }
     }
 
-    fn get_transaction_status(&self, tx_hashes: Vec<String>) -> BTreeMap<String, TxStatus> {
-        self.get_transaction_status(tx_hashes)
+    fn get_transaction_lifecycle(
+        &self,
+        tx_hashes: Vec<String>,
+    ) -> BTreeMap<String, TxLifecycleStatus> {
+        self.get_transaction_lifecycle(tx_hashes)
+    }
+
+    fn get_mempool_status(&self) -> MempoolStatus {
+        self.node_raft.get_mempool_status()
+    }
+
+    fn get_raft_status(&self) -> MempoolRaftStatus {
+        MempoolRaftStatus {
+            block_to_storage_attempts: self.block_to_storage_attempts(),
+            ..self.node_raft.get_raft_status()
+        }
+    }
+
+    fn get_mempool_metrics(&self) -> MempoolMetrics {
+        MempoolMetrics {
+            status: self.node_raft.get_mempool_status(),
+            current_block_num: self.node_raft.get_committed_current_block_num(),
+            mined_block_count: self.mined_block_count,
+            mined_tx_count: self.mined_tx_count,
+            double_spend_count: self.double_spend_count,
+        }
+    }
+
+    fn get_current_difficulty(&self) -> CurrentDifficulty {
+        self.node_raft.get_current_difficulty()
+    }
+
+    fn estimate_fee(&self) -> FeeEstimate {
+        self.node_raft.estimate_fee()
+    }
+
+    fn get_miner_whitelist(&self) -> MinerWhitelist {
+        self.node_raft.get_mempool_miner_whitelist()
+    }
+
+    fn add_miner_whitelist_entries(
+        &mut self,
+        api_keys: Vec<String>,
+        addresses: Vec<SocketAddr>,
+    ) -> Response {
+        let mut whitelist = self.node_raft.get_mempool_miner_whitelist();
+        whitelist.add_entries(api_keys, addresses);
+        self.set_miner_whitelist(whitelist);
+
+        Response {
+            success: true,
+            reason: "Miner whitelist entries added".to_owned(),
+        }
+    }
+
+    fn remove_miner_whitelist_entries(
+        &mut self,
+        api_keys: Vec<String>,
+        addresses: Vec<SocketAddr>,
+    ) -> Response {
+        let mut whitelist = self.node_raft.get_mempool_miner_whitelist();
+        whitelist.remove_entries(&api_keys, &addresses);
+        self.set_miner_whitelist(whitelist);
+
+        Response {
+            success: true,
+            reason: "Miner whitelist entries removed".to_owned(),
+        }
     }
 
     fn get_committed_utxo_tracked_set(&self) -> &TrackedUtxoSet {
@@ -2547,6 +3415,10 @@ impl MempoolApi for MempoolNode {
         self.receive_transactions(transactions)
     }
 
+    fn submit_transaction(&mut self, transaction: Transaction) -> Response {
+        self.submit_transaction(transaction)
+    }
+
     fn create_item_asset_tx(
         &mut self,
         item_amount: u64,
@@ -2601,6 +3473,63 @@ impl MempoolApi for MempoolNode {
         }
     }
 
+    fn pause_network(&mut self) -> Response {
+        if self
+            .inject_next_event(
+                self.local_address(),
+                MempoolRequest::MempoolApi(MempoolApiRequest::PauseNetwork),
+            )
+            .is_err()
+        {
+            return Response {
+                success: false,
+                reason: "Failed to initiate network pause".to_owned(),
+            };
+        }
+        Response {
+            success: true,
+            reason: "Attempt network pause".to_owned(),
+        }
+    }
+
+    fn resume_network(&mut self) -> Response {
+        if self
+            .inject_next_event(
+                self.local_address(),
+                MempoolRequest::MempoolApi(MempoolApiRequest::ResumeNetwork),
+            )
+            .is_err()
+        {
+            return Response {
+                success: false,
+                reason: "Failed to initiate network resume".to_owned(),
+            };
+        }
+        Response {
+            success: true,
+            reason: "Attempt network resume".to_owned(),
+        }
+    }
+
+    fn submit_pool_pow(&mut self, solution: PoolSolution) -> Response {
+        if self
+            .inject_next_event(
+                self.local_address(),
+                MempoolRequest::MempoolApi(MempoolApiRequest::SendPoolPoW { solution }),
+            )
+            .is_err()
+        {
+            return Response {
+                success: false,
+                reason: "Failed to submit pool PoW".to_owned(),
+            };
+        }
+        Response {
+            success: true,
+            reason: "Pool PoW submitted".to_owned(),
+        }
+    }
+
     fn send_shared_config(
         &mut self,
         shared_config: crate::configurations::MempoolNodeSharedConfig,
@@ -2629,6 +3558,22 @@ impl MempoolApi for MempoolNode {
 /// ### Arguments
 ///
 /// * `db`             - Database
+/// Assign each mining participant its own partition of the block nonce space,
+/// ordered by address so every assignment of the same participants matches
+///
+/// ### Arguments
+///
+/// * `participants` - Miners participating in the current block
+fn assign_nonce_partitions(participants: &[SocketAddr]) -> BTreeMap<SocketAddr, NoncePartition> {
+    let participants: BTreeSet<SocketAddr> = participants.iter().copied().collect();
+    let count = participants.len() as u64;
+    participants
+        .into_iter()
+        .zip(0..)
+        .map(|(peer, index)| (peer, NoncePartition { index, count }))
+        .collect()
+}
+
 fn get_local_transactions(db: &SimpleDb) -> BTreeMap<String, Transaction> {
     db.iter_cf_clone(DB_COL_LOCAL_TXS)
         .map(|(k, v)| (String::from_utf8(k), deserialize(&v)))
@@ -2666,3 +3611,57 @@ fn delete_local_transactions(db: &mut SimpleDb, keys: &[String]) {
     let batch = batch.done();
     db.write(batch).unwrap();
 }
+
+/// Verify the scripts and signatures of a transaction against the UTXO entries it spends
+///
+/// ### Arguments
+///
+/// * `tx`       - Transaction to verify
+/// * `b_num`    - Current block number
+/// * `utxo_set` - UTXO entries the transaction may spend
+fn verify_transaction(tx: &Transaction, b_num: u64, utxo_set: &UtxoSet) -> (bool, String) {
+    if tx.is_create_tx() {
+        let is_valid =
+            tx_has_valid_create_script(&tx.inputs[0].script_signature, &tx.outputs[0].value);
+
+        return match is_valid {
+            true => (true, "Create script is valid".to_string()),
+            false => (false, "Create script is invalid".to_string()),
+        };
+    }
+
+    let (is_valid, validity_info) = tx_is_valid(tx, b_num, |v| {
+        utxo_set
+            .get(v)
+            // .filter(|_| !sanction_list.contains(&v.t_hash))
+            .filter(|tx_out| b_num >= tx_out.locktime)
+    });
+
+    (!tx.is_coinbase() && is_valid, validity_info)
+}
+
+/// Status of a transaction given the result of its verification
+///
+/// ### Arguments
+///
+/// * `tx`              - Transaction verified
+/// * `is_valid`        - Whether the transaction passed verification
+/// * `validation_info` - Verification info
+fn verified_tx_status(
+    tx: &Transaction,
+    is_valid: bool,
+    mut validation_info: String,
+) -> (TxStatusType, String) {
+    let mut status = TxStatusType::Confirmed;
+
+    if !is_valid {
+        status = TxStatusType::Rejected;
+    }
+
+    if tx.druid_info.is_some() {
+        status = TxStatusType::Pending;
+        validation_info = "DRUID transaction valid. Awaiting settlement".to_owned();
+    }
+
+    (status, validation_info)
+}
@@ -1,36 +1,45 @@
-use crate::active_raft::ActiveRaft;
+use crate::active_raft::{ActiveRaft, RaftInfo};
 use crate::asert::calculate_asert_target;
 use crate::block_pipeline::{
     MiningPipelineInfo, MiningPipelineInfoImport, MiningPipelineInfoPreDifficulty,
     MiningPipelineItem, MiningPipelinePhaseChange, MiningPipelineStatus, Participants,
     PipelineEventInfo,
 };
+use crate::canonical::canonical_hash;
 use crate::configurations::{MempoolNodeConfig, UnicornFixedInfo};
-use crate::constants::{BLOCK_SIZE_IN_TX, COINBASE_MATURITY, DB_PATH, TX_POOL_LIMIT};
+use crate::constants::{
+    ASSEMBLY_RULESET_VERSION, BLOCK_SIZE_IN_TX, COINBASE_MATURITY, DB_PATH, DEFAULT_FEE_ESTIMATE,
+    DRUID_POOL_LIMIT, FEE_ESTIMATE_BLOCK_WINDOW, MAX_IN_FLIGHT_PROPOSALS, MINING_DIFFICULTY,
+    TX_POOL_LIMIT,
+};
 use crate::db_utils::{self, SimpleDb, SimpleDbError, SimpleDbSpec};
-use crate::interfaces::{BlockStoredInfo, InitialIssuance, UtxoSet, WinningPoWInfo};
+use crate::difficulty_adjustment::{DifficultyAdjustment, DifficultyAdjustmentConfig};
+use crate::interfaces::{
+    BlockStoredInfo, CurrentDifficulty, FeeEstimate, InitialIssuance, MempoolRaftStatus,
+    MempoolStatus, PoWDifficulty, TxStage, UtxoSet, WinningPoWInfo,
+};
+use crate::mempool::{DB_COL_INTERNAL, LOCAL_POOLS_KEY};
 use crate::raft::{RaftCommit, RaftCommitData, RaftData, RaftMessageWrapper};
 use crate::raft_util::{RaftContextKey, RaftInFlightProposals};
 use crate::tracked_utxo::TrackedUtxoSet;
 use crate::unicorn::{UnicornFixedParam, UnicornInfo};
 use crate::utils::{
     calculate_reward, construct_coinbase_tx, create_socket_addr_for_list, get_timestamp_now,
-    get_total_coinbase_tokens, make_utxo_set_from_seed, try_deserialize, BackupCheck,
-    UtxoReAlignCheck,
+    get_total_coinbase_tokens, make_utxo_set_from_seed, try_deserialize, try_deserialize_exact,
+    BackupCheck, UtxoReAlignCheck,
 };
-use bincode::serialize;
+use bincode::{serialize, Error as BincodeError};
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::time::{self, Instant};
 use tracing::{debug, error, info, trace, warn};
-use tw_chain::crypto::sha3_256;
 use tw_chain::primitives::asset::TokenAmount;
 use tw_chain::primitives::block::Block;
-use tw_chain::primitives::transaction::Transaction;
+use tw_chain::primitives::transaction::{OutPoint, Transaction};
 use tw_chain::utils::transaction_utils::{construct_tx_hash, get_inputs_previous_out_point};
 
 pub const DB_SPEC: SimpleDbSpec = SimpleDbSpec {
@@ -57,11 +66,48 @@ pub struct MinerWhitelist {
     pub miner_addresses: Option<HashSet<SocketAddr>>,
 }
 
+impl MinerWhitelist {
+    /// Add API keys and addresses to the whitelist
+    ///
+    /// ### Arguments
+    ///
+    /// * `api_keys`  - Miner API keys to allow
+    /// * `addresses` - Miner addresses to allow
+    pub fn add_entries(&mut self, api_keys: Vec<String>, addresses: Vec<SocketAddr>) {
+        if !api_keys.is_empty() {
+            let keys = self.miner_api_keys.get_or_insert_with(Default::default);
+            keys.extend(api_keys);
+        }
+        if !addresses.is_empty() {
+            let addrs = self.miner_addresses.get_or_insert_with(Default::default);
+            addrs.extend(addresses);
+        }
+    }
+
+    /// Remove API keys and addresses from the whitelist
+    ///
+    /// ### Arguments
+    ///
+    /// * `api_keys`  - Miner API keys to deny
+    /// * `addresses` - Miner addresses to deny
+    pub fn remove_entries(&mut self, api_keys: &[String], addresses: &[SocketAddr]) {
+        if let Some(keys) = &mut self.miner_api_keys {
+            keys.retain(|key| !api_keys.contains(key));
+        }
+        if let Some(addrs) = &mut self.miner_addresses {
+            addrs.retain(|addr| !addresses.contains(addr));
+        }
+    }
+}
+
 /// Item serialized into RaftData and process by Raft.
+/// Persisted in RAFT logs: add new variants last and keep existing payloads unchanged.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(clippy::large_enum_variant)]
 pub enum MempoolRaftItem {
+    /// First block vote without a block size, as logged by older nodes
     FirstBlock(BTreeMap<String, Transaction>),
+    /// Block vote without an assembly ruleset hash, as logged by older nodes
     Block(BlockStoredInfo),
     Transactions(BTreeMap<String, Transaction>),
     DruidTransactions(Vec<BTreeMap<String, Transaction>>),
@@ -69,6 +115,12 @@ pub enum MempoolRaftItem {
     CoordinatedCmd(CoordinatedCommand),
     Timestamp(i64),
     RuntimeData(MempoolRuntimeItem),
+    Pause,
+    Resume,
+    /// First block vote with the proposer's configured block size
+    FirstBlockSized(BTreeMap<String, Transaction>, usize),
+    /// Block vote with the short hash of the proposer's assembly ruleset
+    BlockWithRuleset(BlockStoredInfo, String),
 }
 
 /// Mempool RAFT runtime item; will not get stored to disk
@@ -76,6 +128,72 @@ pub enum MempoolRaftItem {
 pub enum MempoolRuntimeItem {
     AddMiningApiKeys(Vec<(SocketAddr, String)>),
     RemoveMiningApiKeys(Vec<SocketAddr>),
+    AssemblyRuleset(AssemblyRuleset),
+}
+
+/// Resolved values deciding which transactions a block contains.
+/// Block votes carry its short hash so a peer applying a different default
+/// is caught at the vote rather than through a divergent block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssemblyRuleset {
+    pub version: u32,
+    pub block_size_in_tx: usize,
+    pub partition_full_size: usize,
+    pub coinbase_maturity: u64,
+    pub activation_height_asert: u64,
+    pub initial_issuances: Vec<InitialIssuance>,
+    pub difficulty_adjustment: Option<DifficultyAdjustmentConfig>,
+}
+
+impl AssemblyRuleset {
+    /// Short hex encoded hash included in block votes
+    pub fn short_hash(&self) -> String {
+        hex::encode(&canonical_hash(self)[..8])
+    }
+
+    /// Parameters with different values as (name, our value, their value)
+    ///
+    /// ### Arguments
+    ///
+    /// * `other` - Ruleset to compare against
+    pub fn differences(&self, other: &Self) -> Vec<(String, String, String)> {
+        let ours = serde_json::to_value(self).unwrap_or_default();
+        let theirs = serde_json::to_value(other).unwrap_or_default();
+        let (ours, theirs) = match (ours, theirs) {
+            (serde_json::Value::Object(ours), serde_json::Value::Object(theirs)) => (ours, theirs),
+            _ => return Vec::new(),
+        };
+
+        ours.into_iter()
+            .filter_map(|(name, value)| {
+                let other_value = theirs.get(&name).cloned().unwrap_or_default();
+                (value != other_value).then(|| (name, value.to_string(), other_value.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Assembly parameters a peer resolved differently from us
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssemblyRulesetMismatch {
+    pub proposer_id: u64,
+    pub parameters: Vec<(String, String, String)>,
+}
+
+/// Local pools not yet proposed, persisted across restarts
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct LocalPools {
+    tx_pool: BTreeMap<String, Transaction>,
+    tx_druid_pool: Vec<BTreeMap<String, Transaction>>,
+}
+
+/// Outcome of a graceful shutdown of the mempool raft
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// Proposals in flight committed before closing
+    pub drained: usize,
+    /// Local pool entries persisted, including transactions of proposals not committed in time
+    pub persisted: usize,
 }
 
 /// Commited item to process.
@@ -91,6 +209,9 @@ pub enum CommittedItem {
     Transactions,
     Snapshot,
     CoordinatedCmd(CoordinatedCommand),
+    Paused,
+    Resumed,
+    BlockPaused,
 }
 
 impl From<MiningPipelinePhaseChange> for CommittedItem {
@@ -189,6 +310,54 @@ pub struct MempoolConsensusedPreDifficulty {
     init_issuances: Vec<InitialIssuance>,
 }
 
+/// Layout of consensus snapshots from before the block fees, rollback journal,
+/// difficulty adjustment and pause state were consensused
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MempoolConsensusedPreFees {
+    /// Sufficient majority
+    unanimous_majority: usize,
+    /// Sufficient majority
+    sufficient_majority: usize,
+    /// Number of miners
+    partition_full_size: usize,
+    /// Committed transaction pool.
+    tx_pool: BTreeMap<String, Transaction>,
+    /// Committed DRUID transactions.
+    tx_druid_pool: Vec<BTreeMap<String, Transaction>>,
+    /// Header to use for next block if ready to generate.
+    tx_current_block_previous_hash: Option<String>,
+    /// The very first block to consensus.
+    initial_utxo_txs: Option<BTreeMap<String, Transaction>>,
+    /// UTXO set containing the valid transaction to use as previous input hashes.
+    utxo_set: TrackedUtxoSet,
+    /// Accumulating block:
+    /// Requires majority of mempool node votes for normal blocks.
+    /// Requires unanimous vote for first block.
+    current_block_stored_info: BTreeMap<Vec<u8>, (AccumulatingBlockStoredInfo, BTreeSet<u64>)>,
+    /// Coordinated commands sent through RAFT
+    /// Requires unanimous vote
+    current_raft_coordinated_cmd_stored_info: BTreeMap<CoordinatedCommand, BTreeSet<u64>>,
+    /// The last commited raft index.
+    last_committed_raft_idx_and_term: (u64, u64),
+    /// The current circulation of tokens
+    current_issuance: TokenAmount,
+    /// The block pipeline
+    block_pipeline: MiningPipelineInfo,
+    /// The last mining rewards.
+    last_mining_transaction_hashes: Vec<String>,
+    /// Special handling for processing blocks.
+    special_handling: Option<SpecialHandling>,
+    /// Whitelisted miner nodes.
+    miner_whitelist: MinerWhitelist,
+    /// Timestamp for the current block
+    timestamp: i64,
+    /// Runtime data that does not get stored to disk
+    #[serde(skip)]
+    runtime_data: MempoolConsensusedRuntimeData,
+    /// Initial issuances
+    init_issuances: Vec<InitialIssuance>,
+}
+
 /// All fields that are consensused between the RAFT group.
 /// These fields need to be written and read from a committed log event.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -213,6 +382,34 @@ pub struct MempoolConsensused {
     /// Requires majority of mempool node votes for normal blocks.
     /// Requires unanimous vote for first block.
     current_block_stored_info: BTreeMap<Vec<u8>, (AccumulatingBlockStoredInfo, BTreeSet<u64>)>,
+    /// Assembly ruleset hash of each block vote for the current block
+    #[serde(skip)]
+    current_block_ruleset_hashes: BTreeMap<u64, String>,
+    /// Time the first vote for the current block stored info was received
+    #[serde(skip)]
+    current_block_stored_info_started_at: Option<Instant>,
+    /// Duration after which the block stored info with most votes is applied below majority
+    #[serde(skip)]
+    block_stored_info_timeout: Option<Duration>,
+    /// Maximum number of transactions in a block, agreed with the first block
+    #[serde(skip)]
+    block_size_in_tx: usize,
+    /// UTXO entries spent by the block being mined, kept to roll it back.
+    /// Part of the snapshot so a node restored from it can roll back like its peers.
+    current_block_spent_utxo: UtxoSet,
+    /// Pooled transactions and DRUID droplets in the block being mined,
+    /// returned to their pools if it is rolled back
+    current_block_pooled_txs: (
+        BTreeMap<String, Transaction>,
+        Vec<BTreeMap<String, Transaction>>,
+    ),
+    /// Hash of committed transaction batches with the block number they were committed at,
+    /// so a batch re-proposed under a new key is only applied once
+    #[serde(skip)]
+    committed_tx_batches: BTreeMap<Vec<u8>, u64>,
+    /// Number of transactions in each of the most recent blocks, for fee estimation
+    #[serde(skip)]
+    recent_block_fills: VecDeque<usize>,
     /// Coordinated commands sent through RAFT
     /// Requires unanimous vote
     current_raft_coordinated_cmd_stored_info: BTreeMap<CoordinatedCommand, BTreeSet<u64>>,
@@ -220,12 +417,22 @@ pub struct MempoolConsensused {
     last_committed_raft_idx_and_term: (u64, u64),
     /// The current circulation of tokens
     current_issuance: TokenAmount,
+    /// Fees paid to the miner by the transactions of the block being mined
+    current_block_fees: TokenAmount,
+    /// Difficulty retargeted from the timestamps of the blocks mined
+    difficulty_adjustment: DifficultyAdjustment,
+    /// Settings of the difficulty adjustment, disabled if not set.
+    /// Agreed through the assembly ruleset hash carried by block votes
+    #[serde(skip)]
+    difficulty_adjustment_config: Option<DifficultyAdjustmentConfig>,
     /// The block pipeline
     block_pipeline: MiningPipelineInfo,
     /// The last mining rewards.
     last_mining_transaction_hashes: Vec<String>,
     /// Special handling for processing blocks.
     special_handling: Option<SpecialHandling>,
+    /// Whether block generation is paused for maintenance
+    paused: bool,
     /// Whitelisted miner nodes.
     miner_whitelist: MinerWhitelist,
     /// Timestamp for the current block
@@ -267,14 +474,20 @@ pub struct MempoolRaft {
     consensused_snapshot_applied: bool,
     /// Initial item to propose when ready.
     local_initial_proposal: Option<InitialProposal>,
-    /// Local transaction pool.
-    local_tx_pool: BTreeMap<String, Transaction>,
+    /// Local transaction pool, with the time each transaction was added.
+    local_tx_pool: BTreeMap<String, (Transaction, Instant)>,
+    /// Maximum time a transaction waits in the local pool before being evicted.
+    local_tx_max_age: Duration,
     /// Local DRUID transaction pool.
     local_tx_druid_pool: Vec<BTreeMap<String, Transaction>>,
+    /// Maximum number of transactions waiting in the local and consensused pools.
+    tx_pool_limit: usize,
     /// Ordered transaction hashes from the last commit.
     local_tx_hash_last_commited: Vec<String>,
-    /// Min duration between each transaction poposal.
+    /// Max duration between each transaction poposal, unless a full batch is ready earlier.
     propose_transactions_timeout_duration: Duration,
+    /// Duration after which uncommitted transaction proposals are re-proposed.
+    proposal_timeout_duration: Duration,
     /// Timeout expiration time for transactimining_pipeline_statusons poposal.
     propose_transactions_timeout_at: Instant,
     /// Min duration between each event in the mining pipeline.
@@ -283,6 +496,8 @@ pub struct MempoolRaft {
     propose_mining_event_timeout_at: Instant,
     /// Proposed items in flight.
     proposed_in_flight: RaftInFlightProposals,
+    /// Maximum items in flight for proposing more transactions.
+    max_in_flight: usize,
     /// Proposed transaction in flight length.
     proposed_tx_pool_len: usize,
     /// Maximum transaction in flight length.
@@ -291,12 +506,22 @@ pub struct MempoolRaft {
     proposed_and_consensused_tx_pool_len_max: usize,
     /// No longer process commits after shutdown reached
     shutdown_no_commit_process: bool,
+    /// No longer propose local transactions once shutting down
+    proposals_closed: bool,
     /// Check for backup needed
     backup_check: BackupCheck,
     /// Check UTXO set alignment if needed
     utxo_re_align_check: UtxoReAlignCheck,
+    /// Persist snapshot without RAFT for block that given modulo result in 0
+    snapshot_block_modulo: Option<u64>,
     /// Timestamp of the current block
     timestamp: i64,
+    /// Assembly ruleset resolved from our configuration
+    assembly_ruleset: AssemblyRuleset,
+    /// Block number for which we shared our full assembly ruleset
+    assembly_ruleset_shared_b_num: Option<u64>,
+    /// Assembly parameters peers resolved differently from us
+    assembly_ruleset_mismatches: Vec<AssemblyRulesetMismatch>,
 }
 
 impl fmt::Debug for MempoolRaft {
@@ -338,10 +563,15 @@ impl MempoolRaft {
         let propose_transactions_timeout_duration =
             Duration::from_millis(config.mempool_transaction_timeout as u64);
         let propose_transactions_timeout_at = Instant::now();
+        let proposal_timeout_duration =
+            Duration::from_millis(config.mempool_proposal_timeout as u64);
+        let local_tx_max_age = Duration::from_millis(config.mempool_local_tx_max_age as u64);
 
         let propose_mining_event_timeout_duration =
             Duration::from_millis(config.mempool_mining_event_timeout as u64);
         let propose_mining_event_timeout_at = Instant::now();
+        let block_stored_info_timeout =
+            Duration::from_millis(config.mempool_block_stored_info_timeout as u64);
 
         let utxo_set =
             make_utxo_set_from_seed(&config.mempool_seed_utxo, &config.mempool_genesis_tx_in);
@@ -352,6 +582,15 @@ impl MempoolRaft {
         let activation_height_asert = config
             .activation_height_asert
             .unwrap_or(crate::constants::ACTIVATION_HEIGHT_ASERT);
+        let block_size_in_tx = config.block_size_in_tx.unwrap_or(BLOCK_SIZE_IN_TX);
+        let tx_pool_limit = config.tx_pool_limit.unwrap_or(TX_POOL_LIMIT);
+        let max_in_flight = config.max_in_flight.unwrap_or(MAX_IN_FLIGHT_PROPOSALS);
+        // Validated when loading the node configuration
+        let difficulty_adjustment_config = DifficultyAdjustmentConfig::from_config(
+            config.target_block_interval_secs,
+            config.difficulty_adjustment_period,
+        )
+        .unwrap_or_default();
 
         let consensused = MempoolConsensused::default()
             .with_peers_len(peers_len)
@@ -359,36 +598,64 @@ impl MempoolRaft {
             .with_unicorn_fixed_param(config.mempool_unicorn_fixed_param.clone())
             .with_initial_issuances(config.initial_issuances.clone())
             .with_activation_height_asert(activation_height_asert)
+            .with_block_stored_info_timeout(block_stored_info_timeout)
+            .with_block_size_in_tx(block_size_in_tx)
+            .with_difficulty_adjustment(difficulty_adjustment_config)
             .init_block_pipeline_status();
         let local_initial_proposal = Some(InitialProposal::PendingItem {
-            item: MempoolRaftItem::FirstBlock(utxo_set),
+            item: MempoolRaftItem::FirstBlockSized(utxo_set, block_size_in_tx),
             dedup_b_num: None,
         });
         let backup_check = BackupCheck::new(config.backup_block_modulo);
         let utxo_re_align_check = UtxoReAlignCheck::new(config.utxo_re_align_block_modulo);
+        let assembly_ruleset = AssemblyRuleset {
+            version: ASSEMBLY_RULESET_VERSION,
+            block_size_in_tx,
+            partition_full_size: config.mempool_partition_full_size,
+            coinbase_maturity: COINBASE_MATURITY,
+            activation_height_asert,
+            initial_issuances: config.initial_issuances.clone(),
+            difficulty_adjustment: difficulty_adjustment_config,
+        };
 
-        Self {
+        let mut node = Self {
             first_raft_peer,
             raft_active,
             consensused,
             consensused_snapshot_applied: !use_raft,
             local_initial_proposal,
             local_tx_pool: Default::default(),
+            local_tx_max_age,
             local_tx_druid_pool: Default::default(),
+            tx_pool_limit,
             local_tx_hash_last_commited: Default::default(),
             propose_transactions_timeout_duration,
+            proposal_timeout_duration,
             propose_transactions_timeout_at,
             propose_mining_event_timeout_duration,
             propose_mining_event_timeout_at,
             proposed_in_flight: Default::default(),
+            max_in_flight,
             proposed_tx_pool_len: 0,
-            proposed_tx_pool_len_max: BLOCK_SIZE_IN_TX / peers_len,
-            proposed_and_consensused_tx_pool_len_max: BLOCK_SIZE_IN_TX * 2,
+            proposals_closed: false,
+            // Each peer proposes at least one transaction when blocks are smaller than the group
+            proposed_tx_pool_len_max: std::cmp::max(block_size_in_tx / peers_len, 1),
+            proposed_and_consensused_tx_pool_len_max: block_size_in_tx * 2,
             shutdown_no_commit_process: false,
             backup_check,
             utxo_re_align_check,
+            snapshot_block_modulo: config.mempool_snapshot_block_modulo,
             timestamp,
+            assembly_ruleset,
+            assembly_ruleset_shared_b_num: None,
+            assembly_ruleset_mismatches: Default::default(),
+        };
+
+        // Without RAFT, restore the persisted snapshot before any new commit
+        if let Some(snapshot) = node.raft_active.take_bypass_snapshot() {
+            node.received_commit(snapshot).await;
         }
+        node
     }
 
     /// Get runtime data
@@ -500,6 +767,11 @@ impl MempoolRaft {
         self.raft_active.raft_peer_addrs()
     }
 
+    /// Returns a shareable view of the raft membership and leadership
+    pub fn raft_info(&self) -> RaftInfo {
+        self.raft_active.raft_info()
+    }
+
     /// Blocks & waits for a next event from a peer.
     pub fn raft_loop(&self) -> impl Future<Output = ()> {
         self.raft_active.raft_loop()
@@ -507,9 +779,61 @@ impl MempoolRaft {
 
     /// Signal to the raft loop to complete
     pub async fn close_raft_loop(&mut self) {
+        if !self.consensused.is_first_block() {
+            self.persist_bypass_snapshot().await;
+        }
         self.raft_active.close_raft_loop().await
     }
 
+    /// Stop proposing, wait up to `timeout` for the proposals in flight to commit,
+    /// persist the local pools with the transactions of the proposals left,
+    /// then signal to the raft loop to complete.
+    ///
+    /// ### Arguments
+    ///
+    /// * `db`      - Database to store the local pools in
+    /// * `timeout` - Maximum time to wait for the proposals in flight
+    pub async fn shutdown(&mut self, db: &mut SimpleDb, timeout: Duration) -> ShutdownSummary {
+        self.proposals_closed = true;
+        let in_flight = self.proposed_in_flight.len();
+        let deadline = Instant::now() + timeout;
+
+        while !self.proposed_in_flight.is_empty() && Instant::now() < deadline {
+            match time::timeout_at(deadline, self.next_commit()).await {
+                Ok(Some(commit)) => {
+                    self.received_commit(commit).await;
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        let drained = in_flight.saturating_sub(self.proposed_in_flight.len());
+
+        for data in self.proposed_in_flight.take_all_items() {
+            match bincode::deserialize(&data) {
+                Ok(MempoolRaftItem::Transactions(txs)) => {
+                    self.proposed_tx_pool_len = self.proposed_tx_pool_len.saturating_sub(txs.len());
+                    self.append_to_tx_pool(txs);
+                }
+                Ok(MempoolRaftItem::DruidTransactions(droplets)) => {
+                    self.local_tx_druid_pool.extend(droplets);
+                }
+                _ => (),
+            }
+        }
+
+        let persisted = self.local_tx_pool.len() + self.local_tx_druid_pool.len();
+        if let Err(e) = self.save_local_pools(db) {
+            error!("Error saving local pools: {:?}", e);
+        }
+        info!(
+            "Mempool raft shutdown: {} proposals drained, {} local pool entries persisted",
+            drained, persisted
+        );
+
+        self.close_raft_loop().await;
+        ShutdownSummary { drained, persisted }
+    }
+
     /// Extract persistent storage of a closed raft
     pub async fn take_closed_persistent_store(&mut self) -> SimpleDb {
         self.raft_active.take_closed_persistent_store().await
@@ -535,8 +859,16 @@ impl MempoolRaft {
     /// ### Arguments
     /// * 'raft_commit' - a RaftCommit struct from the raft.rs class to be proposed to commit.
     pub async fn received_commit(&mut self, raft_commit: RaftCommit) -> Option<CommittedItem> {
+        if let RaftCommitData::Proposed(..) = raft_commit.data {
+            let (last_idx, _) = self.consensused.last_committed_raft_idx_and_term;
+            if raft_commit.index != 0 && raft_commit.index <= last_idx {
+                trace!("Ignore commit already applied {}", raft_commit.index);
+                return None;
+            }
+        }
+
         self.consensused.last_committed_raft_idx_and_term = (raft_commit.index, raft_commit.term);
-        match raft_commit.data {
+        let committed = match raft_commit.data {
             RaftCommitData::Proposed(data, context) => {
                 self.received_commit_proposal(data, context).await
             }
@@ -547,7 +879,25 @@ impl MempoolRaft {
                     .await;
                 None
             }
+        };
+
+        if committed.is_none() && self.consensused.block_stored_info_timed_out() {
+            return self.apply_timed_out_block_stored_info().await;
         }
+        committed
+    }
+
+    /// Apply the block stored info with most votes when votes did not reach
+    /// a majority before the timeout, e.g. a peer crashed mid-round.
+    async fn apply_timed_out_block_stored_info(&mut self) -> Option<CommittedItem> {
+        let b_num = self.consensused.block_pipeline.current_block_num()?;
+        warn!(
+            "Block stored info for block {} timed out below majority ({} of {} votes): applying most voted",
+            b_num,
+            self.consensused.max_agreeing_block_stored_info(),
+            self.consensused.sufficient_majority
+        );
+        self.apply_block_stored_info(b_num).await
     }
 
     /// Apply snapshot
@@ -567,28 +917,25 @@ impl MempoolRaft {
             // and whether to patch in the UNICORN fixed parameters too.
             warn!("apply_snapshot called self.consensused updated");
 
-            let consensus_check: Result<MempoolConsensused, _> = try_deserialize(&consensused_ser);
+            let block_stored_info_timeout = self.consensused.block_stored_info_timeout;
+            let block_size_in_tx = self.consensused.block_size_in_tx;
+            let difficulty_adjustment_config = self.consensused.difficulty_adjustment_config;
+            let recent_block_fills = std::mem::take(&mut self.consensused.recent_block_fills);
 
-            // Handle the case where the snapshot is from a previous version
-            self.consensused = match consensus_check {
-                Ok(consensused) => consensused,
+            // Majorities follow our raft group rather than the one the snapshot was taken in
+            self.consensused = match MempoolConsensused::from_snapshot(&consensused_ser) {
+                Ok(consensused) => consensused.with_peers_len(self.raft_active.peers_len()),
                 Err(e) => {
-                    warn!("Deserialization of consensus snapshot failed: {:?}", e);
-                    warn!("Attempting to deserialize as a previous version");
-
-                    let consensus_prediff: Result<MempoolConsensusedPreDifficulty, _> =
-                        try_deserialize(&consensused_ser);
-                    match consensus_prediff {
-                        Ok(consensused) => consensused.into(),
-                        Err(e) => {
-                            error!("Deserialization of consensus snapshot failed for previous difficulty: {:?}", e);
-                            error!("apply_snapshot deserialize error: {:?}", e);
-                            return None;
-                        }
-                    }
+                    error!("apply_snapshot deserialize error: {:?}", e);
+                    return None;
                 }
             };
 
+            self.consensused.block_stored_info_timeout = block_stored_info_timeout;
+            self.consensused.block_size_in_tx = block_size_in_tx;
+            self.consensused.difficulty_adjustment_config = difficulty_adjustment_config;
+            self.consensused.recent_block_fills = recent_block_fills;
+
             debug!(
                 "apply_snapshot called self.consensused updated: tx_current_block_num({:?})",
                 self.consensused.block_pipeline.current_block_num()
@@ -633,27 +980,18 @@ impl MempoolRaft {
         trace!("received_commit_proposal {:?} -> {:?}", key, item);
         match item {
             MempoolRaftItem::FirstBlock(uxto_set) => {
-                if !self.consensused.is_first_block() {
-                    error!("Proposed FirstBlock after startup {:?}", key);
-                    return None;
-                }
-
-                self.consensused.append_first_block_info(key, uxto_set);
-                if self.consensused.has_different_block_stored_info() {
-                    error!("Proposed uxtosets are different {:?}", key);
-                }
-
-                if self.consensused.has_block_stored_info_ready() {
-                    // First block complete:
-                    self.consensused.apply_ready_block_stored_info();
-                    self.consensused.generate_first_block().await;
-                    self.consensused.start_items_intake();
-                    self.set_next_propose_mining_event_timeout_at();
-                    self.event_processed_generate_snapshot();
-                    return Some(CommittedItem::FirstBlock);
-                }
+                return self.received_first_block_vote(key, uxto_set, None).await;
+            }
+            MempoolRaftItem::FirstBlockSized(uxto_set, block_size_in_tx) => {
+                return self
+                    .received_first_block_vote(key, uxto_set, Some(block_size_in_tx))
+                    .await;
             }
             MempoolRaftItem::Transactions(mut txs) => {
+                if !self.consensused.insert_committed_tx_batch(&txs) {
+                    trace!("Ignore already committed transactions {:?}", key);
+                    return None;
+                }
                 self.local_tx_hash_last_commited = txs.keys().cloned().collect();
                 self.consensused.tx_pool.append(&mut txs);
                 return Some(CommittedItem::Transactions);
@@ -666,35 +1004,11 @@ impl MempoolRaft {
                 self.consensused.timestamp = timestamp;
             }
             MempoolRaftItem::Block(info) => {
-                let b_num = info.block_num;
-                if !self.consensused.is_current_block(info.block_num) {
-                    trace!("Ignore invalid or outdated block stored info {:?}", key);
-                    return None;
-                }
-
-                self.consensused.append_block_stored_info(key, info);
-                if self.consensused.has_different_block_stored_info() {
-                    warn!("Proposed previous blocks are different {:?}", key);
-                }
-
-                if self.consensused.has_block_stored_info_ready() {
-                    // New block:
-                    // Must not populate further tx_pool & tx_druid_pool
-                    // before generating block.
-                    self.consensused.apply_ready_block_stored_info();
-                    if self.is_shutdown_on_commit() {
-                        self.event_processed_re_align_utxo_set(b_num);
-                        self.event_processed_generate_snapshot();
-                        return Some(CommittedItem::BlockShutdown);
-                    } else {
-                        self.consensused.generate_block().await;
-                        self.consensused.start_items_intake();
-                        self.set_next_propose_mining_event_timeout_at();
-                        self.event_processed_re_align_utxo_set(b_num);
-                        self.event_processed_generate_snapshot();
-                        return Some(CommittedItem::Block);
-                    }
-                }
+                // Older votes only agree with each other
+                return self.received_block_vote(key, info, String::new()).await;
+            }
+            MempoolRaftItem::BlockWithRuleset(info, ruleset_hash) => {
+                return self.received_block_vote(key, info, ruleset_hash).await;
             }
             MempoolRaftItem::PipelineItem(mining_pipeline_item, b_num) => {
                 if !self.consensused.is_current_block(b_num) {
@@ -738,13 +1052,227 @@ impl MempoolRaft {
                     return Some(CommittedItem::CoordinatedCmd(coordinated_command));
                 }
             }
+            MempoolRaftItem::RuntimeData(MempoolRuntimeItem::AssemblyRuleset(ruleset)) => {
+                self.received_assembly_ruleset(key, ruleset);
+            }
             MempoolRaftItem::RuntimeData(runtime_item) => {
                 self.consensused.handle_runtime_item(runtime_item);
             }
+            MempoolRaftItem::Pause => {
+                if self.consensused.is_paused() {
+                    trace!("Ignore pause: already paused {:?}", key);
+                    return None;
+                }
+                self.consensused.paused = true;
+                return Some(CommittedItem::Paused);
+            }
+            MempoolRaftItem::Resume => return self.received_resume(key).await,
+        }
+        None
+    }
+
+    /// Process a committed resume, applying the block stored info
+    /// that became ready while paused.
+    ///
+    /// ### Arguments
+    ///
+    /// * `key` - Key of the proposal
+    async fn received_resume(&mut self, key: RaftContextKey) -> Option<CommittedItem> {
+        if !self.consensused.is_paused() {
+            trace!("Ignore resume: not paused {:?}", key);
+            return None;
+        }
+        self.consensused.paused = false;
+
+        // Votes kept during the pause may only apply to the block being mined
+        let b_num = self.consensused.block_pipeline.current_block_num()?;
+        self.consensused.retain_block_stored_info_for(b_num);
+        if self.consensused.has_block_stored_info_ready() {
+            return self.apply_block_stored_info(b_num).await;
+        }
+        Some(CommittedItem::Resumed)
+    }
+
+    /// Process a committed vote for the first block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `key`              - Key of the vote
+    /// * `uxto_set`         - Voted seed UTXO set
+    /// * `block_size_in_tx` - The voter's block size, unknown for votes of older nodes
+    async fn received_first_block_vote(
+        &mut self,
+        key: RaftContextKey,
+        uxto_set: BTreeMap<String, Transaction>,
+        block_size_in_tx: Option<usize>,
+    ) -> Option<CommittedItem> {
+        if !self.consensused.is_first_block() {
+            error!("Proposed FirstBlock after startup {:?}", key);
+            return None;
+        }
+        if let Some(block_size_in_tx) = block_size_in_tx {
+            if !self.received_first_block_size(key, block_size_in_tx) {
+                return None;
+            }
+        }
+
+        self.consensused.append_first_block_info(key, uxto_set);
+        if self.consensused.has_different_block_stored_info() {
+            error!("Proposed uxtosets are different {:?}", key);
+        }
+
+        if self.consensused.has_block_stored_info_ready() {
+            // First block complete:
+            self.consensused.apply_ready_block_stored_info();
+            self.consensused.generate_first_block().await;
+            self.consensused.start_items_intake();
+            self.set_next_propose_mining_event_timeout_at();
+            self.event_processed_generate_snapshot().await;
+            return Some(CommittedItem::FirstBlock);
+        }
+        None
+    }
+
+    /// Process a committed vote for the previous block info.
+    /// Votes only agree if their assembly ruleset hashes agree.
+    ///
+    /// ### Arguments
+    ///
+    /// * `key`          - Key of the vote
+    /// * `info`         - Voted previous block info
+    /// * `ruleset_hash` - Short hash of the voter's assembly ruleset
+    async fn received_block_vote(
+        &mut self,
+        key: RaftContextKey,
+        info: BlockStoredInfo,
+        ruleset_hash: String,
+    ) -> Option<CommittedItem> {
+        let b_num = info.block_num;
+        if !self.consensused.is_current_block(info.block_num) {
+            trace!("Ignore invalid or outdated block stored info {:?}", key);
+            return None;
+        }
+
+        let was_ready = self.consensused.has_block_stored_info_ready();
+        self.consensused
+            .append_block_stored_info(key, info, ruleset_hash);
+        if self.consensused.has_different_block_ruleset() {
+            error!(
+                "Block assembly rulesets are different for block {}: {:?}",
+                b_num, self.consensused.current_block_ruleset_hashes
+            );
+            if self.assembly_ruleset_shared_b_num != Some(b_num) {
+                // Share our full ruleset so peers can name the differing parameters
+                self.assembly_ruleset_shared_b_num = Some(b_num);
+                let ruleset = self.assembly_ruleset.clone();
+                let item =
+                    MempoolRaftItem::RuntimeData(MempoolRuntimeItem::AssemblyRuleset(ruleset));
+                self.propose_item(&item).await;
+            }
+        } else if self.consensused.has_different_block_stored_info() {
+            warn!("Proposed previous blocks are different {:?}", key);
+        }
+
+        if self.consensused.has_block_stored_info_ready() {
+            if self.consensused.is_paused() {
+                // Keep the votes to generate the next block on resume
+                debug!("Block {} stored while paused: next block withheld", b_num);
+                return (!was_ready).then_some(CommittedItem::BlockPaused);
+            }
+            return self.apply_block_stored_info(b_num).await;
         }
         None
     }
 
+    /// Apply the accumulated block stored info and generate the next block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `b_num` - Number of the block the info was voted for
+    async fn apply_block_stored_info(&mut self, b_num: u64) -> Option<CommittedItem> {
+        // New block:
+        // Must not populate further tx_pool & tx_druid_pool
+        // before generating block.
+        self.consensused.apply_ready_block_stored_info();
+        if self.is_shutdown_on_commit() {
+            self.event_processed_re_align_utxo_set(b_num);
+            self.event_processed_generate_snapshot().await;
+            Some(CommittedItem::BlockShutdown)
+        } else {
+            self.consensused.generate_block().await;
+            self.consensused.start_items_intake();
+            self.set_next_propose_mining_event_timeout_at();
+            self.event_processed_re_align_utxo_set(b_num);
+            self.event_processed_generate_snapshot().await;
+            Some(CommittedItem::Block)
+        }
+    }
+
+    /// Compare a peer's full assembly ruleset with ours and alert on differences
+    ///
+    /// ### Arguments
+    ///
+    /// * `key`     - Key of the proposal
+    /// * `ruleset` - The peer's resolved assembly ruleset
+    fn received_assembly_ruleset(&mut self, key: RaftContextKey, ruleset: AssemblyRuleset) {
+        let parameters = self.assembly_ruleset.differences(&ruleset);
+        if parameters.is_empty() {
+            return;
+        }
+
+        for (name, ours, theirs) in &parameters {
+            error!(
+                "Block assembly parameter {} differs from peer {}: ours {}, theirs {}",
+                name, key.proposer_id, ours, theirs
+            );
+        }
+        self.assembly_ruleset_mismatches
+            .push(AssemblyRulesetMismatch {
+                proposer_id: key.proposer_id,
+                parameters,
+            });
+    }
+
+    /// Check a peer's block size in its first block vote matches ours.
+    /// A peer with a different size never agrees on the first block,
+    /// so the group halts at startup instead of assembling divergent blocks.
+    ///
+    /// ### Arguments
+    ///
+    /// * `key`              - Key of the first block vote
+    /// * `block_size_in_tx` - The peer's configured block size
+    fn received_first_block_size(&mut self, key: RaftContextKey, block_size_in_tx: usize) -> bool {
+        let ours = self.consensused.block_size_in_tx;
+        if block_size_in_tx == ours {
+            return true;
+        }
+
+        error!(
+            "Block size differs from peer {}: ours {}, theirs {}. Cannot agree on the first block",
+            key.proposer_id, ours, block_size_in_tx
+        );
+        self.assembly_ruleset_mismatches
+            .push(AssemblyRulesetMismatch {
+                proposer_id: key.proposer_id,
+                parameters: vec![(
+                    "block_size_in_tx".to_owned(),
+                    ours.to_string(),
+                    block_size_in_tx.to_string(),
+                )],
+            });
+        false
+    }
+
+    /// Get the assembly ruleset resolved from our configuration
+    pub fn get_assembly_ruleset(&self) -> &AssemblyRuleset {
+        &self.assembly_ruleset
+    }
+
+    /// Get the assembly parameters peers resolved differently from us
+    pub fn get_assembly_ruleset_mismatches(&self) -> &[AssemblyRulesetMismatch] {
+        &self.assembly_ruleset_mismatches
+    }
+
     /// Blocks & waits for a new mining pipeline event.
     pub async fn timeout_propose_mining_event(&self) {
         time::sleep_until(self.propose_mining_event_timeout_at).await;
@@ -807,7 +1335,8 @@ impl MempoolRaft {
     /// Process as received block info necessary for new block to be generated.
     pub async fn propose_block_with_last_info(&mut self, block: BlockStoredInfo) -> bool {
         let b_num = block.block_num;
-        let item = MempoolRaftItem::Block(block);
+        let ruleset_hash = self.assembly_ruleset.short_hash();
+        let item = MempoolRaftItem::BlockWithRuleset(block, ruleset_hash);
 
         match self.local_initial_proposal {
             None | Some(InitialProposal::PendingAuthorized) => {
@@ -904,6 +1433,16 @@ impl MempoolRaft {
         .await;
     }
 
+    /// Propose to pause block generation on all peers
+    pub async fn propose_pause(&mut self) {
+        self.propose_item(&MempoolRaftItem::Pause).await;
+    }
+
+    /// Propose to resume block generation on all peers
+    pub async fn propose_resume(&mut self) {
+        self.propose_item(&MempoolRaftItem::Resume).await;
+    }
+
     /// Propose to apply a shared config
     ///
     /// NOTE: Requires a unanimous majority vote
@@ -914,26 +1453,87 @@ impl MempoolRaft {
         .await;
     }
 
+    /// Propose transactions without waiting for the timeout once a full batch is available
+    fn propose_transactions_now_if_batch_full(&mut self) {
+        if self.local_tx_pool.len() >= self.proposed_tx_pool_len_max {
+            self.propose_transactions_timeout_at = Instant::now();
+        }
+    }
+
     /// Process as a result of timeout_propose_transactions.
-    /// Reset timeout, and propose local transactions if available.
+    /// Reset timeout, re-propose stale transactions in flight,
+    /// and propose local transactions if available.
     pub async fn propose_local_transactions_at_timeout(&mut self) {
         self.set_next_propose_transactions_timeout_at();
+        self.re_propose_expired_transactions().await;
+        self.evict_stale_local_txs(self.local_tx_max_age);
+        if self.proposals_closed || self.in_flight_saturated() {
+            return;
+        }
 
         let max_add = self
             .proposed_and_consensused_tx_pool_len_max
             .saturating_sub(self.proposed_and_consensused_tx_pool_len());
 
         let max_propose_len = std::cmp::min(max_add, self.proposed_tx_pool_len_max);
-        let txs = take_first_n(max_propose_len, &mut self.local_tx_pool);
+        let txs: BTreeMap<_, _> = take_first_n(max_propose_len, &mut self.local_tx_pool)
+            .into_iter()
+            .map(|(hash, (tx, _))| (hash, tx))
+            .collect();
         if !txs.is_empty() {
             self.proposed_tx_pool_len += txs.len();
             self.propose_item(&MempoolRaftItem::Transactions(txs)).await;
+            self.propose_transactions_now_if_batch_full();
+        }
+    }
+
+    /// Re-propose under a new key the transactions in flight for longer than
+    /// the proposal timeout, e.g. dropped on leader change or log truncation.
+    async fn re_propose_expired_transactions(&mut self) {
+        let is_transactions = |data: &[u8]| {
+            matches!(
+                bincode::deserialize(data),
+                Ok(MempoolRaftItem::Transactions(_))
+            )
+        };
+        let re_proposed = self
+            .proposed_in_flight
+            .re_propose_expired_items(
+                &mut self.raft_active,
+                self.proposal_timeout_duration,
+                is_transactions,
+            )
+            .await;
+        if !re_proposed.is_empty() {
+            warn!(
+                "Re-proposed {} stale transaction proposals",
+                re_proposed.len()
+            );
+        }
+    }
+
+    /// Whether too many proposals are in flight to propose more transactions,
+    /// leaving them in the local pools until commits drain the proposals
+    fn in_flight_saturated(&self) -> bool {
+        let in_flight = self.proposed_in_flight.len();
+        if in_flight < self.max_in_flight {
+            return false;
         }
+
+        warn!(
+            "Skipping transaction proposals: {} proposals in flight (max {})",
+            in_flight, self.max_in_flight
+        );
+        true
     }
 
     /// Process as a result of timeout_propose_transactions.
     /// Propose druid transactions if available.
     pub async fn propose_local_druid_transactions(&mut self) {
+        if self.proposals_closed || self.in_flight_saturated() {
+            return;
+        }
+
         let txs = std::mem::take(&mut self.local_tx_druid_pool);
         if !txs.is_empty() {
             self.propose_item(&MempoolRaftItem::DruidTransactions(txs))
@@ -1028,18 +1628,137 @@ impl MempoolRaft {
         &self.consensused.current_issuance
     }
 
-    /// Gets the current reward for a given block
-    pub fn get_current_reward(&self) -> &TokenAmount {
-        self.consensused.block_pipeline.get_current_reward()
+    /// Gets the current reward for a given block, including the fees of its transactions
+    pub fn get_current_reward(&self) -> TokenAmount {
+        let reward = self.consensused.block_pipeline.get_current_reward();
+        TokenAmount(reward.0 + self.consensused.current_block_fees.0)
     }
 
-    /// Whether adding these will grow our pool within the limit. Returns a bool.
-    pub fn tx_pool_can_accept(&self, extra_len: usize) -> bool {
-        self.combined_tx_pool_len() + extra_len <= TX_POOL_LIMIT
+    /// Gets the fees paid by the transactions of the block being mined
+    pub fn get_current_block_fees(&self) -> &TokenAmount {
+        &self.consensused.current_block_fees
     }
 
-    /// Get the local DRUID pool transactions
-    pub fn get_local_tx_druid_pool(&self) -> &Vec<BTreeMap<String, Transaction>> {
+    /// Fee suggested for a transaction to be included in the next blocks
+    pub fn estimate_fee(&self) -> FeeEstimate {
+        self.consensused.estimate_fee()
+    }
+
+    /// Difficulty of the block being mined, or reached by the adjustment if none is
+    pub fn get_current_difficulty(&self) -> CurrentDifficulty {
+        let (block_num, header_difficulty) = match self.get_mining_block() {
+            Some(block) => (Some(block.header.b_num), block.header.difficulty.clone()),
+            None => (
+                None,
+                self.consensused.difficulty_adjustment.header_difficulty(),
+            ),
+        };
+
+        let difficulty = if header_difficulty.is_empty() {
+            PoWDifficulty::LeadingZeroes(MINING_DIFFICULTY)
+        } else {
+            PoWDifficulty::CompactTarget(hex::encode(header_difficulty))
+        };
+        CurrentDifficulty {
+            block_num,
+            difficulty,
+        }
+    }
+
+    /// Whether adding these will grow our pool within the limit. Returns a bool.
+    pub fn tx_pool_can_accept(&self, extra_len: usize) -> bool {
+        self.combined_tx_pool_len() + extra_len <= self.tx_pool_limit
+    }
+
+    /// Whether adding these droplets will grow our DRUID pool within the limit. Returns a bool.
+    pub fn druid_pool_can_accept(&self, extra: usize) -> bool {
+        self.local_tx_druid_pool.len() + self.consensused.tx_druid_pool.len() + extra
+            <= DRUID_POOL_LIMIT
+    }
+
+    /// Number of transactions waiting at each stage before being added to a block
+    pub fn get_mempool_status(&self) -> MempoolStatus {
+        MempoolStatus {
+            local_tx_pool_len: self.local_tx_pool.len(),
+            proposed_tx_pool_len: self.proposed_tx_pool_len,
+            consensused_tx_pool_len: self.consensused.tx_pool.len(),
+            druid_pool_len: self.local_tx_druid_pool.len() + self.consensused.tx_druid_pool.len(),
+        }
+    }
+
+    /// Stage of a transaction in the block being mined or the transaction pools
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hash` - Hash of the transaction
+    pub fn get_tx_stage(&self, tx_hash: &str) -> TxStage {
+        let in_druid_pool =
+            |pool: &[BTreeMap<String, Transaction>]| pool.iter().any(|d| d.contains_key(tx_hash));
+
+        if self
+            .consensused
+            .block_pipeline
+            .get_mining_block_tx()
+            .contains_key(tx_hash)
+        {
+            TxStage::InCurrentBlock
+        } else if self.consensused.tx_pool.contains_key(tx_hash)
+            || in_druid_pool(&self.consensused.tx_druid_pool)
+            || self.local_tx_pool.contains_key(tx_hash)
+            || in_druid_pool(&self.local_tx_druid_pool)
+        {
+            TxStage::Pending
+        } else {
+            TxStage::Unknown
+        }
+    }
+
+    /// Read-only snapshot of the raft consensus state
+    pub fn get_raft_status(&self) -> MempoolRaftStatus {
+        let leader_id = self.raft_active.leader_id();
+        let block_stored_info_votes = self
+            .consensused
+            .current_block_stored_info
+            .values()
+            .map(|(_, voters)| voters.len())
+            .collect::<Vec<_>>();
+
+        MempoolRaftStatus {
+            peer_id: self.raft_active.peer_id(),
+            first_raft_peer: self.first_raft_peer,
+            leader_id,
+            is_leader: leader_id == Some(self.raft_active.peer_id()),
+            local_tx_pool_len: self.local_tx_pool.len(),
+            proposed_in_flight_len: self.proposed_in_flight.len(),
+            consensused_tx_pool_len: self.consensused.tx_pool.len(),
+            tx_druid_pool_len: self.consensused.tx_druid_pool.len(),
+            tx_current_block_num: self.get_committed_current_block_num(),
+            block_stored_info_proposals: block_stored_info_votes.len(),
+            block_stored_info_votes,
+            ..Default::default()
+        }
+    }
+
+    /// Inputs spent by transactions pending in the local and consensused pools,
+    /// with the hash of the spending transaction
+    pub fn get_pending_spent_inputs(&self) -> BTreeMap<OutPoint, String> {
+        let local_txs = self.local_tx_pool.iter().map(|(hash, (tx, _))| (hash, tx));
+        let druid_txs = self.local_tx_druid_pool.iter().flatten();
+        let consensused_druid_txs = self.consensused.tx_druid_pool.iter().flatten();
+
+        local_txs
+            .chain(self.consensused.tx_pool.iter())
+            .chain(druid_txs)
+            .chain(consensused_druid_txs)
+            .flat_map(|(hash, tx)| {
+                get_inputs_previous_out_point(Some(tx).into_iter())
+                    .map(move |op| (op.clone(), hash.clone()))
+            })
+            .collect()
+    }
+
+    /// Get the local DRUID pool transactions
+    pub fn get_local_tx_druid_pool(&self) -> &Vec<BTreeMap<String, Transaction>> {
         &self.local_tx_druid_pool
     }
 
@@ -1054,11 +1773,32 @@ impl MempoolRaft {
     }
 
     /// Append new transaction to our local pool from which to propose
-    /// consensused transactions.
+    /// consensused transactions, proposing at once when a full batch is reached.
     /// ### Arguments
     /// * 'transactions' - a mutable BTreeMap that has a String and a Transaction parameters
-    pub fn append_to_tx_pool(&mut self, mut transactions: BTreeMap<String, Transaction>) {
-        self.local_tx_pool.append(&mut transactions);
+    pub fn append_to_tx_pool(&mut self, transactions: BTreeMap<String, Transaction>) {
+        let added_at = Instant::now();
+        self.local_tx_pool.extend(
+            transactions
+                .into_iter()
+                .map(|(hash, tx)| (hash, (tx, added_at))),
+        );
+        self.propose_transactions_now_if_batch_full();
+    }
+
+    /// Drop local transactions waiting to be proposed for longer than `max_age`,
+    /// e.g. because their inputs were spent by another transaction.
+    /// ### Arguments
+    /// * `max_age` - Maximum time a transaction may wait in the local pool
+    pub fn evict_stale_local_txs(&mut self, max_age: Duration) {
+        let len_before = self.local_tx_pool.len();
+        self.local_tx_pool
+            .retain(|_, (_, added_at)| added_at.elapsed() < max_age);
+
+        let evicted = len_before - self.local_tx_pool.len();
+        if evicted > 0 {
+            warn!("Evicted {evicted} stale transactions from the local pool");
+        }
     }
 
     /// Append new transaction to our local pool from which to propose
@@ -1067,6 +1807,87 @@ impl MempoolRaft {
         self.local_tx_druid_pool.push(transactions);
     }
 
+    /// Store the local pools so they survive a restart
+    ///
+    /// ### Arguments
+    ///
+    /// * `db` - Database to store the pools in
+    pub fn save_local_pools(&self, db: &mut SimpleDb) -> Result<(), SimpleDbError> {
+        let pools = LocalPools {
+            tx_pool: self
+                .local_tx_pool
+                .iter()
+                .map(|(hash, (tx, _))| (hash.clone(), tx.clone()))
+                .collect(),
+            tx_druid_pool: self.local_tx_druid_pool.clone(),
+        };
+        debug!(
+            "save_local_pools: {} transactions, {} DRUID droplets",
+            pools.tx_pool.len(),
+            pools.tx_druid_pool.len()
+        );
+        db.put_cf(
+            DB_COL_INTERNAL,
+            LOCAL_POOLS_KEY,
+            &serialize(&pools).unwrap(),
+        )
+    }
+
+    /// Restore the local pools stored before a restart.
+    /// Transactions spending inputs no longer in the committed UTXO set are dropped,
+    /// with their whole droplet for DRUID transactions. Until the consensused snapshot
+    /// is applied the committed UTXO set is unknown, so all entries are kept.
+    ///
+    /// ### Arguments
+    ///
+    /// * `db` - Database the pools were stored in
+    pub fn load_local_pools(&mut self, db: &SimpleDb) -> Result<(), SimpleDbError> {
+        let pools: LocalPools = match db.get_cf(DB_COL_INTERNAL, LOCAL_POOLS_KEY)? {
+            Some(pools) => match try_deserialize(&pools) {
+                Ok(pools) => pools,
+                Err(e) => {
+                    error!("load_local_pools: invalid stored pools: {:?}", e);
+                    return Ok(());
+                }
+            },
+            None => return Ok(()),
+        };
+
+        let check_spent = self.consensused_snapshot_applied;
+        let utxo_set = self.get_committed_utxo_set();
+        let is_unspent = |tx: &Transaction| {
+            !check_spent
+                || get_inputs_previous_out_point(Some(tx).into_iter())
+                    .all(|op| utxo_set.contains_key(op))
+        };
+
+        let stored_len = pools.tx_pool.len() + pools.tx_druid_pool.len();
+        let tx_pool: BTreeMap<_, _> = pools
+            .tx_pool
+            .into_iter()
+            .filter(|(_, tx)| is_unspent(tx))
+            .collect();
+        let tx_druid_pool: Vec<_> = pools
+            .tx_druid_pool
+            .into_iter()
+            .filter(|droplet| droplet.values().all(&is_unspent))
+            .collect();
+
+        let stale = stored_len - tx_pool.len() - tx_druid_pool.len();
+        if stale > 0 {
+            warn!("Dropped {stale} stored local pool entries with spent inputs");
+        }
+        debug!(
+            "load_local_pools: {} transactions, {} DRUID droplets",
+            tx_pool.len(),
+            tx_druid_pool.len()
+        );
+
+        self.append_to_tx_pool(tx_pool);
+        self.local_tx_druid_pool.extend(tx_druid_pool);
+        Ok(())
+    }
+
     /// Current block to mine or being mined.
     pub fn get_mining_block(&self) -> &Option<Block> {
         self.consensused.get_mining_block()
@@ -1123,6 +1944,19 @@ impl MempoolRaft {
         self.consensused.take_mining_block()
     }
 
+    /// Discard the block being mined if it was not stored, restoring the committed UTXO set
+    /// and returning its transactions to the pools.
+    ///
+    /// ### Arguments
+    ///
+    /// * `b_num` - Number of the block to roll back
+    pub fn rollback_mining_block(
+        &mut self,
+        b_num: u64,
+    ) -> Option<(Block, BTreeMap<String, Transaction>)> {
+        self.consensused.rollback_mining_block(b_num)
+    }
+
     /// Take all the transactions hashes last commited
     pub fn take_local_tx_hash_last_commited(&mut self) -> Vec<String> {
         std::mem::take(&mut self.local_tx_hash_last_commited)
@@ -1139,10 +1973,10 @@ impl MempoolRaft {
     }
 
     /// Generate a snapshot, needs to happen at the end of the event processing.
-    pub fn event_processed_generate_snapshot(&mut self) {
+    pub async fn event_processed_generate_snapshot(&mut self) {
         self.set_ignore_dedeup_b_num_less_than_current();
 
-        let consensused_ser = serialize(&self.consensused).unwrap();
+        let consensused_ser = self.consensused.to_snapshot();
         let (snapshot_idx, term) = self.consensused.last_committed_raft_idx_and_term;
 
         debug!("generate_snapshot: (idx: {}, term: {})", snapshot_idx, term);
@@ -1150,11 +1984,40 @@ impl MempoolRaft {
         self.raft_active
             .create_snapshot(snapshot_idx, consensused_ser, backup);
 
+        if self.is_shutdown_on_commit() || self.need_bypass_snapshot() {
+            self.persist_bypass_snapshot().await;
+        }
         if self.is_shutdown_on_commit() {
             self.shutdown_no_commit_process = true;
         }
     }
 
+    /// Persist the consensused snapshot when RAFT is bypassed:
+    /// RAFT persists its own snapshots.
+    async fn persist_bypass_snapshot(&mut self) {
+        if self.raft_active.use_raft() {
+            return;
+        }
+
+        let consensused_ser = self.consensused.to_snapshot();
+        let (snapshot_idx, _) = self.consensused.last_committed_raft_idx_and_term;
+        let backup = self.need_backup();
+        self.raft_active
+            .persist_bypass_snapshot(snapshot_idx, consensused_ser, backup)
+            .await;
+    }
+
+    /// Whether the snapshot is due to be persisted without RAFT for the current block
+    fn need_bypass_snapshot(&self) -> bool {
+        match (
+            self.get_committed_current_block_num(),
+            self.snapshot_block_modulo,
+        ) {
+            (Some(b_num), Some(modulo)) => b_num % modulo == 0,
+            _ => true,
+        }
+    }
+
     /// Ignore processing raft item out of date.
     fn set_ignore_dedeup_b_num_less_than_current(&mut self) {
         self.proposed_in_flight.ignore_dedeup_b_num_less_than(
@@ -1253,21 +2116,120 @@ impl From<MempoolConsensusedPreDifficulty> for MempoolConsensused {
             initial_utxo_txs,
             utxo_set,
             current_block_stored_info,
+            current_block_ruleset_hashes: Default::default(),
+            current_block_stored_info_started_at: Default::default(),
+            block_stored_info_timeout: Default::default(),
+            block_size_in_tx: BLOCK_SIZE_IN_TX,
+            current_block_spent_utxo: Default::default(),
+            current_block_pooled_txs: Default::default(),
+            committed_tx_batches: Default::default(),
+            recent_block_fills: Default::default(),
             current_raft_coordinated_cmd_stored_info,
             last_committed_raft_idx_and_term,
             current_issuance,
+            current_block_fees: Default::default(),
+            difficulty_adjustment: Default::default(),
+            difficulty_adjustment_config: Default::default(),
             block_pipeline: post_diff_block_pipeline,
             last_mining_transaction_hashes,
             runtime_data,
             special_handling,
+            paused: false,
+            miner_whitelist,
+            timestamp,
+            init_issuances,
+        }
+    }
+}
+
+impl From<MempoolConsensusedPreFees> for MempoolConsensused {
+    fn from(consensused: MempoolConsensusedPreFees) -> Self {
+        let MempoolConsensusedPreFees {
+            unanimous_majority,
+            sufficient_majority,
+            partition_full_size,
+            tx_pool,
+            tx_druid_pool,
+            tx_current_block_previous_hash,
+            initial_utxo_txs,
+            utxo_set,
+            current_block_stored_info,
+            current_raft_coordinated_cmd_stored_info,
+            last_committed_raft_idx_and_term,
+            current_issuance,
+            block_pipeline,
+            last_mining_transaction_hashes,
+            special_handling,
+            miner_whitelist,
+            timestamp,
+            runtime_data,
+            init_issuances,
+        } = consensused;
+
+        Self {
+            unanimous_majority,
+            sufficient_majority,
+            partition_full_size,
+            tx_pool,
+            tx_druid_pool,
+            tx_current_block_previous_hash,
+            initial_utxo_txs,
+            utxo_set,
+            current_block_stored_info,
+            current_block_ruleset_hashes: Default::default(),
+            current_block_stored_info_started_at: Default::default(),
+            block_stored_info_timeout: Default::default(),
+            block_size_in_tx: BLOCK_SIZE_IN_TX,
+            current_block_spent_utxo: Default::default(),
+            current_block_pooled_txs: Default::default(),
+            committed_tx_batches: Default::default(),
+            recent_block_fills: Default::default(),
+            current_raft_coordinated_cmd_stored_info,
+            last_committed_raft_idx_and_term,
+            current_issuance,
+            current_block_fees: Default::default(),
+            difficulty_adjustment: Default::default(),
+            difficulty_adjustment_config: Default::default(),
+            block_pipeline,
+            last_mining_transaction_hashes,
+            special_handling,
+            paused: false,
             miner_whitelist,
             timestamp,
+            runtime_data,
             init_issuances,
         }
     }
 }
 
 impl MempoolConsensused {
+    /// Serialize the consensused state to use as RAFT snapshot data
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        serialize(self).unwrap()
+    }
+
+    /// Deserialize the consensused state from RAFT snapshot data,
+    /// also accepting snapshots taken before the block fees
+    /// or before the difficulty function.
+    /// Majorities and local settings are not restored: set them with the `with_` methods.
+    ///
+    /// ### Arguments
+    ///
+    /// * `bytes` - Snapshot data
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, BincodeError> {
+        // Layouts share a prefix, so a snapshot must be read to its last byte
+        let e = match try_deserialize_exact::<Self>(bytes) {
+            Ok(consensused) => return Ok(consensused),
+            Err(e) => e,
+        };
+        warn!("Deserialization of consensus snapshot failed: {:?}", e);
+        warn!("Attempting to deserialize as a previous version");
+        match try_deserialize_exact::<MempoolConsensusedPreFees>(bytes) {
+            Ok(consensused) => Ok(consensused.into()),
+            Err(_) => try_deserialize::<MempoolConsensusedPreDifficulty>(bytes).map(Into::into),
+        }
+    }
+
     /// Get runtime data
     pub fn get_runtime_data(&self) -> MempoolConsensusedRuntimeData {
         self.runtime_data.clone()
@@ -1313,6 +2275,27 @@ impl MempoolConsensused {
         self
     }
 
+    /// Specify the timeout after which block stored info is applied below majority
+    pub fn with_block_stored_info_timeout(mut self, timeout: Duration) -> Self {
+        self.block_stored_info_timeout = Some(timeout);
+        self
+    }
+
+    /// Specify the maximum number of transactions in a block
+    pub fn with_block_size_in_tx(mut self, block_size_in_tx: usize) -> Self {
+        self.block_size_in_tx = block_size_in_tx;
+        self
+    }
+
+    /// Specify the settings of the difficulty adjustment, if enabled
+    pub fn with_difficulty_adjustment(
+        mut self,
+        config: Option<DifficultyAdjustmentConfig>,
+    ) -> Self {
+        self.difficulty_adjustment_config = config;
+        self
+    }
+
     /// Specify the initial issuances with lock ups
     pub fn with_initial_issuances(mut self, initial_issuances: Vec<InitialIssuance>) -> Self {
         self.init_issuances = initial_issuances;
@@ -1389,13 +2372,25 @@ impl MempoolConsensused {
             initial_utxo_txs: Default::default(),
             utxo_set: TrackedUtxoSet::new(utxo_set),
             current_block_stored_info: Default::default(),
+            current_block_ruleset_hashes: Default::default(),
+            current_block_stored_info_started_at: Default::default(),
+            block_stored_info_timeout: Default::default(),
+            block_size_in_tx: BLOCK_SIZE_IN_TX,
+            current_block_spent_utxo: Default::default(),
+            current_block_pooled_txs: Default::default(),
+            committed_tx_batches: Default::default(),
+            recent_block_fills: Default::default(),
             current_raft_coordinated_cmd_stored_info: Default::default(),
             last_committed_raft_idx_and_term,
             current_issuance,
+            current_block_fees: Default::default(),
+            difficulty_adjustment: Default::default(),
+            difficulty_adjustment_config: Default::default(),
             block_pipeline: MiningPipelineInfo::from_import(block_pipeline),
             last_mining_transaction_hashes: Default::default(),
             runtime_data: Default::default(),
             special_handling,
+            paused: false,
             miner_whitelist,
             timestamp,
             init_issuances,
@@ -1440,7 +2435,7 @@ impl MempoolConsensused {
         // Transaction only depend on mined block: append at the end.
         // The block is about to be mined, all transaction accepted can be used
         // to accept next block transactions.
-        // TODO: Roll back append and removal if block rejected by miners.
+        // Use rollback_mining_block if block is not stored.
 
         self.utxo_set.extend_tracked_utxo_set(&block_tx);
         self.block_pipeline
@@ -1492,11 +2487,57 @@ impl MempoolConsensused {
         &self.utxo_set
     }
 
+    /// Record a committed transaction batch.
+    /// Return false if the same batch was already committed under another key.
+    ///
+    /// ### Arguments
+    ///
+    /// * `txs` - Committed transaction batch
+    fn insert_committed_tx_batch(&mut self, txs: &BTreeMap<String, Transaction>) -> bool {
+        let batch_hash = canonical_hash(txs);
+        let b_num = self.block_pipeline.current_block_num().unwrap_or_default();
+        self.committed_tx_batches
+            .insert(batch_hash, b_num)
+            .is_none()
+    }
+
     /// Take mining block when mining is completed, use to populate mined block.
     pub fn take_mining_block(&mut self) -> Option<(Block, BTreeMap<String, Transaction>)> {
+        self.current_block_spent_utxo.clear();
+        self.current_block_pooled_txs = Default::default();
         self.block_pipeline.take_mining_block()
     }
 
+    /// Discard the block being mined, restoring the UTXO set to its state
+    /// before the block was generated and returning its transactions to the pools.
+    ///
+    /// ### Arguments
+    ///
+    /// * `b_num` - Number of the block to roll back
+    pub fn rollback_mining_block(
+        &mut self,
+        b_num: u64,
+    ) -> Option<(Block, BTreeMap<String, Transaction>)> {
+        let mining_b_num = self
+            .block_pipeline
+            .get_mining_block()
+            .as_ref()?
+            .header
+            .b_num;
+        if mining_b_num != b_num {
+            return None;
+        }
+
+        let spent = std::mem::take(&mut self.current_block_spent_utxo);
+        self.current_block_fees = Default::default();
+        let (mut tx_pool, tx_druid_pool) = std::mem::take(&mut self.current_block_pooled_txs);
+        let (block, block_tx) = self.block_pipeline.take_mining_block()?;
+        self.utxo_set.roll_back_tracked_utxo_set(&block_tx, spent);
+        self.tx_pool.append(&mut tx_pool);
+        self.tx_druid_pool.extend(tx_druid_pool);
+        Some((block, block_tx))
+    }
+
     /// Processes the very first block with utxo_set
     pub async fn generate_first_block(&mut self) {
         let next_block_tx = self.initial_utxo_txs.take().unwrap();
@@ -1516,6 +2557,10 @@ impl MempoolConsensused {
     pub async fn generate_block(&mut self) {
         let mut next_block = Block::new();
         let mut next_block_tx = BTreeMap::new();
+        self.current_block_spent_utxo.clear();
+        self.current_block_pooled_txs = Default::default();
+        self.current_block_fees = Default::default();
+        self.prune_committed_tx_batches();
 
         // TODO: add update_mempool_storage_rewards(&mut next_block, &mut next_block_tx)
         self.update_committed_dde_tx(&mut next_block, &mut next_block_tx);
@@ -1530,6 +2575,14 @@ impl MempoolConsensused {
         self.set_committed_mining_block(next_block, next_block_tx)
     }
 
+    /// Forget transaction batches committed before the previous block:
+    /// re-proposals are expected to land well within a block.
+    fn prune_committed_tx_batches(&mut self) {
+        let b_num = self.block_pipeline.current_block_num().unwrap_or_default();
+        self.committed_tx_batches
+            .retain(|_, committed_b_num| *committed_b_num + 1 >= b_num);
+    }
+
     /// Adds transactions for initial issuances if their lock up period has expired
     ///
     /// ### Arguments
@@ -1584,11 +2637,12 @@ impl MempoolConsensused {
             }
 
             // Process valid set of transactions from a single DRUID droplet.
+            self.current_block_pooled_txs.1.push(txs.clone());
             self.update_current_block_tx_with_given_valid_txs(txs, block, block_tx);
         }
     }
 
-    /// Apply all valid consensused transactions to the block until BLOCK_SIZE_IN_TX
+    /// Apply all valid consensused transactions to the block until block_size_in_tx
     /// ### Arguments
     ///
     /// * `block`   - current Block to be set to be updated
@@ -1603,8 +2657,13 @@ impl MempoolConsensused {
             self.tx_pool.remove(&invalid);
         }
 
-        // Select subset of transaction to fill the block.
-        let txs = take_first_n(BLOCK_SIZE_IN_TX, &mut self.tx_pool);
+        // Select subset of transaction to fill the block, highest fees per byte first.
+        let mut tx_pool = std::mem::take(&mut self.tx_pool);
+        let fee = |tx: &Transaction| fee_for(tx) + self.implicit_fee_for(tx).unwrap_or_default();
+        let txs = take_highest_fee_n(self.block_size_in_tx, &mut tx_pool, fee);
+        self.tx_pool = tx_pool;
+        self.record_block_fill(txs.len());
+        self.current_block_pooled_txs.0 = txs.clone();
 
         // Process valid set of transactions.
         self.update_current_block_tx_with_given_valid_txs(txs, block, block_tx);
@@ -1632,6 +2691,10 @@ impl MempoolConsensused {
             );
 
             block.header.difficulty = target.into_array().to_vec();
+        } else if let Some(config) = &self.difficulty_adjustment_config {
+            self.difficulty_adjustment
+                .record_block(self.timestamp, config);
+            block.header.difficulty = self.difficulty_adjustment.header_difficulty();
         }
 
         block.header.previous_hash = Some(previous_hash);
@@ -1653,8 +2716,17 @@ impl MempoolConsensused {
         block: &mut Block,
         block_tx: &mut BTreeMap<String, Transaction>,
     ) {
+        for tx in txs.values() {
+            let fee = self.implicit_fee_for(tx).unwrap_or_default();
+            self.current_block_fees.0 += fee;
+        }
+
         for outpoint in get_inputs_previous_out_point(txs.values()) {
             // All previous hash in valid txs set are present and must be removed.
+            if let Some(tx_out) = self.utxo_set.get(outpoint) {
+                self.current_block_spent_utxo
+                    .insert(outpoint.clone(), tx_out.clone());
+            }
             self.utxo_set.remove_tracked_utxo_entry(outpoint);
         }
         block.transactions.extend(txs.keys().cloned());
@@ -1673,23 +2745,88 @@ impl MempoolConsensused {
         for (hash_tx, value) in new_txs.iter() {
             let mut removed_roll_back = Vec::new();
 
+            let mut is_valid = self.implicit_fee_for(value).is_some();
             for hash_in in get_inputs_previous_out_point(Some(value).into_iter()) {
-                if self.utxo_set.contains_key(hash_in) && removed_all.insert(hash_in) {
+                if is_valid && self.utxo_set.contains_key(hash_in) && removed_all.insert(hash_in) {
                     removed_roll_back.push(hash_in);
                 } else {
-                    // Entry is invalid: roll back, mark entry and check next one.
-                    for h in removed_roll_back {
-                        removed_all.remove(h);
-                    }
-                    invalid.push(hash_tx.clone());
+                    is_valid = false;
                     break;
                 }
             }
+
+            if !is_valid {
+                // Entry is invalid: roll back, mark entry and check next one.
+                for h in removed_roll_back {
+                    removed_all.remove(h);
+                }
+                invalid.push(hash_tx.clone());
+            }
         }
 
         invalid
     }
 
+    /// Record the number of transactions selected for a new block,
+    /// keeping only the most recent blocks
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_count` - Number of pooled transactions in the block
+    fn record_block_fill(&mut self, tx_count: usize) {
+        if self.recent_block_fills.len() >= FEE_ESTIMATE_BLOCK_WINDOW {
+            self.recent_block_fills.pop_front();
+        }
+        self.recent_block_fills.push_back(tx_count);
+    }
+
+    /// Fee suggested for a transaction to be included in the next blocks,
+    /// from the fees of the pooled transactions and the fill rate of the recent blocks
+    pub fn estimate_fee(&self) -> FeeEstimate {
+        let mut pooled_fees: Vec<u64> = self
+            .tx_pool
+            .values()
+            .map(|tx| fee_for(tx) + self.implicit_fee_for(tx).unwrap_or_default())
+            .collect();
+        pooled_fees.sort_unstable();
+
+        let fee = estimate_fee_from(
+            &pooled_fees,
+            &self.recent_block_fills,
+            self.block_size_in_tx,
+        );
+        FeeEstimate {
+            fee: TokenAmount(fee),
+            pooled_txs: pooled_fees.len(),
+        }
+    }
+
+    /// Token value of the inputs of a transaction not spent by its outputs
+    /// or explicit fees, using the committed UTXO set.
+    /// Returns None if the transaction spends more than its inputs.
+    /// ### Arguments
+    ///
+    /// * `tx` - Transaction to get the fee of
+    pub fn implicit_fee_for(&self, tx: &Transaction) -> Option<u64> {
+        let mut inputs = get_inputs_previous_out_point(Some(tx).into_iter()).peekable();
+        if inputs.peek().is_none() {
+            // Nothing spent: item creation
+            return Some(0);
+        }
+
+        let input_value = inputs
+            .filter_map(|out_p| self.utxo_set.get(out_p))
+            .map(|tx_out| tx_out.value.token_amount().0)
+            .fold(0, u64::saturating_add);
+        let output_value = tx
+            .outputs
+            .iter()
+            .map(|tx_out| tx_out.value.token_amount().0)
+            .fold(fee_for(tx), u64::saturating_add);
+
+        input_value.checked_sub(output_value)
+    }
+
     /// Check if computing the first block.
     pub fn is_first_block(&self) -> bool {
         self.block_pipeline.current_block_num().is_none()
@@ -1707,6 +2844,11 @@ impl MempoolConsensused {
         }
     }
 
+    /// Check if block generation is paused for maintenance
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Check if we have inconsistent votes for a coordinated RAFT command
     pub fn has_different_coordinated_raft_cmd_stored_info(&self) -> bool {
         self.current_raft_coordinated_cmd_stored_info.len() > 1
@@ -1717,6 +2859,13 @@ impl MempoolConsensused {
         self.current_block_stored_info.len() > 1
     }
 
+    /// Check if votes for the current block used different assembly rulesets.
+    pub fn has_different_block_ruleset(&self) -> bool {
+        let mut hashes = self.current_block_ruleset_hashes.values();
+        let first = hashes.next();
+        hashes.any(|h| Some(h) != first)
+    }
+
     /// Check if we have enough votes to apply a coordinated RAFT command
     pub fn has_coordinated_raft_cmd_info_ready(&self) -> bool {
         self.max_agreeing_coordinated_raft_cmd_stored_info() >= self.unanimous_majority
@@ -1733,6 +2882,23 @@ impl MempoolConsensused {
         self.max_agreeing_block_stored_info() >= threshold
     }
 
+    /// Check if votes for a non first block stored info have accumulated for longer
+    /// than the timeout without reaching a majority.
+    pub fn block_stored_info_timed_out(&self) -> bool {
+        match (
+            self.current_block_stored_info_started_at,
+            self.block_stored_info_timeout,
+        ) {
+            (Some(started_at), Some(timeout)) => {
+                !self.is_first_block()
+                    && !self.paused
+                    && !self.has_block_stored_info_ready()
+                    && started_at.elapsed() >= timeout
+            }
+            _ => false,
+        }
+    }
+
     // Current maximum vote count for coordinated RAFT command
     fn max_agreeing_coordinated_raft_cmd_stored_info(&self) -> usize {
         self.current_raft_coordinated_cmd_stored_info
@@ -1785,6 +2951,9 @@ impl MempoolConsensused {
                     self.runtime_data.mining_api_keys.remove(&address);
                 }
             }
+            MempoolRuntimeItem::AssemblyRuleset(_) => {
+                // Diagnostics only, compared by MempoolRaft on commit
+            }
         }
     }
 
@@ -1809,10 +2978,21 @@ impl MempoolConsensused {
     ///
     /// ### Arguments
     ///
-    /// * `key`   - Key object of the block to append
-    /// * `block` - BlockStoredInfo to be appended.
-    pub fn append_block_stored_info(&mut self, key: RaftContextKey, block: BlockStoredInfo) {
-        self.append_current_block_stored_info(key, AccumulatingBlockStoredInfo::Block(block))
+    /// * `key`          - Key object of the block to append
+    /// * `block`        - BlockStoredInfo to be appended.
+    /// * `ruleset_hash` - Assembly ruleset hash of the vote
+    pub fn append_block_stored_info(
+        &mut self,
+        key: RaftContextKey,
+        block: BlockStoredInfo,
+        ruleset_hash: String,
+    ) {
+        self.current_block_ruleset_hashes
+            .insert(key.proposer_id, ruleset_hash.clone());
+
+        let block = AccumulatingBlockStoredInfo::Block(block);
+        let block_hash = canonical_hash(&(&ruleset_hash, &block));
+        self.append_current_block_stored_info_with_hash(key, block, block_hash)
     }
 
     /// Append a vote for a coordinated RAFT command
@@ -1843,9 +3023,20 @@ impl MempoolConsensused {
         key: RaftContextKey,
         block: AccumulatingBlockStoredInfo,
     ) {
-        let block_ser = serialize(&block).unwrap();
-        let block_hash = sha3_256::digest(&block_ser).to_vec();
+        let block_hash = canonical_hash(&block);
+        self.append_current_block_stored_info_with_hash(key, block, block_hash)
+    }
 
+    /// Append the given vote under the given accumulation hash.
+    fn append_current_block_stored_info_with_hash(
+        &mut self,
+        key: RaftContextKey,
+        block: AccumulatingBlockStoredInfo,
+        block_hash: Vec<u8>,
+    ) {
+        if self.current_block_stored_info.is_empty() {
+            self.current_block_stored_info_started_at = Some(Instant::now());
+        }
         self.current_block_stored_info
             .entry(block_hash)
             .or_insert((block, BTreeSet::new()))
@@ -1870,7 +3061,11 @@ impl MempoolConsensused {
                 }
 
                 self.special_handling = None;
-                self.current_issuance += get_total_coinbase_tokens(&info.mining_transactions);
+                self.rollback_unstored_mining_block();
+                // Fees are paid from existing tokens: they are not new issuance
+                let block_fees = std::mem::take(&mut self.current_block_fees);
+                let coinbase_tokens = get_total_coinbase_tokens(&info.mining_transactions);
+                self.current_issuance.0 += coinbase_tokens.0.saturating_sub(block_fees.0);
                 self.tx_current_block_previous_hash = Some(info.block_hash);
                 self.utxo_set
                     .extend_tracked_utxo_set(&info.mining_transactions);
@@ -1886,9 +3081,45 @@ impl MempoolConsensused {
             .apply_ready_block_stored_info(block_num, reward);
     }
 
+    /// Roll back the block being mined if it was never mined and so never stored.
+    /// The first block carries the seed UTXO set and is kept.
+    fn rollback_unstored_mining_block(&mut self) {
+        let unstored_b_num = match self.block_pipeline.get_mining_block() {
+            Some(block) if block.header.previous_hash.is_some() => block.header.b_num,
+            _ => return,
+        };
+
+        if let Some((_, block_tx)) = self.rollback_mining_block(unstored_b_num) {
+            warn!(
+                "Rolled back block {} with {} transactions: not stored",
+                unstored_b_num,
+                block_tx.len()
+            );
+        }
+    }
+
+    /// Drop the accumulated block stored info voted for another block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `b_num` - Number of the block the votes must be for
+    pub fn retain_block_stored_info_for(&mut self, b_num: u64) {
+        self.current_block_stored_info
+            .retain(|_, (info, _)| match info {
+                AccumulatingBlockStoredInfo::Block(info) => info.block_num == b_num,
+                AccumulatingBlockStoredInfo::FirstBlock(_) => true,
+            });
+        if self.current_block_stored_info.is_empty() {
+            self.current_block_ruleset_hashes.clear();
+            self.current_block_stored_info_started_at = None;
+        }
+    }
+
     /// Take the block info with most vote and reset accumulator.
     fn take_ready_block_stored_info(&mut self) -> AccumulatingBlockStoredInfo {
         let infos = std::mem::take(&mut self.current_block_stored_info);
+        self.current_block_ruleset_hashes.clear();
+        self.current_block_stored_info_started_at = None;
         infos
             .into_values()
             .max_by_key(|(_, vote_ids)| vote_ids.len())
@@ -1953,15 +3184,95 @@ fn take_first_n<K: Clone + Ord, V>(n: usize, from: &mut BTreeMap<K, V>) -> BTree
     result
 }
 
+/// Take the `n` transactions paying the highest fees per byte from the given map.
+/// Ties are taken in hash order so all peers select the same transactions.
+/// ### Arguments
+///
+/// * `n`   - number of transactions
+/// * `from` - BTreeMap for transactions to be taken from
+/// * `fee` - total fee paid by a transaction
+fn take_highest_fee_n(
+    n: usize,
+    from: &mut BTreeMap<String, Transaction>,
+    fee: impl Fn(&Transaction) -> u64,
+) -> BTreeMap<String, Transaction> {
+    let mut by_fee: Vec<_> = from
+        .iter()
+        .map(|(hash, tx)| {
+            let size = serialize(tx).map_or(1, |tx| tx.len().max(1));
+            (fee(tx) as u128, size as u128, hash.clone())
+        })
+        .collect();
+    by_fee.sort_unstable_by(|(fee_a, size_a, hash_a), (fee_b, size_b, hash_b)| {
+        let fee_per_byte = (fee_b * size_a).cmp(&(fee_a * size_b));
+        fee_per_byte.then_with(|| hash_a.cmp(hash_b))
+    });
+
+    by_fee
+        .into_iter()
+        .take(n)
+        .filter_map(|(_, _, hash)| from.remove_entry(&hash))
+        .collect()
+}
+
+/// Pick a fee among the pooled ones: higher in the distribution the fuller the recent blocks,
+/// and no lower than the fees of the transactions the next block can hold.
+/// Returns DEFAULT_FEE_ESTIMATE if nothing is pooled.
+/// ### Arguments
+///
+/// * `sorted_fees`        - fees of the pooled transactions, in ascending order
+/// * `recent_block_fills` - number of transactions in each of the recent blocks
+/// * `block_size_in_tx`   - maximum number of transactions in a block
+fn estimate_fee_from(
+    sorted_fees: &[u64],
+    recent_block_fills: &VecDeque<usize>,
+    block_size_in_tx: usize,
+) -> u64 {
+    let last = match sorted_fees.len().checked_sub(1) {
+        Some(last) => last,
+        None => return DEFAULT_FEE_ESTIMATE,
+    };
+
+    // Median of the pool until a block fill rate is known
+    let capacity = recent_block_fills.len() * block_size_in_tx.max(1);
+    let filled: usize = recent_block_fills
+        .iter()
+        .map(|fill| (*fill).min(block_size_in_tx.max(1)))
+        .sum();
+    let by_fill_rate = match capacity {
+        0 => last / 2,
+        capacity => last * filled / capacity,
+    };
+
+    // Transactions paying less than the ones filling the next block wait for later ones
+    let by_block_size = sorted_fees.len().saturating_sub(block_size_in_tx);
+    sorted_fees[by_fill_rate.max(by_block_size).min(last)]
+}
+
+/// Total token fees paid by the given transaction.
+/// ### Arguments
+///
+/// * `tx`   - transaction to get the fees of
+pub fn fee_for(tx: &Transaction) -> u64 {
+    tx.fees
+        .iter()
+        .map(|fee| fee.value.token_amount().0)
+        .fold(0, u64::saturating_add)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::configurations::{DbMode, NodeSpec, TxOutSpec};
-    use crate::utils::{create_socket_addr, create_valid_transaction, get_test_common_unicorn};
+    use crate::utils::{
+        create_socket_addr, create_valid_transaction, create_valid_transaction_with_ins_outs,
+        get_test_common_unicorn,
+    };
     use rug::Integer;
     use std::collections::BTreeSet;
     use tw_chain::crypto::sign_ed25519 as sign;
     use tw_chain::primitives::asset::TokenAmount;
+    use tw_chain::primitives::transaction::TxOut;
 
     #[tokio::test]
     async fn generate_first_block_no_raft() {
@@ -2158,101 +3469,1580 @@ mod test {
     }
 
     #[tokio::test]
-    async fn in_flight_transactions_no_raft() {
+    async fn restore_persisted_snapshot_no_raft() {
         //
         // Arrange
         //
-        let mut node = new_test_node(&[]).await;
-        node.proposed_and_consensused_tx_pool_len_max = 3;
-        node.proposed_tx_pool_len_max = 2;
-        node.propose_initial_item().await;
-        let commit = node.next_commit().await;
-        node.received_commit(commit.unwrap()).await.unwrap();
-
-        //
-        // Act
-        //
-        let mut actual_combined_local_flight_consensused = Vec::new();
-        let mut collect_info = |node: &MempoolRaft| {
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let first_block = node.received_commit(commit).await;
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000"],
+            &["000100"],
+            &mut BTreeMap::new(),
+        ));
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_block_with_last_info(previous_block).await;
+        let mut commits = Vec::new();
+        for _ in 0..2 {
+            let commit = node.next_commit().await.unwrap();
+            commits.push(node.received_commit(commit).await);
+        }
+
+        let expected_utxo_set = node.get_committed_utxo_set().clone();
+        let expected_b_num = node.get_committed_current_block_num();
+        let expected_raft_idx = node.consensused.last_committed_raft_idx_and_term;
+
+        //
+        // Act
+        //
+        node.close_raft_loop().await;
+        let raft_db = node.take_closed_persistent_store().await;
+        let restored = new_test_node_with_raft_db(&seed_utxo, raft_db.in_memory()).await;
+
+        //
+        // Assert
+        //
+        assert_eq!(first_block, Some(CommittedItem::FirstBlock));
+        assert_eq!(
+            commits,
+            vec![
+                Some(CommittedItem::Transactions),
+                Some(CommittedItem::Block)
+            ]
+        );
+        assert_eq!(expected_b_num, Some(1));
+        assert_eq!(
+            restored.get_committed_utxo_set().keys().collect::<Vec<_>>(),
+            expected_utxo_set.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(restored.get_committed_current_block_num(), expected_b_num);
+        assert_eq!(
+            restored.consensused.last_committed_raft_idx_and_term,
+            expected_raft_idx
+        );
+        assert!(matches!(
+            restored.local_initial_proposal,
+            Some(InitialProposal::PendingAll)
+        ));
+    }
+
+    #[tokio::test]
+    async fn consensused_snapshot_round_trip_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await;
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000"],
+            &["000100"],
+            &mut BTreeMap::new(),
+        ));
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_block_with_last_info(previous_block).await;
+        for _ in 0..2 {
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await;
+        }
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000001"],
+            &["000101"],
+            &mut BTreeMap::new(),
+        ));
+        node.propose_local_transactions_at_timeout().await;
+        let commit = node.next_commit().await.unwrap();
+        let pool_commit = node.received_commit(commit).await;
+
+        //
+        // Act
+        //
+        let snapshot = node.consensused.to_snapshot();
+        let restored = MempoolConsensused::from_snapshot(&snapshot).unwrap();
+        let restored_same_group = restored.clone().with_peers_len(1);
+        let restored_larger_group = restored.with_peers_len(3);
+
+        //
+        // Assert
+        //
+        let consensused = &node.consensused;
+        assert_eq!(pool_commit, Some(CommittedItem::Transactions));
+        assert_eq!(consensused.tx_pool.len(), 1);
+        assert_eq!(restored_same_group.to_snapshot(), snapshot);
+        assert_eq!(
+            restored_same_group.tx_pool.keys().collect::<Vec<_>>(),
+            consensused.tx_pool.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            restored_same_group.utxo_set.keys().collect::<Vec<_>>(),
+            consensused.utxo_set.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            restored_same_group.get_mining_block(),
+            consensused.get_mining_block()
+        );
+        assert_eq!(
+            restored_same_group.block_pipeline.current_block_num(),
+            Some(1)
+        );
+        assert_eq!(
+            restored_same_group.current_block_stored_info,
+            consensused.current_block_stored_info
+        );
+        assert_eq!(
+            (
+                restored_larger_group.unanimous_majority,
+                restored_larger_group.sufficient_majority
+            ),
+            (3, 2)
+        );
+        assert_eq!(
+            (
+                consensused.unanimous_majority,
+                consensused.sufficient_majority
+            ),
+            (1, 1)
+        );
+    }
+
+    /// Raft items in the layout logged before block votes carried a block size
+    /// or an assembly ruleset hash
+    #[derive(Serialize)]
+    enum PreviousLayoutRaftItem {
+        FirstBlock(BTreeMap<String, Transaction>),
+        Block(BlockStoredInfo),
+    }
+
+    #[tokio::test]
+    async fn raft_items_from_previous_layout_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&["000000"]).await;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let (data, context) = match commit.data {
+            RaftCommitData::Proposed(data, context) => (data, context),
+            data => panic!("Unexpected commit {:?}", data),
+        };
+        let utxo_set = match bincode::deserialize(&data).unwrap() {
+            MempoolRaftItem::FirstBlockSized(utxo_set, _) => utxo_set,
+            item => panic!("Unexpected item {:?}", item),
+        };
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+        let first_block_data = serialize(&PreviousLayoutRaftItem::FirstBlock(utxo_set)).unwrap();
+        let block_data = serialize(&PreviousLayoutRaftItem::Block(previous_block.clone())).unwrap();
+
+        //
+        // Act
+        //
+        let block_item: MempoolRaftItem = bincode::deserialize(&block_data).unwrap();
+        let first_block_commit = RaftCommit {
+            term: commit.term,
+            index: commit.index,
+            data: RaftCommitData::Proposed(first_block_data, context),
+        };
+        let committed = node.received_commit(first_block_commit).await;
+
+        //
+        // Assert
+        //
+        assert_eq!(block_item, MempoolRaftItem::Block(previous_block));
+        assert_eq!(committed, Some(CommittedItem::FirstBlock));
+    }
+
+    #[tokio::test]
+    async fn snapshot_from_previous_layout_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&["000000", "000001"]).await;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await;
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000"],
+            &["000100"],
+            &mut BTreeMap::new(),
+        ));
+        node.propose_local_transactions_at_timeout().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await;
+
+        // Fields in the order snapshots were written before the block fees
+        let c = &node.consensused;
+        let previous_snapshot = serialize(&(
+            (
+                c.unanimous_majority,
+                c.sufficient_majority,
+                c.partition_full_size,
+                &c.tx_pool,
+                &c.tx_druid_pool,
+                &c.tx_current_block_previous_hash,
+                &c.initial_utxo_txs,
+                &c.utxo_set,
+                &c.current_block_stored_info,
+            ),
+            (
+                &c.current_raft_coordinated_cmd_stored_info,
+                c.last_committed_raft_idx_and_term,
+                &c.current_issuance,
+                &c.block_pipeline,
+                &c.last_mining_transaction_hashes,
+                &c.special_handling,
+                &c.miner_whitelist,
+                c.timestamp,
+                &c.init_issuances,
+            ),
+        ))
+        .unwrap();
+
+        //
+        // Act
+        //
+        let as_current = try_deserialize_exact::<MempoolConsensused>(&previous_snapshot);
+        let restored = MempoolConsensused::from_snapshot(&previous_snapshot)
+            .unwrap()
+            .with_peers_len(1);
+
+        //
+        // Assert
+        //
+        assert!(as_current.is_err());
+        assert_eq!(c.tx_pool.len(), 1);
+        assert_eq!(restored.to_snapshot(), c.to_snapshot());
+        assert_eq!(restored.get_mining_block(), c.get_mining_block());
+    }
+
+    #[tokio::test]
+    async fn rollback_mining_block_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut expected_block_addr_to_hashes = BTreeMap::new();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let _first_block = node.received_commit(commit).await.unwrap();
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        let pool_txs = valid_transaction(
+            &["000000", "000001"],
+            &["000100", "000101"],
+            &mut expected_block_addr_to_hashes,
+        );
+        let druid_txs =
+            valid_transaction(&["000002"], &["000202"], &mut expected_block_addr_to_hashes);
+        node.append_to_tx_pool(pool_txs.clone());
+        node.append_to_tx_druid_pool(druid_txs.clone());
+        let utxo_before_block = node.get_committed_utxo_set().clone();
+        let pk_cache_before_block = node.get_committed_utxo_tracked_set().get_pk_cache();
+
+        //
+        // Act
+        //
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_local_druid_transactions().await;
+
+        node.propose_block_with_last_info(previous_block).await;
+        for _ in 0..3 {
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+        }
+        let utxo_with_block = node.get_committed_utxo_set().clone();
+        let b_num = node.get_mining_block().as_ref().unwrap().header.b_num;
+        let rolled_back_wrong_num = node.rollback_mining_block(b_num + 1);
+        let rolled_back = node.rollback_mining_block(b_num);
+
+        //
+        // Assert
+        //
+        let expected_block_t_hashes: BTreeSet<String> =
+            expected_block_addr_to_hashes.values().cloned().collect();
+        let rolled_back_t_hashes: Option<BTreeSet<String>> = rolled_back
+            .as_ref()
+            .map(|(_, block_tx)| block_tx.keys().cloned().collect());
+        let tx_pool_t_hashes: Vec<&String> = node.consensused.tx_pool.keys().collect();
+        let tx_druid_pool_t_hashes: Vec<Vec<&String>> = node
+            .consensused
+            .tx_druid_pool
+            .iter()
+            .map(|txs| txs.keys().collect())
+            .collect();
+
+        assert!(rolled_back_wrong_num.is_none());
+        assert_ne!(utxo_with_block, utxo_before_block);
+        assert_eq!(rolled_back_t_hashes, Some(expected_block_t_hashes));
+        assert_eq!(node.get_committed_utxo_set(), &utxo_before_block);
+        assert_eq!(
+            node.get_committed_utxo_tracked_set().get_pk_cache(),
+            pk_cache_before_block
+        );
+        assert_eq!(tx_pool_t_hashes, pool_txs.keys().collect::<Vec<_>>());
+        assert_eq!(
+            tx_druid_pool_t_hashes,
+            vec![druid_txs.keys().collect::<Vec<_>>()]
+        );
+        assert!(node.get_mining_block().is_none());
+        assert!(node.rollback_mining_block(b_num).is_none());
+    }
+
+    #[tokio::test]
+    async fn rollback_mining_block_after_snapshot_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut restored = new_test_node(&seed_utxo).await;
+        let mut expected_block_addr_to_hashes = BTreeMap::new();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let _first_block = node.received_commit(commit).await.unwrap();
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        let pool_txs = valid_transaction(
+            &["000000", "000001"],
+            &["000100", "000101"],
+            &mut expected_block_addr_to_hashes,
+        );
+        let druid_txs =
+            valid_transaction(&["000002"], &["000202"], &mut expected_block_addr_to_hashes);
+        node.append_to_tx_pool(pool_txs.clone());
+        node.append_to_tx_druid_pool(druid_txs.clone());
+        let utxo_before_block = node.get_committed_utxo_set().clone();
+
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_local_druid_transactions().await;
+        node.propose_block_with_last_info(previous_block).await;
+        for _ in 0..3 {
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+        }
+        let b_num = node.get_mining_block().as_ref().unwrap().header.b_num;
+
+        //
+        // Act
+        //
+        restored.apply_snapshot(node.consensused.to_snapshot());
+        let rolled_back = node.rollback_mining_block(b_num);
+        let restored_rolled_back = restored.rollback_mining_block(b_num);
+
+        //
+        // Assert
+        //
+        let tx_pool_t_hashes: Vec<&String> = restored.consensused.tx_pool.keys().collect();
+        let tx_druid_pool_t_hashes: Vec<Vec<&String>> = restored
+            .consensused
+            .tx_druid_pool
+            .iter()
+            .map(|txs| txs.keys().collect())
+            .collect();
+
+        assert_eq!(
+            restored_rolled_back.map(|(_, block_tx)| block_tx),
+            rolled_back.map(|(_, block_tx)| block_tx)
+        );
+        assert_eq!(restored.get_committed_utxo_set(), &utxo_before_block);
+        assert_eq!(
+            restored.get_committed_utxo_tracked_set().get_pk_cache(),
+            node.get_committed_utxo_tracked_set().get_pk_cache()
+        );
+        assert_eq!(tx_pool_t_hashes, pool_txs.keys().collect::<Vec<_>>());
+        assert_eq!(
+            tx_druid_pool_t_hashes,
+            vec![druid_txs.keys().collect::<Vec<_>>()]
+        );
+    }
+
+    #[tokio::test]
+    async fn rollback_unstored_mining_block_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut expected_block_addr_to_hashes = BTreeMap::new();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let _first_block = node.received_commit(commit).await.unwrap();
+        let first_stored_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+        let competing_stored_block = BlockStoredInfo {
+            block_hash: "4567".to_string(),
+            block_num: 1,
+            ..first_stored_block.clone()
+        };
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000", "000001"],
+            &["000100", "000101"],
+            &mut expected_block_addr_to_hashes,
+        ));
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_block_with_last_info(first_stored_block).await;
+        for _ in 0..2 {
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+        }
+        let utxo_with_block = node.get_committed_utxo_set().clone();
+
+        //
+        // Act
+        //
+        node.propose_block_with_last_info(competing_stored_block)
+            .await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        //
+        // Assert
+        //
+        let expected_block_t_hashes: BTreeSet<String> =
+            expected_block_addr_to_hashes.values().cloned().collect();
+        let mining_block = node.get_mining_block().as_ref().unwrap();
+        let mining_block_t_hashes: BTreeSet<String> =
+            mining_block.transactions.iter().cloned().collect();
+
+        assert_eq!(mining_block.header.b_num, 2);
+        assert_eq!(mining_block_t_hashes, expected_block_t_hashes);
+        assert_eq!(node.get_committed_utxo_set(), &utxo_with_block);
+        assert!(node.consensused.tx_pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn block_vote_assembly_ruleset_mismatch_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&["000000"]).await;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+        node.consensused.sufficient_majority = 2;
+
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+        let local_ruleset = node.get_assembly_ruleset().clone();
+        let peer_ruleset = AssemblyRuleset {
+            partition_full_size: local_ruleset.partition_full_size + 1,
+            ..local_ruleset.clone()
+        };
+        let vote_key = |proposer_id| RaftContextKey {
+            proposer_id,
+            proposer_run: 0,
+            proposal_id: 0,
+        };
+
+        //
+        // Act
+        //
+        let local_hash = local_ruleset.short_hash();
+        let peer_hash = peer_ruleset.short_hash();
+        let local_vote = node
+            .received_block_vote(vote_key(1), previous_block.clone(), local_hash)
+            .await;
+        let peer_vote = node
+            .received_block_vote(vote_key(2), previous_block, peer_hash)
+            .await;
+        let mismatch_at_first_vote = node.consensused.has_different_block_ruleset();
+        node.received_assembly_ruleset(vote_key(2), peer_ruleset);
+
+        //
+        // Assert
+        //
+        let expected_mismatches = vec![AssemblyRulesetMismatch {
+            proposer_id: 2,
+            parameters: vec![(
+                "partition_full_size".to_owned(),
+                "1".to_owned(),
+                "2".to_owned(),
+            )],
+        }];
+        assert_eq!((local_vote, peer_vote), (None, None));
+        assert!(mismatch_at_first_vote);
+        assert!(!node.consensused.has_block_stored_info_ready());
+        assert_eq!(
+            node.get_assembly_ruleset_mismatches(),
+            &expected_mismatches[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn assembly_ruleset_difficulty_adjustment_mismatch_no_raft() {
+        //
+        // Arrange
+        //
+        let config = new_test_config(&["000000"]).await;
+        let mut adjusting_config = config.clone();
+        adjusting_config.target_block_interval_secs = Some(10);
+        adjusting_config.difficulty_adjustment_period = Some(5);
+        let node = new_test_node_with_config(&config, None).await;
+        let adjusting_node = new_test_node_with_config(&adjusting_config, None).await;
+
+        //
+        // Act
+        //
+        let ruleset = node.get_assembly_ruleset();
+        let adjusting_ruleset = adjusting_node.get_assembly_ruleset();
+        let differences = ruleset.differences(adjusting_ruleset);
+
+        //
+        // Assert
+        //
+        let expected_difficulty_adjustment = Some(DifficultyAdjustmentConfig {
+            target_block_interval_secs: 10,
+            period_blocks: 5,
+        });
+        assert_eq!(
+            adjusting_ruleset.difficulty_adjustment,
+            expected_difficulty_adjustment
+        );
+        assert_ne!(ruleset.short_hash(), adjusting_ruleset.short_hash());
+        let names: Vec<_> = differences
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["difficulty_adjustment"]);
+    }
+
+    #[tokio::test]
+    async fn block_stored_info_timeout_below_majority_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&["000000"]).await;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+        node.consensused.sufficient_majority = 2;
+        node.consensused.block_stored_info_timeout = Some(Duration::from_millis(50));
+
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        //
+        // Act
+        //
+        node.propose_block_with_last_info(previous_block).await;
+        let commit = node.next_commit().await.unwrap();
+        let single_vote = node.received_commit(commit).await;
+        let timed_out_before = node.consensused.block_stored_info_timed_out();
+
+        time::sleep(Duration::from_millis(100)).await;
+        let timed_out_after = node.consensused.block_stored_info_timed_out();
+
+        node.propose_timestamp().await;
+        let commit = node.next_commit().await.unwrap();
+        let timed_out_commit = node.received_commit(commit).await;
+
+        //
+        // Assert
+        //
+        assert_eq!(single_vote, None);
+        assert!(!timed_out_before);
+        assert!(timed_out_after);
+        assert_eq!(timed_out_commit, Some(CommittedItem::Block));
+        assert_eq!(node.consensused.block_pipeline.current_block_num(), Some(1));
+        assert!(!node.consensused.block_stored_info_timed_out());
+        assert!(node.consensused.current_block_stored_info.is_empty());
+    }
+
+    #[tokio::test]
+    async fn pause_withholds_block_until_resume_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&["000000"]).await;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+        let stale_block = BlockStoredInfo {
+            block_hash: "4567".to_string(),
+            block_num: 5,
+            ..previous_block.clone()
+        };
+        let vote_key = |proposer_id| RaftContextKey {
+            proposer_id,
+            proposer_run: 0,
+            proposal_id: 0,
+        };
+        let ruleset_hash = node.get_assembly_ruleset().short_hash();
+
+        //
+        // Act
+        //
+        node.propose_pause().await;
+        let commit = node.next_commit().await.unwrap();
+        let paused = node.received_commit(commit).await;
+
+        node.propose_block_with_last_info(previous_block).await;
+        let commit = node.next_commit().await.unwrap();
+        let stored_while_paused = node.received_commit(commit).await;
+        let block_num_while_paused = node.consensused.block_pipeline.current_block_num();
+
+        // Votes for another block kept alongside, outnumbering the current one
+        for proposer_id in [2, 3] {
+            node.consensused.append_block_stored_info(
+                vote_key(proposer_id),
+                stale_block.clone(),
+                ruleset_hash.clone(),
+            );
+        }
+
+        node.propose_resume().await;
+        let commit = node.next_commit().await.unwrap();
+        let resumed = node.received_commit(commit).await;
+
+        //
+        // Assert
+        //
+        let previous_hash = node
+            .get_mining_block()
+            .as_ref()
+            .map(|b| b.header.previous_hash.clone());
+        assert_eq!(paused, Some(CommittedItem::Paused));
+        assert_eq!(stored_while_paused, Some(CommittedItem::BlockPaused));
+        assert_eq!(block_num_while_paused, Some(0));
+        assert_eq!(resumed, Some(CommittedItem::Block));
+        assert_eq!(node.consensused.block_pipeline.current_block_num(), Some(1));
+        assert_eq!(previous_hash, Some(Some("0123".to_string())));
+        assert!(!node.consensused.is_paused());
+    }
+
+    #[tokio::test]
+    async fn in_flight_transactions_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+        node.proposed_and_consensused_tx_pool_len_max = 3;
+        node.proposed_tx_pool_len_max = 2;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await;
+        node.received_commit(commit.unwrap()).await.unwrap();
+
+        //
+        // Act
+        //
+        let mut actual_combined_local_flight_consensused = Vec::new();
+        let mut collect_info = |node: &MempoolRaft| {
             actual_combined_local_flight_consensused.push((
                 node.combined_tx_pool_len(),
                 node.local_tx_pool.len(),
                 node.proposed_tx_pool_len,
-                node.consensused.tx_pool.len(),
+                node.consensused.tx_pool.len(),
+            ))
+        };
+        collect_info(&node);
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000", "000001", "000003", "000004", "000005", "000006"],
+            &["000100", "000101", "000103", "000104", "000105", "000106"],
+            &mut BTreeMap::new(),
+        ));
+        node.append_to_tx_druid_pool(valid_transaction(
+            &["000010", "000011"],
+            &["000200", "000200"],
+            &mut BTreeMap::new(),
+        ));
+        collect_info(&node);
+
+        for _ in 0..3 {
+            node.propose_local_transactions_at_timeout().await;
+            node.propose_local_druid_transactions().await;
+            collect_info(&node);
+
+            loop {
+                tokio::select! {
+                    commit = node.next_commit() => {node.received_commit(commit.unwrap()).await;}
+                    _ = time::sleep(Duration::from_millis(5)) => {break;}
+                }
+            }
+            collect_info(&node);
+        }
+
+        node.consensused.start_items_intake();
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            &Integer::from_str_radix(
+                "4884172366043866459154607462096288731322499881015937556796960093953580287561",
+                10,
+            )
+            .unwrap(),
+            &node.consensused.get_current_unicorn().unicorn.seed
+        );
+        assert_eq!(
+            actual_combined_local_flight_consensused,
+            vec![
+                // Start
+                (0, 0, 0, 0),
+                // Transaction appended
+                (6, 6, 0, 0),
+                // Process 1st time out and commit: 2 in flight then consensused
+                (6, 4, 2, 0),
+                (6, 4, 0, 2),
+                // Process 2nd time out and commit: 1 in flight then consensused
+                // (max reached)
+                (6, 3, 1, 2),
+                (6, 3, 0, 3),
+                // Process 3rd time out and commit: 0 in flight then consensused
+                // (max reached)
+                (6, 3, 0, 3),
+                (6, 3, 0, 3),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn propose_full_batch_before_timeout_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+        node.proposed_and_consensused_tx_pool_len_max = 10;
+        node.proposed_tx_pool_len_max = 3;
+        node.propose_transactions_timeout_duration = Duration::from_secs(60);
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await;
+        node.received_commit(commit.unwrap()).await.unwrap();
+        node.set_next_propose_transactions_timeout_at();
+
+        let wait = Duration::from_millis(20);
+
+        //
+        // Act
+        //
+        node.append_to_tx_pool(valid_transaction(
+            &["000000"],
+            &["000100"],
+            &mut BTreeMap::new(),
+        ));
+        let timed_out_partial_batch = time::timeout(wait, node.timeout_propose_transactions())
+            .await
+            .is_ok();
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000001", "000002", "000003", "000004"],
+            &["000101", "000102", "000103", "000104"],
+            &mut BTreeMap::new(),
+        ));
+        let timed_out_full_batch = time::timeout(wait, node.timeout_propose_transactions())
+            .await
+            .is_ok();
+
+        node.propose_local_transactions_at_timeout().await;
+        let proposed_len = node.proposed_tx_pool_len;
+        let timed_out_remaining_batch = time::timeout(wait, node.timeout_propose_transactions())
+            .await
+            .is_ok();
+
+        //
+        // Assert
+        //
+        assert!(!timed_out_partial_batch);
+        assert!(timed_out_full_batch);
+        assert_eq!(proposed_len, 3);
+        assert_eq!(node.local_tx_pool.len(), 2);
+        assert!(!timed_out_remaining_batch);
+    }
+
+    #[tokio::test]
+    async fn in_flight_proposals_saturated_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+        node.max_in_flight = 2;
+        node.proposed_tx_pool_len_max = 1;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await;
+        node.received_commit(commit.unwrap()).await.unwrap();
+
+        node.append_to_tx_pool(valid_transaction(
+            &["000000", "000001", "000002", "000003"],
+            &["000100", "000101", "000102", "000103"],
+            &mut BTreeMap::new(),
+        ));
+        node.append_to_tx_druid_pool(valid_transaction(
+            &["000010", "000011"],
+            &["000200", "000200"],
+            &mut BTreeMap::new(),
+        ));
+
+        //
+        // Act
+        //
+        let mut actual_in_flight_local_proposed = Vec::new();
+        let mut collect_info = |node: &MempoolRaft| {
+            actual_in_flight_local_proposed.push((
+                node.proposed_in_flight.len(),
+                node.local_tx_pool.len(),
+                node.local_tx_druid_pool.len(),
+                node.proposed_tx_pool_len,
             ))
         };
-        collect_info(&node);
 
-        node.append_to_tx_pool(valid_transaction(
-            &["000000", "000001", "000003", "000004", "000005", "000006"],
-            &["000100", "000101", "000103", "000104", "000105", "000106"],
-            &mut BTreeMap::new(),
-        ));
-        node.append_to_tx_druid_pool(valid_transaction(
-            &["000010", "000011"],
-            &["000200", "000200"],
-            &mut BTreeMap::new(),
-        ));
-        collect_info(&node);
+        for _ in 0..3 {
+            node.propose_local_transactions_at_timeout().await;
+            collect_info(&node);
+        }
+        node.propose_local_druid_transactions().await;
+        collect_info(&node);
+
+        loop {
+            tokio::select! {
+                commit = node.next_commit() => {node.received_commit(commit.unwrap()).await;}
+                _ = time::sleep(Duration::from_millis(5)) => {break;}
+            }
+        }
+        collect_info(&node);
+
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_local_druid_transactions().await;
+        collect_info(&node);
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            actual_in_flight_local_proposed,
+            vec![
+                // Proposals issued until the limit is reached
+                (1, 3, 1, 1),
+                (2, 2, 1, 2),
+                // Saturated: transactions stay in the local pools
+                (2, 2, 1, 2),
+                (2, 2, 1, 2),
+                // Commits drained the proposals in flight
+                (0, 2, 1, 0),
+                // Proposals resume
+                (2, 1, 0, 1),
+            ]
+        );
+        assert_eq!(node.consensused.tx_pool.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn re_propose_dropped_transactions_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+        node.proposal_timeout_duration = Duration::ZERO;
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await;
+        node.received_commit(commit.unwrap()).await.unwrap();
+
+        let txs = valid_transaction(
+            &["000000", "000001"],
+            &["000100", "000101"],
+            &mut BTreeMap::new(),
+        );
+        node.append_to_tx_pool(txs.clone());
+
+        //
+        // Act
+        //
+        node.propose_local_transactions_at_timeout().await;
+        let dropped_commit = node.next_commit().await.unwrap();
+        let in_flight_after_drop = node.proposed_tx_pool_len;
+
+        node.propose_local_transactions_at_timeout().await;
+        let commit = node.next_commit().await.unwrap();
+        let re_proposed_item = node.received_commit(commit).await;
+        let late_item = node.received_commit(dropped_commit).await;
+
+        //
+        // Assert
+        //
+        assert_eq!(in_flight_after_drop, 2);
+        assert_eq!(re_proposed_item, Some(CommittedItem::Transactions));
+        assert_eq!(late_item, None);
+        assert_eq!(node.proposed_tx_pool_len, 0);
+        assert_eq!(node.consensused.tx_pool, txs);
+    }
+
+    #[tokio::test]
+    async fn evict_stale_local_transactions_no_raft() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+        node.local_tx_max_age = Duration::from_millis(10);
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await;
+        node.received_commit(commit.unwrap()).await.unwrap();
+
+        let txs = valid_transaction(&["000000"], &["000100"], &mut BTreeMap::new());
+        node.append_to_tx_pool(txs);
+
+        //
+        // Act
+        //
+        time::sleep(Duration::from_millis(20)).await;
+        node.propose_local_transactions_at_timeout().await;
+
+        //
+        // Assert
+        //
+        assert_eq!(node.local_tx_pool.len(), 0);
+        assert_eq!(node.proposed_tx_pool_len, 0);
+        assert!(node.consensused.tx_pool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn druid_pool_rejects_droplets_over_limit() {
+        //
+        // Arrange
+        //
+        let mut node = new_test_node(&[]).await;
+
+        //
+        // Act
+        //
+        let mut accepted = 0;
+        while node.druid_pool_can_accept(1) {
+            node.append_to_tx_druid_pool(BTreeMap::new());
+            accepted += 1;
+        }
+
+        //
+        // Assert
+        //
+        assert_eq!(accepted, DRUID_POOL_LIMIT);
+        assert!(node.druid_pool_can_accept(0));
+        assert!(!node.druid_pool_can_accept(1));
+    }
+
+    #[test]
+    fn estimate_fee_within_pooled_fees() {
+        //
+        // Arrange
+        //
+        let empty = MempoolConsensused {
+            block_size_in_tx: 10,
+            ..Default::default()
+        };
+        let mut consensused = empty.clone();
+        let pooled_fees = [9, 3, 11, 5, 7];
+        for (i, fee) in pooled_fees.iter().enumerate() {
+            let tx = transaction_with_fee(*fee);
+            consensused.tx_pool.insert(format!("00000{}", i), tx);
+        }
+
+        //
+        // Act
+        //
+        let empty_pool = empty.estimate_fee();
+        let no_history = consensused.estimate_fee();
+        for _ in 0..FEE_ESTIMATE_BLOCK_WINDOW {
+            consensused.record_block_fill(10);
+        }
+        let full_blocks = consensused.estimate_fee();
+        for _ in 0..FEE_ESTIMATE_BLOCK_WINDOW {
+            consensused.record_block_fill(0);
+        }
+        let empty_blocks = consensused.estimate_fee();
+
+        consensused.block_size_in_tx = 2;
+        let small_blocks = consensused.estimate_fee();
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            empty_pool,
+            FeeEstimate {
+                fee: TokenAmount(DEFAULT_FEE_ESTIMATE),
+                pooled_txs: 0
+            }
+        );
+        assert_eq!(no_history.pooled_txs, pooled_fees.len());
+        assert_eq!(
+            consensused.recent_block_fills.len(),
+            FEE_ESTIMATE_BLOCK_WINDOW
+        );
+        for estimate in [&no_history, &full_blocks, &empty_blocks, &small_blocks] {
+            assert!((3..=11).contains(&estimate.fee.0), "{:?}", estimate);
+        }
+        assert_eq!(
+            (no_history.fee, full_blocks.fee, empty_blocks.fee),
+            (TokenAmount(7), TokenAmount(11), TokenAmount(3))
+        );
+        // Only the 2 highest fees fit in the next block
+        assert_eq!(small_blocks.fee, TokenAmount(9));
+    }
+
+    #[test]
+    fn take_highest_fee_n_from_tx_pool() {
+        //
+        // Arrange
+        //
+        let hashes_and_fees = [
+            ("000000", 5),
+            ("000001", 0),
+            ("000002", 9),
+            ("000003", 5),
+            ("000004", 1),
+        ];
+        let mut tx_pool: BTreeMap<String, Transaction> = hashes_and_fees
+            .iter()
+            .map(|(hash, fee)| (hash.to_string(), transaction_with_fee(*fee)))
+            .collect();
+
+        //
+        // Act
+        //
+        let block_txs = take_highest_fee_n(2, &mut tx_pool, fee_for);
+        let next_block_txs = take_highest_fee_n(2, &mut tx_pool, fee_for);
+
+        //
+        // Assert
+        //
+        let keys = |txs: &BTreeMap<String, Transaction>| txs.keys().cloned().collect::<Vec<_>>();
+        let fees: Vec<u64> = block_txs.values().map(fee_for).collect();
+
+        assert_eq!(keys(&block_txs), vec!["000000", "000002"]);
+        assert_eq!(fees, vec![5, 9]);
+        assert_eq!(keys(&next_block_txs), vec!["000003", "000004"]);
+        assert_eq!(keys(&tx_pool), vec!["000001"]);
+    }
+
+    #[test]
+    fn take_highest_fee_n_per_byte_from_tx_pool() {
+        //
+        // Arrange
+        //
+        let mut large_tx = transaction_with_fee(6);
+        large_tx.outputs = (0..8)
+            .map(|_| TxOut::new_token_amount("large".to_owned(), TokenAmount(1), None))
+            .collect();
+        let mut tx_pool: BTreeMap<String, Transaction> = BTreeMap::new();
+        tx_pool.insert("000000".to_owned(), large_tx);
+        tx_pool.insert("000001".to_owned(), transaction_with_fee(5));
+        tx_pool.insert("000002".to_owned(), transaction_with_fee(0));
+        tx_pool.insert("000003".to_owned(), transaction_with_fee(5));
+
+        //
+        // Act
+        //
+        let block_txs = take_highest_fee_n(2, &mut tx_pool, fee_for);
+
+        //
+        // Assert
+        //
+        let keys = |txs: &BTreeMap<String, Transaction>| txs.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&block_txs), vec!["000001", "000003"]);
+        assert_eq!(keys(&tx_pool), vec!["000000", "000002"]);
+    }
+
+    #[tokio::test]
+    async fn generate_block_with_fees_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002", "000003"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let (pk, sk) = sign::gen_keypair();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        let _first_block = node.received_commit(commit).await.unwrap();
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        // Spend two 1 token inputs into a single 1 token output: fee of 1
+        let fee_tx = create_valid_transaction_with_ins_outs(
+            &[(0, "000000"), (0, "000001")],
+            &["000100"],
+            &pk,
+            &sk,
+            TokenAmount(1),
+            None,
+        );
+        let no_fee_tx = create_valid_transaction("000002", 0, "000102", &pk, &sk);
+        let negative_fee_tx = create_valid_transaction_with_ins_outs(
+            &[(0, "000003")],
+            &["000103"],
+            &pk,
+            &sk,
+            TokenAmount(2),
+            None,
+        );
+        let pool_txs: BTreeMap<String, Transaction> =
+            vec![fee_tx.clone(), no_fee_tx.clone(), negative_fee_tx.clone()]
+                .into_iter()
+                .collect();
+        let invalid_txs = node.find_invalid_new_txs(&pool_txs);
+        node.append_to_tx_pool(pool_txs);
 
-        for _ in 0..3 {
+        //
+        // Act
+        //
+        node.propose_local_transactions_at_timeout().await;
+        node.propose_block_with_last_info(previous_block.clone())
+            .await;
+        for _ in 0..2 {
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+        }
+        let base_reward = *node.consensused.block_pipeline.get_current_reward();
+        let block_fees = *node.get_current_block_fees();
+        let reward = node.get_current_reward();
+        let mining_block_t_hashes: BTreeSet<String> = node
+            .get_mining_block()
+            .as_ref()
+            .map(|b| b.transactions.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let issuance_before = *node.get_current_issuance();
+        let b_num = node.get_mining_block().as_ref().unwrap().header.b_num;
+        node.take_mining_block();
+        let coinbase = construct_coinbase_tx(b_num, reward, "000200".to_owned());
+        let stored_block = BlockStoredInfo {
+            block_hash: "4567".to_string(),
+            block_num: b_num,
+            mining_transactions: Some((construct_tx_hash(&coinbase), coinbase))
+                .into_iter()
+                .collect(),
+            ..previous_block
+        };
+        node.propose_block_with_last_info(stored_block).await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        //
+        // Assert
+        //
+        let expected_block_t_hashes: BTreeSet<String> =
+            vec![fee_tx.0, no_fee_tx.0].into_iter().collect();
+
+        assert_eq!(invalid_txs, vec![negative_fee_tx.0]);
+        assert_eq!(mining_block_t_hashes, expected_block_t_hashes);
+        assert_eq!(block_fees, TokenAmount(1));
+        assert_eq!(reward, TokenAmount(base_reward.0 + 1));
+        assert_eq!(
+            *node.get_current_issuance(),
+            TokenAmount(issuance_before.0 + base_reward.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn configured_block_size_carries_overflow_to_next_block_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut config = new_test_config(&seed_utxo).await;
+        config.block_size_in_tx = Some(2);
+        let mut node = new_test_node_with_config(&config, None).await;
+        let (pk, sk) = sign::gen_keypair();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+        let previous_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        let pool_txs: BTreeMap<String, Transaction> = seed_utxo
+            .iter()
+            .enumerate()
+            .map(|(i, seed)| create_valid_transaction(seed, 0, &format!("00010{}", i), &pk, &sk))
+            .collect();
+        node.append_to_tx_pool(pool_txs.clone());
+
+        //
+        // Act
+        //
+        let mut commits = Vec::new();
+        for _ in 0..2 {
             node.propose_local_transactions_at_timeout().await;
-            node.propose_local_druid_transactions().await;
-            collect_info(&node);
+            let commit = node.next_commit().await.unwrap();
+            commits.push(node.received_commit(commit).await);
+        }
+        node.propose_block_with_last_info(previous_block.clone())
+            .await;
+        let commit = node.next_commit().await.unwrap();
+        commits.push(node.received_commit(commit).await);
+        let (first_block, _) = node.take_mining_block().unwrap();
 
-            loop {
-                tokio::select! {
-                    commit = node.next_commit() => {node.received_commit(commit.unwrap()).await;}
-                    _ = time::sleep(Duration::from_millis(5)) => {break;}
-                }
-            }
-            collect_info(&node);
+        let stored_block = BlockStoredInfo {
+            block_hash: "4567".to_string(),
+            block_num: first_block.header.b_num,
+            ..previous_block
+        };
+        node.propose_block_with_last_info(stored_block).await;
+        let commit = node.next_commit().await.unwrap();
+        commits.push(node.received_commit(commit).await);
+        let (second_block, _) = node.take_mining_block().unwrap();
+
+        //
+        // Assert
+        //
+        let block_t_hashes: BTreeSet<String> = first_block
+            .transactions
+            .iter()
+            .chain(second_block.transactions.iter())
+            .cloned()
+            .collect();
+        let expected_t_hashes: BTreeSet<String> = pool_txs.keys().cloned().collect();
+
+        assert_eq!(
+            commits,
+            vec![
+                Some(CommittedItem::Transactions),
+                Some(CommittedItem::Transactions),
+                Some(CommittedItem::Block),
+                Some(CommittedItem::Block)
+            ]
+        );
+        assert_eq!(node.get_assembly_ruleset().block_size_in_tx, 2);
+        assert_eq!(first_block.transactions.len(), 2);
+        assert_eq!(second_block.transactions.len(), 1);
+        assert_eq!(block_t_hashes, expected_t_hashes);
+    }
+
+    #[tokio::test]
+    async fn configured_block_size_never_exceeded_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut config = new_test_config(&seed_utxo).await;
+        config.block_size_in_tx = Some(1);
+        let mut node = new_test_node_with_config(&config, None).await;
+        let (pk, sk) = sign::gen_keypair();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+        let mut stored_block = BlockStoredInfo {
+            block_hash: "0123".to_string(),
+            block_num: 0,
+            nonce: vec![0],
+            mining_transactions: BTreeMap::new(),
+            shutdown: false,
+        };
+
+        let pool_txs: BTreeMap<String, Transaction> = seed_utxo
+            .iter()
+            .enumerate()
+            .map(|(i, seed)| create_valid_transaction(seed, 0, &format!("00010{}", i), &pk, &sk))
+            .collect();
+        node.append_to_tx_pool(pool_txs.clone());
+
+        //
+        // Act
+        //
+        let mut block_tx_lens = Vec::new();
+        let mut block_t_hashes = BTreeSet::new();
+        for _ in 0..seed_utxo.len() {
+            node.propose_local_transactions_at_timeout().await;
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+
+            node.propose_block_with_last_info(stored_block.clone())
+                .await;
+            let commit = node.next_commit().await.unwrap();
+            node.received_commit(commit).await.unwrap();
+
+            let (block, _) = node.take_mining_block().unwrap();
+            block_tx_lens.push(block.transactions.len());
+            block_t_hashes.extend(block.transactions.iter().cloned());
+            stored_block.block_num = block.header.b_num;
         }
 
-        node.consensused.start_items_intake();
+        //
+        // Assert
+        //
+        let expected_t_hashes: BTreeSet<String> = pool_txs.keys().cloned().collect();
+
+        assert_eq!(node.proposed_tx_pool_len_max, 1);
+        assert_eq!(block_tx_lens, vec![1, 1, 1]);
+        assert_eq!(block_t_hashes, expected_t_hashes);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn local_pools_restored_without_spent_entries_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut db = db_utils::new_db(DbMode::InMemory, &crate::mempool::DB_SPEC, None, None);
+        let (pk, sk) = sign::gen_keypair();
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        let valid_txs: BTreeMap<String, Transaction> = vec![
+            create_valid_transaction("000000", 0, "000100", &pk, &sk),
+            create_valid_transaction("000001", 0, "000101", &pk, &sk),
+        ]
+        .into_iter()
+        .collect();
+        let (spent_hash, spent_tx) = create_valid_transaction("000009", 0, "000102", &pk, &sk);
+        let droplet: BTreeMap<String, Transaction> =
+            Some(create_valid_transaction("000002", 0, "000103", &pk, &sk))
+                .into_iter()
+                .collect();
+        let spent_droplet: BTreeMap<String, Transaction> =
+            Some((spent_hash.clone(), spent_tx.clone()))
+                .into_iter()
+                .collect();
+
+        node.append_to_tx_pool(valid_txs.clone());
+        node.append_to_tx_pool(Some((spent_hash, spent_tx)).into_iter().collect());
+        node.append_to_tx_druid_pool(droplet.clone());
+        node.append_to_tx_druid_pool(spent_droplet);
+
+        //
+        // Act
+        //
+        node.save_local_pools(&mut db).unwrap();
+
+        let mut restarted = new_test_node(&seed_utxo).await;
+        restarted.propose_initial_item().await;
+        let commit = restarted.next_commit().await.unwrap();
+        restarted.received_commit(commit).await.unwrap();
+        restarted.load_local_pools(&db).unwrap();
+
+        //
+        // Assert
+        //
+        let restored_txs: BTreeMap<String, Transaction> = restarted
+            .local_tx_pool
+            .iter()
+            .map(|(hash, (tx, _))| (hash.clone(), tx.clone()))
+            .collect();
+
+        assert_eq!(restored_txs, valid_txs);
+        assert_eq!(restarted.get_local_tx_druid_pool(), &vec![droplet]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shutdown_drains_in_flight_proposals_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut db = db_utils::new_db(DbMode::InMemory, &crate::mempool::DB_SPEC, None, None);
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        let proposed_txs = valid_transaction(&["000000"], &["000100"], &mut BTreeMap::new());
+        let local_txs = valid_transaction(&["000001"], &["000101"], &mut BTreeMap::new());
+        node.append_to_tx_pool(proposed_txs.clone());
+        node.propose_local_transactions_at_timeout().await;
+        node.append_to_tx_pool(local_txs.clone());
+
+        //
+        // Act
+        //
+        let summary = node.shutdown(&mut db, Duration::from_secs(5)).await;
+
+        let mut restarted = new_test_node(&seed_utxo).await;
+        restarted.load_local_pools(&db).unwrap();
 
         //
         // Assert
         //
+        let restored_txs: BTreeMap<String, Transaction> = restarted
+            .local_tx_pool
+            .iter()
+            .map(|(hash, (tx, _))| (hash.clone(), tx.clone()))
+            .collect();
+
         assert_eq!(
-            &Integer::from_str_radix(
-                "4884172366043866459154607462096288731322499881015937556796960093953580287561",
-                10,
-            )
-            .unwrap(),
-            &node.consensused.get_current_unicorn().unicorn.seed
+            summary,
+            ShutdownSummary {
+                drained: 1,
+                persisted: 1
+            }
+        );
+        assert_eq!(node.consensused.tx_pool, proposed_txs);
+        assert_eq!(node.proposed_in_flight.len(), 0);
+        assert_eq!(restored_txs, local_txs);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn shutdown_persists_undrained_proposals_no_raft() {
+        //
+        // Arrange
+        //
+        let seed_utxo = ["000000", "000001", "000002"];
+        let mut node = new_test_node(&seed_utxo).await;
+        let mut db = db_utils::new_db(DbMode::InMemory, &crate::mempool::DB_SPEC, None, None);
+
+        node.propose_initial_item().await;
+        let commit = node.next_commit().await.unwrap();
+        node.received_commit(commit).await.unwrap();
+
+        let proposed_txs = valid_transaction(
+            &["000000", "000001"],
+            &["000100", "000101"],
+            &mut BTreeMap::new(),
         );
+        node.append_to_tx_pool(proposed_txs.clone());
+        node.propose_local_transactions_at_timeout().await;
+        let in_flight_before = node.proposed_in_flight.len();
+
+        //
+        // Act
+        //
+        let summary = node.shutdown(&mut db, Duration::ZERO).await;
+        node.append_to_tx_pool(valid_transaction(
+            &["000002"],
+            &["000102"],
+            &mut BTreeMap::new(),
+        ));
+        node.propose_local_transactions_at_timeout().await;
+
+        let mut restarted = new_test_node(&seed_utxo).await;
+        restarted.load_local_pools(&db).unwrap();
+
+        //
+        // Assert
+        //
+        let restored_txs: BTreeMap<String, Transaction> = restarted
+            .local_tx_pool
+            .iter()
+            .map(|(hash, (tx, _))| (hash.clone(), tx.clone()))
+            .collect();
+
+        assert_eq!(in_flight_before, 1);
         assert_eq!(
-            actual_combined_local_flight_consensused,
-            vec![
-                // Start
-                (0, 0, 0, 0),
-                // Transaction appended
-                (6, 6, 0, 0),
-                // Process 1st time out and commit: 2 in flight then consensused
-                (6, 4, 2, 0),
-                (6, 4, 0, 2),
-                // Process 2nd time out and commit: 1 in flight then consensused
-                // (max reached)
-                (6, 3, 1, 2),
-                (6, 3, 0, 3),
-                // Process 3rd time out and commit: 0 in flight then consensused
-                // (max reached)
-                (6, 3, 0, 3),
-                (6, 3, 0, 3),
-            ]
+            summary,
+            ShutdownSummary {
+                drained: 0,
+                persisted: 2
+            }
         );
+        assert!(node.consensused.tx_pool.is_empty());
+        assert_eq!(node.proposed_in_flight.len(), 0);
+        assert_eq!(node.proposed_tx_pool_len, 0);
+        assert_eq!(restored_txs, proposed_txs);
     }
 
     async fn new_test_node(seed_utxo: &[&str]) -> MempoolRaft {
+        new_test_node_with_raft_db(seed_utxo, None).await
+    }
+
+    async fn new_test_node_with_raft_db(
+        seed_utxo: &[&str],
+        raft_db: Option<SimpleDb>,
+    ) -> MempoolRaft {
+        let mempool_config = new_test_config(seed_utxo).await;
+        new_test_node_with_config(&mempool_config, raft_db).await
+    }
+
+    async fn new_test_node_with_config(
+        mempool_config: &MempoolNodeConfig,
+        raft_db: Option<SimpleDb>,
+    ) -> MempoolRaft {
+        let mut node = MempoolRaft::new(mempool_config, raft_db).await;
+        node.set_key_run(0);
+        node
+    }
+
+    async fn new_test_config(seed_utxo: &[&str]) -> MempoolNodeConfig {
         let mempool_node = create_socket_addr("0.0.0.0").await.unwrap();
         let tx_out = TxOutSpec {
             public_key: "5371832122a8e804fa3520ec6861c3fa554a7f6fb617e6f0768452090207e07c"
                 .to_owned(),
+            address: None,
             amount: TokenAmount(1),
             locktime: 0,
         };
-        let mempool_config = MempoolNodeConfig {
+        MempoolNodeConfig {
             mempool_node_idx: 0,
             mempool_db_mode: DbMode::InMemory,
             tls_config: Default::default(),
@@ -2267,6 +5057,12 @@ mod test {
             mempool_raft_tick_timeout: 10,
             mempool_mining_event_timeout: 500,
             mempool_transaction_timeout: 50,
+            mempool_proposal_timeout: 5000,
+            mempool_local_tx_max_age: 600000,
+            mempool_block_stored_info_timeout: 30000,
+            mempool_pow_batch_window: 5,
+            mempool_storage_resend_interval: 2000,
+            mempool_druid_pool_expiry_blocks: 10,
             mempool_seed_utxo: seed_utxo
                 .iter()
                 .map(|v| (v.to_string(), vec![tx_out.clone()]))
@@ -2277,22 +5073,29 @@ mod test {
             jurisdiction: "US".to_string(),
             sanction_list: Vec::new(),
             mempool_api_use_tls: true,
+            mempool_api_rate_limit: None,
             mempool_api_port: 3003,
             routes_pow: Default::default(),
             backup_block_modulo: Default::default(),
             utxo_re_align_block_modulo: Default::default(),
+            mempool_snapshot_block_modulo: Default::default(),
             backup_restore: Default::default(),
             enable_trigger_messages_pipeline_reset: Default::default(),
             mempool_miner_whitelist: Default::default(),
             peer_limit: 1000,
+            max_frame_size: None,
+            peer_reconnect_max_delay_ms: None,
             sub_peer_limit: 1000,
             initial_issuances: Default::default(),
             tx_status_lifetime: 600000,
             activation_height_asert: None,
-        };
-        let mut node = MempoolRaft::new(&mempool_config, Default::default()).await;
-        node.set_key_run(0);
-        node
+            block_size_in_tx: None,
+            tx_pool_limit: None,
+            max_in_flight: None,
+            target_block_interval_secs: None,
+            difficulty_adjustment_period: None,
+            peer_discovery: None,
+        }
     }
 
     fn valid_transaction(
@@ -2316,6 +5119,13 @@ mod test {
         txs.into_iter().map(|(tx, _)| tx).collect()
     }
 
+    fn transaction_with_fee(fee: u64) -> Transaction {
+        let mut tx = Transaction::new();
+        let fee_out = TxOut::new_token_amount("fee_address".to_owned(), TokenAmount(fee), None);
+        tx.fees.push(fee_out);
+        tx
+    }
+
     pub fn key_with_max_value<'a>(
         map: &BTreeMap<String, String>,
         key1: &'a str,
@@ -1,27 +1,37 @@
+use crate::comms_handler::peer_discovery::{configure_peer_discovery, discover_peer_addr};
 use crate::comms_handler::Node;
-use crate::comms_handler::{CommsError, Event, TcpTlsConfig};
+use crate::comms_handler::{
+    default_max_frame_size, split_into_frame_batches, CommsError, Event, TcpTlsConfig,
+};
 use crate::configurations::{ExtraNodeParams, MinerNodeConfig, TlsPrivateInfo};
-use crate::constants::INTERNAL_TX_LIMIT;
+use crate::constants::{
+    INTERNAL_TX_LIMIT, MINER_OUTAGE_CHECK_INTERVAL_MS, MINER_OUTAGE_MINING_SECS,
+    MINER_STATS_UPDATE_INTERVAL_MS,
+};
 use crate::db_utils;
 use crate::interfaces::{
-    BlockchainItem, MempoolRequest, MineApiRequest, MineRequest, MinerInterface, NodeType, PowInfo,
-    ProofOfWork, Response, Rs2JsMsg, StorageRequest, UtxoFetchType, UtxoSet,
+    BlockchainItem, MempoolRequest, MineApiRequest, MineRequest, MinerInterface, NodeType,
+    NoncePartition, PowInfo, ProofOfWork, Response, Rs2JsMsg, StorageRequest, UtxoFetchType,
+    UtxoSet,
 };
+use crate::miner_stats::{MinerStatistics, MinerStats, MinerStatsCounters, MinerStatsHandle};
 use crate::threaded_call::{ThreadedCallChannel, ThreadedCallSender};
 use crate::transactor::Transactor;
 use crate::utils::{
-    self, apply_mining_tx, construct_coinbase_tx, create_socket_addr, format_parition_pow_address,
-    generate_pow_for_block, get_payments_for_wallet, get_payments_for_wallet_from_utxo,
-    to_api_keys, to_route_pow_infos, try_send_to_ui, ApiKeys, DeserializedBlockchainItem,
-    LocalEvent, LocalEventChannel, LocalEventSender, ResponseResult, RoutesPoWInfo,
-    RunningTaskOrResult,
+    self, apply_mining_tx, construct_block_template_id, construct_coinbase_tx, create_socket_addr,
+    format_parition_pow_address, generate_pow_for_block_threaded, get_payments_for_wallet,
+    get_payments_for_wallet_from_utxo, get_timestamp_now, to_api_keys, to_route_pow_infos,
+    try_send_to_ui, ApiKeys, CancellationToken, DeserializedBlockchainItem, LocalEvent,
+    LocalEventChannel, LocalEventSender, ResponseResult, RoutesPoWInfo, RunningTaskOrResult,
 };
 use crate::wallet::{LockedCoinbase, WalletDb, WalletDbError, DB_SPEC};
 use async_trait::async_trait;
 use bincode::{deserialize, serialize};
 use bytes::Bytes;
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::{
     error::Error,
@@ -30,15 +40,16 @@ use std::{
     net::SocketAddr,
     net::{IpAddr, Ipv4Addr},
     str,
-    time::SystemTime,
+    time::{Duration, Instant, SystemTime},
 };
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::task;
+use tokio::time::{self, Interval, MissedTickBehavior};
 use tracing::{debug, error, error_span, info, info_span, trace, warn};
 use tracing_futures::Instrument;
 use tw_chain::primitives::asset::{Asset, TokenAmount};
 use tw_chain::primitives::block::{self, BlockHeader};
-use tw_chain::primitives::transaction::Transaction;
+use tw_chain::primitives::transaction::{Transaction, TxOut};
 use tw_chain::utils::transaction_utils::{
     construct_tx_core, construct_tx_hash, update_input_signatures,
 };
@@ -49,6 +60,12 @@ pub const LAST_COINBASE_KEY: &str = "LastCoinbaseKey";
 /// Key for last pow coinbase produced
 pub const MINING_ADDRESS_KEY: &str = "MiningAddressKey";
 
+/// Key for cumulative mining statistics counters
+pub const MINER_STATS_KEY: &str = "MinerStatsKey";
+
+/// Device name of the CPU block PoW search in mining statistics
+pub const MINER_STATS_CPU_DEVICE: &str = "cpu";
+
 /// Result wrapper for miner errors
 pub type Result<T> = std::result::Result<T, MinerError>;
 
@@ -73,6 +90,34 @@ pub struct BlockPoWReceived {
     reward: TokenAmount,
 }
 
+/// Block template kept so mining can continue through a short mempool outage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedBlockTemplate {
+    pub template_id: String,
+    pub difficulty: Vec<u8>,
+    pub b_num: u64,
+}
+
+/// Block PoW found while the mempool node was unreachable
+#[derive(Debug, Clone)]
+struct QueuedBlockPoW {
+    peer: SocketAddr,
+    template_id: String,
+    b_num: u64,
+    nonce: Vec<u8>,
+    coinbase_hash: String,
+    coinbase: Transaction,
+}
+
+/// Local work cache used while the mempool node is unreachable
+#[derive(Debug, Default)]
+struct MinerWorkCache {
+    template: Option<CachedBlockTemplate>,
+    queued_pow: Option<QueuedBlockPoW>,
+    outage_start: Option<SystemTime>,
+    stale_work_count: u64,
+}
+
 #[derive(Debug)]
 pub enum MinerError {
     ConfigError(&'static str),
@@ -154,6 +199,8 @@ pub struct MinerNode {
     rand_num: Vec<u8>,
     pause_node: Arc<RwLock<bool>>,
     address_aggregation_limit: Option<usize>,
+    address_aggregation_max_inputs: Option<usize>,
+    enable_address_aggregation: bool,
     current_block: CurrentBlockWithMutex,
     last_pow: Option<ProofOfWork>,
     current_coinbase: Option<(String, Transaction)>,
@@ -164,10 +211,19 @@ pub struct MinerNode {
     received_utxo_set: Option<UtxoSet>,
     mining_partition_task: RunningTaskOrResult<(ProofOfWork, PowInfo, SocketAddr)>,
     mining_block_task: RunningTaskOrResult<BlockPoWInfo>,
+    mining_block_cancel: CancellationToken,
+    nonce_partition: Option<NoncePartition>,
+    pow_threads: usize,
+    mining_hash_count: Arc<AtomicU64>,
+    miner_stats: MinerStatsHandle,
+    stats_update: Interval,
     mining_api_key: Option<String>,
     blockchain_item_received: Option<(String, BlockchainItem, SocketAddr)>,
     api_info: (SocketAddr, Option<TlsPrivateInfo>, ApiKeys, RoutesPoWInfo),
     activation_height_asert: u64,
+    work_cache: MinerWorkCache,
+    outage_mining_limit: Duration,
+    outage_check: Interval,
 }
 
 impl MinerNode {
@@ -179,13 +235,6 @@ impl MinerNode {
     /// * `extra`  - additional parameter for construction
     pub async fn new(config: MinerNodeConfig, mut extra: ExtraNodeParams) -> Result<MinerNode> {
         let addr = config.miner_address;
-        let raw_mempool_addr = config
-            .mempool_nodes
-            .get(config.miner_mempool_node_idx)
-            .ok_or(MinerError::ConfigError("Invalid mempool index"))?;
-        let mempool_addr = create_socket_addr(&raw_mempool_addr.address)
-            .await
-            .map_err(|_| MinerError::ConfigError("Invalid mempool node address in config file"))?;
 
         // Restore old keys if backup is present
         if config.backup_restore.unwrap_or(false) {
@@ -206,7 +255,7 @@ impl MinerNode {
             .miner_api_use_tls
             .then(|| tcp_tls_config.clone_private_info());
         let api_keys = to_api_keys(config.api_keys.clone());
-        let node = Node::new(
+        let mut node = Node::new(
             &tcp_tls_config,
             config.peer_limit,
             config.peer_limit,
@@ -215,15 +264,60 @@ impl MinerNode {
             false,
         )
         .await?;
+        if let Some(max_frame_size) = config.max_frame_size {
+            node.set_max_frame_size(max_frame_size);
+        }
+        if let Some(max_delay_ms) = config.peer_reconnect_max_delay_ms {
+            node.set_reconnect_max_delay(Duration::from_millis(max_delay_ms));
+        }
+        let peer_discovery = config.peer_discovery.as_ref();
+        configure_peer_discovery(&mut node, peer_discovery).await?;
+
+        let mempool_addr = match config.mempool_nodes.get(config.miner_mempool_node_idx) {
+            Some(raw_mempool_addr) => create_socket_addr(&raw_mempool_addr.address)
+                .await
+                .map_err(|_| {
+                    MinerError::ConfigError("Invalid mempool node address in config file")
+                })?,
+            None => {
+                let bootstrap_nodes = peer_discovery.map(|c| c.bootstrap_nodes.as_slice());
+                discover_peer_addr(
+                    &mut node,
+                    bootstrap_nodes.unwrap_or_default(),
+                    NodeType::Mempool,
+                )
+                .await
+                .ok_or(MinerError::ConfigError(
+                    "No mempool node configured or discovered",
+                ))?
+            }
+        };
         let api_pow_info = to_route_pow_infos(config.routes_pow.clone());
         let static_miner_address = Arc::new(RwLock::new(config.static_miner_address.clone()));
         let mining_api_key = config.mining_api_key.clone();
         let address_aggregation_limit = config.address_aggregation_limit;
+        let address_aggregation_max_inputs = config.address_aggregation_max_inputs;
+        let enable_address_aggregation = config.enable_address_aggregation.unwrap_or(true);
 
         let activation_height_asert = config
             .activation_height_asert
             .unwrap_or(crate::constants::ACTIVATION_HEIGHT_ASERT);
 
+        let outage_mining_limit = Duration::from_secs(
+            config
+                .outage_mining_secs
+                .unwrap_or(MINER_OUTAGE_MINING_SECS),
+        );
+        let pow_threads = config
+            .pow_threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        let mut outage_check =
+            time::interval(Duration::from_millis(MINER_OUTAGE_CHECK_INTERVAL_MS));
+        outage_check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut stats_update =
+            time::interval(Duration::from_millis(MINER_STATS_UPDATE_INTERVAL_MS));
+        stats_update.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
         MinerNode {
             node,
             local_events: Default::default(),
@@ -243,11 +337,22 @@ impl MinerNode {
             wait_partition_task: Default::default(),
             mining_partition_task: Default::default(),
             mining_block_task: Default::default(),
+            mining_block_cancel: Default::default(),
+            nonce_partition: None,
+            pow_threads,
+            mining_hash_count: Default::default(),
+            miner_stats: MinerStats::new_handle(),
+            stats_update,
             blockchain_item_received: Default::default(),
             mining_api_key,
             api_info: (api_addr, api_tls_info, api_keys, api_pow_info),
             address_aggregation_limit,
+            address_aggregation_max_inputs,
+            enable_address_aggregation,
             activation_height_asert,
+            work_cache: Default::default(),
+            outage_mining_limit,
+            outage_check,
         }
         .load_local_db()
         .await
@@ -385,6 +490,18 @@ impl MinerNode {
         self.static_miner_address.read().await.clone()
     }
 
+    /// Stop accepting connections and mining, store the mining statistics,
+    /// and notify connected peers of the disconnection
+    pub async fn shutdown(&mut self) {
+        join_all(self.node.stop_listening().await).await;
+        self.cancel_block_mining();
+        self.store_miner_stats().await;
+        if let Err(e) = self.wallet_db.flush_persistent_store().await {
+            error!("Error flushing wallet db: {:?}", e);
+        }
+        utils::shutdown_connections(&mut self.node).await;
+    }
+
     /// Extract persistent dbs
     pub async fn take_closed_extra_params(&mut self) -> ExtraNodeParams {
         let wallet_db = self.wallet_db.take_closed_persistent_store().await;
@@ -484,6 +601,30 @@ impl MinerNode {
                     info!("Block PoW found and sent");
                 }
             }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Queued block PoW submitted" => {
+                info!("Queued block PoW sent after reconnection");
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Stale queued block PoW discarded" => {
+                warn!(
+                    "Queued block PoW discarded: {} stale so far",
+                    self.work_cache.stale_work_count
+                );
+            }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Mining halted after mempool outage" => {
+                warn!(
+                    "Mining halted: mempool unreachable for {:?}",
+                    self.outage_mining_limit
+                );
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -513,6 +654,12 @@ impl MinerNode {
                 success: false,
                 reason,
             }) if reason == "Miner not authorized" => return ResponseResult::Exit,
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Frame size limit exceeded" => {
+                warn!("Disconnected peer sending frames over the size limit");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -563,6 +710,14 @@ impl MinerNode {
                         reason: "Block PoW complete".to_string(),
                     }));
                 }
+                _ = self.outage_check.tick(), if self.work_cache.template.is_some() => {
+                    if let Some(res) = self.check_mempool_outage().await {
+                        return Some(Ok(res));
+                    }
+                }
+                _ = self.stats_update.tick() => {
+                    self.update_miner_stats().await;
+                }
                 Some(event) = self.local_events.rx.recv() => {
                     if let Some(res) = self.handle_local_event(event).await {
                         return Some(Ok(res));
@@ -598,6 +753,13 @@ impl MinerNode {
                         reason: "Failed to send startup requests on reconnection".to_string(),
                     });
                 }
+                self.work_cache.outage_start = None;
+                if let Err(err) = self.send_mining_template_check().await {
+                    error!(
+                        "Failed to check cached mining template on reconnect: {}",
+                        err
+                    );
+                }
                 Some(Response {
                     success: true,
                     reason: "Sent startup requests on reconnection".to_string(),
@@ -621,6 +783,23 @@ impl MinerNode {
                     .instrument(peer_span)
                     .await
             }
+            Event::FrameSizeLimitExceeded { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Frame size limit exceeded".to_owned(),
+            })),
+            Event::PeerReconnected { peer } => {
+                if let Err(err) = self.send_startup_requests().await {
+                    error!("Failed to send startup requests on reconnect to {peer:?}: {err}");
+                }
+                Ok(Some(Response {
+                    success: true,
+                    reason: "Peer reconnected".to_owned(),
+                }))
+            }
+            Event::PeerUnreachable { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Peer unreachable".to_owned(),
+            })),
         }
     }
 
@@ -667,6 +846,7 @@ impl MinerNode {
                 reward,
                 block,
                 b_num,
+                partition,
             } => {
                 self.receive_pre_block_and_random(
                     peer,
@@ -676,6 +856,7 @@ impl MinerNode {
                     reward,
                     block,
                     b_num,
+                    partition,
                 )
                 .await
             }
@@ -686,9 +867,18 @@ impl MinerNode {
                     .await
             }
             SendUtxoSet { utxo_set } => Some(self.receive_utxo_set(utxo_set)),
+            SendMiningTemplateStatus {
+                b_num,
+                template_id,
+                current,
+            } => {
+                self.receive_mining_template_status(peer, b_num, template_id, current)
+                    .await
+            }
             Closing => self.receive_closing(peer),
             MinerRemovedAck => Some(self.handle_receive_miner_removed_ack(peer).await),
             MinerNotAuthorized => Some(self.handle_receive_miner_not_authorized(peer).await),
+            NetworkPaused { b_num } => Some(self.handle_receive_network_paused(peer, b_num)),
             MinerApi(api_request) => self.handle_miner_api(peer, api_request).await,
         }
     }
@@ -788,6 +978,27 @@ impl MinerNode {
         }
     }
 
+    /// Handle notice that no new block follows `b_num` until the network is resumed
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`  - Sending peer's socket address
+    /// * `b_num` - Number of the last block generated before the pause
+    pub fn handle_receive_network_paused(&mut self, peer: SocketAddr, b_num: u64) -> Response {
+        if self.mempool_address() != peer {
+            return Response {
+                success: false,
+                reason: "Received network paused notification from non-mempool peer".to_string(),
+            };
+        }
+
+        warn!("Network paused: no new block after block {}", b_num);
+        Response {
+            success: true,
+            reason: "Network paused".to_string(),
+        }
+    }
+
     /// Handle acknowledgement of miner removed from mempool node
     pub async fn handle_receive_miner_removed_ack(&mut self, peer: SocketAddr) -> Response {
         if self.mempool_address() == peer {
@@ -814,6 +1025,7 @@ impl MinerNode {
     /// Handle disconnect from mempool node
     pub async fn handle_disconnect_from_mempool(&mut self) -> Response {
         let mempool_addr = self.mempool_address();
+        self.node.untrack_peers(Some(&[mempool_addr])).await;
         let join_handles = self.node.disconnect_all(Some(&[mempool_addr])).await;
         if join_handles.is_empty() {
             return Response {
@@ -853,6 +1065,7 @@ impl MinerNode {
                 reason: "Failed to connect to mempool".to_string(),
             };
         }
+        self.node.track_peers(&[mempool_addr]).await;
         try_send_to_ui(
             self.ui_feedback_tx.as_ref(),
             Rs2JsMsg::Value(serde_json::json!({
@@ -911,6 +1124,9 @@ impl MinerNode {
                 };
             }
             *self.pause_node.write().await = false;
+            if let Err(err) = self.send_mining_template_check().await {
+                error!("Failed to check cached mining template on resume: {}", err);
+            }
             try_send_to_ui(
                 self.ui_feedback_tx.as_ref(),
                 Rs2JsMsg::Value(serde_json::json!({
@@ -1044,12 +1260,14 @@ impl MinerNode {
         reward: TokenAmount,
         pre_block: Option<BlockHeader>,
         b_num: u64,
+        partition: Option<NoncePartition>,
     ) -> Option<Response> {
         let process_rnd = self
             .receive_random_number(peer, pow_info, rand_num, win_coinbases, b_num)
             .await;
         let process_block = if let Some(pre_block) = pre_block {
-            self.receive_pre_block(peer, pre_block, reward).await
+            self.receive_pre_block(peer, pre_block, reward, partition)
+                .await
         } else {
             false
         };
@@ -1112,11 +1330,13 @@ impl MinerNode {
     /// * `peer`     - Sending peer's socket address
     /// * `pre_block` - New block to be mined
     /// * `reward`    - The block reward to be paid on successful PoW
+    /// * `partition` - Nonce partition to mine the block in
     async fn receive_pre_block(
         &mut self,
         peer: SocketAddr,
         pre_block: BlockHeader,
         reward: TokenAmount,
+        partition: Option<NoncePartition>,
     ) -> bool {
         if peer != self.mempool_address() {
             return false;
@@ -1136,12 +1356,18 @@ impl MinerNode {
             .map(|c| c.block.b_num);
         if new_b_num <= current_b_num {
             if new_b_num == current_b_num {
+                if partition != self.nonce_partition && self.mining_block_task.is_active() {
+                    // Participants changed mid-round: search our new partition instead
+                    self.start_generate_pow_for_current_block(peer, new_block, partition)
+                        .await;
+                    return true;
+                }
                 self.process_found_block_pow().await;
             }
             return false;
         }
 
-        self.start_generate_pow_for_current_block(peer, new_block)
+        self.start_generate_pow_for_current_block(peer, new_block, partition)
             .await;
         true
     }
@@ -1221,6 +1447,12 @@ impl MinerNode {
             debug!("Found block in {}ms", elapsed.as_millis());
         }
 
+        if self.is_disconnected().await {
+            return self
+                .queue_found_block_pow(peer, b_num, nonce, coinbase_hash, coinbase)
+                .await;
+        }
+
         let is_paused = *self.pause_node.read().await;
 
         if !is_paused {
@@ -1241,6 +1473,216 @@ impl MinerNode {
         true
     }
 
+    /// Keep a block PoW found while the mempool node is unreachable until the
+    /// template is confirmed current on reconnection
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`          - Mempool node the PoW is for
+    /// * `b_num`         - Block number the PoW is for
+    /// * `nonce`         - Nonce found for the block
+    /// * `coinbase_hash` - Hash of the mining transaction
+    /// * `coinbase`      - Mining transaction
+    async fn queue_found_block_pow(
+        &mut self,
+        peer: SocketAddr,
+        b_num: u64,
+        nonce: Vec<u8>,
+        coinbase_hash: String,
+        coinbase: Transaction,
+    ) -> bool {
+        let template_id = match &self.work_cache.template {
+            Some(template) if template.b_num == b_num => template.template_id.clone(),
+            _ => {
+                warn!("Dropped block PoW for {b_num}: no matching cached template");
+                return false;
+            }
+        };
+
+        let outage_start = *self
+            .work_cache
+            .outage_start
+            .get_or_insert_with(SystemTime::now);
+        if self.is_outage_limit_reached(outage_start) {
+            self.halt_outage_mining().await;
+            return false;
+        }
+
+        info!("Mempool unreachable: queued block PoW for {b_num}");
+        self.work_cache.queued_pow = Some(QueuedBlockPoW {
+            peer,
+            template_id,
+            b_num,
+            nonce,
+            coinbase_hash,
+            coinbase,
+        });
+        true
+    }
+
+    /// Check the mempool connection while a cached template is mined,
+    /// halting mining once the outage outlasts the configured limit
+    async fn check_mempool_outage(&mut self) -> Option<Response> {
+        if !self.is_disconnected().await {
+            return None;
+        }
+
+        let outage_start = match self.work_cache.outage_start {
+            Some(outage_start) => outage_start,
+            None => {
+                warn!("Mempool unreachable: mining cached template");
+                *self.work_cache.outage_start.insert(SystemTime::now())
+            }
+        };
+        if !self.is_outage_limit_reached(outage_start) {
+            return None;
+        }
+
+        self.halt_outage_mining().await;
+        Some(Response {
+            success: true,
+            reason: "Mining halted after mempool outage".to_string(),
+        })
+    }
+
+    /// Whether an outage started at `outage_start` outlasted the configured limit
+    fn is_outage_limit_reached(&self, outage_start: SystemTime) -> bool {
+        outage_start
+            .elapsed()
+            .is_ok_and(|elapsed| elapsed >= self.outage_mining_limit)
+    }
+
+    /// Stop all block mining and drop any work done during the outage
+    async fn halt_outage_mining(&mut self) {
+        if let Some(queued) = self.work_cache.queued_pow.take() {
+            warn!(
+                "Dropped queued block PoW for {}: outage limit reached",
+                queued.b_num
+            );
+        }
+        self.work_cache.template = None;
+        self.work_cache.outage_start = None;
+        self.cancel_block_mining();
+        self.mining_block_task = Default::default();
+        *self.current_block.lock().await = None;
+    }
+
+    /// Ask the mempool node whether the template of the queued block PoW is still mined
+    pub async fn send_mining_template_check(&mut self) -> Result<()> {
+        let Some(queued) = &self.work_cache.queued_pow else {
+            return Ok(());
+        };
+
+        let request = MempoolRequest::CheckMiningTemplate {
+            b_num: queued.b_num,
+            template_id: queued.template_id.clone(),
+        };
+        self.node.send(queued.peer, request).await?;
+        Ok(())
+    }
+
+    /// Submit the queued block PoW if its template is still mined, discard it otherwise
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`        - Sending peer's socket address
+    /// * `b_num`       - Block number of the checked template
+    /// * `template_id` - Identifier of the checked template
+    /// * `current`     - Whether the template is still mined
+    async fn receive_mining_template_status(
+        &mut self,
+        peer: SocketAddr,
+        b_num: u64,
+        template_id: String,
+        current: bool,
+    ) -> Option<Response> {
+        if peer != self.mempool_address() {
+            return None;
+        }
+
+        let is_checked = |q: &QueuedBlockPoW| q.b_num == b_num && q.template_id == template_id;
+        if !self.work_cache.queued_pow.as_ref().is_some_and(is_checked) {
+            return None;
+        }
+
+        if current && *self.pause_node.read().await {
+            // Checked again when the node resumes
+            return Some(Response {
+                success: true,
+                reason: "Queued block PoW kept while paused".to_string(),
+            });
+        }
+        let queued = self.work_cache.queued_pow.take()?;
+
+        if !current {
+            self.work_cache.stale_work_count += 1;
+            self.miner_stats.lock().unwrap().submission_rejected();
+            self.store_miner_stats().await;
+            return Some(Response {
+                success: true,
+                reason: "Stale queued block PoW discarded".to_string(),
+            });
+        }
+
+        let (nonce, coinbase) = (queued.nonce.clone(), queued.coinbase.clone());
+        if let Err(e) = self.send_pow(peer, b_num, nonce, coinbase).await {
+            let error = format!("receive_mining_template_status PoW {:?}", e);
+            error!("{:?}", &error);
+            try_send_to_ui(self.ui_feedback_tx.as_ref(), Rs2JsMsg::Error { error }).await;
+            self.work_cache.queued_pow = Some(queued);
+            return None;
+        }
+
+        let QueuedBlockPoW {
+            coinbase_hash,
+            coinbase,
+            ..
+        } = queued;
+
+        self.current_coinbase =
+            store_last_coinbase(&self.wallet_db, Some((coinbase_hash, coinbase))).await;
+
+        Some(Response {
+            success: true,
+            reason: "Queued block PoW submitted".to_string(),
+        })
+    }
+
+    /// Hash of the coinbase transaction sent with our last block PoW
+    pub fn current_coinbase_hash(&self) -> Option<&str> {
+        self.current_coinbase
+            .as_ref()
+            .map(|(hash, _)| hash.as_str())
+    }
+
+    /// Number of queued block PoW discarded because their template was no longer mined
+    pub fn stale_work_count(&self) -> u64 {
+        self.work_cache.stale_work_count
+    }
+
+    /// Block template currently mined, kept through mempool outages
+    pub fn cached_block_template(&self) -> Option<&CachedBlockTemplate> {
+        self.work_cache.template.as_ref()
+    }
+
+    /// Nonce partition the current block is mined in
+    pub fn nonce_partition(&self) -> Option<NoncePartition> {
+        self.nonce_partition
+    }
+
+    /// Nonce of the PoW found for the current block, if found
+    pub fn found_block_pow_nonce(&self) -> Option<&[u8]> {
+        match self.mining_block_task.completed_result() {
+            Some(Ok(info)) => Some(&info.header.nonce_and_mining_tx_hash.0),
+            _ => None,
+        }
+    }
+
+    /// Whether a block PoW found during a mempool outage awaits submission
+    pub fn has_queued_block_pow(&self) -> bool {
+        self.work_cache.queued_pow.is_some()
+    }
+
     /// Sends PoW to a mempool node.
     ///
     /// ### Arguments
@@ -1372,6 +1814,11 @@ impl MinerNode {
             assets_won,
             b_num - 1
         );
+        self.miner_stats
+            .lock()
+            .unwrap()
+            .block_won(get_timestamp_now());
+        self.store_miner_stats().await;
 
         self.wallet_db
             .save_usable_payments_to_wallet(payments, b_num, false)
@@ -1391,47 +1838,50 @@ impl MinerNode {
         .await;
     }
 
-    /// Checks and aggregates all the winnings into a single address if the number of addresses stored
-    /// breaches the set threshold `MAX_NO_OF_WINNINGS_HELD`
+    /// Checks and aggregates the spendable winnings into a fresh address if the number of
+    /// spendable outputs held reaches the `address_aggregation_limit` threshold.
+    /// Locked coinbase outputs are left out, and at most `address_aggregation_max_inputs`
+    /// outputs are merged by one aggregation transaction.
     async fn check_for_threshold_and_send_aggregation_tx(&mut self, b_num: u64) {
+        if !self.enable_address_aggregation {
+            return;
+        }
+
         let address_aggregation_limit = self.address_aggregation_limit.unwrap_or(INTERNAL_TX_LIMIT);
+        let max_inputs = self
+            .address_aggregation_max_inputs
+            .unwrap_or(INTERNAL_TX_LIMIT)
+            .max(2);
 
         match self.aggregation_status.clone() {
             AggregationStatus::Idle => {
                 trace!(
-                    "Checking if we are holding more than {address_aggregation_limit:?} addresses to trigger aggregation tx"
+                    "Checking if we are holding more than {address_aggregation_limit:?} spendable outputs to trigger aggregation tx"
                 );
 
-                // All last known addresses
-                let known_addresses = self.wallet_db.get_known_addresses();
+                // All outputs spendable at this block
+                let spendable = self.wallet_db.get_spendable_outpoints();
 
-                // Check if we have a reached the threshold of addresses stored
-                if known_addresses.len() >= address_aggregation_limit {
+                // Check if we have a reached the threshold of outputs stored
+                if spendable.len() >= address_aggregation_limit {
                     trace!("Winnings aggregation triggered");
 
-                    // Slice known addresses up to address_aggregation_limit
-                    let addresses_to_aggregate = known_addresses
-                        .iter()
-                        .take(address_aggregation_limit)
-                        .cloned()
-                        .collect::<BTreeSet<_>>();
+                    // Slice spendable outputs up to max_inputs
+                    let txs_to_aggregate = spendable.into_iter().take(max_inputs).collect();
 
                     // Fetch the aggregating transaction inputs and outputs
-                    let (tx_ins, tx_outs) = self
+                    let (tx_ins, asset) = self
                         .wallet_db
-                        .fetch_tx_ins_and_tx_outs_merge_input_addrs(addresses_to_aggregate, None)
+                        .fetch_tx_ins_and_tx_outs_from_supplied_txs(txs_to_aggregate)
                         .await
                         .unwrap();
+                    let aggregating_addr = self.wallet_db.generate_payment_address().0;
+                    let tx_outs = vec![TxOut::new_asset(aggregating_addr.clone(), asset, None)];
 
                     // Sign the inputs
                     let key_material = self.wallet_db.get_key_material(&tx_ins);
                     let tx_ins = update_input_signatures(&tx_ins, &tx_outs, &key_material);
 
-                    // Aggregation address is last generated address,
-                    // which is generated by passing `None` as the `excess_address`
-                    // to `fetch_tx_ins_and_tx_outs_merge_input_addrs`
-                    let aggregating_addr = self.wallet_db.get_last_generated_address().unwrap(); // Should panic if `None`
-
                     trace!(
                         "Aggregating {:?} assets to {:?}",
                         tx_ins.len(),
@@ -1459,9 +1909,7 @@ impl MinerNode {
                         return;
                     }
 
-                    // After aggregation, our wallets will hold only 2 addresses: one for the holding all the winnings
-                    // and the other for the excess amount(which will be `0` theoretically).
-
+                    // After aggregation, the merged outputs are held by a single fresh address.
                     // TODO: Should we update the wallet DB here, or only once we've got confirmation
                     // from mempool node through received UTXO set?
                     self.wallet_db
@@ -1503,26 +1951,42 @@ impl MinerNode {
     /// TODO: Update the numbers used for reward and block time
     /// * `peer`      - Peer to send PoW to
     /// * `new_block` - Block for PoW
+    /// * `partition` - Nonce partition to mine the block in
     pub async fn start_generate_pow_for_current_block(
         &mut self,
         peer: SocketAddr,
         new_block: BlockPoWReceived,
+        partition: Option<NoncePartition>,
     ) {
         let b_num = new_block.block.b_num;
         let current_payment_address = self.current_payment_address.clone().unwrap();
+        self.work_cache.template = Some(CachedBlockTemplate {
+            template_id: construct_block_template_id(&new_block.block),
+            difficulty: new_block.block.difficulty.clone(),
+            b_num,
+        });
 
         let mining_tx = construct_coinbase_tx(b_num, new_block.reward, current_payment_address);
         let mining_tx_hash = construct_tx_hash(&mining_tx);
 
+        self.cancel_block_mining();
+        self.mining_block_cancel = Default::default();
+        self.nonce_partition = partition;
         self.mining_block_task = {
             let header = apply_mining_tx(new_block.block.clone(), Vec::new(), mining_tx_hash);
             let start_time = SystemTime::now();
-            RunningTaskOrResult::Running(Self::generate_pow_for_block(BlockPoWInfo {
-                peer,
-                start_time,
-                header,
-                coinbase: mining_tx,
-            }))
+            RunningTaskOrResult::Running(Self::generate_pow_for_block(
+                BlockPoWInfo {
+                    peer,
+                    start_time,
+                    header,
+                    coinbase: mining_tx,
+                },
+                partition,
+                self.pow_threads,
+                self.mining_block_cancel.clone(),
+                self.mining_hash_count.clone(),
+            ))
         };
         let mut current_block = self.current_block.lock().await;
         *current_block = Some(new_block);
@@ -1533,14 +1997,66 @@ impl MinerNode {
     ///
     /// ### Arguments
     ///
-    /// * `info`      - Block Proof of work info
-    fn generate_pow_for_block(mut info: BlockPoWInfo) -> task::JoinHandle<BlockPoWInfo> {
+    /// * `info`       - Block Proof of work info
+    /// * `partition`  - Nonce partition to search, or random nonces if None
+    /// * `threads`    - Number of threads searching nonces
+    /// * `cancel`     - Token set to abort the search
+    /// * `hash_count` - Count of hashes attempted, read for mining statistics
+    fn generate_pow_for_block(
+        mut info: BlockPoWInfo,
+        partition: Option<NoncePartition>,
+        threads: usize,
+        cancel: CancellationToken,
+        hash_count: Arc<AtomicU64>,
+    ) -> task::JoinHandle<BlockPoWInfo> {
         task::spawn_blocking(move || {
-            info.header = generate_pow_for_block(info.header);
+            // A cancelled task's handle has already been replaced, so its result is never read
+            let header = info.header.clone();
+            if let Some(header) =
+                generate_pow_for_block_threaded(header, partition, threads, &cancel, &hash_count)
+            {
+                info.header = header;
+            }
             info
         })
     }
 
+    /// Fold the hashes attempted since the last update into the mining statistics
+    async fn update_miner_stats(&mut self) {
+        let hashes = self.mining_hash_count.swap(0, Ordering::Relaxed);
+        let update = MinerStatistics {
+            device: MINER_STATS_CPU_DEVICE.to_owned(),
+            hashes,
+        };
+        self.miner_stats
+            .lock()
+            .unwrap()
+            .record(update, Instant::now());
+
+        if hashes > 0 {
+            self.store_miner_stats().await;
+        }
+    }
+
+    /// Persist the mining statistics counters so restarts do not zero them
+    async fn store_miner_stats(&self) {
+        let counters = self.miner_stats.lock().unwrap().counters().clone();
+        let ser_counters = serialize(&counters).unwrap();
+        self.wallet_db
+            .set_db_value(MINER_STATS_KEY, ser_counters)
+            .await;
+    }
+
+    /// Get the mining statistics shared with the API
+    pub fn miner_stats(&self) -> MinerStatsHandle {
+        self.miner_stats.clone()
+    }
+
+    /// Abort the in-progress block PoW search, if any
+    fn cancel_block_mining(&self) {
+        self.mining_block_cancel.store(true, Ordering::Relaxed);
+    }
+
     /// Generates a valid Partition PoW
     ///
     /// ### Arguments
@@ -1612,6 +2128,11 @@ impl MinerNode {
                 error!("load_local_db: send UtxoRequest {:?}", e);
             }
         }
+        if let Some(counters) = load_miner_stats(&self.wallet_db).await? {
+            debug!("load_local_db: miner_stats {:?}", counters);
+            self.miner_stats.lock().unwrap().restore_counters(counters);
+        }
+
         self.current_coinbase = if let Some(cb) = load_last_coinbase(&self.wallet_db).await? {
             debug!("load_local_db: current_coinbase {:?}", cb);
             Some(cb)
@@ -1677,12 +2198,15 @@ impl Transactor for MinerNode {
         transactions: Vec<Transaction>,
     ) -> Result<()> {
         let _peer_span = info_span!("sending transactions to mempool node for processing");
-        self.node
-            .send(
-                mempool_peer,
-                MempoolRequest::SendTransactions { transactions },
-            )
-            .await?;
+        let max_frame_size = default_max_frame_size(NodeType::Mempool);
+        for transactions in split_into_frame_batches(transactions, max_frame_size) {
+            self.node
+                .send(
+                    mempool_peer,
+                    MempoolRequest::SendTransactions { transactions },
+                )
+                .await?;
+        }
 
         Ok(())
     }
@@ -1776,6 +2300,15 @@ async fn store_last_coinbase(
     coinbase
 }
 
+/// Load mining statistics counters from wallet
+async fn load_miner_stats(wallet_db: &WalletDb) -> Result<Option<MinerStatsCounters>> {
+    Ok(wallet_db
+        .get_db_value(MINER_STATS_KEY)
+        .await
+        .map(|v| deserialize(&v))
+        .transpose()?)
+}
+
 /// Log the received blockchain item
 fn log_received_blockchain_item(_key: &str, item: &BlockchainItem, _peer: &SocketAddr) {
     use DeserializedBlockchainItem::*;
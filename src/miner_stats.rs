@@ -0,0 +1,301 @@
+use crate::constants::{
+    MINER_STATS_AVERAGE_WINDOW_SECS, MINER_STATS_INSTANT_WINDOW_SECS,
+    MINER_STATS_MAX_WIN_TIMESTAMPS,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared mining statistics between the miner node and its API
+pub type MinerStatsHandle = Arc<Mutex<MinerStats>>;
+
+/// Hashes computed by a mining device since its previous update
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MinerStatistics {
+    pub device: String,
+    pub hashes: u64,
+}
+
+/// Cumulative counters persisted so restarts do not zero them
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinerStatsCounters {
+    pub total_hashes: u64,
+    pub device_hashes: BTreeMap<String, u64>,
+    pub blocks_won: u64,
+    /// Most recent block win timestamps, oldest first
+    pub win_timestamps: Vec<i64>,
+    pub rejected_submissions: u64,
+}
+
+/// Summary of the mining statistics of a miner node
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MinerStatsReport {
+    #[serde(flatten)]
+    pub counters: MinerStatsCounters,
+    /// Hashes per second over the instantaneous window
+    pub hash_rate: f64,
+    pub hash_rate_window_secs: u64,
+    /// Hashes per second over the average window
+    pub average_hash_rate: f64,
+    pub average_hash_rate_window_secs: u64,
+}
+
+/// Cumulative mining statistics with the recent hash updates used for hash rates
+#[derive(Debug)]
+pub struct MinerStats {
+    counters: MinerStatsCounters,
+    /// Hashes of each update with the time it was recorded
+    samples: VecDeque<(Instant, u64)>,
+    started_at: Instant,
+}
+
+impl MinerStats {
+    /// Create empty statistics with hash rates measured from `now`
+    ///
+    /// ### Arguments
+    ///
+    /// * `now` - Time hash rate measurement starts
+    pub fn new(now: Instant) -> Self {
+        Self {
+            counters: Default::default(),
+            samples: Default::default(),
+            started_at: now,
+        }
+    }
+
+    /// Create new empty statistics shared with the API
+    pub fn new_handle() -> MinerStatsHandle {
+        Arc::new(Mutex::new(Self::new(Instant::now())))
+    }
+
+    /// Restore the counters persisted before a restart
+    ///
+    /// ### Arguments
+    ///
+    /// * `counters` - Persisted counters
+    pub fn restore_counters(&mut self, counters: MinerStatsCounters) {
+        self.counters = counters;
+    }
+
+    /// Get the cumulative counters
+    pub fn counters(&self) -> &MinerStatsCounters {
+        &self.counters
+    }
+
+    /// Record the hashes computed by a device since its previous update
+    ///
+    /// ### Arguments
+    ///
+    /// * `update` - Hashes computed and the device computing them
+    /// * `now`    - Time the update is recorded
+    pub fn record(&mut self, update: MinerStatistics, now: Instant) {
+        self.expire_samples(now);
+        if update.hashes == 0 {
+            return;
+        }
+
+        let counters = &mut self.counters;
+        counters.total_hashes = counters.total_hashes.saturating_add(update.hashes);
+        let device_hashes = counters.device_hashes.entry(update.device).or_default();
+        *device_hashes = device_hashes.saturating_add(update.hashes);
+        self.samples.push_back((now, update.hashes));
+    }
+
+    /// Record a block won by one of our PoW
+    ///
+    /// ### Arguments
+    ///
+    /// * `timestamp` - Time the win was observed
+    pub fn block_won(&mut self, timestamp: i64) {
+        self.counters.blocks_won += 1;
+        self.counters.win_timestamps.push(timestamp);
+        let excess = self
+            .counters
+            .win_timestamps
+            .len()
+            .saturating_sub(MINER_STATS_MAX_WIN_TIMESTAMPS);
+        self.counters.win_timestamps.drain(..excess);
+    }
+
+    /// Record a PoW submission rejected as no longer current
+    pub fn submission_rejected(&mut self) {
+        self.counters.rejected_submissions += 1;
+    }
+
+    /// Hashes per second over the given window.
+    /// The window is shortened to the time since measurement started,
+    /// and is zero rather than NaN when no time has elapsed.
+    ///
+    /// ### Arguments
+    ///
+    /// * `window` - Window the rate is computed over
+    /// * `now`    - Time the rate is computed at
+    pub fn hash_rate(&self, window: Duration, now: Instant) -> f64 {
+        let span = now.saturating_duration_since(self.started_at).min(window);
+        if span.is_zero() {
+            return 0.0;
+        }
+
+        let hashes: u64 = self
+            .samples
+            .iter()
+            .filter(|(at, _)| now.saturating_duration_since(*at) < window)
+            .map(|(_, hashes)| hashes)
+            .sum();
+        hashes as f64 / span.as_secs_f64()
+    }
+
+    /// Summarize the current mining statistics
+    ///
+    /// ### Arguments
+    ///
+    /// * `now` - Time the report is generated
+    pub fn report(&self, now: Instant) -> MinerStatsReport {
+        let instant_window = Duration::from_secs(MINER_STATS_INSTANT_WINDOW_SECS);
+        let average_window = Duration::from_secs(MINER_STATS_AVERAGE_WINDOW_SECS);
+
+        MinerStatsReport {
+            counters: self.counters.clone(),
+            hash_rate: self.hash_rate(instant_window, now),
+            hash_rate_window_secs: MINER_STATS_INSTANT_WINDOW_SECS,
+            average_hash_rate: self.hash_rate(average_window, now),
+            average_hash_rate_window_secs: MINER_STATS_AVERAGE_WINDOW_SECS,
+        }
+    }
+
+    /// Drop updates older than the longest hash rate window
+    fn expire_samples(&mut self, now: Instant) {
+        let window = Duration::from_secs(MINER_STATS_AVERAGE_WINDOW_SECS);
+        while let Some((at, _)) = self.samples.front() {
+            if now.saturating_duration_since(*at) < window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cpu_update(hashes: u64) -> MinerStatistics {
+        MinerStatistics {
+            device: "cpu".to_owned(),
+            hashes,
+        }
+    }
+
+    #[test]
+    fn miner_stats_steady_hash_rate() {
+        //
+        // Arrange
+        //
+        let start = Instant::now();
+        let mut stats = MinerStats::new(start);
+
+        //
+        // Act
+        //
+        let at_start = stats.report(start);
+        for secs in 1..=20 {
+            stats.record(cpu_update(1_000), start + Duration::from_secs(secs));
+        }
+        let report = stats.report(start + Duration::from_secs(20));
+
+        //
+        // Assert
+        //
+        assert_eq!(at_start.hash_rate, 0.0);
+        assert_eq!(at_start.average_hash_rate, 0.0);
+        assert_eq!(report.counters.total_hashes, 20_000);
+        assert_eq!(report.hash_rate, 1_000.0);
+        assert_eq!(report.average_hash_rate, 1_000.0);
+    }
+
+    #[test]
+    fn miner_stats_idle_hash_rate_decays_to_zero() {
+        //
+        // Arrange
+        //
+        let start = Instant::now();
+        let mut stats = MinerStats::new(start);
+        for secs in 1..=20 {
+            stats.record(cpu_update(1_000), start + Duration::from_secs(secs));
+        }
+
+        //
+        // Act
+        //
+        let last = start + Duration::from_secs(20);
+        let short_idle = stats.report(last + Duration::from_secs(MINER_STATS_INSTANT_WINDOW_SECS));
+        stats.record(cpu_update(0), last + Duration::from_secs(700));
+        let long_idle = stats.report(last + Duration::from_secs(700));
+        stats.record(cpu_update(500), last + Duration::from_secs(701));
+        let resumed = stats.report(last + Duration::from_secs(701));
+
+        //
+        // Assert
+        //
+        assert_eq!(short_idle.hash_rate, 0.0);
+        assert_eq!(short_idle.average_hash_rate, 20_000.0 / 30.0);
+        assert_eq!(long_idle.hash_rate, 0.0);
+        assert_eq!(long_idle.average_hash_rate, 0.0);
+        assert_eq!(long_idle.counters.total_hashes, 20_000);
+        assert_eq!(resumed.hash_rate, 50.0);
+        assert_eq!(resumed.average_hash_rate, 500.0 / 600.0);
+        assert_eq!(stats.samples.len(), 1);
+    }
+
+    #[test]
+    fn miner_stats_counters_per_device_and_restored() {
+        //
+        // Arrange
+        //
+        let start = Instant::now();
+        let mut stats = MinerStats::new(start);
+        let gpu_update = MinerStatistics {
+            device: "gpu".to_owned(),
+            hashes: 300,
+        };
+
+        //
+        // Act
+        //
+        stats.record(cpu_update(100), start + Duration::from_secs(1));
+        stats.record(gpu_update, start + Duration::from_secs(1));
+        stats.record(cpu_update(200), start + Duration::from_secs(2));
+        stats.submission_rejected();
+        for timestamp in 0..MINER_STATS_MAX_WIN_TIMESTAMPS as i64 + 2 {
+            stats.block_won(timestamp);
+        }
+
+        let mut restored = MinerStats::new(start + Duration::from_secs(3));
+        restored.restore_counters(stats.counters().clone());
+        let restored_report = restored.report(start + Duration::from_secs(3));
+
+        //
+        // Assert
+        //
+        let counters = stats.counters();
+        let expected_device_hashes: BTreeMap<String, u64> =
+            vec![("cpu".to_owned(), 300), ("gpu".to_owned(), 300)]
+                .into_iter()
+                .collect();
+        assert_eq!(counters.total_hashes, 600);
+        assert_eq!(counters.device_hashes, expected_device_hashes);
+        assert_eq!(counters.rejected_submissions, 1);
+        assert_eq!(
+            counters.blocks_won,
+            MINER_STATS_MAX_WIN_TIMESTAMPS as u64 + 2
+        );
+        assert_eq!(
+            counters.win_timestamps.len(),
+            MINER_STATS_MAX_WIN_TIMESTAMPS
+        );
+        assert_eq!(counters.win_timestamps.first(), Some(&2));
+        assert_eq!(&restored_report.counters, counters);
+        assert_eq!(restored_report.hash_rate, 0.0);
+    }
+}
@@ -0,0 +1,466 @@
+use crate::interfaces::NoncePartition;
+use crate::utils::{
+    apply_mining_tx, construct_coinbase_tx, is_valid_payment_address, nonce_partition_counter,
+    split_range_into_blocks, validate_pow_block,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+use tw_chain::primitives::asset::TokenAmount;
+use tw_chain::primitives::block::BlockHeader;
+use tw_chain::primitives::transaction::Transaction;
+use tw_chain::utils::transaction_utils::construct_tx_hash;
+
+/// Identifier of a single pool miner connection
+pub type PoolMinerId = u64;
+
+/// Shared pool mining state between the mempool node and its API
+pub type PoolMiningHandle = Arc<Mutex<PoolMining>>;
+
+/// Messages sent by a pool miner over its connection
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PoolMiningRequest {
+    Subscribe { address: String },
+    Submit { b_num: u64, nonce: Vec<u8> },
+}
+
+/// Messages sent to a pool miner over its connection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PoolMiningMessage {
+    Subscribed { miner_id: PoolMinerId },
+    Job(PoolJob),
+    SolutionSubmitted { b_num: u64 },
+    Error(PoolMiningError),
+}
+
+/// Block to mine for a pool miner: the header already commits to the coinbase
+/// paying the miner, only the nonce within the partition remains to be found
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PoolJob {
+    pub b_num: u64,
+    pub header: BlockHeader,
+    pub partition: NoncePartition,
+}
+
+/// Structured error returned to a pool miner
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code")]
+pub enum PoolMiningError {
+    AlreadySubscribed,
+    NotSubscribed,
+    NoJob,
+    StaleJob { b_num: u64 },
+    InvalidAddress,
+    NonceOutOfRange,
+    InvalidPoW,
+    InvalidRequest { message: String },
+    UnknownMiner,
+}
+
+impl fmt::Display for PoolMiningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadySubscribed => write!(f, "Already subscribed"),
+            Self::NotSubscribed => write!(f, "Not subscribed"),
+            Self::NoJob => write!(f, "No block currently mined"),
+            Self::StaleJob { b_num } => write!(f, "Not block currently mined: {b_num}"),
+            Self::InvalidAddress => write!(f, "Invalid coinbase address"),
+            Self::NonceOutOfRange => write!(f, "Nonce outside of the assigned range"),
+            Self::InvalidPoW => write!(f, "Invalid PoW for block"),
+            Self::InvalidRequest { message } => write!(f, "Invalid request: {message}"),
+            Self::UnknownMiner => write!(f, "Unknown miner"),
+        }
+    }
+}
+
+impl std::error::Error for PoolMiningError {}
+
+/// Block PoW found by a pool miner, for the mempool node to propose
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PoolSolution {
+    pub b_num: u64,
+    pub nonce: Vec<u8>,
+    pub coinbase: Transaction,
+}
+
+/// A single pool miner connection
+#[derive(Debug)]
+struct PoolMiner {
+    /// Coinbase address credited for the blocks it mines, once subscribed
+    address: Option<String>,
+    /// Current job with the coinbase its header commits to
+    /// and the nonce counters of its partition
+    job: Option<(PoolJob, Transaction, Range<u64>)>,
+    tx: mpsc::UnboundedSender<PoolMiningMessage>,
+}
+
+/// Block currently offered to pool miners
+#[derive(Debug)]
+struct BlockTemplate {
+    header: BlockHeader,
+    reward: TokenAmount,
+}
+
+/// Shares the block being mined between the subscribed pool miners,
+/// each searching its own partition of the nonce space.
+///
+/// Partitions are reassigned whenever a miner subscribes or disconnects,
+/// so the whole nonce space stays covered.
+#[derive(Debug, Default)]
+pub struct PoolMining {
+    /// Next identifier to give to a miner
+    next_id: PoolMinerId,
+    /// Connected miners
+    miners: BTreeMap<PoolMinerId, PoolMiner>,
+    /// Block currently mined, if any
+    template: Option<BlockTemplate>,
+}
+
+impl PoolMining {
+    /// Create a new shareable handle
+    pub fn new_handle() -> PoolMiningHandle {
+        Arc::new(Mutex::new(Default::default()))
+    }
+
+    /// Register a new miner connection, not yet subscribed
+    pub fn connect(&mut self) -> (PoolMinerId, mpsc::UnboundedReceiver<PoolMiningMessage>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let miner = PoolMiner {
+            address: None,
+            job: None,
+            tx,
+        };
+        self.miners.insert(id, miner);
+        debug!("Pool miner {} connected", id);
+        (id, rx)
+    }
+
+    /// Remove a miner connection, sharing its partition between the remaining miners
+    ///
+    /// ### Arguments
+    ///
+    /// * `id` - The miner to remove
+    pub fn disconnect(&mut self, id: PoolMinerId) {
+        if let Some(miner) = self.miners.remove(&id) {
+            debug!("Pool miner {} disconnected", id);
+            if miner.address.is_some() {
+                self.assign_jobs();
+            }
+        }
+    }
+
+    /// Subscribe a miner with the address its coinbase pays to
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`      - The miner
+    /// * `address` - Coinbase address of the miner
+    pub fn subscribe(&mut self, id: PoolMinerId, address: String) -> Result<(), PoolMiningError> {
+        let miner = self
+            .miners
+            .get_mut(&id)
+            .ok_or(PoolMiningError::UnknownMiner)?;
+        if miner.address.is_some() {
+            return Err(PoolMiningError::AlreadySubscribed);
+        }
+        if !is_valid_payment_address(&address) {
+            return Err(PoolMiningError::InvalidAddress);
+        }
+
+        miner.address = Some(address);
+        let _ = miner
+            .tx
+            .send(PoolMiningMessage::Subscribed { miner_id: id });
+        self.assign_jobs();
+        Ok(())
+    }
+
+    /// Check a solution submitted by a miner against the partition and difficulty of its job
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`    - The miner
+    /// * `b_num` - Block number of the job the solution is for
+    /// * `nonce` - Nonce found by the miner
+    pub fn submit(
+        &self,
+        id: PoolMinerId,
+        b_num: u64,
+        nonce: Vec<u8>,
+    ) -> Result<PoolSolution, PoolMiningError> {
+        let miner = self.miners.get(&id).ok_or(PoolMiningError::UnknownMiner)?;
+        if miner.address.is_none() {
+            return Err(PoolMiningError::NotSubscribed);
+        }
+
+        let (job, coinbase, nonces) = miner.job.as_ref().ok_or(PoolMiningError::NoJob)?;
+        if job.b_num != b_num {
+            return Err(PoolMiningError::StaleJob { b_num });
+        }
+        if !nonce_partition_counter(&nonce).map_or(false, |counter| nonces.contains(&counter)) {
+            return Err(PoolMiningError::NonceOutOfRange);
+        }
+
+        let mut header = job.header.clone();
+        header.nonce_and_mining_tx_hash.0 = nonce.clone();
+        if !validate_pow_block(&header) {
+            return Err(PoolMiningError::InvalidPoW);
+        }
+
+        debug!("Pool miner {} found PoW for block {}", id, b_num);
+        Ok(PoolSolution {
+            b_num,
+            nonce,
+            coinbase: coinbase.clone(),
+        })
+    }
+
+    /// Handle a request received from a miner connection, returning the messages
+    /// to send back and any valid solution to propose.
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`      - The miner
+    /// * `request` - The received request
+    pub fn handle_request(
+        &mut self,
+        id: PoolMinerId,
+        request: PoolMiningRequest,
+    ) -> (Vec<PoolMiningMessage>, Option<PoolSolution>) {
+        use PoolMiningRequest::*;
+
+        let result = match request {
+            Subscribe { address } => self.subscribe(id, address).map(|_| (Vec::new(), None)),
+            Submit { b_num, nonce } => self.submit(id, b_num, nonce).map(|solution| {
+                let msg = PoolMiningMessage::SolutionSubmitted { b_num };
+                (vec![msg], Some(solution))
+            }),
+        };
+
+        result.unwrap_or_else(|e| (vec![PoolMiningMessage::Error(e)], None))
+    }
+
+    /// Offer a new block to the subscribed miners
+    ///
+    /// ### Arguments
+    ///
+    /// * `header` - Header of the block to mine
+    /// * `reward` - Coinbase amount for the block
+    pub fn set_block_template(&mut self, header: BlockHeader, reward: TokenAmount) {
+        self.template = Some(BlockTemplate { header, reward });
+        self.assign_jobs();
+    }
+
+    /// Stop offering the block once mined
+    pub fn clear_block_template(&mut self) {
+        self.template = None;
+        for miner in self.miners.values_mut() {
+            miner.job = None;
+        }
+    }
+
+    /// Number of subscribed miners
+    pub fn get_subscribed_count(&self) -> usize {
+        self.miners.values().filter(|m| m.address.is_some()).count()
+    }
+
+    /// Give each subscribed miner a job for the current block with its own partition,
+    /// dropping the miners whose connection is gone
+    fn assign_jobs(&mut self) {
+        let template = match &self.template {
+            Some(template) => template,
+            None => return,
+        };
+        let b_num = template.header.b_num;
+
+        let mut dropped = Vec::new();
+        let subscribed: Vec<_> = (self.miners.iter_mut())
+            .filter(|(_, miner)| miner.address.is_some())
+            .collect();
+        let count = subscribed.len() as u64;
+        let ranges = split_range_into_blocks(0, u64::MAX, count);
+        for ((index, (id, miner)), nonces) in subscribed.into_iter().enumerate().zip(ranges) {
+            let address = miner.address.clone().unwrap_or_default();
+            let coinbase = construct_coinbase_tx(b_num, template.reward, address);
+            let coinbase_hash = construct_tx_hash(&coinbase);
+            let header = apply_mining_tx(template.header.clone(), Vec::new(), coinbase_hash);
+            let partition = NoncePartition {
+                index: index as u64,
+                count,
+            };
+
+            let job = PoolJob {
+                b_num,
+                header,
+                partition,
+            };
+            if miner.tx.send(PoolMiningMessage::Job(job.clone())).is_err() {
+                dropped.push(*id);
+            }
+            miner.job = Some((job, coinbase, nonces));
+        }
+        trace!("Pool jobs assigned for block {} to {} miners", b_num, count);
+
+        if !dropped.is_empty() {
+            for id in &dropped {
+                self.miners.remove(id);
+            }
+            self.assign_jobs();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::generate_pow_for_block_partitioned;
+    use std::sync::atomic::AtomicU64;
+
+    const ADDRESS_A: &str = "5423e6bd848e0ce5cd794e55235c23138d8833633cd2d7de7f4a10935178457b";
+    const ADDRESS_B: &str = "13bd3351b78beb2d0dadf2058dcc926c";
+    const ADDRESS_C: &str = "fc0aa2394edb0d2df918325d4682c21eacecf173265db7c04623d2a921f3876c";
+
+    #[test]
+    fn disconnected_miner_partition_reassigned() {
+        //
+        // Arrange
+        //
+        let mut pool = PoolMining::default();
+        let (id_1, mut rx_1) = pool.connect();
+        let (id_2, mut rx_2) = pool.connect();
+        let (id_3, rx_3) = pool.connect();
+        pool.subscribe(id_1, ADDRESS_A.to_owned()).unwrap();
+        pool.subscribe(id_2, ADDRESS_B.to_owned()).unwrap();
+        pool.subscribe(id_3, ADDRESS_C.to_owned()).unwrap();
+
+        //
+        // Act
+        //
+        pool.set_block_template(header_for(1), TokenAmount(10));
+        let before = (last_job(&mut rx_1), last_job(&mut rx_2));
+        drop(rx_3);
+        pool.disconnect(id_3);
+        let after = (last_job(&mut rx_1), last_job(&mut rx_2));
+
+        //
+        // Assert
+        //
+        let partitions = |jobs: (Option<PoolJob>, Option<PoolJob>)| {
+            (jobs.0.map(|j| j.partition), jobs.1.map(|j| j.partition))
+        };
+        assert_eq!(
+            partitions(before),
+            (
+                Some(NoncePartition { index: 0, count: 3 }),
+                Some(NoncePartition { index: 1, count: 3 })
+            )
+        );
+        assert_eq!(
+            partitions(after),
+            (
+                Some(NoncePartition { index: 0, count: 2 }),
+                Some(NoncePartition { index: 1, count: 2 })
+            )
+        );
+        assert_eq!(pool.get_subscribed_count(), 2);
+    }
+
+    #[test]
+    fn submitted_solution_checked_against_job() {
+        //
+        // Arrange
+        //
+        let mut pool = PoolMining::default();
+        let (id, mut rx) = pool.connect();
+        let (other_id, _other_rx) = pool.connect();
+        pool.subscribe(id, ADDRESS_A.to_owned()).unwrap();
+        pool.subscribe(other_id, ADDRESS_B.to_owned()).unwrap();
+        pool.set_block_template(header_for(1), TokenAmount(10));
+        let job = last_job(&mut rx).unwrap();
+        let found = generate_pow_for_block_partitioned(
+            job.header.clone(),
+            Some(job.partition),
+            &Default::default(),
+            &AtomicU64::new(0),
+        )
+        .unwrap();
+        let nonce = found.nonce_and_mining_tx_hash.0;
+        let invalid_nonce = (0u64..)
+            .map(|n| n.to_be_bytes().to_vec())
+            .find(|n| pool.submit(id, 1, n.clone()).is_err())
+            .unwrap();
+
+        //
+        // Act
+        //
+        let stale = pool.submit(id, 2, nonce.clone()).err();
+        let invalid = pool.submit(id, 1, invalid_nonce).err();
+        let other_partition = pool.submit(other_id, 1, nonce.clone()).err();
+        let valid = pool.submit(id, 1, nonce.clone()).unwrap();
+        pool.clear_block_template();
+        let cleared = pool.submit(id, 1, nonce.clone()).err();
+
+        //
+        // Assert
+        //
+        assert_eq!(stale, Some(PoolMiningError::StaleJob { b_num: 2 }));
+        assert_eq!(invalid, Some(PoolMiningError::InvalidPoW));
+        assert_eq!(other_partition, Some(PoolMiningError::NonceOutOfRange));
+        assert_eq!(cleared, Some(PoolMiningError::NoJob));
+        assert_eq!((valid.b_num, valid.nonce), (1, nonce));
+        assert_eq!(
+            valid.coinbase.outputs[0].script_public_key.as_deref(),
+            Some(ADDRESS_A)
+        );
+    }
+
+    #[test]
+    fn invalid_coinbase_address_rejected() {
+        //
+        // Arrange
+        //
+        let mut pool = PoolMining::default();
+        let (id, _rx) = pool.connect();
+
+        //
+        // Act
+        //
+        let short = pool.subscribe(id, "a".to_owned()).err();
+        let not_hex = pool.subscribe(id, ADDRESS_A.replace('5', "z")).err();
+        let valid = pool.subscribe(id, ADDRESS_A.to_owned());
+
+        //
+        // Assert
+        //
+        assert_eq!(short, Some(PoolMiningError::InvalidAddress));
+        assert_eq!(not_hex, Some(PoolMiningError::InvalidAddress));
+        assert_eq!(valid, Ok(()));
+        assert_eq!(pool.get_subscribed_count(), 1);
+    }
+
+    fn header_for(b_num: u64) -> BlockHeader {
+        BlockHeader {
+            b_num,
+            ..Default::default()
+        }
+    }
+
+    fn last_job(rx: &mut mpsc::UnboundedReceiver<PoolMiningMessage>) -> Option<PoolJob> {
+        let mut job = None;
+        while let Ok(msg) = rx.try_recv() {
+            if let PoolMiningMessage::Job(j) = msg {
+                job = Some(j);
+            }
+        }
+        job
+    }
+}
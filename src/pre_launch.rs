@@ -154,6 +154,9 @@ impl PreLaunchNode {
             false,
         )
         .await?;
+        if let Some(max_frame_size) = config.max_frame_size {
+            node.set_max_frame_size(max_frame_size);
+        }
         let db = {
             let spec = &config.db_spec;
             db_utils::new_db(config.pre_launch_db_mode, spec, extra.db.take(), None)
@@ -287,6 +290,12 @@ impl PreLaunchNode {
                     return ResponseResult::Exit;
                 }
             }
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Frame size limit exceeded" => {
+                warn!("Disconnected peer sending frames over the size limit");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -384,6 +393,23 @@ impl PreLaunchNode {
                     .instrument(peer_span)
                     .await
             }
+            Event::FrameSizeLimitExceeded { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Frame size limit exceeded".to_owned(),
+            })),
+            Event::PeerReconnected { peer } => {
+                if let Err(err) = self.send_startup_requests().await {
+                    error!("Failed to send startup requests on reconnect to {peer:?}: {err}");
+                }
+                Ok(Some(Response {
+                    success: true,
+                    reason: "Peer reconnected".to_owned(),
+                }))
+            }
+            Event::PeerUnreachable { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Peer unreachable".to_owned(),
+            })),
         }
     }
 
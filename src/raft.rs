@@ -3,6 +3,8 @@ use crate::raft_store::{self, RaftStore};
 use crate::utils::MpscTracingSender;
 use raft::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::{timeout_at, Instant};
@@ -61,6 +63,7 @@ pub struct RaftNodeChannels {
     pub msg_out_rx: RaftMsgReceiver,
     pub cmd_tx: RaftCmdSender,
     pub committed_rx: CommitReceiver,
+    pub leader_id: Arc<AtomicU64>,
 }
 
 /// Fields necessary for launching a Raft loop.
@@ -77,6 +80,8 @@ pub struct RaftConfig {
     msg_out_tx: RaftMsgSender,
     /// Tick timeout duration.
     tick_timeout_duration: Duration,
+    /// Last known leader id, shared for status reporting.
+    leader_id: Arc<AtomicU64>,
 }
 
 /// Wrapper for raft Messages enabling Serialize/Deserialize
@@ -141,6 +146,8 @@ pub struct RaftNode {
     total_tick_count: usize,
     /// Last snapshot index, and whether it need compacting.
     previous_snapshot_idx: (u64, bool),
+    /// Last known leader id, shared for status reporting.
+    leader_id: Arc<AtomicU64>,
     // Context already waiting for committing
 }
 
@@ -178,6 +185,7 @@ impl RaftNode {
             incoming_msgs_count: 0,
             total_tick_count: 0,
             previous_snapshot_idx: (0, false),
+            leader_id: raft_config.leader_id,
         }
     }
 
@@ -195,6 +203,7 @@ impl RaftNode {
         let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
         let (committed_tx, committed_rx) = mpsc::channel(100);
         let (msg_out_tx, msg_out_rx) = mpsc::channel(100);
+        let leader_id = Arc::new(AtomicU64::new(raft::INVALID_ID));
 
         (
             RaftConfig {
@@ -204,11 +213,13 @@ impl RaftNode {
                 msg_out_tx: msg_out_tx.into(),
                 tick_timeout_duration,
                 raft_db,
+                leader_id: leader_id.clone(),
             },
             RaftNodeChannels {
                 msg_out_rx,
                 cmd_tx,
                 committed_rx,
+                leader_id,
             },
         )
     }
@@ -242,6 +253,39 @@ impl RaftNode {
         self.node.get_store().backup_persistent()
     }
 
+    /// Snapshot loaded from persistent storage on start up, if any
+    pub fn loaded_snapshot(&self) -> Option<RaftCommit> {
+        let snapshot = self.node.get_store().snapshot().ok()?;
+        if snapshot.get_data().is_empty() {
+            return None;
+        }
+
+        Some(RaftCommit {
+            term: snapshot.get_metadata().term,
+            index: snapshot.get_metadata().index,
+            data: RaftCommitData::Snapshot(snapshot.data.clone()),
+        })
+    }
+
+    /// Persist a snapshot without running the raft loop
+    ///
+    /// ### Arguments
+    ///
+    /// * `idx`    - Index of the last commit applied to the data
+    /// * `data`   - The data to snapshot
+    /// * `backup` - Whether or not to backup the DB
+    pub fn persist_snapshot(&mut self, idx: u64, data: RaftData, backup: bool) {
+        let store = self.node.mut_store();
+        if let Err(e) = store.persist_snapshot(idx, data) {
+            error!("Persist snapshot error: {:?}", e);
+        }
+        if backup {
+            if let Err(e) = store.backup_persistent() {
+                error!("Backup error: {:?}", e);
+            }
+        }
+    }
+
     /// Async RAFT loop processing inputs and populating output channels.
     async fn next_event(&mut self) -> Option<()> {
         match timeout_at(self.tick_timeout_at, self.cmd_rx.recv()).await {
@@ -315,6 +359,8 @@ impl RaftNode {
         }
 
         self.process_ready().await;
+        self.leader_id
+            .store(self.node.raft.leader_id, Ordering::Relaxed);
         Some(())
     }
 
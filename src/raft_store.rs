@@ -111,6 +111,23 @@ impl RaftStore {
         Ok(())
     }
 
+    /// Store snapshot in persistent storage only: used when raft is bypassed
+    /// and no log entries are kept.
+    pub fn persist_snapshot(&mut self, idx: u64, data: Vec<u8>) -> RaftResult<()> {
+        let mut meta = SnapshotMetadata::new();
+        meta.index = idx;
+
+        let mut snapshot = Snapshot::new();
+        snapshot.set_data(data);
+        snapshot.set_metadata(meta);
+
+        let mut batch = self.presistent.batch_writer();
+        set_persistent_snapshot(&mut batch, &snapshot)?;
+        let batch = batch.done();
+        batch_write(&mut self.presistent, batch)?;
+        Ok(())
+    }
+
     /// Discards all log entries prior to compact_index.
     /// Only apply to in memory storage: persistent storage always match the last snapshot
     pub fn compact(&mut self, compact_index: u64) -> RaftResult<()> {
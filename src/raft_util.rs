@@ -1,11 +1,13 @@
 use crate::active_raft::ActiveRaft;
+use crate::canonical::{canonical_hash, CanonicalEncode};
 use crate::raft::RaftData;
 use bincode::{deserialize, serialize};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
+use std::time::Duration;
+use tokio::time::Instant;
 use tracing::{debug, warn};
-use tw_chain::crypto::sha3_256;
 
 /// Key serialized into RaftData and process by Raft.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -17,8 +19,8 @@ pub struct RaftContextKey {
 
 #[derive(Clone, Default)]
 pub struct RaftInFlightProposals {
-    /// Proposed items in flight.
-    proposed_in_flight: BTreeMap<RaftContextKey, (RaftData, RaftData)>,
+    /// Proposed items in flight, with the time they were proposed.
+    proposed_in_flight: BTreeMap<RaftContextKey, (RaftData, RaftData, Instant)>,
     /// Proposal block num associated with key
     proposed_keys_b_num: BTreeMap<RaftContextKey, u64>,
     /// The last id of a proposed item.
@@ -37,6 +39,16 @@ impl RaftInFlightProposals {
         self.proposed_key_run = Some(key_run);
     }
 
+    /// Number of proposals not yet committed
+    pub fn len(&self) -> usize {
+        self.proposed_in_flight.len()
+    }
+
+    /// Whether all proposals have been committed
+    pub fn is_empty(&self) -> bool {
+        self.proposed_in_flight.is_empty()
+    }
+
     /// Checks a commit of the RaftData for validity
     /// Return commited proposal
     ///
@@ -76,34 +88,28 @@ impl RaftInFlightProposals {
     ///  * `raft_active` - The raft instance to propose to.
     ///  * `item`        - The item to be proposed to a raft.
     ///  * `dedup_b_num` - The block number to use for de-deduplication.
-    pub async fn propose_item<Item: Serialize + Debug>(
+    pub async fn propose_item<Item: Serialize + CanonicalEncode + Debug>(
         &mut self,
         raft_active: &mut ActiveRaft,
         item: &Item,
         dedup_b_num: Option<u64>,
     ) -> Option<RaftContextKey> {
-        let data = serialize(item).unwrap();
         let dedup_info = check_deduplication(
             &self.already_proposed_hashes,
             self.min_b_num,
-            &data,
+            item,
             dedup_b_num,
         )?;
 
-        let key = {
-            self.proposed_last_id += 1;
-            RaftContextKey {
-                proposer_id: raft_active.peer_id(),
-                proposer_run: self.proposed_key_run.unwrap(),
-                proposal_id: self.proposed_last_id,
-            }
-        };
+        let data = serialize(item).unwrap();
+
+        let key = self.next_key(raft_active);
         let context = serialize(&key).unwrap();
 
         debug!("propose_item: {:?} -> {:?}", key, item);
 
         self.proposed_in_flight
-            .insert(key, (data.clone(), context.clone()));
+            .insert(key, (data.clone(), context.clone(), Instant::now()));
         if let Some((item_hash, b_num)) = dedup_info {
             self.already_proposed_hashes.insert(item_hash, (key, b_num));
             self.proposed_keys_b_num.insert(key, b_num);
@@ -113,6 +119,16 @@ impl RaftInFlightProposals {
         Some(key)
     }
 
+    /// Allocate the key for a new proposal.
+    fn next_key(&mut self, raft_active: &ActiveRaft) -> RaftContextKey {
+        self.proposed_last_id += 1;
+        RaftContextKey {
+            proposer_id: raft_active.peer_id(),
+            proposer_run: self.proposed_key_run.unwrap(),
+            proposal_id: self.proposed_last_id,
+        }
+    }
+
     /// Remove all items with provided keys
     pub fn remove_all_keys(&mut self, keys: &BTreeSet<RaftContextKey>) {
         for key in keys.iter() {
@@ -129,13 +145,23 @@ impl RaftInFlightProposals {
     ///  * `raft_active` - The raft instance to propose to.
     ///  * `key`         - The item key to be proposed to a raft.
     pub async fn re_propose_item(&mut self, raft_active: &mut ActiveRaft, key: RaftContextKey) {
-        if let Some((data, context)) = self.proposed_in_flight.get(&key) {
+        if let Some((data, context, _)) = self.proposed_in_flight.get(&key) {
             raft_active
                 .propose_data(data.clone(), context.clone())
                 .await;
         }
     }
 
+    /// Stop tracking all items in flight, returning their data.
+    pub fn take_all_items(&mut self) -> Vec<RaftData> {
+        self.proposed_keys_b_num.clear();
+        self.already_proposed_hashes.clear();
+        std::mem::take(&mut self.proposed_in_flight)
+            .into_values()
+            .map(|(data, _, _)| data)
+            .collect()
+    }
+
     /// Re-Propose all items in flight to raft.
     ///
     /// ### Arguments
@@ -146,13 +172,58 @@ impl RaftInFlightProposals {
             "Re-propose all non committed items: {}",
             self.proposed_in_flight.len()
         );
-        for (data, context) in self.proposed_in_flight.values() {
+        for (data, context, _) in self.proposed_in_flight.values() {
             raft_active
                 .propose_data(data.clone(), context.clone())
                 .await;
         }
     }
 
+    /// Re-propose under a new key the items in flight for longer than `timeout`.
+    /// The replaced key stops being tracked, so a late commit for it is not ours anymore.
+    /// Return the replaced and new key of each re-proposed item.
+    ///
+    /// ### Arguments
+    ///
+    ///  * `raft_active`     - The raft instance to propose to.
+    ///  * `timeout`         - The time after which an item in flight is re-proposed.
+    ///  * `should_propose`  - Whether the given serialized item is to be re-proposed.
+    pub async fn re_propose_expired_items(
+        &mut self,
+        raft_active: &mut ActiveRaft,
+        timeout: Duration,
+        should_propose: impl Fn(&[u8]) -> bool,
+    ) -> Vec<(RaftContextKey, RaftContextKey)> {
+        let expired: Vec<RaftContextKey> = (self.proposed_in_flight.iter())
+            .filter(|(_, (data, _, at))| at.elapsed() >= timeout && should_propose(data.as_slice()))
+            .map(|(key, _)| *key)
+            .collect();
+
+        let mut re_proposed = Vec::with_capacity(expired.len());
+        for old_key in expired {
+            let (data, _, _) = self.proposed_in_flight.remove(&old_key).unwrap();
+            let key = self.next_key(raft_active);
+            let context = serialize(&key).unwrap();
+
+            debug!("re_propose_expired_items: {:?} -> {:?}", old_key, key);
+
+            if let Some(b_num) = self.proposed_keys_b_num.remove(&old_key) {
+                self.proposed_keys_b_num.insert(key, b_num);
+            }
+            for (proposed_key, _) in self.already_proposed_hashes.values_mut() {
+                if *proposed_key == old_key {
+                    *proposed_key = key;
+                }
+            }
+
+            self.proposed_in_flight
+                .insert(key, (data.clone(), context.clone(), Instant::now()));
+            raft_active.propose_data(data, context).await;
+            re_proposed.push((old_key, key));
+        }
+        re_proposed
+    }
+
     /// Re-propose uncommited items relevant for current block.
     ///
     /// ### Arguments
@@ -195,18 +266,18 @@ impl RaftInFlightProposals {
     }
 }
 
-/// Check if serialized item_data was already proposed
+/// Check if item was already proposed, comparing canonical hashes
 ///
 /// ### Arguments
 ///
 ///  * `already_proposed_hashes` - The already proposed hashes.
 ///  * `min_b_num`               - The minimum block number to accept.
-///  * `item_data`               - The item to be proposed to a raft.
+///  * `item`                    - The item to be proposed to a raft.
 ///  * `dedup_b_num`             - The block number to use for de-deduplication.
 fn check_deduplication(
     already_proposed_hashes: &BTreeMap<Vec<u8>, (RaftContextKey, u64)>,
     min_b_num: u64,
-    item_data: &[u8],
+    item: &impl CanonicalEncode,
     dedup_b_num: Option<u64>,
 ) -> Option<Option<(Vec<u8>, u64)>> {
     if let Some(b_num) = dedup_b_num {
@@ -215,7 +286,7 @@ fn check_deduplication(
             return None;
         }
 
-        let data_hash = sha3_256::digest(item_data).to_vec();
+        let data_hash = canonical_hash(item);
         if let Some((key, num)) = already_proposed_hashes.get(&data_hash) {
             debug!("check_deduplication found: key({:?}), b_num({})", key, num);
             None
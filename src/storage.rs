@@ -1,39 +1,61 @@
+use crate::active_raft::RaftInfo;
+use crate::api::utils::load_api_tls_files;
+use crate::chain_quality::{ChainQuality, ChainQualityHandle};
+use crate::comms_handler::peer_discovery::configure_peer_discovery;
 use crate::comms_handler::{CommsError, Event, Node, TcpTlsConfig};
-use crate::configurations::{ExtraNodeParams, StorageNodeConfig, TlsPrivateInfo};
+use crate::configurations::{ApiRateLimits, ExtraNodeParams, StorageNodeConfig, TlsPrivateInfo};
 use crate::constants::{
-    DB_PATH, INDEXED_BLOCK_HASH_PREFIX_KEY, INDEXED_TX_HASH_PREFIX_KEY, LAST_BLOCK_HASH_KEY,
-    NAMED_CONSTANT_PREPEND,
+    BLOCK_STREAM_CAPACITY, CATCHUP_BLOCK_RANGE_LIMIT, DB_PATH, INDEXED_BLOCK_HASH_PREFIX_KEY,
+    INDEXED_TX_HASH_PREFIX_KEY, LAST_BLOCK_HASH_KEY, NAMED_CONSTANT_PREPEND,
 };
 use crate::db_utils::{self, SimpleDb, SimpleDbError, SimpleDbSpec, SimpleDbWriteBatch};
 use crate::interfaces::{
-    BlockStoredInfo, BlockchainItem, BlockchainItemMeta, Contract, DruidTxInfo, MempoolRequest,
-    MineRequest, MinedBlock, NodeType, ProofOfWork, Response, StorageInterface, StorageRequest,
-    StoredSerializingBlock,
+    AddressTxEntry, BlockHeaderSummary, BlockStoredInfo, BlockchainItem, BlockchainItemMeta,
+    Contract, DruidTxInfo, MempoolRequest, MineRequest, MinedBlock, NodeType, ProofOfWork,
+    Response, StorageInterface, StorageRequest, StoredSerializingBlock,
 };
 use crate::raft::RaftCommit;
 use crate::storage_fetch::{FetchStatus, FetchedBlockChain, StorageFetch};
 use crate::storage_raft::{CommittedItem, CompleteBlock, StorageRaft};
 use crate::utils::{
-    construct_valid_block_pow_hash, create_socket_addr, get_genesis_tx_in_display, to_api_keys,
-    to_route_pow_infos, ApiKeys, LocalEvent, LocalEventChannel, LocalEventSender, ResponseResult,
-    RoutesPoWInfo,
+    construct_valid_block_pow_hash, create_socket_addr, get_genesis_tx_in_display,
+    shutdown_connections, to_api_keys, to_route_pow_infos, ApiKeys, LocalEvent, LocalEventChannel,
+    LocalEventSender, ResponseResult, RoutesPoWInfo,
+};
+use crate::utxo_subscription::{
+    utxo_events_for_block, UtxoEvent, UtxoSubscriptions, UtxoSubscriptionsHandle,
 };
 use bincode::{deserialize, serialize};
 use bytes::Bytes;
+use futures::future::join_all;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::str;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tracing::{debug, error, error_span, info, trace, warn};
 use tracing_futures::Instrument;
+use tw_chain::primitives::asset::TokenAmount;
+use tw_chain::primitives::block::Block;
+use tw_chain::primitives::transaction::{OutPoint, Transaction, TxOut};
+use tw_chain::utils::transaction_utils::{
+    get_fees_with_out_point_cloned, get_inputs_previous_out_point, get_tx_out_with_out_point_cloned,
+};
 
 /// Key storing current proposer run
 pub const RAFT_KEY_RUN: &str = "RaftKeyRun";
 pub const LAST_CONTIGUOUS_BLOCK_KEY: &str = "LastContiguousBlockKey";
+/// Key storing the number of the first block whose body was not pruned
+pub const PRUNED_TO_BLOCK_KEY: &str = "PrunedToBlockKey";
+/// Key present once the address history index covers all the stored blocks
+pub const ADDRESS_HISTORY_INDEXED_KEY: &str = "AddressHistoryIndexedKey";
 
 /// Database columns
 pub const DB_COL_INTERNAL: &str = "internal";
@@ -47,6 +69,9 @@ pub const DB_COL_BC_V0_5_0: &str = "block_chain_v0.5.0";
 pub const DB_COL_BC_V0_4_0: &str = "block_chain_v0.4.0";
 pub const DB_COL_BC_V0_3_0: &str = "block_chain_v0.3.0";
 pub const DB_COL_BC_V0_2_0: &str = "block_chain_v0.2.0";
+pub const DB_COL_UTXO_INDEX: &str = "utxo_index";
+pub const DB_COL_BLOCK_HEADERS: &str = "block_headers";
+pub const DB_COL_ADDRESS_HISTORY: &str = "address_history";
 
 /// Version columns
 pub const DB_COLS_BC: &[(&str, u32)] = &[
@@ -76,6 +101,9 @@ pub const DB_SPEC: SimpleDbSpec = SimpleDbSpec {
         DB_COL_BC_V0_4_0,
         DB_COL_BC_V0_3_0,
         DB_COL_BC_V0_2_0,
+        DB_COL_UTXO_INDEX,
+        DB_COL_BLOCK_HEADERS,
+        DB_COL_ADDRESS_HISTORY,
     ],
 };
 
@@ -130,6 +158,29 @@ impl From<bincode::Error> for StorageError {
     }
 }
 
+/// Broadcast of the JSON of each newly stored block to the API block stream
+pub type BlockStreamSender = broadcast::Sender<String>;
+
+/// Tokens created by the stored blockchain, shared with the API.
+/// Computed on demand and cleared whenever a block is stored.
+pub type StoredSupplyHandle = Arc<Mutex<Option<StoredSupply>>>;
+
+/// Tokens created by the stored blockchain up to its latest block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoredSupply {
+    /// Tokens created by the seed transactions of the genesis block
+    pub seed: TokenAmount,
+    /// Tokens issued by the blocks' mining transactions
+    pub issued: TokenAmount,
+}
+
+impl StoredSupply {
+    /// All the tokens created: seed and issued
+    pub fn total(&self) -> TokenAmount {
+        TokenAmount(self.seed.0 + self.issued.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct StorageNode {
     node: Node,
@@ -138,11 +189,23 @@ pub struct StorageNode {
     db: Arc<Mutex<SimpleDb>>,
     local_events: LocalEventChannel,
     mempool_addr: SocketAddr,
-    api_info: (SocketAddr, Option<TlsPrivateInfo>, ApiKeys, RoutesPoWInfo),
+    api_info: (
+        SocketAddr,
+        Option<TlsPrivateInfo>,
+        ApiKeys,
+        RoutesPoWInfo,
+        ApiRateLimits,
+    ),
     whitelisted: HashMap<SocketAddr, bool>,
     shutdown_group: BTreeSet<SocketAddr>,
     blockchain_item_fetched: Option<(String, BlockchainItem, SocketAddr)>,
+    block_range_fetched: Option<(Vec<BlockchainItem>, SocketAddr)>,
     activation_height_asert: u64,
+    utxo_subscriptions: UtxoSubscriptionsHandle,
+    chain_quality: ChainQualityHandle,
+    stored_supply: StoredSupplyHandle,
+    block_stream: BlockStreamSender,
+    prune_keep_last_blocks: Option<u64>,
 }
 
 impl StorageNode {
@@ -161,9 +224,10 @@ impl StorageNode {
             .await
             .map_err(|_| StorageError::ConfigError("Invalid storage address supplied"))?;
 
+        // Storage nodes outnumbering mempool nodes share them in turn
         let raw_mempool_addr = config
             .mempool_nodes
-            .get(config.storage_node_idx)
+            .get(config.storage_node_idx % config.mempool_nodes.len().max(1))
             .ok_or(StorageError::ConfigError("Invalid mempool index"))?;
         let mempool_addr = create_socket_addr(&raw_mempool_addr.address)
             .await
@@ -171,12 +235,24 @@ impl StorageNode {
 
         let tcp_tls_config = TcpTlsConfig::from_tls_spec(addr, &config.tls_config)?;
         let api_addr = SocketAddr::new(addr.ip(), config.storage_api_port);
-        let api_tls_info = config
-            .storage_api_use_tls
-            .then(|| tcp_tls_config.clone_private_info());
+        let api_tls_info = match (
+            &config.storage_api_tls_cert_path,
+            &config.storage_api_tls_key_path,
+        ) {
+            (Some(cert_path), Some(key_path)) => Some(
+                load_api_tls_files(cert_path, key_path)
+                    .map_err(|_| StorageError::ConfigError("Invalid API TLS files"))?,
+            ),
+            _ => config
+                .storage_api_use_tls
+                .then(|| tcp_tls_config.clone_private_info()),
+        };
         let api_keys = to_api_keys(config.api_keys.clone());
+        let api_rate_limit = config
+            .storage_api_rate_limit
+            .unwrap_or_else(ApiRateLimits::node_default);
 
-        let node = Node::new(
+        let mut node = Node::new(
             &tcp_tls_config,
             config.peer_limit,
             config.peer_limit,
@@ -185,6 +261,13 @@ impl StorageNode {
             false,
         )
         .await?;
+        if let Some(max_frame_size) = config.max_frame_size {
+            node.set_max_frame_size(max_frame_size);
+        }
+        if let Some(max_delay_ms) = config.peer_reconnect_max_delay_ms {
+            node.set_reconnect_max_delay(Duration::from_millis(max_delay_ms));
+        }
+        configure_peer_discovery(&mut node, config.peer_discovery.as_ref()).await?;
         let node_raft = StorageRaft::new(&config, extra.raft_db.take()).await;
         let catchup_fetch = StorageFetch::new(&config, addr).await;
         let api_pow_info = to_route_pow_infos(config.routes_pow.clone());
@@ -212,13 +295,25 @@ impl StorageNode {
             node_raft,
             catchup_fetch,
             db,
-            api_info: (api_addr, api_tls_info, api_keys, api_pow_info),
+            api_info: (
+                api_addr,
+                api_tls_info,
+                api_keys,
+                api_pow_info,
+                api_rate_limit,
+            ),
             local_events: Default::default(),
             mempool_addr,
             whitelisted: Default::default(),
             shutdown_group,
             blockchain_item_fetched: Default::default(),
+            block_range_fetched: Default::default(),
             activation_height_asert,
+            utxo_subscriptions: UtxoSubscriptions::new_handle(),
+            chain_quality: ChainQuality::new_handle(),
+            stored_supply: Default::default(),
+            block_stream: broadcast::channel(BLOCK_STREAM_CAPACITY).0,
+            prune_keep_last_blocks: config.prune_keep_last_blocks,
         }
         .load_local_db()
     }
@@ -242,9 +337,52 @@ impl StorageNode {
         Option<TlsPrivateInfo>,
         ApiKeys,
         RoutesPoWInfo,
+        ApiRateLimits,
     ) {
-        let (api_addr, api_tls, api_keys, api_pow_info) = self.api_info.clone();
-        (self.db.clone(), api_addr, api_tls, api_keys, api_pow_info)
+        let (api_addr, api_tls, api_keys, api_pow_info, api_rate_limit) = self.api_info.clone();
+        (
+            self.db.clone(),
+            api_addr,
+            api_tls,
+            api_keys,
+            api_pow_info,
+            api_rate_limit,
+        )
+    }
+
+    /// Returns the UTXO subscriptions shared with the API
+    pub fn utxo_subscriptions(&self) -> UtxoSubscriptionsHandle {
+        self.utxo_subscriptions.clone()
+    }
+
+    /// Returns the chain quality tracking shared with the API
+    pub fn chain_quality(&self) -> ChainQualityHandle {
+        self.chain_quality.clone()
+    }
+
+    /// Returns the stored supply cache shared with the API
+    pub fn stored_supply(&self) -> StoredSupplyHandle {
+        self.stored_supply.clone()
+    }
+
+    /// Returns the stored block broadcast shared with the API
+    pub fn block_stream(&self) -> BlockStreamSender {
+        self.block_stream.clone()
+    }
+
+    /// Broadcast the last stored block to the block stream subscribers, if any.
+    /// Slow subscribers lag behind rather than holding up storage.
+    fn publish_last_stored_block(&self) {
+        if self.block_stream.receiver_count() == 0 {
+            return;
+        }
+
+        let block = get_stored_value_from_db(self.db.clone(), LAST_BLOCK_HASH_KEY)
+            .and_then(|item| String::from_utf8(item.data_json).ok());
+        if let Some(block) = block {
+            // Only fails if all subscribers disconnected meanwhile
+            let _ = self.block_stream.send(block);
+        }
     }
 
     ///Adds a uses data as the payload to create a frame, from the peer address, in the node object of this class.
@@ -288,11 +426,27 @@ impl StorageNode {
         self.node_raft.raft_loop()
     }
 
+    /// Returns the raft membership and leadership shared with the API
+    pub fn raft_info(&self) -> RaftInfo {
+        self.node_raft.raft_info()
+    }
+
     /// Signal to the raft loop to complete
     pub async fn close_raft_loop(&mut self) {
         self.node_raft.close_raft_loop().await
     }
 
+    /// Stop accepting connections, flush pending db writes,
+    /// and notify connected peers of the disconnection
+    pub async fn shutdown(&mut self) {
+        join_all(self.node.stop_listening().await).await;
+        self.close_raft_loop().await;
+        if let Err(e) = self.db.lock().unwrap().flush() {
+            error!("Error flushing main db: {:?}", e);
+        }
+        shutdown_connections(&mut self.node).await;
+    }
+
     /// Extract persistent dbs
     pub async fn take_closed_extra_params(&mut self) -> ExtraNodeParams {
         let raft_db = self.node_raft.take_closed_persistent_store().await;
@@ -343,6 +497,14 @@ impl StorageNode {
                     error!("Blockchain item not sent {:?}", e);
                 }
             }
+            Ok(Response {
+                success: true,
+                reason,
+            }) if reason == "Block range fetched from storage" => {
+                if let Err(e) = self.send_block_range().await {
+                    error!("Block range not sent {:?}", e);
+                }
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -385,10 +547,16 @@ impl StorageNode {
                 success: true,
                 reason,
             }) if reason == "Catch up stored blocks" => {
-                if let Err(e) = self.catchup_fetch_blockchain_item().await {
+                if let Err(e) = self.catchup_fetch_block_range().await {
                     error!("Resend block stored failed {:?}", e);
                 }
             }
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Frame size limit exceeded" => {
+                warn!("Disconnected peer sending frames over the size limit");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -506,17 +674,48 @@ impl StorageNode {
         match self.node_raft.received_commit(commit_data).await {
             Some(CommittedItem::Block) => {
                 let block = self.node_raft.generate_complete_block();
-                let block_stored = {
+                let has_subscribers = self
+                    .utxo_subscriptions
+                    .lock()
+                    .unwrap()
+                    .has_subscribed_addresses();
+                let (block_stored, utxo_events) = {
                     let mut self_db = self.db.lock().unwrap();
 
                     let b_num = block.common.block.header.b_num;
+                    let utxo_events =
+                        has_subscribers.then(|| Self::complete_block_utxo_events(&self_db, &block));
                     let contiguous = self.catchup_fetch.check_contiguous_block_num(b_num);
+                    let previous_hash = get_indexed_block_hash(&self_db, b_num);
+                    let store_start = Instant::now();
                     let stored = Self::store_complete_block(&mut self_db, contiguous, block);
                     self.catchup_fetch.update_contiguous_block_num(contiguous);
                     self.catchup_fetch.increase_running_target(b_num);
+                    if let Some(keep_last) = self.prune_keep_last_blocks {
+                        prune_block_chain_db(&mut self_db, keep_last);
+                    }
 
-                    stored
+                    let superseded =
+                        previous_hash.is_some_and(|hash| hash != stored.block_hash.as_bytes());
+                    self.chain_quality.lock().unwrap().block_stored(
+                        b_num,
+                        superseded,
+                        store_start.elapsed(),
+                        Instant::now(),
+                    );
+                    *self.stored_supply.lock().unwrap() = None;
+
+                    (stored, utxo_events)
                 };
+                {
+                    let b_num = block_stored.block_num;
+                    let mut utxo_subscriptions = self.utxo_subscriptions.lock().unwrap();
+                    match utxo_events {
+                        Some(utxo_events) => utxo_subscriptions.publish_block(b_num, utxo_events),
+                        None => utxo_subscriptions.skip_block(b_num),
+                    }
+                }
+                self.publish_last_stored_block();
                 self.node_raft
                     .event_processed_generate_snapshot(block_stored);
                 self.backup_persistent_dbs().await;
@@ -561,6 +760,23 @@ impl StorageNode {
                     .instrument(peer_span)
                     .await
             }
+            Event::FrameSizeLimitExceeded { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Frame size limit exceeded".to_owned(),
+            })),
+            Event::PeerReconnected { peer } => {
+                if let Err(err) = self.send_startup_requests().await {
+                    error!("Failed to send startup requests on reconnect to {peer:?}: {err}");
+                }
+                Ok(Some(Response {
+                    success: true,
+                    reason: "Peer reconnected".to_owned(),
+                }))
+            }
+            Event::PeerUnreachable { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Peer unreachable".to_owned(),
+            })),
         }
     }
 
@@ -599,6 +815,8 @@ impl StorageNode {
         match req {
             GetBlockchainItem { key } => Some(self.get_blockchain_item(peer, key)),
             SendBlockchainItem { key, item } => Some(self.receive_blockchain_item(peer, key, item)),
+            GetBlockRange { start, end } => Some(self.get_block_range(peer, start, end)),
+            SendBlockRange { items } => Some(self.receive_block_range(peer, items)),
             GetHistory {
                 start_time,
                 end_time,
@@ -640,6 +858,37 @@ impl StorageNode {
         Ok(())
     }
 
+    /// Sends the latest block range fetched from storage.
+    pub async fn send_block_range(&mut self) -> Result<()> {
+        if let Some((items, peer)) = self.block_range_fetched.take() {
+            self.node
+                .send(peer, StorageRequest::SendBlockRange { items })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Get the UTXO creations and spends of a complete block before it is stored.
+    ///
+    /// ### Arguments
+    ///
+    /// * `self_db`  - Database with the previously stored blocks
+    /// * `complete` - CompleteBlock object about to be stored.
+    fn complete_block_utxo_events(self_db: &SimpleDb, complete: &CompleteBlock) -> Vec<UtxoEvent> {
+        let block = &complete.common.block;
+        let block_txs = &complete.common.block_txs;
+        let all_txs: Vec<_> = all_ordered_stored_block_tx_hashes(
+            &block.transactions,
+            std::iter::once(&block.header.nonce_and_mining_tx_hash),
+        )
+        .filter_map(|(_, tx_hash)| block_txs.get_key_value(tx_hash))
+        .collect();
+
+        utxo_events_for_block(block.header.b_num, all_txs.into_iter(), |out_point| {
+            get_stored_tx_out_from_db(self_db, out_point)
+        })
+    }
+
     ///Stores a completed block including transactions and mining transactions.
     ///
     /// ### Arguments
@@ -709,8 +958,6 @@ impl StorageNode {
             std::iter::once(&stored_block.block.header.nonce_and_mining_tx_hash),
         );
 
-        let mut druid_store: BTreeMap<String, Vec<String>> = BTreeMap::new();
-
         // Transaction store
         let mut tx_len = 0;
         for (tx_num, tx_hash) in all_txs {
@@ -719,16 +966,6 @@ impl StorageNode {
                 let tx_input = serialize(tx_value).unwrap();
                 let tx_json = serde_json::to_vec(tx_value).unwrap();
                 let t = BlockchainItemMeta::Tx { block_num, tx_num };
-
-                // Add to DRUID store if relevant
-                if let Some(druid_info) = &tx_value.druid_info {
-                    let druid = druid_info.druid.clone();
-                    druid_store
-                        .entry(druid)
-                        .and_modify(|v| v.push(tx_hash.clone()))
-                        .or_insert(vec![tx_hash.clone()]);
-                }
-
                 put_to_block_chain(&mut batch, &t, tx_hash, &tx_input, &tx_json);
             } else {
                 error!(
@@ -744,23 +981,24 @@ impl StorageNode {
             let pointer =
                 put_to_block_chain(&mut batch, &t, &block_hash, &block_input, &block_json);
             put_named_last_block_to_block_chain(&mut batch, &pointer);
+            put_block_header_summary(
+                &mut batch,
+                &block_header_summary(&block_hash, &stored_block.block),
+            );
 
             if FetchStatus::Contiguous(block_num) == status {
                 put_contiguous_block_num(&mut batch, block_num);
             }
         }
 
+        // UTXO index store
+        put_utxo_index_for_txs(&mut batch, &all_block_txs);
+
         // Druid store
-        for (druid, tx_hashes) in druid_store {
-            let druid_entry = DruidTxInfo { tx_hashes };
-            let druid_input = serialize(&druid_entry).unwrap();
-            let druid_json = serde_json::to_vec(&druid_entry).unwrap();
-            let t = BlockchainItemMeta::Tx {
-                block_num,
-                tx_num: 0,
-            };
-            put_to_block_chain(&mut batch, &t, &druid, &druid_input, &druid_json);
-        }
+        put_druid_index_for_txs(&mut batch, block_num, &all_block_txs);
+
+        // Address history store
+        put_address_history_for_txs(&mut batch, self_db, block_num, &all_block_txs);
 
         let batch = batch.done();
         self_db.write(batch).unwrap();
@@ -827,11 +1065,25 @@ impl StorageNode {
 
             if let BlockchainItemMeta::Block { block_num, .. } = &item.item_meta {
                 if block_num == &b_num {
+                    let block: StoredSerializingBlock = deserialize(&item.data)?;
+                    put_block_header_summary(&mut batch, &block_header_summary(key, &block.block));
                     block_pointer = Some(pointer);
                 }
             }
         }
 
+        let fetched_txs: BTreeMap<String, Transaction> = items
+            .iter()
+            .filter(|item| matches!(item.item_meta, BlockchainItemMeta::Tx { .. }))
+            .filter_map(|item| {
+                let key = String::from_utf8(item.key.clone()).ok()?;
+                Some((key, deserialize(&item.data).ok()?))
+            })
+            .collect();
+        put_utxo_index_for_txs(&mut batch, &fetched_txs);
+        put_druid_index_for_txs(&mut batch, b_num, &fetched_txs);
+        put_address_history_for_txs(&mut batch, self_db, b_num, &fetched_txs);
+
         if let Some(block_pointer) = block_pointer {
             if last_block_stored.block_num == b_num {
                 put_named_last_block_to_block_chain(&mut batch, &block_pointer);
@@ -848,10 +1100,38 @@ impl StorageNode {
         Ok(status)
     }
 
-    /// Sends a request to retrieve a blockchain item from storage
-    pub async fn catchup_fetch_blockchain_item(&mut self) -> Result<()> {
-        if let Some((peer, key)) = self.catchup_fetch.get_fetch_peer_and_key() {
-            let request = StorageRequest::GetBlockchainItem { key };
+    /// Check a fetched block follows the block stored before it, if any.
+    ///
+    /// ### Arguments
+    ///
+    /// * `self_db` - Database with the previously stored blocks
+    /// * `block`   - Block number and items of the fetched block
+    fn check_fetched_previous_hash(
+        self_db: &SimpleDb,
+        (b_num, items): &FetchedBlockChain,
+    ) -> Result<()> {
+        let previous = b_num.checked_sub(1);
+        let previous = match previous.and_then(|b_num| get_indexed_block_hash(self_db, b_num)) {
+            Some(previous) => previous,
+            None => return Ok(()),
+        };
+
+        let block = items
+            .iter()
+            .find(|item| matches!(item.item_meta, BlockchainItemMeta::Block { .. }))
+            .ok_or(StorageError::ConfigError("Block not specified"))?;
+        let block: StoredSerializingBlock = deserialize(&block.data)?;
+        match block.block.header.previous_hash {
+            Some(hash) if hash.as_bytes() == previous => Ok(()),
+            _ => Err(StorageError::ConfigError("Block previous hash mismatch")),
+        }
+    }
+
+    /// Sends a request to retrieve the next range of missing blocks from storage
+    pub async fn catchup_fetch_block_range(&mut self) -> Result<()> {
+        let limit = CATCHUP_BLOCK_RANGE_LIMIT;
+        if let Some((peer, (start, end))) = self.catchup_fetch.get_fetch_peer_and_range(limit) {
+            let request = StorageRequest::GetBlockRange { start, end };
             self.node.send(peer, request).await?;
         } else {
             error!("No peer to catchup from");
@@ -860,6 +1140,62 @@ impl StorageNode {
         Ok(())
     }
 
+    /// Store a block fetched from a storage peer during catch up
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`  - The storage peer the block was fetched from.
+    /// * `block` - Block number and items of the fetched block.
+    fn store_catchup_block(&mut self, peer: SocketAddr, block: FetchedBlockChain) -> Result<u64> {
+        let mut self_db = self.db.lock().unwrap();
+        let b_num = block.0;
+        let block_key = block.1.iter().find_map(|item| match item.item_meta {
+            BlockchainItemMeta::Block { block_num, .. } if block_num == b_num => {
+                Some(item.key.clone())
+            }
+            _ => None,
+        });
+        let previous_hash = get_indexed_block_hash(&self_db, b_num);
+        let store_start = Instant::now();
+
+        let result = match self.node_raft.get_last_block_stored() {
+            Some(last_stored) if last_stored.block_num >= b_num => {
+                Self::check_fetched_previous_hash(&self_db, &block).and_then(|()| {
+                    let contiguous = self.catchup_fetch.check_contiguous_block_num(b_num);
+                    Self::store_fetched_complete_block(&mut self_db, last_stored, contiguous, block)
+                })
+            }
+            _ => Err(StorageError::ConfigError(
+                "Expect only block less than block stored",
+            )),
+        };
+
+        match result {
+            Ok(status) => {
+                self.catchup_fetch.update_contiguous_block_num(status);
+                self.chain_quality.lock().unwrap().block_stored(
+                    b_num,
+                    previous_hash.is_some() && previous_hash != block_key,
+                    store_start.elapsed(),
+                    Instant::now(),
+                );
+                *self.stored_supply.lock().unwrap() = None;
+                Ok(b_num)
+            }
+            Err(e) => {
+                error!(
+                    "Catch up block {} from {} could not be stored: {:?}",
+                    b_num, peer, e
+                );
+                self.chain_quality
+                    .lock()
+                    .unwrap()
+                    .block_rejected("Catchup block not stored", Instant::now());
+                Err(e)
+            }
+        }
+    }
+
     /// Gets a value from the stored blockchain via key
     ///
     /// ### Arguments
@@ -966,14 +1302,50 @@ impl StorageNode {
             return None;
         };
 
-        if let Err(e) = construct_valid_block_pow_hash(&common.block) {
-            debug!("Block received not added. PoW invalid: {}", e);
+        // Only the configured mempool nodes contribute to the block majority
+        if self.node_raft.mempool_peer_id(&peer).is_none() {
+            debug!(
+                "Block received not added. Not from a mempool node: {:?}",
+                peer
+            );
             return Some(Response {
                 success: false,
-                reason: "Block received not added. PoW invalid".to_string(),
+                reason: "Block received not added. Not from a mempool node".to_string(),
             });
         }
 
+        let block_hash = match construct_valid_block_pow_hash(&common.block) {
+            Ok(block_hash) => block_hash,
+            Err(e) => {
+                debug!("Block received not added. PoW invalid: {}", e);
+                self.chain_quality
+                    .lock()
+                    .unwrap()
+                    .block_rejected("PoW invalid", Instant::now());
+                return Some(Response {
+                    success: false,
+                    reason: "Block received not added. PoW invalid".to_string(),
+                });
+            }
+        };
+
+        // Acknowledge every valid receipt so the mempool node stops re-sending the block
+        let b_num = common.block.header.b_num;
+        let ack = MempoolRequest::SendBlockAck {
+            b_num,
+            block_hash: block_hash.clone(),
+        };
+        if let Err(e) = self.node.send(peer, ack).await {
+            debug!("Block {} acknowledgement not sent {:?}", b_num, e);
+        }
+
+        let already_stored = self.get_last_block_stored().as_ref();
+        if already_stored.map_or(false, |stored| stored.block_hash == block_hash) {
+            debug!("Block {} received again: already stored", b_num);
+            self.resend_trigger_message().await;
+            return None;
+        }
+
         if !self
             .node_raft
             .propose_received_part_block(peer, common, extra_info)
@@ -1015,6 +1387,33 @@ impl StorageNode {
             }
         });
 
+        {
+            let mut db = self.db.lock().unwrap();
+            let backfilled = backfill_block_header_summaries(&mut db);
+            if backfilled > 0 {
+                info!(
+                    "load_local_db: backfilled {} block header summaries",
+                    backfilled
+                );
+            }
+
+            let backfilled = backfill_address_history(&mut db);
+            if backfilled > 0 {
+                info!(
+                    "load_local_db: backfilled address history of {} blocks",
+                    backfilled
+                );
+            }
+
+            let block_nums = db
+                .iter_cf_clone(DB_COL_BC_NAMED)
+                .filter_map(|(key, _)| decode_indexed_block_num(&key));
+            self.chain_quality
+                .lock()
+                .unwrap()
+                .load_stored_blocks(block_nums);
+        }
+
         Ok(self)
     }
 
@@ -1022,6 +1421,19 @@ impl StorageNode {
     pub fn get_node(&self) -> &Node {
         &self.node
     }
+
+    /// Delete the bodies of the blocks before the last `keep_last` ones,
+    /// keeping their headers, mining transactions and transactions with unspent outputs.
+    /// Returns the number of blocks pruned.
+    ///
+    /// ### Arguments
+    ///
+    /// * `keep_last` - Number of most recent blocks kept whole
+    pub fn prune_to_height(&mut self, keep_last: u64) -> usize {
+        let pruned = prune_block_chain_db(&mut self.db.lock().unwrap(), keep_last);
+        *self.stored_supply.lock().unwrap() = None;
+        pruned
+    }
 }
 
 impl StorageInterface for StorageNode {
@@ -1044,22 +1456,8 @@ impl StorageInterface for StorageNode {
         let is_complete = self.catchup_fetch.is_complete();
 
         if let Some(block) = to_store {
-            let mut self_db = self.db.lock().unwrap();
-            let b_num = block.0;
-
-            let result = match self.node_raft.get_last_block_stored() {
-                Some(last_stored) if last_stored.block_num >= b_num => {
-                    let contiguous = self.catchup_fetch.check_contiguous_block_num(b_num);
-                    Self::store_fetched_complete_block(&mut self_db, last_stored, contiguous, block)
-                }
-                _ => Err(StorageError::ConfigError(
-                    "Expect only block less than block stored",
-                )),
-            };
-
-            match result {
-                Ok(status) => {
-                    self.catchup_fetch.update_contiguous_block_num(status);
+            match self.store_catchup_block(peer, block) {
+                Ok(b_num) => {
                     self.catchup_fetch.set_first_timeout();
                     let reason = if is_complete {
                         "Blockchain item received: Block stored(Done)"
@@ -1073,16 +1471,10 @@ impl StorageInterface for StorageNode {
                         reason: reason.to_string(),
                     }
                 }
-                Err(e) => {
-                    error!(
-                        "receive_blockchain_item from {} could not process block: {:?}",
-                        peer, e
-                    );
-                    Response {
-                        success: false,
-                        reason: "Blockchain item received: Block failed".to_string(),
-                    }
-                }
+                Err(_) => Response {
+                    success: false,
+                    reason: "Blockchain item received: Block failed".to_string(),
+                },
             }
         } else {
             if !is_complete {
@@ -1096,6 +1488,89 @@ impl StorageInterface for StorageNode {
         }
     }
 
+    fn get_block_range(&mut self, peer: SocketAddr, start: u64, end: u64) -> Response {
+        let end = std::cmp::min(end, start.saturating_add(CATCHUP_BLOCK_RANGE_LIMIT - 1));
+        let mut items = Vec::new();
+
+        'blocks: for b_num in start..=end {
+            let block = match self.get_stored_value(indexed_block_hash_key(b_num)) {
+                Some(block) => block,
+                None => break,
+            };
+            let tx_len = match block.item_meta {
+                BlockchainItemMeta::Block { tx_len, .. } => tx_len,
+                BlockchainItemMeta::Tx { .. } => break,
+            };
+
+            let mut block_items = vec![block];
+            for tx_num in 0..tx_len {
+                match self.get_stored_value(indexed_tx_hash_key(b_num, tx_num)) {
+                    Some(tx) => block_items.push(tx),
+                    None => break 'blocks,
+                }
+            }
+            items.append(&mut block_items);
+        }
+
+        self.block_range_fetched = Some((items, peer));
+        Response {
+            success: true,
+            reason: "Block range fetched from storage".to_string(),
+        }
+    }
+
+    fn receive_block_range(&mut self, peer: SocketAddr, items: Vec<BlockchainItem>) -> Response {
+        if items.is_empty() {
+            // Range not available from this peer
+            self.catchup_fetch.change_to_next_fetch_peer();
+            self.catchup_fetch.set_first_timeout();
+            return Response {
+                success: false,
+                reason: "Block range received: Not found".to_string(),
+            };
+        }
+
+        let mut last_stored = None;
+        for item in items {
+            let key = String::from_utf8_lossy(&item.key).into_owned();
+            if let Some(block) = self.catchup_fetch.receive_blockchain_items(key, item) {
+                let b_num = block.0;
+                match self.store_catchup_block(peer, block) {
+                    Ok(b_num) => last_stored = Some(b_num),
+                    Err(_) => {
+                        self.catchup_fetch.refetch_block_from_next_peer(b_num);
+                        self.catchup_fetch.set_first_timeout();
+                        return Response {
+                            success: false,
+                            reason: "Block range received: Block failed".to_string(),
+                        };
+                    }
+                }
+            }
+
+            if self.catchup_fetch.is_complete() {
+                break;
+            }
+        }
+
+        let is_complete = self.catchup_fetch.is_complete();
+        if !is_complete {
+            self.catchup_fetch.set_first_timeout();
+        }
+
+        let reason = match (last_stored, is_complete) {
+            (Some(_), true) => "Block range received: Block stored(Done)",
+            (Some(_), false) => "Block range received: Block stored",
+            (None, _) => "Block range received",
+        };
+
+        info!("{}(b_num = {:?})", reason, last_stored);
+        Response {
+            success: true,
+            reason: reason.to_string(),
+        }
+    }
+
     fn get_history(&self, _start_time: &u64, _end_time: &u64) -> Response {
         Response {
             success: false,
@@ -1190,6 +1665,109 @@ pub fn put_to_block_chain_at<K: AsRef<[u8]>, V: AsRef<[u8]>>(
     pointer
 }
 
+/// Remove an item from the block chain columns, without its named index
+///
+/// ### Arguments
+///
+/// * `batch`   - Database writer
+/// * `pointer` - The version pointer of the item
+pub fn delete_from_block_chain(batch: &mut SimpleDbWriteBatch, pointer: &[u8]) {
+    let (_, cf, key) = decode_version_pointer(pointer);
+    batch.delete_cf(cf, key);
+    batch.delete_cf(DB_COL_BC_JSON, key);
+    batch.delete_cf(DB_COL_BC_ALL, key);
+    batch.delete_cf(DB_COL_BC_META, key);
+}
+
+/// Delete the bodies of the blocks before the last `keep_last` ones, resuming after the
+/// blocks already pruned. The genesis block is kept whole for its seed transactions.
+/// Pruned blocks keep their header summary and named index, their mining transaction,
+/// and their transactions with outputs still in the UTXO index.
+/// Returns the number of blocks pruned.
+///
+/// ### Arguments
+///
+/// * `db`        - Database to prune
+/// * `keep_last` - Number of most recent blocks kept whole
+pub fn prune_block_chain_db(db: &mut SimpleDb, keep_last: u64) -> usize {
+    let last_block =
+        get_stored_named_data_from_db::<StoredSerializingBlock>(db, LAST_BLOCK_HASH_KEY);
+    let last_b_num = match last_block {
+        Some(last_block) => last_block.block.header.b_num,
+        None => return 0,
+    };
+    let start = match db.get_cf(DB_COL_INTERNAL, PRUNED_TO_BLOCK_KEY) {
+        Ok(Some(b_num)) => deserialize::<u64>(&b_num).unwrap_or(1),
+        _ => 1,
+    };
+    let end = (last_b_num + 1).saturating_sub(keep_last.max(1));
+    if start >= end {
+        return 0;
+    }
+
+    let mut batch = db.batch_writer();
+    let mut pruned = 0;
+    for b_num in start..end {
+        let pointer = ok_or_warn(
+            db.get_cf(DB_COL_BC_NAMED, indexed_block_hash_key(b_num)),
+            "prune_block_chain block",
+        );
+        let Some(pointer) = pointer else {
+            continue;
+        };
+        let Some(stored) = get_pointed_data_from_db::<StoredSerializingBlock>(db, &pointer) else {
+            continue;
+        };
+
+        let mining_tx_hash = &stored.block.header.nonce_and_mining_tx_hash;
+        let all_txs = all_ordered_stored_block_tx_hashes(
+            &stored.block.transactions,
+            std::iter::once(mining_tx_hash),
+        );
+        for (tx_num, tx_hash) in all_txs {
+            let tx_pointer = ok_or_warn(db.get_cf(DB_COL_BC_ALL, tx_hash), "prune_block_chain tx");
+            let Some(tx_pointer) = tx_pointer else {
+                continue;
+            };
+            let is_coinbase = tx_hash == &mining_tx_hash.1
+                || get_pointed_data_from_db::<Transaction>(db, &tx_pointer)
+                    .map_or(true, |tx| tx.is_coinbase());
+            if is_coinbase || has_unspent_outputs(db, tx_hash) {
+                continue;
+            }
+
+            delete_from_block_chain(&mut batch, &tx_pointer);
+            batch.delete_cf(DB_COL_BC_NAMED, indexed_tx_hash_key(b_num, tx_num));
+        }
+
+        delete_from_block_chain(&mut batch, &pointer);
+        pruned += 1;
+    }
+    batch.put_cf(
+        DB_COL_INTERNAL,
+        PRUNED_TO_BLOCK_KEY,
+        serialize(&end).unwrap(),
+    );
+
+    let batch = batch.done();
+    db.write(batch).unwrap();
+    info!("Pruned {} block bodies before block {}", pruned, end);
+    pruned
+}
+
+/// Whether the transaction has outputs in the UTXO index
+///
+/// ### Arguments
+///
+/// * `db`      - Database to read from
+/// * `tx_hash` - Hash of the transaction
+fn has_unspent_outputs(db: &SimpleDb, tx_hash: &str) -> bool {
+    let prefix = format!("{tx_hash}-");
+    db.iter_cf_clone_from(DB_COL_UTXO_INDEX, prefix.as_bytes())
+        .next()
+        .is_some_and(|(key, _)| key.starts_with(prefix.as_bytes()))
+}
+
 /// Add to the last block chain named column
 ///
 /// ### Arguments
@@ -1206,6 +1784,334 @@ pub fn put_contiguous_block_num(batch: &mut SimpleDbWriteBatch, block_num: u64)
     batch.put_cf(DB_COL_INTERNAL, LAST_CONTIGUOUS_BLOCK_KEY, &last_num);
 }
 
+/// Header level summary of a stored block
+///
+/// ### Arguments
+///
+/// * `hash`  - Hash the block is stored under
+/// * `block` - The stored block
+pub fn block_header_summary(hash: &str, block: &Block) -> BlockHeaderSummary {
+    let mining_tx_hash = &block.header.nonce_and_mining_tx_hash.1;
+    BlockHeaderSummary {
+        b_num: block.header.b_num,
+        hash: hash.to_owned(),
+        previous_hash: block.header.previous_hash.clone(),
+        timestamp: block.header.timestamp,
+        tx_count: block.transactions.len(),
+        mining_tx_count: usize::from(!mining_tx_hash.is_empty()),
+    }
+}
+
+/// Add the header summary of a block, indexed by block number
+///
+/// ### Arguments
+///
+/// * `batch`   - Database writer
+/// * `summary` - Header summary of the block being stored
+pub fn put_block_header_summary(batch: &mut SimpleDbWriteBatch, summary: &BlockHeaderSummary) {
+    let summary_ser = serialize(summary).unwrap();
+    batch.put_cf(
+        DB_COL_BLOCK_HEADERS,
+        indexed_block_hash_key(summary.b_num),
+        &summary_ser,
+    );
+}
+
+/// Store the header summary of the stored blocks missing one,
+/// such as blocks stored before summaries were kept.
+/// Returns the number of summaries added.
+///
+/// ### Arguments
+///
+/// * `db` - Database to update
+pub fn backfill_block_header_summaries(db: &mut SimpleDb) -> usize {
+    let missing: Vec<(Vec<u8>, Vec<u8>)> = db
+        .iter_cf_clone(DB_COL_BC_NAMED)
+        .filter(|(key, _)| decode_indexed_block_num(key).is_some())
+        .filter(|(key, _)| matches!(db.get_cf(DB_COL_BLOCK_HEADERS, key), Ok(None)))
+        .collect();
+
+    let mut batch = db.batch_writer();
+    let mut backfilled = 0;
+    for (_, pointer) in missing {
+        let (_, _, hash) = decode_version_pointer(&pointer);
+        let hash = String::from_utf8_lossy(hash).into_owned();
+        if let Some(stored) = get_pointed_data_from_db::<StoredSerializingBlock>(db, &pointer) {
+            put_block_header_summary(&mut batch, &block_header_summary(&hash, &stored.block));
+            backfilled += 1;
+        }
+    }
+
+    let batch = batch.done();
+    db.write(batch).unwrap();
+    backfilled
+}
+
+/// Get the header summaries of the blocks in an inclusive range of numbers,
+/// None for the numbers without stored block
+///
+/// ### Arguments
+///
+/// * `db`    - Database to read from
+/// * `start` - Number of the first block
+/// * `end`   - Number of the last block
+pub fn get_block_header_summaries_from_db(
+    db: &SimpleDb,
+    start: u64,
+    end: u64,
+) -> Vec<Option<BlockHeaderSummary>> {
+    (start..=end)
+        .map(|b_num| {
+            let key = indexed_block_hash_key(b_num);
+            let summary = ok_or_warn(db.get_cf(DB_COL_BLOCK_HEADERS, key), "get_block_header")?;
+            ok_or_warn(
+                deserialize::<BlockHeaderSummary>(&summary).map(Some),
+                "get_block_header ser",
+            )?
+        })
+        .collect()
+}
+
+/// Key of an out point in the UTXO index, ordered by transaction hash then index
+///
+/// ### Arguments
+///
+/// * `out_point` - The out point to index
+pub fn utxo_index_key(out_point: &OutPoint) -> String {
+    format!("{}-{:010}", out_point.t_hash, out_point.n)
+}
+
+/// Add the outputs created by the transactions to the UTXO index and remove the spent ones
+///
+/// ### Arguments
+///
+/// * `batch` - Database writer
+/// * `txs`   - The transactions being stored
+pub fn put_utxo_index_for_txs(batch: &mut SimpleDbWriteBatch, txs: &BTreeMap<String, Transaction>) {
+    let created = get_tx_out_with_out_point_cloned(txs.iter())
+        .chain(get_fees_with_out_point_cloned(txs.iter()));
+    for (out_point, _) in created {
+        let out_point_ser = serialize(&out_point).unwrap();
+        batch.put_cf(
+            DB_COL_UTXO_INDEX,
+            utxo_index_key(&out_point),
+            &out_point_ser,
+        );
+    }
+
+    for out_point in get_inputs_previous_out_point(txs.values()) {
+        batch.delete_cf(DB_COL_UTXO_INDEX, utxo_index_key(out_point));
+    }
+}
+
+/// Index the transactions carrying a DRUID under that DRUID,
+/// transactions without DRUID are not indexed
+///
+/// ### Arguments
+///
+/// * `batch`     - Database writer
+/// * `block_num` - Number of the block the transactions are in
+/// * `txs`       - The transactions being stored
+pub fn put_druid_index_for_txs(
+    batch: &mut SimpleDbWriteBatch,
+    block_num: u64,
+    txs: &BTreeMap<String, Transaction>,
+) {
+    let mut druid_store: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (tx_hash, tx) in txs {
+        if let Some(druid_info) = &tx.druid_info {
+            let tx_hashes = druid_store.entry(&druid_info.druid).or_default();
+            tx_hashes.push(tx_hash.clone());
+        }
+    }
+
+    for (druid, tx_hashes) in druid_store {
+        let druid_entry = DruidTxInfo { tx_hashes };
+        let druid_input = serialize(&druid_entry).unwrap();
+        let druid_json = serde_json::to_vec(&druid_entry).unwrap();
+        let t = BlockchainItemMeta::Tx {
+            block_num,
+            tx_num: 0,
+        };
+        put_to_block_chain(batch, &t, druid, &druid_input, &druid_json);
+    }
+}
+
+/// Get the hashes of the stored transactions indexed under a DRUID
+///
+/// ### Arguments
+///
+/// * `db`    - Database to read from
+/// * `druid` - DRUID of the transactions
+pub fn get_druid_tx_hashes_from_db(db: Arc<Mutex<SimpleDb>>, druid: &str) -> Vec<String> {
+    get_stored_value_from_db(db, druid)
+        .and_then(|item| deserialize::<DruidTxInfo>(&item.data).ok())
+        .map(|druid_entry| druid_entry.tx_hashes)
+        .unwrap_or_default()
+}
+
+/// Get a page of out points from the UTXO index and the key to continue from
+///
+/// ### Arguments
+///
+/// * `db`    - Database to read from
+/// * `start` - Key of the first out point to return
+/// * `limit` - Maximum number of out points to return
+pub fn get_utxo_index_page(
+    db: &SimpleDb,
+    start: &str,
+    limit: usize,
+) -> (Vec<OutPoint>, Option<String>) {
+    let mut entries = db.iter_cf_clone_from(DB_COL_UTXO_INDEX, start.as_bytes());
+    let out_points = entries
+        .by_ref()
+        .take(limit)
+        .filter_map(|(_, v)| deserialize(&v).ok())
+        .collect();
+    let next = entries
+        .next()
+        .map(|(k, _)| String::from_utf8_lossy(&k).into_owned());
+
+    (out_points, next)
+}
+
+/// Key of a transaction in the address history, newest blocks first for each address
+///
+/// ### Arguments
+///
+/// * `address`   - Address touched by the transaction
+/// * `block_num` - Number of the block the transaction is in
+/// * `tx_hash`   - Hash of the transaction
+pub fn address_history_key(address: &str, block_num: u64, tx_hash: &str) -> String {
+    format!("{}-{:016x}-{}", address, u64::MAX - block_num, tx_hash)
+}
+
+/// Index the transactions under the addresses of their outputs and of the outputs they spend,
+/// each transaction being indexed once per address
+///
+/// ### Arguments
+///
+/// * `batch`     - Database writer
+/// * `db`        - Database holding the previously stored transactions
+/// * `block_num` - Number of the block the transactions are in
+/// * `txs`       - The transactions being stored
+pub fn put_address_history_for_txs(
+    batch: &mut SimpleDbWriteBatch,
+    db: &SimpleDb,
+    block_num: u64,
+    txs: &BTreeMap<String, Transaction>,
+) {
+    let mut history = BTreeSet::new();
+    for (tx_hash, tx) in txs {
+        let received = tx.outputs.iter().cloned();
+        let spent = get_inputs_previous_out_point(std::iter::once(tx)).filter_map(|out_point| {
+            match txs.get(&out_point.t_hash) {
+                Some(tx) => tx.outputs.get(out_point.n as usize).cloned(),
+                None => get_stored_tx_out_from_db(db, out_point),
+            }
+        });
+        for address in received.chain(spent).filter_map(|o| o.script_public_key) {
+            history.insert((address, tx_hash));
+        }
+    }
+
+    for (address, tx_hash) in history {
+        let entry = AddressTxEntry {
+            tx_hash: tx_hash.clone(),
+            block_num,
+        };
+        batch.put_cf(
+            DB_COL_ADDRESS_HISTORY,
+            address_history_key(&address, block_num, tx_hash),
+            &serialize(&entry).unwrap(),
+        );
+    }
+}
+
+/// Build the address history of the stored blocks if it was never built,
+/// such as for databases created before the history was kept.
+/// Pruned block bodies cannot be indexed.
+/// Returns the number of blocks indexed.
+///
+/// ### Arguments
+///
+/// * `db` - Database to update
+pub fn backfill_address_history(db: &mut SimpleDb) -> usize {
+    if matches!(
+        db.get_cf(DB_COL_INTERNAL, ADDRESS_HISTORY_INDEXED_KEY),
+        Ok(Some(_))
+    ) {
+        return 0;
+    }
+
+    let pointers: Vec<Vec<u8>> = db
+        .iter_cf_clone(DB_COL_BC_NAMED)
+        .filter(|(key, _)| decode_indexed_block_num(key).is_some())
+        .map(|(_, pointer)| pointer)
+        .collect();
+
+    let mut batch = db.batch_writer();
+    let mut backfilled = 0;
+    for pointer in pointers {
+        let Some(stored) = get_pointed_data_from_db::<StoredSerializingBlock>(db, &pointer) else {
+            continue;
+        };
+
+        let mining_tx_hash = &stored.block.header.nonce_and_mining_tx_hash.1;
+        let txs: BTreeMap<String, Transaction> = stored
+            .block
+            .transactions
+            .iter()
+            .chain(Some(mining_tx_hash))
+            .filter_map(|tx_hash| {
+                let pointer = ok_or_warn(
+                    db.get_cf(DB_COL_BC_ALL, tx_hash),
+                    "backfill_address_history tx",
+                )?;
+                let tx = get_pointed_data_from_db::<Transaction>(db, &pointer)?;
+                Some((tx_hash.clone(), tx))
+            })
+            .collect();
+        put_address_history_for_txs(&mut batch, db, stored.block.header.b_num, &txs);
+        backfilled += 1;
+    }
+    batch.put_cf(
+        DB_COL_INTERNAL,
+        ADDRESS_HISTORY_INDEXED_KEY,
+        &serialize(&true).unwrap(),
+    );
+
+    let batch = batch.done();
+    db.write(batch).unwrap();
+    backfilled
+}
+
+/// Get a page of the transactions touching an address, newest first
+///
+/// ### Arguments
+///
+/// * `db`       - Database to read from
+/// * `address`  - Address touched by the transactions
+/// * `page`     - Zero based number of the page
+/// * `per_page` - Maximum number of transactions in a page
+pub fn get_address_history_page(
+    db: &SimpleDb,
+    address: &str,
+    page: u64,
+    per_page: usize,
+) -> Vec<AddressTxEntry> {
+    let prefix = format!("{}-", address);
+    let skip = usize::try_from(page)
+        .unwrap_or(usize::MAX)
+        .saturating_mul(per_page);
+    db.iter_cf_clone_from(DB_COL_ADDRESS_HISTORY, prefix.as_bytes())
+        .take_while(|(k, _)| k.starts_with(prefix.as_bytes()))
+        .skip(skip)
+        .take(per_page)
+        .filter_map(|(_, v)| deserialize(&v).ok())
+        .collect()
+}
+
 /// Iterate on all the StoredSerializingBlock transaction hashes
 /// First the transactions in provided order and then the mining txs
 ///
@@ -1260,6 +2166,105 @@ pub fn get_stored_value_from_db<K: AsRef<[u8]>>(
     })
 }
 
+/// Get the stored transaction output at the given out point
+///
+/// ### Arguments
+///
+/// * `db`        - Locked database to read from
+/// * `out_point` - Out point of the output to find
+pub fn get_stored_tx_out_from_db(db: &SimpleDb, out_point: &OutPoint) -> Option<TxOut> {
+    let pointer = ok_or_warn(
+        db.get_cf(DB_COL_BC_ALL, &out_point.t_hash),
+        "get_stored_tx_out pointer",
+    )?;
+
+    let (_, cf, key) = decode_version_pointer(&pointer);
+    let data = ok_or_warn(db.get_cf(cf, key), "get_stored_tx_out data")?;
+    let tx = ok_or_warn(
+        deserialize::<Transaction>(&data).map(Some),
+        "get_stored_tx_out tx ser",
+    )?;
+    tx.outputs.get(out_point.n as usize).cloned()
+}
+
+/// Get the tokens created by the stored blockchain up to its latest block
+///
+/// ### Arguments
+///
+/// * `db` - Locked database to read from
+pub fn get_stored_supply_from_db(db: &SimpleDb) -> StoredSupply {
+    let mut supply = StoredSupply::default();
+    let last_block =
+        get_stored_named_data_from_db::<StoredSerializingBlock>(db, LAST_BLOCK_HASH_KEY);
+    let last_b_num = match last_block {
+        Some(last_block) => last_block.block.header.b_num,
+        None => return supply,
+    };
+
+    for b_num in 0..=last_b_num {
+        let key = indexed_block_hash_key(b_num);
+        let Some(stored) = get_stored_named_data_from_db::<StoredSerializingBlock>(db, key) else {
+            // Pruned blocks keep their mining transaction, indexed after the other transactions
+            let header = get_block_header_summaries_from_db(db, b_num, b_num)
+                .pop()
+                .flatten();
+            let mining_tx = header
+                .filter(|header| header.mining_tx_count > 0)
+                .map(|header| indexed_tx_hash_key(b_num, header.tx_count as u32))
+                .and_then(|key| get_stored_named_data_from_db::<Transaction>(db, key));
+            if let Some(tx) = mining_tx {
+                let tokens: u64 = tx.outputs.iter().map(|o| o.value.token_amount().0).sum();
+                supply.issued.0 += tokens;
+            }
+            continue;
+        };
+
+        let mining_tx_hash = &stored.block.header.nonce_and_mining_tx_hash.1;
+        for tx_hash in stored.block.transactions.iter().chain(Some(mining_tx_hash)) {
+            let pointer = ok_or_warn(db.get_cf(DB_COL_BC_ALL, tx_hash), "get_stored_supply tx");
+            let tx =
+                pointer.and_then(|pointer| get_pointed_data_from_db::<Transaction>(db, &pointer));
+            let Some(tx) = tx else {
+                continue;
+            };
+
+            let tokens: u64 = tx.outputs.iter().map(|o| o.value.token_amount().0).sum();
+            if tx_hash == mining_tx_hash {
+                supply.issued.0 += tokens;
+            } else if b_num == 0 {
+                supply.seed.0 += tokens;
+            }
+        }
+    }
+    supply
+}
+
+/// Get the deserialized value a named key points to
+///
+/// ### Arguments
+///
+/// * `db`  - Locked database to read from
+/// * `key` - Named key of the value
+fn get_stored_named_data_from_db<T: DeserializeOwned>(
+    db: &SimpleDb,
+    key: impl AsRef<[u8]>,
+) -> Option<T> {
+    let pointer = ok_or_warn(db.get_cf(DB_COL_BC_NAMED, key), "get_stored_named_data")?;
+    get_pointed_data_from_db(db, &pointer)
+}
+
+/// Get the deserialized value at a version pointer
+///
+/// ### Arguments
+///
+/// * `db`      - Locked database to read from
+/// * `pointer` - Version pointer of the value
+fn get_pointed_data_from_db<T: DeserializeOwned>(db: &SimpleDb, pointer: &[u8]) -> Option<T> {
+    let (_, cf, key) = decode_version_pointer(pointer);
+    let data = ok_or_warn(db.get_cf(cf, key), "get_pointed_data")?;
+    ok_or_warn(deserialize::<T>(&data).map(Some), "get_pointed_data ser")?
+}
+
 /// Version pointer for the column:key
 ///
 /// ### Arguments
@@ -1293,6 +2298,28 @@ pub fn indexed_tx_hash_key(b_num: u64, tx_num: u32) -> String {
     format!("{INDEXED_TX_HASH_PREFIX_KEY}{b_num:016x}_{tx_num:08x}")
 }
 
+/// Decodes the block number from an indexed block key
+///
+/// ### Arguments
+///
+/// * `key` - Key in the named block chain column
+pub fn decode_indexed_block_num(key: &[u8]) -> Option<u64> {
+    let b_num = key.strip_prefix(INDEXED_BLOCK_HASH_PREFIX_KEY.as_bytes())?;
+    u64::from_str_radix(str::from_utf8(b_num).ok()?, 16).ok()
+}
+
+/// Get the hash of the block stored with the given number
+///
+/// ### Arguments
+///
+/// * `db`    - Database to query
+/// * `b_num` - The block number
+pub fn get_indexed_block_hash(db: &SimpleDb, b_num: u64) -> Option<Vec<u8>> {
+    let key = indexed_block_hash_key(b_num);
+    let pointer = ok_or_warn(db.get_cf(DB_COL_BC_NAMED, key), "get_indexed_block_hash")?;
+    Some(decode_version_pointer(&pointer).2.to_vec())
+}
+
 /// Decodes a version pointer
 ///
 /// ### Arguments
@@ -1313,3 +2340,140 @@ fn ok_or_warn<V, E: fmt::Display>(r: std::result::Result<Option<V>, E>, tag: &st
         None
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::configurations::DbMode;
+    use crate::utils::construct_coinbase_tx;
+    use tw_chain::primitives::transaction::TxIn;
+    use tw_chain::script::lang::Script;
+    use tw_chain::utils::transaction_utils::construct_tx_core;
+
+    #[test]
+    fn prune_keeps_headers_and_unspent_transactions() {
+        //
+        // Arrange
+        //
+        let mut db = db_utils::new_db(DbMode::InMemory, &DB_SPEC, None, None);
+        for b_num in 0..6 {
+            let mut block_txs = BTreeMap::new();
+            if (1..4).contains(&b_num) {
+                // Each transaction spends the previous one, leaving only g_tx_3 unspent
+                let previous_out = OutPoint::new(format!("g_tx_{}", b_num - 1), 0);
+                block_txs.insert(format!("g_tx_{b_num}"), spending_tx(previous_out));
+            }
+            store_test_block(&mut db, b_num, block_txs);
+        }
+        let supply_before = get_stored_supply_from_db(&db);
+
+        //
+        // Act
+        //
+        let pruned = prune_block_chain_db(&mut db, 3);
+        let pruned_again = prune_block_chain_db(&mut db, 3);
+
+        //
+        // Assert
+        //
+        let is_stored = |key: &str| matches!(db.get_cf(DB_COL_BC_ALL, key), Ok(Some(_)));
+        let stored: Vec<_> = [
+            "b_block_0",
+            "b_block_1",
+            "b_block_2",
+            "b_block_3",
+            "b_block_5",
+            "g_coinbase_1",
+            "g_coinbase_2",
+            "g_tx_1",
+            "g_tx_2",
+            "g_tx_3",
+        ]
+        .iter()
+        .map(|key| (*key, is_stored(key)))
+        .collect();
+        let headers = get_block_header_summaries_from_db(&db, 0, 5);
+
+        assert_eq!((pruned, pruned_again), (2, 0));
+        assert_eq!(
+            stored,
+            vec![
+                ("b_block_0", true),
+                ("b_block_1", false),
+                ("b_block_2", false),
+                ("b_block_3", true),
+                ("b_block_5", true),
+                ("g_coinbase_1", true),
+                ("g_coinbase_2", true),
+                ("g_tx_1", false),
+                ("g_tx_2", false),
+                ("g_tx_3", true),
+            ]
+        );
+        assert!(headers.iter().all(Option::is_some));
+        assert_eq!(get_indexed_block_hash(&db, 1), Some(b"b_block_1".to_vec()));
+        assert_eq!(get_stored_supply_from_db(&db), supply_before);
+    }
+
+    /// Transaction spending the given out point into a single token output
+    fn spending_tx(previous_out: OutPoint) -> Transaction {
+        let tx_in = TxIn {
+            previous_out: Some(previous_out),
+            script_signature: Script::new(),
+        };
+        let tx_out = TxOut::new_token_amount("addr".to_owned(), TokenAmount(5), None);
+        construct_tx_core(vec![tx_in], vec![tx_out], None)
+    }
+
+    /// Store a block with its transactions and a coinbase the way complete blocks are stored
+    fn store_test_block(
+        db: &mut SimpleDb,
+        b_num: u64,
+        mut block_txs: BTreeMap<String, Transaction>,
+    ) {
+        let mining_tx_hash = format!("g_coinbase_{b_num}");
+        let mut block = Block::new();
+        block.header.b_num = b_num;
+        block.header.nonce_and_mining_tx_hash = (Vec::new(), mining_tx_hash.clone());
+        block.transactions = block_txs.keys().cloned().collect();
+        let coinbase = construct_coinbase_tx(b_num, TokenAmount(10), "addr".to_owned());
+        block_txs.insert(mining_tx_hash, coinbase);
+
+        let mut batch = db.batch_writer();
+        let all_txs = all_ordered_stored_block_tx_hashes(
+            &block.transactions,
+            std::iter::once(&block.header.nonce_and_mining_tx_hash),
+        );
+        let mut tx_len = 0;
+        for (tx_num, tx_hash) in all_txs {
+            tx_len = tx_num + 1;
+            let tx = &block_txs[tx_hash];
+            let (tx_input, tx_json) = (serialize(tx).unwrap(), serde_json::to_vec(tx).unwrap());
+            let t = BlockchainItemMeta::Tx {
+                block_num: b_num,
+                tx_num,
+            };
+            put_to_block_chain(&mut batch, &t, tx_hash, &tx_input, &tx_json);
+        }
+
+        let hash = format!("b_block_{b_num}");
+        let stored_block = StoredSerializingBlock { block };
+        let block_input = serialize(&stored_block).unwrap();
+        let block_json = serde_json::to_vec(&stored_block).unwrap();
+        let b = BlockchainItemMeta::Block {
+            block_num: b_num,
+            tx_len,
+        };
+        let pointer = put_to_block_chain(&mut batch, &b, &hash, &block_input, &block_json);
+        put_named_last_block_to_block_chain(&mut batch, &pointer);
+        put_block_header_summary(
+            &mut batch,
+            &block_header_summary(&hash, &stored_block.block),
+        );
+        put_utxo_index_for_txs(&mut batch, &block_txs);
+        put_address_history_for_txs(&mut batch, db, b_num, &block_txs);
+
+        let batch = batch.done();
+        db.write(batch).unwrap();
+    }
+}
@@ -124,6 +124,26 @@ impl FetchReceive {
     pub fn is_complete(&self) -> bool {
         matches!(self.state, FetchReceiveState::NoWait)
     }
+
+    /// Range of blocks left to receive, restarting any partially received block
+    pub fn restart_block_range(&mut self, limit: u64) -> Option<(u64, u64)> {
+        use FetchReceiveState as S;
+
+        let b_num = match self.state {
+            S::WaitBlock { b_num } | S::WaitTxs { b_num, .. } => b_num,
+            S::NoWait | S::DoneBlock { .. } => return None,
+        };
+
+        self.restart_block(b_num);
+        let end = std::cmp::min(b_num.saturating_add(limit - 1), self.target_b_num);
+        Some((b_num, end))
+    }
+
+    /// Wait for the given block again, dropping anything received for it
+    pub fn restart_block(&mut self, b_num: u64) {
+        self.state = FetchReceiveState::WaitBlock { b_num };
+        self.blockchain_items.clear();
+    }
 }
 
 pub struct StorageFetch {
@@ -243,6 +263,26 @@ impl StorageFetch {
         peer.zip(key)
     }
 
+    /// Get the peer to fetch the next range of at most `limit` blocks from
+    pub fn get_fetch_peer_and_range(&mut self, limit: u64) -> Option<(SocketAddr, (u64, u64))> {
+        let range = self
+            .to_receive
+            .as_mut()
+            .and_then(|r| r.restart_block_range(limit));
+        let peer = self.storage_nodes.get(self.fetch_peer_idx).copied();
+        peer.zip(range)
+    }
+
+    /// Fetch the given block again from the next peer
+    pub fn refetch_block_from_next_peer(&mut self, b_num: u64) {
+        if let Some(to_receive) = &mut self.to_receive {
+            to_receive.restart_block(b_num);
+        } else {
+            self.to_receive = Some(FetchReceive::new_block_fetch(b_num, b_num));
+        }
+        self.change_to_next_fetch_peer();
+    }
+
     /// Select the next peer to fetch from
     pub fn change_to_next_fetch_peer(&mut self) {
         let next = self.fetch_peer_idx + 1;
@@ -1,20 +1,20 @@
-use crate::active_raft::ActiveRaft;
+use crate::active_raft::{ActiveRaft, RaftInfo};
+use crate::canonical::canonical_hash;
 use crate::configurations::StorageNodeConfig;
 use crate::constants::DB_PATH;
 use crate::db_utils::{self, SimpleDb, SimpleDbError, SimpleDbSpec};
 use crate::interfaces::{BlockStoredInfo, CommonBlockInfo, MinedBlockExtraInfo};
 use crate::raft::{RaftCommit, RaftCommitData, RaftData, RaftMessageWrapper};
 use crate::raft_util::{RaftContextKey, RaftInFlightProposals};
-use crate::utils::{create_socket_addr_for_list, BackupCheck};
-use bincode::{deserialize, serialize};
+use crate::utils::{create_socket_addr_for_list, try_deserialize_exact, BackupCheck};
+use bincode::{serialize, Error as BincodeError};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tracing::{debug, trace, warn};
-use tw_chain::crypto::sha3_256;
+use tracing::{debug, error, trace, warn};
 
 pub const DB_SPEC: SimpleDbSpec = SimpleDbSpec {
     db_path: DB_PATH,
@@ -55,6 +55,7 @@ pub struct CompleteBlock {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CompleteBlockBuilder {
     pub common: CommonBlockInfo,
+    /// Extra info sent by each mempool node, by raft peer id
     pub per_node: BTreeMap<u64, MinedBlockExtraInfo>,
 }
 
@@ -90,6 +91,8 @@ pub struct StorageRaft {
     raft_active: ActiveRaft,
     /// Consensused fields.
     consensused: StorageConsensused,
+    /// Raft peer id of each configured mempool node, by address.
+    mempool_peer_ids: BTreeMap<SocketAddr, u64>,
     /// Whether consensused received initial snapshot
     consensused_snapshot_applied: bool,
     /// Proposed items in flight.
@@ -134,16 +137,28 @@ impl StorageRaft {
             db_utils::new_db(config.storage_db_mode, &DB_SPEC, raft_db, None),
         );
 
-        let first_raft_peer = config.storage_node_idx == 0 || !raft_active.use_raft();
-        let peers_len = raft_active.peers_len();
+        let mempool_node_urls = config
+            .mempool_nodes
+            .iter()
+            .map(|s| s.address.clone())
+            .collect::<Vec<String>>();
+        let mempool_peer_ids: BTreeMap<SocketAddr, u64> =
+            create_socket_addr_for_list(&mempool_node_urls)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .zip(1..)
+                .collect();
 
-        let consensused = StorageConsensused::default().with_peers_len(peers_len);
+        let first_raft_peer = config.storage_node_idx == 0 || !raft_active.use_raft();
+        let consensused = StorageConsensused::default().with_peers_len(mempool_peer_ids.len());
         let backup_check = BackupCheck::new(config.backup_block_modulo);
 
         Self {
             first_raft_peer,
             raft_active,
             consensused,
+            mempool_peer_ids,
             consensused_snapshot_applied: !use_raft,
             proposed_in_flight: Default::default(),
             shutdown_no_commit_process: false,
@@ -166,6 +181,11 @@ impl StorageRaft {
         self.raft_active.raft_peer_addrs()
     }
 
+    /// Returns a shareable view of the raft membership and leadership
+    pub fn raft_info(&self) -> RaftInfo {
+        self.raft_active.raft_info()
+    }
+
     /// Blocks & waits for a next event from a peer.
     pub fn raft_loop(&self) -> impl Future<Output = ()> {
         self.raft_active.raft_loop()
@@ -227,7 +247,15 @@ impl StorageRaft {
             None
         } else {
             warn!("apply_snapshot called self.consensused updated");
-            self.consensused = deserialize(&consensused_ser).unwrap();
+
+            // Majority follows our configured mempool nodes rather than the snapshot
+            self.consensused = match StorageConsensused::from_snapshot(&consensused_ser) {
+                Ok(consensused) => consensused.with_peers_len(self.mempool_peer_ids.len()),
+                Err(e) => {
+                    error!("apply_snapshot deserialize error: {:?}", e);
+                    return None;
+                }
+            };
             self.set_ignore_dedeup_b_num_less_than_current();
             Some(CommittedItem::Snapshot)
         }
@@ -254,9 +282,22 @@ impl StorageRaft {
         match item {
             StorageRaftItem::PartBlock(block) => {
                 let b_num = block.common.block.header.b_num;
-                if self.consensused.is_current_block(b_num) {
-                    debug!("PartBlock appened ({},{:?})", b_num, key);
-                    self.consensused.append_received_block(key, block);
+                if let Some(mempool_peer_id) = self.mempool_peer_id(&block.peer) {
+                    if self.consensused.is_current_block(b_num) {
+                        debug!("PartBlock appened ({},{:?})", b_num, key);
+                        self.consensused
+                            .append_received_block(mempool_peer_id, block);
+                    } else {
+                        debug!(
+                            "PartBlock dropped: not current block ({},{:?}) current: {}",
+                            b_num, key, self.consensused.current_block_num
+                        );
+                    }
+                } else {
+                    debug!(
+                        "PartBlock dropped: not from a mempool node ({},{:?}) {:?}",
+                        b_num, key, block.peer
+                    );
                 }
             }
         }
@@ -283,6 +324,15 @@ impl StorageRaft {
         self.raft_active.received_message(msg).await
     }
 
+    /// Raft peer id of the configured mempool node at the given address
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer` - socket address of the mempool node
+    pub fn mempool_peer_id(&self, peer: &SocketAddr) -> Option<u64> {
+        self.mempool_peer_ids.get(peer).copied()
+    }
+
     /// Add block to our local pool from which to propose
     /// consensused blocks.
     ///
@@ -392,7 +442,7 @@ impl StorageRaft {
 }
 
 impl StorageConsensused {
-    /// Specify the raft group size
+    /// Specify the number of mempool nodes a majority of which must send the block
     pub fn with_peers_len(mut self, peers_len: usize) -> Self {
         self.sufficient_majority = peers_len / 2 + 1;
         self
@@ -416,6 +466,16 @@ impl StorageConsensused {
         }
     }
 
+    /// Deserialize the consensused state from RAFT snapshot data.
+    /// The majority is not restored: set it with `with_peers_len`.
+    ///
+    /// ### Arguments
+    ///
+    /// * `bytes` - Snapshot data
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, BincodeError> {
+        try_deserialize_exact(bytes)
+    }
+
     /// Convert to import type
     pub fn into_import(self) -> StorageConsensusedImport {
         StorageConsensusedImport {
@@ -449,15 +509,15 @@ impl StorageConsensused {
         completed_blocks_len >= self.sufficient_majority
     }
 
-    ///Appends a RecievedBlock into the current_block_completed_parts
+    ///Appends a RecievedBlock into the current_block_completed_parts,
+    /// keyed by the raft peer id of the mempool node that sent it
     ///
     /// ### Arguments
     ///
-    /// * `key`   - Key containing the proposer_id to be appended.
-    /// * `block` - RecievedBlock object that is being appended.
-    pub fn append_received_block(&mut self, key: RaftContextKey, block: ReceivedBlock) {
-        let block_ser = serialize(&block.common).unwrap();
-        let block_hash = sha3_256::digest(&block_ser).to_vec();
+    /// * `mempool_peer_id` - Raft peer id of the configured mempool node that sent the block.
+    /// * `block`           - RecievedBlock object that is being appended.
+    pub fn append_received_block(&mut self, mempool_peer_id: u64, block: ReceivedBlock) {
+        let block_hash = canonical_hash(&block.common);
 
         let common = block.common;
         let node_info = block.per_node;
@@ -466,7 +526,7 @@ impl StorageConsensused {
             .entry(block_hash)
             .or_insert(CompleteBlockBuilder { common, per_node })
             .per_node
-            .insert(key.proposer_id, node_info);
+            .insert(mempool_peer_id, node_info);
     }
 
     ///generates a completed block and returns it.
@@ -494,3 +554,75 @@ impl StorageConsensused {
         &self.last_block_stored
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::unicorn::Unicorn;
+    use rug::Integer;
+    use tw_chain::primitives::block::Block;
+
+    #[test]
+    fn snapshot_from_previous_layout() {
+        //
+        // Arrange
+        //
+        let mut block = Block::new();
+        block.header.b_num = 3;
+        let common = CommonBlockInfo {
+            block,
+            block_txs: BTreeMap::new(),
+            pow_p_value: 2,
+            pow_d_value: 2,
+            unicorn: Unicorn::default(),
+            unicorn_witness: Integer::from(7),
+        };
+        let peer = "127.0.0.1:12340".parse().unwrap();
+        let per_node = MinedBlockExtraInfo { shutdown: true };
+
+        let mut c = StorageConsensused::default().with_peers_len(3);
+        c.current_block_num = 3;
+        c.last_committed_raft_idx_and_term = (12, 2);
+        c.append_received_block(
+            2,
+            ReceivedBlock {
+                peer,
+                common,
+                per_node,
+            },
+        );
+
+        // Fields in the order snapshots were written by previous nodes
+        let previous_parts: BTreeMap<_, _> = c
+            .current_block_completed_parts
+            .iter()
+            .map(|(k, v)| {
+                let per_node: BTreeMap<_, _> =
+                    v.per_node.iter().map(|(id, i)| (*id, i.shutdown)).collect();
+                (k.clone(), (&v.common, per_node))
+            })
+            .collect();
+        let previous_snapshot = serialize(&(
+            c.sufficient_majority,
+            c.current_block_num,
+            previous_parts,
+            c.last_committed_raft_idx_and_term,
+            &c.last_block_stored,
+        ))
+        .unwrap();
+
+        //
+        // Act
+        //
+        let restored = StorageConsensused::from_snapshot(&previous_snapshot)
+            .unwrap()
+            .with_peers_len(3);
+
+        //
+        // Assert
+        //
+        assert_eq!(serialize(&restored).unwrap(), serialize(&c).unwrap());
+        assert_eq!(restored.current_block_completed_parts.len(), 1);
+        assert!(!restored.has_block_ready_to_store());
+    }
+}
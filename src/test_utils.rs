@@ -4,9 +4,9 @@
 
 use crate::comms_handler::{test_tls_certificates, Node, TcpTlsConfig, TcpTlsListner};
 use crate::configurations::{
-    DbMode, ExtraNodeParams, MempoolNodeConfig, MinerNodeConfig, NodeSpec, PreLaunchNodeConfig,
-    PreLaunchNodeType, StorageNodeConfig, TlsSpec, UserAutoGenTxSetup, UserNodeConfig, UtxoSetSpec,
-    WalletTxSpec,
+    BootstrapNodeSpec, DbMode, ExtraNodeParams, MempoolNodeConfig, MinerNodeConfig, NodeSpec,
+    PeerDiscoveryConfig, PreLaunchNodeConfig, PreLaunchNodeType, StorageNodeConfig, TlsSpec,
+    UserAutoGenTxSetup, UserNodeConfig, UtxoSetSpec, WalletTxSpec,
 };
 use crate::constants::{DB_PATH, DB_PATH_TEST, WALLET_PATH};
 use crate::interfaces::{InitialIssuance, Response};
@@ -26,7 +26,7 @@ use crate::user::{
 use crate::utils::{
     concat_maps, decode_pub_key, decode_secret_key, get_test_common_unicorn,
     loop_connnect_to_peers_async, loop_wait_connnect_to_peers_async, make_utxo_set_from_seed,
-    LocalEventSender, ResponseResult, StringError,
+    shutdown_connections, LocalEventSender, ResponseResult, StringError,
 };
 use futures::future::join_all;
 use std::collections::{BTreeMap, BTreeSet};
@@ -100,7 +100,14 @@ pub struct NetworkConfig {
     pub mempool_miner_whitelist: MinerWhitelist,
     pub peer_limit: usize,
     pub address_aggregation_limit: Option<usize>,
+    pub address_aggregation_max_inputs: Option<usize>,
+    pub enable_address_aggregation: Option<bool>,
     pub initial_issuances: Vec<InitialIssuance>,
+    /// Miners discover their mempool node from it acting as bootstrap node
+    pub peer_discovery: bool,
+    pub miner_outage_mining_secs: Option<u64>,
+    pub mempool_pow_batch_window: usize,
+    pub mempool_druid_pool_expiry_blocks: u64,
 }
 
 /// Node info to create node
@@ -142,6 +149,10 @@ impl TestTlsSpec {
             untrusted_names: Some(self.pem_certificates_with_ca.keys().cloned().collect()),
             pem_certificate_override: None,
             pem_pkcs8_private_key_override: None,
+            pem_certificate_path: None,
+            pem_pkcs8_private_key_path: None,
+            pem_ca_bundle_path: None,
+            pinned_pem_certificates: Default::default(),
         }
     }
 }
@@ -311,6 +322,35 @@ impl Network {
         self.update_active_nodes();
     }
 
+    /// Gracefully shut down specified nodes, keeping their dbs for re-spawn.
+    pub async fn shutdown_nodes_named(&mut self, names: &[String]) {
+        let mut arc_nodes = BTreeMap::new();
+        let mut raft_loop_handles = BTreeMap::new();
+
+        for name in names {
+            if let Some((k, v)) = self.arc_nodes.remove_entry(name) {
+                arc_nodes.insert(k, v);
+            }
+            if let Some((k, v)) = self.raft_loop_handles.remove_entry(name) {
+                raft_loop_handles.insert(k, v);
+            }
+        }
+        for node in arc_nodes.values() {
+            shutdown(node).await;
+        }
+        join_all(raft_loop_handles.into_values()).await;
+
+        // Store extra params for re-spawn
+        for (name, node) in &arc_nodes {
+            let extra = take_closed_extra_params(node).await;
+            self.extra_params.insert(name.clone(), extra);
+        }
+
+        // Remove from active nodes
+        self.dead_nodes.extend(names.iter().map(|v| v.to_string()));
+        self.update_active_nodes();
+    }
+
     /// Sent startup requests for specified node.
     pub async fn send_startup_requests_named(&mut self, names: &[String]) {
         for name in names {
@@ -554,6 +594,17 @@ impl NetworkConfig {
         };
         self
     }
+
+    /// Use a storage raft group of a different size than the mempool one
+    pub fn with_storage_count(mut self, storage_count: usize) -> Self {
+        self.nodes.insert(
+            NodeType::Storage,
+            (0..storage_count)
+                .map(|idx| format!("storage{}", idx + 1))
+                .collect(),
+        );
+        self
+    }
 }
 
 /// Nodes of any type
@@ -667,6 +718,20 @@ async fn close_raft_loop(node: &ArcNode) {
     }
 }
 
+///Dispatch to shutdown
+async fn shutdown(node: &ArcNode) {
+    match node {
+        ArcNode::Mempool(n) => n.lock().await.shutdown().await,
+        ArcNode::Storage(n) => n.lock().await.shutdown().await,
+        ArcNode::Miner(n) => n.lock().await.shutdown().await,
+        ArcNode::User(n) => n.lock().await.shutdown().await,
+        ArcNode::PreLaunch(_) => {
+            let (mut node_conn, _, _) = connect_info_peers(node).await;
+            shutdown_connections(&mut node_conn).await;
+        }
+    }
+}
+
 ///Dispatch to raft_loop, providing also the address and a tag.
 async fn raft_loop(node: &ArcNode) -> Option<(String, SocketAddr, impl Future<Output = ()>)> {
     use futures::future::FutureExt;
@@ -1034,6 +1099,28 @@ async fn init_miner(
         info.node_infos[c].index
     };
 
+    let mempool_nodes: Vec<_> = info
+        .mempool_nodes
+        .clone()
+        .into_iter()
+        .map(|v| NodeSpec {
+            address: v.to_string(),
+        })
+        .collect();
+    let (mempool_nodes, peer_discovery) = if config.peer_discovery {
+        let bootstrap = BootstrapNodeSpec {
+            address: mempool_nodes[miner_mempool_node_idx].address.clone(),
+            identity_public_key: None,
+        };
+        let peer_discovery = PeerDiscoveryConfig {
+            bootstrap_nodes: vec![bootstrap],
+            ..Default::default()
+        };
+        (Vec::new(), Some(peer_discovery))
+    } else {
+        (mempool_nodes, None)
+    };
+
     // Create node
     let node_info = &info.node_infos[name];
     let config = MinerNodeConfig {
@@ -1042,14 +1129,7 @@ async fn init_miner(
         tls_config: config.tls_config.make_tls_spec(&info.socket_name_mapping),
         api_keys: Default::default(),
         miner_mempool_node_idx,
-        mempool_nodes: info
-            .mempool_nodes
-            .clone()
-            .into_iter()
-            .map(|v| NodeSpec {
-                address: v.to_string(),
-            })
-            .collect(),
+        mempool_nodes,
         passphrase: config.passphrase.clone(),
         miner_api_port: 3004,
         miner_api_use_tls: true,
@@ -1059,8 +1139,15 @@ async fn init_miner(
         static_miner_address: config.static_miner_address.clone(),
         mining_api_key: config.mining_api_key.clone(),
         peer_limit: config.peer_limit,
+        max_frame_size: None,
+        peer_reconnect_max_delay_ms: None,
         address_aggregation_limit: config.address_aggregation_limit,
+        address_aggregation_max_inputs: config.address_aggregation_max_inputs,
+        enable_address_aggregation: config.enable_address_aggregation,
         activation_height_asert: None,
+        peer_discovery,
+        outage_mining_secs: config.miner_outage_mining_secs,
+        pow_threads: Some(1),
     };
     let info_str = format!("{} -> {}", name, node_info.node_spec);
     info!("New Miner {}", info_str);
@@ -1110,13 +1197,20 @@ async fn init_storage(
         storage_raft,
         storage_api_port: 3001,
         storage_api_use_tls: true,
+        storage_api_tls_cert_path: None,
+        storage_api_tls_key_path: None,
+        storage_api_rate_limit: None,
         storage_raft_tick_timeout: 200 / config.test_duration_divider,
         storage_catchup_duration: 2000 / config.test_duration_divider,
         routes_pow: Default::default(),
         backup_block_modulo: config.backup_block_modulo,
         backup_restore: config.backup_restore,
         peer_limit: config.peer_limit,
+        max_frame_size: None,
+        peer_reconnect_max_delay_ms: None,
         activation_height_asert: None,
+        peer_discovery: None,
+        prune_keep_last_blocks: None,
     };
     let info = format!("{} -> {}", name, node_info.node_spec);
     info!("New Storage {}", info);
@@ -1176,6 +1270,12 @@ async fn init_mempool(
         mempool_raft_tick_timeout: 200 / config.test_duration_divider,
         mempool_mining_event_timeout: 500 / config.test_duration_divider,
         mempool_transaction_timeout: 100 / config.test_duration_divider,
+        mempool_proposal_timeout: 5000 / config.test_duration_divider,
+        mempool_local_tx_max_age: 600000,
+        mempool_block_stored_info_timeout: 30000,
+        mempool_pow_batch_window: config.mempool_pow_batch_window,
+        mempool_storage_resend_interval: 200 / config.test_duration_divider,
+        mempool_druid_pool_expiry_blocks: config.mempool_druid_pool_expiry_blocks,
         mempool_seed_utxo: config.mempool_seed_utxo.clone(),
         mempool_genesis_tx_in: config.mempool_genesis_tx_in.clone(),
         mempool_partition_full_size: config.mempool_partition_full_size,
@@ -1184,17 +1284,27 @@ async fn init_mempool(
         sanction_list: Vec::new(),
         mempool_api_port: 3002,
         mempool_api_use_tls: true,
+        mempool_api_rate_limit: None,
         routes_pow: Default::default(),
         backup_block_modulo: config.backup_block_modulo,
         utxo_re_align_block_modulo: config.utxo_re_align_block_modulo,
+        mempool_snapshot_block_modulo: Default::default(),
         backup_restore: config.backup_restore,
         enable_trigger_messages_pipeline_reset: config.enable_pipeline_reset,
         mempool_miner_whitelist: config.mempool_miner_whitelist.clone(),
         peer_limit: config.peer_limit,
+        max_frame_size: None,
+        peer_reconnect_max_delay_ms: None,
         sub_peer_limit: config.peer_limit,
         initial_issuances: config.initial_issuances.clone(),
         tx_status_lifetime: 600000,
         activation_height_asert: None,
+        block_size_in_tx: None,
+        tx_pool_limit: None,
+        max_in_flight: None,
+        target_block_interval_secs: None,
+        difficulty_adjustment_period: None,
+        peer_discovery: config.peer_discovery.then(Default::default),
     };
     let info = format!("{} -> {}", name, node_info.node_spec);
     info!("New Mempool {}", info);
@@ -1243,6 +1353,9 @@ async fn init_user(
             .collect(),
         user_api_port: 3000,
         user_api_use_tls: true,
+        user_api_tls_cert_path: None,
+        user_api_tls_key_path: None,
+        user_api_rate_limit: None,
         user_wallet_seeds,
         passphrase: config.passphrase.clone(),
         user_auto_donate: config.user_auto_donate,
@@ -1250,6 +1363,8 @@ async fn init_user(
         routes_pow: Default::default(),
         backup_block_modulo: Default::default(),
         peer_limit: config.peer_limit,
+        max_frame_size: None,
+        peer_discovery: None,
     };
 
     let info = format!("{} -> {}", name, node_info.node_spec);
@@ -1298,6 +1413,7 @@ async fn init_pre_launch(
             .map(|v| v.to_string())
             .collect(),
         peer_limit: config.peer_limit,
+        max_frame_size: None,
     };
 
     let info = format!("{} -> {}", name, node_info.node_spec);
@@ -1,44 +1,63 @@
 //! Test suite for the network functions.
 
+use crate::api::handlers::TokenSupply;
+use crate::api::routes;
+use crate::api::utils::{create_new_cache, handle_rejection, CACHE_LIVE_TIME};
 use crate::configurations::{
-    MempoolNodeSharedConfig, TxOutSpec, UserAutoGenTxSetup, UtxoSetSpec, WalletTxSpec,
+    LoadGenConfig, LoadGenMix, MempoolNodeSharedConfig, TxOutSpec, UserAutoGenTxSetup, UtxoSetSpec,
+    WalletTxSpec,
 };
-use crate::constants::{NETWORK_VERSION, SANC_LIST_TEST};
+use crate::constants::{NETWORK_VERSION, SANC_LIST_TEST, TX_VERIFICATION_BLOCKING_THRESHOLD};
+use crate::db_utils::SimpleDb;
 use crate::interfaces::{
     BlockStoredInfo, BlockchainItem, BlockchainItemMeta, BlockchainItemType, CommonBlockInfo,
-    DruidPool, MempoolApi, MempoolRequest, MinedBlock, MinedBlockExtraInfo, Response,
-    StorageRequest, StoredSerializingBlock, UserApiRequest, UserRequest, UtxoFetchType, UtxoSet,
-    WinningPoWInfo,
+    DruidPool, MempoolApi, MempoolRaftStatus, MempoolRequest, MinedBlock, MinedBlockExtraInfo,
+    NoncePartition, PaymentResponse, Response, StorageRequest, StoredSerializingBlock, TxStage,
+    TxStatusType, UserApiRequest, UserRequest, UtxoFetchType, UtxoSet, WinningPoWInfo,
 };
+use crate::load_gen::{LoadGenerator, LoadStage, LoadTarget, StageObservation};
 use crate::mempool::MempoolNode;
 use crate::mempool_raft::MinerWhitelist;
-use crate::miner::MinerNode;
-use crate::storage::{all_ordered_stored_block_tx_hashes, StorageNode};
+use crate::miner::{CachedBlockTemplate, MinerNode};
+use crate::pool_mining::{
+    PoolJob, PoolMiningError, PoolMiningHandle, PoolMiningMessage, PoolMiningRequest, PoolSolution,
+};
+use crate::storage::{
+    all_ordered_stored_block_tx_hashes, get_stored_value_from_db, indexed_block_hash_key,
+    StorageNode,
+};
 use crate::storage_raft::CompleteBlock;
 use crate::test_utils::{
     generate_rb_transactions, get_test_tls_spec, map_items, node_join_all_checked,
     remove_all_node_dbs, Network, NetworkConfig, NodeType, RbReceiverData, RbSenderData,
 };
+use crate::threaded_call::{make_threaded_call, ThreadedCallSender};
 use crate::tracked_utxo::TrackedUtxoBalance;
 use crate::transactor::Transactor;
-use crate::user::UserNode;
+use crate::user::{PaymentEvent, PaymentEventKind, UserNode};
 use crate::utils::{
     apply_mining_tx, calculate_reward, construct_coinbase_tx, construct_valid_block_pow_hash,
     create_valid_transaction_with_ins_outs, decode_pub_key, decode_secret_key,
-    generate_pow_for_block, get_sanction_addresses, tracing_log_try_init, LocalEvent, StringError,
+    generate_pow_for_block, generate_pow_for_block_partitioned, get_sanction_addresses,
+    get_total_coinbase_tokens, nonce_partition_counter, nonce_partition_range,
+    tracing_log_try_init, validate_pow_block, LocalEvent, StringError,
 };
+use async_trait::async_trait;
 use bincode::{deserialize, deserialize_from};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::future::Future;
 use std::io::Cursor;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Barrier;
 use tokio::sync::Mutex;
+use tokio::sync::{mpsc, oneshot, Semaphore};
 use tokio::time;
 use tracing::{debug, error, error_span, info};
 use tracing_futures::Instrument;
+use tw_chain::constants::TOTAL_TOKENS;
 use tw_chain::crypto::sha3_256;
 use tw_chain::crypto::sign_ed25519 as sign;
 use tw_chain::crypto::sign_ed25519::{PublicKey, SecretKey};
@@ -51,6 +70,7 @@ use tw_chain::utils::transaction_utils::{
     construct_address, construct_item_create_tx, construct_tx_hash,
     construct_tx_in_signable_asset_hash, get_tx_out_with_out_point_cloned,
 };
+use warp::Filter;
 
 const TIMEOUT_TEST_WAIT_DURATION: Duration = Duration::from_millis(5000);
 
@@ -125,6 +145,7 @@ enum CfgPow {
 #[derive(Clone, Debug)]
 enum CfgModif {
     Drop(&'static str),
+    Shutdown(&'static str),
     Respawn(&'static str),
     HandleEvents(&'static [(&'static str, &'static str)]),
     RestartEventsAll(&'static [(NodeType, &'static str)]),
@@ -237,17 +258,11 @@ async fn full_flow_single_miner_single_raft_with_aggregation_tx_check() {
     for _ in 1..(address_aggregation_limit * 5) + 1 {
         create_block_act(&mut network, Cfg::All, CfgNum::All).await;
 
-        // Check if the miner is _about_ to send aggregation tx
+        // Check if the miner is _about_ to send aggregation tx:
+        // it commits its previous winning before checking the threshold
         {
-            let miner_node = network.miner(miner_addr).unwrap();
-
-            let addrs = miner_node
-                .lock()
-                .await
-                .get_wallet_db()
-                .get_known_addresses()
-                .len();
-            handle_aggregation_tx = addrs % (address_aggregation_limit - 1) == 0;
+            let spendable = miner_spendable_outpoints(&mut network, miner_addr).await;
+            handle_aggregation_tx = spendable.len() + 1 == address_aggregation_limit;
         }
 
         if handle_aggregation_tx {
@@ -285,6 +300,105 @@ async fn full_flow_single_miner_single_raft_with_aggregation_tx_check() {
     test_step_complete(network).await;
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn miner_aggregates_small_coinbases_raft_1_node() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_miner(11780, true, 1, 1);
+    network_config.address_aggregation_limit = Some(50);
+    network_config.mempool_seed_utxo = {
+        let small = |public_key: &str, locktime| TxOutSpec {
+            public_key: public_key.to_owned(),
+            address: None,
+            amount: TokenAmount(1),
+            locktime,
+        };
+        vec![
+            ("000000".to_owned(), vec![small(COMMON_PUB_KEY, 0); 50]),
+            ("000001".to_owned(), vec![small(SOME_PUB_KEYS[0], 1000); 5]),
+        ]
+        .into_iter()
+        .collect()
+    };
+    let mut network = Network::create_from_config(&network_config).await;
+    let active_nodes = network.all_active_nodes().clone();
+    let miner = &active_nodes[&NodeType::Miner][0];
+    let mempool = &active_nodes[&NodeType::Mempool][0];
+
+    let small_coinbases: Vec<_> = (0..50)
+        .map(|n| OutPoint::new("000000".to_owned(), n))
+        .collect();
+    let locked_coinbases: Vec<_> = (0..5)
+        .map(|n| OutPoint::new("000001".to_owned(), n))
+        .collect();
+    let keys = (COMMON_PUB_KEY, COMMON_SEC_KEY);
+    miner_seed_wallet(&mut network, miner, keys, small_coinbases.clone(), 0).await;
+    let keys = (SOME_PUB_KEYS[0], SOME_SEC_KEYS[0]);
+    miner_seed_wallet(&mut network, miner, keys, locked_coinbases.clone(), 1000).await;
+
+    //
+    // Act
+    //
+
+    // Genesis block
+    create_first_block_act(&mut network).await;
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    let spendable_before = miner_spendable_outpoints(&mut network, miner).await;
+    let genesis_coinbase = miner_current_coinbase_hash(&mut network, miner).await;
+    let spendable_amount: TokenAmount = mempool_committed_utxo_set(&mut network, mempool)
+        .await
+        .iter()
+        .filter(|(out_p, _)| {
+            out_p.t_hash == "000000" || Some(&out_p.t_hash) == genesis_coinbase.as_ref()
+        })
+        .map(|(_, tx_out)| tx_out.value.token_amount())
+        .sum();
+
+    // The miner commits its genesis winning and aggregates
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    proof_of_work_act(&mut network, CfgPow::Parallel, CfgNum::All, true, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+    let aggregation_addr = miner_has_aggregation_tx_active(&mut network, miner).await;
+
+    // The aggregation is committed and confirmed to the miner
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    proof_of_work_act(&mut network, CfgPow::Parallel, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    //
+    // Assert
+    //
+    let aggregation_addr = aggregation_addr.unwrap();
+    let spendable_after = miner_spendable_outpoints(&mut network, miner).await;
+    let committed_utxo_set = mempool_committed_utxo_set(&mut network, mempool).await;
+    let aggregated_balance =
+        mempool_get_utxo_balance_for_addresses(&mut network, mempool, vec![aggregation_addr])
+            .await
+            .get_asset_values()
+            .tokens;
+
+    assert_eq!(spendable_before.len(), 50);
+    assert_eq!(
+        spendable_after.len(),
+        2,
+        "Aggregated output and latest winning"
+    );
+    assert_eq!(aggregated_balance, spendable_amount);
+    assert!(small_coinbases
+        .iter()
+        .all(|out_p| !committed_utxo_set.contains_key(out_p)));
+    assert!(locked_coinbases
+        .iter()
+        .all(|out_p| committed_utxo_set.contains_key(out_p)));
+
+    test_step_complete(network).await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn full_flow_single_miner_single_raft_with_static_miner_address_check() {
     test_step_start();
@@ -415,6 +529,17 @@ async fn full_flow_raft_kill_mempool_node_3_nodes() {
     full_flow_tls(network_config, CfgNum::All, modify_cfg).await;
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn full_flow_no_raft_shutdown_and_restart_mempool_node() {
+    let modify_cfg = vec![
+        ("After create block 0", CfgModif::Shutdown("mempool1")),
+        ("After create block 0", CfgModif::Respawn("mempool1")),
+    ];
+
+    let network_config = complete_network_config(11770);
+    full_flow_tls(network_config, CfgNum::All, modify_cfg).await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn full_flow_raft_kill_storage_node_3_nodes() {
     let modify_cfg = vec![
@@ -572,6 +697,7 @@ async fn modify_network(network: &mut Network, tag: &str, modif_config: &[(&str,
     for (_tag, modif) in modif_config.iter().filter(|(t, _)| tag == *t) {
         match modif {
             CfgModif::Drop(v) => network.close_loops_and_drop_named(&[v.to_string()]).await,
+            CfgModif::Shutdown(v) => network.shutdown_nodes_named(&[v.to_string()]).await,
             CfgModif::Respawn(v) => {
                 let nodes = vec![v.to_string()];
                 network.re_spawn_nodes_named(&nodes).await;
@@ -602,43 +728,40 @@ async fn modify_network(network: &mut Network, tag: &str, modif_config: &[(&str,
                 }
             }
             CfgModif::Disconnect(v) => network.disconnect_nodes_named(&[v.to_string()]).await,
-            CfgModif::Reconnect(v) => {
-                network.re_connect_nodes_named(&[v.to_string()]).await;
-                let mut event_tx = network.get_local_event_tx(v).await.unwrap();
-                event_tx
-                    .send(
-                        LocalEvent::ReconnectionComplete,
-                        "reconnection complete test",
-                    )
-                    .await
-                    .unwrap();
-
-                let event = Some((
-                    v.to_string(),
-                    vec!["Sent startup requests on reconnection".to_string()],
-                ))
-                .into_iter()
-                .collect();
-                node_all_handle_different_event(network, &[v.to_string()], &event).await;
-
-                // Process miner's request at mempool node
-                if let Some(miner) = network.miner(v) {
-                    let mempool_addr = miner.lock().await.mempool_address();
-
-                    let mempool_nodes = network.all_active_nodes()[&NodeType::Mempool].clone();
-                    for c in mempool_nodes {
-                        if network.mempool(&c).unwrap().lock().await.local_address() == mempool_addr
-                        {
-                            mempool_handle_event(
-                                network,
-                                &c,
-                                &["Received partition request successfully"],
-                            )
-                            .await;
-                            break;
-                        }
-                    }
-                }
+            CfgModif::Reconnect(v) => reconnect_node_act(network, v).await,
+        }
+    }
+}
+
+async fn reconnect_node_act(network: &mut Network, name: &str) {
+    network.re_connect_nodes_named(&[name.to_string()]).await;
+    let mut event_tx = network.get_local_event_tx(name).await.unwrap();
+    event_tx
+        .send(
+            LocalEvent::ReconnectionComplete,
+            "reconnection complete test",
+        )
+        .await
+        .unwrap();
+
+    let event = Some((
+        name.to_string(),
+        vec!["Sent startup requests on reconnection".to_string()],
+    ))
+    .into_iter()
+    .collect();
+    node_all_handle_different_event(network, &[name.to_string()], &event).await;
+
+    // Process miner's request at mempool node
+    if let Some(miner) = network.miner(name) {
+        let mempool_addr = miner.lock().await.mempool_address();
+
+        let mempool_nodes = network.all_active_nodes()[&NodeType::Mempool].clone();
+        for c in mempool_nodes {
+            if network.mempool(&c).unwrap().lock().await.local_address() == mempool_addr {
+                mempool_handle_event(network, &c, &["Received partition request successfully"])
+                    .await;
+                break;
             }
         }
     }
@@ -798,6 +921,167 @@ async fn send_first_block_to_storage_common(network_config: NetworkConfig, cfg_n
     test_step_complete(network).await;
 }
 
+/// Check that a block first sent while storage is disconnected is re-sent once it
+/// reconnects, until storage acknowledges and stores it
+#[tokio::test(flavor = "current_thread")]
+async fn resend_block_to_storage_until_acknowledged() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config(11820);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let storage_nodes = vec!["storage1".to_owned()];
+    let initial_utxo_txs = network.collect_initial_uxto_txs();
+    let (expected0, block_info0) = complete_first_block(&initial_utxo_txs).await;
+
+    create_first_block_act(&mut network).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info0).await;
+
+    //
+    // Act
+    //
+    network.disconnect_nodes_named(&storage_nodes).await;
+    let (first_send, attempts) = {
+        let mut c = network.mempool("mempool1").unwrap().lock().await;
+        let first_send = c.send_block_to_storage().await;
+        (first_send, c.get_raft_status().block_to_storage_attempts)
+    };
+    network.re_connect_nodes_named(&storage_nodes).await;
+
+    let all_nodes = network.all_active_nodes_name_vec();
+    let expected_events = network.all_active_nodes_events(|t, _| match t {
+        NodeType::Storage => vec![BLOCK_RECEIVED.to_owned(), BLOCK_STORED.to_owned()],
+        NodeType::Mempool | NodeType::Miner | NodeType::User => vec![],
+    });
+    node_all_handle_different_event(&mut network, &all_nodes, &expected_events).await;
+
+    //
+    // Assert
+    //
+    assert!(first_send.is_err(), "{first_send:?}");
+    assert_eq!(attempts, 1);
+
+    let stored0 = storage_get_last_block_stored(&mut network, "storage1").await;
+    let stored0 = stored0.map(|info| (info.block_hash, info.block_num));
+    assert_eq!(stored0, Some((expected0.0, 0)));
+
+    test_step_complete(network).await;
+}
+
+/// Check that a majority of 3 mempool nodes, identified by their raft peer id,
+/// complete the blocks stored by a raft of 2 storage nodes
+#[tokio::test(flavor = "current_thread")]
+async fn send_blocks_to_storage_raft_3_mempool_2_storage() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_storage_raft(11830, 3, 2);
+    let mut network = Network::create_from_config(&network_config).await;
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+    let transactions = valid_transactions(true);
+
+    //
+    // Act
+    //
+    create_first_block_act(&mut network).await;
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+    let stored0 = storage_get_last_block_stored(&mut network, "storage1").await;
+
+    add_transactions_act(&mut network, &transactions).await;
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    proof_of_work_act(&mut network, CfgPow::Parallel, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+    let stored1 = storage_get_last_block_stored(&mut network, "storage1").await;
+
+    //
+    // Assert
+    //
+    let actual = storage_all_get_last_stored_info(&mut network, storage_nodes).await;
+    assert_eq!(equal_first(&actual), node_all(storage_nodes, true));
+
+    let stored0 = stored0.map(|info| info.block_num);
+    let stored1 = stored1.map(|info| (info.block_hash, info.block_num));
+    let last_stored = actual[0].1.clone().map(|(hash, b_num, _)| (hash, b_num));
+    assert_eq!(stored0, Some(0));
+    assert_eq!(stored1.as_ref().map(|(_, b_num)| *b_num), Some(1));
+    assert_eq!(last_stored, stored1);
+
+    test_step_complete(network).await;
+}
+
+/// Check that storage nodes receiving different subsets of the mined block parts
+/// agree through raft on the complete block they store
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_parts_subsets_to_storage_raft_3_mempool_2_storage() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_storage_raft(11880, 3, 2);
+    let mut network = Network::create_from_config(&network_config).await;
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+    let (_, block) = complete_first_block(&network.collect_initial_uxto_txs()).await;
+
+    //
+    // Act
+    //
+    storage_inject_send_block_to_storage(&mut network, "mempool1", "storage1", &block).await;
+    storage_inject_send_block_to_storage(&mut network, "mempool3", "storage2", &block).await;
+    storage_all_handle_event(&mut network, storage_nodes, BLOCK_RECEIVED).await;
+    node_all_handle_event(&mut network, storage_nodes, &[BLOCK_STORED]).await;
+
+    //
+    // Assert
+    //
+    let actual = storage_all_get_last_stored_info(&mut network, storage_nodes).await;
+    let last_stored = storage_all_get_last_block_stored(&mut network, storage_nodes).await;
+    assert_eq!(equal_first(&actual), node_all(storage_nodes, true));
+    assert_eq!(equal_first(&last_stored), node_all(storage_nodes, true));
+    assert_eq!(actual[0].1.as_ref().map(|(_, b_num, _)| *b_num), Some(0));
+
+    test_step_complete(network).await;
+}
+
+/// Check that only the configured mempool nodes contribute to the block majority
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_parts_from_non_mempool_raft_3_mempool_2_storage() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_storage_raft(11890, 3, 2);
+    let mut network = Network::create_from_config(&network_config).await;
+    let (_, block) = complete_first_block(&network.collect_initial_uxto_txs()).await;
+
+    //
+    // Act
+    //
+    storage_inject_send_block_to_storage(&mut network, "miner1", "storage1", &block).await;
+    let actual = {
+        let mut s = network.storage("storage1").unwrap().lock().await;
+        let response = s.handle_next_event(&mut test_timeout()).await;
+        response.and_then(|r| r.ok()).map(|r| (r.success, r.reason))
+    };
+    let last_stored = storage_get_last_block_stored(&mut network, "storage1").await;
+
+    //
+    // Assert
+    //
+    let rejected = "Block received not added. Not from a mempool node".to_owned();
+    assert_eq!(actual, Some((false, rejected)));
+    assert_eq!(last_stored, None);
+
+    test_step_complete(network).await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 async fn add_transactions_no_raft() {
     add_transactions(complete_network_config(10600)).await;
@@ -931,6 +1215,64 @@ async fn create_block_raft_15_nodes() {
     create_block(complete_network_config_with_n_mempool_raft(10150, 15)).await;
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn debug_raft_status_create_block_raft_3_nodes() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_raft(10660, 3);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let transactions = valid_transactions(true);
+    let (_, block_info0) = complete_first_block(&network.collect_initial_uxto_txs()).await;
+
+    create_first_block_act(&mut network).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info0).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    //
+    // Act
+    //
+    add_transactions_act(&mut network, &transactions).await;
+    let status_before = mempool_all_raft_status(&mut network, mempool_nodes).await;
+
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    let status_after = mempool_all_raft_status(&mut network, mempool_nodes).await;
+
+    //
+    // Assert
+    //
+    let leader_ids: BTreeSet<_> = status_before.iter().map(|s| s.leader_id).collect();
+    let leaders: Vec<_> = status_before.iter().filter(|s| s.is_leader).collect();
+    let first_peers: Vec<_> = status_before.iter().map(|s| s.first_raft_peer).collect();
+    assert_eq!(leader_ids.len(), 1);
+    assert!(leader_ids.iter().all(|id| id.is_some()));
+    assert_eq!(leaders.len(), 1);
+    assert_eq!(first_peers, vec![true, false, false]);
+
+    for (before, after) in status_before.iter().zip(status_after.iter()) {
+        let block_num = before.tx_current_block_num.unwrap();
+        assert_eq!(before.local_tx_pool_len, 0);
+        assert_eq!(before.consensused_tx_pool_len, transactions.len());
+        assert_eq!(
+            before.block_stored_info_proposals,
+            before.block_stored_info_votes.len()
+        );
+        assert!(before
+            .block_stored_info_votes
+            .iter()
+            .all(|votes| (1..=mempool_nodes.len()).contains(votes)));
+
+        assert_eq!(after.consensused_tx_pool_len, 0);
+        assert_eq!(after.tx_current_block_num, Some(block_num + 1));
+        assert_eq!(after.block_stored_info_proposals, 0);
+    }
+
+    test_step_complete(network).await;
+}
+
 async fn create_block(network_config: NetworkConfig) {
     create_block_common(network_config, CfgNum::All).await;
 }
@@ -1111,591 +1453,1730 @@ async fn proof_of_work_multi_raft_2_nodes() {
     proof_of_work(cfg).await;
 }
 
-async fn proof_of_work(network_config: NetworkConfig) {
-    proof_of_work_common(network_config, CfgNum::All).await;
-}
-
-async fn proof_of_work_common(network_config: NetworkConfig, cfg_num: CfgNum) {
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_multi_miners_disjoint_nonce_partitions() {
     test_step_start();
 
     //
     // Arrange
     //
+    let mut network_config = complete_network_config_with_n_mempool_miner(11760, false, 1, 3);
+    network_config.mempool_partition_full_size = 3;
+    network_config.mempool_minimum_miner_pool_len = 3;
     let mut network = Network::create_from_config(&network_config).await;
-    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let miner_nodes = &network_config.nodes[&NodeType::Miner];
 
     create_first_block_act(&mut network).await;
     create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
 
     //
     // Act
     //
-    let block_before = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
-
-    proof_of_work_act(&mut network, CfgPow::First, cfg_num, false, None).await;
-    proof_of_work_send_more_act(&mut network, cfg_num).await;
+    mempool_flood_rand_and_block_to_partition(&mut network, "mempool1").await;
+    let all_evts = block_and_partition_evt_in_miner_pow(miner_nodes, miner_nodes);
+    node_all_handle_different_event(&mut network, miner_nodes, &all_evts).await;
 
-    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+    let mempool_partitions = mempool_get_nonce_partitions(&mut network, "mempool1").await;
+    let mut found = Vec::new();
+    for miner in miner_nodes {
+        found.push(miner_get_found_nonce_partition(&mut network, miner).await);
+    }
 
     //
     // Assert
     //
-    assert_eq!(block_before, node_all(mempool_nodes, None));
-    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
+    let expected_partitions: BTreeSet<_> = vec![(0, 3), (1, 3), (2, 3)].into_iter().collect();
+    let to_index_count = |p: &NoncePartition| (p.index, p.count);
+    let mut ranges: Vec<_> = found
+        .iter()
+        .map(|(p, _)| nonce_partition_range(*p))
+        .collect();
+    ranges.sort_by_key(|range| range.start);
 
-    test_step_complete(network).await;
-}
+    assert_eq!(
+        mempool_partitions
+            .values()
+            .map(to_index_count)
+            .collect::<BTreeSet<_>>(),
+        expected_partitions
+    );
+    assert_eq!(
+        found
+            .iter()
+            .map(|(p, _)| to_index_count(p))
+            .collect::<BTreeSet<_>>(),
+        expected_partitions
+    );
+    assert!(ranges.windows(2).all(|w| w[0].end <= w[1].start));
+    for (partition, counter) in &found {
+        assert!(nonce_partition_range(*partition).contains(counter));
+    }
 
-async fn proof_of_work_act(
-    network: &mut Network,
-    cfg_pow: CfgPow,
-    cfg_num: CfgNum,
-    handle_aggregation_tx: bool,
-    prev_mining_reward: Option<TokenAmount>,
-) {
-    proof_of_work_participation_act(network, cfg_num, cfg_pow).await;
-    proof_of_work_block_act(network, cfg_num, handle_aggregation_tx, prev_mining_reward).await;
+    test_step_complete(network).await;
 }
 
-async fn proof_of_work_participation_act(network: &mut Network, cfg_num: CfgNum, cfg_pow: CfgPow) {
-    let active_nodes = network.all_active_nodes().clone();
-    let mempool_nodes = &active_nodes[&NodeType::Mempool];
-    let c_mined = &node_select(mempool_nodes, cfg_num);
-    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
-    const POWS_COMPLETE: [&str; 2] = ["Partition PoW complete", "Block PoW complete"];
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_discovered_mempool() {
+    test_step_start();
 
-    info!("Test Step Miner block Proof of Work: partition-> rand num -> num pow -> pre-block -> block pow");
-    if cfg_pow == CfgPow::First {
-        for mempool in c_mined {
-            let c_miners = &active_mempool_to_miner_mapping.get(mempool).unwrap();
-            mempool_flood_rand_and_block_to_partition(network, mempool).await;
-            miner_all_handle_event(network, c_miners, "Received random number successfully").await;
-            for miner in c_miners.iter() {
-                miner_handle_event(network, miner, "Partition PoW complete").await;
-                miner_process_found_partition_pow(network, miner).await;
-                mempool_handle_event(network, mempool, &["Partition PoW received successfully"])
-                    .await;
-            }
-        }
-
-        node_all_handle_event(network, mempool_nodes, &["Winning PoW intake open"]).await;
-    }
-}
-
-async fn proof_of_work_block_act(
-    network: &mut Network,
-    cfg_num: CfgNum,
-    handle_aggregation_tx: bool,
-    prev_mining_reward: Option<TokenAmount>,
-) {
-    let active_nodes = network.all_active_nodes().clone();
-    let mempool_nodes = &active_nodes[&NodeType::Mempool];
-    let c_mined = &node_select(mempool_nodes, cfg_num);
-    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config(10280);
+    network_config.peer_discovery = true;
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-    for mempool in c_mined {
-        let c_miners = &active_mempool_to_miner_mapping.get(mempool).unwrap();
-        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
-        let in_miners = &in_miners;
-        mempool_flood_rand_and_block_to_partition(network, mempool).await;
-        // TODO: Better to validate all miners => needs to have the miner with barrier as well.
+    //
+    // Act
+    //
+    let mempool_addr = network
+        .mempool("mempool1")
+        .unwrap()
+        .lock()
+        .await
+        .local_address();
+    let miner_mempool_addr = network
+        .miner("miner1")
+        .unwrap()
+        .lock()
+        .await
+        .mempool_address();
 
-        let all_evts = block_and_partition_evt_in_miner_pow(c_miners, in_miners);
-        node_all_handle_different_event(network, c_miners, &all_evts).await;
-        mempool_flood_transactions_to_partition(network, mempool).await;
-        miner_all_handle_event(network, in_miners, "Block is valid").await;
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
 
-        for miner in c_miners.iter() {
-            miner_process_found_partition_pow(network, miner).await;
-            // `handle_aggregation_tx` indicates that an aggregation tx has been sent by the miner
-            // Mempool node needs to handle this special case
-            if handle_aggregation_tx {
-                // Supplied as an array because these Tx event and Partition event can happen in any order
-                let results = &[
-                    "Transactions added to tx pool",
-                    "Transactions committed",
-                    "Partition PoW received successfully",
-                ];
-                mempool_handle_event(network, mempool, results).await;
-                mempool_handle_event(network, mempool, results).await;
-                mempool_handle_event(network, mempool, results).await;
-            } else {
-                // Mempool node needs to process the UTXO request sent by the miner
-                if let Some(addr) = miner_has_aggregation_tx_active(network, miner).await {
-                    mempool_handle_event(network, mempool, &["Received UTXO fetch request"]).await;
+    //
+    // Assert
+    //
+    assert_eq!(miner_mempool_addr, mempool_addr);
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
 
-                    // Send the UTXO set to the miner
-                    {
-                        let mempool_node = network.mempool(mempool).unwrap();
-                        mempool_node
-                            .lock()
-                            .await
-                            .send_fetched_utxo_set()
-                            .await
-                            .unwrap();
-                    }
+    test_step_complete(network).await;
+}
 
-                    // Miner handles the UTXO set and updates its balance
-                    miner_handle_event(network, miner, "Received UTXO set").await;
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_batch_verification_first_arrival_wins() {
+    test_step_start();
 
-                    {
-                        let miner_node = network.miner(miner).unwrap();
-                        miner_node.lock().await.update_running_total().await;
+    //
+    // Arrange
+    //
+    let miner_count = 12;
+    let mut network_config =
+        complete_network_config_with_n_mempool_miner(10700, true, 1, miner_count);
+    network_config.mempool_partition_full_size = miner_count;
+    network_config.mempool_minimum_miner_pool_len = miner_count;
+    network_config.mempool_pow_batch_window = 500;
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-                        let running_total = miner_node
-                            .lock()
-                            .await
-                            .get_wallet_db()
-                            .get_fund_store()
-                            .running_total()
-                            .clone();
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
 
-                        let utxo_bal = mempool_get_utxo_balance_for_addresses(
-                            network,
-                            mempool,
-                            vec![addr.clone()],
-                        )
-                        .await
-                        .get_asset_values()
-                        .tokens;
+    let miners = network.active_mempool_to_miner_mapping()["mempool1"].clone();
+    let in_miners = mempool_get_filtered_participants(&mut network, "mempool1", &miners).await;
 
-                        if let Some(amount) = prev_mining_reward {
-                            // Negate the mining reward from the running total as this check
-                            // happens in the next immediate block after the aggregation tx is processed.
-                            let actual_running_total = running_total.tokens - amount;
-                            assert_eq!(utxo_bal, actual_running_total);
-                        }
-                    }
-                }
-                mempool_handle_event(network, mempool, &["Partition PoW received successfully"])
-                    .await;
-            }
-        }
-        for miner in in_miners {
-            miner_process_found_block_pow(network, miner).await;
-            mempool_handle_event(network, mempool, &["Received PoW successfully"]).await;
-        }
+    //
+    // Act
+    //
+    for miner in &in_miners {
+        miner_process_found_block_pow(&mut network, miner).await;
+        // Space out submissions so their arrival order is known
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
-    node_all_handle_event(network, mempool_nodes, &["Pipeline halted"]).await;
-}
+    mempool_handle_event(&mut network, "mempool1", &["Received PoW successfully"]).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Pipeline halted"]).await;
 
-async fn proof_of_work_send_more_act(network: &mut Network, cfg_num: CfgNum) {
-    let active_nodes = network.all_active_nodes().clone();
-    let mempool_nodes = &active_nodes[&NodeType::Mempool];
-    let c_mined = &node_select(mempool_nodes, cfg_num);
-    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
+    let first_coinbase = miner_current_coinbase_hash(&mut network, &in_miners[0]).await;
+    let winning_coinbase = mempool_mined_block_coinbase_hash(&mut network, "mempool1").await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
 
-    info!("Test Step Miner block Proof of Work to late");
-    for mempool in c_mined {
-        let c_miners = active_mempool_to_miner_mapping.get(mempool).unwrap();
-        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
-        for miner in &in_miners {
-            miner_process_found_block_pow(network, miner).await;
-            mempool_handle_error(network, mempool, &["Not block currently mined"]).await;
-        }
-    }
-}
+    //
+    // Assert
+    //
+    assert_eq!(in_miners.len(), miner_count);
+    assert_eq!(winning_coinbase, first_coinbase);
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
 
-#[tokio::test(flavor = "current_thread")]
-async fn proof_winner_no_raft() {
-    proof_winner(complete_network_config(10900)).await;
+    test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn proof_winner_raft_1_node() {
-    proof_winner(complete_network_config_with_n_mempool_raft(10910, 1)).await;
-}
+async fn proof_of_work_failing_difficulty_rejected() {
+    test_step_start();
 
-#[tokio::test(flavor = "current_thread")]
-async fn proof_winner_multi_no_raft() {
-    proof_winner_multi(complete_network_config_with_n_mempool_miner(
-        10920, false, 1, 3,
-    ))
-    .await;
-}
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_miner(11870, true, 1, 1);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-#[tokio::test(flavor = "current_thread")]
-async fn proof_winner_multi_raft_1_node() {
-    proof_winner_multi(complete_network_config_with_n_mempool_miner(
-        10930, true, 1, 3,
-    ))
-    .await;
-}
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
+
+    let block = mempool_current_mining_block(&mut network, "mempool1").await;
+    let header = block.unwrap().header;
+    let reward = mempool_current_mining_reward(&mut network, "mempool1").await;
+    let coinbase = construct_coinbase_tx(header.b_num, reward, "000200".to_owned());
+    let coinbase_hash = construct_tx_hash(&coinbase);
+    let nonce = (0u64..)
+        .map(|n| n.to_le_bytes().to_vec())
+        .find(|n| {
+            !validate_pow_block(&apply_mining_tx(
+                header.clone(),
+                n.clone(),
+                coinbase_hash.clone(),
+            ))
+        })
+        .unwrap();
+    let block_before = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
 
-#[tokio::test(flavor = "current_thread")]
-async fn proof_winner_multi_raft_2_nodes() {
-    proof_winner_multi(complete_network_config_with_n_mempool_miner(
-        10940, true, 2, 6,
-    ))
-    .await;
-}
+    //
+    // Act
+    //
+    let request = MempoolRequest::SendPoW {
+        block_num: header.b_num,
+        nonce,
+        coinbase,
+    };
+    mempool_inject_next_event(&mut network, "miner1", "mempool1", request).await;
+    mempool_handle_error(&mut network, "mempool1", &["Invalid PoW for block"]).await;
 
-async fn proof_winner_multi(mut network_config: NetworkConfig) {
-    network_config.mempool_partition_full_size = 2;
-    network_config.mempool_minimum_miner_pool_len = 3;
-    proof_winner(network_config).await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+    let still_mining = mempool_current_mining_block(&mut network, "mempool1").await;
+
+    //
+    // Assert
+    //
+    assert_eq!(block_after, block_before);
+    assert!(still_mining.is_some(), "Expect still mining");
+
+    test_step_complete(network).await;
 }
 
-async fn proof_winner(network_config: NetworkConfig) {
+/// Check that pool miners share the block nonce space, and that the block PoW
+/// found by one of them is proposed with a coinbase paying it
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_pool_miners_raft_1_node() {
     test_step_start();
 
     //
     // Arrange
     //
+    let network_config = complete_network_config_with_n_mempool_miner(11890, true, 1, 1);
     let mut network = Network::create_from_config(&network_config).await;
-    let miner_nodes = &network_config.nodes[&NodeType::Miner];
-    let mining_reward = network.mining_reward();
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
     create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
+
+    let pool = mempool_pool_mining(&mut network, "mempool1").await;
+    let (id_1, mut jobs_1) = pool.lock().unwrap().connect();
+    let (id_2, mut jobs_2) = pool.lock().unwrap().connect();
+    let (id_3, jobs_3) = pool.lock().unwrap().connect();
+    for (id, address) in [(id_1, 101), (id_2, 102), (id_3, 103)] {
+        let mut pool = pool.lock().unwrap();
+        pool.subscribe(id, format!("{address:064x}")).unwrap();
+    }
 
     //
     // Act
-    // Does not allow miner reuse.
     //
-    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
-    send_block_to_storage_act(&mut network, CfgNum::All).await;
-    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    drop(jobs_3);
+    pool.lock().unwrap().disconnect(id_3);
+    let job_1 = pool_last_job(&mut jobs_1).unwrap();
+    let job_2 = pool_last_job(&mut jobs_2).unwrap();
 
-    let info_before = node_all_get_wallet_info(&mut network, miner_nodes).await;
-    proof_winner_act(&mut network).await;
-    let info_after = node_all_get_wallet_info(&mut network, miner_nodes).await;
-    let winner_index = info_after.iter().position(|i| !i.0.is_empty());
-    let winner_index = winner_index.unwrap_or_default();
+    let found = generate_pow_for_block_partitioned(
+        job_2.header.clone(),
+        Some(job_2.partition),
+        &Default::default(),
+        &AtomicU64::new(0),
+    )
+    .unwrap();
+    let request = PoolMiningRequest::Submit {
+        b_num: job_2.b_num,
+        nonce: found.nonce_and_mining_tx_hash.0.clone(),
+    };
+    let (_, solution) = pool.lock().unwrap().handle_request(id_2, request);
+    let solution = solution.unwrap();
+    let pool_coinbase = construct_tx_hash(&solution.coinbase);
+
+    mempool_submit_pool_pow(&mut network, "mempool1", solution).await;
+    mempool_handle_event(&mut network, "mempool1", &["Received PoW successfully"]).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Pipeline halted"]).await;
+
+    let winning_coinbase = mempool_mined_block_coinbase_hash(&mut network, "mempool1").await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+    let late_submit = (pool.lock().unwrap())
+        .submit(id_1, job_1.b_num, found.nonce_and_mining_tx_hash.0)
+        .err();
 
     //
     // Assert
     //
-    let expected_before = node_all(miner_nodes, (AssetValues::default(), 1, 0));
-    let mut expected_after = expected_before.clone();
-    expected_after[winner_index] = (AssetValues::new(mining_reward, Default::default()), 2, 1);
     assert_eq!(
-        info_before
-            .iter()
-            .map(|i| (i.0.clone(), i.1.len(), i.2.len()))
-            .collect::<Vec<_>>(),
-        expected_before,
-        "Info Before: {info_before:?}"
-    );
-    assert_eq!(
-        info_after
-            .iter()
-            .map(|i| (i.0.clone(), i.1.len(), i.2.len()))
-            .collect::<Vec<_>>(),
-        expected_after,
-        "Info After: {info_after:?}"
+        (job_1.partition, job_2.partition),
+        (
+            NoncePartition { index: 0, count: 2 },
+            NoncePartition { index: 1, count: 2 }
+        )
     );
+    assert_eq!(winning_coinbase, Some(pool_coinbase));
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
+    assert_eq!(late_submit, Some(PoolMiningError::NoJob));
 
     test_step_complete(network).await;
 }
 
-async fn proof_winner_act(network: &mut Network) {
-    let config = network.config().clone();
-    let active_nodes = network.all_active_nodes().clone();
-    let mempool_nodes = &active_nodes[&NodeType::Mempool];
-    let miner_nodes = &active_nodes[&NodeType::Miner];
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_whitelisted_miner_raft_1_node() {
+    proof_of_work(whitelisted_miner_network_config(10670)).await;
+}
 
-    if miner_nodes.len() < mempool_nodes.len() {
-        info!("Test Step Miner winner: Ignored/Miner re-use");
-        return;
-    }
+#[tokio::test(flavor = "current_thread")]
+async fn proof_of_work_removed_from_whitelist_raft_1_node() {
+    test_step_start();
 
-    info!("Test Step Miner winner:");
-    for mempool in mempool_nodes {
-        let c_miners = &config.mempool_to_miner_mapping.get(mempool).unwrap();
-        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
-        let in_miners = &in_miners;
+    //
+    // Arrange
+    //
+    let network_config = whitelisted_miner_network_config(10680);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-        mempool_flood_rand_and_block_to_partition(network, mempool).await;
-        let all_evts = block_and_partition_evt_in_miner_pow(c_miners, in_miners);
-        node_all_handle_different_event(network, c_miners, &all_evts).await;
-    }
-}
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
 
-#[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_no_raft() {
-    send_block_to_storage(complete_network_config(10300)).await;
-}
+    //
+    // Act
+    //
+    mempool_remove_miner_whitelist_key(&mut network, "mempool1", "key1").await;
+    miner_process_found_block_pow(&mut network, "miner1").await;
+    mempool_handle_error(&mut network, "mempool1", &["Miner not whitelisted"]).await;
 
-#[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_raft_1_node() {
-    send_block_to_storage(complete_network_config_with_n_mempool_raft(10310, 1)).await;
-}
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
 
-#[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_raft_2_nodes() {
-    send_block_to_storage(complete_network_config_with_n_mempool_raft(10320, 2)).await;
-}
+    //
+    // Assert
+    //
+    assert_eq!(block_after, node_all(mempool_nodes, None));
 
-#[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_raft_3_nodes() {
-    send_block_to_storage(complete_network_config_with_n_mempool_raft(10330, 3)).await;
+    test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_raft_majority_3_nodes() {
-    send_block_to_storage_common(
-        complete_network_config_with_n_mempool_raft(10340, 3),
-        CfgNum::Majority,
-    )
-    .await;
+async fn miner_whitelist_survives_restart_raft_1_node() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = whitelisted_miner_network_config(10690);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let added_address: SocketAddr = "127.0.0.1:12300".parse().unwrap();
+
+    //
+    // Act
+    //
+    {
+        let mut c = network.mempool("mempool1").unwrap().lock().await;
+        c.add_miner_whitelist_entries(vec!["key2".to_owned()], vec![added_address]);
+    }
+    network.close_loops_and_drop_named(mempool_nodes).await;
+    network.re_spawn_dead_nodes().await;
+
+    let whitelist = {
+        let c = network.mempool("mempool1").unwrap().lock().await;
+        c.get_miner_whitelist()
+    };
+
+    //
+    // Assert
+    //
+    let expected_keys: HashSet<String> = ["key1", "key2"].iter().map(|k| k.to_string()).collect();
+    assert!(whitelist.active);
+    assert_eq!(whitelist.miner_api_keys, Some(expected_keys));
+    assert_eq!(
+        whitelist.miner_addresses,
+        Some(std::iter::once(added_address).collect())
+    );
+
+    test_step_complete(network).await;
 }
 
-#[tokio::test(flavor = "current_thread")]
-async fn send_block_to_storage_raft_15_nodes() {
-    send_block_to_storage(complete_network_config_with_n_mempool_raft(10350, 15)).await;
+fn whitelisted_miner_network_config(initial_port: u16) -> NetworkConfig {
+    let mut network_config = complete_network_config_with_n_mempool_raft(initial_port, 1);
+    network_config.mining_api_key = Some("key1".to_owned());
+    network_config.mempool_miner_whitelist = MinerWhitelist {
+        active: true,
+        miner_api_keys: Some(std::iter::once("key1".to_owned()).collect()),
+        miner_addresses: None,
+    };
+    network_config
 }
 
-async fn send_block_to_storage(network_config: NetworkConfig) {
-    send_block_to_storage_common(network_config, CfgNum::All).await;
+async fn mempool_remove_miner_whitelist_key(network: &mut Network, mempool: &str, key: &str) {
+    let mut c = network.mempool(mempool).unwrap().lock().await;
+    c.remove_miner_whitelist_entries(vec![key.to_owned()], Vec::new());
 }
 
-async fn send_block_to_storage_common(network_config: NetworkConfig, cfg_num: CfgNum) {
+#[tokio::test(flavor = "current_thread")]
+async fn supply_routes_after_mined_blocks() {
     test_step_start();
 
     //
     // Arrange
     //
+    let network_config = complete_network_config_with_n_mempool_miner(10290, true, 1, 1);
     let mut network = Network::create_from_config(&network_config).await;
-    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
-    let storage_nodes = &network_config.nodes[&NodeType::Storage];
-
-    let transactions = valid_transactions(true);
-    let (_, block_info0) = complete_first_block(&network.collect_initial_uxto_txs()).await;
-    let (expected1, block_info1) = complete_block(1, Some("0"), &transactions).await;
-    let (_expected3, wrong_block3) = complete_block(3, Some("0"), &BTreeMap::new()).await;
-    let block1_mining_tx = complete_block_mining_txs(&block_info1);
+    let mempool = &network_config.nodes[&NodeType::Mempool][0];
+    let seed_issuance = get_total_coinbase_tokens(&network.collect_initial_uxto_txs());
 
+    //
+    // Act
+    //
     create_first_block_act(&mut network).await;
-    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info0).await;
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
     send_block_to_storage_act(&mut network, CfgNum::All).await;
 
-    mempool_all_skip_block_gen(&mut network, mempool_nodes, &block_info1).await;
-    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info1).await;
+    for _ in 0..2 {
+        create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+        proof_of_work_act(&mut network, CfgPow::Parallel, CfgNum::All, false, None).await;
+        send_block_to_storage_act(&mut network, CfgNum::All).await;
+    }
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
 
-    let initial_db_count =
-        storage_all_get_stored_key_values_count(&mut network, storage_nodes).await;
+    let total = mempool_api_get_content(&mut network, mempool, "/total_supply").await;
+    let issued = mempool_api_get_content(&mut network, mempool, "/issued_supply").await;
 
     //
-    // Act
+    // Assert
     //
-    storage_inject_send_block_to_storage(&mut network, "mempool1", "storage1", &wrong_block3).await;
-    storage_handle_event(&mut network, "storage1", BLOCK_RECEIVED).await;
+    let mut expected_issued = seed_issuance;
+    for _ in 0..3 {
+        expected_issued += calculate_reward(expected_issued);
+    }
+    let expected_total = TokenSupply::new(TOTAL_TOKENS);
+    let expected_issued = TokenSupply::new(expected_issued.0);
+    assert_eq!(total, serde_json::to_value(expected_total).unwrap());
+    assert_eq!(issued, serde_json::to_value(expected_issued).unwrap());
 
-    send_block_to_storage_act(&mut network, cfg_num).await;
+    test_step_complete(network).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn miner_outage_queued_pow_accepted_after_reconnect() {
+    test_step_start();
 
     //
-    // Assert
+    // Arrange
     //
-    let actual1 = storage_all_get_last_stored_info(&mut network, storage_nodes).await;
-    assert_eq!(
-        actual1[0],
-        (
-            Some(expected1.1),
-            Some((expected1.0, 1 /*b_num*/, 1 /*mining txs*/,))
-        )
-    );
-    assert_eq!(equal_first(&actual1), node_all(storage_nodes, true));
+    let network_config = complete_network_config_with_n_mempool_miner(10360, true, 1, 1);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-    let actual0_db_count =
-        storage_all_get_stored_key_values_count(&mut network, storage_nodes).await;
-    assert_eq!(
-        substract_vec(&actual0_db_count, &initial_db_count),
-        node_all(
-            storage_nodes,
-            1 + transactions.len() + block1_mining_tx.len()
-        )
-    );
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
 
-    test_step_complete(network).await;
-}
+    //
+    // Act
+    //
+    network.disconnect_nodes_named(&["miner1".to_owned()]).await;
+    miner_process_found_block_pow(&mut network, "miner1").await;
+    let (_, queued_in_outage, _) = miner_get_work_cache_state(&mut network, "miner1").await;
 
-async fn send_block_to_storage_act(network: &mut Network, cfg_num: CfgNum) {
-    let active_nodes = network.all_active_nodes().clone();
-    let storage_nodes = &active_nodes[&NodeType::Storage];
+    reconnect_node_act(&mut network, "miner1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Mining template status sent"]).await;
+    miner_handle_event(&mut network, "miner1", "Queued block PoW submitted").await;
+    mempool_handle_event(&mut network, "mempool1", &["Received PoW successfully"]).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Pipeline halted"]).await;
 
-    let mempool_no_mining = network
-        .active_mempool_to_miner_mapping()
-        .iter()
-        .filter(|(_, miners)| miners.is_empty())
-        .map(|(c, _)| c.clone());
-    let mut dead_nodes = network.dead_nodes().clone();
-    dead_nodes.extend(mempool_no_mining);
+    let (_, queued_after, stale_count) = miner_get_work_cache_state(&mut network, "miner1").await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
 
-    let (msg_c_nodes, msg_s_nodes) = node_combined_select(
-        &network.config().nodes[&NodeType::Mempool],
-        &network.config().nodes[&NodeType::Storage],
-        &dead_nodes,
-        cfg_num,
-    );
+    //
+    // Assert
+    //
+    assert!(queued_in_outage);
+    assert!(!queued_after);
+    assert_eq!(stale_count, 0);
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
 
-    info!("Test Step Mempool Send block to Storage");
-    mempool_all_send_block_to_storage(network, &msg_c_nodes).await;
-    storage_all_handle_event(network, &msg_s_nodes, BLOCK_RECEIVED).await;
-    node_all_handle_event(network, storage_nodes, &[BLOCK_STORED]).await;
+    test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_raft_1_node_with_file_backup() {
-    let mut network_config = complete_network_config_with_n_mempool_raft(11300, 1);
-    network_config.in_memory_db = false;
-    network_config.backup_block_modulo = Some(2);
-    remove_all_node_dbs(&network_config);
-    main_loops_raft_1_node_common(network_config, vec![3], TokenAmount(17), 20, 1, 1_000, &[]).await
-}
+async fn miner_outage_queued_pow_discarded_when_chain_advanced() {
+    test_step_start();
 
-#[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_raft_2_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11310, 2);
-    main_loops_raft_1_node_common(network_config, vec![2], TokenAmount(17), 20, 1, 1_000, &[]).await
-}
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_miner(10370, true, 1, 3);
+    network_config.mempool_partition_full_size = 2;
+    network_config.mempool_minimum_miner_pool_len = 3;
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
 
-#[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_raft_3_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11320, 3);
-    main_loops_raft_1_node_common(network_config, vec![2], TokenAmount(17), 20, 1, 1_000, &[]).await
-}
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
 
-// Slow: Only run when explicitely specified for performance and large tests
-// `RUST_LOG="info,raft=warn" cargo test --lib --release -- --ignored --nocapture main_loops_many_txs_threaded_raft_1_node`
-#[tokio::test(flavor = "multi_thread")]
-#[ignore]
-async fn main_loops_many_txs_threaded_raft_1_node() {
-    let mut network_config = complete_network_config_with_n_mempool_raft(11330, 1);
-    network_config.test_duration_divider = 1;
+    let miners = network.active_mempool_to_miner_mapping()["mempool1"].clone();
+    let in_miners = mempool_get_filtered_participants(&mut network, "mempool1", &miners).await;
+    let (outage_miner, winning_miner) = (in_miners[0].clone(), in_miners[1].clone());
 
-    // 5_000 TxIn in transactions of 3 TxIn.
-    // 10_000 TxIn available so we can create full transaction batch even if previous block did
-    // not contain all transactions already created.
-    main_loops_raft_1_node_common(
-        network_config,
-        vec![5],
-        TokenAmount(1),
-        1_000_000,
-        10_000,
-        5_000 / 3,
-        &[],
+    //
+    // Act
+    //
+    network
+        .disconnect_nodes_named(&[outage_miner.clone()])
+        .await;
+    miner_process_found_block_pow(&mut network, &outage_miner).await;
+    let (_, queued_in_outage, _) = miner_get_work_cache_state(&mut network, &outage_miner).await;
+
+    miner_process_found_block_pow(&mut network, &winning_miner).await;
+    mempool_handle_event(&mut network, "mempool1", &["Received PoW successfully"]).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Pipeline halted"]).await;
+
+    reconnect_node_act(&mut network, &outage_miner).await;
+    mempool_handle_event(&mut network, "mempool1", &["Mining template status sent"]).await;
+    miner_handle_event(
+        &mut network,
+        &outage_miner,
+        "Stale queued block PoW discarded",
     )
-    .await
+    .await;
+
+    let (_, queued_after, stale_count) =
+        miner_get_work_cache_state(&mut network, &outage_miner).await;
+
+    //
+    // Assert
+    //
+    assert!(queued_in_outage);
+    assert!(!queued_after);
+    assert_eq!(stale_count, 1);
+
+    test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_restart_raft_1_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11340, 1);
-    let stop_nums = vec![1, 2];
-    let amount = TokenAmount(17);
-    let modify = vec![("Before start 1", CfgModif::RestartEventsAll(&[]))];
+async fn miner_outage_queued_pow_kept_while_paused() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_miner(11900, true, 1, 1);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
+
+    //
+    // Act
+    //
+    network.disconnect_nodes_named(&["miner1".to_owned()]).await;
+    miner_process_found_block_pow(&mut network, "miner1").await;
+    miner_set_paused(&mut network, "miner1", true).await;
+
+    reconnect_node_act(&mut network, "miner1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Mining template status sent"]).await;
+    miner_handle_event(&mut network, "miner1", "Queued block PoW kept while paused").await;
+    let (_, queued_while_paused, _) = miner_get_work_cache_state(&mut network, "miner1").await;
+
+    miner_resume(&mut network, "miner1").await;
+    mempool_handle_event(
+        &mut network,
+        "mempool1",
+        &["Received partition request successfully"],
+    )
+    .await;
+    mempool_handle_event(&mut network, "mempool1", &["Mining template status sent"]).await;
+    miner_handle_event(&mut network, "miner1", "Queued block PoW submitted").await;
+    mempool_handle_event(&mut network, "mempool1", &["Received PoW successfully"]).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Pipeline halted"]).await;
+
+    let (_, queued_after, stale_count) = miner_get_work_cache_state(&mut network, "miner1").await;
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+
+    //
+    // Assert
+    //
+    assert!(queued_while_paused);
+    assert!(!queued_after);
+    assert_eq!(stale_count, 0);
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
+
+    test_step_complete(network).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn miner_outage_mining_halts_after_limit() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_miner(10380, true, 1, 1);
+    network_config.miner_outage_mining_secs = Some(0);
+    let mut network = Network::create_from_config(&network_config).await;
+
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+    proof_of_work_participation_act(&mut network, CfgNum::All, CfgPow::First).await;
+    proof_of_work_block_found_act(&mut network, "mempool1").await;
+    let (template_before, _, _) = miner_get_work_cache_state(&mut network, "miner1").await;
+
+    //
+    // Act
+    //
+    network.disconnect_nodes_named(&["miner1".to_owned()]).await;
+    miner_handle_event(&mut network, "miner1", "Mining halted after mempool outage").await;
+
+    let found_after = {
+        let mut m = network.miner("miner1").unwrap().lock().await;
+        m.process_found_block_pow().await
+    };
+    let (template_after, queued_after, _) =
+        miner_get_work_cache_state(&mut network, "miner1").await;
+
+    //
+    // Assert
+    //
+    assert_eq!(template_before.map(|t| t.b_num), Some(1));
+    assert_eq!(template_after, None);
+    assert!(!found_after);
+    assert!(!queued_after);
+
+    test_step_complete(network).await;
+}
+
+async fn proof_of_work(network_config: NetworkConfig) {
+    proof_of_work_common(network_config, CfgNum::All).await;
+}
+
+async fn proof_of_work_common(network_config: NetworkConfig, cfg_num: CfgNum) {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+
+    create_first_block_act(&mut network).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+
+    //
+    // Act
+    //
+    let block_before = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+
+    proof_of_work_act(&mut network, CfgPow::First, cfg_num, false, None).await;
+    proof_of_work_send_more_act(&mut network, cfg_num).await;
+
+    let block_after = mempool_all_mined_block_num(&mut network, mempool_nodes).await;
+
+    //
+    // Assert
+    //
+    assert_eq!(block_before, node_all(mempool_nodes, None));
+    assert_eq!(block_after, node_all(mempool_nodes, Some(1)));
+
+    test_step_complete(network).await;
+}
+
+async fn proof_of_work_act(
+    network: &mut Network,
+    cfg_pow: CfgPow,
+    cfg_num: CfgNum,
+    handle_aggregation_tx: bool,
+    prev_mining_reward: Option<TokenAmount>,
+) {
+    proof_of_work_participation_act(network, cfg_num, cfg_pow).await;
+    proof_of_work_block_act(network, cfg_num, handle_aggregation_tx, prev_mining_reward).await;
+}
+
+async fn proof_of_work_participation_act(network: &mut Network, cfg_num: CfgNum, cfg_pow: CfgPow) {
+    let active_nodes = network.all_active_nodes().clone();
+    let mempool_nodes = &active_nodes[&NodeType::Mempool];
+    let c_mined = &node_select(mempool_nodes, cfg_num);
+    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
+    const POWS_COMPLETE: [&str; 2] = ["Partition PoW complete", "Block PoW complete"];
+
+    info!("Test Step Miner block Proof of Work: partition-> rand num -> num pow -> pre-block -> block pow");
+    if cfg_pow == CfgPow::First {
+        for mempool in c_mined {
+            let c_miners = &active_mempool_to_miner_mapping.get(mempool).unwrap();
+            mempool_flood_rand_and_block_to_partition(network, mempool).await;
+            miner_all_handle_event(network, c_miners, "Received random number successfully").await;
+            for miner in c_miners.iter() {
+                miner_handle_event(network, miner, "Partition PoW complete").await;
+                miner_process_found_partition_pow(network, miner).await;
+                mempool_handle_event(network, mempool, &["Partition PoW received successfully"])
+                    .await;
+            }
+        }
+
+        node_all_handle_event(network, mempool_nodes, &["Winning PoW intake open"]).await;
+    }
+}
+
+async fn proof_of_work_block_act(
+    network: &mut Network,
+    cfg_num: CfgNum,
+    handle_aggregation_tx: bool,
+    prev_mining_reward: Option<TokenAmount>,
+) {
+    let active_nodes = network.all_active_nodes().clone();
+    let mempool_nodes = &active_nodes[&NodeType::Mempool];
+    let c_mined = &node_select(mempool_nodes, cfg_num);
+    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
+
+    for mempool in c_mined {
+        let c_miners = &active_mempool_to_miner_mapping.get(mempool).unwrap();
+        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
+        let in_miners = &in_miners;
+        mempool_flood_rand_and_block_to_partition(network, mempool).await;
+        // TODO: Better to validate all miners => needs to have the miner with barrier as well.
+
+        let all_evts = block_and_partition_evt_in_miner_pow(c_miners, in_miners);
+        node_all_handle_different_event(network, c_miners, &all_evts).await;
+        mempool_flood_transactions_to_partition(network, mempool).await;
+        miner_all_handle_event(network, in_miners, "Block is valid").await;
+
+        for miner in c_miners.iter() {
+            miner_process_found_partition_pow(network, miner).await;
+            // `handle_aggregation_tx` indicates that an aggregation tx has been sent by the miner
+            // Mempool node needs to handle this special case
+            if handle_aggregation_tx {
+                // Supplied as an array because these Tx event and Partition event can happen in any order
+                let results = &[
+                    "Transactions added to tx pool",
+                    "Transactions committed",
+                    "Partition PoW received successfully",
+                ];
+                mempool_handle_event(network, mempool, results).await;
+                mempool_handle_event(network, mempool, results).await;
+                mempool_handle_event(network, mempool, results).await;
+            } else {
+                // Mempool node needs to process the UTXO request sent by the miner
+                if let Some(addr) = miner_has_aggregation_tx_active(network, miner).await {
+                    mempool_handle_event(network, mempool, &["Received UTXO fetch request"]).await;
+
+                    // Send the UTXO set to the miner
+                    {
+                        let mempool_node = network.mempool(mempool).unwrap();
+                        mempool_node
+                            .lock()
+                            .await
+                            .send_fetched_utxo_set()
+                            .await
+                            .unwrap();
+                    }
+
+                    // Miner handles the UTXO set and updates its balance
+                    miner_handle_event(network, miner, "Received UTXO set").await;
+
+                    {
+                        let miner_node = network.miner(miner).unwrap();
+                        miner_node.lock().await.update_running_total().await;
+
+                        let running_total = miner_node
+                            .lock()
+                            .await
+                            .get_wallet_db()
+                            .get_fund_store()
+                            .running_total()
+                            .clone();
+
+                        let utxo_bal = mempool_get_utxo_balance_for_addresses(
+                            network,
+                            mempool,
+                            vec![addr.clone()],
+                        )
+                        .await
+                        .get_asset_values()
+                        .tokens;
+
+                        if let Some(amount) = prev_mining_reward {
+                            // Negate the mining reward from the running total as this check
+                            // happens in the next immediate block after the aggregation tx is processed.
+                            let actual_running_total = running_total.tokens - amount;
+                            assert_eq!(utxo_bal, actual_running_total);
+                        }
+                    }
+                }
+                mempool_handle_event(network, mempool, &["Partition PoW received successfully"])
+                    .await;
+            }
+        }
+        for miner in in_miners {
+            miner_process_found_block_pow(network, miner).await;
+            mempool_handle_event(network, mempool, &["Received PoW successfully"]).await;
+        }
+    }
+    node_all_handle_event(network, mempool_nodes, &["Pipeline halted"]).await;
+}
+
+/// Run the block PoW round up to the miners having found their block PoW, without sending it
+async fn proof_of_work_block_found_act(network: &mut Network, mempool: &str) {
+    let c_miners = network.active_mempool_to_miner_mapping()[mempool].clone();
+    let in_miners = mempool_get_filtered_participants(network, mempool, &c_miners).await;
+
+    mempool_flood_rand_and_block_to_partition(network, mempool).await;
+    let all_evts = block_and_partition_evt_in_miner_pow(&c_miners, &in_miners);
+    node_all_handle_different_event(network, &c_miners, &all_evts).await;
+    mempool_flood_transactions_to_partition(network, mempool).await;
+    miner_all_handle_event(network, &in_miners, "Block is valid").await;
+
+    for miner in c_miners.iter() {
+        miner_process_found_partition_pow(network, miner).await;
+        mempool_handle_event(network, mempool, &["Partition PoW received successfully"]).await;
+    }
+}
+
+async fn proof_of_work_send_more_act(network: &mut Network, cfg_num: CfgNum) {
+    let active_nodes = network.all_active_nodes().clone();
+    let mempool_nodes = &active_nodes[&NodeType::Mempool];
+    let c_mined = &node_select(mempool_nodes, cfg_num);
+    let active_mempool_to_miner_mapping = network.active_mempool_to_miner_mapping().clone();
+
+    info!("Test Step Miner block Proof of Work to late");
+    for mempool in c_mined {
+        let c_miners = active_mempool_to_miner_mapping.get(mempool).unwrap();
+        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
+        for miner in &in_miners {
+            miner_process_found_block_pow(network, miner).await;
+            mempool_handle_error(network, mempool, &["Not block currently mined"]).await;
+        }
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn proof_winner_no_raft() {
+    proof_winner(complete_network_config(10900)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn proof_winner_raft_1_node() {
+    proof_winner(complete_network_config_with_n_mempool_raft(10910, 1)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn proof_winner_multi_no_raft() {
+    proof_winner_multi(complete_network_config_with_n_mempool_miner(
+        10920, false, 1, 3,
+    ))
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn proof_winner_multi_raft_1_node() {
+    proof_winner_multi(complete_network_config_with_n_mempool_miner(
+        10930, true, 1, 3,
+    ))
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn proof_winner_multi_raft_2_nodes() {
+    proof_winner_multi(complete_network_config_with_n_mempool_miner(
+        10940, true, 2, 6,
+    ))
+    .await;
+}
+
+async fn proof_winner_multi(mut network_config: NetworkConfig) {
+    network_config.mempool_partition_full_size = 2;
+    network_config.mempool_minimum_miner_pool_len = 3;
+    proof_winner(network_config).await;
+}
+
+async fn proof_winner(network_config: NetworkConfig) {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network = Network::create_from_config(&network_config).await;
+    let miner_nodes = &network_config.nodes[&NodeType::Miner];
+    let mining_reward = network.mining_reward();
+
+    create_first_block_act(&mut network).await;
+
+    //
+    // Act
+    // Does not allow miner reuse.
+    //
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+
+    let info_before = node_all_get_wallet_info(&mut network, miner_nodes).await;
+    proof_winner_act(&mut network).await;
+    let info_after = node_all_get_wallet_info(&mut network, miner_nodes).await;
+    let winner_index = info_after.iter().position(|i| !i.0.is_empty());
+    let winner_index = winner_index.unwrap_or_default();
+
+    //
+    // Assert
+    //
+    let expected_before = node_all(miner_nodes, (AssetValues::default(), 1, 0));
+    let mut expected_after = expected_before.clone();
+    expected_after[winner_index] = (AssetValues::new(mining_reward, Default::default()), 2, 1);
+    assert_eq!(
+        info_before
+            .iter()
+            .map(|i| (i.0.clone(), i.1.len(), i.2.len()))
+            .collect::<Vec<_>>(),
+        expected_before,
+        "Info Before: {info_before:?}"
+    );
+    assert_eq!(
+        info_after
+            .iter()
+            .map(|i| (i.0.clone(), i.1.len(), i.2.len()))
+            .collect::<Vec<_>>(),
+        expected_after,
+        "Info After: {info_after:?}"
+    );
+
+    test_step_complete(network).await;
+}
+
+async fn proof_winner_act(network: &mut Network) {
+    let config = network.config().clone();
+    let active_nodes = network.all_active_nodes().clone();
+    let mempool_nodes = &active_nodes[&NodeType::Mempool];
+    let miner_nodes = &active_nodes[&NodeType::Miner];
+
+    if miner_nodes.len() < mempool_nodes.len() {
+        info!("Test Step Miner winner: Ignored/Miner re-use");
+        return;
+    }
+
+    info!("Test Step Miner winner:");
+    for mempool in mempool_nodes {
+        let c_miners = &config.mempool_to_miner_mapping.get(mempool).unwrap();
+        let in_miners = mempool_get_filtered_participants(network, mempool, c_miners).await;
+        let in_miners = &in_miners;
+
+        mempool_flood_rand_and_block_to_partition(network, mempool).await;
+        let all_evts = block_and_partition_evt_in_miner_pow(c_miners, in_miners);
+        node_all_handle_different_event(network, c_miners, &all_evts).await;
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_no_raft() {
+    send_block_to_storage(complete_network_config(10300)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_raft_1_node() {
+    send_block_to_storage(complete_network_config_with_n_mempool_raft(10310, 1)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_raft_2_nodes() {
+    send_block_to_storage(complete_network_config_with_n_mempool_raft(10320, 2)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_raft_3_nodes() {
+    send_block_to_storage(complete_network_config_with_n_mempool_raft(10330, 3)).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_raft_majority_3_nodes() {
+    send_block_to_storage_common(
+        complete_network_config_with_n_mempool_raft(10340, 3),
+        CfgNum::Majority,
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn send_block_to_storage_raft_15_nodes() {
+    send_block_to_storage(complete_network_config_with_n_mempool_raft(10350, 15)).await;
+}
+
+async fn send_block_to_storage(network_config: NetworkConfig) {
+    send_block_to_storage_common(network_config, CfgNum::All).await;
+}
+
+async fn send_block_to_storage_common(network_config: NetworkConfig, cfg_num: CfgNum) {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+
+    let transactions = valid_transactions(true);
+    let (_, block_info0) = complete_first_block(&network.collect_initial_uxto_txs()).await;
+    let (expected1, block_info1) = complete_block(1, Some("0"), &transactions).await;
+    let (_expected3, wrong_block3) = complete_block(3, Some("0"), &BTreeMap::new()).await;
+    let block1_mining_tx = complete_block_mining_txs(&block_info1);
+
+    create_first_block_act(&mut network).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info0).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    mempool_all_skip_block_gen(&mut network, mempool_nodes, &block_info1).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info1).await;
+
+    let initial_db_count =
+        storage_all_get_stored_key_values_count(&mut network, storage_nodes).await;
+
+    //
+    // Act
+    //
+    storage_inject_send_block_to_storage(&mut network, "mempool1", "storage1", &wrong_block3).await;
+    storage_handle_event(&mut network, "storage1", BLOCK_RECEIVED).await;
+
+    send_block_to_storage_act(&mut network, cfg_num).await;
+
+    //
+    // Assert
+    //
+    let actual1 = storage_all_get_last_stored_info(&mut network, storage_nodes).await;
+    assert_eq!(
+        actual1[0],
+        (
+            Some(expected1.1),
+            Some((expected1.0, 1 /*b_num*/, 1 /*mining txs*/,))
+        )
+    );
+    assert_eq!(equal_first(&actual1), node_all(storage_nodes, true));
+
+    let actual0_db_count =
+        storage_all_get_stored_key_values_count(&mut network, storage_nodes).await;
+    assert_eq!(
+        substract_vec(&actual0_db_count, &initial_db_count),
+        node_all(
+            storage_nodes,
+            1 + transactions.len() + block1_mining_tx.len()
+        )
+    );
+
+    test_step_complete(network).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn transaction_status_stages_no_raft() {
+    transaction_status_stages(complete_network_config(10740)).await;
+}
+
+async fn transaction_status_stages(network_config: NetworkConfig) {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+    let transactions = valid_transactions(true);
+    let tx_hash = transactions.keys().next().unwrap().clone();
+    let (_, block_info0) = complete_first_block(&network.collect_initial_uxto_txs()).await;
+    let (_, block_info1) = complete_block(1, Some("0"), &transactions).await;
+
+    create_first_block_act(&mut network).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info0).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    //
+    // Act
+    //
+    let unknown = mempool_transaction_stages(&mut network, "mempool1", &tx_hash).await;
+
+    add_transactions_act(&mut network, &transactions).await;
+    let pending = mempool_transaction_stages(&mut network, "mempool1", &tx_hash).await;
+
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    let in_current_block = mempool_transaction_stages(&mut network, "mempool1", &tx_hash).await;
+
+    mempool_all_skip_block_gen(&mut network, mempool_nodes, &block_info1).await;
+    mempool_all_skip_mining(&mut network, mempool_nodes, &block_info1).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+    create_block_act(&mut network, Cfg::All, CfgNum::All).await;
+    let stored = mempool_transaction_stages(&mut network, "mempool1", &tx_hash).await;
+
+    let stored_info = storage_all_get_last_stored_info(&mut network, storage_nodes).await;
+
+    //
+    // Assert
+    //
+    let (block_hash, block_num, _) = stored_info[0].1.clone().unwrap();
+    assert_eq!(unknown, vec![TxStage::Unknown]);
+    assert_eq!(pending, vec![TxStage::Pending]);
+    assert_eq!(in_current_block, vec![TxStage::InCurrentBlock]);
+    assert_eq!(
+        stored,
+        vec![TxStage::Stored {
+            block_num,
+            block_hash
+        }]
+    );
+    assert_eq!(block_num, 1);
+
+    test_step_complete(network).await;
+}
+
+async fn send_block_to_storage_act(network: &mut Network, cfg_num: CfgNum) {
+    let active_nodes = network.all_active_nodes().clone();
+    let storage_nodes = &active_nodes[&NodeType::Storage];
+
+    let mempool_no_mining = network
+        .active_mempool_to_miner_mapping()
+        .iter()
+        .filter(|(_, miners)| miners.is_empty())
+        .map(|(c, _)| c.clone());
+    let mut dead_nodes = network.dead_nodes().clone();
+    dead_nodes.extend(mempool_no_mining);
+
+    let (msg_c_nodes, msg_s_nodes) = node_combined_select(
+        &network.config().nodes[&NodeType::Mempool],
+        &network.config().nodes[&NodeType::Storage],
+        &dead_nodes,
+        cfg_num,
+    );
+
+    info!("Test Step Mempool Send block to Storage");
+    mempool_all_send_block_to_storage(network, &msg_c_nodes).await;
+    storage_all_handle_event(network, &msg_s_nodes, BLOCK_RECEIVED).await;
+    node_all_handle_event(network, storage_nodes, &[BLOCK_STORED]).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_raft_1_node_with_file_backup() {
+    let mut network_config = complete_network_config_with_n_mempool_raft(11300, 1);
+    network_config.in_memory_db = false;
+    network_config.backup_block_modulo = Some(2);
+    remove_all_node_dbs(&network_config);
+    main_loops_raft_1_node_common(network_config, vec![3], TokenAmount(17), 20, 1, 1_000, &[]).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_raft_2_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11310, 2);
+    main_loops_raft_1_node_common(network_config, vec![2], TokenAmount(17), 20, 1, 1_000, &[]).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_raft_3_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11320, 3);
+    main_loops_raft_1_node_common(network_config, vec![2], TokenAmount(17), 20, 1, 1_000, &[]).await
+}
+
+// Slow: Only run when explicitely specified for performance and large tests
+// `RUST_LOG="info,raft=warn" cargo test --lib --release -- --ignored --nocapture main_loops_many_txs_threaded_raft_1_node`
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn main_loops_many_txs_threaded_raft_1_node() {
+    let mut network_config = complete_network_config_with_n_mempool_raft(11330, 1);
+    network_config.test_duration_divider = 1;
+
+    // 5_000 TxIn in transactions of 3 TxIn.
+    // 10_000 TxIn available so we can create full transaction batch even if previous block did
+    // not contain all transactions already created.
+    main_loops_raft_1_node_common(
+        network_config,
+        vec![5],
+        TokenAmount(1),
+        1_000_000,
+        10_000,
+        5_000 / 3,
+        &[],
+    )
+    .await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_restart_raft_1_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11340, 1);
+    let stop_nums = vec![1, 2];
+    let amount = TokenAmount(17);
+    let modify = vec![("Before start 1", CfgModif::RestartEventsAll(&[]))];
+    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_restart_raft_3_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11350, 3);
+    let stop_nums = vec![1, 2];
+    let amount = TokenAmount(17);
+    let modify = vec![("Before start 1", CfgModif::RestartEventsAll(&[]))];
+    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_restart_upgrade_raft_1_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11360, 1);
+    let stop_nums = vec![1, 2];
+    let amount = TokenAmount(17);
+    let modify = vec![("Before start 1", CfgModif::RestartUpgradeEventsAll(&[]))];
+    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn main_loops_few_txs_restart_upgrade_raft_3_node() {
+    let network_config = complete_network_config_with_n_mempool_raft(11370, 3);
+    let stop_nums = vec![1, 2];
+    let amount = TokenAmount(17);
+    let modify = vec![("Before start 1", CfgModif::RestartUpgradeEventsAll(&[]))];
     main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
 }
 
+async fn main_loops_raft_1_node_common(
+    mut network_config: NetworkConfig,
+    expected_block_nums: Vec<u64>,
+    initial_amount: TokenAmount,
+    seed_count: i32,
+    seed_wallet_count: i32,
+    tx_max_count: usize,
+    modify_cfg: &[(&str, CfgModif)],
+) {
+    test_step_start();
+
+    //
+    // Arrange
+    // initial_amount is split into TokenAmount(1) TxOuts for the next round.
+    //
+    network_config.mempool_seed_utxo =
+        make_mempool_seed_utxo(&[(seed_count, "000000")], initial_amount);
+    network_config.user_test_auto_gen_setup = UserAutoGenTxSetup {
+        user_initial_transactions: (0..seed_wallet_count)
+            .map(|i| wallet_seed((i, "000000"), &initial_amount))
+            .collect::<Vec<WalletTxSpec>>(),
+        user_setup_tx_chunk_size: Some(5),
+        user_setup_tx_in_per_tx: Some(3),
+        user_setup_tx_max_count: tx_max_count,
+    };
+
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+
+    //
+    // Act
+    //
+    let (mut actual_stored, mut expected_stored) = (Vec::new(), Vec::new());
+    let (mut actual_mempool, mut expected_mempool) = (Vec::new(), Vec::new());
+    for (idx, expected_block_num) in expected_block_nums.iter().copied().enumerate() {
+        let tag = format!("Before start {idx}");
+        modify_network(&mut network, &tag, modify_cfg).await;
+
+        for node_name in mempool_nodes {
+            node_send_coordinated_shutdown(&mut network, node_name, expected_block_num).await;
+        }
+
+        let handles = network
+            .spawn_main_node_loops(TIMEOUT_TEST_WAIT_DURATION)
+            .await;
+        node_join_all_checked(handles, &"").await.unwrap();
+
+        actual_stored
+            .push(storage_all_get_last_block_stored_num(&mut network, storage_nodes).await);
+        actual_mempool
+            .push(mempool_all_committed_current_block_num(&mut network, mempool_nodes).await);
+        expected_stored.push(node_all(storage_nodes, Some(expected_block_num)));
+        expected_mempool.push(node_all(mempool_nodes, Some(expected_block_num)));
+    }
+
+    //
+    // Assert
+    //
+    assert_eq!(actual_stored, expected_stored);
+    assert_eq!(actual_mempool, expected_mempool);
+
+    test_step_complete(network).await;
+}
+
 #[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_restart_raft_3_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11350, 3);
-    let stop_nums = vec![1, 2];
-    let amount = TokenAmount(17);
-    let modify = vec![("Before start 1", CfgModif::RestartEventsAll(&[]))];
-    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
+async fn load_gen_mixed_operations_raft_1_node() {
+    test_step_start();
+
+    //
+    // Arrange
+    // Two funding wallets so DRUID pairs can trade between them.
+    //
+    let mut network_config = complete_network_config_with_n_mempool_raft(11700, 1);
+    let amount = TokenAmount(20);
+    let seed_outs: Vec<_> = (0..8).map(|i| (SOME_PUB_KEYS[i % 2], amount)).collect();
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo_with_info(&[("000000", seed_outs)]);
+    let wallet_seeds = (0..8)
+        .map(|i| WalletTxSpec {
+            out_point: format!("{}-000000", i),
+            secret_key: SOME_SEC_KEYS[i % 2].to_owned(),
+            public_key: SOME_PUB_KEYS[i % 2].to_owned(),
+            amount: amount.0,
+            address_version: None,
+        })
+        .collect();
+    let config = LoadGenConfig {
+        seed: 3,
+        operation_count: 12,
+        rate_per_sec: 20.0,
+        mix: LoadGenMix {
+            payment: 4,
+            batch: 2,
+            druid_pair: 1,
+            aggregation: 1,
+        },
+        recipient_count: 10,
+        zipf_exponent: 1.1,
+        batch_outputs: 3,
+        aggregation_inputs: 3,
+        poll_interval_ms: 100,
+        call_timeout_ms: 2_000,
+        settle_timeout_ms: 30_000,
+        wallet_seeds,
+    };
+
+    let mut network = Network::create_from_config(&network_config).await;
+    let mut target = InProcessLoadTarget::new(&network, "mempool1", "storage1").await;
+    let mut exit_txs = Vec::new();
+    for name in network_config.nodes.values().flatten() {
+        exit_txs.push(network.get_local_event_tx(name).await.unwrap());
+    }
+    let mut generator = LoadGenerator::new(config.clone());
+
+    //
+    // Act
+    //
+    let handles = network
+        .spawn_main_node_loops(TIMEOUT_TEST_WAIT_DURATION)
+        .await;
+    target
+        .wait_for_utxo(OutPoint::new("000000".to_owned(), 0))
+        .await;
+    let report = generator.run(&mut target).await;
+
+    for mut exit_tx in exit_txs {
+        let event = LocalEvent::Exit("Shutdown");
+        exit_tx.send(event, "load gen shutdown").await.unwrap();
+    }
+    node_join_all_checked(handles, &"").await.unwrap();
+
+    //
+    // Assert
+    //
+    let reached: Vec<_> = report.stages.values().map(|s| s.reached).collect();
+    let max_ms: Vec<_> = report.stages.values().map(|s| s.max_ms).collect();
+    let resolved = report.stored + report.rejected + report.starved + report.unresolved;
+
+    assert_eq!(generator.plan(), LoadGenerator::new(config).plan());
+    assert_eq!(report.operations, 12);
+    assert_eq!(resolved, report.operations);
+    assert_eq!(report.unresolved, 0);
+    assert_eq!(report.rejections, BTreeMap::new());
+    assert_eq!(report.stage_regressions, 0);
+    assert!(report.stored > 0);
+    assert!(reached.windows(2).all(|w| w[0] >= w[1]), "{reached:?}");
+    assert!(max_ms.windows(2).all(|w| w[0] <= w[1]), "{max_ms:?}");
+    assert!(serde_json::to_string(&report).is_ok());
+
+    test_step_complete(network).await;
+}
+
+/// Load target using the in-process mempool and storage nodes while their main loops run
+struct InProcessLoadTarget {
+    mempool: ThreadedCallSender<dyn MempoolApi>,
+    storage_db: Arc<std::sync::Mutex<SimpleDb>>,
+}
+
+impl InProcessLoadTarget {
+    async fn new(network: &Network, mempool: &str, storage: &str) -> Self {
+        let mempool = network.mempool(mempool).unwrap().lock().await;
+        let storage = network.storage(storage).unwrap().lock().await;
+        Self {
+            mempool: mempool.threaded_call_tx().clone(),
+            storage_db: storage.api_inputs().0,
+        }
+    }
+
+    /// Wait for the seed UTXO to be committed so funding outputs are spendable
+    async fn wait_for_utxo(&mut self, out_point: OutPoint) {
+        for _ in 0..50 {
+            let out_p = out_point.clone();
+            let committed = make_threaded_call(
+                &mut self.mempool,
+                move |m| m.get_committed_utxo_tracked_set().contains_key(&out_p),
+                "wait utxo",
+            )
+            .await
+            .unwrap();
+
+            if committed {
+                return;
+            }
+            time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("UTXO never committed: {out_point:?}");
+    }
+}
+
+#[async_trait]
+impl LoadTarget for InProcessLoadTarget {
+    async fn submit(&mut self, transactions: Vec<Transaction>) -> Result<(), String> {
+        let response = make_threaded_call(
+            &mut self.mempool,
+            move |m| m.receive_transactions(transactions),
+            "load gen submit",
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        if response.success {
+            Ok(())
+        } else {
+            Err(response.reason)
+        }
+    }
+
+    async fn observe(
+        &mut self,
+        tx_hashes: Vec<String>,
+    ) -> Result<BTreeMap<String, StageObservation>, String> {
+        let hashes = tx_hashes.clone();
+        let (status, in_block) = make_threaded_call(
+            &mut self.mempool,
+            move |m| {
+                let utxo = m.get_committed_utxo_tracked_set();
+                let in_block: BTreeSet<String> = hashes
+                    .iter()
+                    .filter(|h| utxo.contains_key(&OutPoint::new(h.to_string(), 0)))
+                    .cloned()
+                    .collect();
+                (m.get_transaction_status(hashes), in_block)
+            },
+            "load gen observe",
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let observations = tx_hashes
+            .into_iter()
+            .map(|tx_hash| {
+                let stored = get_stored_value_from_db(self.storage_db.clone(), &tx_hash).is_some();
+                let observation = match status.get(&tx_hash).map(|s| &s.status) {
+                    _ if stored => StageObservation::Reached(LoadStage::Stored),
+                    _ if in_block.contains(&tx_hash) => {
+                        StageObservation::Reached(LoadStage::InBlock)
+                    }
+                    Some(TxStatusType::Rejected) => {
+                        StageObservation::Rejected(status[&tx_hash].additional_info.clone())
+                    }
+                    Some(TxStatusType::Confirmed) => StageObservation::Reached(LoadStage::Accepted),
+                    _ => StageObservation::Unknown,
+                };
+                (tx_hash, observation)
+            })
+            .collect();
+
+        Ok(observations)
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn receive_payment_tx_user() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config(10400);
+    network_config
+        .nodes_mut(NodeType::User)
+        .push("user2".to_string());
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo(SEED_UTXO, TokenAmount(11));
+    network_config.user_wallet_seeds = vec![vec![wallet_seed(VALID_TXS_IN[0], &TokenAmount(11))]];
+    let mut network = Network::create_from_config(&network_config).await;
+    let user_nodes = &network_config.nodes[&NodeType::User];
+    let amount = TokenAmount(5);
+    let locktime = Some(5);
+
+    create_first_block_act(&mut network).await;
+
+    //
+    // Act/Assert
+    //
+    let before = node_all_get_wallet_info(&mut network, user_nodes).await;
+
+    node_connect_to(&mut network, "user1", "user2").await;
+
+    // Process requested transactions:
+    user_send_address_request(&mut network, "user1", "user2", amount, locktime).await;
+    user_handle_event(&mut network, "user2", "New address ready to be sent").await;
+
+    user_send_address_to_trading_peer(&mut network, "user2").await;
+    user_handle_event(&mut network, "user1", "Next payment transaction ready").await;
+
+    user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
+    user_handle_event(&mut network, "user2", "Payment transaction received").await;
+
+    // Ignore donations:
+    user_send_donation_address_to_peer(&mut network, "user2", "user1").await;
+    user_handle_error(&mut network, "user1", "Ignore unexpected transaction").await;
+
+    let after_payment_user1_user2 = node_all_get_wallet_info(&mut network, user_nodes).await;
+
+    // Handle trying to spend locked funds:
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 0).await; // Create next block
+    user_send_address_request(&mut network, "user2", "user1", amount, None).await;
+    user_handle_event(&mut network, "user1", "New address ready to be sent").await;
+
+    user_send_address_to_trading_peer(&mut network, "user1").await;
+    // Insufficient funds due to locked funds:
+    user_handle_event_failure(&mut network, "user2", "Insufficient funds for payment").await;
+
+    // Handle trying to spend unlocked funds:
+    for b_num in 1..10 {
+        // Create next block
+        create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, b_num).await;
+        // Update locked coinbase
+        users_filter_locked_coinbase(&mut network, &["user1", "user2"], b_num).await;
+    }
+
+    user_send_address_request(&mut network, "user2", "user1", amount, None).await;
+    user_handle_event(&mut network, "user1", "New address ready to be sent").await;
+
+    user_send_address_to_trading_peer(&mut network, "user1").await;
+    user_handle_event(&mut network, "user2", "Next payment transaction ready").await;
+
+    user_send_next_payment_to_destinations(&mut network, "user2", "mempool1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
+    user_handle_event(&mut network, "user1", "Payment transaction received").await;
+
+    let after_payment_user2_user1 = node_all_get_wallet_info(&mut network, user_nodes).await;
+
+    //
+    // Assert
+    //
+    assert_eq!(
+        before
+            .iter()
+            .map(|(total, _, _)| total.clone())
+            .collect::<Vec<_>>(),
+        vec![AssetValues::token_u64(11), AssetValues::token_u64(0),]
+    );
+    assert_eq!(
+        after_payment_user1_user2
+            .iter()
+            .map(|(total, _, _)| total.clone())
+            .collect::<Vec<_>>(),
+        vec![AssetValues::token_u64(6), AssetValues::token_u64(5)]
+    );
+    assert_eq!(
+        after_payment_user2_user1
+            .iter()
+            .map(|(total, _, _)| total.clone())
+            .collect::<Vec<_>>(),
+        vec![AssetValues::token_u64(11), AssetValues::token_u64(0),]
+    );
+
+    test_step_complete(network).await;
 }
 
-#[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_restart_upgrade_raft_1_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11360, 1);
-    let stop_nums = vec![1, 2];
-    let amount = TokenAmount(17);
-    let modify = vec![("Before start 1", CfgModif::RestartUpgradeEventsAll(&[]))];
-    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
-}
+#[tokio::test(flavor = "current_thread")]
+async fn receive_payment_tx_user_payment_events() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config(11850);
+    network_config
+        .nodes_mut(NodeType::User)
+        .push("user2".to_string());
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo(SEED_UTXO, TokenAmount(11));
+    network_config.user_wallet_seeds = vec![vec![wallet_seed(VALID_TXS_IN[0], &TokenAmount(11))]];
+    let mut network = Network::create_from_config(&network_config).await;
+    let amount = TokenAmount(5);
+
+    create_first_block_act(&mut network).await;
+    user_send_block_notification_request(&mut network, "user2").await;
+    mempool_handle_event(&mut network, "mempool1", &["Received block notification"]).await;
+
+    let payment_events = network.user("user2").unwrap().lock().await.payment_events();
+    let filter = routes::payment_events(
+        &mut Vec::new(),
+        payment_events.clone(),
+        Default::default(),
+        Default::default(),
+    )
+    .recover(handle_rejection);
+    let mut client = warp::test::ws()
+        .path("/payment_events")
+        .header("x-cache-id", "2ae7bc9cba924e3cb73c0249893078d7")
+        .handshake(filter)
+        .await
+        .unwrap();
+    while payment_events.receiver_count() == 0 {
+        time::sleep(Duration::from_millis(1)).await;
+    }
+
+    //
+    // Act
+    //
+    node_connect_to(&mut network, "user1", "user2").await;
+    user_send_address_request(&mut network, "user1", "user2", amount, None).await;
+    user_handle_event(&mut network, "user2", "New address ready to be sent").await;
+
+    user_send_address_to_trading_peer(&mut network, "user2").await;
+    user_handle_event(&mut network, "user1", "Next payment transaction ready").await;
+
+    user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
+    user_handle_event(&mut network, "user2", "Payment transaction received").await;
+    let pending = client.recv().await.unwrap();
+
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 0).await;
+    mempool_flood_block_to_users(&mut network, "mempool1").await;
+    user_handle_event(&mut network, "user2", "Block mining notified").await;
+    let confirmed = client.recv().await.unwrap();
 
-#[tokio::test(flavor = "current_thread")]
-async fn main_loops_few_txs_restart_upgrade_raft_3_node() {
-    let network_config = complete_network_config_with_n_mempool_raft(11370, 3);
-    let stop_nums = vec![1, 2];
-    let amount = TokenAmount(17);
-    let modify = vec![("Before start 1", CfgModif::RestartUpgradeEventsAll(&[]))];
-    main_loops_raft_1_node_common(network_config, stop_nums, amount, 20, 1, 1_000, &modify).await
+    //
+    // Assert
+    //
+    let pending: PaymentEvent = serde_json::from_str(pending.to_str().unwrap()).unwrap();
+    let confirmed: PaymentEvent = serde_json::from_str(confirmed.to_str().unwrap()).unwrap();
+    assert_eq!(pending.event, PaymentEventKind::Pending);
+    assert_eq!(pending.amount, Asset::Token(amount));
+    assert_eq!(
+        confirmed,
+        PaymentEvent {
+            event: PaymentEventKind::Confirmed,
+            ..pending
+        }
+    );
+
+    test_step_complete(network).await;
 }
 
-async fn main_loops_raft_1_node_common(
-    mut network_config: NetworkConfig,
-    expected_block_nums: Vec<u64>,
-    initial_amount: TokenAmount,
-    seed_count: i32,
-    seed_wallet_count: i32,
-    tx_max_count: usize,
-    modify_cfg: &[(&str, CfgModif)],
-) {
+#[tokio::test(flavor = "current_thread")]
+async fn make_payment_with_change_user() {
     test_step_start();
 
     //
     // Arrange
-    // initial_amount is split into TokenAmount(1) TxOuts for the next round.
     //
-    network_config.mempool_seed_utxo =
-        make_mempool_seed_utxo(&[(seed_count, "000000")], initial_amount);
-    network_config.user_test_auto_gen_setup = UserAutoGenTxSetup {
-        user_initial_transactions: (0..seed_wallet_count)
-            .map(|i| wallet_seed((i, "000000"), &initial_amount))
-            .collect::<Vec<WalletTxSpec>>(),
-        user_setup_tx_chunk_size: Some(5),
-        user_setup_tx_in_per_tx: Some(3),
-        user_setup_tx_max_count: tx_max_count,
-    };
-
+    let mut network_config = complete_network_config(10650);
+    network_config
+        .nodes_mut(NodeType::User)
+        .push("user2".to_string());
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo(SEED_UTXO, TokenAmount(11));
+    network_config.user_wallet_seeds = vec![vec![wallet_seed(VALID_TXS_IN[0], &TokenAmount(11))]];
     let mut network = Network::create_from_config(&network_config).await;
-    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
-    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+    let user_nodes = &network_config.nodes[&NodeType::User];
+
+    create_first_block_act(&mut network).await;
+    let payee_address = user_generate_static_address_for_miner(&mut network, "user2").await;
 
     //
     // Act
     //
-    let (mut actual_stored, mut expected_stored) = (Vec::new(), Vec::new());
-    let (mut actual_mempool, mut expected_mempool) = (Vec::new(), Vec::new());
-    for (idx, expected_block_num) in expected_block_nums.iter().copied().enumerate() {
-        let tag = format!("Before start {idx}");
-        modify_network(&mut network, &tag, modify_cfg).await;
+    let insufficient_payment = user_make_payment(&mut network, "user1", &payee_address, 12).await;
+    let change_payment = user_make_payment(&mut network, "user1", &payee_address, 4).await;
 
-        for node_name in mempool_nodes {
-            node_send_coordinated_shutdown(&mut network, node_name, expected_block_num).await;
-        }
+    user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 1).await;
 
-        let handles = network
-            .spawn_main_node_loops(TIMEOUT_TEST_WAIT_DURATION)
-            .await;
-        node_join_all_checked(handles, &"").await.unwrap();
+    let after = node_all_get_wallet_info(&mut network, user_nodes).await;
 
-        actual_stored
-            .push(storage_all_get_last_block_stored_num(&mut network, storage_nodes).await);
-        actual_mempool
-            .push(mempool_all_committed_current_block_num(&mut network, mempool_nodes).await);
-        expected_stored.push(node_all(storage_nodes, Some(expected_block_num)));
-        expected_mempool.push(node_all(mempool_nodes, Some(expected_block_num)));
-    }
+    // Spending the whole change leaves nothing to return:
+    let exact_payment = user_make_payment(&mut network, "user1", &payee_address, 7).await;
 
     //
     // Assert
     //
-    assert_eq!(actual_stored, expected_stored);
-    assert_eq!(actual_mempool, expected_mempool);
+    assert_eq!(
+        (
+            insufficient_payment.success,
+            insufficient_payment.reason.as_str()
+        ),
+        (false, "Insufficient funds for payment")
+    );
+
+    let change_address = change_payment.change_address.unwrap();
+    assert!(change_payment.success);
+    assert_eq!(change_payment.change_amount, Some(TokenAmount(7)));
+
+    let (user1_total, user1_addresses, user1_txs) = &after[0];
+    assert_eq!(user1_total, &AssetValues::token_u64(7));
+    assert!(user1_addresses.contains(&change_address));
+    assert_eq!(
+        user1_txs.values().cloned().collect::<Vec<_>>(),
+        vec![(change_address, Asset::token_u64(7))]
+    );
+    assert_eq!(
+        (exact_payment.success, exact_payment.change_address),
+        (true, None)
+    );
 
     test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn receive_payment_tx_user() {
+async fn payment_address_from_public_key() {
+    let (public_key, _) = sign::gen_keypair();
+    let public_key_vec: Vec<u8> = public_key.as_ref().to_vec();
+    let pub_key = PublicKey::from_slice(&public_key_vec).unwrap();
+    assert_eq!(pub_key, public_key);
+
+    construct_address(&pub_key);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn receive_testnet_donation_payment_tx_user() {
     test_step_start();
 
     //
     // Arrange
     //
-    let mut network_config = complete_network_config(10400);
+    let mut network_config = complete_network_config(10405);
     network_config
         .nodes_mut(NodeType::User)
         .push("user2".to_string());
+    network_config.user_auto_donate = 5;
     network_config.mempool_seed_utxo = make_mempool_seed_utxo(SEED_UTXO, TokenAmount(11));
     network_config.user_wallet_seeds = vec![vec![wallet_seed(VALID_TXS_IN[0], &TokenAmount(11))]];
     let mut network = Network::create_from_config(&network_config).await;
     let user_nodes = &network_config.nodes[&NodeType::User];
-    let amount = TokenAmount(5);
-    let locktime = Some(5);
 
     create_first_block_act(&mut network).await;
 
@@ -1706,203 +3187,259 @@ async fn receive_payment_tx_user() {
 
     node_connect_to(&mut network, "user1", "user2").await;
 
-    // Process requested transactions:
-    user_send_address_request(&mut network, "user1", "user2", amount, locktime).await;
-    user_handle_event(&mut network, "user2", "New address ready to be sent").await;
-
-    user_send_address_to_trading_peer(&mut network, "user2").await;
+    user_send_donation_address_to_peer(&mut network, "user2", "user1").await;
     user_handle_event(&mut network, "user1", "Next payment transaction ready").await;
 
     user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
     mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
-    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
     user_handle_event(&mut network, "user2", "Payment transaction received").await;
 
-    // Ignore donations:
-    user_send_donation_address_to_peer(&mut network, "user2", "user1").await;
-    user_handle_error(&mut network, "user1", "Ignore unexpected transaction").await;
+    let after = node_all_get_wallet_info(&mut network, user_nodes).await;
 
-    let after_payment_user1_user2 = node_all_get_wallet_info(&mut network, user_nodes).await;
+    //
+    // Assert
+    //
+    assert_eq!(
+        before
+            .iter()
+            .map(|(total, _, _)| total.clone())
+            .collect::<Vec<_>>(),
+        vec![AssetValues::token_u64(11), AssetValues::token_u64(0)]
+    );
+    assert_eq!(
+        after
+            .iter()
+            .map(|(total, _, _)| total.clone())
+            .collect::<Vec<_>>(),
+        vec![AssetValues::token_u64(6), AssetValues::token_u64(5)]
+    );
 
-    // Handle trying to spend locked funds:
-    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 0).await; // Create next block
-    user_send_address_request(&mut network, "user2", "user1", amount, None).await;
-    user_handle_event(&mut network, "user1", "New address ready to be sent").await;
+    test_step_complete(network).await;
+}
 
-    user_send_address_to_trading_peer(&mut network, "user1").await;
-    // Insufficient funds due to locked funds:
-    user_handle_event_failure(&mut network, "user2", "Insufficient funds for payment").await;
+#[tokio::test(flavor = "current_thread")]
+async fn reject_payment_txs() {
+    test_step_start();
 
-    // Handle trying to spend unlocked funds:
-    for b_num in 1..10 {
-        // Create next block
-        create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, b_num).await;
-        // Update locked coinbase
-        users_filter_locked_coinbase(&mut network, &["user1", "user2"], b_num).await;
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config(10410);
+    network_config
+        .nodes_mut(NodeType::User)
+        .push("user2".to_string());
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+
+    let valid_txs = valid_transactions(true);
+    let invalid_txs = vec![
+        // New keys not matching utxo_set
+        valid_transactions_with(false, DEFAULT_SEED_AMOUNT, false),
+        // Too much output amount for given inputs
+        valid_transactions_with(true, DEFAULT_SEED_AMOUNT + TokenAmount(1), false),
+        // Too little output amount for given inputs
+        valid_transactions_with(true, DEFAULT_SEED_AMOUNT - TokenAmount(1), false),
+        // Invalid script
+        {
+            let (k, v) = valid_txs.iter().next().unwrap();
+            let (k, mut v) = (k.clone(), v.clone());
+            v.inputs[0].script_signature.stack.push(StackEntry::Num(0));
+            Some((k, v)).into_iter().collect()
+        },
+    ];
+
+    create_first_block_act(&mut network).await;
+
+    //
+    // Act/Assert
+    //
+    for tx in invalid_txs.iter().flat_map(|txs| txs.values()) {
+        user_send_transaction_to_mempool(&mut network, "user2", "mempool1", tx).await;
+    }
+    for tx in invalid_txs.iter().flat_map(|txs| txs.values()) {
+        let reason = rejected_txs_reason("No valid transactions provided", &[tx.clone()]);
+        mempool_handle_error(&mut network, "mempool1", &[reason.as_str()]).await;
+    }
+    add_transactions_act(&mut network, &valid_txs).await;
+
+    //
+    // Assert
+    //
+    let actual = mempool_all_committed_tx_pool(&mut network, mempool_nodes).await;
+    assert_eq!(actual[0], valid_txs);
+    assert_eq!(equal_first(&actual), node_all(mempool_nodes, true));
+
+    test_step_complete(network).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reject_invalid_signature_txs() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config(10415);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+
+    let valid_txs = valid_transactions(true);
+    let sk = decode_secret_key(COMMON_SEC_KEY).unwrap();
+    let invalid_txs: Vec<_> = valid_txs
+        .values()
+        .map(|tx| {
+            let mut tx = tx.clone();
+            for entry in tx.inputs[0].script_signature.stack.iter_mut() {
+                if let StackEntry::Signature(sig) = entry {
+                    *sig = sign::sign_detached(b"corrupted", &sk);
+                }
+            }
+            tx
+        })
+        .collect();
+    let (valid_0, valid_1) = {
+        let mut txs = valid_txs.values().cloned();
+        (txs.next().unwrap(), txs.next().unwrap())
+    };
+
+    // Small batch verified on the event loop, large one on a blocking worker
+    let small_batch = vec![valid_0, invalid_txs[0].clone(), invalid_txs[1].clone()];
+    let large_batch: Vec<_> = std::iter::once(valid_1)
+        .chain(invalid_txs.iter().cloned().cycle())
+        .take(TX_VERIFICATION_BLOCKING_THRESHOLD + 1)
+        .collect();
+
+    create_first_block_act(&mut network).await;
+
+    //
+    // Act
+    //
+    let reason = rejected_txs_reason(
+        "Some transactions invalid. Adding valid transactions only",
+        &invalid_txs,
+    );
+    for batch in [small_batch, large_batch] {
+        user_send_transactions_to_mempool(&mut network, "user1", "mempool1", batch).await;
+        mempool_handle_event(&mut network, "mempool1", &[reason.as_str()]).await;
+        node_all_handle_event(&mut network, mempool_nodes, &["Transactions committed"]).await;
     }
 
-    user_send_address_request(&mut network, "user2", "user1", amount, None).await;
-    user_handle_event(&mut network, "user1", "New address ready to be sent").await;
+    //
+    // Assert
+    //
+    let actual = mempool_all_committed_tx_pool(&mut network, mempool_nodes).await;
+    assert_eq!(actual[0], valid_txs);
+    assert_eq!(equal_first(&actual), node_all(mempool_nodes, true));
 
-    user_send_address_to_trading_peer(&mut network, "user1").await;
-    user_handle_event(&mut network, "user2", "Next payment transaction ready").await;
+    test_step_complete(network).await;
+}
 
-    user_send_next_payment_to_destinations(&mut network, "user2", "mempool1").await;
+#[tokio::test(flavor = "current_thread")]
+async fn reject_double_spend_txs() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config(11750);
+    let mut network = Network::create_from_config(&network_config).await;
+
+    let pk = decode_pub_key(COMMON_PUB_KEY).unwrap();
+    let sk = decode_secret_key(COMMON_SEC_KEY).unwrap();
+    let (ins, amount) = (&VALID_TXS_IN[0..1], DEFAULT_SEED_AMOUNT);
+    let (_, tx) =
+        create_valid_transaction_with_ins_outs(ins, &VALID_TXS_OUT[0..1], &pk, &sk, amount, None);
+    let (_, double_spend_tx) =
+        create_valid_transaction_with_ins_outs(ins, &VALID_TXS_OUT[1..2], &pk, &sk, amount, None);
+
+    create_first_block_act(&mut network).await;
+
+    //
+    // Act
+    //
+    user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &tx).await;
     mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
     mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
-    user_handle_event(&mut network, "user1", "Payment transaction received").await;
 
-    let after_payment_user2_user1 = node_all_get_wallet_info(&mut network, user_nodes).await;
+    user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &double_spend_tx).await;
+    mempool_handle_error(
+        &mut network,
+        "mempool1",
+        &["Double spend transactions provided"],
+    )
+    .await;
+
+    let double_spend_count = mempool_double_spend_count(&mut network, "mempool1").await;
 
     //
     // Assert
     //
-    assert_eq!(
-        before
-            .iter()
-            .map(|(total, _, _)| total.clone())
-            .collect::<Vec<_>>(),
-        vec![AssetValues::token_u64(11), AssetValues::token_u64(0),]
-    );
-    assert_eq!(
-        after_payment_user1_user2
-            .iter()
-            .map(|(total, _, _)| total.clone())
-            .collect::<Vec<_>>(),
-        vec![AssetValues::token_u64(6), AssetValues::token_u64(5)]
-    );
-    assert_eq!(
-        after_payment_user2_user1
-            .iter()
-            .map(|(total, _, _)| total.clone())
-            .collect::<Vec<_>>(),
-        vec![AssetValues::token_u64(11), AssetValues::token_u64(0),]
-    );
+    assert_eq!(double_spend_count, 1);
 
     test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn payment_address_from_public_key() {
-    let (public_key, _) = sign::gen_keypair();
-    let public_key_vec: Vec<u8> = public_key.as_ref().to_vec();
-    let pub_key = PublicKey::from_slice(&public_key_vec).unwrap();
-    assert_eq!(pub_key, public_key);
-
-    construct_address(&pub_key);
-}
-
-#[tokio::test(flavor = "current_thread")]
-async fn receive_testnet_donation_payment_tx_user() {
+async fn spend_address_seed_utxo_raft_3_nodes() {
     test_step_start();
 
     //
     // Arrange
     //
-    let mut network_config = complete_network_config(10405);
-    network_config
-        .nodes_mut(NodeType::User)
-        .push("user2".to_string());
-    network_config.user_auto_donate = 5;
-    network_config.mempool_seed_utxo = make_mempool_seed_utxo(SEED_UTXO, TokenAmount(11));
-    network_config.user_wallet_seeds = vec![vec![wallet_seed(VALID_TXS_IN[0], &TokenAmount(11))]];
+    let mut network_config = complete_network_config_with_n_mempool_raft(11790, 3);
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo_for_address(None);
     let mut network = Network::create_from_config(&network_config).await;
-    let user_nodes = &network_config.nodes[&NodeType::User];
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let txs = valid_transactions(true);
 
     create_first_block_act(&mut network).await;
 
     //
-    // Act/Assert
+    // Act
     //
-    let before = node_all_get_wallet_info(&mut network, user_nodes).await;
-
-    node_connect_to(&mut network, "user1", "user2").await;
-
-    user_send_donation_address_to_peer(&mut network, "user2", "user1").await;
-    user_handle_event(&mut network, "user1", "Next payment transaction ready").await;
-
-    user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
-    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
-    user_handle_event(&mut network, "user2", "Payment transaction received").await;
-
-    let after = node_all_get_wallet_info(&mut network, user_nodes).await;
+    add_transactions_act(&mut network, &txs).await;
 
     //
     // Assert
     //
-    assert_eq!(
-        before
-            .iter()
-            .map(|(total, _, _)| total.clone())
-            .collect::<Vec<_>>(),
-        vec![AssetValues::token_u64(11), AssetValues::token_u64(0)]
-    );
-    assert_eq!(
-        after
-            .iter()
-            .map(|(total, _, _)| total.clone())
-            .collect::<Vec<_>>(),
-        vec![AssetValues::token_u64(6), AssetValues::token_u64(5)]
-    );
+    let actual = mempool_all_committed_tx_pool(&mut network, mempool_nodes).await;
+    assert_eq!(actual[0], txs);
+    assert_eq!(equal_first(&actual), node_all(mempool_nodes, true));
 
     test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn reject_payment_txs() {
+async fn reject_locked_seed_utxo_txs() {
     test_step_start();
 
     //
     // Arrange
     //
-    let mut network_config = complete_network_config(10410);
-    network_config
-        .nodes_mut(NodeType::User)
-        .push("user2".to_string());
+    let mut network_config = complete_network_config(11810);
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo_for_address(Some(1000));
     let mut network = Network::create_from_config(&network_config).await;
     let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
-
-    let valid_txs = valid_transactions(true);
-    let invalid_txs = vec![
-        // New keys not matching utxo_set
-        valid_transactions_with(false, DEFAULT_SEED_AMOUNT, false),
-        // Too much output amount for given inputs
-        valid_transactions_with(true, DEFAULT_SEED_AMOUNT + TokenAmount(1), false),
-        // Too little output amount for given inputs
-        valid_transactions_with(true, DEFAULT_SEED_AMOUNT - TokenAmount(1), false),
-        // Invalid script
-        {
-            let (k, v) = valid_txs.iter().next().unwrap();
-            let (k, mut v) = (k.clone(), v.clone());
-            v.inputs[0].script_signature.stack.push(StackEntry::Num(0));
-            Some((k, v)).into_iter().collect()
-        },
-    ];
+    let txs = valid_transactions(true);
 
     create_first_block_act(&mut network).await;
 
     //
-    // Act/Assert
+    // Act
     //
-    for tx in invalid_txs.iter().flat_map(|txs| txs.values()) {
-        user_send_transaction_to_mempool(&mut network, "user2", "mempool1", tx).await;
+    for tx in txs.values() {
+        user_send_transaction_to_mempool(&mut network, "user1", "mempool1", tx).await;
     }
-    for _tx in invalid_txs.iter().flat_map(|txs| txs.values()) {
-        mempool_handle_error(
-            &mut network,
-            "mempool1",
-            &["No valid transactions provided"],
-        )
-        .await;
+    for tx in txs.values() {
+        let reason = rejected_txs_reason("No valid transactions provided", &[tx.clone()]);
+        mempool_handle_error(&mut network, "mempool1", &[reason.as_str()]).await;
     }
-    add_transactions_act(&mut network, &valid_txs).await;
 
     //
     // Assert
     //
     let actual = mempool_all_committed_tx_pool(&mut network, mempool_nodes).await;
-    assert_eq!(actual[0], valid_txs);
-    assert_eq!(equal_first(&actual), node_all(mempool_nodes, true));
+    assert_eq!(actual[0], BTreeMap::new());
 
     test_step_complete(network).await;
 }
@@ -2035,6 +3572,21 @@ async fn proof_of_work_reject() {
         mempool_inject_next_event(&mut network, miner, mempool, request).await;
         mempool_handle_error(&mut network, mempool, &["Coinbase transaction invalid"]).await;
     }
+    {
+        // For miner in partition and correct block, but wrong coinbase amount
+        let request = MempoolRequest::SendPoW {
+            block_num,
+            nonce: Default::default(),
+            coinbase: construct_coinbase_tx(block_num, TokenAmount(1), "000200".to_owned()),
+        };
+        mempool_inject_next_event(&mut network, miner, mempool, request).await;
+        mempool_handle_error(
+            &mut network,
+            mempool,
+            &["Coinbase amount does not match block reward"],
+        )
+        .await;
+    }
 
     //
     // Assert
@@ -2384,31 +3936,40 @@ async fn miner_request_blockchain_item(
 }
 
 #[tokio::test(flavor = "current_thread")]
-async fn catchup_fetch_blockchain_item_raft() {
+async fn catchup_fetch_block_range_raft() {
+    test_step_start();
+    let mut network_config = complete_network_config_with_n_mempool_raft(11420, 3);
+    network_config.test_duration_divider = 10;
+    catchup_fetch_block_range_common(network_config, 3).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn catchup_fetch_block_range_10_blocks_raft() {
     test_step_start();
+    let mut network_config = complete_network_config_with_n_mempool_raft(10730, 3);
+    network_config.test_duration_divider = 10;
+    catchup_fetch_block_range_common(network_config, 11).await;
+}
 
+async fn catchup_fetch_block_range_common(network_config: NetworkConfig, block_count: usize) {
     //
     // Arrange
     //
-    let mut network_config = complete_network_config_with_n_mempool_raft(11420, 3);
-    network_config.test_duration_divider = 10;
-
     let mut network = Network::create_from_config(&network_config).await;
     let storage_nodes = &network_config.nodes[&NodeType::Storage];
     let transactions = vec![network.collect_initial_uxto_txs(), valid_transactions(true)];
-    let all_txs = transactions.iter().cloned();
-    let _all_txs: Vec<Vec<_>> = all_txs.map(|v| v.into_iter().collect()).collect();
-    let ((_block_keys, _), blocks) = complete_blocks(3, &transactions).await;
+    let ((block_keys, _), blocks) = complete_blocks(block_count, &transactions).await;
 
     let tx_lens = transactions.iter().map(|v| v.len());
     let items_counts = tx_lens.chain(std::iter::repeat(0)).map(|tx| 2 + tx);
     let items_counts: Vec<usize> = items_counts.take(blocks.len()).collect();
 
+    let respawn_tag = format!("Before store block {block_count}");
     let modify_cfg = vec![
         ("Before store block 1", CfgModif::Drop("storage3")),
-        ("Before store block 3", CfgModif::Respawn("storage3")),
+        (respawn_tag.as_str(), CfgModif::Respawn("storage3")),
         (
-            "Before store block 3",
+            respawn_tag.as_str(),
             CfgModif::HandleEvents(&[
                 ("storage3", "Snapshot applied"),
                 ("storage3", "Snapshot applied: Fetch missing blocks"),
@@ -2433,34 +3994,23 @@ async fn catchup_fetch_blockchain_item_raft() {
         node_all_handle_event(&mut network, &storage_nodes, &[BLOCK_STORED]).await;
     }
 
-    let tag = "Before store block 3";
-    modify_network(&mut network, tag, &modify_cfg).await;
+    modify_network(&mut network, &respawn_tag, &modify_cfg).await;
 
     // Process event and treat it as messaged lost => switch to storage2
     storage_handle_event(&mut network, "storage3", "Catch up stored blocks").await;
 
-    for (b_num, items_count) in items_counts.iter().copied().enumerate().skip(1) {
-        for i in 0..items_count {
-            info!(
-                "Test Step Storage catchup b_num={} (count={}) i={}",
-                b_num, items_count, i,
-            );
-
-            let network = &mut network;
-            let receive_evt = if i < items_count - 1 {
-                "Blockchain item received"
-            } else if b_num < items_counts.len() - 1 {
-                "Blockchain item received: Block stored"
-            } else {
-                "Blockchain item received: Block stored(Done)"
-            };
-
-            storage_handle_event(network, "storage3", "Catch up stored blocks").await;
-            storage_catchup_fetch_blockchain_item(network, "storage3").await;
-            storage_handle_event(network, "storage2", "Blockchain item fetched from storage").await;
-            storage_send_blockchain_item(network, "storage2").await;
-            storage_handle_event(network, "storage3", receive_evt).await;
-        }
+    {
+        let network = &mut network;
+        storage_handle_event(network, "storage3", "Catch up stored blocks").await;
+        storage_catchup_fetch_block_range(network, "storage3").await;
+        storage_handle_event(network, "storage2", "Block range fetched from storage").await;
+        storage_send_block_range(network, "storage2").await;
+        storage_handle_event(
+            network,
+            "storage3",
+            "Block range received: Block stored(Done)",
+        )
+        .await;
     }
 
     //
@@ -2471,6 +4021,17 @@ async fn catchup_fetch_blockchain_item_raft() {
         storage_all_get_stored_key_values_count(&mut network, storage_nodes).await;
     assert_eq!(actual_db_count, node_all(storage_nodes, all_items_count));
 
+    let last_b_num = Some(block_count as u64 - 1);
+    let actual_last_b_num =
+        storage_all_get_last_block_stored_num(&mut network, storage_nodes).await;
+    assert_eq!(actual_last_b_num, node_all(storage_nodes, last_b_num));
+
+    for (b_num, block_key) in block_keys.iter().enumerate() {
+        let actual_key =
+            storage_all_get_indexed_block_key(&mut network, storage_nodes, b_num as u64).await;
+        assert_eq!(actual_key, node_all(storage_nodes, Some(block_key.clone())));
+    }
+
     test_step_complete(network).await;
 }
 
@@ -2809,6 +4370,67 @@ pub async fn create_item_asset_raft_1_node() {
     test_step_complete(network).await;
 }
 
+#[tokio::test(flavor = "current_thread")]
+pub async fn make_item_payment_raft_1_node() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_raft(11840, 1);
+    network_config
+        .nodes_mut(NodeType::User)
+        .push("user2".to_string());
+    network_config.mempool_seed_utxo = BTreeMap::new();
+    let mut network = Network::create_from_config(&network_config).await;
+    let item_metadata = Some("item_metadata".to_string());
+
+    create_first_block_act(&mut network).await;
+    let payee_address = user_generate_static_address_for_miner(&mut network, "user2").await;
+    let tx_hash = create_item_asset_act(&mut network, "user1", "mempool1", 10, item_metadata).await;
+    create_block_act(&mut network, Cfg::IgnoreStorage, CfgNum::All).await;
+
+    //
+    // Act
+    //
+    let insufficient_payment = {
+        let mut u = network.user("user1").unwrap().lock().await;
+        u.make_item_payment_transactions(None, payee_address.clone(), 11, tx_hash.clone(), None)
+    };
+    let item_payment = {
+        let mut u = network.user("user1").unwrap().lock().await;
+        u.make_item_payment_transactions(None, payee_address.clone(), 4, tx_hash.clone(), None)
+    };
+
+    user_send_next_payment_to_destinations(&mut network, "user1", "mempool1").await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions committed"]).await;
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 1).await;
+
+    let address_list = UtxoFetchType::AnyOf(vec![payee_address]);
+    request_utxo_set_and_update_running_total_act(&mut network, "user2", "mempool1", address_list)
+        .await;
+    let wallet_assets_actual =
+        user_get_wallet_asset_totals_for_tx(&mut network, "user1", "user2").await;
+
+    //
+    // Assert
+    //
+    assert!(!insufficient_payment.success);
+    assert!(item_payment.success);
+    assert_eq!(item_payment.change_amount, None);
+    assert!(item_payment.change_address.is_some());
+    assert_eq!(
+        wallet_assets_actual,
+        (
+            AssetValues::item(map_items(vec![(tx_hash.clone(), 6)])),
+            AssetValues::item(map_items(vec![(tx_hash, 4)]))
+        )
+    );
+
+    test_step_complete(network).await;
+}
+
 #[tokio::test(flavor = "current_thread")]
 pub async fn create_item_asset_on_mempool_raft_1_node() {
     test_step_start();
@@ -3290,48 +4912,150 @@ async fn reject_item_based_payment() {
         receiver_half_druid: "receiver_druid".to_owned(),
     };
 
-    let rb_txs = generate_rb_transactions(rb_sender_data, rb_receiver_data);
-    let rb_send_txs = vec![rb_txs[0].1.clone(), rb_txs[0].1.clone()];
-    let mut rb_recv_txs = vec![rb_txs[1].1.clone(), rb_txs[1].1.clone()];
+    let rb_txs = generate_rb_transactions(rb_sender_data, rb_receiver_data);
+    let rb_send_txs = vec![rb_txs[0].1.clone(), rb_txs[0].1.clone()];
+    let mut rb_recv_txs = vec![rb_txs[1].1.clone(), rb_txs[1].1.clone()];
+
+    // Invalid participant count
+    rb_recv_txs[0].druid_info.as_mut().unwrap().participants = 3;
+
+    // Invalid DRUID expectations
+    rb_recv_txs[1].druid_info.as_mut().unwrap().expectations = vec![DruidExpectation {
+        from: "wrong_from_addr".to_owned(), /* Invalid From Addr */
+        to: SOME_PUB_KEY_ADDRS[1].to_owned(),
+        asset: Asset::token_u64(6), /* Invalid TokenAmount */
+    }];
+
+    //
+    // Act
+    //
+    for i in 0..2 {
+        user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &rb_send_txs[i]).await;
+        mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+        user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &rb_recv_txs[i]).await;
+        mempool_handle_event(
+            &mut network,
+            "mempool1",
+            &["Some transactions invalid. Adding valid transactions only"],
+        )
+        .await;
+    }
+
+    //
+    // Assert
+    //
+    let actual_pending_druid_pool = mempool_pending_druid_pool(&mut network, "mempool1").await;
+    let actual_local_druid_pool = mempool_local_druid_pool(&mut network, "mempool1").await;
+
+    assert_eq!(
+        (
+            actual_pending_druid_pool.len(),
+            actual_local_druid_pool.len()
+        ),
+        (0, 0)
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn druid_droplet_expires_without_counterpart_raft_1_node() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_raft(10710, 1);
+    network_config.mempool_druid_pool_expiry_blocks = 2;
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo_with_info({
+        &[("000000", vec![(SOME_PUB_KEYS[0], DEFAULT_SEED_AMOUNT)])]
+    });
+    let mut network = Network::create_from_config(&network_config).await;
+    create_first_block_act(&mut network).await;
+    let (send_tx, _) = druid_expiry_rb_transactions("item_hash");
+    let send_tx_hash = construct_tx_hash(&send_tx);
+
+    //
+    // Act
+    //
+    user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &send_tx).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+
+    let mut pending_druid_pool_lens = Vec::new();
+    for b_num in 0..2 {
+        create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, b_num).await;
+        let pending = mempool_pending_druid_pool(&mut network, "mempool1").await;
+        pending_druid_pool_lens.push(pending.len());
+    }
+
+    let tx_status = {
+        let c = network.mempool("mempool1").unwrap().lock().await;
+        c.get_transaction_status(vec![send_tx_hash.clone()])
+    };
+
+    //
+    // Assert
+    //
+    assert_eq!(pending_druid_pool_lens, vec![1, 0]);
+    assert_eq!(
+        tx_status.get(&send_tx_hash).map(|s| s.status.clone()),
+        Some(TxStatusType::Rejected)
+    );
+
+    test_step_complete(network).await;
+}
 
-    // Invalid participant count
-    rb_recv_txs[0].druid_info.as_mut().unwrap().participants = 3;
+#[tokio::test(flavor = "current_thread")]
+async fn druid_droplet_completes_within_expiry_raft_1_node() {
+    test_step_start();
 
-    // Invalid DRUID expectations
-    rb_recv_txs[1].druid_info.as_mut().unwrap().expectations = vec![DruidExpectation {
-        from: "wrong_from_addr".to_owned(), /* Invalid From Addr */
-        to: SOME_PUB_KEY_ADDRS[1].to_owned(),
-        asset: Asset::token_u64(6), /* Invalid TokenAmount */
-    }];
+    //
+    // Arrange
+    //
+    let mut network_config = complete_network_config_with_n_mempool_raft(10720, 1);
+    network_config.mempool_druid_pool_expiry_blocks = 2;
+    network_config.mempool_seed_utxo = make_mempool_seed_utxo_with_info({
+        &[("000000", vec![(SOME_PUB_KEYS[0], DEFAULT_SEED_AMOUNT)])]
+    });
+    let mut network = Network::create_from_config(&network_config).await;
+    create_first_block_act(&mut network).await;
+
+    let item_tx = construct_item_create_tx(
+        0,
+        decode_pub_key(SOME_PUB_KEYS[1]).unwrap(),
+        &decode_secret_key(SOME_SEC_KEYS[1]).unwrap(),
+        1,
+        GenesisTxHashSpec::Create,
+        None,
+        None,
+    );
+    let item_tx_hash = construct_tx_hash(&item_tx);
+    let mut item_txs = BTreeMap::default();
+    item_txs.insert(item_tx_hash.clone(), item_tx);
+    add_transactions_act(&mut network, &item_txs).await;
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 0).await;
+    let (send_tx, recv_tx) = druid_expiry_rb_transactions(&item_tx_hash);
 
     //
     // Act
     //
-    for i in 0..2 {
-        user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &rb_send_txs[i]).await;
-        mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
-        user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &rb_recv_txs[i]).await;
-        mempool_handle_event(
-            &mut network,
-            "mempool1",
-            &["Some transactions invalid. Adding valid transactions only"],
-        )
-        .await;
-    }
+    user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &send_tx).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    create_block_act_with(&mut network, Cfg::IgnoreStorage, CfgNum::All, 1).await;
+    let pending_before = mempool_pending_druid_pool(&mut network, "mempool1").await;
+
+    user_send_transaction_to_mempool(&mut network, "user1", "mempool1", &recv_tx).await;
+    mempool_handle_event(&mut network, "mempool1", &["Transactions added to tx pool"]).await;
+    let pending_after = mempool_pending_druid_pool(&mut network, "mempool1").await;
+    let local_after = mempool_local_druid_pool(&mut network, "mempool1").await;
 
     //
     // Assert
     //
-    let actual_pending_druid_pool = mempool_pending_druid_pool(&mut network, "mempool1").await;
-    let actual_local_druid_pool = mempool_local_druid_pool(&mut network, "mempool1").await;
-
     assert_eq!(
-        (
-            actual_pending_druid_pool.len(),
-            actual_local_druid_pool.len()
-        ),
-        (0, 0)
+        (pending_before.len(), pending_after.len(), local_after.len()),
+        (1, 0, 1)
     );
+
+    test_step_complete(network).await;
 }
 
 #[tokio::test(flavor = "current_thread")]
@@ -3525,6 +5249,62 @@ async fn all_nodes_same_config(network: &mut Network, mempool_ring: &[String]) -
     all_shared_config_same
 }
 
+#[tokio::test(flavor = "current_thread")]
+async fn mempool_pause_and_resume_network_raft_3_nodes() {
+    test_step_start();
+
+    //
+    // Arrange
+    //
+    let network_config = complete_network_config_with_n_mempool_raft(11860, 3);
+    let mut network = Network::create_from_config(&network_config).await;
+    let mempool_nodes = &network_config.nodes[&NodeType::Mempool];
+    let storage_nodes = &network_config.nodes[&NodeType::Storage];
+    let miner_nodes = &network_config.nodes[&NodeType::Miner];
+    let transactions = valid_transactions(true);
+    let transactions_h = transactions.keys().cloned().collect::<Vec<_>>();
+
+    create_first_block_act(&mut network).await;
+    proof_of_work_act(&mut network, CfgPow::First, CfgNum::All, false, None).await;
+    send_block_to_storage_act(&mut network, CfgNum::All).await;
+
+    //
+    // Act
+    //
+    mempool_pause_network_act("mempool1", mempool_nodes, &mut network).await;
+    add_transactions_act(&mut network, &transactions).await;
+
+    // Block 0 stored while paused: block 1 is withheld
+    storage_all_send_stored_block(&mut network, storage_nodes).await;
+    mempool_all_handle_event(&mut network, mempool_nodes, "Received block stored").await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Block generation paused"]).await;
+    miner_all_handle_event(&mut network, miner_nodes, "Network paused").await;
+    let block_transaction_paused =
+        mempool_all_current_block_transactions(&mut network, mempool_nodes).await;
+
+    mempool_resume_network_act("mempool1", &mut network).await;
+    node_all_handle_event(&mut network, mempool_nodes, &["Block committed"]).await;
+    let block_transaction_resumed =
+        mempool_all_current_block_transactions(&mut network, mempool_nodes).await;
+    let mut block_num_resumed = Vec::new();
+    for mempool in mempool_nodes {
+        let block = mempool_current_mining_block(&mut network, mempool).await;
+        block_num_resumed.push(block.map(|b| b.header.b_num));
+    }
+
+    //
+    // Assert
+    //
+    assert_eq!(block_transaction_paused, node_all(mempool_nodes, None));
+    assert_eq!(
+        block_transaction_resumed,
+        node_all(mempool_nodes, Some(transactions_h))
+    );
+    assert_eq!(block_num_resumed, node_all(mempool_nodes, Some(1)));
+
+    test_step_complete(network).await;
+}
+
 async fn mempool_all_nodes_paused(network: &mut Network, mempool_ring: &[String]) -> bool {
     let mut all_nodes_paused = true;
     for mempool in mempool_ring {
@@ -3567,6 +5347,21 @@ async fn mempool_initiate_coordinated_pause_act(
     node_all_handle_event(network, mempool_ring, &["Node pause configuration set"]).await;
 }
 
+async fn mempool_pause_network_act(mempool: &str, mempool_ring: &[String], network: &mut Network) {
+    let mut c = network.mempool(mempool).unwrap().lock().await;
+    let _ = c.pause_network();
+    drop(c); // Drop mempool node to avoid borrow checker violation
+    mempool_handle_event(network, mempool, &["Network pause proposed"]).await;
+    node_all_handle_event(network, mempool_ring, &["Network paused"]).await;
+}
+
+async fn mempool_resume_network_act(mempool: &str, network: &mut Network) {
+    let mut c = network.mempool(mempool).unwrap().lock().await;
+    let _ = c.resume_network();
+    drop(c); // Drop mempool node to avoid borrow checker violation
+    mempool_handle_event(network, mempool, &["Network resume proposed"]).await;
+}
+
 async fn mempool_initiate_send_shared_config_act(
     shared_config: MempoolNodeSharedConfig,
     mempool: &str,
@@ -3925,6 +5720,13 @@ async fn mempool_mined_block_num(network: &mut Network, mempool: &str) -> Option
         .map(|b| b.common.block.header.b_num)
 }
 
+async fn mempool_mined_block_coinbase_hash(network: &mut Network, mempool: &str) -> Option<String> {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    c.get_current_mined_block()
+        .as_ref()
+        .map(|b| b.common.block.header.nonce_and_mining_tx_hash.1.clone())
+}
+
 async fn mempool_all_mined_block_num(
     network: &mut Network,
     mempool_group: &[String],
@@ -3937,6 +5739,18 @@ async fn mempool_all_mined_block_num(
     result
 }
 
+async fn mempool_all_raft_status(
+    network: &mut Network,
+    mempool_group: &[String],
+) -> Vec<MempoolRaftStatus> {
+    let mut result = Vec::new();
+    for name in mempool_group {
+        let c = network.mempool(name).unwrap().lock().await;
+        result.push(c.get_raft_status());
+    }
+    result
+}
+
 async fn mempool_all_current_block_transactions(
     network: &mut Network,
     mempool_group: &[String],
@@ -3963,6 +5777,22 @@ async fn mempool_current_mining_block(network: &mut Network, mempool: &str) -> O
     c.get_mining_block().clone()
 }
 
+async fn mempool_current_mining_reward(network: &mut Network, mempool: &str) -> TokenAmount {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    c.get_current_mining_reward()
+}
+
+async fn mempool_pool_mining(network: &mut Network, mempool: &str) -> PoolMiningHandle {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    c.pool_mining()
+}
+
+async fn mempool_submit_pool_pow(network: &mut Network, mempool: &str, solution: PoolSolution) {
+    let mut c = network.mempool(mempool).unwrap().lock().await;
+    let response = c.submit_pool_pow(solution);
+    assert!(response.success, "{response:?}");
+}
+
 async fn mempool_committed_current_block_num(network: &mut Network, mempool: &str) -> Option<u64> {
     let c = network.mempool(mempool).unwrap().lock().await;
     c.get_committed_current_block_num()
@@ -4037,11 +5867,52 @@ async fn mempool_committed_tx_druid_pool(
     c.get_committed_tx_druid_pool().clone()
 }
 
+/// Sender and receiver halves of a DRUID trade of seed tokens for the given item
+fn druid_expiry_rb_transactions(item_tx_hash: &str) -> (Transaction, Transaction) {
+    let rb_sender_data = RbSenderData {
+        sender_pub_addr: SOME_PUB_KEY_ADDRS[0].to_owned(),
+        sender_pub_key: SOME_PUB_KEYS[0].to_owned(),
+        sender_sec_key: SOME_SEC_KEYS[0].to_owned(),
+        sender_prev_out: OutPoint::new("000000".to_owned(), 0),
+        sender_amount: DEFAULT_SEED_AMOUNT,
+        sender_half_druid: "sender_druid".to_owned(),
+        sender_expected_drs: Some(item_tx_hash.to_owned()),
+    };
+
+    let rb_receiver_data = RbReceiverData {
+        receiver_pub_addr: SOME_PUB_KEY_ADDRS[1].to_owned(),
+        receiver_pub_key: SOME_PUB_KEYS[1].to_owned(),
+        receiver_sec_key: SOME_SEC_KEYS[1].to_owned(),
+        receiver_prev_out: OutPoint::new(item_tx_hash.to_owned(), 0),
+        receiver_half_druid: "receiver_druid".to_owned(),
+    };
+
+    let rb_txs = generate_rb_transactions(rb_sender_data, rb_receiver_data);
+    (rb_txs[0].1.clone(), rb_txs[1].1.clone())
+}
+
+/// Stages of a transaction hash requested twice, answered once
+async fn mempool_transaction_stages(
+    network: &mut Network,
+    mempool: &str,
+    tx_hash: &str,
+) -> Vec<TxStage> {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    let tx_hashes = vec![tx_hash.to_owned(), tx_hash.to_owned()];
+    let lifecycle = c.get_transaction_lifecycle(tx_hashes);
+    lifecycle.into_values().map(|l| l.stage).collect()
+}
+
 async fn mempool_pending_druid_pool(network: &mut Network, mempool: &str) -> DruidPool {
     let c = network.mempool(mempool).unwrap().lock().await;
     c.get_pending_druid_pool().clone()
 }
 
+async fn mempool_double_spend_count(network: &mut Network, mempool: &str) -> u64 {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    c.double_spend_count()
+}
+
 async fn mempool_local_druid_pool(
     network: &mut Network,
     mempool: &str,
@@ -4082,6 +5953,51 @@ async fn mempool_get_prev_mining_reward(network: &mut Network, mempool: &str) ->
     c.get_current_mining_reward()
 }
 
+/// Serve a GET supply route for the mempool, handling its events until the reply is built
+async fn mempool_api_get_content(
+    network: &mut Network,
+    mempool: &str,
+    path: &str,
+) -> serde_json::Value {
+    let mut c = network.mempool(mempool).unwrap().lock().await;
+    let mut dp = Vec::new();
+    let semaphore = Arc::new(Semaphore::new(1));
+    let filter = routes::total_supply(
+        &mut dp,
+        Default::default(),
+        Default::default(),
+        semaphore.clone(),
+        create_new_cache(CACHE_LIVE_TIME),
+    )
+    .or(routes::issued_supply(
+        &mut dp,
+        c.threaded_call_tx().clone(),
+        Default::default(),
+        Default::default(),
+        semaphore,
+        create_new_cache(CACHE_LIVE_TIME),
+    ))
+    .recover(handle_rejection);
+
+    let request = warp::test::request().method("GET").path(path);
+    let (done_tx, done_rx) = oneshot::channel();
+    let reply = tokio::spawn(async move {
+        let res = request.reply(&filter).await;
+        let _ = done_tx.send(());
+        res
+    });
+
+    let mut exit = Box::pin(async move {
+        let _ = done_rx.await;
+        "Api reply received"
+    });
+    mempool_handle_event_for_node(&mut c, true, &["Api reply received"], &mut exit).await;
+
+    let res = reply.await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    body["content"].clone()
+}
+
 async fn mempool_all_inject_next_event(
     network: &mut Network,
     from_group: &[String],
@@ -4110,6 +6026,14 @@ async fn mempool_flood_rand_and_block_to_partition(network: &mut Network, mempoo
     c.flood_rand_and_block_to_partition().await.unwrap();
 }
 
+async fn mempool_get_nonce_partitions(
+    network: &mut Network,
+    mempool: &str,
+) -> BTreeMap<SocketAddr, NoncePartition> {
+    let c = network.mempool(mempool).unwrap().lock().await;
+    c.get_nonce_partitions().clone()
+}
+
 async fn mempool_flood_transactions_to_partition(network: &mut Network, mempool: &str) {
     let mut c = network.mempool(mempool).unwrap().lock().await;
     c.flood_transactions_to_partition().await.unwrap();
@@ -4195,9 +6119,14 @@ async fn storage_send_blockchain_item(network: &mut Network, from_storage: &str)
     s.send_blockchain_item().await.unwrap();
 }
 
-async fn storage_catchup_fetch_blockchain_item(network: &mut Network, from_storage: &str) {
+async fn storage_send_block_range(network: &mut Network, from_storage: &str) {
+    let mut s = network.storage(from_storage).unwrap().lock().await;
+    s.send_block_range().await.unwrap();
+}
+
+async fn storage_catchup_fetch_block_range(network: &mut Network, from_storage: &str) {
     let mut s = network.storage(from_storage).unwrap().lock().await;
-    s.catchup_fetch_blockchain_item().await.unwrap();
+    s.catchup_fetch_block_range().await.unwrap();
 }
 
 async fn storage_inject_send_block_to_storage(
@@ -4239,6 +6168,18 @@ async fn storage_get_last_block_stored(
     s.get_last_block_stored().clone()
 }
 
+async fn storage_all_get_last_block_stored(
+    network: &mut Network,
+    storage_group: &[String],
+) -> Vec<Option<BlockStoredInfo>> {
+    let mut result = Vec::new();
+    for name in storage_group {
+        let r = storage_get_last_block_stored(network, name).await;
+        result.push(r);
+    }
+    result
+}
+
 async fn storage_all_get_last_block_stored_num(
     network: &mut Network,
     storage_group: &[String],
@@ -4251,6 +6192,20 @@ async fn storage_all_get_last_block_stored_num(
     result
 }
 
+async fn storage_all_get_indexed_block_key(
+    network: &mut Network,
+    storage_group: &[String],
+    b_num: u64,
+) -> Vec<Option<String>> {
+    let mut result = Vec::new();
+    for name in storage_group {
+        let s = network.storage(name).unwrap().lock().await;
+        let item = s.get_stored_value(indexed_block_hash_key(b_num));
+        result.push(item.map(|item| String::from_utf8_lossy(&item.key).into_owned()));
+    }
+    result
+}
+
 async fn storage_get_last_stored_info(
     network: &mut Network,
     storage: &str,
@@ -4491,10 +6446,19 @@ async fn user_send_transaction_to_mempool(
     from_user: &str,
     to_mempool: &str,
     tx: &Transaction,
+) {
+    user_send_transactions_to_mempool(network, from_user, to_mempool, vec![tx.clone()]).await;
+}
+
+async fn user_send_transactions_to_mempool(
+    network: &mut Network,
+    from_user: &str,
+    to_mempool: &str,
+    txs: Vec<Transaction>,
 ) {
     let mempool_node_addr = network.get_address(to_mempool).await.unwrap();
     let mut u = network.user(from_user).unwrap().lock().await;
-    u.send_transactions_to_mempool(mempool_node_addr, vec![tx.clone()])
+    u.send_transactions_to_mempool(mempool_node_addr, txs)
         .await
         .unwrap();
 }
@@ -4536,6 +6500,16 @@ async fn user_send_address_request(
         .unwrap();
 }
 
+async fn user_make_payment(
+    network: &mut Network,
+    from_user: &str,
+    to_address: &str,
+    amount: u64,
+) -> PaymentResponse {
+    let mut u = network.user(from_user).unwrap().lock().await;
+    u.make_payment_transactions(None, to_address.to_owned(), TokenAmount(amount), None)
+}
+
 async fn user_generate_static_address_for_miner(network: &mut Network, user: &str) -> String {
     let mut u = network.user(user).unwrap().lock().await;
     u.generate_static_address_for_miner().await
@@ -4554,6 +6528,11 @@ async fn user_send_address_to_trading_peer(network: &mut Network, user: &str) {
     u.send_address_to_trading_peer().await.unwrap();
 }
 
+async fn user_send_block_notification_request(network: &mut Network, user: &str) {
+    let mut u = network.user(user).unwrap().lock().await;
+    u.send_block_notification_request().await.unwrap();
+}
+
 async fn user_process_mining_notified(
     network: &mut Network,
     user: &str,
@@ -4767,6 +6746,35 @@ async fn miner_process_found_partition_pow(network: &mut Network, from_miner: &s
     m.process_found_partition_pow().await;
 }
 
+async fn miner_spendable_outpoints(network: &mut Network, miner: &str) -> Vec<(OutPoint, Asset)> {
+    let m = network.miner(miner).unwrap().lock().await;
+    m.get_wallet_db().get_spendable_outpoints()
+}
+
+/// Store outputs paying a key in the miner wallet, locked until the locktime
+async fn miner_seed_wallet(
+    network: &mut Network,
+    miner: &str,
+    (pub_key, sec_key): (&str, &str),
+    out_points: Vec<OutPoint>,
+    locktime: u64,
+) {
+    let m = network.miner(miner).unwrap().lock().await;
+    let mut wallet_db = m.get_wallet_db().clone();
+    let pk = decode_pub_key(pub_key).unwrap();
+    let sk = decode_secret_key(sec_key).unwrap();
+    let (address, _) = wallet_db.store_payment_address(pk, sk, None);
+
+    let payments = out_points
+        .into_iter()
+        .map(|out_p| (out_p, Asset::token_u64(1), address.clone(), locktime))
+        .collect();
+    wallet_db
+        .save_usable_payments_to_wallet(payments, 0, false)
+        .await
+        .unwrap();
+}
+
 async fn miner_has_aggregation_tx_active(
     network: &mut Network,
     from_miner: &str,
@@ -4780,6 +6788,47 @@ async fn miner_process_found_block_pow(network: &mut Network, from_miner: &str)
     m.process_found_block_pow().await;
 }
 
+async fn miner_get_found_nonce_partition(
+    network: &mut Network,
+    miner: &str,
+) -> (NoncePartition, u64) {
+    let m = network.miner(miner).unwrap().lock().await;
+    let nonce = m.found_block_pow_nonce().unwrap();
+    (
+        m.nonce_partition().unwrap(),
+        nonce_partition_counter(nonce).unwrap(),
+    )
+}
+
+async fn miner_current_coinbase_hash(network: &mut Network, miner: &str) -> Option<String> {
+    let m = network.miner(miner).unwrap().lock().await;
+    m.current_coinbase_hash().map(str::to_owned)
+}
+
+async fn miner_set_paused(network: &mut Network, miner: &str, paused: bool) {
+    let mut m = network.miner(miner).unwrap().lock().await;
+    m.force_set_paused(paused).await;
+}
+
+async fn miner_resume(network: &mut Network, miner: &str) {
+    let mut m = network.miner(miner).unwrap().lock().await;
+    let addr = m.local_address();
+    let response = m.receive_pause_node_request(addr, false).await;
+    assert_eq!(response.reason, "Node is resumed");
+}
+
+async fn miner_get_work_cache_state(
+    network: &mut Network,
+    miner: &str,
+) -> (Option<CachedBlockTemplate>, bool, u64) {
+    let m = network.miner(miner).unwrap().lock().await;
+    (
+        m.cached_block_template().cloned(),
+        m.has_queued_block_pow(),
+        m.stale_work_count(),
+    )
+}
+
 async fn miner_set_static_miner_address(network: &mut Network, miner: &str, static_addr: String) {
     let mut m = network.miner(miner).unwrap().lock().await;
     m.set_static_miner_address(Some(static_addr)).await;
@@ -4799,6 +6848,17 @@ async fn test_step_complete(network: Network) {
     info!("Test Step complete")
 }
 
+/// Mempool response reason listing the transactions rejected by verification
+fn rejected_txs_reason(reason: &str, rejected_txs: &[Transaction]) -> String {
+    let rejected_txs: BTreeSet<_> = rejected_txs.iter().map(construct_tx_hash).collect();
+    let rejected_txs: Vec<_> = rejected_txs.into_iter().collect();
+    format!(
+        "{}. Rejected invalid transactions: {}",
+        reason,
+        rejected_txs.join(", ")
+    )
+}
+
 fn valid_transactions(fixed: bool) -> BTreeMap<String, Transaction> {
     valid_transactions_with(fixed, DEFAULT_SEED_AMOUNT, true)
 }
@@ -4851,6 +6911,7 @@ fn make_mempool_seed_utxo_with_info(seed: &[(&str, Vec<(&str, TokenAmount)>)]) -
                 txo.iter()
                     .map(|(pk, amount)| TxOutSpec {
                         public_key: pk.to_string(),
+                        address: None,
                         amount: *amount,
                         locktime: 0,
                     })
@@ -4860,6 +6921,18 @@ fn make_mempool_seed_utxo_with_info(seed: &[(&str, Vec<(&str, TokenAmount)>)]) -
         .collect()
 }
 
+/// Seed the default UTXO outputs to the address of the common key instead of its public key
+fn make_mempool_seed_utxo_for_address(locktime: Option<u64>) -> UtxoSetSpec {
+    let address = construct_address(&decode_pub_key(COMMON_PUB_KEY).unwrap());
+    let mut seed = make_mempool_seed_utxo(SEED_UTXO, DEFAULT_SEED_AMOUNT);
+    for tx_out in seed.values_mut().flatten() {
+        tx_out.public_key = String::new();
+        tx_out.address = Some(address.clone());
+        tx_out.locktime = locktime.unwrap_or_default();
+    }
+    seed
+}
+
 fn block_and_partition_evt_in_miner_pow(
     c_miners: &[String],
     in_miners: &[String],
@@ -4905,6 +6978,16 @@ fn test_timeout_barrier(barrier: &'_ Barrier) -> impl Future<Output = &'static s
     })
 }
 
+fn pool_last_job(jobs: &mut mpsc::UnboundedReceiver<PoolMiningMessage>) -> Option<PoolJob> {
+    let mut job = None;
+    while let Ok(msg) = jobs.try_recv() {
+        if let PoolMiningMessage::Job(j) = msg {
+            job = Some(j);
+        }
+    }
+    job
+}
+
 fn equal_first<T: Eq>(values: &[T]) -> Vec<bool> {
     values.iter().map(|v| *v == values[0]).collect()
 }
@@ -5120,7 +7203,13 @@ fn basic_network_config(initial_port: u16) -> NetworkConfig {
         mining_api_key: Default::default(),
         peer_limit: 1000,
         address_aggregation_limit: Some(5),
+        address_aggregation_max_inputs: None,
+        enable_address_aggregation: None,
         initial_issuances: Default::default(),
+        peer_discovery: false,
+        miner_outage_mining_secs: None,
+        mempool_pow_batch_window: 5,
+        mempool_druid_pool_expiry_blocks: 10,
     }
 }
 
@@ -5135,6 +7224,15 @@ fn complete_network_config_with_n_mempool_raft(
     complete_network_config_with_n_mempool_miner(initial_port, true, mempool_count, mempool_count)
 }
 
+fn complete_network_config_with_n_mempool_storage_raft(
+    initial_port: u16,
+    mempool_count: usize,
+    storage_count: usize,
+) -> NetworkConfig {
+    complete_network_config_with_n_mempool_raft(initial_port, mempool_count)
+        .with_storage_count(storage_count)
+}
+
 fn complete_network_config_with_n_mempool_miner(
     initial_port: u16,
     use_raft: bool,
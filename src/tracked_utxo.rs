@@ -99,6 +99,23 @@ impl TrackedUtxoSet {
         self.re_align();
     }
 
+    /// Remove base 'UtxoSet' entries added for `block_tx` and restore the `spent` entries
+    pub fn roll_back_tracked_utxo_set(
+        &mut self,
+        block_tx: &BTreeMap<String, Transaction>,
+        spent: UtxoSet,
+    ) {
+        let added = get_tx_out_with_out_point_cloned(block_tx.iter())
+            .chain(get_fees_with_out_point_cloned(block_tx.iter()));
+        for (out_point, _) in added {
+            self.base.remove(&out_point);
+        }
+        self.base.extend(spent);
+
+        // Re-align `pk_cache` to `base`
+        self.re_align();
+    }
+
     /// Remove base 'UtxoSet' and pk_cache entry concurrently
     pub fn remove_tracked_utxo_entry<'a>(&mut self, key: &'a OutPoint) -> Option<&'a OutPoint> {
         self.base.remove(key)?.script_public_key.and_then(|spk| {
@@ -297,6 +297,9 @@ pub fn get_upgrade_storage_db(
     let raft_db = new_db_with_version(db_mode, raft_spec, version, old_dbs.raft_db, None)?;
 
     db.upgrade_create_missing_cf(storage::DB_COL_BC_NOW)?;
+    db.upgrade_create_missing_cf(storage::DB_COL_UTXO_INDEX)?;
+    db.upgrade_create_missing_cf(storage::DB_COL_BLOCK_HEADERS)?;
+    db.upgrade_create_missing_cf(storage::DB_COL_ADDRESS_HISTORY)?;
     Ok(ExtraNodeParams {
         db: Some(db),
         raft_db: Some(raft_db),
@@ -723,7 +723,13 @@ fn complete_network_config(initial_port: u16) -> NetworkConfig {
         mempool_miner_whitelist: Default::default(),
         peer_limit: 1000,
         address_aggregation_limit: Some(5),
+        address_aggregation_max_inputs: None,
+        enable_address_aggregation: None,
         initial_issuances: Default::default(),
+        peer_discovery: false,
+        miner_outage_mining_secs: None,
+        mempool_pow_batch_window: 5,
+        mempool_druid_pool_expiry_blocks: 10,
     }
     .with_groups(1, 1)
 }
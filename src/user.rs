@@ -1,5 +1,12 @@
-use crate::comms_handler::{CommsError, Event, Node, TcpTlsConfig};
-use crate::configurations::{ExtraNodeParams, TlsPrivateInfo, UserAutoGenTxSetup, UserNodeConfig};
+use crate::api::utils::load_api_tls_files;
+use crate::comms_handler::peer_discovery::{configure_peer_discovery, discover_peer_addr};
+use crate::comms_handler::{
+    default_max_frame_size, split_into_frame_batches, CommsError, Event, Node, TcpTlsConfig,
+};
+use crate::configurations::{
+    ApiRateLimits, ExtraNodeParams, TlsPrivateInfo, UserAutoGenTxSetup, UserNodeConfig,
+};
+use crate::constants::{PAYMENT_EVENTS_CAPACITY, PENDING_PAYMENT_CANCEL_HORIZON};
 use crate::interfaces::{
     MempoolRequest, NodeType, PaymentResponse, RbPaymentData, RbPaymentRequestData,
     RbPaymentResponseData, Response, UserApi, UserApiRequest, UserRequest, UtxoFetchType, UtxoSet,
@@ -8,16 +15,17 @@ use crate::threaded_call::{ThreadedCallChannel, ThreadedCallSender};
 use crate::transaction_gen::{PendingMap, TransactionGen};
 use crate::transactor::Transactor;
 use crate::utils::{
-    create_socket_addr, generate_half_druid, get_payments_for_wallet_from_utxo, to_api_keys,
-    to_route_pow_infos, try_send_to_ui, ApiKeys, LocalEvent, LocalEventChannel, LocalEventSender,
-    ResponseResult, RoutesPoWInfo,
+    create_socket_addr, generate_half_druid, get_payments_for_wallet_from_utxo,
+    shutdown_connections, to_api_keys, to_route_pow_infos, try_send_to_ui, ApiKeys, LocalEvent,
+    LocalEventChannel, LocalEventSender, ResponseResult, RoutesPoWInfo,
 };
 use crate::wallet::{AddressStore, WalletDb, WalletDbError};
 use crate::Rs2JsMsg;
 use async_trait::async_trait;
 use bincode::deserialize;
 use bytes::Bytes;
-use serde::Serialize;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use tw_chain::primitives::asset::{Asset, TokenAmount};
 use tw_chain::primitives::block::Block;
@@ -30,7 +38,7 @@ use tw_chain::utils::transaction_utils::{
 };
 
 use std::{collections::BTreeMap, error::Error, fmt, future::Future, net::SocketAddr};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::task;
 use tracing::{debug, error, error_span, info, info_span, trace, warn};
 use tracing_futures::Instrument;
@@ -120,6 +128,28 @@ pub enum AutoDonate {
     Enabled(TokenAmount),
 }
 
+/// Stage of a payment received to one of the wallet addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentEventKind {
+    /// Payment transaction received from the payer, not yet in a block
+    Pending,
+    /// Payment transaction included in a block notified by the mempool
+    Confirmed,
+}
+
+/// Payment received to one of the wallet addresses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentEvent {
+    pub event: PaymentEventKind,
+    pub tx_hash: String,
+    pub address: String,
+    pub amount: Asset,
+}
+
+/// Broadcast of the received payments to the API payment events stream
+pub type PaymentEventSender = broadcast::Sender<PaymentEvent>;
+
 /// An instance of a UserNode
 #[derive(Debug)]
 pub struct UserNode {
@@ -129,7 +159,13 @@ pub struct UserNode {
     threaded_calls: ThreadedCallChannel<dyn UserApi>,
     ui_feedback_tx: Option<mpsc::Sender<Rs2JsMsg>>,
     mempool_addr: SocketAddr,
-    api_info: (SocketAddr, Option<TlsPrivateInfo>, ApiKeys, RoutesPoWInfo),
+    api_info: (
+        SocketAddr,
+        Option<TlsPrivateInfo>,
+        ApiKeys,
+        RoutesPoWInfo,
+        ApiRateLimits,
+    ),
     trading_peer: Option<SocketAddr>,
     next_payment: Option<(Option<SocketAddr>, Transaction)>,
     last_block_notified: Block,
@@ -139,6 +175,11 @@ pub struct UserNode {
     next_rb_payment_response: Option<(SocketAddr, Option<RbPaymentResponseData>)>,
     next_rb_payment_data: Option<RbPaymentData>,
     next_rb_payment: Option<(Option<SocketAddr>, Transaction)>,
+    payment_fee: TokenAmount,
+    payment_events: PaymentEventSender,
+    /// Payments received from payers and not yet in a block, by transaction hash,
+    /// with the block number they were received at
+    incoming_payments: BTreeMap<String, (u64, Vec<(String, Asset)>)>,
 }
 
 impl UserNode {
@@ -150,24 +191,26 @@ impl UserNode {
     /// * `extra`  - additional parameter for construction
     pub async fn new(config: UserNodeConfig, mut extra: ExtraNodeParams) -> Result<UserNode> {
         let addr = config.user_address;
-        let raw_mempool_addr = config
-            .mempool_nodes
-            .get(config.user_mempool_node_idx)
-            .ok_or(UserError::ConfigError("Invalid mempool index"))?;
-        let mempool_addr = create_socket_addr(&raw_mempool_addr.address)
-            .await
-            .map_err(|_| UserError::ConfigError("Invalid mempool address"))?;
 
         let tls_addr = create_socket_addr(&addr).await.unwrap();
         let tcp_tls_config = TcpTlsConfig::from_tls_spec(tls_addr, &config.tls_config)?;
         let api_addr = SocketAddr::new(tls_addr.ip(), config.user_api_port);
-        let api_tls_info = config
-            .user_api_use_tls
-            .then(|| tcp_tls_config.clone_private_info());
+        let api_tls_info = match (
+            &config.user_api_tls_cert_path,
+            &config.user_api_tls_key_path,
+        ) {
+            (Some(cert_path), Some(key_path)) => Some(
+                load_api_tls_files(cert_path, key_path)
+                    .map_err(|_| UserError::ConfigError("Invalid API TLS files"))?,
+            ),
+            _ => config
+                .user_api_use_tls
+                .then(|| tcp_tls_config.clone_private_info()),
+        };
         let api_keys = to_api_keys(config.api_keys.clone());
         let api_pow_info = to_route_pow_infos(config.routes_pow.clone());
         let disable_tcp_listener = extra.disable_tcp_listener;
-        let node = Node::new(
+        let mut node = Node::new(
             &tcp_tls_config,
             config.peer_limit,
             config.peer_limit,
@@ -176,6 +219,29 @@ impl UserNode {
             false,
         )
         .await?;
+        if let Some(max_frame_size) = config.max_frame_size {
+            node.set_max_frame_size(max_frame_size);
+        }
+        let peer_discovery = config.peer_discovery.as_ref();
+        configure_peer_discovery(&mut node, peer_discovery).await?;
+
+        let mempool_addr = match config.mempool_nodes.get(config.user_mempool_node_idx) {
+            Some(raw_mempool_addr) => create_socket_addr(&raw_mempool_addr.address)
+                .await
+                .map_err(|_| UserError::ConfigError("Invalid mempool address"))?,
+            None => {
+                let bootstrap_nodes = peer_discovery.map(|c| c.bootstrap_nodes.as_slice());
+                discover_peer_addr(
+                    &mut node,
+                    bootstrap_nodes.unwrap_or_default(),
+                    NodeType::Mempool,
+                )
+                .await
+                .ok_or(UserError::ConfigError(
+                    "No mempool node configured or discovered",
+                ))?
+            }
+        };
 
         let wallet_db = match extra.shared_wallet_db {
             Some(shared_db) => shared_db,
@@ -202,7 +268,15 @@ impl UserNode {
             threaded_calls: Default::default(),
             ui_feedback_tx: Default::default(),
             mempool_addr,
-            api_info: (api_addr, api_tls_info, api_keys, api_pow_info),
+            api_info: (
+                api_addr,
+                api_tls_info,
+                api_keys,
+                api_pow_info,
+                config
+                    .user_api_rate_limit
+                    .unwrap_or_else(ApiRateLimits::node_default),
+            ),
             trading_peer: None,
             next_payment: None,
             last_block_notified: Default::default(),
@@ -212,6 +286,9 @@ impl UserNode {
             next_rb_payment_response: None,
             next_rb_payment_data: None,
             next_rb_payment: None,
+            payment_fee: Default::default(),
+            payment_events: broadcast::channel(PAYMENT_EVENTS_CAPACITY).0,
+            incoming_payments: Default::default(),
         })
     }
 
@@ -290,8 +367,10 @@ impl UserNode {
         Option<TlsPrivateInfo>,
         ApiKeys,
         RoutesPoWInfo,
+        ApiRateLimits,
     ) {
-        let (api_addr, api_tls_info, api_keys, routes_pow_info) = self.api_info.clone();
+        let (api_addr, api_tls_info, api_keys, routes_pow_info, api_rate_limit) =
+            self.api_info.clone();
         (
             self.wallet_db.clone(),
             self.node.clone(),
@@ -299,9 +378,20 @@ impl UserNode {
             api_tls_info,
             api_keys,
             routes_pow_info,
+            api_rate_limit,
         )
     }
 
+    /// Stop accepting connections, flush pending wallet writes,
+    /// and notify connected peers of the disconnection
+    pub async fn shutdown(&mut self) {
+        join_all(self.node.stop_listening().await).await;
+        if let Err(e) = self.wallet_db.flush_persistent_store().await {
+            error!("Error flushing wallet db: {:?}", e);
+        }
+        shutdown_connections(&mut self.node).await;
+    }
+
     /// Extract persistent dbs
     pub async fn take_closed_extra_params(&mut self) -> ExtraNodeParams {
         let wallet_db = self.wallet_db.take_closed_persistent_store().await;
@@ -424,6 +514,12 @@ impl UserNode {
             }) if reason == "Received UTXO set" => {
                 self.update_running_total().await;
             }
+            Ok(Response {
+                success: false,
+                reason,
+            }) if reason == "Frame size limit exceeded" => {
+                warn!("Disconnected peer sending frames over the size limit");
+            }
             Ok(Response {
                 success: true,
                 reason,
@@ -497,6 +593,11 @@ impl UserNode {
         &self.local_events.tx
     }
 
+    /// Received payments broadcast shared with the API
+    pub fn payment_events(&self) -> PaymentEventSender {
+        self.payment_events.clone()
+    }
+
     /// UI feedback channel.
     pub fn ui_feedback_tx(&self) -> Option<mpsc::Sender<Rs2JsMsg>> {
         self.ui_feedback_tx.clone()
@@ -550,6 +651,23 @@ impl UserNode {
                     .instrument(peer_span)
                     .await
             }
+            Event::FrameSizeLimitExceeded { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Frame size limit exceeded".to_owned(),
+            })),
+            Event::PeerReconnected { peer } => {
+                if let Err(err) = self.send_startup_requests().await {
+                    error!("Failed to send startup requests on reconnect to {peer:?}: {err}");
+                }
+                Ok(Some(Response {
+                    success: true,
+                    reason: "Peer reconnected".to_owned(),
+                }))
+            }
+            Event::PeerUnreachable { peer: _ } => Ok(Some(Response {
+                success: false,
+                reason: "Peer unreachable".to_owned(),
+            })),
         }
     }
 
@@ -751,6 +869,7 @@ impl UserNode {
     /// Handle connect to mempool node
     pub async fn handle_disconnect_from_mempool(&mut self) -> Response {
         let mempool_addr = self.mempool_address();
+        self.node.untrack_peers(Some(&[mempool_addr])).await;
         let join_handles = self.node.disconnect_all(Some(&[mempool_addr])).await;
         if join_handles.is_empty() {
             return Response {
@@ -790,6 +909,7 @@ impl UserNode {
                 reason: "Failed to connect to mempool".to_string(),
             };
         }
+        self.node.track_peers(&[mempool_addr]).await;
         if let Err(e) = self.send_block_notification_request().await {
             error!("Failed to send startup requests to mempool: {e:?}");
             return Response {
@@ -890,6 +1010,7 @@ impl UserNode {
         self.wallet_db
             .store_payment_transaction(tx.clone(), b_num)
             .await;
+        self.wallet_db.store_pending_payment(&tx, b_num);
 
         if let Some(peer) = peer {
             self.send_payment_to_receiver(peer, tx).await?;
@@ -913,6 +1034,7 @@ impl UserNode {
         self.wallet_db
             .store_payment_transaction(transaction.clone(), b_num)
             .await;
+        self.wallet_db.store_pending_payment(&transaction, b_num);
         let _peer_span =
             info_span!("sending item-based transaction to mempool node for processing");
         let transactions = vec![transaction.clone()];
@@ -1001,9 +1123,19 @@ impl UserNode {
     /// * `transaction` - Transaction to receive and save to wallet
     pub async fn receive_payment_transaction(&mut self, transaction: Transaction) -> Response {
         let b_num = self.last_block_notified.header.b_num;
-        self.wallet_db
+        let tx_hash = construct_tx_hash(&transaction);
+        let payments: Vec<_> = self
+            .wallet_db
             .store_payment_transaction(transaction, b_num)
-            .await;
+            .await
+            .into_iter()
+            .map(|(_, asset, address, _)| (address, asset))
+            .collect();
+
+        if !payments.is_empty() {
+            self.publish_payment_events(PaymentEventKind::Pending, &tx_hash, &payments);
+            self.incoming_payments.insert(tx_hash, (b_num, payments));
+        }
 
         Response {
             success: true,
@@ -1034,6 +1166,8 @@ impl UserNode {
                     reason: "Ignore unexpected transaction".to_string(),
                     tx_hash: "".to_string(),
                     tx: None,
+                    change_address: None,
+                    change_amount: None,
                 })
             }
         };
@@ -1059,8 +1193,50 @@ impl UserNode {
         excess_address: Option<String>,
         locktime: Option<u64>,
     ) -> PaymentResponse {
+        // The payment fee is left out of the outputs for the miner to collect
         let tx_out = TxOut::new_token_amount(address, amount, locktime);
-        let asset_required = Asset::Token(amount);
+        let asset_required = Asset::Token(amount + self.payment_fee);
+        self.make_asset_payment_transactions(peer, tx_out, asset_required, excess_address)
+    }
+
+    /// Process specified item asset payment, updating wallet and next_payment.
+    /// Items are spent like tokens, with any excess returned as change, but no fee is taken.
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`         - Peer recieving the payment.
+    /// * `address`      - Address to assign the payment transaction to
+    /// * `item_amount`  - Amount of the item asset paid
+    /// * `genesis_hash` - Genesis hash of the item asset paid
+    /// * `locktime`     - Locktime for transaction
+    pub fn make_item_payment_transactions(
+        &mut self,
+        peer: Option<SocketAddr>,
+        address: String,
+        item_amount: u64,
+        genesis_hash: String,
+        locktime: Option<u64>,
+    ) -> PaymentResponse {
+        let asset_required = Asset::item(item_amount, Some(genesis_hash), None);
+        let tx_out = TxOut::new_asset(address, asset_required.clone(), locktime);
+        self.make_asset_payment_transactions(peer, tx_out, asset_required, None)
+    }
+
+    /// Build the payment transaction spending the asset required for the payment output
+    ///
+    /// ### Arguments
+    ///
+    /// * `peer`           - Peer recieving the payment.
+    /// * `tx_out`         - Payment output
+    /// * `asset_required` - Asset to take from the wallet, including any fee
+    /// * `excess_address` - Address to assign the excess to
+    fn make_asset_payment_transactions(
+        &mut self,
+        peer: Option<SocketAddr>,
+        tx_out: TxOut,
+        asset_required: Asset,
+        excess_address: Option<String>,
+    ) -> PaymentResponse {
         let (tx_ins, tx_outs) = if let Ok(value) = self
             .wallet_db
             .fetch_tx_ins_and_tx_outs_provided_excess(asset_required, vec![tx_out], excess_address)
@@ -1072,9 +1248,23 @@ impl UserNode {
                 reason: "Insufficient funds for payment".to_string(),
                 tx_hash: "".to_string(),
                 tx: None,
+                change_address: None,
+                change_amount: None,
             };
         };
 
+        // Any output after the payment itself holds the change
+        let (change_address, change_amount) = match tx_outs.get(1) {
+            Some(change) => (
+                change.script_public_key.clone(),
+                match &change.value {
+                    Asset::Token(amount) => Some(*amount),
+                    _ => None,
+                },
+            ),
+            None => (None, None),
+        };
+
         let key_material = self.wallet_db.get_key_material(&tx_ins);
         let final_tx_ins = update_input_signatures(&tx_ins, &tx_outs, &key_material);
         let payment_tx = construct_tx_core(final_tx_ins, tx_outs, None);
@@ -1088,6 +1278,8 @@ impl UserNode {
             reason: "Payment transaction pending".to_string(),
             tx_hash,
             tx: Some(payment_tx),
+            change_address,
+            change_amount,
         }
     }
 
@@ -1252,6 +1444,9 @@ impl UserNode {
             self.wallet_db
                 .filter_locked_coinbase(block.header.b_num)
                 .await;
+            self.wallet_db
+                .confirm_pending_payments(&block.transactions, block.header.b_num);
+            self.confirm_incoming_payments(&block.transactions, block.header.b_num);
             self.last_block_notified = block;
             // Send the block to the UI for realtime feedback
             try_send_to_ui(
@@ -1273,6 +1468,54 @@ impl UserNode {
         }
     }
 
+    /// Publish the confirmation of the received payments included in a block,
+    /// forgetting the ones still not in a block past the safety horizon
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hashes` - Hashes of the transactions in the block
+    /// * `b_num`     - Number of the block
+    fn confirm_incoming_payments(&mut self, tx_hashes: &[String], b_num: u64) {
+        for tx_hash in tx_hashes {
+            if let Some((_, payments)) = self.incoming_payments.remove(tx_hash) {
+                self.publish_payment_events(PaymentEventKind::Confirmed, tx_hash, &payments);
+            }
+        }
+
+        self.incoming_payments.retain(|_, (received_b_num, _)| {
+            received_b_num.saturating_add(PENDING_PAYMENT_CANCEL_HORIZON) > b_num
+        });
+    }
+
+    /// Broadcast the events of a received payment to the payment events subscribers, if any.
+    /// Slow subscribers lag behind rather than holding up the node.
+    ///
+    /// ### Arguments
+    ///
+    /// * `event`    - Stage of the payment
+    /// * `tx_hash`  - Hash of the payment transaction
+    /// * `payments` - Addresses of the wallet paid, with the amount paid to each
+    fn publish_payment_events(
+        &self,
+        event: PaymentEventKind,
+        tx_hash: &str,
+        payments: &[(String, Asset)],
+    ) {
+        if self.payment_events.receiver_count() == 0 {
+            return;
+        }
+
+        for (address, amount) in payments {
+            // Only fails if all subscribers disconnected meanwhile
+            let _ = self.payment_events.send(PaymentEvent {
+                event,
+                tx_hash: tx_hash.to_owned(),
+                address: address.clone(),
+                amount: amount.clone(),
+            });
+        }
+    }
+
     /// Process a notification for block mining to auto generate next transactions
     pub async fn process_mining_notified(&mut self) {
         if self.test_auto_gen_tx.is_none() {
@@ -1347,6 +1590,20 @@ impl UserNode {
         &self.wallet_db
     }
 
+    /// Get the fee paid by the payments made
+    pub fn get_payment_fee(&self) -> TokenAmount {
+        self.payment_fee
+    }
+
+    /// Set the fee paid by the payments made, such as the mempool node's fee estimate
+    ///
+    /// ### Arguments
+    ///
+    /// * `fee` - Tokens left to the miner by each payment
+    pub fn set_payment_fee(&mut self, fee: TokenAmount) {
+        self.payment_fee = fee;
+    }
+
     /// Get the last block notified to us
     pub fn get_last_block_notified(&self) -> &Block {
         &self.last_block_notified
@@ -1576,6 +1833,16 @@ impl UserApi for UserNode {
     ) -> PaymentResponse {
         self.make_payment_transactions(None, address, amount, locktime)
     }
+
+    fn make_item_payment(
+        &mut self,
+        address: String,
+        item_amount: u64,
+        genesis_hash: String,
+        locktime: Option<u64>,
+    ) -> PaymentResponse {
+        self.make_item_payment_transactions(None, address, item_amount, genesis_hash, locktime)
+    }
 }
 
 #[async_trait]
@@ -1588,12 +1855,15 @@ impl Transactor for UserNode {
         transactions: Vec<Transaction>,
     ) -> Result<()> {
         let _peer_span = info_span!("Sending transactions to mempool node for processing");
-        self.node
-            .send(
-                mempool_peer,
-                MempoolRequest::SendTransactions { transactions },
-            )
-            .await?;
+        let max_frame_size = default_max_frame_size(NodeType::Mempool);
+        for transactions in split_into_frame_batches(transactions, max_frame_size) {
+            self.node
+                .send(
+                    mempool_peer,
+                    MempoolRequest::SendTransactions { transactions },
+                )
+                .await?;
+        }
 
         Ok(())
     }
@@ -5,7 +5,8 @@ use crate::constants::{
     REWARD_ISSUANCE_VAL, REWARD_SMOOTHING_VAL,
 };
 use crate::interfaces::{
-    BlockchainItem, BlockchainItemMeta, DruidDroplet, PowInfo, ProofOfWork, StoredSerializingBlock,
+    BlockchainItem, BlockchainItemMeta, DruidDroplet, NoncePartition, PowInfo, ProofOfWork,
+    StoredSerializingBlock,
 };
 use crate::wallet::WalletDb;
 use crate::Rs2JsMsg;
@@ -22,6 +23,8 @@ use std::fs::File;
 use std::future::Future;
 use std::io::Read;
 use std::net::{IpAddr, SocketAddr};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, UNIX_EPOCH};
 use tokio::runtime::Runtime;
@@ -116,6 +119,9 @@ impl<T> MpscTracingSender<T> {
     }
 }
 
+/// Shared flag set to abort an in-progress PoW search
+pub type CancellationToken = Arc<AtomicBool>;
+
 /// A running tasks or end result
 #[derive(Debug)]
 pub enum RunningTaskOrResult<T> {
@@ -285,7 +291,7 @@ pub fn tracing_log_try_init() -> Result<(), Box<dyn Error + Send + Sync + 'stati
     builder.try_init()
 }
 
-/// Attempts to connect to all peers
+/// Attempts to connect to all peers, retrying each with the node's connection backoff
 ///
 /// ### Arguments
 ///
@@ -303,7 +309,8 @@ pub async fn loop_connnect_to_peers_async(
     loop {
         for peer in node.unconnected_peers(&peers).await {
             trace!(?peer, "Try to connect to");
-            if let Err(e) = node.connect_to(peer).await {
+            let policy = node.connect_retry_policy();
+            if let Err(e) = node.connect_to_with_retry(peer, policy).await {
                 trace!(?peer, ?e, "Try to connect to failed");
             } else {
                 trace!(?peer, "Try to connect to succeeded");
@@ -595,17 +602,229 @@ pub fn try_deserialize<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, Binc
     bincode::deserialize(data)
 }
 
+/// Will attempt deserialization of a given byte array using bincode,
+/// failing if any bytes are left over
+///
+/// ### Arguments
+///
+/// * `data`    - Byte array to attempt deserialization on
+pub fn try_deserialize_exact<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, BincodeError> {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(data)
+}
+
 /// Generate Proof of Work for a block with a mining transaction
 ///
 /// ### Arguments
 ///
 /// * `header`   - The header for PoW
-pub fn generate_pow_for_block(mut header: BlockHeader) -> BlockHeader {
-    header.nonce_and_mining_tx_hash.0 = generate_pow_nonce();
-    while !validate_pow_block(&header) {
-        header.nonce_and_mining_tx_hash.0 = generate_pow_nonce();
+pub fn generate_pow_for_block(header: BlockHeader) -> BlockHeader {
+    let cancel: CancellationToken = Default::default();
+    generate_pow_for_block_cancellable(header, &cancel).unwrap()
+}
+
+/// Generate Proof of Work for a block with a mining transaction,
+/// returning None as soon as the search is cancelled
+///
+/// ### Arguments
+///
+/// * `header`   - The header for PoW
+/// * `cancel`   - Token set to abort the search
+pub fn generate_pow_for_block_cancellable(
+    header: BlockHeader,
+    cancel: &CancellationToken,
+) -> Option<BlockHeader> {
+    generate_pow_for_block_counted(header, cancel, &AtomicU64::new(0))
+}
+
+/// Generate Proof of Work for a block with a mining transaction,
+/// adding each hash attempted to the given count
+///
+/// ### Arguments
+///
+/// * `header`     - The header for PoW
+/// * `cancel`     - Token set to abort the search
+/// * `hash_count` - Count of hashes attempted
+pub fn generate_pow_for_block_counted(
+    header: BlockHeader,
+    cancel: &CancellationToken,
+    hash_count: &AtomicU64,
+) -> Option<BlockHeader> {
+    search_pow_for_block(header, None, || cancel.load(Ordering::Relaxed), hash_count)
+}
+
+/// Generate Proof of Work for a block searching only the nonces of the given partition,
+/// or random nonces when not partitioned
+///
+/// Partitioned nonces are a big-endian counter from the partition range followed by
+/// a random salt, so miners given different partitions never try the same nonce.
+///
+/// ### Arguments
+///
+/// * `header`     - The header for PoW
+/// * `partition`  - Share of the nonce space to search
+/// * `cancel`     - Token set to abort the search
+/// * `hash_count` - Count of hashes attempted
+pub fn generate_pow_for_block_partitioned(
+    header: BlockHeader,
+    partition: Option<NoncePartition>,
+    cancel: &CancellationToken,
+    hash_count: &AtomicU64,
+) -> Option<BlockHeader> {
+    let counters = partition.map(nonce_partition_range);
+    search_pow_for_block(
+        header,
+        counters,
+        || cancel.load(Ordering::Relaxed),
+        hash_count,
+    )
+}
+
+/// Generate Proof of Work for a block across worker threads, each searching its own
+/// block of the partition nonces, or random nonces when not partitioned
+///
+/// All workers stop as soon as one finds a valid nonce or the search is cancelled,
+/// and every hash they attempted is added to the count.
+///
+/// ### Arguments
+///
+/// * `header`     - The header for PoW
+/// * `partition`  - Share of the nonce space to search
+/// * `threads`    - Number of worker threads
+/// * `cancel`     - Token set to abort the search
+/// * `hash_count` - Count of hashes attempted
+pub fn generate_pow_for_block_threaded(
+    header: BlockHeader,
+    partition: Option<NoncePartition>,
+    threads: usize,
+    cancel: &CancellationToken,
+    hash_count: &AtomicU64,
+) -> Option<BlockHeader> {
+    let threads = threads.max(1);
+    let blocks: Vec<Option<Range<u64>>> = match partition.map(nonce_partition_range) {
+        Some(counters) if !counters.is_empty() => {
+            split_range_into_blocks(counters.start, counters.end, threads as u64)
+                .into_iter()
+                .filter(|block| !block.is_empty())
+                .map(Some)
+                .collect()
+        }
+        _ => vec![None; threads],
+    };
+
+    let found = AtomicBool::new(false);
+    let stopped = || cancel.load(Ordering::Relaxed) || found.load(Ordering::Relaxed);
+    std::thread::scope(|s| {
+        let handles: Vec<_> = blocks
+            .into_iter()
+            .map(|block| {
+                let (header, stopped, found) = (header.clone(), &stopped, &found);
+                s.spawn(move || {
+                    let header = search_pow_for_block(header, block, stopped, hash_count);
+                    if header.is_some() {
+                        found.store(true, Ordering::Relaxed);
+                    }
+                    header
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap())
+            .next()
+    })
+}
+
+/// Search a valid Proof of Work nonce for a block until stopped
+///
+/// Nonces are a big-endian counter from `counters` followed by a random salt,
+/// or random nonces when there is no counter to search.
+///
+/// ### Arguments
+///
+/// * `header`     - The header for PoW
+/// * `counters`   - Nonce counters to search
+/// * `stopped`    - Whether to abort the search
+/// * `hash_count` - Count of hashes attempted
+fn search_pow_for_block(
+    mut header: BlockHeader,
+    counters: Option<Range<u64>>,
+    stopped: impl Fn() -> bool,
+    hash_count: &AtomicU64,
+) -> Option<BlockHeader> {
+    let counters = match counters {
+        Some(counters) if !counters.is_empty() => counters,
+        _ => loop {
+            if stopped() {
+                return None;
+            }
+            header.nonce_and_mining_tx_hash.0 = generate_pow_nonce();
+            hash_count.fetch_add(1, Ordering::Relaxed);
+            if validate_pow_block(&header) {
+                return Some(header);
+            }
+        },
+    };
+
+    loop {
+        let salt = generate_random_num(8);
+        for counter in counters.clone() {
+            if stopped() {
+                return None;
+            }
+            header.nonce_and_mining_tx_hash.0 = [&counter.to_be_bytes()[..], &salt].concat();
+            hash_count.fetch_add(1, Ordering::Relaxed);
+            if validate_pow_block(&header) {
+                return Some(header);
+            }
+        }
     }
-    header
+}
+
+/// Split `start..end` into `count` contiguous blocks of near equal length,
+/// the first blocks taking one extra value each when it does not divide evenly
+///
+/// ### Arguments
+///
+/// * `start` - First value of the range
+/// * `end`   - End of the range, excluded
+/// * `count` - Number of blocks
+pub fn split_range_into_blocks(start: u64, end: u64, count: u64) -> Vec<Range<u64>> {
+    (0..count)
+        .map(|index| range_block(start, end, count, index))
+        .collect()
+}
+
+/// Range of nonce counters searched by a partition
+///
+/// ### Arguments
+///
+/// * `partition` - Share of the nonce space
+pub fn nonce_partition_range(partition: NoncePartition) -> Range<u64> {
+    if partition.index >= partition.count {
+        return 0..0;
+    }
+    range_block(0, u64::MAX, partition.count, partition.index)
+}
+
+/// Counter a partitioned PoW nonce was generated from
+///
+/// ### Arguments
+///
+/// * `nonce` - Nonce generated by `generate_pow_for_block_partitioned`
+pub fn nonce_partition_counter(nonce: &[u8]) -> Option<u64> {
+    Some(u64::from_be_bytes(nonce.get(..8)?.try_into().ok()?))
+}
+
+/// Block `index` of `start..end` split into `count` blocks, with `index < count`
+fn range_block(start: u64, end: u64, count: u64, index: u64) -> Range<u64> {
+    let len = end.saturating_sub(start);
+    let (size, remainder) = (len / count, len % count);
+    let block_start = start + index * size + index.min(remainder);
+    block_start..block_start + size + u64::from(index < remainder)
 }
 
 /// Verify block is valid & consistent: Can be fully verified from PoW hash.
@@ -644,6 +863,38 @@ pub fn validate_pow_block(header: &BlockHeader) -> bool {
     validate_pow_block_hash(header).is_some()
 }
 
+/// Validate Proof of Work for several candidate blocks in parallel,
+/// returning the index of the first valid one
+///
+/// ### Arguments
+///
+/// * `headers`   - The headers for PoW, in priority order
+pub fn find_first_valid_pow_block(headers: &[BlockHeader]) -> Option<usize> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_len = headers.len().div_ceil(threads).max(1);
+
+    let valid: Vec<bool> = std::thread::scope(|s| {
+        let handles: Vec<_> = headers
+            .chunks(chunk_len)
+            .map(|chunk| s.spawn(move || chunk.iter().map(validate_pow_block).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    valid.iter().position(|valid| *valid)
+}
+
+/// Identify the block template sent to miners, before any nonce or mining transaction is applied
+///
+/// ### Arguments
+///
+/// * `header`   - The header of the block being mined
+pub fn construct_block_template_id(header: &BlockHeader) -> String {
+    hex::encode(sha3_256::digest(&serialize(header).unwrap()))
+}
+
 /// Validate Proof of Work for a block with a mining transaction returning the PoW hash
 ///
 /// ### Arguments
@@ -846,14 +1097,15 @@ pub fn make_utxo_set_from_seed(
                 outputs: tx_out
                     .iter()
                     .map(|out| {
-                        let script_public_key =
-                            if let Some(addr) = pk_to_address.get(&out.public_key) {
-                                addr.clone()
-                            } else {
-                                let addr = decode_pub_key_as_address(&out.public_key);
-                                pk_to_address.insert(out.public_key.clone(), addr.clone());
-                                addr
-                            };
+                        let script_public_key = if let Some(addr) = &out.address {
+                            addr.clone()
+                        } else if let Some(addr) = pk_to_address.get(&out.public_key) {
+                            addr.clone()
+                        } else {
+                            let addr = decode_pub_key_as_address(&out.public_key);
+                            pk_to_address.insert(out.public_key.clone(), addr.clone());
+                            addr
+                        };
 
                         TxOut::new_token_amount(script_public_key, out.amount, Some(out.locktime))
                     })
@@ -895,6 +1147,16 @@ pub fn decode_wallet_out_point(out_point: &str) -> OutPoint {
     OutPoint::new(tx_hash, n)
 }
 
+/// Whether a string is a well-formed payment address: hex encoded,
+/// with the length of a current or of a legacy address
+///
+/// ### Arguments
+///
+/// * `address`    - Address to check
+pub fn is_valid_payment_address(address: &str) -> bool {
+    matches!(address.len(), 32 | 64) && address.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
 /// Decodes the public key as address
 ///
 /// ### Arguments
@@ -953,8 +1215,10 @@ pub fn decode_signature(sig: &str) -> Result<Signature, StringError> {
 ///
 /// * `node_conn` - Node to use for connections
 pub async fn shutdown_connections(node_conn: &mut Node) {
+    node_conn.untrack_peers(None).await;
     node_conn.abort_heartbeat_handle();
     join_all(node_conn.stop_listening().await).await;
+    node_conn.notify_disconnect_all().await;
     join_all(node_conn.disconnect_all(None).await).await;
 }
 
@@ -976,18 +1240,26 @@ pub fn loops_re_connect_disconnect(
     let mut local_events_tx_for_disconnect = local_events_tx.clone();
     // PERMANENT CONNEXION HANDLING
     let re_connect = {
-        let (stop_re_connect_tx, stop_re_connect_rx) = tokio::sync::oneshot::channel::<()>();
+        let (stop_re_connect_tx, mut stop_re_connect_rx) = tokio::sync::oneshot::channel::<()>();
         let node_conn = node_conn.clone();
         (
             async move {
                 info!("Start connect to requested peers");
-                loop_connnect_to_peers_async(
-                    node_conn,
-                    addrs_to_connect,
-                    Some(stop_re_connect_rx),
-                    local_events_tx,
-                )
-                .await;
+                // Lost connections are reconnected to by the node with a backoff
+                node_conn.track_peers(&addrs_to_connect).await;
+                tokio::select! {
+                    _ = loop_connnect_to_peers_async(
+                        node_conn.clone(),
+                        addrs_to_connect,
+                        None,
+                        local_events_tx,
+                    ) => {
+                        info!("Connect complete");
+                        let _ = (&mut stop_re_connect_rx).await;
+                    }
+                    _ = &mut stop_re_connect_rx => (),
+                };
+                node_conn.untrack_peers(None).await;
                 info!("Reconnect complete");
             },
             stop_re_connect_tx,
@@ -1121,6 +1393,45 @@ async fn shutdown_on_path(
     }
 }
 
+/// Wait for an interrupt or terminate signal and trigger an immediate shutdown.
+///
+/// ### Arguments
+///
+/// * `local_events_tx` - Channel for local events
+pub async fn exit_on_shutdown_signal(mut local_events_tx: LocalEventSender) {
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Could not listen for SIGTERM ({:?})", e);
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => {
+            if let Err(e) = result {
+                warn!("Could not listen for interrupt ({:?})", e);
+                return;
+            }
+        }
+        _ = terminate => (),
+    }
+
+    warn!("shutdown from signal");
+    let event = LocalEvent::Exit("Shutdown");
+    if let Err(e) = local_events_tx.send(event, "signal_shutdown").await {
+        warn!("Cound not send {:?} ({:?})", event, e);
+    }
+}
+
 /// Get all the script_public_key and OutPoint from the (hash,transactions)
 ///
 /// ### Arguments
@@ -1381,4 +1692,179 @@ mod util_tests {
             SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 12300)
         );
     }
+
+    #[test]
+    /// Tests that setting the token mid-search aborts block PoW generation
+    fn test_generate_pow_for_block_cancelled() {
+        // Target of 1: no nonce will realistically be found
+        let header = BlockHeader {
+            difficulty: vec![0x03, 0x00, 0x00, 0x01],
+            ..Default::default()
+        };
+        let cancel: CancellationToken = Default::default();
+
+        let start = std::time::Instant::now();
+        let result = std::thread::scope(|s| {
+            let search = s.spawn(|| generate_pow_for_block_cancellable(header, &cancel));
+            std::thread::sleep(Duration::from_millis(50));
+            cancel.store(true, Ordering::Relaxed);
+            search.join().unwrap()
+        });
+
+        assert!(result.is_none());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    /// Tests the block reward at the start of issuance and at the tail emission boundaries
+    fn test_calculate_reward_boundaries() {
+        let tail = D_DISPLAY_PLACES_U64 * REWARD_SMOOTHING_VAL as u64;
+        let decay_step = 1u64 << REWARD_ISSUANCE_VAL;
+
+        assert_eq!(
+            calculate_reward(TokenAmount(0)),
+            TokenAmount((TOTAL_TOKENS >> REWARD_ISSUANCE_VAL) + tail)
+        );
+        assert_eq!(
+            calculate_reward(TokenAmount(TOTAL_TOKENS - decay_step)),
+            TokenAmount(tail + 1)
+        );
+        assert_eq!(
+            calculate_reward(TokenAmount(TOTAL_TOKENS - decay_step + 1)),
+            TokenAmount(tail)
+        );
+        assert_eq!(
+            calculate_reward(TokenAmount(TOTAL_TOKENS)),
+            TokenAmount(tail)
+        );
+        assert_eq!(calculate_reward(TokenAmount(u64::MAX)), TokenAmount(tail));
+    }
+
+    #[test]
+    /// Tests that the block reward never increases with issuance, and that issuing its
+    /// non-tail part never takes the issuance past the total supply
+    fn test_calculate_reward_issuance_bounded() {
+        use rand::{Rng, SeedableRng};
+        let tail = D_DISPLAY_PLACES_U64 * REWARD_SMOOTHING_VAL as u64;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+        for _ in 0..10_000 {
+            let (a, b) = (
+                rng.gen_range(0, TOTAL_TOKENS),
+                rng.gen_range(0, TOTAL_TOKENS),
+            );
+            let (low, high) = (a.min(b), a.max(b));
+            let reward = calculate_reward(TokenAmount(low));
+
+            assert!(reward.0 >= calculate_reward(TokenAmount(high)).0);
+            assert!(low + (reward.0 - tail) <= TOTAL_TOKENS);
+        }
+
+        // Issuing every block reward from genesis converges on the total supply
+        let mut issuance = 0;
+        for _ in 0..1_000_000 {
+            issuance += calculate_reward(TokenAmount(issuance)).0 - tail;
+            assert!(issuance <= TOTAL_TOKENS);
+        }
+    }
+
+    #[test]
+    /// Tests that split blocks cover the range without overlap
+    fn test_split_range_into_blocks() {
+        let blocks = split_range_into_blocks(10, 20, 3);
+        let full = split_range_into_blocks(0, u64::MAX, 3);
+
+        assert_eq!(blocks, vec![10..14, 14..17, 17..20]);
+        assert_eq!(full.first().map(|b| b.start), Some(0));
+        assert_eq!(full.last().map(|b| b.end), Some(u64::MAX));
+        assert!(full.windows(2).all(|w| w[0].end == w[1].start));
+        assert_eq!(
+            nonce_partition_range(NoncePartition { index: 1, count: 3 }),
+            full[1]
+        );
+        assert!(nonce_partition_range(NoncePartition { index: 3, count: 3 }).is_empty());
+    }
+
+    #[test]
+    /// Tests that partitioned block PoW uses a nonce from its partition
+    fn test_generate_pow_for_block_partitioned() {
+        let partition = NoncePartition { index: 2, count: 3 };
+        let cancel: CancellationToken = Default::default();
+
+        let header = generate_pow_for_block_partitioned(
+            Default::default(),
+            Some(partition),
+            &cancel,
+            &AtomicU64::new(0),
+        )
+        .unwrap();
+        let nonce = &header.nonce_and_mining_tx_hash.0;
+
+        assert!(validate_pow_block(&header));
+        assert_eq!(nonce.len(), 16);
+        assert!(nonce_partition_range(partition).contains(&nonce_partition_counter(nonce).unwrap()));
+    }
+
+    #[test]
+    /// Tests that threaded block PoW finds a nonce from its partition meeting leading zeroes
+    fn test_generate_pow_for_block_threaded() {
+        let partition = NoncePartition { index: 1, count: 3 };
+        let cancel: CancellationToken = Default::default();
+        let hash_count = AtomicU64::new(0);
+
+        let header = generate_pow_for_block_threaded(
+            Default::default(),
+            Some(partition),
+            4,
+            &cancel,
+            &hash_count,
+        )
+        .unwrap();
+        let nonce = &header.nonce_and_mining_tx_hash.0;
+        let pow_hash = sha3_256::digest(&serialize(&header).unwrap());
+
+        assert!(pow_hash[..MINING_DIFFICULTY].iter().all(|v| *v == 0));
+        assert!(validate_pow_block(&header));
+        assert!(nonce_partition_range(partition).contains(&nonce_partition_counter(nonce).unwrap()));
+        assert!(hash_count.load(Ordering::Relaxed) >= 1);
+    }
+
+    #[test]
+    /// Tests that threaded block PoW finds a nonce meeting a compact target
+    fn test_generate_pow_for_block_threaded_compact_target() {
+        use crate::asert::{HeaderHash, Target};
+
+        let target = Target::from_leading_zeroes(1);
+        let header = BlockHeader {
+            difficulty: target.compact().into_array().to_vec(),
+            ..Default::default()
+        };
+        let cancel: CancellationToken = Default::default();
+
+        let header =
+            generate_pow_for_block_threaded(header, None, 4, &cancel, &AtomicU64::new(0)).unwrap();
+        let header_hash = HeaderHash::try_calculate(&header).unwrap();
+
+        assert!(header_hash.is_below_target(&target.compact().expand()));
+        assert!(validate_pow_block(&header));
+    }
+
+    #[test]
+    /// Tests that a cancelled threaded block PoW stops all workers without a nonce
+    fn test_generate_pow_for_block_threaded_cancelled() {
+        let partition = NoncePartition { index: 0, count: 2 };
+        let cancel: CancellationToken = Arc::new(AtomicBool::new(true));
+        let hash_count = AtomicU64::new(0);
+
+        let header = generate_pow_for_block_threaded(
+            Default::default(),
+            Some(partition),
+            4,
+            &cancel,
+            &hash_count,
+        );
+
+        assert!(header.is_none());
+        assert_eq!(hash_count.load(Ordering::Relaxed), 0);
+    }
 }
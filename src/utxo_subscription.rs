@@ -0,0 +1,811 @@
+use crate::constants::{
+    UTXO_SUBSCRIPTION_ADDRESS_CAP, UTXO_SUBSCRIPTION_CAPACITY,
+    UTXO_SUBSCRIPTION_CHECKPOINT_INTERVAL, UTXO_SUBSCRIPTION_HISTORY_LEN,
+    UTXO_SUBSCRIPTION_MAX_REPLAY_RANGE,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+use tw_chain::primitives::asset::Asset;
+use tw_chain::primitives::transaction::{OutPoint, Transaction, TxOut};
+
+/// Identifier of a single subscribed connection
+pub type SubscriberId = u64;
+
+/// Shared UTXO subscriptions between the node and its API
+pub type UtxoSubscriptionsHandle = Arc<Mutex<UtxoSubscriptions>>;
+
+/// Whether a UTXO was created or spent by a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UtxoDirection {
+    Created,
+    Spent,
+}
+
+/// A UTXO creation or spend affecting a subscribed address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoEvent {
+    pub block_num: u64,
+    pub out_point: OutPoint,
+    pub address: String,
+    pub amount: Asset,
+    pub direction: UtxoDirection,
+}
+
+/// Messages sent by a client over its subscription connection
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum UtxoSubscriptionRequest {
+    AddAddresses { addresses: Vec<String> },
+    RemoveAddresses { addresses: Vec<String> },
+    Replay { from_block: u64, to_block: u64 },
+}
+
+/// Messages sent to a client over its subscription connection
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum UtxoSubscriptionMessage {
+    Event(UtxoEvent),
+    Checkpoint { block_num: u64 },
+    Subscribed { address_count: usize },
+    ReplayComplete { from_block: u64, to_block: u64 },
+    Error(UtxoSubscriptionError),
+}
+
+/// Structured error returned to a subscribed client
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code")]
+pub enum UtxoSubscriptionError {
+    AddressCapExceeded { cap: usize, requested: usize },
+    ReplayRangeInvalid { from_block: u64, to_block: u64 },
+    ReplayRangeTooLarge { max_range: u64 },
+    ReplayRangeUnavailable { oldest_block: Option<u64> },
+    InvalidRequest { message: String },
+    UnknownSubscriber,
+    SubscriberLagged { capacity: usize },
+}
+
+impl fmt::Display for UtxoSubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddressCapExceeded { cap, requested } => {
+                write!(f, "Address cap exceeded: {requested} > {cap}")
+            }
+            Self::ReplayRangeInvalid {
+                from_block,
+                to_block,
+            } => write!(f, "Invalid replay range: {from_block}..={to_block}"),
+            Self::ReplayRangeTooLarge { max_range } => {
+                write!(f, "Replay range larger than {max_range} blocks")
+            }
+            Self::ReplayRangeUnavailable { oldest_block } => {
+                write!(
+                    f,
+                    "Replay range unavailable, oldest block: {oldest_block:?}"
+                )
+            }
+            Self::InvalidRequest { message } => write!(f, "Invalid request: {message}"),
+            Self::UnknownSubscriber => write!(f, "Unknown subscriber"),
+            Self::SubscriberLagged { capacity } => {
+                write!(f, "Subscriber fell more than {capacity} messages behind")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UtxoSubscriptionError {}
+
+/// A single subscribed connection
+#[derive(Debug)]
+struct Subscriber {
+    addresses: BTreeSet<String>,
+    tx: mpsc::Sender<UtxoSubscriptionMessage>,
+}
+
+/// Routes UTXO events of applied blocks to the subscribers of the affected addresses.
+///
+/// Invariant: `index` contains exactly the addresses of all `subscribers`
+#[derive(Debug)]
+pub struct UtxoSubscriptions {
+    /// Next identifier to give to a subscriber
+    next_id: SubscriberId,
+    /// Maximum number of addresses per subscriber
+    address_cap: usize,
+    /// Subscribed connections
+    subscribers: HashMap<SubscriberId, Subscriber>,
+    /// Shared address to subscribers index
+    index: HashMap<String, BTreeSet<SubscriberId>>,
+    /// Events of the most recent blocks for gap replay
+    history: VecDeque<(u64, Vec<UtxoEvent>)>,
+    /// Maximum number of blocks kept in `history`
+    history_len: usize,
+    /// Maximum number of messages queued for a subscriber
+    capacity: usize,
+}
+
+impl Default for UtxoSubscriptions {
+    fn default() -> Self {
+        Self::new(
+            UTXO_SUBSCRIPTION_ADDRESS_CAP,
+            UTXO_SUBSCRIPTION_HISTORY_LEN,
+            UTXO_SUBSCRIPTION_CAPACITY,
+        )
+    }
+}
+
+impl UtxoSubscriptions {
+    /// Create a new UtxoSubscriptions
+    ///
+    /// ### Arguments
+    ///
+    /// * `address_cap` - Maximum number of addresses per subscriber
+    /// * `history_len` - Number of blocks kept for gap replay
+    /// * `capacity`    - Number of messages a subscriber may fall behind before being dropped
+    pub fn new(address_cap: usize, history_len: usize, capacity: usize) -> Self {
+        Self {
+            next_id: 0,
+            address_cap,
+            subscribers: Default::default(),
+            index: Default::default(),
+            history: Default::default(),
+            history_len,
+            capacity,
+        }
+    }
+
+    /// Create a new shareable handle with default limits
+    pub fn new_handle() -> UtxoSubscriptionsHandle {
+        Arc::new(Mutex::new(Default::default()))
+    }
+
+    /// Register a new subscriber with no address.
+    /// One slot past the capacity is kept for the error sent when it is dropped.
+    pub fn subscribe(&mut self) -> (SubscriberId, mpsc::Receiver<UtxoSubscriptionMessage>) {
+        let (tx, rx) = mpsc::channel(self.capacity + 1);
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let addresses = Default::default();
+        self.subscribers.insert(id, Subscriber { addresses, tx });
+        debug!("UTXO subscriber {} added", id);
+        (id, rx)
+    }
+
+    /// Remove a subscriber and all its addresses
+    ///
+    /// ### Arguments
+    ///
+    /// * `id` - The subscriber to remove
+    pub fn unsubscribe(&mut self, id: SubscriberId) {
+        if let Some(subscriber) = self.subscribers.remove(&id) {
+            for address in &subscriber.addresses {
+                self.remove_from_index(address, id);
+            }
+            debug!("UTXO subscriber {} removed", id);
+        }
+    }
+
+    /// Add addresses to a subscriber, returning its new address count
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`        - The subscriber
+    /// * `addresses` - Addresses to add
+    pub fn add_addresses(
+        &mut self,
+        id: SubscriberId,
+        addresses: Vec<String>,
+    ) -> Result<usize, UtxoSubscriptionError> {
+        let subscriber = self
+            .subscribers
+            .get_mut(&id)
+            .ok_or(UtxoSubscriptionError::UnknownSubscriber)?;
+
+        let new_addresses: BTreeSet<String> = addresses
+            .into_iter()
+            .filter(|a| !subscriber.addresses.contains(a))
+            .collect();
+
+        let requested = subscriber.addresses.len() + new_addresses.len();
+        if requested > self.address_cap {
+            return Err(UtxoSubscriptionError::AddressCapExceeded {
+                cap: self.address_cap,
+                requested,
+            });
+        }
+
+        for address in new_addresses {
+            self.index.entry(address.clone()).or_default().insert(id);
+            subscriber.addresses.insert(address);
+        }
+        Ok(subscriber.addresses.len())
+    }
+
+    /// Remove addresses from a subscriber, returning its new address count
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`        - The subscriber
+    /// * `addresses` - Addresses to remove
+    pub fn remove_addresses(
+        &mut self,
+        id: SubscriberId,
+        addresses: Vec<String>,
+    ) -> Result<usize, UtxoSubscriptionError> {
+        let subscriber = self
+            .subscribers
+            .get_mut(&id)
+            .ok_or(UtxoSubscriptionError::UnknownSubscriber)?;
+
+        let removed: Vec<String> = addresses
+            .into_iter()
+            .filter(|a| subscriber.addresses.remove(a))
+            .collect();
+        let address_count = subscriber.addresses.len();
+
+        for address in &removed {
+            self.remove_from_index(address, id);
+        }
+        Ok(address_count)
+    }
+
+    /// Route the events of an applied block to the relevant subscribers,
+    /// and record them for gap replay.
+    ///
+    /// ### Arguments
+    ///
+    /// * `block_num` - The applied block number
+    /// * `events`    - All UTXO events of the block
+    pub fn publish_block(&mut self, block_num: u64, events: Vec<UtxoEvent>) {
+        self.send_events(block_num, &events);
+
+        trace!("UTXO events published for block {}", block_num);
+        self.history.push_back((block_num, events));
+        while self.history.len() > self.history_len {
+            self.history.pop_front();
+        }
+    }
+
+    /// Note an applied block whose events were not computed because no address
+    /// was subscribed. Replay cannot span it, so the recorded history is dropped.
+    ///
+    /// ### Arguments
+    ///
+    /// * `block_num` - The applied block number
+    pub fn skip_block(&mut self, block_num: u64) {
+        self.send_events(block_num, &[]);
+
+        trace!("UTXO events skipped for block {}", block_num);
+        self.history.clear();
+    }
+
+    /// Whether any subscriber tracks an address, so block events are needed
+    pub fn has_subscribed_addresses(&self) -> bool {
+        !self.index.is_empty()
+    }
+
+    /// Send the events of an applied block to the relevant subscribers,
+    /// and a checkpoint to all of them at the checkpoint interval.
+    fn send_events(&mut self, block_num: u64, events: &[UtxoEvent]) {
+        let mut dropped = Vec::new();
+        for event in events {
+            let ids = match self.index.get(&event.address) {
+                Some(ids) => ids,
+                None => continue,
+            };
+
+            for id in ids {
+                if dropped.contains(id) {
+                    continue;
+                }
+                let subscriber = &self.subscribers[id];
+                let msg = UtxoSubscriptionMessage::Event(event.clone());
+                if !self.send_message(subscriber, msg) {
+                    dropped.push(*id);
+                }
+            }
+        }
+
+        if block_num % UTXO_SUBSCRIPTION_CHECKPOINT_INTERVAL == 0 {
+            for (id, subscriber) in &self.subscribers {
+                if dropped.contains(id) {
+                    continue;
+                }
+                let msg = UtxoSubscriptionMessage::Checkpoint { block_num };
+                if !self.send_message(subscriber, msg) {
+                    dropped.push(*id);
+                }
+            }
+        }
+
+        for id in dropped {
+            self.unsubscribe(id);
+        }
+    }
+
+    /// Queue a message for a subscriber, returning false if it must be dropped.
+    /// A subscriber reaching its capacity is sent a `SubscriberLagged` error
+    /// in the reserved last slot instead of the message.
+    fn send_message(&self, subscriber: &Subscriber, msg: UtxoSubscriptionMessage) -> bool {
+        if subscriber.tx.capacity() > 1 {
+            return subscriber.tx.try_send(msg).is_ok();
+        }
+
+        let capacity = self.capacity;
+        let error = UtxoSubscriptionError::SubscriberLagged { capacity };
+        let _ = subscriber
+            .tx
+            .try_send(UtxoSubscriptionMessage::Error(error));
+        false
+    }
+
+    /// Get the events of the given subscriber for an inclusive block range,
+    /// followed by a `ReplayComplete` message for the range.
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`         - The subscriber
+    /// * `from_block` - First block of the range
+    /// * `to_block`   - Last block of the range
+    pub fn replay(
+        &self,
+        id: SubscriberId,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<UtxoSubscriptionMessage>, UtxoSubscriptionError> {
+        let subscriber = self
+            .subscribers
+            .get(&id)
+            .ok_or(UtxoSubscriptionError::UnknownSubscriber)?;
+
+        if from_block > to_block {
+            return Err(UtxoSubscriptionError::ReplayRangeInvalid {
+                from_block,
+                to_block,
+            });
+        }
+        if to_block - from_block >= UTXO_SUBSCRIPTION_MAX_REPLAY_RANGE {
+            return Err(UtxoSubscriptionError::ReplayRangeTooLarge {
+                max_range: UTXO_SUBSCRIPTION_MAX_REPLAY_RANGE,
+            });
+        }
+
+        let oldest_block = self.history.front().map(|(b_num, _)| *b_num);
+        let newest_block = self.history.back().map(|(b_num, _)| *b_num);
+        match (oldest_block, newest_block) {
+            (Some(oldest), Some(newest)) if oldest <= from_block && to_block <= newest => (),
+            _ => return Err(UtxoSubscriptionError::ReplayRangeUnavailable { oldest_block }),
+        }
+
+        let mut messages: Vec<_> = self
+            .history
+            .iter()
+            .filter(|(b_num, _)| (from_block..=to_block).contains(b_num))
+            .flat_map(|(_, events)| events.iter())
+            .filter(|event| subscriber.addresses.contains(&event.address))
+            .cloned()
+            .map(UtxoSubscriptionMessage::Event)
+            .collect();
+        messages.push(UtxoSubscriptionMessage::ReplayComplete {
+            from_block,
+            to_block,
+        });
+
+        Ok(messages)
+    }
+
+    /// Handle a request received from a subscriber connection
+    /// and return the messages to send back.
+    ///
+    /// ### Arguments
+    ///
+    /// * `id`      - The subscriber
+    /// * `request` - The received request
+    pub fn handle_request(
+        &mut self,
+        id: SubscriberId,
+        request: UtxoSubscriptionRequest,
+    ) -> Vec<UtxoSubscriptionMessage> {
+        use UtxoSubscriptionRequest::*;
+
+        let result = match request {
+            AddAddresses { addresses } => self
+                .add_addresses(id, addresses)
+                .map(|address_count| vec![UtxoSubscriptionMessage::Subscribed { address_count }]),
+            RemoveAddresses { addresses } => self
+                .remove_addresses(id, addresses)
+                .map(|address_count| vec![UtxoSubscriptionMessage::Subscribed { address_count }]),
+            Replay {
+                from_block,
+                to_block,
+            } => self.replay(id, from_block, to_block),
+        };
+
+        result.unwrap_or_else(|e| vec![UtxoSubscriptionMessage::Error(e)])
+    }
+
+    /// Number of subscribers tracking the given address
+    pub fn get_address_subscriber_count(&self, address: &str) -> usize {
+        self.index.get(address).map(|ids| ids.len()).unwrap_or(0)
+    }
+
+    /// Remove a subscriber from the index entry of an address
+    fn remove_from_index(&mut self, address: &str, id: SubscriberId) {
+        if let Some(ids) = self.index.get_mut(address) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.index.remove(address);
+            }
+        }
+    }
+}
+
+/// Get all the UTXO events for an applied block.
+///
+/// ### Arguments
+///
+/// * `block_num`       - The applied block number
+/// * `block_txs`       - The block transactions in block order
+/// * `get_previous_out` - Lookup for spent outputs not created in this block
+pub fn utxo_events_for_block<'a>(
+    block_num: u64,
+    block_txs: impl Iterator<Item = (&'a String, &'a Transaction)> + Clone,
+    mut get_previous_out: impl FnMut(&OutPoint) -> Option<TxOut>,
+) -> Vec<UtxoEvent> {
+    let created: BTreeMap<OutPoint, &TxOut> = block_txs
+        .clone()
+        .flat_map(|(hash, tx)| {
+            let outputs = tx.outputs.iter().enumerate();
+            outputs.map(move |(n, tx_out)| (OutPoint::new(hash.clone(), n as i32), tx_out))
+        })
+        .collect();
+
+    let mut events = Vec::new();
+    for (hash, tx) in block_txs {
+        for out_point in tx.inputs.iter().filter_map(|i| i.previous_out.as_ref()) {
+            let tx_out = match created.get(out_point) {
+                Some(tx_out) => Some((*tx_out).clone()),
+                None => get_previous_out(out_point),
+            };
+
+            if let Some(TxOut {
+                value,
+                script_public_key: Some(address),
+                ..
+            }) = tx_out
+            {
+                events.push(UtxoEvent {
+                    block_num,
+                    out_point: out_point.clone(),
+                    address,
+                    amount: value,
+                    direction: UtxoDirection::Spent,
+                });
+            }
+        }
+
+        for (n, tx_out) in tx.outputs.iter().enumerate() {
+            if let Some(address) = &tx_out.script_public_key {
+                events.push(UtxoEvent {
+                    block_num,
+                    out_point: OutPoint::new(hash.clone(), n as i32),
+                    address: address.clone(),
+                    amount: tx_out.value.clone(),
+                    direction: UtxoDirection::Created,
+                });
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tw_chain::primitives::asset::TokenAmount;
+    use tw_chain::primitives::transaction::TxIn;
+    use tw_chain::script::lang::Script;
+    use tw_chain::utils::transaction_utils::{construct_tx_core, construct_tx_hash};
+
+    #[test]
+    fn overlapping_subscribers_receive_own_events() {
+        //
+        // Arrange
+        //
+        let mut subs = UtxoSubscriptions::new(10, 10, 10);
+        let (id_1, mut rx_1) = subs.subscribe();
+        let (id_2, mut rx_2) = subs.subscribe();
+        subs.add_addresses(id_1, addrs(&["a", "b"])).unwrap();
+        subs.add_addresses(id_2, addrs(&["b", "c"])).unwrap();
+
+        let (hash_1, tx_1) = tx_with(&[], &["a", "b", "d"]);
+        let (hash_2, tx_2) = tx_with(&[(&hash_1, 1)], &["c"]);
+        let (hash_3, tx_3) = tx_with(&[(&hash_1, 0), (&hash_2, 0)], &["a"]);
+
+        //
+        // Act
+        //
+        publish(&mut subs, 1, &[(&hash_1, &tx_1)]);
+        publish(&mut subs, 2, &[(&hash_2, &tx_2)]);
+        subs.remove_addresses(id_1, addrs(&["b"])).unwrap();
+        publish(&mut subs, 3, &[(&hash_3, &tx_3)]);
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            received(&mut rx_1),
+            expected(&[
+                (1, "a", UtxoDirection::Created),
+                (1, "b", UtxoDirection::Created),
+                (2, "b", UtxoDirection::Spent),
+                (3, "a", UtxoDirection::Spent),
+                (3, "a", UtxoDirection::Created),
+            ])
+        );
+        assert_eq!(
+            received(&mut rx_2),
+            expected(&[
+                (1, "b", UtxoDirection::Created),
+                (2, "b", UtxoDirection::Spent),
+                (2, "c", UtxoDirection::Created),
+                (3, "c", UtxoDirection::Spent),
+            ])
+        );
+        assert_eq!(subs.get_address_subscriber_count("b"), 1);
+    }
+
+    #[test]
+    fn gap_replay_fills_missed_range() {
+        //
+        // Arrange
+        //
+        let mut subs = UtxoSubscriptions::new(10, 10, 10);
+        let (id, mut rx) = subs.subscribe();
+        subs.add_addresses(id, addrs(&["a"])).unwrap();
+
+        let (hash_1, tx_1) = tx_with(&[], &["a"]);
+        let (hash_2, tx_2) = tx_with(&[(&hash_1, 0)], &["a", "b"]);
+        let (hash_3, tx_3) = tx_with(&[(&hash_2, 1)], &["a"]);
+        publish(&mut subs, 1, &[(&hash_1, &tx_1)]);
+        publish(&mut subs, 2, &[(&hash_2, &tx_2)]);
+        publish(&mut subs, 3, &[(&hash_3, &tx_3)]);
+        let live = received(&mut rx);
+
+        //
+        // Act
+        //
+        let replayed = subs.replay(id, 2, 3).unwrap();
+        let out_of_history = subs.replay(id, 0, 1);
+        let inverted = subs.replay(id, 3, 2);
+
+        //
+        // Assert
+        //
+        let replayed_events: Vec<_> = replayed
+            .iter()
+            .filter_map(|m| match m {
+                UtxoSubscriptionMessage::Event(e) => {
+                    Some((e.block_num, e.address.clone(), e.direction))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(replayed_events, live[1..].to_vec());
+        assert_eq!(
+            replayed.last(),
+            Some(&UtxoSubscriptionMessage::ReplayComplete {
+                from_block: 2,
+                to_block: 3
+            })
+        );
+        assert_eq!(
+            out_of_history,
+            Err(UtxoSubscriptionError::ReplayRangeUnavailable {
+                oldest_block: Some(1)
+            })
+        );
+        assert_eq!(
+            inverted,
+            Err(UtxoSubscriptionError::ReplayRangeInvalid {
+                from_block: 3,
+                to_block: 2
+            })
+        );
+    }
+
+    #[test]
+    fn skipped_block_ends_replay_history() {
+        //
+        // Arrange
+        //
+        let mut subs = UtxoSubscriptions::new(10, 10, 10);
+        let (id, _rx) = subs.subscribe();
+
+        let (hash_1, tx_1) = tx_with(&[], &["a"]);
+        let (hash_3, tx_3) = tx_with(&[(&hash_1, 0)], &["a"]);
+        subs.add_addresses(id, addrs(&["a"])).unwrap();
+        publish(&mut subs, 1, &[(&hash_1, &tx_1)]);
+        subs.remove_addresses(id, addrs(&["a"])).unwrap();
+        let unsubscribed_has_addresses = subs.has_subscribed_addresses();
+        subs.skip_block(2);
+        subs.add_addresses(id, addrs(&["a"])).unwrap();
+        publish(&mut subs, 3, &[(&hash_3, &tx_3)]);
+
+        //
+        // Act
+        //
+        let across_skipped = subs.replay(id, 1, 3);
+        let after_skipped = subs.replay(id, 3, 3).unwrap();
+
+        //
+        // Assert
+        //
+        assert!(!unsubscribed_has_addresses);
+        assert!(subs.has_subscribed_addresses());
+        assert_eq!(
+            across_skipped,
+            Err(UtxoSubscriptionError::ReplayRangeUnavailable {
+                oldest_block: Some(3)
+            })
+        );
+        assert_eq!(after_skipped.len(), 2);
+    }
+
+    #[test]
+    fn address_cap_exceeded_returns_error() {
+        //
+        // Arrange
+        //
+        let mut subs = UtxoSubscriptions::new(2, 10, 10);
+        let (id, _rx) = subs.subscribe();
+        subs.add_addresses(id, addrs(&["a"])).unwrap();
+
+        //
+        // Act
+        //
+        let msgs = subs.handle_request(
+            id,
+            UtxoSubscriptionRequest::AddAddresses {
+                addresses: addrs(&["a", "b", "c"]),
+            },
+        );
+        let ser = serde_json::to_string(&msgs).unwrap();
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            msgs,
+            vec![UtxoSubscriptionMessage::Error(
+                UtxoSubscriptionError::AddressCapExceeded {
+                    cap: 2,
+                    requested: 3
+                }
+            )]
+        );
+        assert_eq!(
+            ser,
+            r#"[{"type":"Error","code":"AddressCapExceeded","cap":2,"requested":3}]"#
+        );
+        assert_eq!(subs.get_address_subscriber_count("b"), 0);
+    }
+
+    #[test]
+    fn lagging_subscriber_dropped_with_error() {
+        //
+        // Arrange
+        //
+        let mut subs = UtxoSubscriptions::new(10, 10, 2);
+        let (id, mut rx) = subs.subscribe();
+        let (other_id, mut other_rx) = subs.subscribe();
+        subs.add_addresses(id, addrs(&["a"])).unwrap();
+        subs.add_addresses(other_id, addrs(&["a"])).unwrap();
+
+        let (hash_1, tx_1) = tx_with(&[], &["a"]);
+        let (hash_2, tx_2) = tx_with(&[(&hash_1, 0)], &["a"]);
+        publish(&mut subs, 1, &[(&hash_1, &tx_1)]);
+        let other_received = received(&mut other_rx);
+
+        //
+        // Act
+        //
+        publish(&mut subs, 2, &[(&hash_2, &tx_2)]);
+        let mut queued = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            queued.push(msg);
+        }
+
+        //
+        // Assert
+        //
+        assert_eq!(queued.len(), 3);
+        assert_eq!(
+            queued.last(),
+            Some(&UtxoSubscriptionMessage::Error(
+                UtxoSubscriptionError::SubscriberLagged { capacity: 2 }
+            ))
+        );
+        assert_eq!(rx.try_recv(), Err(mpsc::error::TryRecvError::Disconnected));
+        assert_eq!(
+            subs.handle_request(
+                id,
+                UtxoSubscriptionRequest::RemoveAddresses {
+                    addresses: addrs(&["a"])
+                }
+            ),
+            vec![UtxoSubscriptionMessage::Error(
+                UtxoSubscriptionError::UnknownSubscriber
+            )]
+        );
+        assert_eq!(other_received.len(), 1);
+        assert_eq!(received(&mut other_rx).len(), 2);
+        assert_eq!(subs.get_address_subscriber_count("a"), 1);
+    }
+
+    fn addrs(addresses: &[&str]) -> Vec<String> {
+        addresses.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn tx_with(inputs: &[(&str, i32)], outputs: &[&str]) -> (String, Transaction) {
+        let tx_ins = inputs
+            .iter()
+            .map(|(hash, n)| TxIn {
+                previous_out: Some(OutPoint::new(hash.to_string(), *n)),
+                script_signature: Script::new(),
+            })
+            .collect();
+        let tx_outs = outputs
+            .iter()
+            .map(|address| TxOut::new_token_amount(address.to_string(), TokenAmount(1), None))
+            .collect();
+        let tx = construct_tx_core(tx_ins, tx_outs, None);
+        let hash = construct_tx_hash(&tx);
+        (hash, tx)
+    }
+
+    fn publish(subs: &mut UtxoSubscriptions, block_num: u64, txs: &[(&String, &Transaction)]) {
+        let all_previous: BTreeMap<OutPoint, TxOut> = subs
+            .history
+            .iter()
+            .flat_map(|(_, events)| events.iter())
+            .filter(|e| e.direction == UtxoDirection::Created)
+            .map(|e| {
+                let tx_out = TxOut {
+                    value: e.amount.clone(),
+                    script_public_key: Some(e.address.clone()),
+                    ..Default::default()
+                };
+                (e.out_point.clone(), tx_out)
+            })
+            .collect();
+
+        let events = utxo_events_for_block(block_num, txs.iter().copied(), |op| {
+            all_previous.get(op).cloned()
+        });
+        subs.publish_block(block_num, events);
+    }
+
+    fn received(
+        rx: &mut mpsc::Receiver<UtxoSubscriptionMessage>,
+    ) -> Vec<(u64, String, UtxoDirection)> {
+        let mut result = Vec::new();
+        while let Ok(msg) = rx.try_recv() {
+            if let UtxoSubscriptionMessage::Event(e) = msg {
+                result.push((e.block_num, e.address, e.direction));
+            }
+        }
+        result
+    }
+
+    fn expected(events: &[(u64, &str, UtxoDirection)]) -> Vec<(u64, String, UtxoDirection)> {
+        events
+            .iter()
+            .map(|(b_num, address, direction)| (*b_num, address.to_string(), *direction))
+            .collect()
+    }
+}
@@ -0,0 +1,599 @@
+//! Log of the events affecting the wallet balances, and period reports built from it.
+//!
+//! Block headers carry no timestamp, so each event records the time it was
+//! observed by the wallet alongside the current block number.
+
+use crate::constants::ACTIVITY_EXPORT_CHUNK_ROWS;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use tw_chain::primitives::asset::Asset;
+use tw_chain::primitives::transaction::OutPoint;
+
+/// Asset kind of token amounts, items use their genesis hash
+pub const TOKEN_ASSET_KIND: &str = "Token";
+
+/// Header line of CSV reports
+const CSV_HEADER: &str =
+    "b_num,timestamp,kind,tx_hash,counterparty,asset,credit,debit,fee,running_balance\n";
+
+/// Kind of event affecting the wallet balances
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivityKind {
+    Receive,
+    Send,
+    /// Transaction paying only to addresses of this wallet
+    Aggregation,
+    /// Correction after re-syncing the wallet with the UTXO set
+    Adjustment,
+}
+
+impl ActivityKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Receive => "Receive",
+            Self::Send => "Send",
+            Self::Aggregation => "Aggregation",
+            Self::Adjustment => "Adjustment",
+        }
+    }
+}
+
+/// Change to the balance of one asset kind
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletActivity {
+    pub b_num: u64,
+    /// Unix time in seconds the event was observed by the wallet
+    pub timestamp: u64,
+    pub kind: ActivityKind,
+    pub tx_hash: Option<String>,
+    pub counterparty: Option<String>,
+    pub asset: String,
+    pub credit: u64,
+    pub debit: u64,
+    /// Fees paid by the transaction, part of the debit
+    pub fee: u64,
+}
+
+/// Direction of a payment in the transaction history
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionDirection {
+    Sent,
+    Received,
+}
+
+/// Payment sent or received by the wallet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionHistoryEntry {
+    pub b_num: u64,
+    pub tx_hash: Option<String>,
+    pub direction: TransactionDirection,
+    pub asset: String,
+    pub amount: u64,
+    pub counterparty: Option<String>,
+}
+
+/// Range of a report, bounds included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivityRange {
+    BlockNum { start: u64, end: u64 },
+    Timestamp { start: u64, end: u64 },
+}
+
+impl ActivityRange {
+    /// Position of the activity relative to the range
+    fn compare(&self, activity: &WalletActivity) -> std::cmp::Ordering {
+        let (value, start, end) = match *self {
+            Self::BlockNum { start, end } => (activity.b_num, start, end),
+            Self::Timestamp { start, end } => (activity.timestamp, start, end),
+        };
+        if value < start {
+            std::cmp::Ordering::Less
+        } else if value > end {
+            std::cmp::Ordering::Greater
+        } else {
+            std::cmp::Ordering::Equal
+        }
+    }
+}
+
+/// Format of an exported report
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Activity with the balance of its asset kind once applied
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityReportRow {
+    #[serde(flatten)]
+    pub activity: WalletActivity,
+    pub running_balance: i64,
+}
+
+/// Wallet activity over a range, with the balances per asset kind around it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityReport {
+    pub opening_balances: BTreeMap<String, i64>,
+    pub entries: Vec<ActivityReportRow>,
+    pub closing_balances: BTreeMap<String, i64>,
+}
+
+impl ActivityReport {
+    /// Serialize the report in chunks of at most `ACTIVITY_EXPORT_CHUNK_ROWS` entries
+    ///
+    /// ### Arguments
+    ///
+    /// * `format` - Format of the serialized report
+    pub fn into_chunks(self, format: ExportFormat) -> impl Iterator<Item = Vec<u8>> + Send {
+        let (head, tail) = match format {
+            ExportFormat::Csv => (
+                format!(
+                    "{}{}",
+                    CSV_HEADER,
+                    csv_balances("Opening", &self.opening_balances)
+                ),
+                csv_balances("Closing", &self.closing_balances),
+            ),
+            ExportFormat::Json => (
+                format!(
+                    "{{\"opening_balances\":{},\"entries\":[",
+                    serde_json::to_string(&self.opening_balances).unwrap()
+                ),
+                format!(
+                    "],\"closing_balances\":{}}}",
+                    serde_json::to_string(&self.closing_balances).unwrap()
+                ),
+            ),
+        };
+
+        let mut entries = self.entries.into_iter().enumerate().peekable();
+        let body = std::iter::from_fn(move || {
+            entries.peek()?;
+            let mut chunk = String::new();
+            for (idx, row) in entries.by_ref().take(ACTIVITY_EXPORT_CHUNK_ROWS) {
+                match format {
+                    ExportFormat::Csv => chunk.push_str(&csv_row(&row)),
+                    ExportFormat::Json => {
+                        if idx > 0 {
+                            chunk.push(',');
+                        }
+                        chunk.push_str(&serde_json::to_string(&row).unwrap());
+                    }
+                }
+            }
+            Some(chunk)
+        });
+
+        std::iter::once(head)
+            .chain(body)
+            .chain(std::iter::once(tail))
+            .map(String::into_bytes)
+    }
+}
+
+/// Events affecting the wallet balances, in the order they were observed
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityLog {
+    entries: Vec<WalletActivity>,
+    /// Outputs already accounted for, so re-synced payments are not counted twice
+    recorded: BTreeSet<OutPoint>,
+}
+
+impl ActivityLog {
+    pub fn entries(&self) -> &[WalletActivity] {
+        &self.entries
+    }
+
+    /// Record payments to this wallet not already accounted for
+    ///
+    /// ### Arguments
+    ///
+    /// * `payments`  - Payments OutPoint, amount, receiver key address and locktime
+    /// * `b_num`     - Current block number
+    /// * `timestamp` - Current unix time in seconds
+    pub fn record_receipts(
+        &mut self,
+        payments: &[(OutPoint, Asset, String, u64)],
+        b_num: u64,
+        timestamp: u64,
+    ) {
+        for (out_p, asset, _, _) in payments {
+            if !self.recorded.insert(out_p.clone()) {
+                continue;
+            }
+            let (asset, amount) = asset_kind_amount(asset);
+            self.entries.push(WalletActivity {
+                b_num,
+                timestamp,
+                kind: ActivityKind::Receive,
+                tx_hash: Some(out_p.t_hash.clone()),
+                counterparty: None,
+                asset,
+                credit: amount,
+                debit: 0,
+                fee: 0,
+            });
+        }
+    }
+
+    /// Record a transaction spending outputs of this wallet, one entry per asset kind
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hash`   - Hash of the spending transaction
+    /// * `spent`     - Assets of the wallet outputs spent
+    /// * `payments`  - Outputs of the transaction with their receiver key address
+    /// * `own`       - Key addresses of this wallet
+    /// * `fees`      - Fees paid by the transaction
+    /// * `b_num`     - Current block number
+    /// * `timestamp` - Current unix time in seconds
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_spend(
+        &mut self,
+        tx_hash: &str,
+        spent: &[Asset],
+        payments: &[(OutPoint, Asset, String, u64)],
+        own: &BTreeSet<String>,
+        fees: &[Asset],
+        b_num: u64,
+        timestamp: u64,
+    ) {
+        let mut net: BTreeMap<String, i64> = BTreeMap::new();
+        for asset in spent {
+            let (asset, amount) = asset_kind_amount(asset);
+            *net.entry(asset).or_default() -= amount as i64;
+        }
+
+        let mut counterparties = BTreeSet::new();
+        for (out_p, asset, address, _) in payments {
+            if own.contains(address) {
+                self.recorded.insert(out_p.clone());
+                let (asset, amount) = asset_kind_amount(asset);
+                *net.entry(asset).or_default() += amount as i64;
+            } else {
+                counterparties.insert(address.as_str());
+            }
+        }
+
+        let fee = fees
+            .iter()
+            .map(asset_kind_amount)
+            .filter(|(asset, _)| asset == TOKEN_ASSET_KIND)
+            .map(|(_, amount)| amount)
+            .sum();
+        let kind = if counterparties.is_empty() {
+            ActivityKind::Aggregation
+        } else {
+            ActivityKind::Send
+        };
+        let counterparty = Some(counterparties.into_iter().collect::<Vec<_>>().join(";"))
+            .filter(|c| !c.is_empty());
+
+        for (asset, change) in net {
+            let fee = if asset == TOKEN_ASSET_KIND { fee } else { 0 };
+            self.entries.push(WalletActivity {
+                b_num,
+                timestamp,
+                kind,
+                tx_hash: Some(tx_hash.to_owned()),
+                counterparty: counterparty.clone(),
+                asset,
+                credit: change.max(0) as u64,
+                debit: (-change).max(0) as u64,
+                fee,
+            });
+        }
+    }
+
+    /// Payments sent and received, oldest first.
+    /// Aggregations and adjustments are not payments and are left out.
+    ///
+    /// ### Arguments
+    ///
+    /// * `limit` - Number of most recent payments kept, all of them if None
+    pub fn transaction_history(&self, limit: Option<usize>) -> Vec<TransactionHistoryEntry> {
+        let history: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|activity| {
+                let (direction, amount) = match activity.kind {
+                    ActivityKind::Receive => (TransactionDirection::Received, activity.credit),
+                    ActivityKind::Send => (TransactionDirection::Sent, activity.debit),
+                    ActivityKind::Aggregation | ActivityKind::Adjustment => return None,
+                };
+                Some(TransactionHistoryEntry {
+                    b_num: activity.b_num,
+                    tx_hash: activity.tx_hash.clone(),
+                    direction,
+                    asset: activity.asset.clone(),
+                    amount,
+                    counterparty: activity.counterparty.clone(),
+                })
+            })
+            .collect();
+
+        let skipped = limit.map_or(0, |limit| history.len().saturating_sub(limit));
+        history.into_iter().skip(skipped).collect()
+    }
+
+    /// Record adjustments so the logged balances match the complete set of wallet outputs
+    ///
+    /// ### Arguments
+    ///
+    /// * `payments`  - All outputs of this wallet
+    /// * `b_num`     - Current block number
+    /// * `timestamp` - Current unix time in seconds
+    pub fn reconcile(
+        &mut self,
+        payments: &[(OutPoint, Asset, String, u64)],
+        b_num: u64,
+        timestamp: u64,
+    ) {
+        let mut held: BTreeMap<String, i64> = BTreeMap::new();
+        for (_, asset, _, _) in payments {
+            let (asset, amount) = asset_kind_amount(asset);
+            *held.entry(asset).or_default() += amount as i64;
+        }
+
+        let mut difference = held;
+        for (asset, balance) in balances(&self.entries) {
+            *difference.entry(asset).or_default() -= balance;
+        }
+
+        for (asset, change) in difference.into_iter().filter(|(_, c)| *c != 0) {
+            self.entries.push(WalletActivity {
+                b_num,
+                timestamp,
+                kind: ActivityKind::Adjustment,
+                tx_hash: None,
+                counterparty: None,
+                asset,
+                credit: change.max(0) as u64,
+                debit: (-change).max(0) as u64,
+                fee: 0,
+            });
+        }
+    }
+
+    /// Build the report of the activity within the given range
+    ///
+    /// ### Arguments
+    ///
+    /// * `range` - Block number or timestamp range of the report
+    pub fn report(&self, range: ActivityRange) -> ActivityReport {
+        let mut balances: BTreeMap<String, i64> = BTreeMap::new();
+        balances.insert(TOKEN_ASSET_KIND.to_owned(), 0);
+
+        let mut entries = self.entries.iter().peekable();
+        while let Some(activity) = entries.next_if(|a| range.compare(a).is_lt()) {
+            *balances.entry(activity.asset.clone()).or_default() += balance_change(activity);
+        }
+
+        let opening_balances = balances.clone();
+        let entries = entries
+            .filter(|a| range.compare(a).is_eq())
+            .map(|activity| {
+                let balance = balances.entry(activity.asset.clone()).or_default();
+                *balance += balance_change(activity);
+                ActivityReportRow {
+                    activity: activity.clone(),
+                    running_balance: *balance,
+                }
+            })
+            .collect();
+
+        ActivityReport {
+            opening_balances,
+            entries,
+            closing_balances: balances,
+        }
+    }
+}
+
+/// Balances per asset kind after applying all the given activity
+fn balances(entries: &[WalletActivity]) -> BTreeMap<String, i64> {
+    let mut balances = BTreeMap::new();
+    for activity in entries {
+        *balances.entry(activity.asset.clone()).or_default() += balance_change(activity);
+    }
+    balances
+}
+
+fn balance_change(activity: &WalletActivity) -> i64 {
+    activity.credit as i64 - activity.debit as i64
+}
+
+/// Asset kind and amount of an asset
+fn asset_kind_amount(asset: &Asset) -> (String, u64) {
+    match asset {
+        Asset::Token(amount) => (TOKEN_ASSET_KIND.to_owned(), amount.0),
+        Asset::Item(item) => (
+            format!("Item:{}", item.genesis_hash.clone().unwrap_or_default()),
+            item.amount,
+        ),
+    }
+}
+
+fn csv_balances(kind: &str, balances: &BTreeMap<String, i64>) -> String {
+    let mut lines = String::new();
+    for (asset, balance) in balances {
+        writeln!(lines, ",,{},,,{},,,,{}", kind, csv_field(asset), balance).unwrap();
+    }
+    lines
+}
+
+fn csv_row(row: &ActivityReportRow) -> String {
+    let a = &row.activity;
+    format!(
+        "{},{},{},{},{},{},{},{},{},{}\n",
+        a.b_num,
+        a.timestamp,
+        a.kind.as_str(),
+        csv_field(a.tx_hash.as_deref().unwrap_or_default()),
+        csv_field(a.counterparty.as_deref().unwrap_or_default()),
+        csv_field(&a.asset),
+        a.credit,
+        a.debit,
+        a.fee,
+        row.running_balance
+    )
+}
+
+/// Quote a CSV field if needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn payment(t_hash: &str, n: i32, amount: u64, address: &str) -> (OutPoint, Asset, String, u64) {
+        (
+            OutPoint::new(t_hash.to_owned(), n),
+            Asset::token_u64(amount),
+            address.to_owned(),
+            0,
+        )
+    }
+
+    /// Receive 100 and 50, send 30 to "bob" with a fee of 2 and change of 18,
+    /// then re-sync to a UTXO set missing 5 tokens.
+    fn scripted_log() -> ActivityLog {
+        let own: BTreeSet<String> = vec!["me".to_owned()].into_iter().collect();
+        let mut log = ActivityLog::default();
+        log.record_receipts(&[payment("r1", 0, 100, "me")], 1, 1_000);
+        log.record_receipts(&[payment("r2", 0, 50, "me")], 2, 2_000);
+
+        let spend_outputs = vec![payment("s1", 0, 30, "bob"), payment("s1", 1, 18, "me")];
+        log.record_spend(
+            "s1",
+            &[Asset::token_u64(50)],
+            &spend_outputs,
+            &own,
+            &[Asset::token_u64(2)],
+            3,
+            3_000,
+        );
+        log.record_receipts(&spend_outputs[1..], 4, 4_000);
+
+        let utxo_set = vec![payment("r1", 0, 95, "me"), payment("s1", 1, 18, "me")];
+        log.reconcile(&utxo_set, 5, 5_000);
+        log
+    }
+
+    #[test]
+    fn activity_report_running_balances() {
+        //
+        // Arrange
+        //
+        let log = scripted_log();
+        let range = ActivityRange::BlockNum { start: 2, end: 5 };
+
+        //
+        // Act
+        //
+        let report = log.report(range);
+        let csv: Vec<u8> = report
+            .clone()
+            .into_chunks(ExportFormat::Csv)
+            .flatten()
+            .collect();
+        let json: Vec<u8> = report
+            .clone()
+            .into_chunks(ExportFormat::Json)
+            .flatten()
+            .collect();
+
+        //
+        // Assert
+        //
+        let balances: Vec<_> = report
+            .entries
+            .iter()
+            .map(|r| (r.activity.kind, r.running_balance))
+            .collect();
+        assert_eq!(
+            balances,
+            vec![
+                (ActivityKind::Receive, 150),
+                (ActivityKind::Send, 118),
+                (ActivityKind::Adjustment, 113),
+            ]
+        );
+        assert_eq!(report.opening_balances[TOKEN_ASSET_KIND], 100);
+        assert_eq!(report.closing_balances[TOKEN_ASSET_KIND], 113);
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "{}{}{}{}{}{}",
+                CSV_HEADER,
+                ",,Opening,,,Token,,,,100\n",
+                "2,2000,Receive,r2,,Token,50,0,0,150\n",
+                "3,3000,Send,s1,bob,Token,0,32,2,118\n",
+                "5,5000,Adjustment,,,Token,0,5,0,113\n",
+                ",,Closing,,,Token,,,,113\n",
+            )
+        );
+        let json: ActivityReport = serde_json::from_slice(&json).unwrap();
+        assert_eq!(json, report);
+    }
+
+    #[test]
+    fn activity_report_empty_range() {
+        //
+        // Arrange
+        //
+        let log = scripted_log();
+        let range = ActivityRange::Timestamp {
+            start: 3_500,
+            end: 4_500,
+        };
+
+        //
+        // Act
+        //
+        let report = log.report(range);
+        let csv: Vec<u8> = report
+            .clone()
+            .into_chunks(ExportFormat::Csv)
+            .flatten()
+            .collect();
+        let json: Vec<u8> = report
+            .clone()
+            .into_chunks(ExportFormat::Json)
+            .flatten()
+            .collect();
+
+        //
+        // Assert
+        //
+        assert!(report.entries.is_empty());
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!(
+                "{}{}{}",
+                CSV_HEADER, ",,Opening,,,Token,,,,118\n", ",,Closing,,,Token,,,,118\n",
+            )
+        );
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"opening_balances\":{\"Token\":118},\"entries\":[],\"closing_balances\":{\"Token\":118}}"
+        );
+    }
+}
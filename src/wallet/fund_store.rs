@@ -143,6 +143,27 @@ impl FundStore {
         }
     }
 
+    pub fn remove_tx(&mut self, out_p: &OutPoint) -> Option<Asset> {
+        let amount = self.transactions.remove(out_p)?;
+        for page in &mut self.transaction_pages {
+            page.remove(out_p);
+        }
+        let mut index = 0;
+        self.transaction_pages.retain(|page| {
+            index += 1;
+            index == 1 || !page.is_empty()
+        });
+
+        self.running_total.update_sub(&amount);
+        Some(amount)
+    }
+
+    pub fn unspend_tx(&mut self, out_p: &OutPoint) {
+        if let Some(amount) = self.spent_transactions.remove(out_p) {
+            self.store_tx(out_p.clone(), amount);
+        }
+    }
+
     pub fn spend_tx(&mut self, out_p: &OutPoint) {
         if let Some((out_p_v, amount)) = self.transactions.remove_entry(out_p) {
             if self
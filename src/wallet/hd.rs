@@ -0,0 +1,261 @@
+//! Hierarchical deterministic derivation of the wallet keys from a single seed.
+//!
+//! Keys are derived following SLIP-0010, the ed25519 variant of BIP32,
+//! which only defines hardened children: address `i` uses the key at `m/i'`.
+//! The seed is backed up as the entropy of a BIP39 mnemonic phrase.
+
+use bip39::Mnemonic;
+use ring::hmac;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+use tw_chain::crypto::sign_ed25519::{PublicKey, SecretKey};
+
+/// Length in bytes of a newly generated seed
+pub const HD_SEED_LEN: usize = 32;
+
+/// Number of addresses derived again when restoring a seed, as the BIP44 address gap limit
+pub const HD_RESTORE_GAP_LIMIT: u32 = 20;
+
+/// HMAC key the master key is derived from the seed with
+const MASTER_HMAC_KEY: &[u8] = b"ed25519 seed";
+
+/// Offset of the hardened child indexes
+const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// PKCS#8 v2 encoding of an ed25519 key, preceding its 32 bytes seed
+const PKCS8_SEED_PREFIX: &[u8] = &[
+    0x30, 0x53, 0x02, 0x01, 0x01, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// PKCS#8 v2 encoding of an ed25519 key, preceding its 32 bytes public key
+const PKCS8_PUBLIC_KEY_PREFIX: &[u8] = &[0xa1, 0x23, 0x03, 0x21, 0x00];
+
+/// Private key with the chain code its children are derived with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedKey {
+    key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Derive the master key of a seed
+    ///
+    /// ### Arguments
+    ///
+    /// * `seed` - Seed all the keys are derived from
+    pub fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(MASTER_HMAC_KEY, &[seed])
+    }
+
+    /// Derive the hardened child at an index below 2^31
+    ///
+    /// ### Arguments
+    ///
+    /// * `index` - Index of the child, without the hardened offset
+    pub fn derive_child(&self, index: u32) -> Self {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        Self::from_hmac(&self.chain_code, &[&[0], &self.key, &index])
+    }
+
+    /// Signing keys using this key as ed25519 seed
+    pub fn keypair(&self) -> (PublicKey, SecretKey) {
+        // Any 32 bytes are a valid ed25519 seed
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(&self.key).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let pkcs8 = [
+            PKCS8_SEED_PREFIX,
+            &self.key[..],
+            PKCS8_PUBLIC_KEY_PREFIX,
+            public_key,
+        ]
+        .concat();
+
+        (
+            PublicKey::from_slice(public_key).unwrap(),
+            SecretKey::from_slice(&pkcs8).unwrap(),
+        )
+    }
+
+    /// Split the HMAC-SHA512 of the data into key and chain code
+    fn from_hmac(hmac_key: &[u8], data: &[&[u8]]) -> Self {
+        let hmac_key = hmac::Key::new(hmac::HMAC_SHA512, hmac_key);
+        let mut context = hmac::Context::with_key(&hmac_key);
+        for data in data {
+            context.update(data);
+        }
+
+        let tag = context.sign();
+        let (key, chain_code) = tag.as_ref().split_at(32);
+        Self {
+            key: key.try_into().unwrap(),
+            chain_code: chain_code.try_into().unwrap(),
+        }
+    }
+}
+
+/// Seed of the wallet with the index of the next address to derive,
+/// and whether the seed is known outside the wallet
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HdWalletStore {
+    pub seed: Vec<u8>,
+    pub next_index: u32,
+    pub backed_up: bool,
+}
+
+impl HdWalletStore {
+    /// Create the store of a seed given to the wallet, deriving from its first address
+    ///
+    /// ### Arguments
+    ///
+    /// * `seed` - Seed all the addresses are derived from
+    pub fn new(seed: &[u8]) -> Self {
+        Self {
+            seed: seed.to_vec(),
+            next_index: 0,
+            backed_up: true,
+        }
+    }
+
+    /// Create the store of a new random seed, not backed up yet
+    pub fn generate() -> Self {
+        Self {
+            backed_up: false,
+            ..Self::new(&rand::random::<[u8; HD_SEED_LEN]>())
+        }
+    }
+
+    /// Derive the keys at an index
+    ///
+    /// ### Arguments
+    ///
+    /// * `index` - Index of the address
+    pub fn derive(&self, index: u32) -> (PublicKey, SecretKey) {
+        ExtendedKey::master(&self.seed)
+            .derive_child(index)
+            .keypair()
+    }
+
+    /// Derive the keys at the next index, advancing it
+    pub fn derive_next(&mut self) -> (PublicKey, SecretKey) {
+        let keypair = self.derive(self.next_index);
+        self.next_index += 1;
+        keypair
+    }
+}
+
+/// Encode a seed as BIP39 mnemonic phrase: 24 words for a 32 bytes seed.
+/// Returns None for seeds of a length BIP39 cannot encode.
+///
+/// ### Arguments
+///
+/// * `seed` - Seed to encode
+pub fn seed_to_mnemonic(seed: &[u8]) -> Option<String> {
+    Mnemonic::from_entropy(seed)
+        .ok()
+        .map(|mnemonic| mnemonic.to_string())
+}
+
+/// Decode the seed of a BIP39 mnemonic phrase, validating its words and checksum
+///
+/// ### Arguments
+///
+/// * `phrase` - Mnemonic phrase, words separated by whitespace
+pub fn mnemonic_to_seed(phrase: &str) -> Option<Vec<u8>> {
+    let phrase = phrase
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ");
+    Mnemonic::parse_normalized(&phrase)
+        .ok()
+        .map(|mnemonic| mnemonic.to_entropy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tw_chain::crypto::sign_ed25519 as sign;
+
+    #[test]
+    /// Derivation matches the first SLIP-0010 ed25519 test vector
+    fn hd_derivation_matches_slip10_vector() {
+        //
+        // Arrange
+        //
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+
+        //
+        // Act
+        //
+        let master = ExtendedKey::master(&seed);
+        let child = master.derive_child(0);
+
+        //
+        // Assert
+        //
+        assert_eq!(
+            hex::encode(master.key),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex::encode(master.chain_code),
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+        );
+        assert_eq!(
+            hex::encode(child.key),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            hex::encode(child.chain_code),
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69"
+        );
+    }
+
+    #[test]
+    /// Derived keys sign messages verified by their public key
+    fn hd_derived_keys_sign() {
+        //
+        // Arrange
+        //
+        let mut store = HdWalletStore::generate();
+
+        //
+        // Act
+        //
+        let (pk_0, sk_0) = store.derive_next();
+        let (pk_1, _) = store.derive_next();
+        let signature = sign::sign_detached(b"message", &sk_0);
+
+        //
+        // Assert
+        //
+        assert_eq!(store.next_index, 2);
+        assert_ne!(pk_0.as_ref(), pk_1.as_ref());
+        assert_eq!(store.derive(0).0.as_ref(), pk_0.as_ref());
+        assert!(sign::verify_detached(&signature, b"message", &pk_0));
+        assert!(!sign::verify_detached(&signature, b"message", &pk_1));
+    }
+
+    #[test]
+    /// Mnemonic encoding matches a BIP39 test vector
+    fn hd_seed_mnemonic_matches_bip39_vector() {
+        //
+        // Arrange
+        //
+        let seed = [0u8; HD_SEED_LEN];
+        let expected = ["abandon"; 23].join(" ") + " art";
+
+        //
+        // Act
+        //
+        let phrase = seed_to_mnemonic(&seed).unwrap();
+        let decoded = mnemonic_to_seed(&format!("  {}\n", phrase.to_uppercase()));
+
+        //
+        // Assert
+        //
+        assert_eq!(phrase, expected);
+        assert_eq!(decoded, Some(seed.to_vec()));
+    }
+}
@@ -1,15 +1,17 @@
 use crate::configurations::{DbMode, WalletTxSpec};
-use crate::constants::{FUND_KEY, KNOWN_ADDRESS_KEY, WALLET_PATH};
+use crate::constants::{FUND_KEY, KNOWN_ADDRESS_KEY, PENDING_PAYMENT_CANCEL_HORIZON, WALLET_PATH};
 use crate::db_utils::{
     self, CustomDbSpec, SimpleDb, SimpleDbError, SimpleDbSpec, SimpleDbWriteBatch, DB_COL_DEFAULT,
 };
-use crate::utils::{get_payments_for_wallet, make_wallet_tx_info};
+use crate::utils::{get_payments_for_wallet, get_timestamp_now, make_wallet_tx_info};
 use crate::Rs2JsMsg;
 use bincode::{deserialize, serialize};
 use hex::FromHexError;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use std::{error, fmt, io};
 use tokio::task;
 use tracing::{debug, warn};
@@ -17,14 +19,20 @@ use tw_chain::crypto::pbkdf2 as pwhash;
 use tw_chain::crypto::secretbox_chacha20_poly1305 as secretbox;
 use tw_chain::crypto::sign_ed25519 as sign;
 use tw_chain::crypto::sign_ed25519::{PublicKey, SecretKey};
-use tw_chain::primitives::asset::{Asset, TokenAmount};
+use tw_chain::primitives::asset::{Asset, AssetValues, TokenAmount};
 use tw_chain::primitives::transaction::{OutPoint, Transaction, TxConstructor, TxIn, TxOut};
 use tw_chain::utils::transaction_utils::{
     construct_address_for, construct_payment_tx_ins, construct_tx_hash,
     construct_tx_in_signable_hash,
 };
+pub mod activity;
 pub mod fund_store;
+pub mod hd;
+pub mod pending;
+pub use activity::ActivityLog;
 pub use fund_store::FundStore;
+pub use hd::{HdWalletStore, HD_RESTORE_GAP_LIMIT};
+pub use pending::{CancelPendingError, PendingPayment, PendingPaymentState, PendingPayments};
 
 /// Key for locked coinbase transactions
 pub const LOCKED_COINBASE_KEY: &str = "LockedCoinbaseKey";
@@ -38,6 +46,24 @@ pub const OUTGOING_TXS_KEY: &str = "OutgoingTxs";
 /// Storage key for all incoming transactions
 pub const INCOMING_TXS_KEY: &str = "IncomingTxs";
 
+/// Storage key for the log of wallet activity
+pub const ACTIVITY_LOG_KEY: &str = "ActivityLog";
+
+/// Storage key for the payments sent to mempool
+pub const PENDING_PAYMENTS_KEY: &str = "PendingPayments";
+
+/// Storage key for the seed the payment addresses are derived from
+pub const HD_WALLET_KEY: &str = "HdWallet";
+
+/// Storage key for the labels given to the wallet addresses
+pub const ADDRESS_LABELS_KEY: &str = "AddressLabels";
+
+/// Leading bytes identifying a portable wallet export file
+pub const WALLET_EXPORT_MAGIC: &[u8] = b"AIBWALLET";
+
+/// Version of the portable wallet export format
+pub const WALLET_EXPORT_VERSION: u32 = 2;
+
 pub const DB_SPEC: SimpleDbSpec = SimpleDbSpec {
     db_path: WALLET_PATH,
     suffix: "",
@@ -67,6 +93,14 @@ pub enum WalletDbError {
     MasterKeyMissingError,
     OutgoingTxMissingError,
     IncomingTxMissingError,
+    ExportFormatError,
+    ImportKeyConflictError(String),
+    HdSeedConflictError,
+    InvalidMnemonic,
+    WalletLockedError,
+    UnlockTimeoutError,
+    UnknownAddressError(String),
+    CancelPendingPayment(CancelPendingError),
 }
 
 impl fmt::Display for WalletDbError {
@@ -83,6 +117,14 @@ impl fmt::Display for WalletDbError {
             Self::MasterKeyMissingError => write!(f, "MasterKeyMissingError"),
             Self::OutgoingTxMissingError => write!(f, "OutgoingTxMissingError"),
             Self::IncomingTxMissingError => write!(f, "IncomingTxMissingError"),
+            Self::ExportFormatError => write!(f, "ExportFormatError"),
+            Self::ImportKeyConflictError(addr) => write!(f, "ImportKeyConflictError: {addr}"),
+            Self::HdSeedConflictError => write!(f, "HdSeedConflictError"),
+            Self::InvalidMnemonic => write!(f, "InvalidMnemonic"),
+            Self::WalletLockedError => write!(f, "WalletLockedError"),
+            Self::UnlockTimeoutError => write!(f, "UnlockTimeoutError"),
+            Self::UnknownAddressError(addr) => write!(f, "UnknownAddressError: {addr}"),
+            Self::CancelPendingPayment(err) => write!(f, "{err}"),
         }
     }
 }
@@ -101,6 +143,14 @@ impl error::Error for WalletDbError {
             Self::MasterKeyMissingError => None,
             Self::OutgoingTxMissingError => None,
             Self::IncomingTxMissingError => None,
+            Self::ExportFormatError => None,
+            Self::ImportKeyConflictError(_) => None,
+            Self::HdSeedConflictError => None,
+            Self::InvalidMnemonic => None,
+            Self::WalletLockedError => None,
+            Self::UnlockTimeoutError => None,
+            Self::UnknownAddressError(_) => None,
+            Self::CancelPendingPayment(_) => None,
         }
     }
 }
@@ -185,6 +235,58 @@ pub struct MasterKeyStore {
     pub enc_master_key: Vec<u8>,
 }
 
+/// Full wallet contents carried in a portable export file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletExport {
+    pub addresses: BTreeMap<String, AddressStore>,
+    pub fund_store: FundStore,
+    pub transaction_stores: BTreeMap<OutPoint, TransactionStore>,
+    pub outgoing_txs: Vec<(String, Transaction)>,
+    pub incoming_txs: BTreeMap<String, (OutPoint, Asset)>,
+    pub hd_wallet: Option<HdWalletStore>,
+}
+
+/// Wallet contents carried in version 1 export files, without the HD wallet seed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WalletExportV1 {
+    addresses: BTreeMap<String, AddressStore>,
+    fund_store: FundStore,
+    transaction_stores: BTreeMap<OutPoint, TransactionStore>,
+    outgoing_txs: Vec<(String, Transaction)>,
+    incoming_txs: BTreeMap<String, (OutPoint, Asset)>,
+}
+
+impl From<WalletExportV1> for WalletExport {
+    fn from(contents: WalletExportV1) -> Self {
+        Self {
+            addresses: contents.addresses,
+            fund_store: contents.fund_store,
+            transaction_stores: contents.transaction_stores,
+            outgoing_txs: contents.outgoing_txs,
+            incoming_txs: contents.incoming_txs,
+            hd_wallet: None,
+        }
+    }
+}
+
+/// Versioned header and encrypted `WalletExport` following the magic bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WalletExportFile {
+    version: u32,
+    salt: pwhash::Salt,
+    nonce: secretbox::Nonce,
+    enc_contents: Vec<u8>,
+}
+
+/// Outcome of importing a wallet export file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletImportSummary {
+    pub keys_added: usize,
+    pub keys_skipped: usize,
+    pub transactions_added: usize,
+    pub transactions_skipped: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct WalletDb {
     db: Arc<Mutex<SimpleDb>>,
@@ -194,6 +296,8 @@ pub struct WalletDb {
     last_generated_address: Option<String>,
     last_locked_coinbase_filter_b_num: Option<u64>,
     last_constructed_tx: Option<Transaction>,
+    /// Time until which the API may reveal keys and make payments, shared by all clones
+    unlocked_until: Arc<Mutex<Option<Instant>>>,
 }
 
 impl WalletDb {
@@ -211,15 +315,59 @@ impl WalletDb {
 
         let batch = batch.done();
         db.write(batch).unwrap();
-        Ok(Self {
+        Ok(Self::from_db_and_key(db, masterkey))
+    }
+
+    /// Open a wallet deriving its payment addresses from the given seed,
+    /// so the addresses of a lost wallet are restored by generating them again.
+    ///
+    /// ### Arguments
+    ///
+    /// * `db_mode`        - Database mode
+    /// * `db`             - Existing wallet database, if any
+    /// * `passphrase`     - Passphrase the master key is encrypted with
+    /// * `custom_db_spec` - Custom database specification, if any
+    /// * `seed`           - Seed the payment addresses are derived from
+    pub fn from_seed(
+        db_mode: DbMode,
+        db: Option<SimpleDb>,
+        passphrase: Option<String>,
+        custom_db_spec: Option<CustomDbSpec>,
+        seed: &[u8],
+    ) -> Result<Self> {
+        let wallet = Self::new(db_mode, db, passphrase, custom_db_spec)?;
+        wallet.set_hd_seed(seed)?;
+        Ok(wallet)
+    }
+
+    /// Open a wallet over an existing database, unlocking its key stores with the passphrase.
+    /// A new master key is created if the database does not have one yet.
+    ///
+    /// ### Arguments
+    ///
+    /// * `db`         - Wallet database
+    /// * `passphrase` - Passphrase the master key is encrypted with
+    pub fn with_passphrase(mut db: SimpleDb, passphrase: String) -> Result<Self> {
+        let mut batch = db.batch_writer();
+        let masterkey = get_or_save_master_key_store(&db, &mut batch, passphrase.as_bytes())?;
+
+        let batch = batch.done();
+        db.write(batch)?;
+        Ok(Self::from_db_and_key(db, masterkey))
+    }
+
+    /// Create the wallet over an opened database and its unlocked master key
+    fn from_db_and_key(db: SimpleDb, encryption_key: secretbox::Key) -> Self {
+        Self {
             db: Arc::new(Mutex::new(db)),
             locked_coinbase: Arc::new(Mutex::new(None)),
-            encryption_key: masterkey,
+            encryption_key,
             ui_feedback_tx: None,
             last_generated_address: None,
             last_locked_coinbase_filter_b_num: None,
             last_constructed_tx: None,
-        })
+            unlocked_until: Arc::new(Mutex::new(None)),
+        }
     }
 
     /// Set the UI feedback channel
@@ -328,6 +476,58 @@ impl WalletDb {
         Ok(())
     }
 
+    /// Unlock the wallet for key-revealing and payment-creating API calls.
+    ///
+    /// A wallet whose master key is not protected by a passphrase yet
+    /// is migrated to the given one on its first unlock.
+    ///
+    /// ### Arguments
+    ///
+    /// * `passphrase` - Current wallet passphrase, or the new one of an unprotected wallet
+    /// * `timeout`    - Time after which the wallet locks again
+    pub async fn unlock(&self, passphrase: String, timeout: Duration) -> Result<()> {
+        let until = Instant::now()
+            .checked_add(timeout)
+            .ok_or(WalletDbError::UnlockTimeoutError)?;
+        if let Err(e) = self.test_passphrase(passphrase.clone()).await {
+            let unprotected = self.test_passphrase(String::new()).await.is_ok();
+            if !matches!(e, WalletDbError::PassphraseError) || !unprotected {
+                return Err(e);
+            }
+            self.re_encrypt_master_key(String::new(), passphrase)
+                .await?;
+        }
+
+        *self.unlocked_until.lock().unwrap() = Some(until);
+        Ok(())
+    }
+
+    /// Lock the wallet until it is unlocked again
+    pub fn lock(&self) {
+        *self.unlocked_until.lock().unwrap() = None;
+    }
+
+    /// Whether the wallet is unlocked, locking it if its timeout elapsed
+    pub fn is_unlocked(&self) -> bool {
+        let mut unlocked_until = self.unlocked_until.lock().unwrap();
+        match *unlocked_until {
+            Some(until) if Instant::now() < until => true,
+            _ => {
+                *unlocked_until = None;
+                false
+            }
+        }
+    }
+
+    /// Fail with `WalletLockedError` unless the wallet is unlocked
+    pub fn check_unlocked(&self) -> Result<()> {
+        if self.is_unlocked() {
+            Ok(())
+        } else {
+            Err(WalletDbError::WalletLockedError)
+        }
+    }
+
     /// Get the last generated address
     pub fn get_last_generated_address(&self) -> Option<String> {
         self.last_generated_address.clone()
@@ -409,19 +609,210 @@ impl WalletDb {
         self.db.lock().unwrap().take()
     }
 
+    /// Flush pending writes of persistent storage
+    pub async fn flush_persistent_store(&self) -> Result<()> {
+        self.db.lock().unwrap().flush()?;
+        Ok(())
+    }
+
     /// Backup persistent storage
     pub async fn backup_persistent_store(&mut self) -> Result<()> {
         self.db.lock().unwrap().file_backup()?;
         Ok(())
     }
 
+    /// Export the full wallet to a portable blob encrypted with a passphrase
+    ///
+    /// ### Arguments
+    ///
+    /// * `passphrase` - Passphrase the export key is derived from
+    pub async fn export_wallet(&self, passphrase: String) -> Result<Vec<u8>> {
+        let db = self.db.clone();
+        let encryption_key = self.encryption_key.clone();
+        task::spawn_blocking(move || {
+            let mut db = db.lock().unwrap();
+            let contents = get_wallet_export(&db, &encryption_key);
+            let export = seal_wallet_export(&contents, passphrase.as_bytes())?;
+
+            // The exported seed is now backed up
+            if let Some(store) = &contents.hd_wallet {
+                let mut batch = db.batch_writer();
+                set_hd_wallet_store(&mut batch, store, &encryption_key);
+                let batch = batch.done();
+                db.write(batch)?;
+            }
+            Ok(export)
+        })
+        .await?
+    }
+
+    /// Import a wallet export blob, merging its contents into this wallet
+    ///
+    /// Existing keys are skipped, and the import fails without writing
+    /// anything if an existing key has different secret material.
+    ///
+    /// ### Arguments
+    ///
+    /// * `export`     - Encrypted export produced by `export_wallet`
+    /// * `passphrase` - Passphrase the export was written with
+    pub async fn import_wallet(
+        &self,
+        export: Vec<u8>,
+        passphrase: String,
+    ) -> Result<WalletImportSummary> {
+        let db = self.db.clone();
+        let encryption_key = self.encryption_key.clone();
+        task::spawn_blocking(move || {
+            let contents = open_wallet_export(&export, passphrase.as_bytes())?;
+            import_wallet_export(&mut db.lock().unwrap(), contents, &encryption_key)
+        })
+        .await?
+    }
+
+    /// Export the full wallet to a portable file encrypted with a passphrase
+    ///
+    /// ### Arguments
+    ///
+    /// * `path`       - File to write the export to
+    /// * `passphrase` - Passphrase the export key is derived from
+    pub async fn export_to_file(&self, path: impl AsRef<Path>, passphrase: String) -> Result<()> {
+        let export = self.export_wallet(passphrase).await?;
+        std::fs::write(path, export)?;
+        Ok(())
+    }
+
+    /// Import a wallet export file, merging its contents into this wallet
+    ///
+    /// ### Arguments
+    ///
+    /// * `path`       - File written by `export_to_file`
+    /// * `passphrase` - Passphrase the export was written with
+    pub async fn import_from_file(
+        &self,
+        path: impl AsRef<Path>,
+        passphrase: String,
+    ) -> Result<WalletImportSummary> {
+        let export = std::fs::read(path)?;
+        self.import_wallet(export, passphrase).await
+    }
+
     /// Generates a new payment address, saving the related keys to the wallet
     /// TODO: Add static address capability for frequent payments
     pub fn generate_payment_address(&mut self) -> (String, AddressStore) {
-        let (public_key, secret_key) = sign::gen_keypair();
+        let (public_key, secret_key) = self.derive_next_keypair();
         self.store_payment_address(public_key, secret_key, None)
     }
 
+    /// Set the seed payment addresses are derived from, starting from its first address.
+    /// A seed generated by the wallet and never backed up is replaced,
+    /// the addresses already derived from it staying in the wallet.
+    /// A wallet deriving from another backed up seed is left unchanged.
+    ///
+    /// ### Arguments
+    ///
+    /// * `seed` - Seed the payment addresses are derived from
+    pub fn set_hd_seed(&self, seed: &[u8]) -> Result<()> {
+        let mut db = self.db.lock().unwrap();
+        let store = match get_hd_wallet_store(&db, &self.encryption_key) {
+            Some(store) if store.seed == seed && store.backed_up => return Ok(()),
+            Some(store) if store.seed == seed => HdWalletStore {
+                backed_up: true,
+                ..store
+            },
+            Some(store) if store.backed_up => return Err(WalletDbError::HdSeedConflictError),
+            _ => HdWalletStore::new(seed),
+        };
+
+        let mut batch = db.batch_writer();
+        set_hd_wallet_store(&mut batch, &store, &self.encryption_key);
+        let batch = batch.done();
+        db.write(batch)?;
+        Ok(())
+    }
+
+    /// Export the seed payment addresses are derived from as a BIP39 mnemonic phrase.
+    /// Wallets without a seed yet get a random one.
+    ///
+    /// ### Arguments
+    ///
+    /// * `passphrase` - Current wallet passphrase
+    pub async fn export_mnemonic(&self, passphrase: String) -> Result<String> {
+        self.test_passphrase(passphrase).await?;
+
+        let mut db = self.db.lock().unwrap();
+        let store = HdWalletStore {
+            backed_up: true,
+            ..get_hd_wallet_store(&db, &self.encryption_key).unwrap_or_else(HdWalletStore::generate)
+        };
+        let mut batch = db.batch_writer();
+        set_hd_wallet_store(&mut batch, &store, &self.encryption_key);
+        let batch = batch.done();
+        db.write(batch)?;
+        hd::seed_to_mnemonic(&store.seed).ok_or(WalletDbError::InvalidMnemonic)
+    }
+
+    /// Restore the seed payment addresses are derived from with a BIP39 mnemonic phrase.
+    /// The addresses of the backed up wallet are then generated again up to the gap limit,
+    /// so their funds are found by the next update of the wallet UTXOs.
+    ///
+    /// ### Arguments
+    ///
+    /// * `phrase`     - Mnemonic phrase exported from the backed up wallet
+    /// * `passphrase` - Current wallet passphrase
+    pub async fn import_mnemonic(&self, phrase: &str, passphrase: String) -> Result<()> {
+        self.test_passphrase(passphrase).await?;
+        let seed = hd::mnemonic_to_seed(phrase).ok_or(WalletDbError::InvalidMnemonic)?;
+        self.set_hd_seed(&seed)?;
+        self.restore_hd_addresses(HD_RESTORE_GAP_LIMIT)
+    }
+
+    /// Save the addresses derived from the seed up to at least `gap_limit` of them,
+    /// advancing the derivation index past them
+    ///
+    /// ### Arguments
+    ///
+    /// * `gap_limit` - Number of addresses to derive from the first one
+    fn restore_hd_addresses(&self, gap_limit: u32) -> Result<()> {
+        let mut store = {
+            let db = self.db.lock().unwrap();
+            get_hd_wallet_store(&db, &self.encryption_key).unwrap_or_else(HdWalletStore::generate)
+        };
+        store.next_index = store.next_index.max(gap_limit);
+
+        for index in 0..store.next_index {
+            let (public_key, secret_key) = store.derive(index);
+            let address = construct_address_for(&public_key, None);
+            let keys = AddressStore {
+                public_key,
+                secret_key,
+                address_version: None,
+            };
+            self.save_address_to_wallet(address, keys)?;
+        }
+
+        let mut db = self.db.lock().unwrap();
+        let mut batch = db.batch_writer();
+        set_hd_wallet_store(&mut batch, &store, &self.encryption_key);
+        let batch = batch.done();
+        db.write(batch)?;
+        Ok(())
+    }
+
+    /// Derive the keys of the next payment address, advancing the derivation index.
+    /// Wallets without a seed yet get a random one.
+    fn derive_next_keypair(&self) -> (PublicKey, SecretKey) {
+        let mut db = self.db.lock().unwrap();
+        let mut store =
+            get_hd_wallet_store(&db, &self.encryption_key).unwrap_or_else(HdWalletStore::generate);
+        let keypair = store.derive_next();
+
+        let mut batch = db.batch_writer();
+        set_hd_wallet_store(&mut batch, &store, &self.encryption_key);
+        let batch = batch.done();
+        db.write(batch).unwrap();
+        keypair
+    }
+
     /// Store a new payment address, saving the related keys to the wallet
     pub fn store_payment_address(
         &mut self,
@@ -546,12 +937,20 @@ impl WalletDb {
             let mut fund_store = get_fund_store(&db);
             let mut locked_coinbase = locked_coinbase.clone();
             let addresses = get_known_key_address(&db);
+            let mut activity_log = get_activity_log(&db);
 
             let usable_payments: Vec<_> = payments
                 .into_iter()
                 .filter(|(_, _, a, _)| addresses.contains(a))
                 .collect();
 
+            let timestamp = get_timestamp_now() as u64;
+            activity_log.record_receipts(&usable_payments, current_b_num, timestamp);
+            if reset_db {
+                activity_log.reconcile(&usable_payments, current_b_num, timestamp);
+            }
+            set_activity_log(&mut batch, &activity_log);
+
             // Reset DB if needed
             if reset_db {
                 debug!("Resetting DB");
@@ -686,15 +1085,20 @@ impl WalletDb {
         Ok((tx_ins, total_amount))
     }
 
-    /// Store payment transaction
+    /// Store payment transaction, returning its outputs paying the wallet
     ///
     /// ### Arguments
     ///
     /// * `transaction` - Transaction to be received and saved to wallet
     /// * `b_num` - Block number
-    pub async fn store_payment_transaction(&mut self, transaction: Transaction, b_num: u64) {
+    pub async fn store_payment_transaction(
+        &mut self,
+        transaction: Transaction,
+        b_num: u64,
+    ) -> Vec<(OutPoint, Asset, String, u64)> {
         let hash = construct_tx_hash(&transaction);
         let payments = get_payments_for_wallet(Some((&hash, &transaction)).into_iter());
+        self.record_spend_activity(&hash, &transaction, &payments, b_num);
         let our_payments = self
             .save_usable_payments_to_wallet(payments, b_num, false)
             .await
@@ -708,6 +1112,47 @@ impl WalletDb {
         db.write(batch).unwrap();
 
         tracing::debug!("store_payment_transactions: {:?}", our_payments);
+        our_payments
+    }
+
+    /// Record a transaction spending outputs of this wallet in the activity log
+    ///
+    /// ### Arguments
+    ///
+    /// * `hash`        - Hash of the transaction
+    /// * `transaction` - Transaction to be saved to wallet
+    /// * `payments`    - Outputs of the transaction
+    /// * `b_num`       - Block number
+    fn record_spend_activity(
+        &self,
+        hash: &str,
+        transaction: &Transaction,
+        payments: &[(OutPoint, Asset, String, u64)],
+        b_num: u64,
+    ) {
+        let mut db = self.db.lock().unwrap();
+        let fund_store = get_fund_store(&db);
+        let spent: Vec<Asset> = transaction
+            .inputs
+            .iter()
+            .filter_map(|tx_in| tx_in.previous_out.as_ref())
+            .filter_map(|out_p| fund_store.spent_transactions().get(out_p))
+            .cloned()
+            .collect();
+        if spent.is_empty() {
+            return;
+        }
+
+        let own = get_known_key_address(&db);
+        let fees: Vec<Asset> = transaction.fees.iter().map(|f| f.value.clone()).collect();
+        let mut activity_log = get_activity_log(&db);
+        let timestamp = get_timestamp_now() as u64;
+        activity_log.record_spend(hash, &spent, payments, &own, &fees, b_num, timestamp);
+
+        let mut batch = db.batch_writer();
+        set_activity_log(&mut batch, &activity_log);
+        let batch = batch.done();
+        db.write(batch).unwrap();
     }
 
     /// Fetches valid TxIns based on the wallet's running total and available unspent
@@ -825,6 +1270,175 @@ impl WalletDb {
         .unwrap()
     }
 
+    /// Delete an address keypair and the unspent transactions it tracks,
+    /// returning the number of entries removed (0 if the address is unknown)
+    ///
+    /// ### Arguments
+    ///
+    /// * `address` - Address of the keypair to delete
+    pub async fn delete_address_from_wallet(&self, address: String) -> Result<usize> {
+        let db = self.db.clone();
+        task::spawn_blocking(move || {
+            let mut db = db.lock().unwrap();
+            delete_address_from_wallet(&mut db, &address)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Track a payment transaction sent to mempool until it is seen in a block
+    ///
+    /// ### Arguments
+    ///
+    /// * `transaction` - Payment transaction sent
+    /// * `b_num`       - Block being mined when the payment was sent
+    pub fn store_pending_payment(&self, transaction: &Transaction, b_num: u64) {
+        let mut db = self.db.lock().unwrap();
+        let own = get_known_key_address(&db);
+        let destination = transaction
+            .outputs
+            .iter()
+            .find(|tx_out| !matches!(&tx_out.script_public_key, Some(a) if own.contains(a)));
+
+        let mut pending_payments = get_pending_payments(&db);
+        pending_payments.insert(PendingPayment {
+            tx_hash: construct_tx_hash(transaction),
+            destination: destination.and_then(|tx_out| tx_out.script_public_key.clone()),
+            amount: destination.map(|tx_out| tx_out.value.clone()),
+            submitted_b_num: b_num,
+            state: PendingPaymentState::Pending,
+            inputs: transaction
+                .inputs
+                .iter()
+                .filter_map(|tx_in| tx_in.previous_out.clone())
+                .collect(),
+        });
+
+        let mut batch = db.batch_writer();
+        set_pending_payments(&mut batch, &pending_payments);
+        let batch = batch.done();
+        db.write(batch).unwrap();
+    }
+
+    /// Get the payments sent to mempool
+    pub fn get_pending_payments(&self) -> Vec<PendingPayment> {
+        let db = self.db.lock().unwrap();
+        get_pending_payments(&db).payments().cloned().collect()
+    }
+
+    /// Confirm the payments included in a block notified to the wallet
+    ///
+    /// A cancelled payment still included wins over the cancel: its inputs are spent again
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hashes` - Hashes of the transactions in the block
+    /// * `b_num`     - Number of the block
+    pub fn confirm_pending_payments(&self, tx_hashes: &[String], b_num: u64) {
+        let mut db = self.db.lock().unwrap();
+        let mut pending_payments = get_pending_payments(&db);
+        let cancelled = pending_payments.confirm(tx_hashes, b_num);
+
+        let mut batch = db.batch_writer();
+        if !cancelled.is_empty() {
+            let mut fund_store = get_fund_store(&db);
+            for out_p in cancelled.iter().flat_map(|p| p.inputs.iter()) {
+                fund_store.spend_tx(out_p);
+            }
+            set_fund_store(&mut batch, fund_store);
+        }
+        set_pending_payments(&mut batch, &pending_payments);
+        let batch = batch.done();
+        db.write(batch).unwrap();
+    }
+
+    /// Cancel a payment not confirmed within the safety horizon:
+    /// its inputs become spendable again and its change outputs are dropped
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hash` - Hash of the payment transaction
+    pub fn cancel_pending_payment(&self, tx_hash: &str) -> Result<PendingPayment> {
+        let mut db = self.db.lock().unwrap();
+        let mut pending_payments = get_pending_payments(&db);
+        let payment = pending_payments
+            .cancel(tx_hash, PENDING_PAYMENT_CANCEL_HORIZON)
+            .map_err(WalletDbError::CancelPendingPayment)?;
+
+        let mut fund_store = get_fund_store(&db);
+        let change: Vec<OutPoint> = fund_store
+            .transactions()
+            .keys()
+            .filter(|out_p| out_p.t_hash == payment.tx_hash)
+            .cloned()
+            .collect();
+        for out_p in &change {
+            fund_store.remove_tx(out_p);
+        }
+        for out_p in &payment.inputs {
+            fund_store.unspend_tx(out_p);
+        }
+
+        let mut batch = db.batch_writer();
+        set_fund_store(&mut batch, fund_store);
+        for out_p in &change {
+            delete_transaction_store(&mut batch, out_p);
+        }
+        set_pending_payments(&mut batch, &pending_payments);
+        let batch = batch.done();
+        db.write(batch)
+            .map_err(|e| WalletDbError::Database(SimpleDbError::from(e)))?;
+
+        Ok(payment)
+    }
+
+    /// Get the log of wallet activity
+    pub fn get_activity_log(&self) -> ActivityLog {
+        let db = self.db.lock().unwrap();
+        get_activity_log(&db)
+    }
+
+    /// Set the label of a wallet address, replacing its previous label.
+    /// An empty label removes it.
+    ///
+    /// ### Arguments
+    ///
+    /// * `address` - Address of the wallet to label
+    /// * `label`   - Label of the address
+    pub fn set_label(&self, address: String, label: String) -> Result<()> {
+        let mut db = self.db.lock().unwrap();
+        if !get_known_key_address(&db).contains(&address) {
+            return Err(WalletDbError::UnknownAddressError(address));
+        }
+
+        let mut labels = get_address_labels(&db);
+        if label.is_empty() {
+            labels.remove(&address);
+        } else {
+            labels.insert(address, label);
+        }
+
+        let mut batch = db.batch_writer();
+        set_address_labels(&mut batch, &labels);
+        let batch = batch.done();
+        db.write(batch)?;
+        Ok(())
+    }
+
+    /// Get the label of a wallet address, if any
+    ///
+    /// ### Arguments
+    ///
+    /// * `address` - Address of the wallet
+    pub fn get_label(&self, address: &str) -> Option<String> {
+        get_address_labels(&self.db.lock().unwrap()).remove(address)
+    }
+
+    /// Get the labels of all the labelled wallet addresses
+    pub fn get_labels(&self) -> BTreeMap<String, String> {
+        get_address_labels(&self.db.lock().unwrap())
+    }
+
     /// Get a the serialized value stored at given key
     pub async fn get_db_value(&self, key: &'static str) -> Option<Vec<u8>> {
         let db = self.db.clone();
@@ -895,11 +1509,53 @@ impl WalletDb {
             .collect()
     }
 
+    /// Get the unspent outputs of the wallet that can be spent now,
+    /// leaving out the coinbase outputs still locked
+    pub fn get_spendable_outpoints(&self) -> Vec<(OutPoint, Asset)> {
+        let mut fund_store = self.get_fund_store();
+        fund_store.filter_locked_coinbase(&self.locked_coinbase.lock().unwrap());
+
+        let spent = fund_store.spent_transactions().clone();
+        fund_store
+            .into_transactions()
+            .into_iter()
+            .filter(|(out_p, _)| !spent.contains_key(out_p))
+            .collect()
+    }
+
     /// Get the wallet transaction address
     pub fn get_transaction_address(&self, out_p: &OutPoint) -> String {
         self.get_transaction_store(out_p).key_address
     }
 
+    /// Get the total balance and the balance held by each of the given addresses
+    ///
+    /// Addresses without any tracked transaction map to a zero balance
+    ///
+    /// ### Arguments
+    ///
+    /// * `addresses` - Addresses to get the balance for
+    pub fn get_balance_for_addresses(
+        &self,
+        addresses: &[String],
+    ) -> (AssetValues, BTreeMap<String, AssetValues>) {
+        let mut total = AssetValues::default();
+        let mut balances: BTreeMap<String, AssetValues> = addresses
+            .iter()
+            .map(|addr| (addr.clone(), AssetValues::default()))
+            .collect();
+
+        let db = self.db.lock().unwrap();
+        for (out_p, asset) in get_fund_store(&db).transactions() {
+            let key_address = get_transaction_store(&db, out_p).key_address;
+            if let Some(balance) = balances.get_mut(&key_address) {
+                balance.update_add(asset);
+                total.update_add(asset);
+            }
+        }
+        (total, balances)
+    }
+
     /// Load locked coinbase from wallet
     pub async fn load_locked_coinbase(&mut self) -> Result<()> {
         let mut cb = self.locked_coinbase.lock().unwrap();
@@ -1137,6 +1793,86 @@ pub fn get_outgoing_txs(db: &SimpleDb) -> Result<Vec<(String, Transaction)>> {
     Ok(outgoing_tx)
 }
 
+/// Gets the payments sent to mempool, empty if none was recorded
+pub fn get_pending_payments(db: &SimpleDb) -> PendingPayments {
+    match db.get_cf(DB_COL_DEFAULT, PENDING_PAYMENTS_KEY) {
+        Ok(Some(store)) => deserialize(&store).unwrap(),
+        Ok(None) => Default::default(),
+        Err(e) => {
+            warn!("Error accessing wallet: {:?}", e);
+            Default::default()
+        }
+    }
+}
+
+/// Sets the payments sent to mempool
+pub fn set_pending_payments(db: &mut SimpleDbWriteBatch, pending_payments: &PendingPayments) {
+    let store = serialize(pending_payments).unwrap();
+    db.put_cf(DB_COL_DEFAULT, PENDING_PAYMENTS_KEY, &store);
+}
+
+/// Gets the log of wallet activity, empty if none was recorded
+pub fn get_activity_log(db: &SimpleDb) -> ActivityLog {
+    match db.get_cf(DB_COL_DEFAULT, ACTIVITY_LOG_KEY) {
+        Ok(Some(store)) => deserialize(&store).unwrap(),
+        Ok(None) => Default::default(),
+        Err(e) => {
+            warn!("Error accessing wallet: {:?}", e);
+            Default::default()
+        }
+    }
+}
+
+/// Saves the log of wallet activity
+pub fn set_activity_log(db: &mut SimpleDbWriteBatch, activity_log: &ActivityLog) {
+    let store = serialize(activity_log).unwrap();
+    db.put_cf(DB_COL_DEFAULT, ACTIVITY_LOG_KEY, &store);
+}
+
+/// Gets the labels of the wallet addresses, empty if none was set
+pub fn get_address_labels(db: &SimpleDb) -> BTreeMap<String, String> {
+    match db.get_cf(DB_COL_DEFAULT, ADDRESS_LABELS_KEY) {
+        Ok(Some(store)) => deserialize(&store).unwrap(),
+        Ok(None) => Default::default(),
+        Err(e) => {
+            warn!("Error accessing wallet: {:?}", e);
+            Default::default()
+        }
+    }
+}
+
+/// Saves the labels of the wallet addresses
+pub fn set_address_labels(db: &mut SimpleDbWriteBatch, labels: &BTreeMap<String, String>) {
+    let store = serialize(labels).unwrap();
+    db.put_cf(DB_COL_DEFAULT, ADDRESS_LABELS_KEY, &store);
+}
+
+/// Get the seed and derivation index of the payment addresses, if set
+pub fn get_hd_wallet_store(
+    db: &SimpleDb,
+    encryption_key: &secretbox::Key,
+) -> Option<HdWalletStore> {
+    match db.get_cf(DB_COL_DEFAULT, HD_WALLET_KEY) {
+        Ok(Some(store)) => {
+            let decrypted = decrypt_store(store, encryption_key);
+            Some(deserialize(&decrypted).unwrap())
+        }
+        Ok(None) => None,
+        Err(e) => panic!("Error accessing wallet: {:?}", e),
+    }
+}
+
+/// Save the seed and derivation index of the payment addresses
+pub fn set_hd_wallet_store(
+    db: &mut SimpleDbWriteBatch,
+    store: &HdWalletStore,
+    encryption_key: &secretbox::Key,
+) {
+    let store = serialize(store).unwrap();
+    let input = encrypt_store(store, encryption_key);
+    db.put_cf(DB_COL_DEFAULT, HD_WALLET_KEY, &input);
+}
+
 // Set a new master key store
 pub fn set_new_master_key_store(
     batch: &mut SimpleDbWriteBatch,
@@ -1219,6 +1955,172 @@ pub fn encrypt_store(store: Vec<u8>, encryption_key: &secretbox::Key) -> Vec<u8>
     input
 }
 
+/// Collect the full wallet contents for export
+pub fn get_wallet_export(db: &SimpleDb, encryption_key: &secretbox::Key) -> WalletExport {
+    let addresses = get_known_key_address(db)
+        .into_iter()
+        .map(|addr| {
+            let store = get_address_store(db, &addr, encryption_key);
+            (addr, store)
+        })
+        .collect();
+    let fund_store = get_fund_store(db);
+    let transaction_stores = fund_store
+        .transactions()
+        .keys()
+        .map(|out_p| (out_p.clone(), get_transaction_store(db, out_p)))
+        .collect();
+
+    WalletExport {
+        addresses,
+        fund_store,
+        transaction_stores,
+        outgoing_txs: get_outgoing_txs(db).unwrap_or_default(),
+        incoming_txs: get_incoming_txs(db).unwrap_or_default(),
+        hd_wallet: get_hd_wallet_store(db, encryption_key).map(|store| HdWalletStore {
+            backed_up: true,
+            ..store
+        }),
+    }
+}
+
+/// Encrypt wallet contents into the versioned export file format
+pub fn seal_wallet_export(contents: &WalletExport, passphrase: &[u8]) -> Result<Vec<u8>> {
+    seal_export_file(WALLET_EXPORT_VERSION, serialize(contents)?, passphrase)
+}
+
+/// Encrypt serialized wallet contents of the given export format version
+fn seal_export_file(version: u32, contents: Vec<u8>, passphrase: &[u8]) -> Result<Vec<u8>> {
+    let salt = pwhash::gen_salt();
+    let nonce = secretbox::gen_nonce();
+    let pass_key = make_key(passphrase, salt);
+    let enc_contents = secretbox::seal(contents, &nonce, &pass_key).unwrap();
+
+    let mut file = WALLET_EXPORT_MAGIC.to_vec();
+    file.extend(serialize(&WalletExportFile {
+        version,
+        salt,
+        nonce,
+        enc_contents,
+    })?);
+    Ok(file)
+}
+
+/// Decrypt wallet contents from the versioned export file format,
+/// including version 1 files without the HD wallet seed
+pub fn open_wallet_export(file: &[u8], passphrase: &[u8]) -> Result<WalletExport> {
+    let file = file
+        .strip_prefix(WALLET_EXPORT_MAGIC)
+        .ok_or(WalletDbError::ExportFormatError)?;
+    let version = deserialize::<u32>(file)?;
+    if !(1..=WALLET_EXPORT_VERSION).contains(&version) {
+        return Err(WalletDbError::ExportFormatError);
+    }
+
+    let file: WalletExportFile = deserialize(file)?;
+    let pass_key = make_key(passphrase, file.salt);
+    let contents = secretbox::open(file.enc_contents, &file.nonce, &pass_key)
+        .ok_or(WalletDbError::PassphraseError)?;
+    match version {
+        1 => Ok(deserialize::<WalletExportV1>(&contents)?.into()),
+        _ => Ok(deserialize(&contents)?),
+    }
+}
+
+/// Merge exported wallet contents into the wallet, deriving the next addresses
+/// from the exported seed unless the wallet derives from another backed up seed
+///
+/// Fails before writing if an existing key has different secret material
+pub fn import_wallet_export(
+    db: &mut SimpleDb,
+    contents: WalletExport,
+    encryption_key: &secretbox::Key,
+) -> Result<WalletImportSummary> {
+    let mut summary = WalletImportSummary::default();
+    let mut address_list = get_known_key_address(db);
+    let mut new_addresses = Vec::new();
+    for (addr, store) in contents.addresses {
+        if !address_list.contains(&addr) {
+            new_addresses.push((addr, store));
+        } else if get_address_store(db, &addr, encryption_key)
+            .secret_key
+            .as_ref()
+            == store.secret_key.as_ref()
+        {
+            summary.keys_skipped += 1;
+        } else {
+            return Err(WalletDbError::ImportKeyConflictError(addr));
+        }
+    }
+
+    let hd_wallet = match (get_hd_wallet_store(db, encryption_key), contents.hd_wallet) {
+        (_, None) => None,
+        (Some(local), Some(export)) if local.seed == export.seed => Some(HdWalletStore {
+            next_index: local.next_index.max(export.next_index),
+            backed_up: true,
+            ..local
+        }),
+        (Some(local), Some(_)) if local.backed_up => {
+            return Err(WalletDbError::HdSeedConflictError)
+        }
+        (_, Some(export)) => Some(HdWalletStore {
+            backed_up: true,
+            ..export
+        }),
+    };
+
+    let mut batch = db.batch_writer();
+    if let Some(store) = &hd_wallet {
+        set_hd_wallet_store(&mut batch, store, encryption_key);
+    }
+    summary.keys_added = new_addresses.len();
+    for (addr, store) in new_addresses {
+        save_address_store_to_wallet(&mut batch, &addr, store, encryption_key);
+        address_list.insert(addr);
+    }
+    set_known_key_address(&mut batch, address_list);
+
+    let mut fund_store = get_fund_store_err(db)?;
+    for (out_p, amount) in contents.fund_store.transactions() {
+        if fund_store.transactions().contains_key(out_p)
+            || fund_store.spent_transactions().contains_key(out_p)
+        {
+            summary.transactions_skipped += 1;
+            continue;
+        }
+        if let Some(store) = contents.transaction_stores.get(out_p) {
+            save_transaction_to_wallet(&mut batch, out_p, store);
+        }
+        fund_store.store_tx(out_p.clone(), amount.clone());
+        summary.transactions_added += 1;
+    }
+    set_fund_store(&mut batch, fund_store);
+
+    if !contents.outgoing_txs.is_empty() {
+        let mut outgoing_txs = get_outgoing_txs(db).unwrap_or_default();
+        let known: BTreeSet<String> = outgoing_txs.iter().map(|(h, _)| h.clone()).collect();
+        outgoing_txs.extend(
+            contents
+                .outgoing_txs
+                .into_iter()
+                .filter(|(h, _)| !known.contains(h)),
+        );
+        batch.put_cf(DB_COL_DEFAULT, OUTGOING_TXS_KEY, &serialize(&outgoing_txs)?);
+    }
+
+    if !contents.incoming_txs.is_empty() {
+        let mut incoming_txs = get_incoming_txs(db).unwrap_or_default();
+        for (k, v) in contents.incoming_txs {
+            incoming_txs.entry(k).or_insert(v);
+        }
+        batch.put_cf(DB_COL_DEFAULT, INCOMING_TXS_KEY, &serialize(&incoming_txs)?);
+    }
+
+    let batch = batch.done();
+    db.write(batch)?;
+    Ok(summary)
+}
+
 /// Make TxConstructors from stored TxOut
 /// Also return the used info for db cleanup
 #[allow(clippy::type_complexity)]
@@ -1353,8 +2255,10 @@ pub fn destroy_spent_transactions_and_keys(
         spent_addrs
     };
 
+    let mut labels = get_address_labels(db);
     for keys_address in &remove_key_addresses {
         address_store.remove(keys_address);
+        labels.remove(keys_address);
     }
 
     //
@@ -1362,6 +2266,7 @@ pub fn destroy_spent_transactions_and_keys(
     //
     set_fund_store(&mut batch, fund_store);
     set_known_key_address(&mut batch, address_store);
+    set_address_labels(&mut batch, &labels);
     for keys_address in &remove_key_addresses {
         delete_address_store(&mut batch, keys_address);
     }
@@ -1375,6 +2280,48 @@ pub fn destroy_spent_transactions_and_keys(
     (remove_key_addresses, spent_txs)
 }
 
+/// Delete an address keypair and the unspent transactions it tracks
+///
+/// ### Arguments
+///
+/// * `db`      - Database to delete from
+/// * `address` - Address of the keypair to delete
+pub fn delete_address_from_wallet(db: &mut SimpleDb, address: &str) -> Result<usize> {
+    let mut address_list = get_known_key_address(db);
+    if !address_list.remove(address) {
+        return Ok(0);
+    }
+
+    let mut fund_store = get_fund_store(db);
+    let address_txs: Vec<OutPoint> = fund_store
+        .transactions()
+        .keys()
+        .filter(|out_p| get_transaction_store(db, out_p).key_address == address)
+        .cloned()
+        .collect();
+    for out_p in &address_txs {
+        fund_store.remove_tx(out_p);
+    }
+
+    let mut labels = get_address_labels(db);
+    labels.remove(address);
+
+    let mut batch = db.batch_writer();
+    set_fund_store(&mut batch, fund_store);
+    set_known_key_address(&mut batch, address_list);
+    set_address_labels(&mut batch, &labels);
+    delete_address_store(&mut batch, address);
+    for out_p in &address_txs {
+        delete_transaction_store(&mut batch, out_p);
+    }
+
+    let batch = batch.done();
+    db.write(batch)
+        .map_err(|e| WalletDbError::Database(SimpleDbError::from(e)))?;
+
+    Ok(1 + address_txs.len())
+}
+
 /// Retrieve addresses from a subset that may or may not contain assets
 ///
 /// ## Arguments
@@ -1441,7 +2388,7 @@ pub fn tx_constructor_from_prev_out(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tw_chain::utils::transaction_utils::construct_address;
+    use tw_chain::utils::transaction_utils::{construct_address, construct_tx_core};
 
     #[test]
     /// Creating a valid payment address
@@ -1496,6 +2443,57 @@ mod tests {
         .unwrap();
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn with_passphrase_round_trip() {
+        //
+        // Arrange
+        //
+        let db = db_utils::new_db(DbMode::InMemory, &DB_SPEC, None, None);
+        let mut wallet = WalletDb::with_passphrase(db, "Test Passphrase".to_owned()).unwrap();
+        let (key_addr, store) = wallet.generate_payment_address();
+
+        //
+        // Act
+        //
+        let db = wallet.take_closed_persistent_store().await;
+        let raw_store = db.get_cf(DB_COL_DEFAULT, &key_addr).unwrap().unwrap();
+        let wallet = WalletDb::with_passphrase(db, "Test Passphrase".to_owned()).unwrap();
+
+        //
+        // Assert
+        //
+        let secret_key = store.secret_key.as_ref();
+        assert!(!raw_store
+            .windows(secret_key.len())
+            .any(|window| window == secret_key));
+        assert_eq!(wallet.get_known_addresses(), vec![key_addr.clone()]);
+        assert_eq!(
+            wallet.get_address_store(&key_addr).secret_key.as_ref(),
+            secret_key
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn with_passphrase_wrong_passphrase() {
+        //
+        // Arrange
+        //
+        let db = db_utils::new_db(DbMode::InMemory, &DB_SPEC, None, None);
+        let mut wallet = WalletDb::with_passphrase(db, "Test Passphrase".to_owned()).unwrap();
+        wallet.generate_payment_address();
+        let db = wallet.take_closed_persistent_store().await;
+
+        //
+        // Act
+        //
+        let wallet = WalletDb::with_passphrase(db, "Wrong Passphrase".to_owned());
+
+        //
+        // Assert
+        //
+        assert!(matches!(wallet, Err(WalletDbError::PassphraseError)));
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn wallet_life_cycle() {
         //
@@ -1580,4 +2578,663 @@ mod tests {
                 .collect();
         assert_eq!(destroyed_txs, expected_destroyedkeys);
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pending_payment_confirm_then_cancel() {
+        //
+        // Arrange
+        //
+        let (wallet, _, tx) = wallet_with_pending_payment().await;
+        let tx_hash = construct_tx_hash(&tx);
+
+        //
+        // Act
+        //
+        wallet.confirm_pending_payments(&[tx_hash.clone()], 1);
+        let cancelled = wallet.cancel_pending_payment(&tx_hash);
+
+        //
+        // Assert
+        //
+        let states: Vec<_> = wallet
+            .get_pending_payments()
+            .into_iter()
+            .map(|p| (p.tx_hash, p.state))
+            .collect();
+        let unspent: Vec<_> = wallet
+            .get_fund_store()
+            .transactions()
+            .keys()
+            .cloned()
+            .collect();
+
+        assert!(matches!(
+            cancelled,
+            Err(WalletDbError::CancelPendingPayment(
+                CancelPendingError::Confirmed
+            ))
+        ));
+        assert_eq!(
+            states,
+            vec![(tx_hash.clone(), PendingPaymentState::Confirmed { b_num: 1 })]
+        );
+        assert_eq!(unspent, vec![OutPoint::new(tx_hash, 1)]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pending_payment_cancel_then_resubmit() {
+        //
+        // Arrange
+        //
+        let (mut wallet, out_p, tx) = wallet_with_pending_payment().await;
+        let tx_hash = construct_tx_hash(&tx);
+        let horizon = PENDING_PAYMENT_CANCEL_HORIZON;
+
+        //
+        // Act
+        //
+        let too_recent = wallet.cancel_pending_payment(&tx_hash);
+        wallet.confirm_pending_payments(&[], horizon);
+        let cancelled = wallet.cancel_pending_payment(&tx_hash).unwrap();
+        let unspent_after_cancel: Vec<_> = wallet
+            .get_fund_store()
+            .transactions()
+            .keys()
+            .cloned()
+            .collect();
+
+        let resubmitted = pay_from_wallet(&mut wallet, horizon).await;
+        let resubmitted_hash = construct_tx_hash(&resubmitted);
+        wallet.confirm_pending_payments(&[resubmitted_hash.clone()], horizon + 1);
+
+        //
+        // Assert
+        //
+        let states: BTreeMap<_, _> = wallet
+            .get_pending_payments()
+            .into_iter()
+            .map(|p| (p.tx_hash, p.state))
+            .collect();
+        let expected_states: BTreeMap<_, _> = vec![
+            (tx_hash, PendingPaymentState::Cancelled { b_num: horizon }),
+            (
+                resubmitted_hash,
+                PendingPaymentState::Confirmed { b_num: horizon + 1 },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let resubmitted_inputs: Vec<_> = resubmitted
+            .inputs
+            .iter()
+            .filter_map(|tx_in| tx_in.previous_out.clone())
+            .collect();
+
+        assert!(matches!(
+            too_recent,
+            Err(WalletDbError::CancelPendingPayment(
+                CancelPendingError::TooRecent
+            ))
+        ));
+        assert_eq!(cancelled.inputs, vec![out_p.clone()]);
+        assert_eq!(unspent_after_cancel, vec![out_p.clone()]);
+        assert_eq!(resubmitted_inputs, vec![out_p]);
+        assert_eq!(states, expected_states);
+    }
+
+    /// Wallet holding a single 5 tokens output, spent by a 3 tokens payment pending in mempool
+    async fn wallet_with_pending_payment() -> (WalletDb, OutPoint, Transaction) {
+        let mut wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (key_addr, _) = wallet.generate_payment_address();
+        let out_p = OutPoint::new("tx_hash".to_owned(), 0);
+        wallet
+            .save_usable_payments_to_wallet(
+                vec![(out_p.clone(), Asset::token_u64(5), key_addr, 0)],
+                0,
+                false,
+            )
+            .await
+            .unwrap();
+
+        let tx = pay_from_wallet(&mut wallet, 0).await;
+        (wallet, out_p, tx)
+    }
+
+    /// Send a 3 tokens payment from the wallet, tracking it as pending
+    async fn pay_from_wallet(wallet: &mut WalletDb, b_num: u64) -> Transaction {
+        let tx_outs = vec![TxOut::new_token_amount(
+            "destination".to_owned(),
+            TokenAmount(3),
+            None,
+        )];
+        let (tx_ins, tx_outs) = wallet
+            .fetch_tx_ins_and_tx_outs(Asset::token_u64(3), tx_outs)
+            .unwrap();
+        let tx = construct_tx_core(tx_ins, tx_outs, None);
+        wallet.store_payment_transaction(tx.clone(), b_num).await;
+        wallet.store_pending_payment(&tx, b_num);
+        tx
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_export_v1_import() {
+        //
+        // Arrange
+        //
+        let out_p = OutPoint::new("tx_hash".to_owned(), 0);
+        let passphrase = "Export Passphrase";
+
+        let mut wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (key_addr, _) = wallet.generate_payment_address();
+        wallet
+            .save_usable_payments_to_wallet(
+                vec![(out_p.clone(), Asset::token_u64(7), key_addr.clone(), 0)],
+                Default::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        let mut imported = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Other Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+
+        let export = wallet.export_wallet(passphrase.to_owned()).await.unwrap();
+        let contents = open_wallet_export(&export, passphrase.as_bytes()).unwrap();
+        let v1_contents = WalletExportV1 {
+            addresses: contents.addresses,
+            fund_store: contents.fund_store,
+            transaction_stores: contents.transaction_stores,
+            outgoing_txs: contents.outgoing_txs,
+            incoming_txs: contents.incoming_txs,
+        };
+        let v1_export =
+            seal_export_file(1, serialize(&v1_contents).unwrap(), passphrase.as_bytes()).unwrap();
+        let unknown_export = seal_export_file(
+            WALLET_EXPORT_VERSION + 1,
+            serialize(&v1_contents).unwrap(),
+            passphrase.as_bytes(),
+        )
+        .unwrap();
+
+        //
+        // Act
+        //
+        let v1_opened = open_wallet_export(&v1_export, passphrase.as_bytes()).unwrap();
+        let summary = imported
+            .import_wallet(v1_export, passphrase.to_owned())
+            .await
+            .unwrap();
+        let unknown = imported
+            .import_wallet(unknown_export, passphrase.to_owned())
+            .await;
+
+        //
+        // Assert
+        //
+        let expected_summary = WalletImportSummary {
+            keys_added: 1,
+            keys_skipped: 0,
+            transactions_added: 1,
+            transactions_skipped: 0,
+        };
+        assert!(contents.hd_wallet.is_some());
+        assert!(v1_opened.hd_wallet.is_none());
+        assert_eq!(summary, expected_summary);
+        assert_eq!(imported.get_transaction_address(&out_p), key_addr);
+        assert!(matches!(unknown, Err(WalletDbError::ExportFormatError)));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_export_import_round_trip() {
+        //
+        // Arrange
+        //
+        let out_p = OutPoint::new("tx_hash".to_owned(), 0);
+        let amount = Asset::token_u64(7);
+
+        let mut wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (key_addr, _) = wallet.generate_payment_address();
+        let (_key_addr_unused, _) = wallet.generate_payment_address();
+        wallet
+            .save_usable_payments_to_wallet(
+                vec![(out_p.clone(), amount.clone(), key_addr.clone(), 0)],
+                Default::default(),
+                false,
+            )
+            .await
+            .unwrap();
+
+        let mut imported = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Other Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        // Address from a generated seed, replaced by the exported one
+        let (generated_addr, _) = imported.generate_payment_address();
+
+        //
+        // Act
+        //
+        let export = wallet
+            .export_wallet("Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        let first = imported
+            .import_wallet(export.clone(), "Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        let second = imported
+            .import_wallet(export.clone(), "Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        let wrong_passphrase = imported
+            .import_wallet(export, "Wrong Passphrase".to_owned())
+            .await;
+        let next_addr = wallet.generate_payment_address().0;
+        let imported_next_addr = imported.generate_payment_address().0;
+
+        //
+        // Assert
+        //
+        let expected_first = WalletImportSummary {
+            keys_added: 2,
+            keys_skipped: 0,
+            transactions_added: 1,
+            transactions_skipped: 0,
+        };
+        let expected_second = WalletImportSummary {
+            keys_added: 0,
+            keys_skipped: 2,
+            transactions_added: 0,
+            transactions_skipped: 1,
+        };
+        assert_eq!((first, second), (expected_first, expected_second));
+        assert!(matches!(
+            wrong_passphrase,
+            Err(WalletDbError::PassphraseError)
+        ));
+
+        let mut expected_addresses = wallet.get_known_addresses();
+        expected_addresses.push(generated_addr);
+        let mut actual_addresses = imported.get_known_addresses();
+        expected_addresses.sort();
+        actual_addresses.sort();
+        assert_eq!(actual_addresses, expected_addresses);
+        assert_eq!(imported_next_addr, next_addr);
+        assert_eq!(
+            imported.get_address_store(&key_addr).secret_key.as_ref(),
+            wallet.get_address_store(&key_addr).secret_key.as_ref()
+        );
+        assert_eq!(imported.get_transaction_address(&out_p), key_addr);
+        assert_eq!(
+            imported.get_fund_store().running_total(),
+            wallet.get_fund_store().running_total()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_export_import_file_round_trip() {
+        //
+        // Arrange
+        //
+        let path = std::env::temp_dir().join("wallet_export_import_file_round_trip.aibw");
+        let mut wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (key_addr, _) = wallet.generate_payment_address();
+
+        let imported = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Other Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+
+        //
+        // Act
+        //
+        wallet
+            .export_to_file(&path, "Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        let wrong_passphrase = imported
+            .import_from_file(&path, "Wrong Passphrase".to_owned())
+            .await;
+        let summary = imported
+            .import_from_file(&path, "Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let missing_file = imported
+            .import_from_file(&path, "Export Passphrase".to_owned())
+            .await;
+
+        //
+        // Assert
+        //
+        assert!(matches!(
+            wrong_passphrase,
+            Err(WalletDbError::PassphraseError)
+        ));
+        assert_eq!(summary.keys_added, 1);
+        assert!(matches!(missing_file, Err(WalletDbError::IO(_))));
+        assert_eq!(
+            imported.get_address_store(&key_addr).secret_key.as_ref(),
+            wallet.get_address_store(&key_addr).secret_key.as_ref()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_import_refuses_conflicting_key() {
+        //
+        // Arrange
+        //
+        let mut wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (key_addr, _) = wallet.generate_payment_address();
+
+        let imported = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let (public_key, secret_key) = sign::gen_keypair();
+        let conflicting = AddressStore {
+            public_key,
+            secret_key,
+            address_version: None,
+        };
+        imported
+            .save_address_to_wallet(key_addr.clone(), conflicting.clone())
+            .unwrap();
+
+        //
+        // Act
+        //
+        let export = wallet
+            .export_wallet("Export Passphrase".to_owned())
+            .await
+            .unwrap();
+        let result = imported
+            .import_wallet(export, "Export Passphrase".to_owned())
+            .await;
+
+        //
+        // Assert
+        //
+        assert!(matches!(
+            result,
+            Err(WalletDbError::ImportKeyConflictError(addr)) if addr == key_addr
+        ));
+        assert_eq!(imported.get_known_addresses(), vec![key_addr.clone()]);
+        assert_eq!(
+            imported.get_address_store(&key_addr).secret_key.as_ref(),
+            conflicting.secret_key.as_ref()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn hd_wallet_restored_from_seed() {
+        //
+        // Arrange
+        //
+        let seed = [7u8; 32];
+        let mut wallet = WalletDb::from_seed(DbMode::InMemory, None, None, None, &seed).unwrap();
+        let (public_key, secret_key) = sign::gen_keypair();
+        let (imported_addr, _) = wallet.store_payment_address(public_key, secret_key, None);
+        let derived: Vec<String> = (0..5)
+            .map(|_| wallet.generate_payment_address().0)
+            .collect();
+
+        //
+        // Act
+        //
+        let mut restored = WalletDb::from_seed(DbMode::InMemory, None, None, None, &seed).unwrap();
+        let restored_derived: Vec<String> = (0..5)
+            .map(|_| restored.generate_payment_address().0)
+            .collect();
+        let other_seed = wallet.set_hd_seed(&[8u8; 32]);
+
+        //
+        // Assert
+        //
+        let mut expected_known = derived.clone();
+        expected_known.push(imported_addr.clone());
+        expected_known.sort();
+        assert_eq!(restored_derived, derived);
+        assert_eq!(derived.iter().collect::<BTreeSet<_>>().len(), 5);
+        assert_eq!(wallet.get_known_addresses(), expected_known);
+        assert!(matches!(
+            other_seed,
+            Err(WalletDbError::HdSeedConflictError)
+        ));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_unlock_and_relock() {
+        //
+        // Arrange
+        //
+        let wallet = WalletDb::new(
+            DbMode::InMemory,
+            None,
+            Some("Test Passphrase".to_owned()),
+            None,
+        )
+        .unwrap();
+        let initially_unlocked = wallet.is_unlocked();
+
+        //
+        // Act
+        //
+        let wrong = wallet
+            .unlock("Wrong Passphrase".to_owned(), Duration::from_secs(60))
+            .await;
+        let unlocked_after_wrong = wallet.is_unlocked();
+
+        wallet
+            .unlock("Test Passphrase".to_owned(), Duration::from_secs(60))
+            .await
+            .unwrap();
+        let unlocked = wallet.clone().is_unlocked();
+        wallet.lock();
+        let unlocked_after_lock = wallet.is_unlocked();
+
+        wallet
+            .unlock("Test Passphrase".to_owned(), Duration::from_millis(20))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        let timed_out = wallet.check_unlocked();
+
+        let overflowing = wallet
+            .unlock("Test Passphrase".to_owned(), Duration::from_secs(u64::MAX))
+            .await;
+        let unlocked_after_overflowing = wallet.is_unlocked();
+
+        //
+        // Assert
+        //
+        assert!(!initially_unlocked);
+        assert!(matches!(wrong, Err(WalletDbError::PassphraseError)));
+        assert!(!unlocked_after_wrong);
+        assert!(unlocked);
+        assert!(!unlocked_after_lock);
+        assert!(matches!(timed_out, Err(WalletDbError::WalletLockedError)));
+        assert!(matches!(
+            overflowing,
+            Err(WalletDbError::UnlockTimeoutError)
+        ));
+        assert!(!unlocked_after_overflowing);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn wallet_unlock_migrates_unprotected_wallet() {
+        //
+        // Arrange
+        //
+        let mut wallet = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        let (key_addr, store) = wallet.generate_payment_address();
+
+        //
+        // Act
+        //
+        wallet
+            .unlock("New Passphrase".to_owned(), Duration::from_secs(60))
+            .await
+            .unwrap();
+        let blank_after_migration = wallet.test_passphrase(String::new()).await;
+        let db = wallet.take_closed_persistent_store().await;
+        let reopened = WalletDb::with_passphrase(db, "New Passphrase".to_owned()).unwrap();
+
+        //
+        // Assert
+        //
+        assert!(matches!(
+            blank_after_migration,
+            Err(WalletDbError::PassphraseError)
+        ));
+        assert_eq!(
+            reopened.get_address_store(&key_addr).secret_key.as_ref(),
+            store.secret_key.as_ref()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn mnemonic_export_import_round_trip() {
+        //
+        // Arrange
+        //
+        let passphrase = "Test Passphrase".to_owned();
+        let mut wallet =
+            WalletDb::new(DbMode::InMemory, None, Some(passphrase.clone()), None).unwrap();
+        let derived: Vec<String> = (0..3)
+            .map(|_| wallet.generate_payment_address().0)
+            .collect();
+
+        //
+        // Act
+        //
+        let phrase = wallet.export_mnemonic(passphrase.clone()).await.unwrap();
+        let wrong_passphrase = wallet.export_mnemonic("Wrong Passphrase".to_owned()).await;
+
+        let mut restored = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        restored
+            .import_mnemonic(&phrase, String::new())
+            .await
+            .unwrap();
+        let restored_known = restored.get_known_addresses();
+        let next_restored = restored.generate_payment_address().0;
+
+        //
+        // Assert
+        //
+        assert_eq!(phrase.split_whitespace().count(), 24);
+        assert!(matches!(
+            wrong_passphrase,
+            Err(WalletDbError::PassphraseError)
+        ));
+        assert_eq!(restored_known.len(), HD_RESTORE_GAP_LIMIT as usize);
+        assert!(derived.iter().all(|addr| restored_known.contains(addr)));
+        assert!(!restored_known.contains(&next_restored));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn mnemonic_import_replaces_generated_seed() {
+        //
+        // Arrange
+        //
+        let mut wallet = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        let derived: Vec<String> = (0..2)
+            .map(|_| wallet.generate_payment_address().0)
+            .collect();
+        let phrase = wallet.export_mnemonic(String::new()).await.unwrap();
+
+        let mut generated = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        let (generated_addr, _) = generated.generate_payment_address();
+
+        let backed_up = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        let backed_up_phrase = backed_up.export_mnemonic(String::new()).await.unwrap();
+
+        //
+        // Act
+        //
+        let replaced = generated.import_mnemonic(&phrase, String::new()).await;
+        let replaced_phrase = generated.export_mnemonic(String::new()).await.unwrap();
+        let conflict = backed_up.import_mnemonic(&phrase, String::new()).await;
+        let kept_phrase = backed_up.export_mnemonic(String::new()).await.unwrap();
+
+        //
+        // Assert
+        //
+        let known = generated.get_known_addresses();
+        assert!(replaced.is_ok());
+        assert_eq!(replaced_phrase, phrase);
+        assert!(known.contains(&generated_addr));
+        assert!(derived.iter().all(|addr| known.contains(addr)));
+        assert!(matches!(conflict, Err(WalletDbError::HdSeedConflictError)));
+        assert_eq!(kept_phrase, backed_up_phrase);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn mnemonic_import_invalid_checksum() {
+        //
+        // Arrange
+        //
+        let wallet = WalletDb::new(DbMode::InMemory, None, None, None).unwrap();
+        // Valid words whose last word does not match the checksum of the others
+        let bad_checksum = ["abandon"; 24].join(" ");
+        let unknown_word = ["abandon"; 23].join(" ") + " notaword";
+        let valid = ["abandon"; 23].join(" ") + " art";
+
+        //
+        // Act
+        //
+        let bad_checksum = wallet.import_mnemonic(&bad_checksum, String::new()).await;
+        let unknown_word = wallet.import_mnemonic(&unknown_word, String::new()).await;
+        let valid = wallet.import_mnemonic(&valid, String::new()).await;
+
+        //
+        // Assert
+        //
+        assert!(matches!(bad_checksum, Err(WalletDbError::InvalidMnemonic)));
+        assert!(matches!(unknown_word, Err(WalletDbError::InvalidMnemonic)));
+        assert!(valid.is_ok());
+    }
 }
@@ -0,0 +1,132 @@
+//! Payments sent to mempool that have not been seen in a block yet.
+//!
+//! Their inputs are spent locally as soon as they are sent: tracking them lets
+//! the user give up on a payment that never made it into a block.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use tw_chain::primitives::asset::Asset;
+use tw_chain::primitives::transaction::OutPoint;
+
+/// State of a payment sent to mempool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PendingPaymentState {
+    Pending,
+    Confirmed { b_num: u64 },
+    Cancelled { b_num: u64 },
+}
+
+/// Payment sent to mempool
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingPayment {
+    pub tx_hash: String,
+    /// First output not paying to this wallet, if any
+    pub destination: Option<String>,
+    pub amount: Option<Asset>,
+    /// Block being mined when the payment was sent
+    pub submitted_b_num: u64,
+    pub state: PendingPaymentState,
+    /// Outputs of this wallet spent by the payment
+    pub inputs: Vec<OutPoint>,
+}
+
+/// Why a pending payment cannot be cancelled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancelPendingError {
+    NotFound,
+    Confirmed,
+    AlreadyCancelled,
+    TooRecent,
+}
+
+impl fmt::Display for CancelPendingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "Pending payment not found"),
+            Self::Confirmed => write!(f, "Pending payment already confirmed"),
+            Self::AlreadyCancelled => write!(f, "Pending payment already cancelled"),
+            Self::TooRecent => write!(f, "Pending payment may still be confirmed"),
+        }
+    }
+}
+
+/// Payments sent to mempool, by transaction hash
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingPayments {
+    /// Last block notified to the wallet
+    last_b_num: u64,
+    payments: BTreeMap<String, PendingPayment>,
+}
+
+impl PendingPayments {
+    /// Last block notified to the wallet
+    pub fn last_b_num(&self) -> u64 {
+        self.last_b_num
+    }
+
+    /// All tracked payments, in transaction hash order
+    pub fn payments(&self) -> impl Iterator<Item = &PendingPayment> {
+        self.payments.values()
+    }
+
+    /// Track a payment just sent to mempool
+    ///
+    /// ### Arguments
+    ///
+    /// * `payment` - Payment to track
+    pub fn insert(&mut self, payment: PendingPayment) {
+        self.payments.insert(payment.tx_hash.clone(), payment);
+    }
+
+    /// Confirm the payments included in a block, returning the ones that had been cancelled
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hashes` - Hashes of the transactions in the block
+    /// * `b_num`     - Number of the block
+    pub fn confirm(&mut self, tx_hashes: &[String], b_num: u64) -> Vec<PendingPayment> {
+        self.last_b_num = std::cmp::max(self.last_b_num, b_num);
+
+        let mut cancelled = Vec::new();
+        for tx_hash in tx_hashes {
+            if let Some(payment) = self.payments.get_mut(tx_hash) {
+                if let PendingPaymentState::Cancelled { .. } = payment.state {
+                    cancelled.push(payment.clone());
+                }
+                payment.state = PendingPaymentState::Confirmed { b_num };
+            }
+        }
+        cancelled
+    }
+
+    /// Cancel a payment still unconfirmed `horizon` blocks after it was sent
+    ///
+    /// ### Arguments
+    ///
+    /// * `tx_hash` - Hash of the payment transaction
+    /// * `horizon` - Number of blocks to wait for confirmation before cancelling
+    pub fn cancel(
+        &mut self,
+        tx_hash: &str,
+        horizon: u64,
+    ) -> Result<PendingPayment, CancelPendingError> {
+        let last_b_num = self.last_b_num;
+        let payment = self
+            .payments
+            .get_mut(tx_hash)
+            .ok_or(CancelPendingError::NotFound)?;
+
+        match payment.state {
+            PendingPaymentState::Confirmed { .. } => Err(CancelPendingError::Confirmed),
+            PendingPaymentState::Cancelled { .. } => Err(CancelPendingError::AlreadyCancelled),
+            PendingPaymentState::Pending if last_b_num < payment.submitted_b_num + horizon => {
+                Err(CancelPendingError::TooRecent)
+            }
+            PendingPaymentState::Pending => {
+                payment.state = PendingPaymentState::Cancelled { b_num: last_b_num };
+                Ok(payment.clone())
+            }
+        }
+    }
+}